@@ -0,0 +1,97 @@
+/*!
+Measures the pure data hot paths between capturing an input and submitting it.
+
+```text
+cargo bench --bench hotpaths
+cargo bench --bench hotpaths --features unstable_ds4
+```
+
+None of these need the driver, see `statecell` for handing states over between threads.
+
+Baseline on an Intel Xeon virtual machine, release build:
+
+```text
+filter passthrough: 10.3ns per op
+filter xinput deadzone: 69.4ns per op
+filter axis matrix: 72.6ns per op
+filter axis matrix + xinput deadzone: 120.4ns per op
+xgamepad to_bytes: 1.8ns per op
+xgamepad from_bytes: 1.2ns per op
+xgamepad compare: 0.8ns per op
+ds4 report builder: 5.3ns per op
+ds4 report to_bytes: 0.9ns per op
+ds4 report from_bytes: 1.5ns per op
+```
+
+The passthrough filter measured 18.8ns before `InputFilter::apply` learned to skip the dpad stage when it does nothing.
+*/
+
+use std::hint::black_box;
+use std::time;
+use vigem_client::*;
+
+const ITERATIONS: u32 = 10000000;
+
+fn measure<T>(name: &str, mut f: impl FnMut(u32) -> T) {
+	// Warm up
+	for i in 0..ITERATIONS / 10 {
+		black_box(f(black_box(i)));
+	}
+	let start = time::Instant::now();
+	for i in 0..ITERATIONS {
+		black_box(f(black_box(i)));
+	}
+	let elapsed = start.elapsed();
+	println!("{}: {:.1}ns per op", name, elapsed.as_nanos() as f64 / ITERATIONS as f64);
+}
+
+// A gamepad state which changes every iteration
+fn gamepad(i: u32) -> XGamepad {
+	XGamepad {
+		buttons: XButtons { raw: (i & 0xF) as u16 },
+		left_trigger: i as u8,
+		right_trigger: (i >> 8) as u8,
+		thumb_lx: i as i16,
+		thumb_ly: (i >> 3) as i16,
+		thumb_rx: !i as i16,
+		thumb_ry: (i >> 5) as i16,
+	}
+}
+
+fn main() {
+	let passthrough = InputFilter::new();
+	measure("filter passthrough", |i| passthrough.apply(&gamepad(i)));
+
+	let deadzone = InputFilter::compensate_xinput_deadzone();
+	measure("filter xinput deadzone", |i| deadzone.apply(&gamepad(i)));
+
+	let matrix = InputFilter {
+		axis_matrix: Some(AxisMatrix::identity().swap_sticks().invert_axis(Axis::LY)),
+		dpad_rotation: 1,
+		..InputFilter::new()
+	};
+	measure("filter axis matrix", |i| matrix.apply(&gamepad(i)));
+
+	let both = InputFilter { axis_matrix: matrix.axis_matrix, ..deadzone };
+	measure("filter axis matrix + xinput deadzone", |i| both.apply(&gamepad(i)));
+
+	measure("xgamepad to_bytes", |i| gamepad(i).to_bytes());
+	measure("xgamepad from_bytes", |i| XGamepad::from_bytes(&[i as u8; 12]));
+
+	// Skipping unchanged states compares every update against the last submitted one
+	let last = gamepad(0);
+	measure("xgamepad compare", |i| gamepad(i) == last);
+
+	#[cfg(feature = "unstable_ds4")]
+	{
+		let buttons = DS4Buttons::new(DS4Buttons::CROSS | DS4Buttons::R1);
+		measure("ds4 report builder", |i| {
+			DS4Report::pressing(buttons)
+				.with_dpad(DpadDirection::ALL[(i & 7) as usize])
+				.with_special(DS4SpecialButtons::new(i as u8))
+				.left_trigger_t(Trigger::analog((i & 0xFF) as f32 / 255.0))
+		});
+		measure("ds4 report to_bytes", |i| DS4Report { buttons: i as u16, ..DS4Report::neutral() }.to_bytes());
+		measure("ds4 report from_bytes", |i| DS4Report::from_bytes(&[i as u8; 10]));
+	}
+}
@@ -0,0 +1,70 @@
+/*!
+Compares updating many targets with the blocking API against a single thread driving an I/O completion port.
+
+```text
+cargo bench --bench iocp
+```
+
+The blocking API waits for every driver round trip in turn,
+the [`IocpDriver`](vigem_client::IocpDriver) keeps all of them in flight and collects the completions in batches.
+*/
+
+#[cfg(windows)]
+use std::time;
+
+#[cfg(windows)]
+const TARGETS: usize = 32;
+#[cfg(windows)]
+const ROUNDS: u32 = 1000;
+
+#[cfg(windows)]
+fn measure(name: &str, mut f: impl FnMut()) {
+	// Warm up
+	for _ in 0..ROUNDS / 10 {
+		f();
+	}
+	let start = time::Instant::now();
+	for _ in 0..ROUNDS {
+		f();
+	}
+	let elapsed = start.elapsed();
+	println!("{}: {:?} per round of {} updates", name, elapsed / ROUNDS, TARGETS);
+}
+
+#[cfg(windows)]
+fn main() {
+	let client = vigem_client::Client::connect().unwrap();
+	let mut targets: Vec<_> = (0..TARGETS).map(|_| vigem_client::Xbox360Wired::new(&client, vigem_client::TargetId::XBOX360_WIRED)).collect();
+	for target in &mut targets {
+		target.plugin().unwrap();
+	}
+	for target in &mut targets {
+		target.wait_ready().unwrap();
+	}
+
+	let mut gamepad = vigem_client::XGamepad::default();
+	measure("blocking", || {
+		gamepad.thumb_lx = gamepad.thumb_lx.wrapping_add(1);
+		for target in &mut targets {
+			target.update(&gamepad).unwrap();
+		}
+	});
+
+	let mut driver = vigem_client::IocpDriver::new(&client).unwrap();
+	measure("iocp", || {
+		gamepad.thumb_lx = gamepad.thumb_lx.wrapping_add(1);
+		for target in &targets {
+			driver.begin_update(target, &gamepad).unwrap();
+		}
+		while driver.pending() > 0 {
+			for event in driver.poll(None).unwrap() {
+				event.result.unwrap();
+			}
+		}
+	});
+}
+
+#[cfg(not(windows))]
+fn main() {
+	eprintln!("ViGEmBus is only available on Windows");
+}
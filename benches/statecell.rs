@@ -0,0 +1,50 @@
+/*!
+Compares handing gamepad states over between threads with a `StateCell` against a `Mutex`.
+
+```text
+cargo bench --bench statecell
+```
+
+A producer thread publishes as fast as it can while the consumer reads, the contention is the worst case for the mutex.
+Neither needs the driver.
+*/
+
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+use std::{thread, time};
+use vigem_client::{StateCell, XGamepad};
+
+const ITERATIONS: u32 = 1000000;
+
+fn measure<P: FnMut(i16) + Send + 'static>(name: &str, mut publish: P, mut read: impl FnMut() -> XGamepad) {
+	let stop = Arc::new(AtomicBool::new(false));
+	let producer = {
+		let stop = stop.clone();
+		thread::spawn(move || {
+			let mut thumb_lx = 0i16;
+			while !stop.load(Ordering::Relaxed) {
+				thumb_lx = thumb_lx.wrapping_add(1);
+				publish(thumb_lx);
+			}
+		})
+	};
+
+	let start = time::Instant::now();
+	let mut sum = 0i64;
+	for _ in 0..ITERATIONS {
+		sum += read().thumb_lx as i64;
+	}
+	let elapsed = start.elapsed();
+	stop.store(true, Ordering::Relaxed);
+	producer.join().unwrap();
+	println!("{}: {:?} per read under contention ({})", name, elapsed / ITERATIONS, sum % 2);
+}
+
+fn main() {
+	let cell = Arc::new(StateCell::new(XGamepad::default()));
+	let producer_cell = cell.clone();
+	measure("state cell", move |thumb_lx| producer_cell.publish(XGamepad { thumb_lx, ..Default::default() }), || cell.latest());
+
+	let mutex = Arc::new(Mutex::new(XGamepad::default()));
+	let producer_mutex = mutex.clone();
+	measure("mutex", move |thumb_lx| *producer_mutex.lock().unwrap() = XGamepad { thumb_lx, ..Default::default() }, || *mutex.lock().unwrap());
+}
@@ -0,0 +1,71 @@
+/*!
+Measures the update hot path of a plugged in target.
+
+```text
+cargo bench --bench update -- [buffered] [no-write-through]
+```
+
+By default the client opens the service handle like `Client::connect`,
+`buffered` clears `FILE_FLAG_NO_BUFFERING` and `no-write-through` clears `FILE_FLAG_WRITE_THROUGH` to A/B test their effect.
+
+Every update enters the maintenance gate of the client, uncontended this is a single atomic increment and decrement.
+The driver round trip dominates, compare against `maintenance` which closes and reopens an idle gate.
+*/
+
+#[cfg(windows)]
+use std::{env, time};
+
+#[cfg(windows)]
+const ITERATIONS: u32 = 10000;
+
+#[cfg(windows)]
+fn measure(name: &str, mut f: impl FnMut()) {
+	// Warm up
+	for _ in 0..ITERATIONS / 10 {
+		f();
+	}
+	let start = time::Instant::now();
+	for _ in 0..ITERATIONS {
+		f();
+	}
+	let elapsed = start.elapsed();
+	println!("{}: {:?} per iteration", name, elapsed / ITERATIONS);
+}
+
+#[cfg(windows)]
+fn main() {
+	let args: Vec<String> = env::args().collect();
+	let buffered = args.iter().any(|arg| arg == "buffered");
+	let no_write_through = args.iter().any(|arg| arg == "no-write-through");
+	println!("no_buffering: {}, write_through: {}", !buffered, !no_write_through);
+
+	let client = vigem_client::Client::builder()
+		.no_buffering(!buffered)
+		.write_through(!no_write_through)
+		.connect()
+		.unwrap();
+	let mut target = vigem_client::Xbox360Wired::new(&client, vigem_client::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let mut gamepad = vigem_client::XGamepad::default();
+	measure("update", || {
+		gamepad.thumb_lx = gamepad.thumb_lx.wrapping_add(1);
+		target.update(&gamepad).unwrap();
+	});
+
+	measure("maintenance", || {
+		drop(client.maintenance());
+	});
+
+	measure("update after maintenance", || {
+		drop(client.maintenance());
+		gamepad.thumb_lx = gamepad.thumb_lx.wrapping_add(1);
+		target.update(&gamepad).unwrap();
+	});
+}
+
+#[cfg(not(windows))]
+fn main() {
+	eprintln!("ViGEmBus is only available on Windows");
+}
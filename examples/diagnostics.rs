@@ -0,0 +1,53 @@
+/*!
+Prints what the ViGEmBus driver looks like to this crate, for bug reports.
+
+```text
+cargo run --example diagnostics
+```
+
+Connects even if the driver rejects the interface version of this crate and reports the version it speaks instead.
+*/
+
+#[cfg(windows)]
+use vigem_client::{BusVersion, Client, Error};
+
+#[cfg(windows)]
+fn main() {
+	println!("{}", vigem_client::features::summary());
+
+	let client = match Client::connect_unchecked() {
+		Ok(client) => client,
+		Err(Error::BusNotFound) => {
+			println!("driver not installed, get it from https://github.com/ViGEm/ViGEmBus");
+			return;
+		},
+		Err(Error::BusDisabled) => {
+			println!("driver installed but its device is disabled or not started, check the Device Manager");
+			return;
+		},
+		Err(err) => {
+			println!("connect failed: {}", err);
+			return;
+		},
+	};
+
+	let version = client.bus_version();
+	println!("{}", version);
+	if !version.accepted {
+		println!("update the driver or this crate, version {} is required", BusVersion::REQUIRED);
+		return;
+	}
+
+	// Serial numbers start at one, a driver too old to tell answers None
+	match client.probe_serial(1) {
+		Ok(Some(true)) => println!("serial 1 is plugged in"),
+		Ok(Some(false)) => println!("serial 1 is free"),
+		Ok(None) => println!("driver too old to probe serial numbers, ViGEmBus 1.17 or newer is required"),
+		Err(err) => println!("probing serial 1 failed: {}", err),
+	}
+}
+
+#[cfg(not(windows))]
+fn main() {
+	eprintln!("ViGEmBus is only available on Windows");
+}
@@ -0,0 +1,200 @@
+/*!
+Walks through the common failure modes of a ViGEmBus setup and prints what to do about them.
+
+```text
+cargo run --example doctor --features xusb-rumble,unstable_ds4
+```
+
+Checks, in order: the driver is present, it speaks the version of this crate, an Xbox360 target plugs in and gets ready,
+XInput assigns it a user index and reports its updates back, rumble notifications arrive, a DualShock4 target plugs in and accepts updates.
+Stops early when a check makes the following ones pointless, exits with a non-zero code if any check failed.
+*/
+
+#[cfg(windows)]
+use std::{process, sync::mpsc, time};
+#[cfg(windows)]
+use vigem_client::{BusInstance, Client, DS4Report, DualShock4Wired, Error, TargetId, XButtons, XGamepad, Xbox360Wired};
+#[cfg(windows)]
+use winapi::um::xinput;
+
+#[cfg(windows)]
+const TIMEOUT: time::Duration = time::Duration::from_secs(2);
+
+#[cfg(windows)]
+#[derive(Default)]
+struct Report {
+	passed: u32,
+	failed: u32,
+	skipped: u32,
+}
+
+#[cfg(windows)]
+impl Report {
+	fn pass(&mut self, check: &str, detail: impl std::fmt::Display) {
+		self.passed += 1;
+		println!("PASS  {}: {}", check, detail);
+	}
+	fn fail(&mut self, check: &str, detail: impl std::fmt::Display, hint: &str) {
+		self.failed += 1;
+		println!("FAIL  {}: {}", check, detail);
+		println!("      hint: {}", hint);
+	}
+	fn skip(&mut self, check: &str, reason: &str) {
+		self.skipped += 1;
+		println!("SKIP  {}: {}", check, reason);
+	}
+	// Records the outcome of a check failing with a typed error
+	fn check<T>(&mut self, check: &str, result: Result<T, Error>, detail: impl FnOnce(&T) -> String) -> Option<T> {
+		match result {
+			Ok(value) => {
+				self.pass(check, detail(&value));
+				Some(value)
+			},
+			Err(err) => {
+				self.fail(check, format_args!("{} ({:?})", err, err), hint(err));
+				None
+			},
+		}
+	}
+	fn finish(&self) -> ! {
+		println!();
+		println!("{} passed, {} failed, {} skipped", self.passed, self.failed, self.skipped);
+		process::exit(if self.failed == 0 { 0 } else { 1 });
+	}
+}
+
+#[cfg(windows)]
+fn hint(err: Error) -> &'static str {
+	match err {
+		Error::BusNotFound => "install the ViGEmBus driver from https://github.com/ViGEm/ViGEmBus and reboot",
+		Error::BusDisabled => "enable the ViGEmBus device in the Device Manager, or reinstall the driver if it reports a problem",
+		Error::BusAccessFailed(_) => "another program may hold the driver open exclusively, or run this from an elevated prompt",
+		Error::BusVersionMismatch => "update the ViGEmBus driver or this crate so both speak the same interface version",
+		Error::NoFreeSlot => "unplug virtual controllers left behind by other programs, or reboot",
+		Error::TargetNotReady => "the target was updated before it was ready, wait for wait_ready first",
+		Error::UserIndexOutOfRange => "XInput did not pick up the controller, check it shows up in the Game Controllers control panel",
+		Error::OperationAborted => "the target was unplugged while waiting, check for other programs unplugging virtual controllers",
+		Error::UncheckedClient => "the driver rejected the interface version, update the driver or this crate",
+		Error::ResourceCreation(_) => "the process ran out of handles or memory",
+		_ => "report this output at https://github.com/CasualX/vigem-client/issues",
+	}
+}
+
+#[cfg(windows)]
+fn main() {
+	println!("{}", vigem_client::features::summary());
+	println!();
+	let mut report = Report::default();
+
+	// Driver presence
+	let (result, instances) = Client::connect_diagnostic();
+	for (index, instance) in instances.iter().enumerate() {
+		println!("      instance {}: {:?}", index, instance);
+	}
+	// Instances which opened answered the version check, whichever way
+	if !instances.iter().any(|instance| matches!(instance, BusInstance::Accepted | BusInstance::VersionRejected)) {
+		let err = result.err().unwrap_or(Error::BusNotFound);
+		report.fail("driver", format_args!("{} ({:?})", err, err), hint(err));
+		report.finish();
+	}
+	report.pass("driver", format_args!("{} device interface(s) found", instances.len()));
+
+	// Version compatibility
+	let client = match result {
+		Ok(client) => client,
+		Err(err) => {
+			report.fail("version", format_args!("{} ({:?})", err, err), hint(err));
+			// Tell which version the driver speaks instead
+			if let Ok(client) = Client::connect_unchecked() {
+				println!("      {}", client.bus_version());
+			}
+			report.finish();
+		},
+	};
+	report.pass("version", client.bus_version());
+	if let Some(version) = report.check("driver version", client.driver_version(), |version| version.to_string()) {
+		if !version.wait_device_ready {
+			println!("      warning: the driver cannot tell when targets are ready, update ViGEmBus to 1.17 or newer");
+		}
+	}
+	let capabilities = report.check("capabilities", client.capabilities(), |caps| format!("{:?}", caps));
+
+	// Xbox360 target
+	let mut x360 = Xbox360Wired::new(&client, TargetId::XBOX360_WIRED);
+	if report.check("x360 plugin", x360.plugin(), |_| format!("serial {}", x360.serial_no())).is_some() {
+		report.check("x360 ready", x360.wait_ready_timed(), |elapsed| format!("after {:?}", elapsed));
+
+		match report.check("x360 user index", x360.wait_for_user_index(TIMEOUT), |index| format!("{:?}", index)) {
+			Some(user_index) => {
+				let probe = XGamepad { buttons: XButtons!(START|BACK), left_trigger: 201, thumb_rx: -12345, ..XGamepad::neutral() };
+				match x360.verify_xinput(&probe, TIMEOUT) {
+					Ok(Some(elapsed)) => report.pass("x360 xinput loopback", format_args!("reported back after {:?}", elapsed)),
+					Ok(None) => report.fail("x360 xinput loopback", "XInput did not report the update", "check no program hides virtual controllers from XInput, eg. HidHide"),
+					Err(err) => report.fail("x360 xinput loopback", err, hint(err)),
+				}
+				notification(&mut report, &mut x360, user_index.as_u32());
+			},
+			None => {
+				report.skip("x360 xinput loopback", "no user index");
+				report.skip("x360 notifications", "no user index");
+			},
+		}
+		// Already unplugged after checking the notifications
+		let _ = x360.unplug();
+	}
+
+	// DualShock4 target
+	let mut ds4 = DualShock4Wired::new(&client, TargetId::DUALSHOCK4_WIRED);
+	if report.check("ds4 plugin", ds4.plugin(), |_| String::from("plugged in")).is_some() {
+		report.check("ds4 ready", ds4.wait_ready_timed(), |elapsed| format!("after {:?}", elapsed));
+		report.check("ds4 update", ds4.update(&DS4Report::PRESS_CROSS).and_then(|_| ds4.update(&DS4Report::neutral())), |_| String::from("accepted"));
+		let _ = ds4.unplug();
+	}
+	match capabilities {
+		Some(caps) if caps.ds4_extended_report => report.pass("ds4 extended report", "supported"),
+		Some(_) => report.skip("ds4 extended report", "not supported by this version of the crate"),
+		None => report.skip("ds4 extended report", "capabilities unknown"),
+	}
+
+	report.finish();
+}
+
+#[cfg(windows)]
+fn notification(report: &mut Report, target: &mut Xbox360Wired<&Client>, user_index: u32) {
+	let notification = match target.request_notification() {
+		Ok(notification) => notification,
+		Err(err) => return report.fail("x360 notifications", err, hint(err)),
+	};
+	let (sender, receiver) = mpsc::channel();
+	let thread = notification.spawn_thread(move |_, data| { let _ = sender.send(data); });
+
+	// The driver only forwards the high byte of the motor speeds
+	let mut vibration = xinput::XINPUT_VIBRATION { wLeftMotorSpeed: 0x4200, wRightMotorSpeed: 0x2100 };
+	let start = time::Instant::now();
+	if unsafe { xinput::XInputSetState(user_index, &mut vibration) } != 0 {
+		report.fail("x360 notifications", "XInputSetState failed", "XInput did not pick up the controller");
+	} else {
+		let received = loop {
+			match receiver.recv_timeout(TIMEOUT.saturating_sub(start.elapsed())) {
+				Ok(data) if (data.large_motor, data.small_motor) == (0x42, 0x21) => break true,
+				Ok(_) => continue,
+				Err(_) => break false,
+			}
+		};
+		if received {
+			report.pass("x360 notifications", format_args!("rumble received after {:?}", start.elapsed()));
+		} else {
+			report.fail("x360 notifications", "no rumble notification received", "check no other program holds a notification request for the target");
+		}
+		vibration = xinput::XINPUT_VIBRATION { wLeftMotorSpeed: 0, wRightMotorSpeed: 0 };
+		unsafe { xinput::XInputSetState(user_index, &mut vibration) };
+	}
+	// The notification thread exits once the target is unplugged
+	let _ = target.unplug();
+	let _ = thread.join();
+}
+
+#[cfg(not(windows))]
+fn main() {
+	eprintln!("ViGEmBus is only available on Windows");
+}
@@ -0,0 +1,89 @@
+use std::{thread, time};
+use vigem_client::prelude::*;
+use vigem_client::testgen::Waveform;
+
+// Touchpad click flag of `DS4Report::special`
+const TOUCHPAD: u8 = 0x02;
+
+fn main() {
+	// Connect to the ViGEmBus driver
+	let client = Client::connect().unwrap();
+
+	// Create the virtual controller target
+	let id = TargetId::DUALSHOCK4_WIRED;
+	let mut target = DualShock4Wired::new(client, id);
+
+	// Reject reports with a malformed dpad instead of submitting them
+	target.set_validate_reports(true);
+
+	// Plugin the virtual controller
+	target.plugin().unwrap();
+
+	// Wait for the virtual controller to be ready to accept updates
+	// Updates submitted before it is ready may fail with `TargetNotReady`
+	target.wait_ready().unwrap();
+
+	// Tap cross to get past a menu, presets cover the common single button reports
+	target.update(&DS4Report::PRESS_CROSS).unwrap();
+	thread::sleep(time::Duration::from_millis(50));
+	target.update(&DS4Report::neutral()).unwrap();
+
+	// This example submits the basic input report, it is supported by every ViGEmBus version with DS4 support.
+	// The extended report with touchpad and motion data requires ViGEmBus 1.17 or newer and is not implemented by this crate yet.
+	println!("Using the basic DS4 report path");
+
+	// Map the left thumb stick onto the touchpad, touching during the first half of every other second
+	let mut touchpad = vigem_client::TouchpadMapper::new(vigem_client::TouchpadMode::Absolute { scale: 0.8 });
+
+	// Spin the left thumb stick in circles and ramp up the triggers
+	let circle = Waveform::circle(1.0, time::Duration::from_secs(6));
+	let ramp = Waveform::ramp_trigger(time::Duration::from_secs(2));
+
+	let start = time::Instant::now();
+	loop {
+		let t = start.elapsed();
+		let elapsed = t.as_secs_f64();
+
+		// Play for 10 seconds
+		if elapsed >= 10.0 {
+			break;
+		}
+
+		let mut report = circle.ds4_report(t);
+		ramp.apply_ds4(t, &mut report);
+
+		// Spin the right thumb stick the other way around, the sticks are centered at 0x80
+		report.thumb_rx = 255 - report.thumb_lx;
+		report.thumb_ry = report.thumb_ly;
+
+		// Walk the dpad clockwise through all directions, once per second
+		let dpad = DpadDirection::ALL[elapsed as usize % 8];
+		report.set_dpad(dpad);
+
+		// Alternate the face buttons
+		report.buttons |= if elapsed.fract() < 0.5 { DS4Buttons::CROSS } else { DS4Buttons::TRIANGLE };
+
+		// A real DualShock4 also reports the L2 and R2 buttons when the triggers are pressed, games may only look at either
+		if report.trigger_l > 0 {
+			report.buttons |= DS4Buttons::L2;
+		}
+		if report.trigger_r > 0 {
+			report.buttons |= DS4Buttons::R2;
+		}
+
+		// The touch packets need the extended report, until then only click the touchpad while touching
+		let touching = elapsed as u32 & 1 == 0 && elapsed.fract() < 0.5;
+		let x = (report.thumb_lx as f32 - 128.0) / 127.0;
+		let y = (report.thumb_ly as f32 - 128.0) / 127.0;
+		touchpad.feed(x, y, touching);
+		if let Some(touch) = touchpad.frame() {
+			if touch.point1().is_some() {
+				report.special |= TOUCHPAD;
+			}
+		}
+
+		let _ = target.update(&report);
+
+		thread::sleep(time::Duration::from_millis(10));
+	}
+}
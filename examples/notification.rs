@@ -1,14 +1,21 @@
+#[cfg(windows)]
 use std::{thread, time, sync, sync::atomic};
+#[cfg(windows)]
+use winapi::um::xinput;
+#[cfg(windows)]
+use vigem_client::prelude::*;
+#[cfg(windows)]
+use vigem_client::testgen::Waveform;
 
+#[cfg(windows)]
 fn sleep(ms: u32) {
 	thread::sleep(time::Duration::from_millis(ms as u64));
 }
 
+#[cfg(windows)]
 fn main() {
-	let xinput = rusty_xinput::XInputHandle::load_default().unwrap();
-
-	let client = vigem_client::Client::connect().unwrap();
-	let mut target = vigem_client::XTarget::new(client, vigem_client::TargetId::XBOX360_WIRED);
+	let client = Client::connect().unwrap();
+	let mut target = Xbox360Wired::new(client, TargetId::XBOX360_WIRED);
 
 	target.plugin().unwrap();
 	target.wait_ready().unwrap();
@@ -22,10 +29,7 @@ fn main() {
 	sleep(100);
 
 	// The input state of the virtual controller
-	let gamepad = vigem_client::XGamepad {
-		buttons: vigem_client::XButtons!(UP | RIGHT | LB | A | X),
-		..Default::default()
-	};
+	let gamepad = XGamepad::pressing(XButtons!(UP | RIGHT | LB | A | X));
 	target.update(&gamepad).unwrap();
 
 	let count = sync::Arc::new(atomic::AtomicUsize::new(0));
@@ -40,16 +44,21 @@ fn main() {
 	// Give the notification thread some time to start up
 	sleep(100);
 
-	// Generate some random motor speeds
-	let mut rng = urandom::new();
-	for _ in 0..20 {
-		let motor_speed = rng.next_u32();
-		let left_motor_speed = (motor_speed & 0xffff) as u16;
-		let right_motor_speed = (motor_speed >> 16) as u16;
+	// Ramp the motor speeds up, the left motor twice as fast as the right motor
+	let left_ramp = Waveform::ramp_trigger(time::Duration::from_secs(1));
+	let right_ramp = Waveform::ramp_trigger(time::Duration::from_secs(2));
+	for i in 0..20 {
+		let t = time::Duration::from_millis(i * 100);
+		let left_motor_speed = left_ramp.gamepad(t).left_trigger;
+		let right_motor_speed = right_ramp.gamepad(t).right_trigger;
 		// XInput the motor speeds are u16, but ViGEm only uses the high byte...
-		println!("xinput.set_state({}, {}, {})", user_index, left_motor_speed >> 8, right_motor_speed >> 8);
-		xinput.set_state(user_index, left_motor_speed, right_motor_speed).unwrap();
-		sleep(rng.range(50..200));
+		println!("XInputSetState({}, {}, {})", user_index, left_motor_speed, right_motor_speed);
+		let mut vibration = xinput::XINPUT_VIBRATION {
+			wLeftMotorSpeed: (left_motor_speed as u16) << 8,
+			wRightMotorSpeed: (right_motor_speed as u16) << 8,
+		};
+		assert_eq!(unsafe { xinput::XInputSetState(user_index, &mut vibration) }, 0);
+		sleep(100);
 	}
 
 	// Dropping the target causes the notification request to abort and the thread to return
@@ -58,3 +67,8 @@ fn main() {
 
 	println!("Got {} notifications!", count.load(atomic::Ordering::SeqCst));
 }
+
+#[cfg(not(windows))]
+fn main() {
+	eprintln!("ViGEmBus is only available on Windows");
+}
@@ -1,12 +1,13 @@
 use std::{thread, time};
+use vigem_client::prelude::*;
 
 fn main() {
 	// Connect to the ViGEmBus driver
-	let client = vigem_client::Client::connect().unwrap();
+	let client = Client::connect().unwrap();
 
 	// Create the virtual controller target
-	let id = vigem_client::TargetId::XBOX360_WIRED;
-	let mut target = vigem_client::Xbox360Wired::new(client, id);
+	let id = TargetId::XBOX360_WIRED;
+	let mut target = Xbox360Wired::new(client, id);
 
 	// Plugin the virtual controller
 	target.plugin().unwrap();
@@ -15,10 +16,7 @@ fn main() {
 	target.wait_ready().unwrap();
 
 	// The input state of the virtual controller
-	let mut gamepad = vigem_client::XGamepad {
-		buttons: vigem_client::XButtons!(UP | RIGHT | LB | A | X),
-		..Default::default()
-	};
+	let mut gamepad = XGamepad::pressing(XButtons!(UP | RIGHT | LB | A | X));
 
 	let start = time::Instant::now();
 	loop {
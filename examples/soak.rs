@@ -0,0 +1,316 @@
+/*!
+Soak test of the driver interaction.
+
+Runs every scenario for the given number of minutes and prints a pass/fail summary:
+
+```text
+cargo run --release --example soak --features xusb-rumble,unstable_ds4 -- [minutes per scenario] [targets]
+```
+
+Scenarios:
+
+* `updates`: continuous 1 kHz updates on multiple Xbox360 targets, each on its own thread.
+  Prints the distribution of the latency from capturing the input until the driver accepted it.
+* `replug`: randomized plug and unplug cycles of mixed Xbox360 and DualShock4 targets interleaved with updates.
+* `notifications`: scripted XInputSetState calls verified against the received notifications.
+* `ds4`: DualShock4 report hammering without delay.
+*/
+
+#[cfg(windows)]
+use std::{env, fmt, thread, time};
+#[cfg(windows)]
+use std::collections::BTreeMap;
+#[cfg(windows)]
+use std::sync::{Arc, Mutex};
+#[cfg(windows)]
+use winapi::um::xinput;
+
+#[cfg(windows)]
+#[derive(Default)]
+struct Counters {
+	ok: u64,
+	errors: BTreeMap<String, u64>,
+	max_latency: time::Duration,
+	missed: u64,
+	stamped: vigem_client::LatencyStats,
+}
+
+#[cfg(windows)]
+impl Counters {
+	fn record<T>(&mut self, result: Result<T, vigem_client::Error>) -> Option<T> {
+		match result {
+			Ok(value) => {
+				self.ok += 1;
+				Some(value)
+			},
+			Err(err) => {
+				*self.errors.entry(format!("{:?}", err)).or_default() += 1;
+				None
+			},
+		}
+	}
+	fn latency(&mut self, elapsed: time::Duration) {
+		self.max_latency = self.max_latency.max(elapsed);
+	}
+	fn merge(&mut self, other: Counters) {
+		self.ok += other.ok;
+		for (err, count) in other.errors {
+			*self.errors.entry(err).or_default() += count;
+		}
+		self.max_latency = self.max_latency.max(other.max_latency);
+		self.missed += other.missed;
+		for sample in other.stamped.samples() {
+			self.stamped.record_latency(sample);
+		}
+	}
+	fn error_count(&self) -> u64 {
+		self.errors.values().sum()
+	}
+}
+
+#[cfg(windows)]
+impl fmt::Display for Counters {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "ok: {}, missed: {}, max latency: {:?}", self.ok, self.missed, self.max_latency)?;
+		for (err, count) in &self.errors {
+			write!(f, ", {}: {}", err, count)?;
+		}
+		if !self.stamped.is_empty() {
+			let stats = &self.stamped;
+			let percentile = |p| stats.percentile(p).unwrap_or_default();
+			write!(f, "\n  stamped latency p50: {:?}, p90: {:?}, p99: {:?}, max: {:?}",
+				percentile(50.0), percentile(90.0), percentile(99.0), stats.max().unwrap_or_default())?;
+			write!(f, "\n  histogram (250µs buckets): {:?}", stats.histogram(time::Duration::from_micros(250), 8))?;
+		}
+		Ok(())
+	}
+}
+
+// Tiny xorshift generator, the soak test needs no statistical quality
+#[cfg(windows)]
+struct Rng(u32);
+#[cfg(windows)]
+impl Rng {
+	fn next(&mut self) -> u32 {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 17;
+		self.0 ^= self.0 << 5;
+		self.0
+	}
+}
+
+#[cfg(windows)]
+fn gamepad(tick: u32) -> vigem_client::XGamepad {
+	vigem_client::XGamepad {
+		buttons: vigem_client::XButtons((tick & 0xf3ff) as u16),
+		left_trigger: tick as u8,
+		right_trigger: !tick as u8,
+		thumb_lx: tick as i16,
+		thumb_ly: (tick >> 1) as i16,
+		..Default::default()
+	}
+}
+
+#[cfg(windows)]
+fn updates(client: &Arc<vigem_client::Client>, duration: time::Duration, targets: usize) -> Counters {
+	let threads: Vec<_> = (0..targets).map(|_| {
+		let client = client.clone();
+		thread::spawn(move || {
+			let mut counters = Counters::default();
+			let mut target = vigem_client::Xbox360Wired::new(client, vigem_client::TargetId::XBOX360_WIRED);
+			if counters.record(target.plugin().and_then(|_| target.wait_ready())).is_none() {
+				return counters;
+			}
+			let start = time::Instant::now();
+			let mut tick = 0;
+			while start.elapsed() < duration {
+				let gamepad = gamepad(tick);
+				let captured_at = time::Instant::now();
+				if counters.record(target.update_stamped(&gamepad, captured_at)).is_some() {
+					counters.latency(captured_at.elapsed());
+				}
+				tick += 1;
+				thread::sleep(time::Duration::from_millis(1));
+			}
+			if let Some(stats) = target.set_latency_stats(None) {
+				counters.stamped = stats;
+			}
+			counters
+		})
+	}).collect();
+
+	let stamped = vigem_client::LatencyStats::new(targets.max(1) * vigem_client::LatencyStats::DEFAULT_CAPACITY);
+	let mut counters = Counters { stamped, ..Counters::default() };
+	for thread in threads {
+		counters.merge(thread.join().unwrap());
+	}
+	counters
+}
+
+#[cfg(windows)]
+fn replug(client: &Arc<vigem_client::Client>, duration: time::Duration, targets: usize) -> Counters {
+	let mut counters = Counters::default();
+	let mut rng = Rng(0x2545F491);
+	let mut pads: Vec<vigem_client::AnyTarget> = (0..targets).map(|i| match i % 2 {
+		0 => vigem_client::Xbox360Wired::new(client.clone(), vigem_client::TargetId::XBOX360_WIRED).into(),
+		_ => vigem_client::DualShock4Wired::new(client.clone(), vigem_client::TargetId::DUALSHOCK4_WIRED).into(),
+	}).collect();
+
+	let start = time::Instant::now();
+	while start.elapsed() < duration {
+		let pad = &mut pads[rng.next() as usize % targets];
+		if pad.is_attached() {
+			if rng.next() & 3 == 0 {
+				counters.record(pad.unplug());
+			}
+			else {
+				let gamepad = gamepad(rng.next());
+				counters.record(match pad.kind() {
+					vigem_client::TargetKind::Xbox360Wired => pad.submit_xbox(&gamepad),
+					vigem_client::TargetKind::DualShock4Wired => pad.submit_ds4(&gamepad.into()),
+				});
+			}
+		}
+		else {
+			counters.record(pad.plugin().and_then(|_| pad.wait_ready()));
+		}
+		thread::sleep(time::Duration::from_millis((rng.next() % 20) as u64));
+	}
+
+	// Everything plugged in by this scenario must be accounted for
+	drop(pads);
+	if client.attached_count() != 0 {
+		*counters.errors.entry("LeakedTargets".to_string()).or_default() += client.attached_count() as u64;
+	}
+	counters
+}
+
+#[cfg(windows)]
+fn notifications(client: &Arc<vigem_client::Client>, duration: time::Duration) -> Counters {
+	let mut counters = Counters::default();
+	let mut target = vigem_client::Xbox360Wired::new(client.clone(), vigem_client::TargetId::XBOX360_WIRED);
+	if counters.record(target.plugin().and_then(|_| target.wait_ready())).is_none() {
+		return counters;
+	}
+	let user_index = match counters.record(target.get_user_index()) {
+		Some(user_index) => user_index.as_u32(),
+		None => return counters,
+	};
+
+	let received = Arc::new(Mutex::new(Vec::new()));
+	let thread = match counters.record(target.request_notification()) {
+		Some(notification) => {
+			let received = received.clone();
+			notification.spawn_thread(move |_, data| received.lock().unwrap().push((data.large_motor, data.small_motor)))
+		},
+		None => return counters,
+	};
+	thread::sleep(time::Duration::from_millis(100));
+
+	let mut rng = Rng(0x9E3779B9);
+	let mut sent = Vec::new();
+	let start = time::Instant::now();
+	while start.elapsed() < duration {
+		let speed = rng.next();
+		let mut vibration = xinput::XINPUT_VIBRATION {
+			wLeftMotorSpeed: speed as u16,
+			wRightMotorSpeed: (speed >> 16) as u16,
+		};
+		// The driver only forwards the high byte of the motor speeds
+		let expected = ((speed >> 8) as u8, (speed >> 24) as u8);
+		let at = time::Instant::now();
+		if unsafe { xinput::XInputSetState(user_index, &mut vibration) } != 0 {
+			*counters.errors.entry("XInputSetState".to_string()).or_default() += 1;
+			continue;
+		}
+		sent.push(expected);
+		// Wait for the notification to arrive before scripting the next one
+		while received.lock().unwrap().len() < sent.len() && at.elapsed() < time::Duration::from_millis(100) {
+			thread::yield_now();
+		}
+		counters.latency(at.elapsed());
+	}
+
+	drop(target);
+	thread.join().unwrap();
+
+	let received = received.lock().unwrap();
+	for expected in &sent {
+		if received.contains(expected) {
+			counters.ok += 1;
+		}
+		else {
+			counters.missed += 1;
+		}
+	}
+	counters
+}
+
+#[cfg(windows)]
+fn ds4(client: &Arc<vigem_client::Client>, duration: time::Duration) -> Counters {
+	let mut counters = Counters::default();
+	let mut target = vigem_client::DualShock4Wired::new(client.clone(), vigem_client::TargetId::DUALSHOCK4_WIRED);
+	target.set_validate_reports(true);
+	if counters.record(target.plugin().and_then(|_| target.wait_ready())).is_none() {
+		return counters;
+	}
+	let mut rng = Rng(0x1B873593);
+	let start = time::Instant::now();
+	while start.elapsed() < duration {
+		let bits = rng.next();
+		let report = vigem_client::DS4Report {
+			thumb_lx: bits as u8,
+			thumb_ly: (bits >> 8) as u8,
+			buttons: vigem_client::DpadDirection::ALL[bits as usize % 9] as u16 | (bits >> 16) as u16 & 0xfff0,
+			trigger_l: (bits >> 24) as u8,
+			..Default::default()
+		};
+		if let Some(Some(elapsed)) = counters.record(target.update_timed(&report)) {
+			counters.latency(elapsed);
+		}
+	}
+	counters
+}
+
+#[cfg(windows)]
+fn main() {
+	let mut args = env::args().skip(1);
+	let minutes: f64 = args.next().map(|arg| arg.parse().expect("minutes")).unwrap_or(1.0);
+	let targets: usize = args.next().map(|arg| arg.parse().expect("targets")).unwrap_or(4).max(1);
+	let duration = time::Duration::from_secs_f64(minutes * 60.0);
+
+	let client = Arc::new(vigem_client::Client::connect().unwrap());
+	let mut failed = false;
+
+	let mut report = |name: &str, counters: Counters, pass: bool| {
+		println!("{:<14} {} {}", name, if pass { "PASS" } else { "FAIL" }, counters);
+		failed |= !pass;
+	};
+
+	let counters = updates(&client, duration, targets);
+	let pass = counters.error_count() == 0;
+	report("updates", counters, pass);
+
+	let counters = replug(&client, duration, targets);
+	let pass = counters.error_count() == 0;
+	report("replug", counters, pass);
+
+	let counters = notifications(&client, duration);
+	let pass = counters.error_count() == 0 && counters.missed == 0;
+	report("notifications", counters, pass);
+
+	let counters = ds4(&client, duration);
+	let pass = counters.error_count() == 0;
+	report("ds4", counters, pass);
+
+	if failed {
+		println!("FAIL");
+		std::process::exit(1);
+	}
+	println!("PASS");
+}
+
+#[cfg(not(windows))]
+fn main() {
+	eprintln!("ViGEmBus is only available on Windows");
+}
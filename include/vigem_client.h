@@ -0,0 +1,225 @@
+/* Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#ifndef VIGEM_CLIENT_H
+#define VIGEM_CLIENT_H
+
+#include <stdint.h>
+
+/**
+ * Success.
+ */
+#define VIGEM_RS_OK 0
+
+/**
+ * The operation failed, see [`vigem_rs_last_error_message`].
+ */
+#define VIGEM_RS_ERROR -1
+
+/**
+ * The handle is not a live handle of the expected kind, eg. it was already destroyed.
+ */
+#define VIGEM_RS_INVALID_HANDLE -2
+
+/**
+ * A required pointer argument is null.
+ */
+#define VIGEM_RS_NULL_POINTER -3
+
+/**
+ * The operation panicked, see [`vigem_rs_last_error_message`].
+ */
+#define VIGEM_RS_PANIC -4
+
+/**
+ * The ViGEmBus driver is not installed, or its device is disabled or not started.
+ */
+#define VIGEM_RS_BUS_NOT_FOUND -5
+
+/**
+ * The target is not plugged in.
+ */
+#define VIGEM_RS_NOT_PLUGGED_IN -6
+
+/**
+ * Opaque handle of a client.
+ */
+typedef struct VigemRsClient VigemRsClient;
+
+#if defined(VIGEM_RS_DS4)
+/**
+ * Opaque handle of a DualShock4 target.
+ */
+typedef struct VigemRsDs4 VigemRsDs4;
+#endif
+
+/**
+ * Opaque handle of an Xbox360 target.
+ */
+typedef struct VigemRsX360 VigemRsX360;
+
+/**
+ * XInput compatible button flags.
+ */
+typedef uint16_t VigemRsXButtons;
+
+/**
+ * XInput compatible gamepad.
+ *
+ * Represents an [`XINPUT_GAMEPAD`]-compatible report structure.
+ *
+ * ![image](https://user-images.githubusercontent.com/2324759/124391245-f889b180-dcef-11eb-927c-4b76d2ca332d.png)
+ */
+typedef struct VigemRsXGamepad {
+  VigemRsXButtons buttons;
+  uint8_t left_trigger;
+  uint8_t right_trigger;
+  int16_t thumb_lx;
+  int16_t thumb_ly;
+  int16_t thumb_rx;
+  int16_t thumb_ry;
+} VigemRsXGamepad;
+
+#if defined(VIGEM_RS_DS4)
+/**
+ * DualShock4 HID Input report.
+ */
+typedef struct VigemRsDS4Report {
+  uint8_t thumb_lx;
+  uint8_t thumb_ly;
+  uint8_t thumb_rx;
+  uint8_t thumb_ry;
+  uint16_t buttons;
+  uint8_t special;
+  uint8_t trigger_l;
+  uint8_t trigger_r;
+} VigemRsDS4Report;
+#endif
+
+/**
+ * Returns the message of the last error of the calling thread.
+ *
+ * The string is empty if no error occurred yet and is valid until the next failing call on the thread.
+ */
+const char *vigem_rs_last_error_message(void);
+
+/**
+ * Connects to the ViGEmBus driver.
+ *
+ * # Safety
+ *
+ * `client` must be valid for writes, it receives the client handle or null on failure.
+ */
+int32_t vigem_rs_connect(VigemRsClient **client);
+
+/**
+ * Destroys the client handle.
+ *
+ * Targets created with the client keep it connected until they are destroyed.
+ *
+ * # Safety
+ *
+ * Any value is accepted as handle.
+ */
+int32_t vigem_rs_client_destroy(VigemRsClient *client);
+
+/**
+ * Creates an Xbox360 target with the client, the target is not plugged in.
+ *
+ * # Safety
+ *
+ * `target` must be valid for writes, it receives the target handle or null on failure.
+ */
+int32_t vigem_rs_x360_create(VigemRsClient *client, VigemRsX360 **target);
+
+/**
+ * Plugs in the Xbox360 target and waits until it is ready.
+ *
+ * # Safety
+ *
+ * Any value is accepted as handle.
+ */
+int32_t vigem_rs_x360_plugin(VigemRsX360 *target);
+
+/**
+ * Updates the Xbox360 target.
+ *
+ * # Safety
+ *
+ * `gamepad` must be valid for reads.
+ */
+int32_t vigem_rs_x360_update(VigemRsX360 *target, const VigemRsXGamepad *gamepad);
+
+/**
+ * Unplugs the Xbox360 target, it can be plugged in again.
+ *
+ * # Safety
+ *
+ * Any value is accepted as handle.
+ */
+int32_t vigem_rs_x360_unplug(VigemRsX360 *target);
+
+/**
+ * Destroys the Xbox360 target handle, unplugging the target.
+ *
+ * # Safety
+ *
+ * Any value is accepted as handle.
+ */
+int32_t vigem_rs_x360_destroy(VigemRsX360 *target);
+
+#if defined(VIGEM_RS_DS4)
+/**
+ * Creates a DualShock4 target with the client, the target is not plugged in.
+ *
+ * # Safety
+ *
+ * `target` must be valid for writes, it receives the target handle or null on failure.
+ */
+int32_t vigem_rs_ds4_create(VigemRsClient *client, VigemRsDs4 **target);
+#endif
+
+#if defined(VIGEM_RS_DS4)
+/**
+ * Plugs in the DualShock4 target and waits until it is ready.
+ *
+ * # Safety
+ *
+ * Any value is accepted as handle.
+ */
+int32_t vigem_rs_ds4_plugin(VigemRsDs4 *target);
+#endif
+
+#if defined(VIGEM_RS_DS4)
+/**
+ * Updates the DualShock4 target.
+ *
+ * # Safety
+ *
+ * `report` must be valid for reads.
+ */
+int32_t vigem_rs_ds4_update(VigemRsDs4 *target, const VigemRsDS4Report *report);
+#endif
+
+#if defined(VIGEM_RS_DS4)
+/**
+ * Unplugs the DualShock4 target, it can be plugged in again.
+ *
+ * # Safety
+ *
+ * Any value is accepted as handle.
+ */
+int32_t vigem_rs_ds4_unplug(VigemRsDs4 *target);
+#endif
+
+#if defined(VIGEM_RS_DS4)
+/**
+ * Destroys the DualShock4 target handle, unplugging the target.
+ *
+ * # Safety
+ *
+ * Any value is accepted as handle.
+ */
+int32_t vigem_rs_ds4_destroy(VigemRsDs4 *target);
+#endif
+
+#endif /* VIGEM_CLIENT_H */
@@ -0,0 +1,176 @@
+// Only the client tracks its targets, which is not built on other platforms
+#![cfg_attr(not(windows), allow(dead_code))]
+
+use crate::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Serial numbers of the targets plugged in through a client.
+///
+/// Targets report every change of their attachment, the records only change when the driver is known to have changed too.
+#[derive(Debug, Default)]
+pub(crate) struct Attached {
+	serials: Mutex<Vec<u32>>,
+}
+
+impl Attached {
+	#[inline]
+	fn lock(&self) -> MutexGuard<'_, Vec<u32>> {
+		self.serials.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+
+	#[inline]
+	pub fn count(&self) -> usize {
+		self.lock().len()
+	}
+
+	#[inline]
+	pub fn serials(&self) -> Vec<u32> {
+		self.lock().clone()
+	}
+
+	/// The driver accepted a target under the serial number.
+	#[inline]
+	pub fn plugged_in(&self, serial_no: u32) {
+		self.lock().push(serial_no);
+	}
+
+	/// A target took over the serial number without plugging it in, eg. restoring a persisted fleet.
+	///
+	/// A serial number released and adopted through the same client is recorded once.
+	#[inline]
+	pub fn adopted(&self, serial_no: u32) {
+		let mut serials = self.lock();
+		if !serials.contains(&serial_no) {
+			serials.push(serial_no);
+		}
+	}
+
+	/// The unplug request of the target under the serial number finished with the result.
+	///
+	/// Only a completed request removes the record.
+	/// A failed or abandoned request, eg. a bounded unplug giving up on a wedged driver, leaves the target plugged in as far as anyone knows.
+	#[inline]
+	pub fn unplugged(&self, serial_no: u32, result: Result<(), u32>) {
+		if result.is_err() {
+			return;
+		}
+		let mut serials = self.lock();
+		if let Some(index) = serials.iter().position(|&serial| serial == serial_no) {
+			serials.swap_remove(index);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// WAIT_TIMEOUT, the result of a bounded unplug giving up
+	const TIMEOUT: u32 = 258;
+
+	fn sorted(attached: &Attached) -> Vec<u32> {
+		let mut serials = attached.serials();
+		serials.sort_unstable();
+		serials
+	}
+
+	#[test]
+	fn plugin() {
+		let attached = Attached::default();
+		assert_eq!(attached.count(), 0);
+		attached.plugged_in(1);
+		attached.plugged_in(2);
+		attached.plugged_in(3);
+		assert_eq!(attached.count(), 3);
+		assert_eq!(sorted(&attached), [1, 2, 3]);
+	}
+
+	#[test]
+	fn unplug() {
+		let attached = Attached::default();
+		attached.plugged_in(1);
+		attached.plugged_in(2);
+		attached.plugged_in(3);
+		attached.unplugged(2, Ok(()));
+		assert_eq!(sorted(&attached), [1, 3]);
+		attached.unplugged(1, Ok(()));
+		attached.unplugged(3, Ok(()));
+		assert_eq!(attached.count(), 0);
+	}
+
+	#[test]
+	fn unplug_failed() {
+		// The explicit unplug returns the error, the target stays plugged in
+		let attached = Attached::default();
+		attached.plugged_in(1);
+		attached.unplugged(1, Err(5));
+		assert_eq!(attached.serials(), [1]);
+	}
+
+	#[test]
+	fn unplug_unknown() {
+		// Serial numbers of other clients are not recorded
+		let attached = Attached::default();
+		attached.plugged_in(1);
+		attached.unplugged(2, Ok(()));
+		assert_eq!(attached.serials(), [1]);
+	}
+
+	#[test]
+	fn drop_completed() {
+		// Drop and the manual drop unplug bounded, a completed request removes the record
+		let attached = Attached::default();
+		attached.plugged_in(1);
+		attached.plugged_in(2);
+		attached.unplugged(1, Ok(()));
+		attached.unplugged(2, Ok(()));
+		assert_eq!(attached.count(), 0);
+	}
+
+	#[test]
+	fn drop_gave_up() {
+		// Giving up on a wedged driver may leave the target plugged in, it keeps counting against the slots
+		let attached = Attached::default();
+		attached.plugged_in(1);
+		attached.plugged_in(2);
+		attached.unplugged(1, Err(TIMEOUT));
+		attached.unplugged(2, Ok(()));
+		assert_eq!(attached.serials(), [1]);
+	}
+
+	#[test]
+	fn persist() {
+		// Persisting releases the targets without unplugging, they stay recorded and their drop does not unplug them
+		let attached = Attached::default();
+		attached.plugged_in(1);
+		attached.plugged_in(2);
+		assert_eq!(sorted(&attached), [1, 2]);
+
+		// Restoring through the same client does not record them twice
+		attached.adopted(1);
+		attached.adopted(2);
+		assert_eq!(sorted(&attached), [1, 2]);
+
+		attached.unplugged(1, Ok(()));
+		attached.unplugged(2, Ok(()));
+		assert_eq!(attached.count(), 0);
+	}
+
+	#[test]
+	fn restore_other_client() {
+		// Restoring through another client records the targets there
+		let attached = Attached::default();
+		attached.adopted(7);
+		attached.adopted(9);
+		assert_eq!(sorted(&attached), [7, 9]);
+	}
+
+	#[test]
+	fn serial_reused() {
+		// The driver may hand out the serial number of an unplugged target again
+		let attached = Attached::default();
+		attached.plugged_in(1);
+		attached.unplugged(1, Ok(()));
+		attached.plugged_in(1);
+		assert_eq!(attached.serials(), [1]);
+	}
+}
@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+use crate::*;
+
+/// DualShock4 controls an action can be bound to.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DS4Button {
+	/// Button flags above the dpad nibble of [`DS4Report::buttons`].
+	Buttons(u16),
+	/// Button flags of [`DS4Report::special`].
+	Special(u8),
+	/// Dpad direction.
+	Dpad(DpadDirection),
+}
+
+/// What an action is bound to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Binding {
+	/// Xbox360 buttons.
+	Button(XButtons),
+	/// DualShock4 buttons or dpad direction.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	DS4(DS4Button),
+	/// Deflects an axis, normalized to `-1.0..=1.0` for the thumbsticks and `0.0..=1.0` for the triggers.
+	Axis { axis: Axis, deflection: f32 },
+}
+
+/// Binds named actions to controls and applies the active actions onto reports.
+///
+/// Applying the bindings only presses controls, controls pressed in the report are kept:
+///
+/// * Buttons of all active bindings are or'ed together.
+/// * Active dpad bindings combine like pressing the dpad buttons together, opposite directions cancel out.
+/// * Active axis bindings set the axis to their deflection, later bindings override earlier ones on the same axis.
+///   Bind a deflection of `1.0` or `-1.0` for full deflection.
+/// * Bindings for the other kind of controller are ignored, axis bindings apply to both.
+///
+/// An action may be bound more than once, all its bindings apply in the order they were bound.
+///
+/// ```
+/// use std::collections::HashSet;
+/// use vigem_client::{Axis, Bindings, XButtons, XGamepad};
+///
+/// let mut bindings = Bindings::new();
+/// bindings.bind_button("Jump", vigem_client::XButtons!(A));
+/// bindings.bind_button("Menu", vigem_client::XButtons!(START));
+/// bindings.bind_axis("Forward", Axis::LY, 1.0);
+///
+/// let active: HashSet<String> = ["Jump", "Forward"].iter().map(|s| s.to_string()).collect();
+/// let mut gamepad = XGamepad::default();
+/// bindings.apply(&active, &mut gamepad);
+/// assert_eq!(gamepad.buttons, vigem_client::XButtons!(A));
+/// assert_eq!(gamepad.thumb_ly, i16::MAX);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bindings {
+	bindings: Vec<(String, Binding)>,
+}
+
+impl Bindings {
+	/// Creates an empty binding table.
+	#[inline]
+	pub const fn new() -> Bindings {
+		Bindings { bindings: Vec::new() }
+	}
+
+	/// Binds an action.
+	#[inline]
+	pub fn bind(&mut self, action: &str, binding: Binding) {
+		self.bindings.push((action.to_string(), binding));
+	}
+
+	/// Binds an action to Xbox360 buttons.
+	#[inline]
+	pub fn bind_button(&mut self, action: &str, buttons: XButtons) {
+		self.bind(action, Binding::Button(buttons));
+	}
+
+	/// Binds an action to DualShock4 buttons or a dpad direction.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn bind_ds4(&mut self, action: &str, button: DS4Button) {
+		self.bind(action, Binding::DS4(button));
+	}
+
+	/// Binds an action to deflect an axis.
+	#[inline]
+	pub fn bind_axis(&mut self, action: &str, axis: Axis, deflection: f32) {
+		self.bind(action, Binding::Axis { axis, deflection });
+	}
+
+	/// Removes all bindings of an action.
+	#[inline]
+	pub fn unbind(&mut self, action: &str) {
+		self.bindings.retain(|(name, _)| name != action);
+	}
+
+	/// Returns the bindings of an action in the order they were bound.
+	#[inline]
+	pub fn get<'a>(&'a self, action: &'a str) -> impl Iterator<Item = &'a Binding> + 'a {
+		self.bindings.iter().filter(move |(name, _)| name == action).map(|(_, binding)| binding)
+	}
+
+	/// Returns all bindings in the order they were bound.
+	#[inline]
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &Binding)> {
+		self.bindings.iter().map(|(name, binding)| (name.as_str(), binding))
+	}
+
+	/// Returns the number of bindings.
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.bindings.len()
+	}
+
+	/// Returns if there are no bindings.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.bindings.is_empty()
+	}
+
+	fn active<'a>(&'a self, active: &'a HashSet<String>) -> impl Iterator<Item = &'a Binding> + 'a {
+		self.bindings.iter().filter(move |(name, _)| active.contains(name)).map(|(_, binding)| binding)
+	}
+
+	/// Applies the active actions onto an Xbox360 gamepad state.
+	pub fn apply(&self, active: &HashSet<String>, gamepad: &mut XGamepad) {
+		for binding in self.active(active) {
+			match *binding {
+				Binding::Button(buttons) => gamepad.buttons.raw |= buttons.raw,
+				#[cfg(feature = "unstable_ds4")]
+				Binding::DS4(_) => {},
+				Binding::Axis { axis, deflection } => {
+					let stick = || (deflection_of(axis, deflection) * i16::MAX as f32).round() as i16;
+					match axis {
+						Axis::LX => gamepad.thumb_lx = stick(),
+						Axis::LY => gamepad.thumb_ly = stick(),
+						Axis::RX => gamepad.thumb_rx = stick(),
+						Axis::RY => gamepad.thumb_ry = stick(),
+						Axis::LT => gamepad.left_trigger = trigger(deflection),
+						Axis::RT => gamepad.right_trigger = trigger(deflection),
+					}
+				},
+			}
+		}
+	}
+
+	/// Applies the active actions onto a DualShock4 report.
+	///
+	/// The vertical thumbstick axes are flipped as they point down on the DualShock4.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	pub fn apply_ds4(&self, active: &HashSet<String>, report: &mut DS4Report) {
+		let (mut dx, mut dy) = dpad_vector(report.buttons);
+		for binding in self.active(active) {
+			match *binding {
+				Binding::Button(_) => {},
+				Binding::DS4(DS4Button::Buttons(buttons)) => report.buttons |= buttons & !0xf,
+				Binding::DS4(DS4Button::Special(special)) => report.special |= special,
+				Binding::DS4(DS4Button::Dpad(direction)) => {
+					let (x, y) = dpad_vector(direction as u16);
+					dx += x;
+					dy += y;
+				},
+				Binding::Axis { axis, deflection } => {
+					let stick = |sign: f32| (128.0 + sign * deflection_of(axis, deflection) * 127.0).round() as u8;
+					match axis {
+						Axis::LX => report.thumb_lx = stick(1.0),
+						Axis::LY => report.thumb_ly = stick(-1.0),
+						Axis::RX => report.thumb_rx = stick(1.0),
+						Axis::RY => report.thumb_ry = stick(-1.0),
+						Axis::LT => report.trigger_l = trigger(deflection),
+						Axis::RT => report.trigger_r = trigger(deflection),
+					}
+				},
+			}
+		}
+		report.buttons = report.buttons & !0xf | dpad_direction(dx.signum(), dy.signum()) as u16;
+	}
+}
+
+fn deflection_of(axis: Axis, deflection: f32) -> f32 {
+	let min = if axis.is_trigger() { 0.0 } else { -1.0 };
+	if deflection.is_nan() { 0.0 } else { deflection.clamp(min, 1.0) }
+}
+
+fn trigger(deflection: f32) -> u8 {
+	(deflection_of(Axis::LT, deflection) * u8::MAX as f32).round() as u8
+}
+
+// Unit vector of the dpad direction, y points north
+#[cfg(feature = "unstable_ds4")]
+pub(crate) fn dpad_vector(buttons: u16) -> (i32, i32) {
+	const VECTORS: [(i32, i32); 8] = [(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)];
+	VECTORS.get((buttons & 0xf) as usize).copied().unwrap_or((0, 0))
+}
+
+#[cfg(feature = "unstable_ds4")]
+pub(crate) fn dpad_direction(x: i32, y: i32) -> DpadDirection {
+	match (x, y) {
+		(0, 1) => DpadDirection::North,
+		(1, 1) => DpadDirection::NorthEast,
+		(1, 0) => DpadDirection::East,
+		(1, -1) => DpadDirection::SouthEast,
+		(0, -1) => DpadDirection::South,
+		(-1, -1) => DpadDirection::SouthWest,
+		(-1, 0) => DpadDirection::West,
+		(-1, 1) => DpadDirection::NorthWest,
+		_ => DpadDirection::None,
+	}
+}
@@ -1,13 +1,19 @@
 #![allow(non_snake_case)]
 
 use std::{mem, ptr};
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
+use std::sync::{Mutex, MutexGuard, PoisonError};
+#[cfg(feature = "xusb-rumble")]
+use std::sync::{Arc, Condvar};
 use winapi::um::handleapi::*;
 use winapi::um::ioapiset::*;
 use winapi::um::minwinbase::*;
 use winapi::um::synchapi::*;
 use winapi::um::errhandlingapi::*;
+use winapi::um::winbase::WAIT_OBJECT_0;
 use winapi::shared::winerror;
 use winapi::shared::ntdef::HANDLE;
+use winapi::shared::minwindef::{BOOL, LPVOID};
 use winapi::shared::guiddef::GUID;
 
 pub static GUID_DEVINTERFACE: GUID = GUID {
@@ -15,19 +21,140 @@ pub static GUID_DEVINTERFACE: GUID = GUID {
 	Data4: [0xB0, 0x43, 0xED, 0x0F, 0x93, 0x2F, 0x01, 0x4F],
 };
 
+// Device interface of the XUSB function driver of Xbox360 controllers
+pub static GUID_DEVINTERFACE_XUSB: GUID = GUID {
+	Data1: 0xEC87F1E3, Data2: 0xC13B, Data3: 0x4100,
+	Data4: [0xB5, 0xF7, 0x8B, 0x84, 0xD5, 0x42, 0x60, 0xCB],
+};
+
+// Device interface of HID collections
+pub static GUID_DEVINTERFACE_HID: GUID = GUID {
+	Data1: 0x4D1E55B2, Data2: 0xF16F, Data3: 0x11CF,
+	Data4: [0x88, 0xCB, 0x00, 0x11, 0x11, 0x00, 0x00, 0x30],
+};
+
 // IO control codes
 // const IOCTL_BASE: u32 = 0x801;
 pub const IOCTL_PLUGIN_TARGET: u32 = 0x2AA004; //IOCTL_BASE + 0x000;
 pub const IOCTL_UNPLUG_TARGET: u32 = 0x2AA008; //IOCTL_BASE + 0x001;
 pub const IOCTL_CHECK_VERSION: u32 = 0x2AA00C; //IOCTL_BASE + 0x002;
 pub const IOCTL_WAIT_DEVICE_READY: u32 = 0x2AA010; //IOCTL_BASE + 0x003;
-#[cfg(feature = "unstable_xtarget_notification")]
+#[cfg(feature = "xusb-rumble")]
 pub const IOCTL_XUSB_REQUEST_NOTIFICATION : u32 = 0x2AE804; //IOCTL_BASE + 0x200 (RW);
 pub const IOCTL_XUSB_SUBMIT_REPORT: u32 = 0x2AA808; //IOCTL_BASE + 0x201;
-#[cfg(feature = "unstable_ds4")]
 pub const IOCTL_DS4_SUBMIT_REPORT: u32 = 0x2AA80C; //IOCTL_BASE + 0x202;
+#[cfg(feature = "ds4-output")]
+pub const IOCTL_DS4_REQUEST_NOTIFICATION: u32 = 0x2AE810; //IOCTL_BASE + 0x203 (RW);
 pub const IOCTL_XUSB_GET_USER_INDEX: u32 = 0x2AE81C; //IOCTL_BASE + 0x206;
 
+/// Keeps the completion of a request waited on through its event from being posted to a completion port.
+///
+/// Setting the low-order bit of the event handle is the documented way to do so, waiting on the overlapped result ignores the bit.
+/// Requests of the blocking APIs must not show up in the completion port of a client, see [`Client::enable_iocp`](crate::Client::enable_iocp).
+#[inline]
+pub fn skip_port(event: HANDLE) -> HANDLE {
+	if event.is_null() {
+		return event;
+	}
+	(event as usize | 1) as HANDLE
+}
+/// Recovers the event handle from [`skip_port`].
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
+#[inline]
+pub fn unskip_port(event: HANDLE) -> HANDLE {
+	(event as usize & !1) as HANDLE
+}
+
+/// Issues an overlapped ioctl without waiting for its completion.
+///
+/// The completion is posted to the completion port the device is associated with, unless it fails synchronously.
+#[inline]
+pub unsafe fn device_io_control_async(
+	device: HANDLE, code: u32,
+	in_buffer: LPVOID, in_size: u32,
+	out_buffer: LPVOID, out_size: u32,
+	overlapped: &mut OVERLAPPED,
+) -> Result<(), u32> {
+	let result = DeviceIoControl(device, code, in_buffer, in_size, out_buffer, out_size, ptr::null_mut(), overlapped);
+	match IoStatus::classify(result, GetLastError()) {
+		IoStatus::Failed(err) => Err(err),
+		IoStatus::Completed | IoStatus::Pending => Ok(()),
+	}
+}
+
+/// How an overlapped request issued by `DeviceIoControl` went.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum IoStatus {
+	/// Completed synchronously, there is nothing to wait on.
+	Completed,
+	/// Queued by the driver, its completion must be waited on.
+	Pending,
+	/// Failed synchronously with the error code, there is nothing to wait on.
+	Failed(u32),
+}
+impl IoStatus {
+	/// Classifies the return value of `DeviceIoControl` and the last error right after it.
+	///
+	/// The last error is only meaningful when the call failed.
+	#[inline]
+	fn classify(result: BOOL, last_error: u32) -> IoStatus {
+		if result != 0 {
+			IoStatus::Completed
+		}
+		else if last_error == winerror::ERROR_IO_PENDING {
+			IoStatus::Pending
+		}
+		else {
+			IoStatus::Failed(last_error)
+		}
+	}
+	/// Completes the classification of a pending request with the return value of `GetOverlappedResult` and the last error right after it.
+	#[inline]
+	fn overlapped_result(result: BOOL, last_error: u32) -> Result<(), u32> {
+		if result != 0 { Ok(()) } else { Err(last_error) }
+	}
+}
+
+/// Issues an overlapped ioctl and waits for its completion.
+///
+/// Synchronous failures are returned directly without waiting on the overlapped event,
+/// only pending requests are waited on.
+#[inline]
+unsafe fn device_io_control(
+	device: HANDLE, code: u32,
+	in_buffer: LPVOID, in_size: u32,
+	out_buffer: LPVOID, out_size: u32,
+	overlapped: &mut OVERLAPPED,
+) -> Result<(), u32> {
+	let mut transferred = 0;
+
+	let result = DeviceIoControl(device, code, in_buffer, in_size, out_buffer, out_size, &mut transferred, overlapped);
+	match IoStatus::classify(result, GetLastError()) {
+		IoStatus::Completed => Ok(()),
+		IoStatus::Failed(err) => Err(err),
+		IoStatus::Pending => {
+			let result = GetOverlappedResult(device, overlapped, &mut transferred, /*bWait: */1);
+			IoStatus::overlapped_result(result, GetLastError())
+		},
+	}
+}
+
+/// Issues an ioctl with a caller provided input buffer and waits for its completion, see [`RawTarget`](crate::RawTarget).
+#[inline]
+pub unsafe fn ioctl_raw(device: HANDLE, code: u32, buffer: &mut [u8], event: HANDLE) -> Result<(), u32> {
+	let mut overlapped: OVERLAPPED = mem::zeroed();
+	overlapped.hEvent = skip_port(event);
+
+	device_io_control(
+		device,
+		code,
+		buffer.as_mut_ptr() as _,
+		buffer.len() as u32,
+		ptr::null_mut(),
+		0,
+		&mut overlapped)
+}
+
 #[repr(C)]
 pub struct CheckVersion {
 	pub Size: u32,
@@ -37,30 +164,39 @@ impl CheckVersion {
 	pub const COMMON: u32 = 0x0001;
 	#[inline]
 	pub const fn common() -> CheckVersion {
+		CheckVersion::new(Self::COMMON)
+	}
+	#[inline]
+	pub const fn new(version: u32) -> CheckVersion {
 		CheckVersion {
 			Size: mem::size_of::<CheckVersion>() as u32,
-			Version: Self::COMMON,
+			Version: version,
 		}
 	}
 	#[inline]
 	pub unsafe fn ioctl(&mut self, device: HANDLE) -> bool {
-		let mut transferred = 0;
+		self.ioctl_result(device).is_ok()
+	}
+	#[inline]
+	pub unsafe fn ioctl_result(&mut self, device: HANDLE) -> Result<(), u32> {
+		let event = CreateEventW(ptr::null_mut(), 0, 0, ptr::null());
+		if event.is_null() {
+			return Err(GetLastError());
+		}
 		let mut overlapped: OVERLAPPED = mem::zeroed();
-		overlapped.hEvent = CreateEventW(ptr::null_mut(), 0, 0, ptr::null());
+		overlapped.hEvent = skip_port(event);
 
-		DeviceIoControl(
+		let result = device_io_control(
 			device,
 			IOCTL_CHECK_VERSION,
 			self as *mut _ as _,
 			mem::size_of_val(self) as u32,
 			ptr::null_mut(),
 			0,
-			&mut transferred,
 			&mut overlapped);
 
-		let success = GetOverlappedResult(device, &mut overlapped, &mut transferred, /*bWait: */1);
-		CloseHandle(overlapped.hEvent);
-		return success != 0;
+		CloseHandle(event);
+		result
 	}
 }
 
@@ -96,25 +232,17 @@ impl PluginTarget {
 	}
 	#[inline]
 	pub unsafe fn ioctl(&mut self, device: HANDLE, event: HANDLE) -> Result<(), u32> {
-		let mut transferred = 0;
 		let mut overlapped: OVERLAPPED = mem::zeroed();
-		overlapped.hEvent = event;
+		overlapped.hEvent = skip_port(event);
 
-		DeviceIoControl(
+		device_io_control(
 			device,
 			IOCTL_PLUGIN_TARGET,
 			self as *mut _ as _,
 			mem::size_of_val(self) as u32,
 			ptr::null_mut(),
 			0,
-			&mut transferred,
-			&mut overlapped);
-
-		if GetOverlappedResult(device, &mut overlapped, &mut transferred, /*bWait: */1) == 0 {
-			return Err(GetLastError());
-		}
-
-		Ok(())
+			&mut overlapped)
 	}
 }
 
@@ -131,32 +259,68 @@ impl WaitDeviceReady {
 			SerialNo: serial_no,
 		}
 	}
+	/// Issues the request, consulting `watch` for how long to wait before consulting it again while the device is not ready.
+	///
+	/// The pending request is cancelled if `watch` fails.
 	#[inline]
-	pub unsafe fn ioctl(&mut self, device: HANDLE, event: HANDLE) -> Result<(), u32> {
-		let mut transferred = 0;
+	pub unsafe fn ioctl_watched<E: From<u32>>(&mut self, device: HANDLE, event: HANDLE, mut watch: impl FnMut() -> Result<u32, E>) -> Result<(), E> {
 		let mut overlapped: OVERLAPPED = mem::zeroed();
-		overlapped.hEvent = event;
-
-		DeviceIoControl(
-			device,
-			IOCTL_WAIT_DEVICE_READY,
-			self as *mut _ as _,
-			mem::size_of_val(self) as u32,
-			ptr::null_mut(),
-			0,
-			&mut transferred,
-			&mut overlapped);
+		overlapped.hEvent = skip_port(event);
+		let mut transferred = 0;
 
-		if GetOverlappedResult(device, &mut overlapped, &mut transferred, /*bWait: */1) == 0 {
-			let err = GetLastError();
-			// Version pre-1.17 where this IOCTL doesn't exist
-			if err != winerror::ERROR_INVALID_PARAMETER {
-				return Err(err);
+		if DeviceIoControl(device, IOCTL_WAIT_DEVICE_READY, self as *mut _ as _, mem::size_of_val(self) as u32, ptr::null_mut(), 0, &mut transferred, &mut overlapped) == 0 {
+			match GetLastError() {
+				winerror::ERROR_IO_PENDING => (),
+				// Version pre-1.17 where this IOCTL doesn't exist
+				winerror::ERROR_INVALID_PARAMETER => return Ok(()),
+				err => return Err(err.into()),
+			}
+			loop {
+				let timeout_ms = match watch() {
+					Ok(timeout_ms) => timeout_ms,
+					Err(err) => {
+						// The overlapped structure must outlive the request, wait for the cancellation to complete
+						CancelIoEx(device, &mut overlapped);
+						GetOverlappedResult(device, &mut overlapped, &mut transferred, /*bWait: */1);
+						return Err(err);
+					},
+				};
+				if WaitForSingleObject(event, timeout_ms) == WAIT_OBJECT_0 {
+					break;
+				}
 			}
 		}
 
+		if GetOverlappedResult(device, &mut overlapped, &mut transferred, /*bWait: */1) == 0 {
+			return match GetLastError() {
+				winerror::ERROR_INVALID_PARAMETER => Ok(()),
+				err => Err(err.into()),
+			};
+		}
 		Ok(())
 	}
+	/// Issues the request without waiting for the device to be ready.
+	///
+	/// Pending requests are cancelled, returns if the request was pending.
+	#[inline]
+	pub unsafe fn probe(&mut self, device: HANDLE, event: HANDLE) -> Result<bool, u32> {
+		let mut overlapped: OVERLAPPED = mem::zeroed();
+		overlapped.hEvent = skip_port(event);
+		let mut transferred = 0;
+
+		if DeviceIoControl(device, IOCTL_WAIT_DEVICE_READY, self as *mut _ as _, mem::size_of_val(self) as u32, ptr::null_mut(), 0, &mut transferred, &mut overlapped) != 0 {
+			return Ok(false);
+		}
+		let err = GetLastError();
+		if err != winerror::ERROR_IO_PENDING {
+			return Err(err);
+		}
+
+		// The overlapped structure must outlive the request, wait for the cancellation to complete
+		CancelIoEx(device, &mut overlapped);
+		GetOverlappedResult(device, &mut overlapped, &mut transferred, /*bWait: */1);
+		Ok(true)
+	}
 }
 
 #[repr(C)]
@@ -174,25 +338,109 @@ impl UnplugTarget {
 	}
 	#[inline]
 	pub unsafe fn ioctl(&mut self, device: HANDLE, event: HANDLE) -> Result<(), u32> {
-		let mut transferred = 0;
 		let mut overlapped: OVERLAPPED = mem::zeroed();
-		overlapped.hEvent = event;
+		overlapped.hEvent = skip_port(event);
 
-		DeviceIoControl(
+		device_io_control(
 			device,
 			IOCTL_UNPLUG_TARGET,
 			self as *mut _ as _,
 			mem::size_of_val(self) as u32,
 			ptr::null_mut(),
 			0,
-			&mut transferred,
-			&mut overlapped);
+			&mut overlapped)
+	}
+	/// Issues the request, giving up waiting for its completion after the timeout.
+	///
+	/// An expired request is cancelled. If the driver does not complete the cancellation either,
+	/// the request and its event are leaked as the driver may still write to them.
+	#[inline]
+	pub unsafe fn ioctl_bounded(self, device: HANDLE, timeout_ms: u32) -> Result<(), u32> {
+		struct Pending {
+			overlapped: OVERLAPPED,
+			unplug: UnplugTarget,
+		}
 
-		if GetOverlappedResult(device, &mut overlapped, &mut transferred, /*bWait: */1) == 0 {
+		let event = CreateEventW(ptr::null_mut(), 1, 0, ptr::null());
+		if event.is_null() {
 			return Err(GetLastError());
 		}
+		let mut pending = Box::new(Pending { overlapped: mem::zeroed(), unplug: self });
+		pending.overlapped.hEvent = skip_port(event);
+		let mut transferred = 0;
 
-		Ok(())
+		let issued = DeviceIoControl(
+			device,
+			IOCTL_UNPLUG_TARGET,
+			&mut pending.unplug as *mut _ as _,
+			mem::size_of::<UnplugTarget>() as u32,
+			ptr::null_mut(),
+			0,
+			&mut transferred,
+			&mut pending.overlapped);
+
+		let result = if issued != 0 {
+			Ok(())
+		}
+		else {
+			let err = GetLastError();
+			if err != winerror::ERROR_IO_PENDING {
+				Err(err)
+			}
+			else {
+				let mut wait = BoundedWait::after(WaitForSingleObject(event, timeout_ms), false);
+				if wait == BoundedWait::Cancel {
+					CancelIoEx(device, &mut pending.overlapped);
+					wait = BoundedWait::after(WaitForSingleObject(event, CANCEL_GRACE_MS), true);
+				}
+				if wait == BoundedWait::GiveUp {
+					mem::forget(pending);
+					return Err(winerror::ERROR_TIMEOUT);
+				}
+				if GetOverlappedResult(device, &mut pending.overlapped, &mut transferred, /*bWait: */0) == 0 {
+					Err(GetLastError())
+				}
+				else {
+					Ok(())
+				}
+			}
+		};
+
+		CloseHandle(event);
+		result
+	}
+}
+
+/// Bound on waiting for the driver in destructors, a wedged driver must not hang the process.
+pub const DROP_TIMEOUT_MS: u32 = 2000;
+/// Time given to the driver to complete a cancelled request before giving up on it.
+pub const CANCEL_GRACE_MS: u32 = 100;
+
+/// What to do about a pending request after a bounded wait on its event.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum BoundedWait {
+	/// The request completed, its result can be collected.
+	Completed,
+	/// The wait expired, cancel the request and wait for the cancellation.
+	Cancel,
+	/// The cancellation was not honoured in time, the driver may still write to the request which must be leaked.
+	GiveUp,
+}
+impl BoundedWait {
+	/// Decides from the return value of `WaitForSingleObject` and whether the request was already cancelled.
+	///
+	/// A failed wait is treated as expired, the request is never freed while it may be pending.
+	#[inline]
+	fn after(wait: u32, cancelled: bool) -> BoundedWait {
+		if wait == WAIT_OBJECT_0 {
+			BoundedWait::Completed
+		}
+		else if !cancelled {
+			BoundedWait::Cancel
+		}
+		else {
+			BoundedWait::GiveUp
+		}
 	}
 }
 
@@ -213,29 +461,21 @@ impl XUsbSubmitReport {
 	}
 	#[inline]
 	pub unsafe fn ioctl(&mut self, device: HANDLE, event: HANDLE) -> Result<(), u32> {
-		let mut transferred = 0;
 		let mut overlapped: OVERLAPPED = mem::zeroed();
-		overlapped.hEvent = event;
+		overlapped.hEvent = skip_port(event);
 
-		DeviceIoControl(
+		device_io_control(
 			device,
 			IOCTL_XUSB_SUBMIT_REPORT,
 			self as *mut _ as _,
 			mem::size_of_val(self) as u32,
 			ptr::null_mut(),
 			0,
-			&mut transferred,
-			&mut overlapped);
-
-		if GetOverlappedResult(device, &mut overlapped, &mut transferred, /*bWait: */1) == 0 {
-			return Err(GetLastError());
-		}
-
-		Ok(())
+			&mut overlapped)
 	}
 }
 
-#[cfg(feature = "unstable_xtarget_notification")]
+#[cfg(feature = "xusb-rumble")]
 #[repr(C)]
 pub struct XUsbRequestNotification {
 	pub Size: u32,
@@ -245,7 +485,7 @@ pub struct XUsbRequestNotification {
 	pub LedNumber: u8,
 }
 
-#[cfg(feature = "unstable_xtarget_notification")]
+#[cfg(feature = "xusb-rumble")]
 impl XUsbRequestNotification {
 	#[inline]
 	pub const fn new(serial_no: u32) -> XUsbRequestNotification {
@@ -258,8 +498,51 @@ impl XUsbRequestNotification {
 		}
 	}
 }
+#[cfg(feature = "xusb-rumble")]
+impl NotificationBuffer for XUsbRequestNotification {
+	const IOCTL: u32 = IOCTL_XUSB_REQUEST_NOTIFICATION;
+}
+
+#[cfg(feature = "ds4-output")]
+#[repr(C)]
+pub struct DS4RequestNotification {
+	pub Size: u32,
+	pub SerialNo: u32,
+	pub SmallMotor: u8,
+	pub LargeMotor: u8,
+	pub LightbarRed: u8,
+	pub LightbarGreen: u8,
+	pub LightbarBlue: u8,
+}
+
+#[cfg(feature = "ds4-output")]
+impl DS4RequestNotification {
+	#[inline]
+	pub const fn new(serial_no: u32) -> DS4RequestNotification {
+		DS4RequestNotification {
+			Size: mem::size_of::<DS4RequestNotification>() as u32,
+			SerialNo: serial_no,
+			SmallMotor: 0,
+			LargeMotor: 0,
+			LightbarRed: 0,
+			LightbarGreen: 0,
+			LightbarBlue: 0,
+		}
+	}
+}
 
-#[cfg(feature = "unstable_xtarget_notification")]
+#[cfg(feature = "ds4-output")]
+impl NotificationBuffer for DS4RequestNotification {
+	const IOCTL: u32 = IOCTL_DS4_REQUEST_NOTIFICATION;
+}
+
+// Buffers exchanged with the driver by a notification request.
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
+pub trait NotificationBuffer {
+	const IOCTL: u32;
+}
+
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
 #[repr(C)]
 pub struct RequestNotification<T> {
 	pub overlapped: OVERLAPPED,
@@ -267,13 +550,18 @@ pub struct RequestNotification<T> {
 }
 // Safety: This instance must have a stable address (eg. on the heap)
 // Required for non-blocking DeviceIoControl, see msdn.
-#[cfg(feature = "unstable_xtarget_notification")]
-impl<T> RequestNotification<T> {
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
+impl<T: NotificationBuffer> RequestNotification<T> {
+	/// Fails with the error of creating the event the request completes through.
 	#[inline]
-	pub fn new(buffer: T) -> RequestNotification<T> {
+	pub fn new(buffer: T) -> Result<RequestNotification<T>, u32> {
+		let event = unsafe { CreateEventW(ptr::null_mut(), 0, 0, ptr::null()) };
+		if event.is_null() {
+			return Err(unsafe { GetLastError() });
+		}
 		let mut overlapped: OVERLAPPED = unsafe { mem::zeroed() };
-		overlapped.hEvent = unsafe { CreateEventW(ptr::null_mut(), 0, 0, ptr::null()) };
-		RequestNotification { overlapped, buffer }
+		overlapped.hEvent = skip_port(event);
+		Ok(RequestNotification { overlapped, buffer })
 	}
 	#[inline]
 	pub unsafe fn ioctl(&mut self, device: HANDLE) {
@@ -284,7 +572,7 @@ impl<T> RequestNotification<T> {
 
 		DeviceIoControl(
 			device,
-			IOCTL_XUSB_REQUEST_NOTIFICATION,
+			T::IOCTL,
 			buffer_ptr,
 			buffer_size,
 			buffer_ptr,
@@ -292,8 +580,11 @@ impl<T> RequestNotification<T> {
 			&mut transferred,
 			&mut self.overlapped);
 	}
+	/// Cancels the pending request, giving up waiting for the cancellation after the timeout.
+	///
+	/// Returns `ERROR_TIMEOUT` if the request may still be pending, this instance must then be leaked.
 	#[inline]
-	pub unsafe fn cancel(&mut self, device: HANDLE) -> Result<(), u32> {
+	pub unsafe fn cancel_bounded(&mut self, device: HANDLE, timeout_ms: u32) -> Result<(), u32> {
 		if CancelIoEx(device, &mut self.overlapped) == 0 {
 			let err = GetLastError();
 			// If no pending IO then everything is fine
@@ -302,8 +593,11 @@ impl<T> RequestNotification<T> {
 			}
 			return Err(err);
 		}
+		if BoundedWait::after(WaitForSingleObject(unskip_port(self.overlapped.hEvent), timeout_ms), true) == BoundedWait::GiveUp {
+			return Err(winerror::ERROR_TIMEOUT);
+		}
 		let mut transferred = 0;
-		if GetOverlappedResult(device, &mut self.overlapped, &mut transferred, /*bWait: */1) == 0 {
+		if GetOverlappedResult(device, &mut self.overlapped, &mut transferred, /*bWait: */0) == 0 {
 			let err = GetLastError();
 			// Expect the operation to be aborted
 			if err != winerror::ERROR_OPERATION_ABORTED {
@@ -321,10 +615,151 @@ impl<T> RequestNotification<T> {
 		Ok(())
 	}
 }
-#[cfg(feature = "unstable_xtarget_notification")]
+// Lets a target cancel the pending request of a notification it handed out.
+// The pending request is registered once its owner is pinned and cleared before the owner is dropped.
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
+#[derive(Default)]
+pub struct CancelLink {
+	pending: Mutex<Option<(HANDLE, *mut OVERLAPPED)>>,
+}
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
+unsafe impl Sync for CancelLink {}
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
+unsafe impl Send for CancelLink {}
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
+impl CancelLink {
+	#[inline]
+	fn lock(&self) -> MutexGuard<'_, Option<(HANDLE, *mut OVERLAPPED)>> {
+		self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+	// Safety: the overlapped structure must stay valid until `clear` is called
+	#[inline]
+	pub unsafe fn set(&self, device: HANDLE, overlapped: *mut OVERLAPPED) {
+		*self.lock() = Some((device, overlapped));
+	}
+	#[inline]
+	pub fn clear(&self) {
+		*self.lock() = None;
+	}
+	#[inline]
+	pub fn cancel(&self) {
+		if let Some((device, overlapped)) = *self.lock() {
+			unsafe { CancelIoEx(device, overlapped); }
+		}
+	}
+}
+
+// Generation of a target, bumped whenever it is plugged in or unplugged.
+// Notification requests remember the generation they were requested for,
+// completions for another generation belong to a target reusing the serial number and are discarded.
+// Reannouncing a target swaps it for a new generation under the same serial number instead,
+// requests of the generations since `base` follow the target to its current generation.
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
+#[derive(Debug, Default)]
+pub struct Generation {
+	state: Mutex<GenerationState>,
+	#[cfg(feature = "xusb-rumble")]
+	swapped: Condvar,
+}
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
+#[derive(Debug, Default)]
+struct GenerationState {
+	current: u32,
+	base: u32,
+	swapping: bool,
+}
+// Reannouncing a target, ends the swap as discontinued if dropped without ending it
+#[cfg(feature = "xusb-rumble")]
+pub struct Swap(Arc<Generation>);
+#[cfg(feature = "xusb-rumble")]
+impl Swap {
+	#[inline]
+	pub fn end(self, continued: bool) {
+		self.0.end_swap(continued);
+		mem::forget(self);
+	}
+}
+#[cfg(feature = "xusb-rumble")]
+impl Drop for Swap {
+	fn drop(&mut self) {
+		self.0.end_swap(false);
+	}
+}
+// Where a request of an earlier generation stands, see `Generation::follow`
+#[cfg(feature = "xusb-rumble")]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Follow {
+	// The generation is the current generation
+	Current,
+	// The target was reannounced since, continue with the current generation
+	Continued(u32),
+	// The target is being reannounced
+	Swapping,
+	// The target was unplugged since
+	Gone,
+}
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
+impl Generation {
+	#[inline]
+	fn lock(&self) -> MutexGuard<'_, GenerationState> {
+		self.state.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+	#[inline]
+	pub fn get(&self) -> u32 {
+		self.lock().current
+	}
+	// Plugging in and unplugging while swapping is part of the swap, the swap ends with a bump of its own
+	#[inline]
+	pub fn bump(&self) {
+		let mut state = self.lock();
+		if !state.swapping {
+			state.current = state.current.wrapping_add(1);
+			state.base = state.current;
+		}
+	}
+	// Starts reannouncing the target, requests aborted meanwhile wait for the swap to end
+	#[cfg(feature = "xusb-rumble")]
+	#[inline]
+	pub fn begin_swap(self: &Arc<Self>) -> Swap {
+		self.lock().swapping = true;
+		Swap(self.clone())
+	}
+	#[cfg(feature = "xusb-rumble")]
+	#[inline]
+	fn end_swap(&self, continued: bool) {
+		let mut state = self.lock();
+		state.current = state.current.wrapping_add(1);
+		if !continued {
+			state.base = state.current;
+		}
+		state.swapping = false;
+		drop(state);
+		self.swapped.notify_all();
+	}
+	// Follows the generation of a request to the current generation, waiting for a swap in progress to end if `wait`
+	#[cfg(feature = "xusb-rumble")]
+	pub fn follow(&self, generation: u32, wait: bool) -> Follow {
+		let mut state = self.lock();
+		loop {
+			if generation.wrapping_sub(state.base) > state.current.wrapping_sub(state.base) {
+				return Follow::Gone;
+			}
+			if !state.swapping {
+				break;
+			}
+			if !wait {
+				return Follow::Swapping;
+			}
+			state = self.swapped.wait(state).unwrap_or_else(PoisonError::into_inner);
+		}
+		if generation == state.current { Follow::Current } else { Follow::Continued(state.current) }
+	}
+}
+
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
 impl<T> Drop for RequestNotification<T> {
 	fn drop(&mut self) {
-		unsafe { CloseHandle(self.overlapped.hEvent); }
+		unsafe { CloseHandle(unskip_port(self.overlapped.hEvent)); }
 	}
 }
 
@@ -347,25 +782,65 @@ impl DS4SubmitReport {
 	}
 	#[inline]
 	pub unsafe fn ioctl(&mut self, device: HANDLE, event: HANDLE) -> Result<(), u32> {
-		let mut transferred = 0;
 		let mut overlapped: OVERLAPPED = mem::zeroed();
-		overlapped.hEvent = event;
+		overlapped.hEvent = skip_port(event);
 
-		DeviceIoControl(
+		device_io_control(
 			device,
 			IOCTL_DS4_SUBMIT_REPORT,
 			self as *mut _ as _,
 			mem::size_of_val(self) as u32,
 			ptr::null_mut(),
 			0,
-			&mut transferred,
-			&mut overlapped);
+			&mut overlapped)
+	}
+}
 
-		if GetOverlappedResult(device, &mut overlapped, &mut transferred, /*bWait: */1) == 0 {
-			return Err(GetLastError());
+// Packed like the driver's header, the extended report is not aligned
+#[cfg(feature = "unstable_ds4")]
+#[repr(C, packed)]
+pub struct DS4SubmitReportEx {
+	pub Size: u32,
+	pub SerialNo: u32,
+	pub Report: crate::DS4ReportEx,
+}
+#[cfg(feature = "unstable_ds4")]
+impl DS4SubmitReportEx {
+	#[inline]
+	pub const fn new(serial_no: u32, report: crate::DS4ReportEx) -> DS4SubmitReportEx {
+		DS4SubmitReportEx {
+			Size: mem::size_of::<DS4SubmitReportEx>() as u32,
+			SerialNo: serial_no,
+			Report: report,
 		}
+	}
+	// Same request as the basic report, the driver tells them apart by their size
+	#[inline]
+	pub unsafe fn ioctl(&mut self, device: HANDLE, event: HANDLE) -> Result<(), u32> {
+		let mut overlapped: OVERLAPPED = mem::zeroed();
+		overlapped.hEvent = skip_port(event);
 
-		Ok(())
+		device_io_control(
+			device,
+			IOCTL_DS4_SUBMIT_REPORT,
+			self as *mut _ as _,
+			mem::size_of_val(self) as u32,
+			ptr::null_mut(),
+			0,
+			&mut overlapped)
+	}
+	/// Classifies the result of submitting to a serial number no target is plugged in under.
+	///
+	/// Only a driver which accepted the size goes on to look up the target and fails not finding it.
+	/// Drivers before ViGEmBus 1.17 reject the size, anything else cannot tell either way.
+	#[inline]
+	pub fn probe_result(result: Result<(), u32>) -> Result<bool, u32> {
+		match result {
+			Err(winerror::ERROR_DEV_NOT_EXIST | winerror::ERROR_FILE_NOT_FOUND) => Ok(true),
+			Err(winerror::ERROR_INVALID_PARAMETER | winerror::ERROR_INVALID_FUNCTION | winerror::ERROR_NOT_SUPPORTED) => Ok(false),
+			Ok(()) => Ok(false),
+			Err(err) => Err(err),
+		}
 	}
 }
 
@@ -386,24 +861,118 @@ impl XUsbGetUserIndex {
 	}
 	#[inline]
 	pub unsafe fn ioctl(&mut self, device: HANDLE, event: HANDLE) -> Result<(), u32> {
-		let mut transferred = 0;
 		let mut overlapped: OVERLAPPED = mem::zeroed();
-		overlapped.hEvent = event;
+		overlapped.hEvent = skip_port(event);
 
-		DeviceIoControl(
+		device_io_control(
 			device,
 			IOCTL_XUSB_GET_USER_INDEX,
 			self as *mut _ as _,
 			mem::size_of_val(self) as u32,
 			self as *mut _ as _,
 			mem::size_of_val(self) as u32,
-			&mut transferred,
-			&mut overlapped);
+			&mut overlapped)
+	}
+}
 
-		if GetOverlappedResult(device, &mut overlapped, &mut transferred, /*bWait: */1) == 0 {
-			return Err(GetLastError());
-		}
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use winapi::um::winbase::WAIT_FAILED;
+
+	// CTL_CODE(FILE_DEVICE_BUS_EXTENDER, IOCTL_BASE + function, METHOD_BUFFERED, access)
+	const fn ctl_code(function: u32, access: u32) -> u32 {
+		const FILE_DEVICE_BUS_EXTENDER: u32 = 0x2A;
+		const IOCTL_BASE: u32 = 0x801;
+		const METHOD_BUFFERED: u32 = 0;
+		(FILE_DEVICE_BUS_EXTENDER << 16) | (access << 14) | ((IOCTL_BASE + function) << 2) | METHOD_BUFFERED
+	}
+	const FILE_WRITE_ACCESS: u32 = 0x2;
+	const FILE_READ_WRITE_ACCESS: u32 = 0x3;
+
+	#[test]
+	fn ioctl_codes() {
+		assert_eq!(IOCTL_PLUGIN_TARGET, ctl_code(0x000, FILE_WRITE_ACCESS));
+		assert_eq!(IOCTL_UNPLUG_TARGET, ctl_code(0x001, FILE_WRITE_ACCESS));
+		assert_eq!(IOCTL_CHECK_VERSION, ctl_code(0x002, FILE_WRITE_ACCESS));
+		assert_eq!(IOCTL_WAIT_DEVICE_READY, ctl_code(0x003, FILE_WRITE_ACCESS));
+		#[cfg(feature = "xusb-rumble")]
+		assert_eq!(IOCTL_XUSB_REQUEST_NOTIFICATION, ctl_code(0x200, FILE_READ_WRITE_ACCESS));
+		assert_eq!(IOCTL_XUSB_SUBMIT_REPORT, ctl_code(0x201, FILE_WRITE_ACCESS));
+		assert_eq!(IOCTL_DS4_SUBMIT_REPORT, ctl_code(0x202, FILE_WRITE_ACCESS));
+		#[cfg(feature = "ds4-output")]
+		assert_eq!(IOCTL_DS4_REQUEST_NOTIFICATION, ctl_code(0x203, FILE_READ_WRITE_ACCESS));
+		assert_eq!(IOCTL_XUSB_GET_USER_INDEX, ctl_code(0x206, FILE_READ_WRITE_ACCESS));
+	}
 
-		Ok(())
+	#[cfg(feature = "unstable_ds4")]
+	#[test]
+	fn ds4_submit_sizes() {
+		// DS4_SUBMIT_REPORT and the packed DS4_SUBMIT_REPORT_EX of ViGEmBusShared.h
+		assert_eq!(DS4SubmitReport::new(1, crate::DS4Report::neutral()).Size, 20);
+		let submit = DS4SubmitReportEx::new(1, crate::DS4ReportEx::neutral());
+		assert_eq!({ submit.Size }, 71);
+		assert_eq!(mem::size_of_val(&submit), 71);
+		assert_eq!(mem::offset_of!(DS4SubmitReportEx, Report), 8);
+	}
+
+	#[cfg(feature = "unstable_ds4")]
+	#[test]
+	fn ds4_extended_probe() {
+		assert_eq!(DS4SubmitReportEx::probe_result(Err(winerror::ERROR_DEV_NOT_EXIST)), Ok(true));
+		assert_eq!(DS4SubmitReportEx::probe_result(Err(winerror::ERROR_FILE_NOT_FOUND)), Ok(true));
+		// Rejected by drivers before 1.17 for its size
+		assert_eq!(DS4SubmitReportEx::probe_result(Err(winerror::ERROR_INVALID_PARAMETER)), Ok(false));
+		assert_eq!(DS4SubmitReportEx::probe_result(Err(winerror::ERROR_INVALID_FUNCTION)), Ok(false));
+		assert_eq!(DS4SubmitReportEx::probe_result(Err(winerror::ERROR_NOT_SUPPORTED)), Ok(false));
+		// Accepted without looking up the target, the size was not checked
+		assert_eq!(DS4SubmitReportEx::probe_result(Ok(())), Ok(false));
+		assert_eq!(DS4SubmitReportEx::probe_result(Err(winerror::ERROR_ACCESS_DENIED)), Err(winerror::ERROR_ACCESS_DENIED));
+	}
+
+	#[test]
+	fn classify_completed() {
+		// The last error is stale after a successful call and must be ignored
+		assert_eq!(IoStatus::classify(1, 0), IoStatus::Completed);
+		assert_eq!(IoStatus::classify(1, winerror::ERROR_IO_PENDING), IoStatus::Completed);
+		assert_eq!(IoStatus::classify(1, winerror::ERROR_ACCESS_DENIED), IoStatus::Completed);
+	}
+
+	#[test]
+	fn classify_pending() {
+		assert_eq!(IoStatus::classify(0, winerror::ERROR_IO_PENDING), IoStatus::Pending);
+	}
+
+	#[test]
+	fn classify_failed() {
+		assert_eq!(IoStatus::classify(0, winerror::ERROR_DEV_NOT_EXIST), IoStatus::Failed(winerror::ERROR_DEV_NOT_EXIST));
+		assert_eq!(IoStatus::classify(0, winerror::ERROR_INVALID_PARAMETER), IoStatus::Failed(winerror::ERROR_INVALID_PARAMETER));
+		assert_eq!(IoStatus::classify(0, winerror::ERROR_OPERATION_ABORTED), IoStatus::Failed(winerror::ERROR_OPERATION_ABORTED));
+	}
+
+	#[test]
+	fn overlapped_result() {
+		assert_eq!(IoStatus::overlapped_result(1, winerror::ERROR_IO_PENDING), Ok(()));
+		assert_eq!(IoStatus::overlapped_result(0, winerror::ERROR_OPERATION_ABORTED), Err(winerror::ERROR_OPERATION_ABORTED));
+		assert_eq!(IoStatus::overlapped_result(0, winerror::ERROR_DEV_NOT_EXIST), Err(winerror::ERROR_DEV_NOT_EXIST));
+	}
+
+	#[test]
+	fn bounded_wait_completed() {
+		assert_eq!(BoundedWait::after(WAIT_OBJECT_0, false), BoundedWait::Completed);
+		// The cancellation was honoured, the request completed as aborted
+		assert_eq!(BoundedWait::after(WAIT_OBJECT_0, true), BoundedWait::Completed);
+	}
+
+	#[test]
+	fn bounded_wait_expired() {
+		assert_eq!(BoundedWait::after(winerror::WAIT_TIMEOUT, false), BoundedWait::Cancel);
+		assert_eq!(BoundedWait::after(WAIT_FAILED, false), BoundedWait::Cancel);
+	}
+
+	#[test]
+	fn bounded_wait_cancel_not_honoured() {
+		assert_eq!(BoundedWait::after(winerror::WAIT_TIMEOUT, true), BoundedWait::GiveUp);
+		assert_eq!(BoundedWait::after(WAIT_FAILED, true), BoundedWait::GiveUp);
 	}
 }
@@ -1,106 +1,1020 @@
-use std::{mem, ptr};
-use std::os::windows::io as win_io;
+use std::{fmt, iter, mem, ptr, thread, time};
+use std::ffi::{OsStr, OsString};
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::sync::{Mutex, PoisonError};
+use std::os::windows::io::{self as win_io, AsRawHandle, FromRawHandle, IntoRawHandle};
 use winapi::um::handleapi::*;
 use winapi::um::setupapi::*;
 use winapi::um::fileapi::*;
 use winapi::um::winnt::*;
 use winapi::um::winbase::*;
 use winapi::um::errhandlingapi::*;
+use winapi::um::ioapiset::CreateIoCompletionPort;
+use winapi::um::processthreadsapi::GetCurrentProcess;
+use winapi::shared::devpkey::DEVPKEY_Device_DriverVersion;
+use winapi::shared::devpropdef::DEVPROP_TYPE_STRING;
+use winapi::shared::guiddef::GUID;
 use winapi::shared::ntdef::HANDLE;
+use winapi::shared::winerror;
 use crate::*;
 
+/// Configures the connection to the ViGEmBus service, see [`Client::builder`].
+///
+/// Defaults to the flags used by [`Client::connect`], both `FILE_FLAG_NO_BUFFERING` and `FILE_FLAG_WRITE_THROUGH`.
+/// Clearing them is meant for measuring their effect on the submit latency and CPU usage, eg. with `benches/update.rs`.
+///
+/// The service handle is always opened with `FILE_FLAG_OVERLAPPED`, every request waits on its own event
+/// or is posted to a completion port, there is deliberately no option to disable it:
+///
+/// ```compile_fail
+/// let client = vigem_client::Client::builder().overlapped(false).connect();
+/// ```
+///
+/// ```no_run
+/// let client = vigem_client::Client::builder()
+/// 	.no_buffering(false)
+/// 	.write_through(false)
+/// 	.connect()?;
+/// # Ok::<(), vigem_client::Error>(())
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ClientBuilder {
+	no_buffering: bool,
+	write_through: bool,
+	interface: u128,
+}
+
+impl Default for ClientBuilder {
+	#[inline]
+	fn default() -> Self {
+		ClientBuilder::new()
+	}
+}
+
+impl ClientBuilder {
+	/// Creates a builder with the default flags.
+	#[inline]
+	pub const fn new() -> ClientBuilder {
+		ClientBuilder { no_buffering: true, write_through: true, interface: guid_to_u128(&bus::GUID_DEVINTERFACE) }
+	}
+
+	/// Opens the service handle with `FILE_FLAG_NO_BUFFERING`, enabled by default.
+	#[inline]
+	pub const fn no_buffering(self, enabled: bool) -> ClientBuilder {
+		ClientBuilder { no_buffering: enabled, ..self }
+	}
+
+	/// Opens the service handle with `FILE_FLAG_WRITE_THROUGH`, enabled by default.
+	#[inline]
+	pub const fn write_through(self, enabled: bool) -> ClientBuilder {
+		ClientBuilder { write_through: enabled, ..self }
+	}
+
+	/// Enumerates the device interfaces registered under the GUID, defaults to the ViGEmBus interface GUID.
+	///
+	/// Meant for forks of ViGEmBus which register their own interface GUID, the interface version check is the same.
+	/// Connecting fails with [`Error::BusNotFound`] if no device registers the GUID.
+	///
+	/// ```no_run
+	/// // {A1B2C3D4-0001-4000-8000-00AABBCCDDEE}
+	/// let fork = winapi::shared::guiddef::GUID {
+	/// 	Data1: 0xA1B2C3D4, Data2: 0x0001, Data3: 0x4000,
+	/// 	Data4: [0x80, 0x00, 0x00, 0xAA, 0xBB, 0xCC, 0xDD, 0xEE],
+	/// };
+	/// let client = vigem_client::Client::builder().interface_guid(&fork).connect()?;
+	/// # Ok::<(), vigem_client::Error>(())
+	/// ```
+	#[inline]
+	pub const fn interface_guid(self, guid: &GUID) -> ClientBuilder {
+		ClientBuilder { interface: guid_to_u128(guid), ..self }
+	}
+
+	/// Connects to the ViGEmBus service.
+	#[inline]
+	pub fn connect(&self) -> Result<Client, Error> {
+		Client::open(self, false, &mut Vec::new())
+	}
+
+	/// Connects to the ViGEmBus service, retrying until the timeout elapses.
+	///
+	/// See [`Client::connect_with_retry`].
+	#[inline(never)]
+	pub fn connect_with_retry(&self, timeout: time::Duration, interval: time::Duration) -> Result<Client, Error> {
+		// Timeouts too large to represent retry without a deadline
+		let deadline = time::Instant::now().checked_add(timeout);
+		loop {
+			let err = match self.connect() {
+				Ok(client) => return Ok(client),
+				Err(err) => err,
+			};
+			if !retry_connect(err) {
+				return Err(err);
+			}
+			let sleep = match deadline {
+				None => interval,
+				Some(deadline) => {
+					let now = time::Instant::now();
+					if now >= deadline {
+						return Err(err);
+					}
+					interval.min(deadline - now)
+				},
+			};
+			thread::sleep(sleep);
+		}
+	}
+
+	/// Connects to the ViGEmBus service even if the driver rejects the interface version of this crate.
+	///
+	/// See [`Client::connect_unchecked`].
+	#[inline]
+	pub fn connect_unchecked(&self) -> Result<Client, Error> {
+		Client::open(self, true, &mut Vec::new())
+	}
+
+	/// Connects to the ViGEmBus service and reports what happened with every device interface enumerated along the way.
+	///
+	/// See [`Client::connect_diagnostic`].
+	#[inline]
+	pub fn connect_diagnostic(&self) -> (Result<Client, Error>, Vec<BusInstance>) {
+		let mut instances = Vec::new();
+		let result = Client::open(self, false, &mut instances);
+		(result, instances)
+	}
+
+	/// Connects to the ViGEmBus device interface at the path, skipping enumeration.
+	///
+	/// See [`Client::connect_to_path`].
+	#[inline]
+	pub fn connect_to_path(&self, path: &OsStr) -> Result<Client, Error> {
+		Client::open_path(path, self.flags())
+	}
+
+	/// Lists every device interface without connecting to any.
+	///
+	/// See [`Client::enumerate_buses`].
+	#[inline]
+	pub fn enumerate_buses(&self) -> Result<Vec<BusInfo>, Error> {
+		Client::enumerate(self)
+	}
+
+	/// Tells if the device interfaces are registered and active.
+	///
+	/// See [`Client::bus_presence`].
+	#[inline]
+	pub fn bus_presence(&self) -> Result<BusPresence, Error> {
+		unsafe { BusPresence::query(&self.interface()) }
+	}
+
+	fn flags(&self) -> u32 {
+		let mut flags = 0;
+		if self.no_buffering {
+			flags |= FILE_FLAG_NO_BUFFERING;
+		}
+		if self.write_through {
+			flags |= FILE_FLAG_WRITE_THROUGH;
+		}
+		flags
+	}
+
+	fn interface(&self) -> GUID {
+		let bytes = self.interface.to_be_bytes();
+		let mut data4 = [0; 8];
+		data4.copy_from_slice(&bytes[8..]);
+		GUID {
+			Data1: u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+			Data2: u16::from_be_bytes([bytes[4], bytes[5]]),
+			Data3: u16::from_be_bytes([bytes[6], bytes[7]]),
+			Data4: data4,
+		}
+	}
+}
+
+// Errors of a driver which is still starting up or briefly held open by another process
+fn retry_connect(err: Error) -> bool {
+	match err {
+		Error::BusNotFound | Error::BusDisabled => true,
+		Error::BusAccessFailed(err) => matches!(err, winerror::ERROR_SHARING_VIOLATION | winerror::ERROR_FILE_NOT_FOUND),
+		_ => false,
+	}
+}
+
+// Packs the GUID in its textual order, GUID does not implement the traits derived by ClientBuilder
+const fn guid_to_u128(guid: &GUID) -> u128 {
+	let d = guid.Data4;
+	(guid.Data1 as u128) << 96 | (guid.Data2 as u128) << 80 | (guid.Data3 as u128) << 64 | u64::from_be_bytes(d) as u128
+}
+
+/// Outcome of the interface version check of the ViGEmBus driver, see [`Client::bus_version`].
+///
+/// The driver does not report its version, it only accepts or rejects the version offered by the client.
+/// When it rejects the version of this crate, [`Client::connect_unchecked`] offers the versions up to [`PROBED`](Self::PROBED) to find the one it speaks.
+///
+/// ```
+/// let version = vigem_client::BusVersion { required: 1, accepted: false, driver: Some(2) };
+/// assert_eq!(version.to_string(), "driver speaks version 2, this crate needs 1");
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BusVersion {
+	/// Interface version this crate speaks.
+	pub required: u32,
+	/// If the driver accepted the required version.
+	pub accepted: bool,
+	/// Interface version the driver accepted when probed, `None` if the required version was accepted or no probed version was.
+	pub driver: Option<u32>,
+}
+
+impl BusVersion {
+	/// Interface version this crate speaks.
+	pub const REQUIRED: u32 = bus::CheckVersion::COMMON;
+
+	/// Highest interface version probed by [`Client::connect_unchecked`].
+	pub const PROBED: u32 = 16;
+
+	/// The driver accepted the interface version of this crate.
+	#[inline]
+	pub const fn accepted() -> BusVersion {
+		BusVersion { required: BusVersion::REQUIRED, accepted: true, driver: None }
+	}
+
+	// Offers the required version, then the versions up to PROBED if the driver rejects it
+	unsafe fn negotiate(device: HANDLE) -> BusVersion {
+		if bus::CheckVersion::common().ioctl(device) {
+			return BusVersion::accepted();
+		}
+		let driver = (0..=BusVersion::PROBED)
+			.filter(|&version| version != BusVersion::REQUIRED)
+			.find(|&version| bus::CheckVersion::new(version).ioctl(device));
+		BusVersion { required: BusVersion::REQUIRED, accepted: false, driver }
+	}
+}
+
+/// Version of the connected ViGEmBus driver, see [`Client::driver_version`].
+///
+/// ```
+/// let version = vigem_client::DriverVersion {
+/// 	bus: vigem_client::BusVersion::accepted(),
+/// 	file: Some([1, 21, 442, 0]),
+/// 	wait_device_ready: true,
+/// };
+/// assert_eq!(version.to_string(), "ViGEmBus 1.21.442.0, driver speaks version 1");
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct DriverVersion {
+	/// Outcome of the interface version check, issued again when queried.
+	pub bus: BusVersion,
+	/// Version of the installed driver package, eg. `[1, 21, 442, 0]`.
+	///
+	/// Read from the device properties when connecting, `None` if they could not be read or the client was created from a raw handle.
+	pub file: Option<[u16; 4]>,
+	/// If the driver supports waiting for a target to be ready, ViGEmBus 1.17 or newer.
+	///
+	/// Older drivers have targets accept updates only after an arbitrary delay, see [`Xbox360Wired::wait_ready`](crate::Xbox360Wired::wait_ready).
+	pub wait_device_ready: bool,
+}
+
+impl fmt::Display for DriverVersion {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.file {
+			Some([major, minor, build, revision]) => write!(f, "ViGEmBus {}.{}.{}.{}, {}", major, minor, build, revision, self.bus),
+			None => write!(f, "ViGEmBus of unknown version, {}", self.bus),
+		}
+	}
+}
+
+// A device interface enumerated by `for_each_interface`
+pub(crate) struct Interface<'a> {
+	// The nul terminated path of the device interface
+	pub path: &'a [u16],
+	// The device node exposing the interface
+	pub dev_inst: u32,
+	device_info_set: HDEVINFO,
+	device_info_data: &'a mut SP_DEVINFO_DATA,
+}
+
+impl Interface<'_> {
+	// Reads the version of the driver package from the device properties
+	#[inline]
+	pub unsafe fn driver_file_version(&mut self) -> Option<[u16; 4]> {
+		driver_file_version(self.device_info_set, self.device_info_data)
+	}
+}
+
+// Calls the closure with every device interface registered under the GUID,
+// or the windows error code if its path could not be queried, until the closure returns false
+pub(crate) unsafe fn for_each_interface<F: FnMut(Result<Interface<'_>, u32>) -> bool>(guid: &GUID, mut f: F) -> Result<(), Error> {
+	let mut member_index = 0;
+	let mut device_interface_data: SP_DEVICE_INTERFACE_DATA = mem::zeroed();
+	device_interface_data.cbSize = mem::size_of_val(&device_interface_data) as u32;
+
+	let mut detail_data_buffer = mem::MaybeUninit::<[u32; 0x300]>::uninit();
+	let mut heap_buffer = Vec::<u32>::new();
+
+	let device_info_set = SetupDiGetClassDevsW(
+		guid,
+		ptr::null(),
+		ptr::null_mut(),
+		DIGCF_PRESENT | DIGCF_DEVICEINTERFACE);
+
+	if device_info_set == INVALID_HANDLE_VALUE {
+		return Err(Error::WinError(GetLastError()));
+	}
+
+	// Enumerate device instances
+	while SetupDiEnumDeviceInterfaces(
+		device_info_set,
+		ptr::null_mut(),
+		guid,
+		member_index,
+		&mut device_interface_data) != 0
+	{
+		member_index += 1;
+
+		// Start with the stack buffer which fits the paths of most devices
+		let mut detail_data_ptr = detail_data_buffer.as_mut_ptr() as PSP_DEVICE_INTERFACE_DETAIL_DATA_W;
+		let mut detail_data_size = mem::size_of_val(&detail_data_buffer);
+		*ptr::addr_of_mut!((*detail_data_ptr).cbSize) = mem::size_of::<SP_DEVICE_INTERFACE_DETAIL_DATA_W>() as u32;
+
+		// Get detail buffer
+		let mut required_size = 0;
+		let mut device_info_data: SP_DEVINFO_DATA = mem::zeroed();
+		device_info_data.cbSize = mem::size_of_val(&device_info_data) as u32;
+		let mut success = SetupDiGetDeviceInterfaceDetailW(
+			device_info_set,
+			&mut device_interface_data,
+			detail_data_ptr,
+			detail_data_size as u32,
+			&mut required_size,
+			&mut device_info_data) != 0;
+
+		// Longer paths, eg. of devices nested in containers, retry with a heap buffer of the required size
+		if !success && GetLastError() == winerror::ERROR_INSUFFICIENT_BUFFER {
+			heap_buffer.clear();
+			heap_buffer.resize((required_size as usize).div_ceil(4), 0);
+			detail_data_ptr = heap_buffer.as_mut_ptr() as PSP_DEVICE_INTERFACE_DETAIL_DATA_W;
+			detail_data_size = heap_buffer.len() * 4;
+			*ptr::addr_of_mut!((*detail_data_ptr).cbSize) = mem::size_of::<SP_DEVICE_INTERFACE_DETAIL_DATA_W>() as u32;
+			success = SetupDiGetDeviceInterfaceDetailW(
+				device_info_set,
+				&mut device_interface_data,
+				detail_data_ptr,
+				detail_data_size as u32,
+				&mut required_size,
+				&mut device_info_data) != 0;
+		}
+
+		let interface = if !success {
+			Err(GetLastError())
+		}
+		else {
+			let device_path = ptr::addr_of!((*detail_data_ptr).DevicePath) as *const u16;
+			let max_len = (detail_data_size - (device_path as usize - detail_data_ptr as usize)) / 2;
+			let len = (0..max_len).find(|&i| *device_path.add(i) == 0).unwrap_or(max_len - 1);
+			let path = std::slice::from_raw_parts(device_path, len + 1);
+			let dev_inst = device_info_data.DevInst;
+			Ok(Interface { path, dev_inst, device_info_set, device_info_data: &mut device_info_data })
+		};
+
+		if !f(interface) {
+			break;
+		}
+	}
+
+	SetupDiDestroyDeviceInfoList(device_info_set);
+	Ok(())
+}
+
+// Opens the device interface at the nul terminated path, returns the windows error code on failure
+unsafe fn open_device(path: *const u16, flags: u32) -> Result<HANDLE, u32> {
+	let device = CreateFileW(
+		path,
+		GENERIC_READ | GENERIC_WRITE,
+		FILE_SHARE_READ | FILE_SHARE_WRITE,
+		ptr::null_mut(),
+		OPEN_EXISTING,
+		FILE_ATTRIBUTE_NORMAL | FILE_FLAG_OVERLAPPED | flags,
+		ptr::null_mut());
+	if device == INVALID_HANDLE_VALUE {
+		return Err(GetLastError());
+	}
+	Ok(device)
+}
+
+// Reads the version of the driver package from the device properties
+unsafe fn driver_file_version(device_info_set: HDEVINFO, device_info_data: &mut SP_DEVINFO_DATA) -> Option<[u16; 4]> {
+	let mut buffer = [0u16; 64];
+	let mut property_type = 0;
+	if SetupDiGetDevicePropertyW(
+		device_info_set,
+		device_info_data,
+		&DEVPKEY_Device_DriverVersion,
+		&mut property_type,
+		buffer.as_mut_ptr() as *mut u8,
+		mem::size_of_val(&buffer) as u32,
+		ptr::null_mut(),
+		0) == 0 || property_type != DEVPROP_TYPE_STRING
+	{
+		return None;
+	}
+	let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+	parse_file_version(&String::from_utf16_lossy(&buffer[..len]))
+}
+
+fn parse_file_version(text: &str) -> Option<[u16; 4]> {
+	let mut version = [0u16; 4];
+	let mut parts = text.trim().split('.');
+	for part in &mut version {
+		*part = parts.next()?.parse().ok()?;
+	}
+	if parts.next().is_some() {
+		return None;
+	}
+	Some(version)
+}
+
+impl fmt::Display for BusVersion {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match (self.accepted, self.driver) {
+			(true, _) => write!(f, "driver speaks version {}", self.required),
+			(false, Some(driver)) => write!(f, "driver speaks version {}, this crate needs {}", driver, self.required),
+			(false, None) => write!(f, "driver speaks an unknown version above {}, this crate needs {}", BusVersion::PROBED, self.required),
+		}
+	}
+}
+
+/// Outcome of opening a single ViGEmBus device interface, see [`Client::connect_diagnostic`] and [`Client::enumerate_buses`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum BusInstance {
+	/// The path of the device interface could not be queried, contains the windows error code.
+	DetailFailed(u32),
+	/// The device could not be opened, contains the windows error code.
+	///
+	/// Eg. `ERROR_ACCESS_DENIED` when another process opened the device exclusively.
+	AccessFailed(u32),
+	/// The driver rejected the interface version of this crate.
+	VersionRejected,
+	/// The driver accepted the connection.
+	Accepted,
+}
+
+/// A ViGEmBus device interface, see [`Client::enumerate_buses`].
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BusInfo {
+	/// Path of the device interface, to be opened with [`Client::connect_to_path`].
+	///
+	/// `None` if the path could not be queried, see [`BusInstance::DetailFailed`].
+	pub path: Option<OsString>,
+	/// Outcome of opening the device interface and checking the interface version.
+	pub instance: BusInstance,
+	/// Version of the installed driver package, see [`DriverVersion::file`].
+	pub driver_file: Option<[u16; 4]>,
+}
+
+impl BusInfo {
+	/// Returns if [`Client::connect`] would accept this instance.
+	#[inline]
+	pub fn is_usable(&self) -> bool {
+		self.instance == BusInstance::Accepted
+	}
+}
+
+/// Presence of the ViGEmBus device on the machine, see [`Client::bus_presence`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum BusPresence {
+	/// A device interface is active, connecting can proceed.
+	Active,
+	/// Device interfaces are registered but none is active, the device is disabled or not started.
+	Disabled,
+	/// No device interface is registered, the driver is not installed.
+	NotInstalled,
+}
+
+impl BusPresence {
+	/// Classifies the `SPINT_*` flags of every registered device interface, present or not.
+	///
+	/// Interfaces flagged `SPINT_REMOVED` are ignored.
+	///
+	/// ```
+	/// use vigem_client::BusPresence;
+	/// use winapi::um::setupapi::{SPINT_ACTIVE, SPINT_REMOVED};
+	///
+	/// assert_eq!(BusPresence::classify([0, SPINT_ACTIVE]), BusPresence::Active);
+	/// assert_eq!(BusPresence::classify([0]), BusPresence::Disabled);
+	/// assert_eq!(BusPresence::classify([SPINT_REMOVED]), BusPresence::NotInstalled);
+	/// ```
+	pub fn classify<I: IntoIterator<Item = u32>>(flags: I) -> BusPresence {
+		let mut presence = BusPresence::NotInstalled;
+		for flags in flags {
+			if flags & SPINT_REMOVED != 0 {
+				continue;
+			}
+			if flags & SPINT_ACTIVE != 0 {
+				return BusPresence::Active;
+			}
+			presence = BusPresence::Disabled;
+		}
+		presence
+	}
+
+	/// Returns the error connecting fails with when no device interface is enumerated, `None` if active.
+	#[inline]
+	pub const fn error(self) -> Option<Error> {
+		match self {
+			BusPresence::Active => None,
+			BusPresence::Disabled => Some(Error::BusDisabled),
+			BusPresence::NotInstalled => Some(Error::BusNotFound),
+		}
+	}
+
+	// Enumerates the device interfaces registered under the GUID including those of devices not present
+	unsafe fn query(guid: &GUID) -> Result<BusPresence, Error> {
+		let device_info_set = SetupDiGetClassDevsW(guid, ptr::null(), ptr::null_mut(), DIGCF_DEVICEINTERFACE);
+		if device_info_set == INVALID_HANDLE_VALUE {
+			return Err(Error::WinError(GetLastError()));
+		}
+		let mut flags = Vec::new();
+		let mut device_interface_data: SP_DEVICE_INTERFACE_DATA = mem::zeroed();
+		device_interface_data.cbSize = mem::size_of_val(&device_interface_data) as u32;
+		while SetupDiEnumDeviceInterfaces(
+			device_info_set,
+			ptr::null_mut(),
+			guid,
+			flags.len() as u32,
+			&mut device_interface_data) != 0
+		{
+			flags.push(device_interface_data.Flags);
+		}
+		SetupDiDestroyDeviceInfoList(device_info_set);
+		Ok(BusPresence::classify(flags))
+	}
+}
+
+/// What the connected ViGEmBus driver supports, see [`Client::capabilities`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct Capabilities {
+	/// Outcome of the interface version check.
+	pub bus_version: BusVersion,
+	/// If the driver answers [`Client::probe_serial`], ViGEmBus 1.17 or newer.
+	pub probe_serial: bool,
+	/// If DualShock4 targets accept the extended input report, ViGEmBus 1.17 or newer.
+	///
+	/// See [`DualShock4Wired::update_ex`](crate::DualShock4Wired::update_ex).
+	/// Always false without the `unstable_ds4` feature, which includes the extended report.
+	pub ds4_extended_report: bool,
+}
+
 /// The ViGEmBus service connection.
 #[derive(Debug)]
 pub struct Client {
 	pub(crate) device: HANDLE,
+	version: BusVersion,
+	driver_file: Option<[u16; 4]>,
+	unchecked_targets: bool,
+	attached: Attached,
+	gate: Gate,
+	iocp: Mutex<CompletionPort>,
 }
 
 impl Client {
 	/// Connects to the ViGEmBus service.
+	///
+	/// Same as [`Client::builder().connect()`](ClientBuilder::connect).
+	/// Fails with [`Error::BusNotFound`] if the driver is not installed and [`Error::BusDisabled`] if its device is disabled or not started.
+	#[inline]
 	pub fn connect() -> Result<Client, Error> {
-		unsafe {
-			let mut error = Error::BusNotFound;
+		ClientBuilder::new().connect()
+	}
 
-			let mut member_index = 0;
-			let mut device_interface_data: SP_DEVICE_INTERFACE_DATA = mem::zeroed();
-			device_interface_data.cbSize = mem::size_of_val(&device_interface_data) as u32;
+	/// Connects to the ViGEmBus service, retrying until the timeout elapses.
+	///
+	/// Meant for programs started at login, where the driver may not be ready yet.
+	/// Retries while the bus is not found, not started or could not be opened because of a sharing violation, sleeping the interval in between.
+	/// Other errors, eg. [`Error::BusVersionMismatch`], are returned immediately, the last error is returned when the timeout elapses.
+	///
+	/// ```no_run
+	/// use std::time::Duration;
+	/// let client = vigem_client::Client::connect_with_retry(Duration::from_secs(30), Duration::from_millis(500))?;
+	/// # Ok::<(), vigem_client::Error>(())
+	/// ```
+	#[inline]
+	pub fn connect_with_retry(timeout: time::Duration, interval: time::Duration) -> Result<Client, Error> {
+		ClientBuilder::new().connect_with_retry(timeout, interval)
+	}
 
-			let mut detail_data_buffer = mem::MaybeUninit::<[u32; 0x300]>::uninit();
+	/// Connects to the ViGEmBus service even if the driver rejects the interface version of this crate.
+	///
+	/// **Dangerous**: the driver speaks another version of the interface, requests may be misinterpreted or fail in unexpected ways.
+	/// Meant for diagnostic tools which report the [version](Self::bus_version) to the user, nothing about the client is guaranteed to work.
+	/// Targets of a client whose version was rejected fail to plug in with [`Error::UncheckedClient`]
+	/// unless [allowed](Self::allow_unchecked_targets).
+	///
+	/// If the driver accepts the version the client is the same as one returned by [`connect`](Self::connect).
+	///
+	/// Same as [`Client::builder().connect_unchecked()`](ClientBuilder::connect_unchecked).
+	#[inline]
+	pub fn connect_unchecked() -> Result<Client, Error> {
+		ClientBuilder::new().connect_unchecked()
+	}
 
-			let device_info_set = SetupDiGetClassDevsW(
-				&bus::GUID_DEVINTERFACE,
-				ptr::null(),
-				ptr::null_mut(),
-				DIGCF_PRESENT | DIGCF_DEVICEINTERFACE);
+	/// Connects to the ViGEmBus service and reports what happened with every device interface enumerated along the way.
+	///
+	/// No instances means the driver is not installed or its device is disabled.
+	/// The connection is made like [`connect`](Self::connect), the instances tell why it failed:
+	///
+	/// ```no_run
+	/// let (result, instances) = vigem_client::Client::connect_diagnostic();
+	/// for (index, instance) in instances.iter().enumerate() {
+	/// 	println!("instance {}: {:?}", index, instance);
+	/// }
+	/// let client = result?;
+	/// # Ok::<(), vigem_client::Error>(())
+	/// ```
+	///
+	/// Same as [`Client::builder().connect_diagnostic()`](ClientBuilder::connect_diagnostic).
+	#[inline]
+	pub fn connect_diagnostic() -> (Result<Client, Error>, Vec<BusInstance>) {
+		ClientBuilder::new().connect_diagnostic()
+	}
 
-			if device_info_set == INVALID_HANDLE_VALUE {
-				return Err(Error::WinError(GetLastError()));
-			}
+	/// Connects to the ViGEmBus device interface at the path, skipping enumeration.
+	///
+	/// Meant for machines with several ViGEmBus compatible drivers installed, eg. a fork under test.
+	/// The path is a device interface path as enumerated by SetupDi, eg. `\\?\ROOT#SYSTEM#0001#{96e42b22-f5e9-42f8-b043-ed0f932f014f}`.
+	///
+	/// # Errors
+	///
+	/// * [`Error::BusAccessFailed`] if the path could not be opened, contains the windows error code.
+	/// * [`Error::BusVersionMismatch`] if the driver rejects the interface version of this crate.
+	///
+	/// The [driver version](Self::driver_version) of the client does not include the driver package version.
+	#[inline]
+	pub fn connect_to_path(path: &OsStr) -> Result<Client, Error> {
+		ClientBuilder::new().connect_to_path(path)
+	}
 
-			// Enumerate device instances
-			while SetupDiEnumDeviceInterfaces(
-				device_info_set,
-				ptr::null_mut(),
-				&bus::GUID_DEVINTERFACE,
-				member_index,
-				&mut device_interface_data) != 0
-			{
-				member_index += 1;
-
-				// Allocate target buffer
-				// This is a fixed size stack buffer which should be big enough for everyone
-				let detail_data_ptr = detail_data_buffer.as_mut_ptr() as PSP_DEVICE_INTERFACE_DETAIL_DATA_W;
-				*ptr::addr_of_mut!((*detail_data_ptr).cbSize) = mem::size_of::<SP_DEVICE_INTERFACE_DETAIL_DATA_W>() as u32;
-
-				// Get detail buffer
-				let mut required_size = 0;
-				if SetupDiGetDeviceInterfaceDetailW(
-					device_info_set,
-					&mut device_interface_data,
-					detail_data_ptr,
-					mem::size_of_val(&detail_data_buffer) as u32,
-					&mut required_size,
-					ptr::null_mut()) == 0
-				{
-					error = Error::WinError(GetLastError());
-					continue;
-				}
+	/// Tells if the ViGEmBus device interfaces are registered and active, without connecting.
+	///
+	/// Distinguishes a driver which is not installed from one whose device is disabled or not started,
+	/// which [`connect`](Self::connect) reports as [`Error::BusNotFound`] and [`Error::BusDisabled`] respectively.
+	#[inline]
+	pub fn bus_presence() -> Result<BusPresence, Error> {
+		ClientBuilder::new().bus_presence()
+	}
+
+	/// Lists every ViGEmBus device interface without connecting to any.
+	///
+	/// Walks the same instances in the same order as [`connect`](Self::connect), which connects to the first [usable](BusInfo::is_usable) one.
+	/// Every instance is opened for the version check and closed again, instances whose path could not be queried are listed with the error.
+	///
+	/// ```no_run
+	/// for bus in vigem_client::Client::enumerate_buses().unwrap() {
+	/// 	println!("{:?}: {:?}", bus.path, bus.instance);
+	/// }
+	/// ```
+	#[inline]
+	pub fn enumerate_buses() -> Result<Vec<BusInfo>, Error> {
+		ClientBuilder::new().enumerate_buses()
+	}
+
+	/// Returns a builder to configure the connection to the ViGEmBus service.
+	#[inline]
+	pub fn builder() -> ClientBuilder {
+		ClientBuilder::new()
+	}
+
+	fn open(builder: &ClientBuilder, unchecked: bool, instances: &mut Vec<BusInstance>) -> Result<Client, Error> {
+		let flags = builder.flags();
+		unsafe {
+			let mut error = Error::BusNotFound;
+			let mut accepted = None;
+			// First instance rejecting the version, connected to if no instance accepts it
+			let mut rejected = None;
+
+			for_each_interface(&builder.interface(), |interface| {
+				let mut interface = match interface {
+					Ok(interface) => interface,
+					Err(err) => {
+						instances.push(BusInstance::DetailFailed(err));
+						error = Error::WinError(err);
+						return true;
+					},
+				};
 
 				// bus found, open it
-				let device_path = ptr::addr_of!((*detail_data_ptr).DevicePath) as *const u16;
-				let device = CreateFileW(
-					device_path,
-					GENERIC_READ | GENERIC_WRITE,
-					FILE_SHARE_READ | FILE_SHARE_WRITE,
-					ptr::null_mut(),
-					OPEN_EXISTING,
-					FILE_ATTRIBUTE_NORMAL | FILE_FLAG_NO_BUFFERING | FILE_FLAG_WRITE_THROUGH | FILE_FLAG_OVERLAPPED,
-					ptr::null_mut());
-
-				if device == INVALID_HANDLE_VALUE {
-					error = Error::BusAccessFailed(GetLastError());
-					continue;
-				}
+				let driver_file = interface.driver_file_version();
+				let device = match open_device(interface.path.as_ptr(), flags) {
+					Ok(device) => device,
+					Err(err) => {
+						instances.push(BusInstance::AccessFailed(err));
+						error = Error::BusAccessFailed(err);
+						return true;
+					},
+				};
 
-				let mut check_version = bus::CheckVersion::common();
-				if check_version.ioctl(device) {
-					SetupDiDestroyDeviceInfoList(device_info_set);
-					return Ok(Client { device })
+				if bus::CheckVersion::common().ioctl(device) {
+					instances.push(BusInstance::Accepted);
+					accepted = Some((device, driver_file));
+					return false;
 				}
 
 				// version mismatch, look for another instance
-				CloseHandle(device);
+				instances.push(BusInstance::VersionRejected);
+				if unchecked && rejected.is_none() {
+					rejected = Some((device, driver_file));
+				}
+				else {
+					CloseHandle(device);
+				}
 				error = Error::BusVersionMismatch;
+				true
+			})?;
+
+			match (accepted, rejected) {
+				(Some((device, driver_file)), rejected) => {
+					if let Some((rejected, _)) = rejected {
+						CloseHandle(rejected);
+					}
+					let mut client = Client::from_device(device);
+					client.driver_file = driver_file;
+					Ok(client)
+				},
+				(None, Some((device, driver_file))) => {
+					let mut client = Client::from_device(device);
+					client.version = BusVersion::negotiate(device);
+					client.driver_file = driver_file;
+					Ok(client)
+				},
+				// Tell a disabled device apart from a missing driver
+				(None, None) if error == Error::BusNotFound => match BusPresence::query(&builder.interface()) {
+					Ok(BusPresence::Disabled) => Err(Error::BusDisabled),
+					_ => Err(error),
+				},
+				(None, None) => Err(error),
+			}
+		}
+	}
+
+	fn enumerate(builder: &ClientBuilder) -> Result<Vec<BusInfo>, Error> {
+		let flags = builder.flags();
+		let mut buses = Vec::new();
+		unsafe {
+			for_each_interface(&builder.interface(), |interface| {
+				let mut interface = match interface {
+					Ok(interface) => interface,
+					Err(err) => {
+						buses.push(BusInfo { path: None, instance: BusInstance::DetailFailed(err), driver_file: None });
+						return true;
+					},
+				};
+				let driver_file = interface.driver_file_version();
+				let instance = match open_device(interface.path.as_ptr(), flags) {
+					Ok(device) => {
+						let accepted = bus::CheckVersion::common().ioctl(device);
+						CloseHandle(device);
+						if accepted { BusInstance::Accepted } else { BusInstance::VersionRejected }
+					},
+					Err(err) => BusInstance::AccessFailed(err),
+				};
+				let path = OsString::from_wide(&interface.path[..interface.path.len() - 1]);
+				buses.push(BusInfo { path: Some(path), instance, driver_file });
+				true
+			})?;
+		}
+		Ok(buses)
+	}
+
+	fn open_path(path: &OsStr, flags: u32) -> Result<Client, Error> {
+		let path: Vec<u16> = path.encode_wide().chain(iter::once(0)).collect();
+		unsafe {
+			let device = open_device(path.as_ptr(), flags).map_err(Error::BusAccessFailed)?;
+			if !bus::CheckVersion::common().ioctl(device) {
+				CloseHandle(device);
+				return Err(Error::BusVersionMismatch);
+			}
+			Ok(Client::from_device(device))
+		}
+	}
+
+	#[inline]
+	fn from_device(device: HANDLE) -> Client {
+		handles::opened(device);
+		Client {
+			device,
+			version: BusVersion::accepted(),
+			driver_file: None,
+			unchecked_targets: false,
+			attached: Attached::default(),
+			gate: Gate::default(),
+			iocp: Mutex::new(CompletionPort::default()),
+		}
+	}
+
+	/// Returns the outcome of the interface version check.
+	///
+	/// Always accepted unless connected with [`connect_unchecked`](Self::connect_unchecked).
+	/// A client created from a raw handle assumes the version was accepted.
+	#[inline]
+	pub fn bus_version(&self) -> BusVersion {
+		self.version
+	}
+
+	/// Allows plugging in targets even if the driver rejected the interface version.
+	///
+	/// **Dangerous**: see [`connect_unchecked`](Self::connect_unchecked).
+	/// Without it targets of such a client fail to plug in with [`Error::UncheckedClient`].
+	#[inline]
+	pub fn allow_unchecked_targets(&mut self) {
+		self.unchecked_targets = true;
+	}
+
+	// Targets of a client whose version was rejected may only be plugged in when allowed
+	#[inline]
+	pub(crate) fn check_plugin(&self) -> Result<(), Error> {
+		if self.version.accepted || self.unchecked_targets {
+			Ok(())
+		}
+		else {
+			Err(Error::UncheckedClient)
+		}
+	}
+
+	/// Returns the number of targets created through this client which are currently plugged in.
+	///
+	/// Only targets constructed with this client instance are counted, not those of clients created with [`try_clone`](Self::try_clone).
+	#[inline]
+	pub fn attached_count(&self) -> usize {
+		self.attached.count()
+	}
+
+	/// Returns the serial numbers of the targets created through this client which are currently plugged in.
+	#[inline]
+	pub fn attached_serials(&self) -> Vec<u32> {
+		self.attached.serials()
+	}
+
+	/// Probes whether a target with the serial number is plugged in, by any client.
+	///
+	/// Returns:
+	///
+	/// * `Ok(Some(true))`: The target is plugged in, whether it is ready to accept updates or still busy getting ready.
+	/// * `Ok(Some(false))`: No target with the serial number is plugged in.
+	/// * `Ok(None)`: The driver is too old to tell, ViGEmBus 1.17 or newer is required.
+	///
+	/// The answer may be outdated by the time it returns, other clients can plug in or unplug targets at any moment.
+	/// Use it for diagnostics and cleanup, not to reserve a serial number.
+	#[inline(never)]
+	pub fn probe_serial(&self, serial_no: u32) -> Result<Option<bool>, Error> {
+		if serial_no == 0 {
+			return Ok(Some(false));
+		}
+		let event = Event::null().sync()?;
+		let _pass = self.enter();
+		let mut wait = bus::WaitDeviceReady::new(serial_no);
+		match unsafe { wait.probe(self.device, event.handle) } {
+			Ok(_) => Ok(Some(true)),
+			Err(winerror::ERROR_FILE_NOT_FOUND | winerror::ERROR_DEV_NOT_EXIST) => Ok(Some(false)),
+			// Version pre-1.17 where this IOCTL doesn't exist
+			Err(winerror::ERROR_INVALID_PARAMETER | winerror::ERROR_INVALID_FUNCTION) => Ok(None),
+			Err(err) => Err(Error::WinError(err)),
+		}
+	}
+
+	/// Checks the connection to the driver is still alive.
+	///
+	/// Issues the interface version check again on the existing handle, eg. to detect a driver which was upgraded or restarted underneath the client.
+	/// Fails with [`Error::BusVersionMismatch`] if the driver now rejects the interface version of this crate,
+	/// or [`Error::WinError`] if the request fails otherwise, in which case the client should be replaced by connecting again.
+	///
+	/// Does not affect the targets and may be called concurrently with them.
+	/// Always fails for a client whose version was rejected when [connecting unchecked](Self::connect_unchecked).
+	#[inline(never)]
+	pub fn ping(&self) -> Result<(), Error> {
+		let _pass = self.enter();
+		match unsafe { bus::CheckVersion::common().ioctl_result(self.device) } {
+			Ok(()) => Ok(()),
+			Err(winerror::ERROR_NOT_SUPPORTED) => Err(Error::BusVersionMismatch),
+			Err(err) => Err(Error::WinError(err)),
+		}
+	}
+
+	/// Queries the version of the connected driver.
+	///
+	/// Issues the interface version check again, which does not affect the targets already plugged in.
+	/// Callable at any time, eg. to warn users of drivers too old to [wait for targets to be ready](DriverVersion::wait_device_ready).
+	#[inline(never)]
+	pub fn driver_version(&self) -> Result<DriverVersion, Error> {
+		let bus = {
+			let _pass = self.enter();
+			unsafe { BusVersion::negotiate(self.device) }
+		};
+		let wait_device_ready = self.probe_serial(u32::MAX)?.is_some();
+		Ok(DriverVersion { bus, file: self.driver_file, wait_device_ready })
+	}
+
+	/// Queries what the connected driver supports.
+	///
+	/// Probes the driver with requests which do not affect any target.
+	#[inline(never)]
+	pub fn capabilities(&self) -> Result<Capabilities, Error> {
+		// No target is ever plugged in under the highest serial number, only the answer matters
+		let probe_serial = self.probe_serial(u32::MAX)?.is_some();
+		// Older drivers are known to reject the extended report, do not bother them with it
+		#[cfg(feature = "unstable_ds4")]
+		let ds4_extended_report = probe_serial && self.probe_ds4_extended()?;
+		#[cfg(not(feature = "unstable_ds4"))]
+		let ds4_extended_report = false;
+		Ok(Capabilities { bus_version: self.version, probe_serial, ds4_extended_report })
+	}
+
+	// Submits an extended report to the highest serial number, see `bus::DS4SubmitReportEx::probe_result`
+	#[cfg(feature = "unstable_ds4")]
+	fn probe_ds4_extended(&self) -> Result<bool, Error> {
+		let event = Event::null().sync()?;
+		let _pass = self.enter();
+		let mut submit = bus::DS4SubmitReportEx::new(u32::MAX, crate::DS4ReportEx::neutral());
+		let result = unsafe { submit.ioctl(self.device, event.handle) };
+		bus::DS4SubmitReportEx::probe_result(result).map_err(Error::WinError)
+	}
+
+	/// Associates the service handle with an I/O completion port, see [`IocpDriver`].
+	///
+	/// The blocking APIs keep working, their requests wait on their own events and are not posted to the completion port.
+	/// Enabling it again does nothing.
+	///
+	/// The association belongs to the handle, a client [cloned](Self::try_clone) from an enabled client cannot enable it again.
+	#[inline(never)]
+	pub fn enable_iocp(&self) -> Result<(), Error> {
+		let mut iocp = self.iocp.lock().unwrap_or_else(PoisonError::into_inner);
+		if iocp.port.is_null() {
+			iocp.port = unsafe { CreateIoCompletionPort(self.device, ptr::null_mut(), 0, 1) };
+			if iocp.port.is_null() {
+				return Err(Error::WinError(unsafe { GetLastError() }));
 			}
+		}
+		Ok(())
+	}
+
+	/// Returns if the service handle is associated with a completion port, see [`enable_iocp`](Self::enable_iocp).
+	#[inline]
+	pub fn is_iocp_enabled(&self) -> bool {
+		!self.iocp.lock().unwrap_or_else(PoisonError::into_inner).port.is_null()
+	}
 
-			SetupDiDestroyDeviceInfoList(device_info_set);
-			Err(error)
+	// Claims the completion port for a driver, enabling it if needed
+	pub(crate) fn claim_iocp(&self) -> Result<HANDLE, Error> {
+		self.enable_iocp()?;
+		let mut iocp = self.iocp.lock().unwrap_or_else(PoisonError::into_inner);
+		if iocp.driven {
+			return Err(Error::CompletionPortInUse);
 		}
+		iocp.driven = true;
+		Ok(iocp.port)
+	}
+
+	#[inline]
+	pub(crate) fn release_iocp(&self) {
+		self.iocp.lock().unwrap_or_else(PoisonError::into_inner).driven = false;
+	}
+
+	/// Takes exclusive access to the client for maintenance until the guard is dropped.
+	///
+	/// Blocks requests to the driver from other threads, eg. target updates, and waits for the requests in flight to complete.
+	/// The calling thread keeps access to the driver, eg. to [unplug all](TargetSet::unplug_all) targets.
+	///
+	/// Pending notification requests are not waited on, they use their own duplicated handle like [cloned](Self::try_clone) clients.
+	/// Maintenance is not reentrant, taking it again on the same thread before the guard is dropped deadlocks.
+	#[inline]
+	pub fn maintenance(&self) -> MaintenanceGuard<'_> {
+		MaintenanceGuard { _closed: self.gate.close() }
+	}
+
+	// Holds off maintenance for the duration of a driver request
+	#[inline]
+	pub(crate) fn enter(&self) -> Pass<'_> {
+		handles::check(self.device);
+		self.gate.enter()
+	}
+
+	#[inline]
+	pub(crate) fn track_plugin(&self, serial_no: u32) {
+		self.attached.plugged_in(serial_no);
+	}
+
+	#[inline]
+	pub(crate) fn track_adopt(&self, serial_no: u32) {
+		self.attached.adopted(serial_no);
+	}
+
+	#[inline]
+	pub(crate) fn track_unplug(&self, serial_no: u32, result: Result<(), u32>) {
+		self.attached.unplugged(serial_no, result);
 	}
 
 	/// Duplicates the ViGEmBus service handle.
 	#[inline]
 	pub fn try_clone(&self) -> Result<Client, Error> {
+		handles::check(self.device);
 		unsafe {
 			let process_handle = (!0) as *mut _;
 			let mut target_handle = mem::MaybeUninit::uninit();
@@ -112,11 +1026,84 @@ impl Client {
 				let err = GetLastError();
 				return Err(Error::WinError(err));
 			}
-			Ok(Client { device: target_handle.assume_init() })
+			let mut client = Client::from_device(target_handle.assume_init());
+			client.version = self.version;
+			client.driver_file = self.driver_file;
+			client.unchecked_targets = self.unchecked_targets;
+			Ok(client)
+		}
+	}
+
+	/// Duplicates the ViGEmBus service handle into another process.
+	///
+	/// Meant for a broker which connects on behalf of a sandboxed process, the process handle needs the `PROCESS_DUP_HANDLE` access right.
+	/// Returns the handle value in the target process, which is meaningless in this process.
+	/// Pass it to the target process which takes ownership with [`from_raw_handle`](win_io::FromRawHandle::from_raw_handle):
+	///
+	/// ```no_run
+	/// use std::os::windows::io::FromRawHandle;
+	///
+	/// # let handle_from_broker = std::ptr::null_mut();
+	/// // In the target process, with the value received from the broker
+	/// let client = unsafe { vigem_client::Client::from_raw_handle(handle_from_broker) };
+	/// client.ping()?;
+	/// # Ok::<(), vigem_client::Error>(())
+	/// ```
+	///
+	/// Both handles refer to the same connection, its targets stay plugged in until every handle is closed.
+	/// Fails with [`Error::WinError`], eg. `ERROR_ACCESS_DENIED` if the process handle lacks the access right.
+	#[inline(never)]
+	pub fn duplicate_to(&self, process: win_io::BorrowedHandle<'_>) -> Result<win_io::RawHandle, Error> {
+		handles::check(self.device);
+		unsafe {
+			let mut target_handle = ptr::null_mut();
+			let success = DuplicateHandle(
+				GetCurrentProcess(), self.device,
+				process.as_raw_handle(), &mut target_handle,
+				0, 0, DUPLICATE_SAME_ACCESS);
+			if success == 0 {
+				return Err(Error::WinError(GetLastError()));
+			}
+			Ok(target_handle)
+		}
+	}
+
+	/// Gives up the client without closing the ViGEmBus service handle.
+	///
+	/// The targets plugged in through the handle stay plugged in until the handle is closed or the process exits,
+	/// the driver unplugs the targets of a handle when it is closed.
+	/// The completion port of an [`IocpDriver`] is closed, the raw handle is no longer associated with a driver.
+	///
+	/// Same as [`into_raw_handle`](win_io::IntoRawHandle::into_raw_handle), the caller owns the returned handle.
+	#[inline]
+	pub fn leak(self) -> win_io::RawHandle {
+		let this = mem::ManuallyDrop::new(self);
+		handles::released(this.device);
+		unsafe {
+			let attached = ptr::read(&this.attached);
+			let gate = ptr::read(&this.gate);
+			let iocp = ptr::read(&this.iocp).into_inner().unwrap_or_else(PoisonError::into_inner);
+			drop((attached, gate));
+			if !iocp.port.is_null() {
+				CloseHandle(iocp.port);
+			}
 		}
+		this.device
 	}
 }
 
+/// Exclusive access to a client for maintenance, see [`Client::maintenance`].
+pub struct MaintenanceGuard<'a> {
+	_closed: Closed<'a>,
+}
+
+impl fmt::Debug for MaintenanceGuard<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("MaintenanceGuard")
+	}
+}
+
+// The device handle is opened for overlapped IO, every request brings its own OVERLAPPED and event
 unsafe impl Sync for Client {}
 unsafe impl Send for Client {}
 
@@ -126,16 +1113,50 @@ impl win_io::AsRawHandle for Client {
 		self.device
 	}
 }
+/// Transfers ownership of the ViGEmBus service handle to the caller, see [`Client::leak`].
 impl win_io::IntoRawHandle for Client {
 	#[inline]
 	fn into_raw_handle(self) -> HANDLE {
-		self.device
+		self.leak()
 	}
 }
+/// Takes ownership of a ViGEmBus service handle opened for overlapped IO.
+///
+/// # Safety
+///
+/// The client closes the handle when dropped, the caller must own the handle and give up on it.
+/// Creating a client from a handle still owned elsewhere, eg. the [`as_raw_handle`](win_io::AsRawHandle::as_raw_handle) of another client,
+/// closes the handle twice: the second close may hit an unrelated handle that reused the value
+/// and the remaining owner keeps issuing requests on a closed handle.
+///
+/// The `debug-handles` feature detects these mistakes in debug builds.
 impl win_io::FromRawHandle for Client {
 	#[inline]
 	unsafe fn from_raw_handle(device: HANDLE) -> Client {
-		Client { device }
+		Client::from_device(device)
+	}
+}
+/// Borrows the ViGEmBus service handle, the client keeps ownership.
+impl win_io::AsHandle for Client {
+	#[inline]
+	fn as_handle(&self) -> win_io::BorrowedHandle<'_> {
+		unsafe { win_io::BorrowedHandle::borrow_raw(self.device) }
+	}
+}
+/// Transfers ownership of the ViGEmBus service handle, see [`Client::leak`].
+impl From<Client> for win_io::OwnedHandle {
+	#[inline]
+	fn from(client: Client) -> win_io::OwnedHandle {
+		unsafe { win_io::OwnedHandle::from_raw_handle(client.leak()) }
+	}
+}
+/// Takes ownership of a ViGEmBus service handle opened for overlapped IO.
+///
+/// The handle is not checked, a handle to anything other than ViGEmBus fails the requests made through the client.
+impl From<win_io::OwnedHandle> for Client {
+	#[inline]
+	fn from(handle: win_io::OwnedHandle) -> Client {
+		Client::from_device(handle.into_raw_handle())
 	}
 }
 
@@ -143,7 +1164,12 @@ impl Drop for Client {
 	#[inline]
 	fn drop(&mut self) {
 		unsafe {
+			handles::closed(self.device);
 			CloseHandle(self.device);
+			let port = self.iocp.get_mut().unwrap_or_else(PoisonError::into_inner).port;
+			if !port.is_null() {
+				CloseHandle(port);
+			}
 		}
 	}
 }
@@ -0,0 +1,156 @@
+/*!
+Compatibility shim with the naming of the C ViGEmClient API.
+
+Free functions named after their `vigem_*` counterparts delegating to this crate's types, intended to make porting C and C++ code mechanical.
+
+Semantic differences with the C API:
+
+* There is no `vigem_alloc`/`vigem_connect` split, connect with [`Client::connect`].
+* Targets are allocated for a client and borrow it, the `client` arguments of the other functions must be the same client.
+* There is no `vigem_target_free`, targets are unplugged and freed when dropped.
+* Errors are returned as [`Error`] instead of `VIGEM_ERROR` codes:
+  `VIGEM_ERROR_BUS_NOT_FOUND` is [`Error::BusNotFound`] or [`Error::BusDisabled`], `VIGEM_ERROR_NO_FREE_SLOT` is [`Error::NoFreeSlot`],
+  `VIGEM_ERROR_TARGET_NOT_PLUGGED_IN` is [`Error::NotPluggedIn`], `VIGEM_ERROR_TARGET_UNINITIALIZED` has no equivalent,
+  `VIGEM_ERROR_ALREADY_CONNECTED` is [`Error::AlreadyConnected`], `VIGEM_ERROR_BUS_VERSION_MISMATCH` is [`Error::BusVersionMismatch`],
+  `VIGEM_ERROR_BUS_ACCESS_FAILED` is [`Error::BusAccessFailed`] and `VIGEM_ERROR_WINAPI` is [`Error::WinError`].
+
+```no_run
+use vigem_client::compat::*;
+
+let client = vigem_client::Client::connect().unwrap();
+let mut pad = target_x360_alloc(&client);
+target_add(&client, &mut pad).unwrap();
+
+let report = XUSB_REPORT::default();
+target_x360_update(&client, &mut pad, report).unwrap();
+
+target_remove(&client, &mut pad).unwrap();
+```
+*/
+
+#![allow(non_camel_case_types)]
+
+use std::borrow::Borrow;
+#[cfg(feature = "xusb-rumble")]
+use std::thread;
+use crate::*;
+
+/// Equivalent of `XUSB_REPORT`.
+pub type XUSB_REPORT = XGamepad;
+
+/// Equivalent of `DS4_REPORT`.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+pub type DS4_REPORT = DS4Report;
+
+/// Targets accepted by [`target_add`] and [`target_remove`].
+///
+/// Implemented for [`Xbox360Wired`] and [`DualShock4Wired`].
+pub trait Target {
+	#[doc(hidden)]
+	fn compat_client(&self) -> &Client;
+	#[doc(hidden)]
+	fn compat_add(&mut self) -> Result<(), Error>;
+	#[doc(hidden)]
+	fn compat_remove(&mut self) -> Result<(), Error>;
+}
+
+impl<CL: Borrow<Client>> Target for Xbox360Wired<CL> {
+	fn compat_client(&self) -> &Client {
+		self.client().borrow()
+	}
+	fn compat_add(&mut self) -> Result<(), Error> {
+		self.plugin()?;
+		self.wait_ready()
+	}
+	fn compat_remove(&mut self) -> Result<(), Error> {
+		self.unplug()
+	}
+}
+
+impl<CL: Borrow<Client>> Target for DualShock4Wired<CL> {
+	fn compat_client(&self) -> &Client {
+		self.client().borrow()
+	}
+	fn compat_add(&mut self) -> Result<(), Error> {
+		self.plugin()?;
+		self.wait_ready()
+	}
+	fn compat_remove(&mut self) -> Result<(), Error> {
+		self.unplug()
+	}
+}
+
+/// Equivalent of `vigem_target_x360_alloc`.
+///
+/// Allocates an Xbox360 wired target with the default vendor and product ids, it is not plugged in.
+#[inline]
+pub fn target_x360_alloc(client: &Client) -> Xbox360Wired<&Client> {
+	Xbox360Wired::new(client, TargetId::XBOX360_WIRED)
+}
+
+/// Equivalent of `vigem_target_ds4_alloc`.
+///
+/// Allocates a DualShock4 wired target with the default vendor and product ids, it is not plugged in.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[inline]
+pub fn target_ds4_alloc(client: &Client) -> DualShock4Wired<&Client> {
+	DualShock4Wired::new(client, TargetId::DUALSHOCK4_WIRED)
+}
+
+/// Equivalent of `vigem_target_add`.
+///
+/// Plugs the target in and waits until it is ready, like the C API.
+#[inline]
+pub fn target_add<T: Target>(client: &Client, target: &mut T) -> Result<(), Error> {
+	debug_assert!(std::ptr::eq(client, target.compat_client()), "target belongs to a different client");
+	target.compat_add()
+}
+
+/// Equivalent of `vigem_target_remove`.
+///
+/// Dropping the target also removes it.
+#[inline]
+pub fn target_remove<T: Target>(client: &Client, target: &mut T) -> Result<(), Error> {
+	debug_assert!(std::ptr::eq(client, target.compat_client()), "target belongs to a different client");
+	target.compat_remove()
+}
+
+/// Equivalent of `vigem_target_x360_update`.
+#[inline]
+pub fn target_x360_update<CL: Borrow<Client>>(client: &Client, target: &mut Xbox360Wired<CL>, report: XUSB_REPORT) -> Result<(), Error> {
+	debug_assert!(std::ptr::eq(client, target.client().borrow()), "target belongs to a different client");
+	target.update(&report)
+}
+
+/// Equivalent of `vigem_target_ds4_update`.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[inline]
+pub fn target_ds4_update<CL: Borrow<Client>>(client: &Client, target: &mut DualShock4Wired<CL>, report: DS4_REPORT) -> Result<(), Error> {
+	debug_assert!(std::ptr::eq(client, target.client().borrow()), "target belongs to a different client");
+	target.update(&report)
+}
+
+/// Equivalent of `vigem_target_x360_get_user_index`.
+#[inline]
+pub fn target_x360_get_user_index<CL: Borrow<Client>>(client: &Client, target: &mut Xbox360Wired<CL>) -> Result<u32, Error> {
+	debug_assert!(std::ptr::eq(client, target.client().borrow()), "target belongs to a different client");
+	target.get_user_index().map(UserIndex::as_u32)
+}
+
+/// Equivalent of `vigem_target_x360_register_notification`.
+///
+/// The callback receives the large motor, small motor and led number on a dedicated thread.
+/// Unlike the C API there is no unregister function, the thread exits when the target is removed and can be joined afterwards.
+#[cfg(feature = "xusb-rumble")]
+#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
+#[inline]
+pub fn target_x360_register_notification<CL, F>(client: &Client, target: &mut Xbox360Wired<CL>, mut callback: F) -> Result<thread::JoinHandle<()>, Error>
+	where CL: Borrow<Client>, F: FnMut(u8, u8, u8) + Send + 'static
+{
+	debug_assert!(std::ptr::eq(client, target.client().borrow()), "target belongs to a different client");
+	let notification = target.request_notification()?;
+	Ok(notification.spawn_thread(move |_, data| callback(data.large_motor, data.small_motor, data.led_number)))
+}
@@ -0,0 +1,20 @@
+/*!
+Constants mirroring the XInput API.
+
+These are available without depending on winapi's xinput bindings.
+*/
+
+/// Deadzone of the left thumbstick games are recommended to apply.
+///
+/// Mirrors `XINPUT_GAMEPAD_LEFT_THUMB_DEADZONE`.
+pub const LEFT_THUMB_DEADZONE: u16 = 7849;
+
+/// Deadzone of the right thumbstick games are recommended to apply.
+///
+/// Mirrors `XINPUT_GAMEPAD_RIGHT_THUMB_DEADZONE`.
+pub const RIGHT_THUMB_DEADZONE: u16 = 8689;
+
+/// Threshold below which games are recommended to ignore trigger values.
+///
+/// Mirrors `XINPUT_GAMEPAD_TRIGGER_THRESHOLD`.
+pub const TRIGGER_THRESHOLD: u8 = 30;
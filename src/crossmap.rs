@@ -0,0 +1,261 @@
+use crate::*;
+use crate::bindings::{dpad_direction, dpad_vector};
+
+/// How the analog triggers relate to the L2/R2 buttons of the DualShock4, see [`CrossMap`].
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TriggerPolicy {
+	/// L2/R2 are pressed past [`consts::TRIGGER_THRESHOLD`], a pressed L2/R2 raises the Xbox trigger just past it.
+	///
+	/// Same as [`Trigger::to_ds4`].
+	#[default]
+	Threshold,
+	/// L2/R2 are pressed by any pull, a pressed L2/R2 raises the Xbox trigger to at least one.
+	AnyPull,
+	/// L2/R2 are never pressed and ignored, only the analog values are converted.
+	AnalogOnly,
+}
+
+/// Configurable conversion between Xbox360 gamepad states and DualShock4 reports.
+///
+/// The default map is the plain conversion of `From<XGamepad> for DS4Report` and `From<DS4Report> for XGamepad`:
+/// the buttons in the same place on the controller correspond, Guide is PS, Back is Share and Start is Options.
+/// The thumbstick axes pair up in order, the vertical axes are flipped as they point down on the DualShock4.
+/// The touchpad click has no Xbox360 counterpart.
+///
+/// Converting a DualShock4 report to Xbox360 reverses the map:
+/// an Xbox360 button is pressed if its DualShock4 button is, a dpad direction is pressed if the dpad points that way.
+///
+/// ```
+/// use vigem_client::{CrossMap, DS4Button, DS4Buttons, DS4Report, XGamepad};
+///
+/// // Confirm with circle for Japanese-convention games
+/// let map = CrossMap::new().swap(DS4Button::Buttons(DS4Buttons::CROSS), DS4Button::Buttons(DS4Buttons::CIRCLE));
+///
+/// let gamepad = XGamepad::pressing(vigem_client::XButtons!(A));
+/// let report = map.convert_x_to_ds4(&gamepad);
+/// assert_eq!(report.buttons & !0xF, DS4Buttons::CIRCLE);
+/// assert_eq!(map.convert_ds4_to_x(&report), gamepad);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CrossMap {
+	/// DualShock4 control of every Xbox360 button flag by bit position, `None` leaves the button unmapped.
+	pub buttons: [Option<DS4Button>; 16],
+	/// Xbox360 thumbstick axis of the DualShock4 axes `[lx, ly, rx, ry]`, trigger axes read as centered.
+	pub sticks: [Axis; 4],
+	/// Inverts the DualShock4 axes `[lx, ly, rx, ry]` on top of the flipped vertical axes.
+	pub invert: [bool; 4],
+	/// How the triggers relate to the L2/R2 buttons.
+	pub triggers: TriggerPolicy,
+}
+
+impl Default for CrossMap {
+	#[inline]
+	fn default() -> CrossMap {
+		CrossMap::new()
+	}
+}
+
+impl CrossMap {
+	/// The plain conversion.
+	pub const fn new() -> CrossMap {
+		CrossMap {
+			buttons: [
+				Some(DS4Button::Dpad(DpadDirection::North)),
+				Some(DS4Button::Dpad(DpadDirection::South)),
+				Some(DS4Button::Dpad(DpadDirection::West)),
+				Some(DS4Button::Dpad(DpadDirection::East)),
+				Some(DS4Button::Buttons(DS4Buttons::OPTIONS)),
+				Some(DS4Button::Buttons(DS4Buttons::SHARE)),
+				Some(DS4Button::Buttons(DS4Buttons::L3)),
+				Some(DS4Button::Buttons(DS4Buttons::R3)),
+				Some(DS4Button::Buttons(DS4Buttons::L1)),
+				Some(DS4Button::Buttons(DS4Buttons::R1)),
+				Some(DS4Button::Special(DS4SpecialButtons::PS)),
+				None,
+				Some(DS4Button::Buttons(DS4Buttons::CROSS)),
+				Some(DS4Button::Buttons(DS4Buttons::CIRCLE)),
+				Some(DS4Button::Buttons(DS4Buttons::SQUARE)),
+				Some(DS4Button::Buttons(DS4Buttons::TRIANGLE)),
+			],
+			sticks: [Axis::LX, Axis::LY, Axis::RX, Axis::RY],
+			invert: [false; 4],
+			triggers: TriggerPolicy::Threshold,
+		}
+	}
+
+	/// Maps the Xbox360 buttons to the DualShock4 control, `None` to leave them unmapped.
+	#[inline]
+	pub fn map(mut self, buttons: XButtons, ds4: Option<DS4Button>) -> CrossMap {
+		for (bit, entry) in self.buttons.iter_mut().enumerate() {
+			if buttons.raw & (1 << bit) != 0 {
+				*entry = ds4;
+			}
+		}
+		self
+	}
+
+	/// Swaps the Xbox360 buttons mapped to two DualShock4 controls.
+	#[inline]
+	pub fn swap(mut self, a: DS4Button, b: DS4Button) -> CrossMap {
+		for entry in &mut self.buttons {
+			if *entry == Some(a) {
+				*entry = Some(b);
+			}
+			else if *entry == Some(b) {
+				*entry = Some(a);
+			}
+		}
+		self
+	}
+
+	/// Swaps the left and right thumbsticks.
+	#[inline]
+	pub fn swap_sticks(mut self) -> CrossMap {
+		self.sticks.swap(0, 2);
+		self.sticks.swap(1, 3);
+		self
+	}
+
+	/// Converts an Xbox360 gamepad state to a DualShock4 report.
+	pub fn convert_x_to_ds4(&self, gamepad: &XGamepad) -> DS4Report {
+		let mut report = DS4Report::neutral();
+		let (mut dx, mut dy) = (0, 0);
+		for (bit, entry) in self.buttons.iter().enumerate() {
+			match *entry {
+				Some(ds4) if gamepad.buttons.raw & (1 << bit) != 0 => match ds4 {
+					DS4Button::Buttons(buttons) => report.buttons |= buttons & !0xF,
+					DS4Button::Special(special) => report.special |= special,
+					DS4Button::Dpad(direction) => {
+						let (x, y) = dpad_vector(direction as u16);
+						dx += x;
+						dy += y;
+					},
+				},
+				_ => {},
+			}
+		}
+		report.set_dpad(dpad_direction(dx.signum(), dy.signum()));
+
+		let stick = |i: usize| {
+			let value = match self.sticks[i] {
+				Axis::LX => gamepad.thumb_lx,
+				Axis::LY => gamepad.thumb_ly,
+				Axis::RX => gamepad.thumb_rx,
+				Axis::RY => gamepad.thumb_ry,
+				Axis::LT | Axis::RT => 0,
+			};
+			// The vertical axes point down on the DualShock4
+			let flip = (i % 2 == 1) != self.invert[i];
+			let value = (value as f32 / i16::MAX as f32).clamp(-1.0, 1.0);
+			let value = if flip { -value } else { value };
+			(128.0 + value * 127.0).round() as u8
+		};
+		report.thumb_lx = stick(0);
+		report.thumb_ly = stick(1);
+		report.thumb_rx = stick(2);
+		report.thumb_ry = stick(3);
+
+		report.trigger_l = gamepad.left_trigger;
+		report.trigger_r = gamepad.right_trigger;
+		if self.trigger_pressed(gamepad.left_trigger) {
+			report.buttons |= DS4Buttons::L2;
+		}
+		if self.trigger_pressed(gamepad.right_trigger) {
+			report.buttons |= DS4Buttons::R2;
+		}
+		report
+	}
+
+	/// Converts a DualShock4 report to an Xbox360 gamepad state.
+	pub fn convert_ds4_to_x(&self, report: &DS4Report) -> XGamepad {
+		let mut gamepad = XGamepad::default();
+		let (dx, dy) = dpad_vector(report.buttons);
+		for (bit, entry) in self.buttons.iter().enumerate() {
+			let pressed = match *entry {
+				Some(DS4Button::Buttons(buttons)) => buttons & !0xF != 0 && report.buttons & buttons & !0xF == buttons & !0xF,
+				Some(DS4Button::Special(special)) => special != 0 && report.special & special == special,
+				Some(DS4Button::Dpad(direction)) => {
+					let (x, y) = dpad_vector(direction as u16);
+					(x, y) != (0, 0) && (x == 0 || x == dx) && (y == 0 || y == dy)
+				},
+				None => false,
+			};
+			if pressed {
+				gamepad.buttons.raw |= 1 << bit;
+			}
+		}
+
+		let mut sticks = [0i16; 4];
+		for (i, &raw) in [report.thumb_lx, report.thumb_ly, report.thumb_rx, report.thumb_ry].iter().enumerate() {
+			let flip = (i % 2 == 1) != self.invert[i];
+			let value = ((raw as f32 - 128.0) / 127.0).clamp(-1.0, 1.0);
+			let value = if flip { -value } else { value };
+			let value = (value * i16::MAX as f32).round() as i16;
+			match self.sticks[i] {
+				Axis::LX => sticks[0] = value,
+				Axis::LY => sticks[1] = value,
+				Axis::RX => sticks[2] = value,
+				Axis::RY => sticks[3] = value,
+				Axis::LT | Axis::RT => {},
+			}
+		}
+		gamepad.thumb_lx = sticks[0];
+		gamepad.thumb_ly = sticks[1];
+		gamepad.thumb_rx = sticks[2];
+		gamepad.thumb_ry = sticks[3];
+
+		gamepad.left_trigger = self.trigger_from_ds4(report.trigger_l, report.buttons & DS4Buttons::L2 != 0);
+		gamepad.right_trigger = self.trigger_from_ds4(report.trigger_r, report.buttons & DS4Buttons::R2 != 0);
+		gamepad
+	}
+
+	fn trigger_pressed(&self, value: u8) -> bool {
+		match self.triggers {
+			TriggerPolicy::Threshold => value > consts::TRIGGER_THRESHOLD,
+			TriggerPolicy::AnyPull => value > 0,
+			TriggerPolicy::AnalogOnly => false,
+		}
+	}
+
+	fn trigger_from_ds4(&self, value: u8, pressed: bool) -> u8 {
+		match (self.triggers, pressed) {
+			(TriggerPolicy::Threshold, true) => value.max(consts::TRIGGER_THRESHOLD + 1),
+			(TriggerPolicy::AnyPull, true) => value.max(1),
+			_ => value,
+		}
+	}
+}
+
+/// Converts with the default [`CrossMap`].
+impl From<XGamepad> for DS4Report {
+	#[inline]
+	fn from(gamepad: XGamepad) -> DS4Report {
+		CrossMap::new().convert_x_to_ds4(&gamepad)
+	}
+}
+/// Converts with the default [`CrossMap`].
+impl<'a> From<&'a XGamepad> for DS4Report {
+	#[inline]
+	fn from(gamepad: &'a XGamepad) -> DS4Report {
+		CrossMap::new().convert_x_to_ds4(gamepad)
+	}
+}
+
+/// Converts with the default [`CrossMap`].
+impl From<DS4Report> for XGamepad {
+	#[inline]
+	fn from(report: DS4Report) -> XGamepad {
+		CrossMap::new().convert_ds4_to_x(&report)
+	}
+}
+/// Converts with the default [`CrossMap`].
+impl<'a> From<&'a DS4Report> for XGamepad {
+	#[inline]
+	fn from(report: &'a DS4Report) -> XGamepad {
+		CrossMap::new().convert_ds4_to_x(report)
+	}
+}
@@ -0,0 +1,170 @@
+use std::{ffi, mem, ptr, thread, time};
+use std::os::windows::ffi::OsStringExt;
+use winapi::um::cfgmgr32::*;
+use winapi::um::handleapi::INVALID_HANDLE_VALUE;
+use winapi::um::setupapi::*;
+use winapi::shared::guiddef::GUID;
+use winapi::shared::winerror;
+use winapi::shared::ntdef::HANDLE;
+use winapi::shared::cfg::DN_HAS_PROBLEM;
+use crate::*;
+
+/// Time waited for the device of a freshly plugged in target to appear, see `device_path`.
+pub(crate) const DEVICE_PATH_TIMEOUT: time::Duration = time::Duration::from_secs(5);
+
+const POLL_INTERVAL: time::Duration = time::Duration::from_millis(20);
+
+/// Time given to Windows to install the device of a freshly plugged in target before its installation state is consulted.
+pub(crate) const INSTALL_GRACE: time::Duration = time::Duration::from_secs(10);
+
+const INSTALL_POLL_INTERVAL: time::Duration = time::Duration::from_millis(250);
+
+// Tells a device still being installed apart from a device which failed to install while waiting for a target to become ready.
+// Installing the driver of a new device can take a while, its problem code is only trusted after the grace period.
+pub(crate) struct InstallWatch {
+	deadline: time::Instant,
+}
+
+impl InstallWatch {
+	#[inline]
+	pub(crate) fn new(grace: time::Duration) -> InstallWatch {
+		InstallWatch { deadline: time::Instant::now() + grace }
+	}
+
+	// Returns how long to wait for readiness before consulting the problem code again.
+	pub(crate) fn next(&self, now: time::Instant, problem: impl FnOnce() -> Option<u32>) -> Result<time::Duration, Error> {
+		if now < self.deadline {
+			return Ok(self.deadline - now);
+		}
+		match problem() {
+			Some(code) => Err(Error::DeviceInstallFailed(code)),
+			None => Ok(INSTALL_POLL_INTERVAL),
+		}
+	}
+
+	// Waits for the target to become ready, consulting the problem code of its device node after the grace period.
+	pub(crate) unsafe fn wait_ready(device: HANDLE, event: HANDLE, id: TargetId, serial_no: u32) -> Result<(), Error> {
+		let watch = InstallWatch::new(INSTALL_GRACE);
+		bus::WaitDeviceReady::new(serial_no).ioctl_watched(device, event, || {
+			let timeout = watch.next(time::Instant::now(), || install_problem(id, serial_no))?;
+			Ok(timeout.as_millis().min(u32::MAX as u128) as u32)
+		})
+	}
+}
+
+// Finds the path of the device interface belonging to the target.
+// The instance id assigned by the bus is found on the device node exposing the interface, or on its parent.
+pub(crate) fn device_path(interface: &GUID, parent: bool, id: TargetId, serial_no: u32, timeout: time::Duration) -> Result<ffi::OsString, Error> {
+	let deadline = time::Instant::now() + timeout;
+	loop {
+		let mut found = unsafe { enumerate(interface, parent, id, serial_no)? };
+		if found.len() > 1 {
+			return Err(Error::AmbiguousDevice);
+		}
+		if let Some(path) = found.pop() {
+			return Ok(path);
+		}
+		// Wait for the device to materialize
+		if time::Instant::now() >= deadline {
+			return Err(Error::DeviceNotFound);
+		}
+		thread::sleep(POLL_INTERVAL);
+	}
+}
+
+// Waits for the device interface belonging to the unplugged target to disappear.
+pub(crate) fn wait_removed(interface: &GUID, parent: bool, id: TargetId, serial_no: u32, timeout: time::Duration) -> Result<(), Error> {
+	let deadline = time::Instant::now() + timeout;
+	while !unsafe { enumerate(interface, parent, id, serial_no)? }.is_empty() {
+		if time::Instant::now() >= deadline {
+			return Err(Error::WinError(winerror::ERROR_TIMEOUT));
+		}
+		thread::sleep(POLL_INTERVAL);
+	}
+	Ok(())
+}
+
+// Paths of the device interfaces belonging to the target, fails if the path of any interface could not be queried
+unsafe fn enumerate(interface: &GUID, parent: bool, id: TargetId, serial_no: u32) -> Result<Vec<ffi::OsString>, Error> {
+	let mut found = Vec::new();
+	let mut error = None;
+	for_each_interface(interface, |interface| {
+		let interface = match interface {
+			Ok(interface) => interface,
+			Err(err) => {
+				error = Some(err);
+				return false;
+			},
+		};
+
+		let mut dev_inst = interface.dev_inst;
+		if parent && CM_Get_Parent(&mut dev_inst, interface.dev_inst, 0) != CR_SUCCESS {
+			return true;
+		}
+		let mut instance_id = [0u16; MAX_DEVICE_ID_LEN + 1];
+		if CM_Get_Device_IDW(dev_inst, instance_id.as_mut_ptr(), instance_id.len() as u32, 0) != CR_SUCCESS {
+			return true;
+		}
+
+		if is_target(&from_wide(&instance_id), id, serial_no) {
+			found.push(ffi::OsString::from_wide(&interface.path[..interface.path.len() - 1]));
+		}
+		true
+	})?;
+	match error {
+		Some(err) => Err(Error::WinError(err)),
+		None => Ok(found),
+	}
+}
+
+// Problem code of the device node of the target, `None` while it has none or has not appeared yet.
+// A device which failed to install may not expose its interface, look for the node among all devices enumerated by USB.
+fn install_problem(id: TargetId, serial_no: u32) -> Option<u32> {
+	unsafe {
+		let enumerator: Vec<u16> = "USB\0".encode_utf16().collect();
+		let device_info_set = SetupDiGetClassDevsW(ptr::null(), enumerator.as_ptr(), ptr::null_mut(), DIGCF_PRESENT | DIGCF_ALLCLASSES);
+		if device_info_set == INVALID_HANDLE_VALUE {
+			return None;
+		}
+
+		let mut problem = None;
+		let mut member_index = 0;
+		let mut device_info_data: SP_DEVINFO_DATA = mem::zeroed();
+		device_info_data.cbSize = mem::size_of_val(&device_info_data) as u32;
+
+		while SetupDiEnumDeviceInfo(device_info_set, member_index, &mut device_info_data) != 0 {
+			member_index += 1;
+
+			let mut instance_id = [0u16; MAX_DEVICE_ID_LEN + 1];
+			if CM_Get_Device_IDW(device_info_data.DevInst, instance_id.as_mut_ptr(), instance_id.len() as u32, 0) != CR_SUCCESS {
+				continue;
+			}
+			if !is_target(&from_wide(&instance_id), id, serial_no) {
+				continue;
+			}
+
+			let (mut status, mut code) = (0, 0);
+			if CM_Get_DevNode_Status(&mut status, &mut code, device_info_data.DevInst, 0) == CR_SUCCESS && status & DN_HAS_PROBLEM != 0 {
+				problem = Some(code);
+			}
+			break;
+		}
+
+		SetupDiDestroyDeviceInfoList(device_info_set);
+		problem
+	}
+}
+
+fn from_wide(buffer: &[u16]) -> String {
+	let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+	String::from_utf16_lossy(&buffer[..len])
+}
+
+// Instance ids of the targets look like `USB\VID_054C&PID_05C4\01`, the bus numbers the instances by serial number
+fn is_target(instance_id: &str, id: TargetId, serial_no: u32) -> bool {
+	let mut parts = instance_id.split('\\');
+	let (Some(_), Some(device), Some(instance), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+		return false;
+	};
+	device.eq_ignore_ascii_case(&format!("VID_{:04X}&PID_{:04X}", id.vendor, id.product)) && instance.parse() == Ok(serial_no)
+}
@@ -0,0 +1,187 @@
+use std::{fmt, marker, time};
+use crate::*;
+
+/// Why a [`PacedDriver`] stopped.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DriveStop {
+	/// The callback returned `None`.
+	Finished,
+	/// Submitting a report failed with an error other than [`Error::TargetNotReady`].
+	Failed(Error),
+}
+
+/// Counts of a [`PacedDriver`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct DriveSummary {
+	/// Number of times the callback was called.
+	pub ticks: u64,
+	/// Number of reports submitted.
+	pub submitted: u64,
+	/// Number of reports the target was not ready for, driving continues with the next tick.
+	pub not_ready: u64,
+	/// Time since the first tick as of the last tick.
+	pub elapsed: time::Duration,
+	/// Why the driver stopped, `None` while it is running.
+	pub stop: Option<DriveStop>,
+}
+
+/// Pulls reports from a callback at a fixed rate and submits them to the target.
+///
+/// Every tick the callback is called with the time elapsed since the first tick, the report it returns is submitted.
+/// Driving stops when the callback returns `None` or submitting fails with an error other than [`Error::TargetNotReady`].
+///
+/// Block the calling thread with [`run`](Self::run), see also [`Xbox360Wired::drive`],
+/// or pump it from the host's loop for thread restricted hosts, see [`Pump`].
+///
+/// ```no_run
+/// use std::time::{Duration, Instant};
+/// use vigem_client::Pump;
+///
+/// let client = vigem_client::Client::connect().unwrap();
+/// let mut target = vigem_client::Xbox360Wired::new(client, vigem_client::TargetId::XBOX360_WIRED);
+/// target.plugin().unwrap();
+/// target.wait_ready().unwrap();
+///
+/// let mut driver = vigem_client::PacedDriver::new(&mut target, 100.0, |elapsed: Duration| {
+/// 	// Hold A for a second
+/// 	(elapsed < Duration::from_secs(1)).then(|| vigem_client::XGamepad::pressing(vigem_client::XButtons!(A)))
+/// });
+///
+/// // From the host's per-frame callback
+/// while !driver.is_finished() {
+/// 	driver.pump(Instant::now()).unwrap();
+/// 	# break;
+/// }
+/// ```
+pub struct PacedDriver<T, R, F> {
+	target: T,
+	produce: F,
+	clock: FixedRate,
+	start: Option<time::Instant>,
+	summary: DriveSummary,
+	_report: marker::PhantomData<fn() -> R>,
+}
+
+impl<T: Submit<R>, R, F: FnMut(time::Duration) -> Option<R>> PacedDriver<T, R, F> {
+	/// Drives the target at the rate in Hz.
+	///
+	/// Panics if the rate is not positive and finite, see [`FixedRate::from_hz`].
+	#[inline]
+	pub fn new(target: T, rate_hz: f32, produce: F) -> PacedDriver<T, R, F> {
+		PacedDriver {
+			target,
+			produce,
+			clock: FixedRate::from_hz(rate_hz as f64),
+			start: None,
+			summary: DriveSummary::default(),
+			_report: marker::PhantomData,
+		}
+	}
+
+	/// Returns the interval between ticks.
+	#[inline]
+	pub fn interval(&self) -> time::Duration {
+		self.clock.interval()
+	}
+
+	/// Returns the counts so far.
+	#[inline]
+	pub fn summary(&self) -> &DriveSummary {
+		&self.summary
+	}
+
+	/// Returns if driving stopped.
+	#[inline]
+	pub fn is_finished(&self) -> bool {
+		self.summary.stop.is_some()
+	}
+
+	/// Returns when the next tick is due when pumped, `None` before the first tick or once finished.
+	#[inline]
+	pub fn next_tick(&self) -> Option<time::Instant> {
+		if self.is_finished() {
+			return None;
+		}
+		self.clock.next_tick()
+	}
+
+	/// Returns the target.
+	#[inline]
+	pub fn target(&self) -> &T {
+		&self.target
+	}
+
+	/// Returns the target.
+	#[inline]
+	pub fn target_mut(&mut self) -> &mut T {
+		&mut self.target
+	}
+
+	/// Returns the target.
+	#[inline]
+	pub fn into_target(self) -> T {
+		self.target
+	}
+
+	/// Ticks now regardless of the rate.
+	///
+	/// Returns if driving continues.
+	pub fn tick(&mut self, now: time::Instant) -> bool {
+		if self.is_finished() {
+			return false;
+		}
+		let start = *self.start.get_or_insert(now);
+		self.summary.elapsed = now.saturating_duration_since(start);
+		self.summary.ticks += 1;
+		let Some(report) = (self.produce)(self.summary.elapsed) else {
+			self.summary.stop = Some(DriveStop::Finished);
+			return false;
+		};
+		match self.target.submit_report(&report) {
+			Ok(()) => self.summary.submitted += 1,
+			Err(Error::TargetNotReady) => self.summary.not_ready += 1,
+			Err(err) => {
+				self.summary.stop = Some(DriveStop::Failed(err));
+				return false;
+			},
+		}
+		true
+	}
+
+	/// Ticks on the clock source until driving stops.
+	///
+	/// The rate of the driver is ignored, the clock source paces the ticks.
+	pub fn run<C: ClockSource + ?Sized>(&mut self, clock: &mut C) -> DriveSummary {
+		while !self.is_finished() {
+			clock.wait_next_tick();
+			self.tick(clock.now());
+		}
+		self.summary
+	}
+}
+
+/// Ticks when due at the rate of the driver, missed ticks are skipped.
+///
+/// Returns the error which stopped driving on the pump it occurred, later pumps do nothing.
+impl<T: Submit<R>, R, F: FnMut(time::Duration) -> Option<R>> Pump for PacedDriver<T, R, F> {
+	fn pump(&mut self, now: time::Instant) -> Result<(), Error> {
+		if self.is_finished() || !self.clock.poll(now) {
+			return Ok(());
+		}
+		self.tick(now);
+		match self.summary.stop {
+			Some(DriveStop::Failed(err)) => Err(err),
+			_ => Ok(()),
+		}
+	}
+}
+
+impl<T: fmt::Debug, R, F> fmt::Debug for PacedDriver<T, R, F> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("PacedDriver")
+			.field("target", &self.target)
+			.field("interval", &self.clock.interval())
+			.field("summary", &self.summary)
+			.finish()
+	}
+}
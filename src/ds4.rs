@@ -1,10 +1,520 @@
-use std::{fmt, mem, ptr};
+#[cfg(any(windows, feature = "unstable_ds4"))]
+use std::{fmt, mem};
+#[cfg(windows)]
+use std::{ffi, ptr};
+#[cfg(any(windows, feature = "unstable_ds4"))]
+use std::time;
+#[cfg(windows)]
+use std::sync::Arc;
+#[cfg(windows)]
 use std::borrow::Borrow;
+#[cfg(feature = "unstable_ds4")]
+use std::convert::TryFrom;
+#[cfg(feature = "unstable_ds4")]
+use std::str;
+#[cfg(all(windows, feature = "ds4-output"))]
+use std::{marker, pin, thread};
+#[cfg(all(windows, feature = "ds4-output"))]
+use std::sync::Weak;
+#[cfg(all(windows, feature = "ds4-output"))]
+use std::collections::VecDeque;
+#[cfg(all(windows, feature = "ds4-output"))]
+use winapi::shared::winerror;
 use crate::*;
 
-/// DualShock4 HID Input report.
+/// DualShock4 dpad direction.
+///
+/// Stored in the low nibble of [`DS4Report::buttons`].
 #[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum DpadDirection {
+	North = 0,
+	NorthEast = 1,
+	East = 2,
+	SouthEast = 3,
+	South = 4,
+	SouthWest = 5,
+	West = 6,
+	NorthWest = 7,
+	None = 8,
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl DpadDirection {
+	/// All directions in order of their raw value.
+	pub const ALL: [DpadDirection; 9] = [
+		DpadDirection::North, DpadDirection::NorthEast, DpadDirection::East, DpadDirection::SouthEast,
+		DpadDirection::South, DpadDirection::SouthWest, DpadDirection::West, DpadDirection::NorthWest,
+		DpadDirection::None,
+	];
+
+	/// Rotates the direction clockwise by the given number of quarter turns.
+	///
+	/// `None` is not affected.
+	///
+	/// ```
+	/// use vigem_client::DpadDirection;
+	/// assert_eq!(DpadDirection::North.rotated_cw(1), DpadDirection::East);
+	/// assert_eq!(DpadDirection::SouthWest.rotated_cw(2), DpadDirection::NorthEast);
+	/// assert_eq!(DpadDirection::None.rotated_cw(3), DpadDirection::None);
+	/// ```
+	#[inline]
+	pub const fn rotated_cw(self, quarter_turns: u8) -> DpadDirection {
+		match self {
+			DpadDirection::None => DpadDirection::None,
+			_ => DpadDirection::ALL[((self as u8 + (quarter_turns % 4) * 2) % 8) as usize],
+		}
+	}
+
+	/// Mirrors the direction.
+	///
+	/// Mirroring horizontally swaps east and west, mirroring vertically swaps north and south.
+	/// `None` is not affected.
+	///
+	/// ```
+	/// use vigem_client::DpadDirection;
+	/// assert_eq!(DpadDirection::NorthEast.mirrored(true, false), DpadDirection::NorthWest);
+	/// assert_eq!(DpadDirection::NorthEast.mirrored(false, true), DpadDirection::SouthEast);
+	/// assert_eq!(DpadDirection::NorthEast.mirrored(true, true), DpadDirection::SouthWest);
+	/// ```
+	#[inline]
+	pub const fn mirrored(self, horizontal: bool, vertical: bool) -> DpadDirection {
+		let mut dir = self as u8;
+		if let DpadDirection::None = self {
+			return self;
+		}
+		if horizontal {
+			dir = (8 - dir) % 8;
+		}
+		if vertical {
+			dir = (12 - dir) % 8;
+		}
+		DpadDirection::ALL[dir as usize]
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl TryFrom<u16> for DpadDirection {
+	type Error = DS4ValidationError;
+	#[inline]
+	fn try_from(raw: u16) -> Result<DpadDirection, DS4ValidationError> {
+		match DpadDirection::ALL.get(raw as usize) {
+			Some(&dir) => Ok(dir),
+			None => Err(DS4ValidationError::Dpad(raw as u8)),
+		}
+	}
+}
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl From<DpadDirection> for u16 {
+	#[inline]
+	fn from(dir: DpadDirection) -> u16 {
+		dir as u16
+	}
+}
+
+/// DualShock4 button flags above the dpad nibble of [`DS4Report::buttons`].
+///
+/// The dpad is a direction rather than flags, see [`DpadDirection`].
+///
+/// ```
+/// use vigem_client::DS4Buttons;
+/// let buttons = DS4Buttons { raw: DS4Buttons::CROSS | DS4Buttons::L1 };
+/// assert_eq!(buttons.raw, 0x0120);
+/// ```
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(transparent)]
+pub struct DS4Buttons {
+	pub raw: u16,
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl DS4Buttons {
+	/// Fingerprint of the byte layout, see [`WIRE_FORMAT_VERSION`](crate::WIRE_FORMAT_VERSION).
+	pub const LAYOUT_FINGERPRINT: u64 = crate::layout_fingerprint!(DS4Buttons { raw });
+
+	/// Square button.
+	pub const SQUARE: u16   = 0x0010;
+	/// Cross button.
+	pub const CROSS: u16    = 0x0020;
+	/// Circle button.
+	pub const CIRCLE: u16   = 0x0040;
+	/// Triangle button.
+	pub const TRIANGLE: u16 = 0x0080;
+	/// Left shoulder button.
+	pub const L1: u16       = 0x0100;
+	/// Right shoulder button.
+	pub const R1: u16       = 0x0200;
+	/// Left trigger button.
+	pub const L2: u16       = 0x0400;
+	/// Right trigger button.
+	pub const R2: u16       = 0x0800;
+	/// Share button.
+	pub const SHARE: u16    = 0x1000;
+	/// Options button.
+	pub const OPTIONS: u16  = 0x2000;
+	/// Left thumb button.
+	pub const L3: u16       = 0x4000;
+	/// Right thumb button.
+	pub const R3: u16       = 0x8000;
+
+	/// Creates the button flags, the dpad nibble is cleared.
+	#[inline]
+	pub const fn new(raw: u16) -> DS4Buttons {
+		DS4Buttons { raw: raw & !0xF }
+	}
+
+	/// Creates the button word from its raw value, keeping every bit.
+	///
+	/// Nothing is masked or validated, for bits the crate does not interpret yet. You own the consequences.
+	#[inline]
+	pub const fn from_raw(raw: u16) -> DS4Buttons {
+		DS4Buttons { raw }
+	}
+
+	/// Returns the raw button word, including the dpad nibble.
+	#[inline]
+	pub const fn raw(&self) -> u16 {
+		self.raw
+	}
+}
+
+/// Prints the raw button word, a dpad nibble not holding a direction is annotated as `unknown`.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl fmt::Debug for DS4Buttons {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut f = f.debug_struct("DS4Buttons");
+		f.field("raw", &format_args!("{:#06X}", self.raw));
+		if self.dpad().is_none() {
+			f.field("unknown", &format_args!("{:#X}", self.raw & 0xF));
+		}
+		f.finish()
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+impl From<u16> for DS4Buttons {
+	#[inline]
+	fn from(raw: u16) -> Self {
+		DS4Buttons::new(raw)
+	}
+}
+#[cfg(feature = "unstable_ds4")]
+impl From<DS4Buttons> for u16 {
+	#[inline]
+	fn from(buttons: DS4Buttons) -> Self {
+		buttons.raw
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl DS4Buttons {
+	/// Returns the buttons with the dpad direction for formatting, see [`dpad`](Self::dpad).
+	#[inline]
+	pub const fn with_dpad(self, direction: DpadDirection) -> DS4Buttons {
+		let nibble = match direction {
+			DpadDirection::None => 0,
+			_ => direction as u16 + 1,
+		};
+		DS4Buttons { raw: self.raw & !0xF | nibble }
+	}
+
+	/// Returns the dpad direction carried for formatting and parsing.
+	///
+	/// The dpad is not part of the button flags: the low nibble is ignored by [`DS4Report::with_buttons`] and cleared by [`new`](Self::new).
+	/// The [`Display`](fmt::Display) and [`FromStr`](str::FromStr) implementations carry a `DPAD_*` token in it instead,
+	/// as the direction plus one with zero for [`DpadDirection::None`]. Apply it with [`DS4Report::with_dpad`].
+	/// Returns `None` if the nibble holds no direction.
+	///
+	/// ```
+	/// use vigem_client::{DpadDirection, DS4Buttons, DS4Report};
+	///
+	/// let buttons: DS4Buttons = "CROSS|R1|DPAD_SOUTH".parse().unwrap();
+	/// let report = DS4Report::pressing(buttons).with_dpad(buttons.dpad().unwrap());
+	/// assert_eq!(report.dpad(), Ok(DpadDirection::South));
+	/// assert_eq!(buttons.to_string(), "CROSS|R1|DPAD_SOUTH");
+	/// ```
+	#[inline]
+	pub const fn dpad(&self) -> Option<DpadDirection> {
+		match self.raw & 0xF {
+			0 => Some(DpadDirection::None),
+			nibble @ 1..=8 => Some(DpadDirection::ALL[nibble as usize - 1]),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+const DS4_BUTTON_NAMES: [&str; 12] = [
+	"SQUARE", "CROSS", "CIRCLE", "TRIANGLE",
+	"L1", "R1", "L2", "R2",
+	"SHARE", "OPTIONS", "L3", "R3",
+];
+
+#[cfg(feature = "unstable_ds4")]
+const DS4_DPAD_NAMES: [&str; 9] = [
+	"DPAD_NORTH", "DPAD_NORTHEAST", "DPAD_EAST", "DPAD_SOUTHEAST",
+	"DPAD_SOUTH", "DPAD_SOUTHWEST", "DPAD_WEST", "DPAD_NORTHWEST",
+	"DPAD_NONE",
+];
+
+#[cfg(feature = "unstable_ds4")]
+const DS4_BUTTON_VOCABULARY: [&str; 21] = [
+	"SQUARE", "CROSS", "CIRCLE", "TRIANGLE",
+	"L1", "R1", "L2", "R2",
+	"SHARE", "OPTIONS", "L3", "R3",
+	"DPAD_NORTH", "DPAD_NORTHEAST", "DPAD_EAST", "DPAD_SOUTHEAST",
+	"DPAD_SOUTH", "DPAD_SOUTHWEST", "DPAD_WEST", "DPAD_NORTHWEST",
+	"DPAD_NONE",
+];
+
+// Splits the `|` separated tokens, an empty string has no tokens
+#[cfg(feature = "unstable_ds4")]
+fn tokens(s: &str) -> impl Iterator<Item = &str> {
+	let s = s.trim();
+	s.split('|').map(str::trim).filter(move |_| !s.is_empty())
+}
+
+// Finds the token in the names ignoring case
+#[cfg(feature = "unstable_ds4")]
+fn find_token(names: &[&str], token: &str) -> Option<usize> {
+	names.iter().position(|name| name.eq_ignore_ascii_case(token))
+}
+
+/// Formats the pressed buttons as `|` separated names followed by the dpad, eg. `CROSS|R1|DPAD_SOUTH`.
+///
+/// The released dpad is implied and not printed, nothing pressed formats as an empty string.
+/// A dpad nibble not holding a direction is printed as its raw value, eg. `0xB`.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl fmt::Display for DS4Buttons {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut sep = "";
+		for (index, name) in DS4_BUTTON_NAMES.iter().enumerate() {
+			if self.raw & (0x10 << index) != 0 {
+				f.write_str(sep)?;
+				f.write_str(name)?;
+				sep = "|";
+			}
+		}
+		match self.dpad() {
+			Some(DpadDirection::None) => Ok(()),
+			Some(direction) => write!(f, "{}{}", sep, DS4_DPAD_NAMES[direction as usize]),
+			None => write!(f, "{}{:#X}", sep, self.raw & 0xF),
+		}
+	}
+}
+
+/// Parses `|` separated button names and at most one `DPAD_*` direction, ignoring case and whitespace around the names.
+///
+/// Accepts the names printed by [`Display`](fmt::Display) as well as `DPAD_NONE` and raw values like `0xB` for the dpad nibble.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl str::FromStr for DS4Buttons {
+	type Err = DS4ButtonsParseError;
+	fn from_str(s: &str) -> Result<DS4Buttons, DS4ButtonsParseError> {
+		let mut buttons = DS4Buttons::default();
+		let mut dpad = None;
+		for token in tokens(s) {
+			if let Some(index) = find_token(&DS4_BUTTON_NAMES, token) {
+				buttons.raw |= 0x10 << index;
+				continue;
+			}
+			let nibble = match find_token(&DS4_DPAD_NAMES, token) {
+				Some(index) => buttons.with_dpad(DpadDirection::ALL[index]).raw & 0xF,
+				None => match parse_nibble(token) {
+					Some(nibble) => nibble,
+					None => return Err(DS4ButtonsParseError::UnknownToken { token: token.into(), expected: &DS4_BUTTON_VOCABULARY }),
+				},
+			};
+			if dpad.replace(nibble).is_some() {
+				return Err(DS4ButtonsParseError::MultipleDpad { token: token.into() });
+			}
+			buttons.raw |= nibble;
+		}
+		Ok(buttons)
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+fn parse_nibble(token: &str) -> Option<u16> {
+	let hex = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X"))?;
+	u16::from_str_radix(hex, 16).ok().filter(|&nibble| nibble <= 0xF)
+}
+
+/// DualShock4 special button flags of [`DS4Report::special`].
+///
+/// ```
+/// use vigem_client::{DS4Report, DS4SpecialButtons};
+///
+/// let special: DS4SpecialButtons = "PS_HOME|TOUCHPAD".parse().unwrap();
+/// assert_eq!(special.raw, DS4SpecialButtons::PS | DS4SpecialButtons::TOUCHPAD);
+/// assert_eq!(DS4Report::neutral().with_special(special).special, 0x3);
+/// ```
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(transparent)]
+pub struct DS4SpecialButtons {
+	pub raw: u8,
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl DS4SpecialButtons {
+	/// Fingerprint of the byte layout, see [`WIRE_FORMAT_VERSION`](crate::WIRE_FORMAT_VERSION).
+	pub const LAYOUT_FINGERPRINT: u64 = crate::layout_fingerprint!(DS4SpecialButtons { raw });
+
+	/// PS button.
+	pub const PS: u8       = 0x01;
+	/// Touchpad click.
+	pub const TOUCHPAD: u8 = 0x02;
+
+	/// Creates the button flags, the report counter in the upper bits is cleared.
+	#[inline]
+	pub const fn new(raw: u8) -> DS4SpecialButtons {
+		DS4SpecialButtons { raw: raw & 0x3 }
+	}
+
+	/// Creates the special byte from its raw value, keeping every bit.
+	///
+	/// Nothing is masked or validated, for bits the crate does not interpret yet. You own the consequences.
+	#[inline]
+	pub const fn from_raw(raw: u8) -> DS4SpecialButtons {
+		DS4SpecialButtons { raw }
+	}
+
+	/// Returns the raw special byte.
+	#[inline]
+	pub const fn raw(&self) -> u8 {
+		self.raw
+	}
+}
+
+/// Prints the raw special byte, bits other than the known buttons are annotated as `unknown`.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl fmt::Debug for DS4SpecialButtons {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut f = f.debug_struct("DS4SpecialButtons");
+		f.field("raw", &format_args!("{:#04X}", self.raw));
+		if self.raw & !0x3 != 0 {
+			f.field("unknown", &format_args!("{:#04X}", self.raw & !0x3));
+		}
+		f.finish()
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+impl From<u8> for DS4SpecialButtons {
+	#[inline]
+	fn from(raw: u8) -> Self {
+		DS4SpecialButtons::new(raw)
+	}
+}
+#[cfg(feature = "unstable_ds4")]
+impl From<DS4SpecialButtons> for u8 {
+	#[inline]
+	fn from(buttons: DS4SpecialButtons) -> Self {
+		buttons.raw
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+const DS4_SPECIAL_NAMES: [&str; 2] = ["PS_HOME", "TOUCHPAD"];
+
+/// Formats the pressed buttons as `|` separated names, eg. `PS_HOME|TOUCHPAD`.
+///
+/// Nothing pressed formats as an empty string, other bits are printed as their raw value, eg. `0x4`.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl fmt::Display for DS4SpecialButtons {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut sep = "";
+		for (index, name) in DS4_SPECIAL_NAMES.iter().enumerate() {
+			if self.raw & (1 << index) != 0 {
+				f.write_str(sep)?;
+				f.write_str(name)?;
+				sep = "|";
+			}
+		}
+		if self.raw & !0x3 != 0 {
+			write!(f, "{}{:#X}", sep, self.raw & !0x3)?;
+		}
+		Ok(())
+	}
+}
+
+/// Parses `|` separated button names, ignoring case and whitespace around the names.
+///
+/// Accepts the names printed by [`Display`](fmt::Display) and raw values like `0x4`.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl str::FromStr for DS4SpecialButtons {
+	type Err = DS4ButtonsParseError;
+	fn from_str(s: &str) -> Result<DS4SpecialButtons, DS4ButtonsParseError> {
+		let mut buttons = DS4SpecialButtons::default();
+		for token in tokens(s) {
+			if let Some(index) = find_token(&DS4_SPECIAL_NAMES, token) {
+				buttons.raw |= 1 << index;
+				continue;
+			}
+			let hex = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X"));
+			match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+				Some(raw) => buttons.raw |= raw,
+				None => return Err(DS4ButtonsParseError::UnknownToken { token: token.into(), expected: &DS4_SPECIAL_NAMES }),
+			}
+		}
+		Ok(buttons)
+	}
+}
+
+/// DualShock4 button parse errors.
+///
+/// See the [`FromStr`](str::FromStr) implementations of [`DS4Buttons`] and [`DS4SpecialButtons`].
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum DS4ButtonsParseError {
+	/// The token is not a button name, with the accepted names.
+	UnknownToken { token: String, expected: &'static [&'static str] },
+	/// More than one dpad direction, with the second direction.
+	MultipleDpad { token: String },
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl fmt::Display for DS4ButtonsParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			DS4ButtonsParseError::UnknownToken { token, expected } => write!(f, "unknown button {:?}, expected one of: {}", token, expected.join(", ")),
+			DS4ButtonsParseError::MultipleDpad { token } => write!(f, "more than one dpad direction: {:?}", token),
+		}
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl std::error::Error for DS4ButtonsParseError {}
+
+/// DualShock4 HID Input report.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct DS4Report {
 	pub thumb_lx: u8,
@@ -17,70 +527,1779 @@ pub struct DS4Report {
 	pub trigger_r: u8,
 }
 #[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
 impl Default for DS4Report {
 	#[inline]
-	fn default() -> Self {
-		DS4Report {
-			thumb_lx: 0x80,
-			thumb_ly: 0x80,
-			thumb_rx: 0x80,
-			thumb_ry: 0x80,
-			buttons: 0x8,
-			special: 0,
-			trigger_l: 0,
-			trigger_r: 0,
+	fn default() -> Self {
+		DS4Report::neutral()
+	}
+}
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl DS4Report {
+	/// Fingerprint of the byte layout, see [`WIRE_FORMAT_VERSION`](crate::WIRE_FORMAT_VERSION).
+	pub const LAYOUT_FINGERPRINT: u64 = crate::layout_fingerprint!(DS4Report { thumb_lx, thumb_ly, thumb_rx, thumb_ry, buttons, special, trigger_l, trigger_r });
+
+	/// The neutral report: nothing pressed, the dpad released and the thumbsticks centered.
+	#[inline]
+	pub const fn neutral() -> DS4Report {
+		DS4Report {
+			thumb_lx: 0x80,
+			thumb_ly: 0x80,
+			thumb_rx: 0x80,
+			thumb_ry: 0x80,
+			buttons: 0x8,
+			special: 0,
+			trigger_l: 0,
+			trigger_r: 0,
+		}
+	}
+
+	/// Cross pressed, nothing else.
+	pub const PRESS_CROSS: DS4Report = DS4Report::pressing(DS4Buttons { raw: DS4Buttons::CROSS });
+	/// Circle pressed, nothing else.
+	pub const PRESS_CIRCLE: DS4Report = DS4Report::pressing(DS4Buttons { raw: DS4Buttons::CIRCLE });
+	/// Square pressed, nothing else.
+	pub const PRESS_SQUARE: DS4Report = DS4Report::pressing(DS4Buttons { raw: DS4Buttons::SQUARE });
+	/// Triangle pressed, nothing else.
+	pub const PRESS_TRIANGLE: DS4Report = DS4Report::pressing(DS4Buttons { raw: DS4Buttons::TRIANGLE });
+	/// Options pressed, nothing else.
+	pub const PRESS_OPTIONS: DS4Report = DS4Report::pressing(DS4Buttons { raw: DS4Buttons::OPTIONS });
+	/// Share pressed, nothing else.
+	pub const PRESS_SHARE: DS4Report = DS4Report::pressing(DS4Buttons { raw: DS4Buttons::SHARE });
+	/// L1 pressed, nothing else.
+	pub const PRESS_L1: DS4Report = DS4Report::pressing(DS4Buttons { raw: DS4Buttons::L1 });
+	/// R1 pressed, nothing else.
+	pub const PRESS_R1: DS4Report = DS4Report::pressing(DS4Buttons { raw: DS4Buttons::R1 });
+	/// Dpad up pressed, nothing else.
+	pub const DPAD_UP: DS4Report = DS4Report::neutral().with_dpad(DpadDirection::North);
+	/// Dpad down pressed, nothing else.
+	pub const DPAD_DOWN: DS4Report = DS4Report::neutral().with_dpad(DpadDirection::South);
+	/// Dpad left pressed, nothing else.
+	pub const DPAD_LEFT: DS4Report = DS4Report::neutral().with_dpad(DpadDirection::West);
+	/// Dpad right pressed, nothing else.
+	pub const DPAD_RIGHT: DS4Report = DS4Report::neutral().with_dpad(DpadDirection::East);
+
+	/// The neutral report with the buttons pressed.
+	///
+	/// ```
+	/// use vigem_client::{DS4Buttons, DS4Report};
+	/// let report = DS4Report::pressing(DS4Buttons { raw: DS4Buttons::CROSS | DS4Buttons::R1 });
+	/// assert_eq!(report.compact().to_string(), "Cross+R1 | LT:0 RT:0 | L(128,128) R(128,128)");
+	/// ```
+	#[inline]
+	pub const fn pressing(buttons: DS4Buttons) -> DS4Report {
+		DS4Report::neutral().with_buttons(buttons)
+	}
+
+	/// Returns if the report is neutral up to the tolerance, ie. the user is not touching the controller.
+	///
+	/// No buttons may be pressed and the dpad must be released, the triggers may be up to `tolerance` and the thumbsticks up to `tolerance` away from center.
+	/// Thumbsticks fed from a physical pad jitter around center, a tolerance of 1 or 2 absorbs that.
+	/// The report counter in the upper bits of the special byte is not user input and is ignored.
+	///
+	/// ```
+	/// let report = vigem_client::DS4Report { thumb_lx: 0x7f, thumb_ry: 0x81, ..Default::default() };
+	/// assert!(report.is_neutral(1));
+	/// assert!(!report.is_neutral(0));
+	/// ```
+	#[inline]
+	pub const fn is_neutral(&self, tolerance: u8) -> bool {
+		self.buttons == DS4Report::neutral().buttons
+			&& self.special & 0x3 == 0
+			&& self.trigger_l <= tolerance
+			&& self.trigger_r <= tolerance
+			&& self.thumb_lx.abs_diff(0x80) <= tolerance
+			&& self.thumb_ly.abs_diff(0x80) <= tolerance
+			&& self.thumb_rx.abs_diff(0x80) <= tolerance
+			&& self.thumb_ry.abs_diff(0x80) <= tolerance
+	}
+
+	/// Returns the report with the buttons pressed in addition to those already pressed.
+	///
+	/// The dpad is not affected.
+	#[inline]
+	pub const fn with_buttons(self, buttons: DS4Buttons) -> DS4Report {
+		DS4Report { buttons: self.buttons | buttons.raw & !0xF, ..self }
+	}
+
+	/// Returns the report with the dpad direction, keeping the other buttons.
+	#[inline]
+	pub const fn with_dpad(self, direction: DpadDirection) -> DS4Report {
+		DS4Report { buttons: self.buttons & !0xF | direction as u16, ..self }
+	}
+
+	/// Returns the report with the special buttons pressed in addition to those already pressed.
+	///
+	/// The report counter is not affected.
+	#[inline]
+	pub const fn with_special(self, special: DS4SpecialButtons) -> DS4Report {
+		DS4Report { special: self.special | special.raw & 0x3, ..self }
+	}
+
+	/// Returns the report with the button word replaced by the raw value, including the dpad nibble.
+	///
+	/// Unlike [`with_buttons`](Self::with_buttons) nothing is masked or validated, for bits the crate does not interpret yet.
+	/// You own the consequences, eg. a dpad nibble above 8 fails [`validate`](Self::validate).
+	#[inline]
+	pub const fn buttons_raw(self, buttons: u16) -> DS4Report {
+		DS4Report { buttons, ..self }
+	}
+
+	/// Returns the report with the special byte replaced by the raw value, including the report counter bits.
+	///
+	/// Unlike [`with_special`](Self::with_special) nothing is masked, for bits the crate does not interpret yet.
+	/// You own the consequences.
+	#[inline]
+	pub const fn special_raw(self, special: u8) -> DS4Report {
+		DS4Report { special, ..self }
+	}
+
+	/// Returns the report with the left trigger value and the L2 button in sync, see [`Trigger::to_ds4`].
+	#[inline]
+	pub fn left_trigger_t(self, trigger: Trigger) -> DS4Report {
+		let (trigger_l, pressed) = trigger.to_ds4();
+		let buttons = if pressed { self.buttons | DS4Buttons::L2 } else { self.buttons & !DS4Buttons::L2 };
+		DS4Report { trigger_l, buttons, ..self }
+	}
+
+	/// Returns the report with the right trigger value and the R2 button in sync, see [`Trigger::to_ds4`].
+	#[inline]
+	pub fn right_trigger_t(self, trigger: Trigger) -> DS4Report {
+		let (trigger_r, pressed) = trigger.to_ds4();
+		let buttons = if pressed { self.buttons | DS4Buttons::R2 } else { self.buttons & !DS4Buttons::R2 };
+		DS4Report { trigger_r, buttons, ..self }
+	}
+
+	/// Parses an input report captured from a physical DualShock4, eg. with hidapi.
+	///
+	/// Accepts the USB input report `0x01` (64 bytes), the short Bluetooth input report `0x01` (10 bytes)
+	/// and the full Bluetooth input report `0x11` (78 bytes), including the leading report id byte.
+	/// The layout is detected from the report id and length, the CRC tail of Bluetooth reports is not checked.
+	///
+	/// The report counter in the upper bits of the special byte is dropped.
+	/// Motion sensors and touch data are not part of this report and are ignored.
+	#[inline]
+	pub fn from_usb_input_report(data: &[u8]) -> Result<DS4Report, DS4ParseError> {
+		let offset = match (data.first(), data.len()) {
+			(None, _) => return Err(DS4ParseError::Length(0)),
+			(Some(0x01), 10..) => 1,
+			(Some(0x11), 78..) => 3,
+			(Some(0x01 | 0x11), len) => return Err(DS4ParseError::Length(len)),
+			(Some(&id), _) => return Err(DS4ParseError::ReportId(id)),
+		};
+		let data = &data[offset..];
+		Ok(DS4Report {
+			thumb_lx: data[0],
+			thumb_ly: data[1],
+			thumb_rx: data[2],
+			thumb_ry: data[3],
+			buttons: u16::from_le_bytes([data[4], data[5]]),
+			special: data[6] & 0x3,
+			trigger_l: data[7],
+			trigger_r: data[8],
+		})
+	}
+
+	/// Returns a compact single line formatter for overlays and logs.
+	///
+	/// The format is stable: the dpad as an arrow glyph and the pressed buttons joined by `+` (or `-` if none),
+	/// followed by the triggers and the thumbsticks as `x,y` pairs, separated by ` | `.
+	/// An invalid dpad is shown as `?`.
+	///
+	/// ```
+	/// let report = vigem_client::DS4Report {
+	/// 	buttons: 0x0021,
+	/// 	trigger_r: 255,
+	/// 	thumb_lx: 0,
+	/// 	..Default::default()
+	/// };
+	/// assert_eq!(report.compact().to_string(), "↗+Cross | LT:0 RT:255 | L(0,128) R(128,128)");
+	/// ```
+	#[inline]
+	pub fn compact(&self) -> CompactDS4Report<'_> {
+		CompactDS4Report(self)
+	}
+
+	/// Decodes the report from its `repr(C)` byte layout.
+	///
+	/// The layout is the field order of this struct, multi-byte fields are little-endian.
+	/// The last byte is padding and is ignored.
+	#[inline]
+	pub const fn from_bytes(bytes: &[u8; mem::size_of::<DS4Report>()]) -> DS4Report {
+		DS4Report {
+			thumb_lx: bytes[0],
+			thumb_ly: bytes[1],
+			thumb_rx: bytes[2],
+			thumb_ry: bytes[3],
+			buttons: u16::from_le_bytes([bytes[4], bytes[5]]),
+			special: bytes[6],
+			trigger_l: bytes[7],
+			trigger_r: bytes[8],
+		}
+	}
+
+	/// Encodes the report in its `repr(C)` byte layout, the padding byte is zero.
+	///
+	/// See [`from_bytes`](Self::from_bytes) for the layout.
+	#[inline]
+	pub const fn to_bytes(&self) -> [u8; mem::size_of::<DS4Report>()] {
+		let buttons = self.buttons.to_le_bytes();
+		[
+			self.thumb_lx, self.thumb_ly, self.thumb_rx, self.thumb_ry,
+			buttons[0], buttons[1], self.special, self.trigger_l, self.trigger_r, 0,
+		]
+	}
+
+	/// Checks the report for states a real DualShock4 can never produce.
+	///
+	/// ```
+	/// use vigem_client::{DS4Report, DS4ValidationError};
+	///
+	/// let mut report = DS4Report::default();
+	/// assert_eq!(report.validate(), Ok(()));
+	///
+	/// report.buttons = 0xB;
+	/// assert_eq!(report.validate(), Err(DS4ValidationError::Dpad(0xB)));
+	/// ```
+	#[inline]
+	pub fn validate(&self) -> Result<(), DS4ValidationError> {
+		// The low nibble encodes the dpad direction: 0..=7 clockwise from north, 8 is neutral
+		let dpad = (self.buttons & 0xF) as u8;
+		if dpad > 8 {
+			return Err(DS4ValidationError::Dpad(dpad));
+		}
+		Ok(())
+	}
+
+	/// Returns the raw dpad nibble of the buttons.
+	///
+	/// Unlike [`dpad`](Self::dpad) this returns out of range values, eg. to detect corrupted reports.
+	#[inline]
+	pub const fn dpad_raw(&self) -> u16 {
+		self.buttons & 0xF
+	}
+
+	/// Returns the dpad direction.
+	///
+	/// ```
+	/// use vigem_client::{DpadDirection, DS4Report, DS4ValidationError};
+	///
+	/// let mut report = DS4Report::default();
+	/// report.set_dpad(DpadDirection::SouthWest);
+	/// assert_eq!(report.dpad(), Ok(DpadDirection::SouthWest));
+	///
+	/// report.buttons |= 0xF;
+	/// assert_eq!(report.dpad(), Err(DS4ValidationError::Dpad(0xF)));
+	/// ```
+	#[inline]
+	pub fn dpad(&self) -> Result<DpadDirection, DS4ValidationError> {
+		DpadDirection::try_from(self.dpad_raw())
+	}
+
+	/// Sets the dpad direction keeping the other buttons.
+	#[inline]
+	pub fn set_dpad(&mut self, direction: DpadDirection) {
+		self.buttons = self.buttons & !0xF | direction as u16;
+	}
+}
+
+/// Decodes the dpad and splits the special byte into its button flags and the report counter in the upper bits.
+///
+/// The output is stable, use [`compact`](DS4Report::compact) for a shorter single line format.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl fmt::Debug for DS4Report {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		struct Dpad(u16);
+		impl fmt::Debug for Dpad {
+			fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+				match DpadDirection::try_from(self.0) {
+					Ok(direction) => direction.fmt(f),
+					Err(_) => write!(f, "Invalid({:#X})", self.0),
+				}
+			}
+		}
+		f.debug_struct("DS4Report")
+			.field("thumb_lx", &self.thumb_lx)
+			.field("thumb_ly", &self.thumb_ly)
+			.field("thumb_rx", &self.thumb_rx)
+			.field("thumb_ry", &self.thumb_ry)
+			.field("buttons", &format_args!("{:#06X}", self.buttons))
+			.field("dpad", &Dpad(self.dpad_raw()))
+			.field("special", &format_args!("{:#04X}", self.special & 0x3))
+			.field("counter", &(self.special >> 2))
+			.field("trigger_l", &self.trigger_l)
+			.field("trigger_r", &self.trigger_r)
+			.finish()
+	}
+}
+
+/// DualShock4 input report parse errors.
+///
+/// See [`DS4Report::from_usb_input_report`] and [`DS4ReportEx::from_usb_input_report`].
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum DS4ParseError {
+	/// The report id is not a supported input report.
+	ReportId(u8),
+	/// The report is too short for its report id.
+	Length(usize),
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl fmt::Display for DS4ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			DS4ParseError::ReportId(id) => write!(f, "unsupported report id: {:#x}", id),
+			DS4ParseError::Length(len) => write!(f, "report too short: {} bytes", len),
+		}
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl std::error::Error for DS4ParseError {}
+
+#[cfg(feature = "unstable_ds4")]
+mod sealed {
+	pub trait Sealed {}
+}
+
+/// Reports which can be submitted to a [`DualShock4Wired`] target, see [`DualShock4Wired::submit`].
+///
+/// This trait is sealed and implemented for [`DS4Report`] and [`DS4ReportEx`].
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+pub trait Ds4Submittable: sealed::Sealed {
+	#[doc(hidden)]
+	fn check(&self) -> Result<(), DS4ValidationError>;
+	#[doc(hidden)]
+	fn merge(&self, shadow: &mut DS4ReportEx);
+}
+
+#[cfg(feature = "unstable_ds4")]
+impl sealed::Sealed for DS4Report {}
+#[cfg(feature = "unstable_ds4")]
+impl Ds4Submittable for DS4Report {
+	#[inline]
+	fn check(&self) -> Result<(), DS4ValidationError> {
+		self.validate()
+	}
+	#[inline]
+	fn merge(&self, shadow: &mut DS4ReportEx) {
+		shadow.set_report(self);
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+impl sealed::Sealed for DS4ReportEx {}
+#[cfg(feature = "unstable_ds4")]
+impl Ds4Submittable for DS4ReportEx {
+	#[inline]
+	fn check(&self) -> Result<(), DS4ValidationError> {
+		self.validate()
+	}
+	#[inline]
+	fn merge(&self, shadow: &mut DS4ReportEx) {
+		*shadow = *self;
+	}
+}
+
+/// DualShock4 report validation errors.
+///
+/// Each variant names the offending field and carries its out of range value.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum DS4ValidationError {
+	/// The dpad nibble of the buttons field is not a direction.
+	///
+	/// Valid values are 0 to 7 for the directions clockwise starting at north and 8 for neutral.
+	Dpad(u8),
+	/// The battery level nibble of the extended report is above [`BatteryStatus::LEVEL_FULL`].
+	Battery(u8),
+	/// The extended report claims more than three touch packets.
+	TouchPackets(u8),
+	/// A touching contact lies beyond [`DS4TouchReport::WIDTH`].
+	TouchX(u16),
+	/// A touching contact lies beyond [`DS4TouchReport::HEIGHT`].
+	TouchY(u16),
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl fmt::Display for DS4ValidationError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			DS4ValidationError::Dpad(value) => write!(f, "dpad out of range: {:#x}", value),
+			DS4ValidationError::Battery(level) => write!(f, "battery level out of range: {}", level),
+			DS4ValidationError::TouchPackets(count) => write!(f, "touch packet count out of range: {}", count),
+			DS4ValidationError::TouchX(x) => write!(f, "touch x out of range: {}", x),
+			DS4ValidationError::TouchY(y) => write!(f, "touch y out of range: {}", y),
+		}
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl std::error::Error for DS4ValidationError {}
+
+/// Compact formatter of a [`DS4Report`], see [`DS4Report::compact`].
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Debug)]
+pub struct CompactDS4Report<'a>(&'a DS4Report);
+
+#[cfg(feature = "unstable_ds4")]
+impl fmt::Display for CompactDS4Report<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		const DPAD: [&str; 9] = ["↑", "↗", "→", "↘", "↓", "↙", "←", "↖", ""];
+		const BUTTONS: [&str; 12] = [
+			"Square", "Cross", "Circle", "Triangle",
+			"L1", "R1", "L2", "R2",
+			"Share", "Options", "L3", "R3",
+		];
+		const SPECIAL: [&str; 2] = ["PS", "Touchpad"];
+
+		let report = self.0;
+		let mut plus = false;
+		let mut write = |f: &mut fmt::Formatter, name: &str| {
+			if plus {
+				f.write_str("+")?;
+			}
+			plus = true;
+			f.write_str(name)
+		};
+
+		match DPAD.get((report.buttons & 0xF) as usize) {
+			Some(&"") => (),
+			Some(glyph) => write(f, glyph)?,
+			None => write(f, "?")?,
+		}
+		for (index, name) in BUTTONS.iter().enumerate() {
+			if report.buttons & (0x10 << index) != 0 {
+				write(f, name)?;
+			}
+		}
+		for (index, name) in SPECIAL.iter().enumerate() {
+			if report.special & (1 << index) != 0 {
+				write(f, name)?;
+			}
+		}
+		if !plus {
+			f.write_str("-")?;
+		}
+		write!(f, " | LT:{} RT:{} | L({},{}) R({},{})",
+			report.trigger_l, report.trigger_r,
+			report.thumb_lx, report.thumb_ly, report.thumb_rx, report.thumb_ry)
+	}
+}
+
+/// Timestamps touch packets across frames.
+///
+/// Every touch packet carries an 8-bit timestamp which increments modulo 256 with every packet sent.
+/// This counter is independent of the timestamp of the input report itself, which counts time rather than packets.
+///
+/// A frame reports the most recent touch packet followed by the previous packets, most recent first.
+///
+/// ```
+/// let mut timestamper = vigem_client::DS4TouchTimestamper::new(254);
+/// assert_eq!(timestamper.next(), 254);
+/// assert_eq!(timestamper.next(), 255);
+/// assert_eq!(timestamper.next(), 0);
+/// assert_eq!(timestamper.frame(), [0, 255, 254]);
+/// ```
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct DS4TouchTimestamper {
+	next: u8,
+	history: [u8; 3],
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl Default for DS4TouchTimestamper {
+	#[inline]
+	fn default() -> Self {
+		DS4TouchTimestamper::new(0)
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl DS4TouchTimestamper {
+	/// Creates a new timestamper, the first packet is stamped with `start`.
+	#[inline]
+	pub const fn new(start: u8) -> DS4TouchTimestamper {
+		let prev = start.wrapping_sub(1);
+		DS4TouchTimestamper { next: start, history: [prev, prev.wrapping_sub(1), prev.wrapping_sub(2)] }
+	}
+
+	/// Returns the timestamp for the next touch packet.
+	#[allow(clippy::should_implement_trait)]
+	#[inline]
+	pub fn next(&mut self) -> u8 {
+		let stamp = self.next;
+		self.next = stamp.wrapping_add(1);
+		self.history = [stamp, self.history[0], self.history[1]];
+		stamp
+	}
+
+	/// Returns the timestamps of the last three touch packets, most recent first.
+	#[inline]
+	pub const fn frame(&self) -> [u8; 3] {
+		self.history
+	}
+}
+
+/// A touch contact on the DualShock4 touchpad.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct DS4TouchPoint {
+	/// Contact id, 7 bits which increment with every new touch.
+	pub id: u8,
+	/// Horizontal position, from 0 to [`DS4TouchReport::WIDTH`] exclusive.
+	pub x: u16,
+	/// Vertical position, from 0 to [`DS4TouchReport::HEIGHT`] exclusive.
+	pub y: u16,
+}
+
+/// DualShock4 touch packet with up to two contacts.
+///
+/// Uses the layout of the touch packets in the extended input report.
+///
+/// ```
+/// use vigem_client::{DS4TouchPoint, DS4TouchReport};
+///
+/// let point = DS4TouchPoint { id: 5, x: 1000, y: 500 };
+/// let report = DS4TouchReport::new(7, Some(point), None);
+/// assert_eq!(report.point1(), Some(point));
+/// assert_eq!(report.point2(), None);
+/// ```
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[repr(C)]
+pub struct DS4TouchReport {
+	/// Timestamp of the packet, see [`DS4TouchTimestamper`].
+	pub packet_counter: u8,
+	/// Contact id of the first contact, the high bit is set when not touching.
+	pub is_up_tracking_num1: u8,
+	/// Packed 12-bit coordinates of the first contact.
+	pub touch_data1: [u8; 3],
+	/// Contact id of the second contact, the high bit is set when not touching.
+	pub is_up_tracking_num2: u8,
+	/// Packed 12-bit coordinates of the second contact.
+	pub touch_data2: [u8; 3],
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl DS4TouchReport {
+	/// Fingerprint of the byte layout, see [`WIRE_FORMAT_VERSION`](crate::WIRE_FORMAT_VERSION).
+	pub const LAYOUT_FINGERPRINT: u64 = crate::layout_fingerprint!(DS4TouchReport { packet_counter, is_up_tracking_num1, touch_data1, is_up_tracking_num2, touch_data2 });
+
+	/// Width of the touchpad coordinate space.
+	pub const WIDTH: u16 = 1920;
+	/// Height of the touchpad coordinate space.
+	pub const HEIGHT: u16 = 942;
+
+	/// Creates a touch packet, `None` contacts are reported as not touching.
+	#[inline]
+	pub const fn new(packet_counter: u8, point1: Option<DS4TouchPoint>, point2: Option<DS4TouchPoint>) -> DS4TouchReport {
+		let (is_up_tracking_num1, touch_data1) = encode_touch(point1);
+		let (is_up_tracking_num2, touch_data2) = encode_touch(point2);
+		DS4TouchReport { packet_counter, is_up_tracking_num1, touch_data1, is_up_tracking_num2, touch_data2 }
+	}
+
+	/// Returns the first contact if touching.
+	#[inline]
+	pub const fn point1(&self) -> Option<DS4TouchPoint> {
+		decode_touch(self.is_up_tracking_num1, self.touch_data1)
+	}
+
+	/// Returns the second contact if touching.
+	#[inline]
+	pub const fn point2(&self) -> Option<DS4TouchPoint> {
+		decode_touch(self.is_up_tracking_num2, self.touch_data2)
+	}
+
+	/// Decodes the packet from its `repr(C)` byte layout, the field order of this struct.
+	#[inline]
+	pub const fn from_bytes(bytes: &[u8; mem::size_of::<DS4TouchReport>()]) -> DS4TouchReport {
+		DS4TouchReport {
+			packet_counter: bytes[0],
+			is_up_tracking_num1: bytes[1],
+			touch_data1: [bytes[2], bytes[3], bytes[4]],
+			is_up_tracking_num2: bytes[5],
+			touch_data2: [bytes[6], bytes[7], bytes[8]],
+		}
+	}
+
+	/// Encodes the packet in its `repr(C)` byte layout.
+	#[inline]
+	pub const fn to_bytes(&self) -> [u8; mem::size_of::<DS4TouchReport>()] {
+		[
+			self.packet_counter,
+			self.is_up_tracking_num1, self.touch_data1[0], self.touch_data1[1], self.touch_data1[2],
+			self.is_up_tracking_num2, self.touch_data2[0], self.touch_data2[1], self.touch_data2[2],
+		]
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+const fn encode_touch(point: Option<DS4TouchPoint>) -> (u8, [u8; 3]) {
+	match point {
+		Some(point) => (point.id & 0x7f, [
+			point.x as u8,
+			((point.x >> 8) & 0xf) as u8 | ((point.y & 0xf) << 4) as u8,
+			(point.y >> 4) as u8,
+		]),
+		None => (0x80, [0; 3]),
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+const fn decode_touch(is_up_tracking_num: u8, data: [u8; 3]) -> Option<DS4TouchPoint> {
+	if is_up_tracking_num & 0x80 != 0 {
+		return None;
+	}
+	Some(DS4TouchPoint {
+		id: is_up_tracking_num,
+		x: data[0] as u16 | ((data[1] as u16 & 0xf) << 8),
+		y: (data[1] as u16 >> 4) | ((data[2] as u16) << 4),
+	})
+}
+
+/// DualShock4 battery and cable status of [`DS4ReportEx::battery_lvl_special`].
+///
+/// The low nibble holds the battery level from 0 to 10 in steps of ten percent, 11 once charged up on the cable.
+/// Bit 4 is set while the cable is connected.
+///
+/// ```
+/// use vigem_client::BatteryStatus;
+///
+/// let status = BatteryStatus::from_raw(0x16);
+/// assert_eq!(status.level(), 6);
+/// assert_eq!(status.percent(), 60);
+/// assert!(status.is_cable() && status.is_charging());
+/// ```
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(transparent)]
+pub struct BatteryStatus {
+	pub raw: u8,
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl BatteryStatus {
+	/// Cable connected bit.
+	pub const CABLE: u8 = 0x10;
+	/// Battery level of a fully charged controller on the cable.
+	pub const LEVEL_FULL: u8 = 11;
+	/// A wired controller charged up on the cable, the status of [`DS4ReportEx::neutral`].
+	pub const WIRED_FULL: BatteryStatus = BatteryStatus { raw: BatteryStatus::CABLE | BatteryStatus::LEVEL_FULL };
+
+	/// Creates the status from the battery level and whether the cable is connected.
+	///
+	/// The level is truncated to the low nibble.
+	#[inline]
+	pub const fn new(level: u8, cable: bool) -> BatteryStatus {
+		BatteryStatus { raw: level & 0xF | if cable { BatteryStatus::CABLE } else { 0 } }
+	}
+
+	/// Creates the status from its raw value, keeping every bit.
+	#[inline]
+	pub const fn from_raw(raw: u8) -> BatteryStatus {
+		BatteryStatus { raw }
+	}
+
+	/// Returns the battery level nibble, see [`LEVEL_FULL`](Self::LEVEL_FULL).
+	#[inline]
+	pub const fn level(&self) -> u8 {
+		self.raw & 0xF
+	}
+
+	/// Returns the battery charge in percent, levels above 10 are reported as 100.
+	#[inline]
+	pub const fn percent(&self) -> u8 {
+		let level = self.level();
+		if level > 10 { 100 } else { level * 10 }
+	}
+
+	/// Returns if the cable is connected.
+	#[inline]
+	pub const fn is_cable(&self) -> bool {
+		self.raw & BatteryStatus::CABLE != 0
+	}
+
+	/// Returns if the battery is charging, ie. the cable is connected and it is not yet full.
+	#[inline]
+	pub const fn is_charging(&self) -> bool {
+		self.is_cable() && self.level() < BatteryStatus::LEVEL_FULL
+	}
+}
+
+/// Formats the battery percentage followed by `charging` or `cable` when the cable is connected, eg. `60% charging`.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl fmt::Display for BatteryStatus {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}%", self.percent())?;
+		if self.is_charging() {
+			f.write_str(" charging")?;
+		}
+		else if self.is_cable() {
+			f.write_str(" cable")?;
+		}
+		Ok(())
+	}
+}
+
+/// DualShock4 v1 complete HID Input report.
+///
+/// Extends [`DS4Report`] with the timestamp, battery status, motion sensors and touch packets.
+/// The layout is the USB input report `0x01` without its report id, padded to 63 bytes.
+/// It is packed: fields are read and written by value, references to the multi-byte fields cannot be taken.
+///
+/// Submitted by [`DualShock4Wired::update_ex`](crate::DualShock4Wired::update_ex) on ViGEmBus 1.17 or newer,
+/// see [`Capabilities::ds4_extended_report`](crate::Capabilities::ds4_extended_report).
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[repr(C, packed)]
+pub struct DS4ReportEx {
+	pub thumb_lx: u8,
+	pub thumb_ly: u8,
+	pub thumb_rx: u8,
+	pub thumb_ry: u8,
+	pub buttons: u16,
+	pub special: u8,
+	pub trigger_l: u8,
+	pub trigger_r: u8,
+	/// Time of the report in units of about 5.33 µs, wraps around.
+	///
+	/// Unrelated to the packet counters of the touch packets, see [`DS4TouchTimestamper`].
+	pub timestamp: u16,
+	pub battery_lvl: u8,
+	pub gyro_x: i16,
+	pub gyro_y: i16,
+	pub gyro_z: i16,
+	pub accel_x: i16,
+	pub accel_y: i16,
+	pub accel_z: i16,
+	pub _unknown1: [u8; 5],
+	/// Battery and cable status, see [`BatteryStatus`].
+	pub battery_lvl_special: u8,
+	pub _unknown2: [u8; 2],
+	/// Number of valid touch packets, 0 to 3 on USB.
+	pub touch_packets_n: u8,
+	/// The most recent touch packet.
+	pub current_touch: DS4TouchReport,
+	/// The touch packets before the current one, most recent first.
+	pub previous_touch: [DS4TouchReport; 2],
+	pub _padding: [u8; 3],
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl Default for DS4ReportEx {
+	#[inline]
+	fn default() -> Self {
+		DS4ReportEx::neutral()
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl From<DS4Report> for DS4ReportEx {
+	#[inline]
+	fn from(report: DS4Report) -> Self {
+		DS4ReportEx::neutral().with_report(&report)
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl From<DS4ReportEx> for DS4Report {
+	#[inline]
+	fn from(report: DS4ReportEx) -> Self {
+		report.report()
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl DS4ReportEx {
+	/// Fingerprint of the byte layout, see [`WIRE_FORMAT_VERSION`](crate::WIRE_FORMAT_VERSION).
+	pub const LAYOUT_FINGERPRINT: u64 = crate::layout_fingerprint!(DS4ReportEx {
+		thumb_lx, thumb_ly, thumb_rx, thumb_ry, buttons, special, trigger_l, trigger_r,
+		timestamp, battery_lvl, gyro_x, gyro_y, gyro_z, accel_x, accel_y, accel_z,
+		_unknown1, battery_lvl_special, _unknown2, touch_packets_n, current_touch, previous_touch, _padding,
+	});
+
+	/// The neutral report: the neutral [`DS4Report`], a wired controller charged up, the sensors at rest and nothing touching.
+	#[inline]
+	pub const fn neutral() -> DS4ReportEx {
+		const UP: DS4TouchReport = DS4TouchReport::new(0, None, None);
+		DS4ReportEx {
+			thumb_lx: 0x80,
+			thumb_ly: 0x80,
+			thumb_rx: 0x80,
+			thumb_ry: 0x80,
+			buttons: 0x8,
+			special: 0,
+			trigger_l: 0,
+			trigger_r: 0,
+			timestamp: 0,
+			battery_lvl: 0,
+			gyro_x: 0,
+			gyro_y: 0,
+			gyro_z: 0,
+			accel_x: 0,
+			accel_y: 0,
+			accel_z: 0,
+			_unknown1: [0; 5],
+			battery_lvl_special: BatteryStatus::WIRED_FULL.raw,
+			_unknown2: [0; 2],
+			touch_packets_n: 0,
+			current_touch: UP,
+			previous_touch: [UP; 2],
+			_padding: [0; 3],
+		}
+	}
+
+	/// Decodes the report from its `repr(C)` byte layout.
+	///
+	/// The layout is the field order of this struct without any padding between fields, multi-byte fields are little-endian.
+	/// The reserved and padding bytes are kept as is.
+	#[inline]
+	pub const fn from_bytes(bytes: &[u8; mem::size_of::<DS4ReportEx>()]) -> DS4ReportEx {
+		const fn i16_at(bytes: &[u8; mem::size_of::<DS4ReportEx>()], at: usize) -> i16 {
+			i16::from_le_bytes([bytes[at], bytes[at + 1]])
+		}
+		const fn touch_at(bytes: &[u8; mem::size_of::<DS4ReportEx>()], at: usize) -> DS4TouchReport {
+			DS4TouchReport::from_bytes(&[
+				bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3], bytes[at + 4],
+				bytes[at + 5], bytes[at + 6], bytes[at + 7], bytes[at + 8],
+			])
+		}
+		DS4ReportEx {
+			thumb_lx: bytes[0],
+			thumb_ly: bytes[1],
+			thumb_rx: bytes[2],
+			thumb_ry: bytes[3],
+			buttons: u16::from_le_bytes([bytes[4], bytes[5]]),
+			special: bytes[6],
+			trigger_l: bytes[7],
+			trigger_r: bytes[8],
+			timestamp: u16::from_le_bytes([bytes[9], bytes[10]]),
+			battery_lvl: bytes[11],
+			gyro_x: i16_at(bytes, 12),
+			gyro_y: i16_at(bytes, 14),
+			gyro_z: i16_at(bytes, 16),
+			accel_x: i16_at(bytes, 18),
+			accel_y: i16_at(bytes, 20),
+			accel_z: i16_at(bytes, 22),
+			_unknown1: [bytes[24], bytes[25], bytes[26], bytes[27], bytes[28]],
+			battery_lvl_special: bytes[29],
+			_unknown2: [bytes[30], bytes[31]],
+			touch_packets_n: bytes[32],
+			current_touch: touch_at(bytes, 33),
+			previous_touch: [touch_at(bytes, 42), touch_at(bytes, 51)],
+			_padding: [bytes[60], bytes[61], bytes[62]],
+		}
+	}
+
+	/// Encodes the report in its `repr(C)` byte layout.
+	///
+	/// See [`from_bytes`](Self::from_bytes) for the layout.
+	#[inline]
+	pub const fn to_bytes(&self) -> [u8; mem::size_of::<DS4ReportEx>()] {
+		let mut bytes = [0; mem::size_of::<DS4ReportEx>()];
+		let basic = self.report().to_bytes();
+		let mut i = 0;
+		while i < 9 {
+			bytes[i] = basic[i];
+			i += 1;
+		}
+		let timestamp = self.timestamp.to_le_bytes();
+		bytes[9] = timestamp[0];
+		bytes[10] = timestamp[1];
+		bytes[11] = self.battery_lvl;
+		let motion = [self.gyro_x, self.gyro_y, self.gyro_z, self.accel_x, self.accel_y, self.accel_z];
+		let mut i = 0;
+		while i < motion.len() {
+			let value = motion[i].to_le_bytes();
+			bytes[12 + i * 2] = value[0];
+			bytes[13 + i * 2] = value[1];
+			i += 1;
+		}
+		let unknown1 = self._unknown1;
+		let mut i = 0;
+		while i < unknown1.len() {
+			bytes[24 + i] = unknown1[i];
+			i += 1;
+		}
+		bytes[29] = self.battery_lvl_special;
+		bytes[30] = self._unknown2[0];
+		bytes[31] = self._unknown2[1];
+		bytes[32] = self.touch_packets_n;
+		let frame = self.touch_frame();
+		let mut i = 0;
+		while i < frame.len() {
+			let touch = frame[i].to_bytes();
+			let mut j = 0;
+			while j < touch.len() {
+				bytes[33 + i * touch.len() + j] = touch[j];
+				j += 1;
+			}
+			i += 1;
+		}
+		bytes[60] = self._padding[0];
+		bytes[61] = self._padding[1];
+		bytes[62] = self._padding[2];
+		bytes
+	}
+
+	/// Returns the basic report, the buttons, thumbsticks and triggers.
+	#[inline]
+	pub const fn report(&self) -> DS4Report {
+		DS4Report {
+			thumb_lx: self.thumb_lx,
+			thumb_ly: self.thumb_ly,
+			thumb_rx: self.thumb_rx,
+			thumb_ry: self.thumb_ry,
+			buttons: self.buttons,
+			special: self.special,
+			trigger_l: self.trigger_l,
+			trigger_r: self.trigger_r,
+		}
+	}
+
+	/// Returns the report with the fields of the basic report replaced, keeping the timestamp, status, sensors and touch packets.
+	#[inline]
+	pub const fn with_report(self, report: &DS4Report) -> DS4ReportEx {
+		DS4ReportEx {
+			thumb_lx: report.thumb_lx,
+			thumb_ly: report.thumb_ly,
+			thumb_rx: report.thumb_rx,
+			thumb_ry: report.thumb_ry,
+			buttons: report.buttons,
+			special: report.special,
+			trigger_l: report.trigger_l,
+			trigger_r: report.trigger_r,
+			..self
+		}
+	}
+
+	/// Replaces the fields of the basic report, keeping the timestamp, status, sensors and touch packets.
+	#[inline]
+	pub fn set_report(&mut self, report: &DS4Report) {
+		*self = self.with_report(report);
+	}
+
+	/// Returns the battery and cable status.
+	#[inline]
+	pub const fn battery(&self) -> BatteryStatus {
+		BatteryStatus::from_raw(self.battery_lvl_special)
+	}
+
+	/// Returns the angular velocity, x, y and z.
+	#[inline]
+	pub const fn gyro(&self) -> [i16; 3] {
+		[self.gyro_x, self.gyro_y, self.gyro_z]
+	}
+
+	/// Returns the acceleration, x, y and z.
+	#[inline]
+	pub const fn accel(&self) -> [i16; 3] {
+		[self.accel_x, self.accel_y, self.accel_z]
+	}
+
+	/// Checks the report for states a real DualShock4 can never produce.
+	///
+	/// On top of [`DS4Report::validate`]:
+	///
+	/// * The battery level nibble must be at most [`BatteryStatus::LEVEL_FULL`].
+	/// * At most three touch packets fit the report.
+	/// * The touching contacts of the valid touch packets must lie within [`DS4TouchReport::WIDTH`] and [`DS4TouchReport::HEIGHT`].
+	///
+	/// The thumbsticks, triggers, timestamp and motion sensors accept every value.
+	///
+	/// ```
+	/// use vigem_client::{DS4ReportEx, DS4ValidationError};
+	///
+	/// let mut report = DS4ReportEx::default();
+	/// assert_eq!(report.validate(), Ok(()));
+	///
+	/// report.touch_packets_n = 4;
+	/// assert_eq!(report.validate(), Err(DS4ValidationError::TouchPackets(4)));
+	/// ```
+	#[inline]
+	pub fn validate(&self) -> Result<(), DS4ValidationError> {
+		self.report().validate()?;
+		let level = self.battery().level();
+		if level > BatteryStatus::LEVEL_FULL {
+			return Err(DS4ValidationError::Battery(level));
+		}
+		let count = self.touch_packets_n;
+		if count > 3 {
+			return Err(DS4ValidationError::TouchPackets(count));
+		}
+		for touch in &self.touch_frame()[..count as usize] {
+			for point in [touch.point1(), touch.point2()].iter().flatten() {
+				if point.x >= DS4TouchReport::WIDTH {
+					return Err(DS4ValidationError::TouchX(point.x));
+				}
+				if point.y >= DS4TouchReport::HEIGHT {
+					return Err(DS4ValidationError::TouchY(point.y));
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Returns the touch packets of the report, most recent first.
+	///
+	/// Only the first [`touch_packets_n`](Self::touch_packets_n) packets are valid, up to three.
+	#[inline]
+	pub const fn touch_frame(&self) -> [DS4TouchReport; 3] {
+		let previous = self.previous_touch;
+		[self.current_touch, previous[0], previous[1]]
+	}
+
+	/// Parses an input report captured from a physical DualShock4, eg. with hidapi.
+	///
+	/// Accepts the same reports as [`DS4Report::from_usb_input_report`], including the leading report id byte.
+	/// The USB input report `0x01` (64 bytes) and the full Bluetooth input report `0x11` (78 bytes) carry the whole extended report:
+	/// the bytes after the report id, respectively after the two Bluetooth header bytes, are in the layout of [`from_bytes`](Self::from_bytes).
+	/// The CRC tail of Bluetooth reports is not checked.
+	/// The short Bluetooth input report `0x01` (10 bytes) only carries the basic report, the other fields are neutral.
+	///
+	/// Unlike the basic report, the report counter in the upper bits of the special byte is kept.
+	/// Bluetooth reports may claim a fourth touch packet which does not fit the report, the count is capped at three.
+	pub fn from_usb_input_report(data: &[u8]) -> Result<DS4ReportEx, DS4ParseError> {
+		const LEN: usize = mem::size_of::<DS4ReportEx>();
+		let offset = match (data.first(), data.len()) {
+			(Some(0x01), 64..) => 1,
+			(Some(0x11), 78..) => 3,
+			_ => {
+				let mut report = DS4ReportEx::from(DS4Report::from_usb_input_report(data)?);
+				report.special = data[7];
+				return Ok(report);
+			},
+		};
+		let mut bytes = [0; LEN];
+		bytes.copy_from_slice(&data[offset..offset + LEN]);
+		let mut report = DS4ReportEx::from_bytes(&bytes);
+		report.touch_packets_n = report.touch_packets_n.min(3);
+		Ok(report)
+	}
+
+	/// Gyro threshold of the [`IdleInput`] impl, see [`is_user_idle`](Self::is_user_idle).
+	pub const GYRO_NOISE: u16 = 32;
+
+	/// Returns if the user is not touching the controller.
+	///
+	/// The basic report must be [neutral](DS4Report::is_neutral) up to the tolerance,
+	/// no contact of the valid touch packets may be touching the touchpad
+	/// and no component of the [gyro](Self::gyro) may be more than `gyro_threshold` away from zero.
+	///
+	/// Gravity keeps the [accelerometer](Self::accel) away from zero even at rest, it is ignored.
+	/// So are the timestamp, the report counter and the battery status, which change on their own.
+	///
+	/// ```
+	/// let report = vigem_client::DS4ReportEx { gyro_x: -5, accel_y: 8192, timestamp: 1234, ..Default::default() };
+	/// assert!(report.is_user_idle(0, 5));
+	/// assert!(!report.is_user_idle(0, 4));
+	/// ```
+	#[inline]
+	pub const fn is_user_idle(&self, tolerance: u8, gyro_threshold: u16) -> bool {
+		if !self.report().is_neutral(tolerance) {
+			return false;
+		}
+		let gyro = self.gyro();
+		let mut i = 0;
+		while i < gyro.len() {
+			if gyro[i].unsigned_abs() > gyro_threshold {
+				return false;
+			}
+			i += 1;
+		}
+		let frame = self.touch_frame();
+		let count = if self.touch_packets_n < 3 { self.touch_packets_n as usize } else { 3 };
+		let mut i = 0;
+		while i < count {
+			if frame[i].point1().is_some() || frame[i].point2().is_some() {
+				return false;
+			}
+			i += 1;
+		}
+		true
+	}
+
+	/// Returns a builder starting from the neutral report.
+	#[inline]
+	pub const fn builder() -> DS4ReportExBuilder {
+		DS4ReportExBuilder::new()
+	}
+}
+
+/// Decodes the fields instead of printing them raw.
+///
+/// The basic part is decoded like [`DS4Report`], with the report counter apart from the special buttons,
+/// the battery status as a [`BatteryStatus`] and only the valid touch packets are listed.
+/// The reserved bytes and the `battery_lvl` byte are left out.
+///
+/// The default format fits on one line, starting with the [compact](DS4Report::compact) basic report:
+///
+/// ```text
+/// DS4ReportEx { ↗+Cross+PS | LT:0 RT:200 | L(0,128) R(128,255), counter: 5, timestamp: 1000, battery: 60% charging, gyro: [1, -2, 3], accel: [0, 8192, 0], touch: [#42 5@(1000,500)] }
+/// ```
+///
+/// The alternate format `{:#?}` lists every decoded field on its own line.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl fmt::Debug for DS4ReportEx {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		struct Touch(DS4TouchReport);
+		impl fmt::Debug for Touch {
+			fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+				if f.alternate() {
+					return f.debug_struct("DS4TouchReport")
+						.field("packet_counter", &self.0.packet_counter)
+						.field("point1", &self.0.point1())
+						.field("point2", &self.0.point2())
+						.finish();
+				}
+				write!(f, "#{}", self.0.packet_counter)?;
+				let points = [self.0.point1(), self.0.point2()];
+				for point in points.iter().flatten() {
+					write!(f, " {}@({},{})", point.id, point.x, point.y)?;
+				}
+				if points == [None, None] {
+					f.write_str(" -")?;
+				}
+				Ok(())
+			}
+		}
+		struct Touches(DS4ReportEx);
+		impl fmt::Debug for Touches {
+			fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+				let count = self.0.touch_packets_n.min(3) as usize;
+				f.debug_list().entries(self.0.touch_frame()[..count].iter().map(|&touch| Touch(touch))).finish()
+			}
+		}
+
+		let report = self.report();
+		if !f.alternate() {
+			return write!(f, "DS4ReportEx {{ {}, counter: {}, timestamp: {}, battery: {}, gyro: {:?}, accel: {:?}, touch: {:?} }}",
+				report.compact(), self.special >> 2, { self.timestamp }, self.battery(), self.gyro(), self.accel(), Touches(*self));
+		}
+		f.debug_struct("DS4ReportEx")
+			.field("report", &report)
+			.field("timestamp", &{ self.timestamp })
+			.field("battery", &format_args!("{}", self.battery()))
+			.field("gyro", &self.gyro())
+			.field("accel", &self.accel())
+			.field("touch", &Touches(*self))
+			.finish()
+	}
+}
+
+/// Builds a [`DS4ReportEx`].
+///
+/// The report [`timestamp`](Self::timestamp) and the packet counters of the touch packets are independent:
+/// the report timestamp counts time and advances with every report,
+/// the packet counters count touch packets and only advance when a new packet is added.
+/// Reports repeating the same touch packets therefore repeat their counters while the report timestamp moves on.
+///
+/// ```
+/// use vigem_client::{DS4ReportEx, DS4TouchPoint, DS4TouchTimestamper};
+///
+/// let mut timestamper = DS4TouchTimestamper::new(255);
+/// let point = DS4TouchPoint { id: 1, x: 960, y: 471 };
+/// let report = DS4ReportEx::builder()
+/// 	.timestamp(1500)
+/// 	.touch_reports_with(&mut timestamper, &[[Some(point), None], [None, None]])
+/// 	.build();
+///
+/// // Stamped oldest first, reported most recent first
+/// let [current, previous, _] = report.touch_frame();
+/// assert_eq!((current.packet_counter, current.point1()), (0, None));
+/// assert_eq!((previous.packet_counter, previous.point1()), (255, Some(point)));
+/// assert_eq!(report.touch_packets_n, 2);
+/// ```
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct DS4ReportExBuilder {
+	report: DS4ReportEx,
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl DS4ReportExBuilder {
+	/// Starts from the neutral report.
+	#[inline]
+	pub const fn new() -> DS4ReportExBuilder {
+		DS4ReportExBuilder { report: DS4ReportEx::neutral() }
+	}
+
+	/// Sets the basic report, the buttons, thumbsticks and triggers.
+	#[inline]
+	pub const fn report(self, report: &DS4Report) -> DS4ReportExBuilder {
+		DS4ReportExBuilder { report: self.report.with_report(report) }
+	}
+
+	/// Sets the report timestamp, see [`DS4ReportEx::timestamp`].
+	#[inline]
+	pub const fn timestamp(self, timestamp: u16) -> DS4ReportExBuilder {
+		DS4ReportExBuilder { report: DS4ReportEx { timestamp, ..self.report } }
+	}
+
+	/// Sets the battery and cable status.
+	#[inline]
+	pub const fn battery(self, battery: BatteryStatus) -> DS4ReportExBuilder {
+		DS4ReportExBuilder { report: DS4ReportEx { battery_lvl_special: battery.raw, ..self.report } }
+	}
+
+	/// Sets the angular velocity, x, y and z.
+	#[inline]
+	pub const fn gyro(self, [gyro_x, gyro_y, gyro_z]: [i16; 3]) -> DS4ReportExBuilder {
+		DS4ReportExBuilder { report: DS4ReportEx { gyro_x, gyro_y, gyro_z, ..self.report } }
+	}
+
+	/// Sets the acceleration, x, y and z.
+	#[inline]
+	pub const fn accel(self, [accel_x, accel_y, accel_z]: [i16; 3]) -> DS4ReportExBuilder {
+		DS4ReportExBuilder { report: DS4ReportEx { accel_x, accel_y, accel_z, ..self.report } }
+	}
+
+	/// Sets the touch packets as they are, most recent first.
+	///
+	/// # Panics
+	///
+	/// Panics if more than three packets are given.
+	#[inline]
+	pub fn touch_reports(self, reports: &[DS4TouchReport]) -> DS4ReportExBuilder {
+		assert!(reports.len() <= 3, "at most three touch packets fit the report: {}", reports.len());
+		let mut frame = [DS4TouchReport::new(0, None, None); 3];
+		frame[..reports.len()].copy_from_slice(reports);
+		DS4ReportExBuilder {
+			report: DS4ReportEx {
+				touch_packets_n: reports.len() as u8,
+				current_touch: frame[0],
+				previous_touch: [frame[1], frame[2]],
+				..self.report
+			},
+		}
+	}
+
+	/// Sets new touch packets with the contacts, stamped by the timestamper.
+	///
+	/// The contacts are given oldest first, each entry becomes one touch packet stamped with [`DS4TouchTimestamper::next`].
+	/// The report lists them most recent first, so the packet counters decrease along [`DS4ReportEx::touch_frame`].
+	/// Keep one timestamper per target such that the counters keep incrementing across reports.
+	///
+	/// # Panics
+	///
+	/// Panics if more than three packets are given.
+	pub fn touch_reports_with(self, timestamper: &mut DS4TouchTimestamper, points: &[[Option<DS4TouchPoint>; 2]]) -> DS4ReportExBuilder {
+		assert!(points.len() <= 3, "at most three touch packets fit the report: {}", points.len());
+		let mut reports = [DS4TouchReport::new(0, None, None); 3];
+		for (i, &[point1, point2]) in points.iter().enumerate() {
+			reports[points.len() - 1 - i] = DS4TouchReport::new(timestamper.next(), point1, point2);
+		}
+		self.touch_reports(&reports[..points.len()])
+	}
+
+	/// Returns the report.
+	#[inline]
+	pub const fn build(self) -> DS4ReportEx {
+		self.report
+	}
+}
+
+/// DualShock4 lightbar color.
+#[cfg(feature = "ds4-output")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct DS4LightbarColor {
+	pub red: u8,
+	pub green: u8,
+	pub blue: u8,
+}
+
+/// DualShock4 output notification structure.
+#[cfg(feature = "ds4-output")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct DS4Notification {
+	pub large_motor: u8,
+	pub small_motor: u8,
+	pub lightbar: DS4LightbarColor,
+}
+
+/// DualShock4 output notification request.
+#[cfg(all(windows, feature = "ds4-output"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+pub struct DS4RequestNotification {
+	client: Client,
+	// Boxed to be leaked if the driver does not complete the cancellation when dropped
+	dsrn: mem::ManuallyDrop<Box<bus::RequestNotification<bus::DS4RequestNotification>>>,
+	serial_no: u32,
+	uid: TargetUid,
+	// Generation of the target the notifications were requested for
+	generation: u32,
+	current: Arc<bus::Generation>,
+	// Kept outside the buffer the driver writes into while a request is pending
+	attached: bool,
+	link: Arc<bus::CancelLink>,
+	tuning: Option<ThreadTuning>,
+	_unpin: marker::PhantomPinned,
+}
+
+#[cfg(all(windows, feature = "ds4-output"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+impl DS4RequestNotification {
+	/// Returns if the underlying target is still attached.
+	#[inline]
+	pub fn is_attached(&self) -> bool {
+		self.attached
+	}
+
+	/// Returns the serial number of the target the notifications were requested for.
+	#[inline]
+	pub fn serial_no(&self) -> u32 {
+		self.serial_no
+	}
+
+	/// Returns the uid of the target the notifications were requested for, see [`TargetUid`].
+	#[inline]
+	pub fn uid(&self) -> TargetUid {
+		self.uid
+	}
+
+	/// Sets the scheduling hints applied to the thread handling the notifications.
+	///
+	/// Applies to [`spawn_thread`](Self::spawn_thread) and the other consumers spawning a thread, eg. [`RumbleListener`].
+	/// The hints are best effort, the thread runs untuned if they cannot be applied.
+	#[inline]
+	pub fn with_tuning(mut self, tuning: ThreadTuning) -> Self {
+		self.tuning = Some(tuning);
+		self
+	}
+
+	/// Returns the scheduling hints applied to the thread handling the notifications.
+	#[inline]
+	pub fn tuning(&self) -> Option<ThreadTuning> {
+		self.tuning
+	}
+
+	// Applies the scheduling hints to the thread handling the notifications
+	#[inline]
+	pub(crate) fn apply_tuning(&self) -> Option<TuningGuard> {
+		self.tuning.and_then(|tuning| tuning.apply_to_current_thread().ok())
+	}
+
+	// Lets a listener cancel the pending request from another thread
+	#[inline]
+	pub(crate) fn cancel_link(&self) -> Arc<bus::CancelLink> {
+		self.link.clone()
+	}
+
+	/// Spawns a thread to handle the notifications.
+	///
+	/// The callback `f` is invoked for every notification.
+	///
+	/// Returns a [`JoinHandle`](thread::JoinHandle) for the created thread.
+	/// The thread exits once the target from which the notifications are requested is unplugged or dropped.
+	#[inline]
+	pub fn spawn_thread<F: FnMut(&DS4RequestNotification, DS4Notification) + Send + 'static>(self, mut f: F) -> thread::JoinHandle<()> {
+		thread::spawn(move || {
+			let _tuning = self.apply_tuning();
+			// Safety: the request notification object is not accessible after it is pinned
+			let mut reqn = self;
+			let mut reqn = unsafe { pin::Pin::new_unchecked(&mut reqn) };
+			loop {
+				reqn.as_mut().request();
+				match reqn.as_mut().poll(true) {
+					Ok(None) => {},
+					Ok(Some(data)) => f(&reqn, data),
+					Err(_) => break,
+				}
+			}
+		})
+	}
+
+	/// Requests a notification.
+	#[inline(never)]
+	pub fn request(self: pin::Pin<&mut Self>) {
+		unsafe {
+			let device = self.client.device;
+			let this = self.get_unchecked_mut();
+			let dsrn = &mut this.dsrn;
+			// The target was unplugged since, its serial number may already be reused
+			if this.current.get() != this.generation {
+				this.attached = false;
+			}
+			if this.attached {
+				this.link.set(device, &mut dsrn.overlapped);
+				dsrn.ioctl(device);
+			}
+		}
+	}
+
+	/// Polls the request for notifications.
+	///
+	/// If `wait` is true this method will block until a notification is received.
+	/// Else returns immediately if no notification is received yet.
+	///
+	/// Returns:
+	///
+	/// * `Ok(None)`: When `wait` is false and there is no notification yet.
+	/// * `Ok(Some(_))`: The notification was successfully received.  
+	///   Another request should be made or any other calls to `poll` return the same result.
+	/// * `Err(OperationAborted)`: The underlying target was unplugged causing any pending notification requests to abort.
+	///   Notifications completing after the target was unplugged are discarded the same way, they may belong to a target reusing its serial number.
+	/// * `Err(_)`: An unexpected error occurred.
+	#[inline(never)]
+	pub fn poll(self: pin::Pin<&mut Self>, wait: bool) -> Result<Option<DS4Notification>, Error> {
+		unsafe {
+			let device = self.client.device;
+			let this = self.get_unchecked_mut();
+			let dsrn = &mut this.dsrn;
+			match dsrn.poll(device, wait) {
+				// Completed for a target reusing the serial number after the target was unplugged, discard it
+				Ok(()) if this.current.get() != this.generation => {
+					this.attached = false;
+					Err(Error::OperationAborted)
+				},
+				Ok(()) => Ok(Some(DS4Notification {
+					large_motor: dsrn.buffer.LargeMotor,
+					small_motor: dsrn.buffer.SmallMotor,
+					lightbar: DS4LightbarColor {
+						red: dsrn.buffer.LightbarRed,
+						green: dsrn.buffer.LightbarGreen,
+						blue: dsrn.buffer.LightbarBlue,
+					},
+				})),
+				Err(winerror::ERROR_IO_INCOMPLETE) => Ok(None),
+				Err(winerror::ERROR_OPERATION_ABORTED) => {
+					this.attached = false;
+					Err(Error::OperationAborted)
+				},
+				Err(err) => Err(Error::WinError(err)),
+			}
+		}
+	}
+}
+
+// Requests and polls take `Pin<&mut Self>`, shared access never reads the buffer the driver writes into
+#[cfg(all(windows, feature = "ds4-output"))]
+unsafe impl Sync for DS4RequestNotification {}
+#[cfg(all(windows, feature = "ds4-output"))]
+unsafe impl Send for DS4RequestNotification {}
+
+#[cfg(all(windows, feature = "ds4-output"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+impl fmt::Debug for DS4RequestNotification {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("DS4RequestNotification")
+			.field("client", &format_args!("{:?}", self.client))
+			.field("serial_no", &self.serial_no)
+			.field("uid", &self.uid)
+			.field("tuning", &self.tuning)
+			.field("attached", &self.is_attached())
+			.finish()
+	}
+}
+
+#[cfg(all(windows, feature = "ds4-output"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+impl Drop for DS4RequestNotification {
+	fn drop(&mut self) {
+		// Wait for the target to finish cancelling if it is doing so
+		self.link.clear();
+		unsafe {
+			let this = pin::Pin::new_unchecked(self).get_unchecked_mut();
+			if this.attached {
+				// Give up on a wedged driver, the pending request keeps the buffer alive
+				let device = this.client.device;
+				if let Err(winerror::ERROR_TIMEOUT) = this.dsrn.cancel_bounded(device, bus::DROP_TIMEOUT_MS) {
+					return;
+				}
+			}
+			mem::ManuallyDrop::drop(&mut this.dsrn);
+		}
+	}
+}
+
+/// Receives notifications by [pumping](Pump::pump) instead of a dedicated thread.
+///
+/// Each pump issues a notification request if none is pending and collects the completed ones without waiting.
+#[cfg(all(windows, feature = "ds4-output"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+pub struct DS4NotificationPoller {
+	reqn: pin::Pin<Box<DS4RequestNotification>>,
+	pending: bool,
+	queue: VecDeque<DS4Notification>,
+}
+
+#[cfg(all(windows, feature = "ds4-output"))]
+impl DS4NotificationPoller {
+	/// Creates a poller for the notification request.
+	#[inline]
+	pub fn new(reqn: DS4RequestNotification) -> DS4NotificationPoller {
+		DS4NotificationPoller { reqn: Box::pin(reqn), pending: false, queue: VecDeque::new() }
+	}
+
+	/// Returns if the underlying target is still attached.
+	///
+	/// Once the target is unplugged no more notifications are received.
+	#[inline]
+	pub fn is_attached(&self) -> bool {
+		self.reqn.is_attached()
+	}
+
+	/// Returns the oldest received notification.
+	#[inline]
+	pub fn try_recv(&mut self) -> Option<DS4Notification> {
+		self.queue.pop_front()
+	}
+}
+
+#[cfg(all(windows, feature = "ds4-output"))]
+impl Pump for DS4NotificationPoller {
+	fn pump(&mut self, _now: time::Instant) -> Result<(), Error> {
+		while self.reqn.is_attached() {
+			if !self.pending {
+				self.reqn.as_mut().request();
+				self.pending = true;
+			}
+			match self.reqn.as_mut().poll(false) {
+				Ok(None) => break,
+				Ok(Some(data)) => {
+					self.pending = false;
+					self.queue.push_back(data);
+				},
+				Err(Error::OperationAborted) => break,
+				Err(err) => {
+					self.pending = false;
+					return Err(err);
+				},
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(all(windows, feature = "ds4-output"))]
+impl fmt::Debug for DS4NotificationPoller {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("DS4NotificationPoller")
+			.field("reqn", &self.reqn)
+			.field("pending", &self.pending)
+			.field("queued", &self.queue.len())
+			.finish()
+	}
+}
+
+/// Configuration of a [`DualShock4Wired`] target.
+///
+/// Captures everything needed to create identical targets, but not whether a target is plugged in.
+/// Callbacks such as the progress observer and per-thread resources such as the dedicated event are not captured.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DS4TargetTemplate {
+	/// Vendor and product ids.
+	pub id: TargetId,
+	/// Whether reports are validated before they are submitted.
+	pub validate_reports: bool,
+	/// What updating the target does while it is not plugged in, see [`DualShock4Wired::set_detached_policy`].
+	pub detached_policy: DetachedPolicy,
+	/// The report submitted once the target is ready, see [`DualShock4Wired::set_initial_report`].
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	pub initial_report: Option<DS4Report>,
+	/// The stale input timeout, see [`DualShock4Wired::set_stale_timeout`].
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	pub stale_timeout: Option<time::Duration>,
+	/// Whether the stale input timeout is only serviced when pumped, see [`DualShock4Wired::set_stale_timeout_manual`].
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	pub stale_timeout_manual: bool,
+	/// Whether the target is guarded against panics, see [`DualShock4Wired::set_panic_guard`].
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	pub panic_guard: bool,
+	/// The capacity of the latency stats, `None` if none are kept, see [`DualShock4Wired::set_latency_stats`].
+	pub latency_capacity: Option<usize>,
+	/// Which input report is submitted, see [`DualShock4Wired::set_report_mode`].
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	pub report_mode: DS4ReportMode,
+}
+
+impl DS4TargetTemplate {
+	/// Creates a new template with the default configuration.
+	#[inline]
+	pub const fn new(id: TargetId) -> DS4TargetTemplate {
+		DS4TargetTemplate {
+			id,
+			validate_reports: false,
+			detached_policy: DetachedPolicy::Error,
+			#[cfg(feature = "unstable_ds4")]
+			initial_report: None,
+			#[cfg(feature = "unstable_ds4")]
+			stale_timeout: None,
+			#[cfg(feature = "unstable_ds4")]
+			stale_timeout_manual: false,
+			#[cfg(feature = "unstable_ds4")]
+			panic_guard: false,
+			latency_capacity: None,
+			#[cfg(feature = "unstable_ds4")]
+			report_mode: DS4ReportMode::Basic,
+		}
+	}
+
+	/// Checks the configuration can be applied to a target.
+	///
+	/// Fails with [`Error::InvalidConfig`] for a latency capacity of zero.
+	#[inline]
+	pub fn validate(&self) -> Result<(), Error> {
+		if self.latency_capacity == Some(0) {
+			return Err(Error::InvalidConfig);
 		}
+		Ok(())
 	}
-}
 
-// /// DualShock4 v1 complete HID Input report.
-// #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-// #[repr(C)]
-// pub struct DS4ReportEx {
-// 	pub thumb_lx: u8,
-// 	pub thumb_ly: u8,
-// 	pub thumb_rx: u8,
-// 	pub thumb_ry: u8,
-// 	pub buttons: u16,
-// 	pub special: u8,
-// 	pub trigger_l: u8,
-// 	pub trigger_r: u8,
-// 	pub timestamp: u16,
-// 	pub battery_lvl: u8,
-// 	pub gyro_x: i16,
-// 	pub gyro_y: i16,
-// 	pub gyro_z: i16,
-// 	pub accel_x: i16,
-// 	pub accel_y: i16,
-// 	pub accel_z: i16,
-// 	pub _unknown1: [u8; 5],
-// 	pub battery_lvl_special: u8,
-// 	pub _unknown2: [u8; 2],
-// 	pub touch_packets_n: u8, // 0x00 to 0x03 (USB max)
-// 	pub current_touch: DS4Touch,
-// 	pub previous_touch: [DS4Touch; 2],
-// }
+	/// Creates a new target from this template.
+	///
+	/// The target is not plugged in.
+	/// Fails if the template does not [validate](Self::validate)
+	/// or the client handle cannot be duplicated for the stale input timeout or the panic guard.
+	#[cfg(windows)]
+	#[inline(never)]
+	pub fn instantiate<CL: Borrow<Client>>(&self, client: CL) -> Result<DualShock4Wired<CL>, Error> {
+		self.validate()?;
+		let mut target = DualShock4Wired::new(client, self.id);
+		target.validate_reports = self.validate_reports;
+		target.detached = self.detached_policy;
+		#[cfg(feature = "unstable_ds4")]
+		{
+			target.initial_report = self.initial_report;
+			target.set_watch(self.stale_timeout, !self.stale_timeout_manual)?;
+			target.set_panic_guard(self.panic_guard)?;
+			target.set_report_mode(self.report_mode);
+		}
+		target.latency = self.latency_capacity.map(LatencyStats::new);
+		Ok(target)
+	}
+}
 
 /// A virtual Sony DualShock 4 (wired).
+///
+/// A target may be used from any thread as long as calls do not overlap, eg. behind a `Mutex` or moved between threads.
+/// Every blocking call waits on an event of the calling thread, or on the [dedicated event](Self::set_dedicated_event) which is reset before each request.
+#[cfg(windows)]
 pub struct DualShock4Wired<CL: Borrow<Client>> {
 	client: CL,
 	event: Event,
 	serial_no: u32,
 	id: TargetId,
+	uid: TargetUid,
+	validate_reports: bool,
+	watch: Option<Arc<Watch>>,
+	panic_guard: Option<Arc<PanicGuard>>,
+	detached: DetachedPolicy,
+	progress: Option<ProgressObserver>,
+	latency: Option<LatencyStats>,
+	#[cfg(feature = "unstable_ds4")]
+	initial_report: Option<DS4Report>,
+	#[cfg(feature = "unstable_ds4")]
+	shadow: Shadow,
+	#[cfg(feature = "ds4-output")]
+	notifications: Vec<Weak<bus::CancelLink>>,
+	#[cfg(feature = "ds4-output")]
+	generation: Arc<bus::Generation>,
 }
 
+#[cfg(windows)]
 impl<CL: Borrow<Client>> DualShock4Wired<CL> {
 	/// Creates a new instance.
 	#[inline]
 	pub fn new(client: CL, id: TargetId) -> DualShock4Wired<CL> {
-		let event = Event::new(false, false);
-		DualShock4Wired { client, event, serial_no: 0, id }
+		let event = Event::null();
+		DualShock4Wired {
+			client, event, serial_no: 0, id,
+			uid: TargetUid::next(),
+			validate_reports: false,
+			watch: None,
+			panic_guard: None,
+			detached: DetachedPolicy::Error,
+			progress: None,
+			latency: None,
+			#[cfg(feature = "unstable_ds4")]
+			initial_report: None,
+			#[cfg(feature = "unstable_ds4")]
+			shadow: Shadow::default(),
+			#[cfg(feature = "ds4-output")]
+			notifications: Vec::new(),
+			#[cfg(feature = "ds4-output")]
+			generation: Arc::default(),
+		}
+	}
+
+	/// Enables validating reports before they are submitted.
+	///
+	/// When enabled `update` and `update_ex` return [`Error::InvalidReport`] for reports which fail
+	/// [`DS4Report::validate`] or [`DS4ReportEx::validate`] without submitting them.
+	/// Disabled by default.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn set_validate_reports(&mut self, validate: bool) {
+		self.validate_reports = validate;
+	}
+
+	/// Returns if reports are validated before they are submitted.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn validate_reports(&self) -> bool {
+		self.validate_reports
 	}
 
 	/// Returns if the controller is plugged in.
+	///
+	/// A target unplugged by its panic guard is not plugged in, see `set_panic_guard`.
 	#[inline]
 	pub fn is_attached(&self) -> bool {
-		self.serial_no != 0
+		self.serial_no != 0 && !self.panic_guard.as_ref().is_some_and(|guard| guard.is_neutralized())
+	}
+
+	// Forgets the serial number if the panic guard unplugged the target, eg. for a caught panic on the main thread
+	fn sync_panic_guard(&mut self) {
+		if self.panic_guard.as_ref().is_some_and(|guard| guard.take_neutralized()) {
+			let serial_no = self.release();
+			self.client.borrow().track_unplug(serial_no, Ok(()));
+			#[cfg(feature = "ds4-output")]
+			self.notifications.clear();
+		}
 	}
 
 	/// Returns the id the controller was constructed with.
@@ -89,39 +2308,163 @@ impl<CL: Borrow<Client>> DualShock4Wired<CL> {
 		self.id
 	}
 
+	/// Returns the process-unique identifier of the target, see [`TargetUid`].
+	#[inline]
+	pub fn uid(&self) -> TargetUid {
+		self.uid
+	}
+
+	/// Returns the serial number assigned by the driver, zero if not plugged in.
+	#[inline]
+	pub fn serial_no(&self) -> u32 {
+		self.serial_no
+	}
+
+	/// Sets what updating the target does while it is not plugged in.
+	///
+	/// Defaults to [`DetachedPolicy::Error`].
+	#[inline]
+	pub fn set_detached_policy(&mut self, policy: DetachedPolicy) {
+		self.detached = policy;
+	}
+
+	/// Returns what updating the target does while it is not plugged in.
+	#[inline]
+	pub fn detached_policy(&self) -> DetachedPolicy {
+		self.detached
+	}
+
+	// Applies the detached policy before an update, returns false if the update should be skipped.
+	#[cfg(feature = "unstable_ds4")]
+	#[inline]
+	fn prepare_update(&mut self) -> Result<bool, Error> {
+		self.sync_panic_guard();
+		if self.is_attached() {
+			return Ok(true);
+		}
+		match self.detached {
+			DetachedPolicy::Error => Err(Error::NotPluggedIn),
+			DetachedPolicy::Ignore => Ok(false),
+			DetachedPolicy::AutoPlugin => {
+				self.plugin()?;
+				self.wait_ready()?;
+				Ok(true)
+			},
+		}
+	}
+
 	/// Returns the client.
 	#[inline]
 	pub fn client(&self) -> &CL {
 		&self.client
 	}
 
+	/// Returns the configuration of this target.
+	#[inline]
+	pub fn template(&self) -> DS4TargetTemplate {
+		DS4TargetTemplate {
+			id: self.id,
+			validate_reports: self.validate_reports,
+			detached_policy: self.detached,
+			#[cfg(feature = "unstable_ds4")]
+			initial_report: self.initial_report,
+			#[cfg(feature = "unstable_ds4")]
+			stale_timeout: self.watch.as_ref().map(|watch| watch.timeout()),
+			#[cfg(feature = "unstable_ds4")]
+			stale_timeout_manual: self.watch.as_ref().is_some_and(|watch| !watch.is_background()),
+			#[cfg(feature = "unstable_ds4")]
+			panic_guard: self.panic_guard.is_some(),
+			latency_capacity: self.latency.as_ref().map(LatencyStats::capacity),
+			#[cfg(feature = "unstable_ds4")]
+			report_mode: self.shadow.mode(),
+		}
+	}
+
 	/// Unplugs and destroys the controller, returning the client.
 	#[inline]
 	pub fn drop(mut self) -> CL {
-		let _ = self.unplug();
+		let _ = self.unplug_impl(true);
 
 		unsafe {
 			let client = (&self.client as *const CL).read();
 			ptr::drop_in_place(&mut self.event);
+			ptr::drop_in_place(&mut self.watch);
+			ptr::drop_in_place(&mut self.panic_guard);
+			ptr::drop_in_place(&mut self.progress);
+			ptr::drop_in_place(&mut self.latency);
+			#[cfg(feature = "ds4-output")]
+			ptr::drop_in_place(&mut self.notifications);
+			#[cfg(feature = "ds4-output")]
+			ptr::drop_in_place(&mut self.generation);
 			mem::forget(self);
 			client
 		}
 	}
 
+	/// Sets the observer notified of the progress of [`plugin`](Self::plugin) and [`wait_ready`](Self::wait_ready), `None` to remove it.
+	///
+	/// The observer is invoked on the calling thread without holding any locks.
+	/// It cannot call back into the target, which is mutably borrowed while plugging in.
+	#[inline]
+	pub fn set_progress_observer(&mut self, observer: Option<ProgressObserver>) {
+		self.progress = observer;
+	}
+
+	/// Sets an observer notified of the progress together with the [uid](Self::uid) of the target.
+	///
+	/// Same as [`set_progress_observer`](Self::set_progress_observer), for observers shared by multiple targets which log which target progressed.
+	#[inline]
+	pub fn observe_progress<F: Fn(TargetUid, PlugStage) + Send + Sync + 'static>(&mut self, observer: F) {
+		let uid = self.uid;
+		self.progress = Some(Box::new(move |stage| observer(uid, stage)));
+	}
+
+	#[inline]
+	fn progress(&self, stage: PlugStage) {
+		if let Some(observer) = &self.progress {
+			observer(stage);
+		}
+	}
+
+	/// Uses an event object of its own to wait on the driver.
+	///
+	/// By default targets wait on an event object shared by all targets on the calling thread,
+	/// which keeps the number of kernel objects down when driving many targets.
+	/// Failure to create the event object is reported as [`Error::ResourceCreation`].
+	#[inline]
+	pub fn set_dedicated_event(&mut self, dedicated: bool) -> Result<(), Error> {
+		if dedicated {
+			self.event.create_if_null(false, false).map_err(Error::ResourceCreation)
+		}
+		else {
+			self.event = Event::null();
+			Ok(())
+		}
+	}
+
 	/// Plugs the controller in.
+	///
+	/// Failure to create the event object of the calling thread used to wait on the driver is reported as [`Error::ResourceCreation`].
 	#[inline(never)]
 	pub fn plugin(&mut self) -> Result<(), Error> {
+		self.sync_panic_guard();
 		if self.is_attached() {
 			return Err(Error::AlreadyConnected);
 		}
+		self.client.borrow().check_plugin()?;
+
+		let event = self.event.sync()?;
 
 		self.serial_no = unsafe {
 			let mut plugin = bus::PluginTarget::ds4_wired(1, self.id.vendor, self.id.product);
-			let device = self.client.borrow().device;
+			let client = self.client.borrow();
+			let device = client.device;
 
 			// Yes this is how the driver is implemented
-			while plugin.ioctl(device, self.event.handle).is_err() {
+			self.progress(PlugStage::SerialScan { attempt: plugin.SerialNo });
+			while { let _pass = client.enter(); plugin.ioctl(device, event.handle) }.is_err() {
 				plugin.SerialNo += 1;
+				self.progress(PlugStage::SerialScan { attempt: plugin.SerialNo });
 				if plugin.SerialNo >= u16::MAX as u32 {
 					return Err(Error::NoFreeSlot);
 				}
@@ -130,83 +2473,520 @@ impl<CL: Borrow<Client>> DualShock4Wired<CL> {
 			plugin.SerialNo
 		};
 
+		self.client.borrow().track_plugin(self.serial_no);
+		if let Some(watch) = &self.watch {
+			watch.set_serial_no(self.serial_no);
+		}
+		if let Some(guard) = &self.panic_guard {
+			guard.set_serial_no(self.serial_no);
+		}
+		#[cfg(feature = "ds4-output")]
+		self.generation.bump();
+		self.progress(PlugStage::DriverAccepted { serial: self.serial_no });
 		Ok(())
 	}
 
+	// Takes over a target plugged in under the serial number without plugging it in, see `Fleet::restore`
+	#[cfg(feature = "unstable_ds4")]
+	pub(crate) fn adopt(&mut self, serial_no: u32) {
+		self.serial_no = serial_no;
+		#[cfg(feature = "ds4-output")]
+		self.generation.bump();
+		if let Some(watch) = &self.watch {
+			watch.set_serial_no(serial_no);
+		}
+		if let Some(guard) = &self.panic_guard {
+			guard.set_serial_no(serial_no);
+		}
+		self.client.borrow().track_adopt(serial_no);
+	}
+
+	// Lets go of the target without unplugging it, it remains plugged in until adopted or the client is closed
+	pub(crate) fn release(&mut self) -> u32 {
+		if let Some(watch) = &self.watch {
+			watch.set_serial_no(0);
+		}
+		if let Some(guard) = &self.panic_guard {
+			guard.set_serial_no(0);
+		}
+		#[cfg(feature = "ds4-output")]
+		self.generation.bump();
+		mem::replace(&mut self.serial_no, 0)
+	}
+
 	/// Unplugs the controller.
+	///
+	/// Waits for the driver to complete the request, however long it takes.
+	/// Dropping the target instead gives up waiting after a few seconds.
 	#[inline(never)]
 	pub fn unplug(&mut self) -> Result<(), Error> {
+		self.unplug_impl(false)
+	}
+
+	// Unplugs the controller, bounded gives up waiting for the driver after `bus::DROP_TIMEOUT_MS`
+	fn unplug_impl(&mut self, bounded: bool) -> Result<(), Error> {
+		self.sync_panic_guard();
 		if !self.is_attached() {
 			return Err(Error::NotPluggedIn);
 		}
+		let event = if bounded { None } else { Some(self.event.sync()?) };
 
-		unsafe {
+		// Stop the watchdog from submitting reports to a serial number which may be reused
+		if let Some(watch) = &self.watch {
+			watch.set_serial_no(0);
+		}
+		if let Some(guard) = &self.panic_guard {
+			guard.set_serial_no(0);
+		}
+
+		// Abort the pending notification requests so their listeners observe OperationAborted promptly
+		#[cfg(feature = "ds4-output")]
+		for link in self.notifications.drain(..) {
+			if let Some(link) = link.upgrade() {
+				link.cancel();
+			}
+		}
+
+		let result = unsafe {
 			let mut unplug = bus::UnplugTarget::new(self.serial_no);
-			let device = self.client.borrow().device;
-			unplug.ioctl(device, self.event.handle)?;
+			let client = self.client.borrow();
+			let _pass = client.enter();
+			let device = client.device;
+			match &event {
+				Some(event) => unplug.ioctl(device, event.handle),
+				None => unplug.ioctl_bounded(device, bus::DROP_TIMEOUT_MS),
+			}
+		};
+		self.client.borrow().track_unplug(self.serial_no, result);
+
+		if let Err(err) = result {
+			if let Some(watch) = &self.watch {
+				watch.set_serial_no(self.serial_no);
+			}
+			if let Some(guard) = &self.panic_guard {
+				guard.set_serial_no(self.serial_no);
+			}
+			return Err(Error::WinError(err));
 		}
 
 		self.serial_no = 0;
+		#[cfg(feature = "ds4-output")]
+		self.generation.bump();
 		Ok(())
 	}
 
-	/// Waits until the virtual controller is ready.
+	/// Waits until the virtual controller is ready and submits the [initial report](Self::set_initial_report).
 	///
 	/// Any updates submitted before the virtual controller is ready may return an error.
+	///
+	/// If the virtual controller is not ready after a grace period, the installation state of its device is consulted.
+	/// Fails with [`Error::DeviceInstallFailed`] if Windows failed to install the device, eg. due to a broken driver store.
+	///
+	/// Without the `unstable_ds4` feature the target cannot be updated and no report is submitted.
 	#[inline(never)]
 	pub fn wait_ready(&mut self) -> Result<(), Error> {
+		self.wait_ready_timed().map(|_| ())
+	}
+
+	/// Waits until the virtual controller is ready and returns how long the driver took.
+	///
+	/// Same as [`wait_ready`](Self::wait_ready) but measures the driver round trip with a monotonic clock,
+	/// the submission of the initial report is not included.
+	#[inline(never)]
+	pub fn wait_ready_timed(&mut self) -> Result<time::Duration, Error> {
 		if !self.is_attached() {
 			return Err(Error::NotPluggedIn);
 		}
 
-		unsafe {
-			let mut wait = bus::WaitDeviceReady::new(self.serial_no);
-			let device = self.client.borrow().device;
-			wait.ioctl(device, self.event.handle)?;
+		let event = self.event.sync()?;
+		self.progress(PlugStage::WaitingReady);
+		let elapsed = unsafe {
+			let client = self.client.borrow();
+			let _pass = client.enter();
+			let device = client.device;
+			let start = time::Instant::now();
+			devpath::InstallWatch::wait_ready(device, event.handle, self.id, self.serial_no)?;
+			start.elapsed()
+		};
+		self.progress(PlugStage::Ready);
+
+		#[cfg(feature = "unstable_ds4")]
+		{
+			let initial = self.initial_report.unwrap_or_default();
+			self.update(&initial)?;
 		}
+		Ok(elapsed)
+	}
 
-		Ok(())
+	/// Sets the report submitted once the virtual controller is ready, `None` for the neutral report.
+	///
+	/// Submitted by [`wait_ready`](Self::wait_ready), including when plugging in automatically with [`DetachedPolicy::AutoPlugin`],
+	/// such that games see a known state from the moment the controller appears.
+	/// It is an ordinary submission, eg. it is validated when [validating reports](Self::set_validate_reports) and refreshes the stale timeout.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn set_initial_report(&mut self, report: Option<DS4Report>) {
+		self.initial_report = report;
 	}
 
-	/// Updates the virtual controller state.
+	/// Returns the report submitted once the virtual controller is ready, `None` for the neutral report.
 	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn initial_report(&self) -> Option<&DS4Report> {
+		self.initial_report.as_ref()
+	}
+
+	/// Returns the device path of the virtual controller's HID interface, eg. to open it with `CreateFile` or hidapi.
+	///
+	/// The device is matched by the vendor and product ids and the serial number the bus assigned to the target.
+	/// Waits a few seconds for the device to appear after plugging in, see [`device_path_timeout`](Self::device_path_timeout).
+	#[inline]
+	pub fn device_path(&mut self) -> Result<ffi::OsString, Error> {
+		self.device_path_timeout(devpath::DEVICE_PATH_TIMEOUT)
+	}
+
+	/// Returns the device path of the virtual controller's HID interface, waiting up to the timeout for it to appear.
+	///
+	/// Fails with [`Error::DeviceNotFound`] on timeout and [`Error::AmbiguousDevice`] if more than one device matches the target.
 	#[inline(never)]
-	pub fn update(&mut self, report: &DS4Report) -> Result<(), Error> {
+	pub fn device_path_timeout(&mut self, timeout: time::Duration) -> Result<ffi::OsString, Error> {
 		if !self.is_attached() {
 			return Err(Error::NotPluggedIn);
 		}
+		// The HID collection is a child of the USB device node created by the bus
+		devpath::device_path(&bus::GUID_DEVINTERFACE_HID, true, self.id, self.serial_no, timeout)
+	}
+
+	/// Updates the virtual controller state.
+	///
+	/// Merges the report into the [shadow](Self::shadow) and submits it in the [report mode](Self::set_report_mode).
+	/// If the target is not plugged in the [detached policy](Self::set_detached_policy) applies.
+	///
+	/// Same as [`submit`](Self::submit) for the basic report.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn update(&mut self, report: &DS4Report) -> Result<(), Error> {
+		self.submit(report)
+	}
+
+	/// Updates the virtual controller state with either kind of report.
+	///
+	/// The basic [`DS4Report`] is merged into the [shadow](Self::shadow), the [`DS4ReportEx`] replaces it.
+	/// The shadow is submitted in the [report mode](Self::set_report_mode),
+	/// unless [down-converting](Self::set_downgrade_reports) extended reports for a driver which does not support them.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline(never)]
+	pub fn submit<R: Ds4Submittable>(&mut self, report: &R) -> Result<(), Error> {
+		self.submit_update(report, false)?;
+		Ok(())
+	}
+
+	/// Sets whether extended reports are down-converted to basic reports for drivers which do not support them.
+	///
+	/// Only affects the [`DS4ReportMode::Extended`] mode: instead of the driver rejecting the extended report,
+	/// the basic part of the [shadow](Self::shadow) is submitted.
+	/// The first such update queries the [capabilities](Client::capabilities) of the driver and remembers the answer for the lifetime of the target.
+	///
+	/// Defaults to `false`, the updates fail with the error of the driver.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn set_downgrade_reports(&mut self, downgrade: bool) {
+		self.shadow.set_downgrade(downgrade);
+	}
+
+	/// Returns whether extended reports are down-converted for drivers which do not support them.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn downgrade_reports(&self) -> bool {
+		self.shadow.downgrade()
+	}
+
+	// Applies the detached policy and validates the report, the round trip is only measured when timed
+	#[cfg(feature = "unstable_ds4")]
+	fn submit_update<R: Ds4Submittable>(&mut self, report: &R, timed: bool) -> Result<Submitted, Error> {
+		if !self.prepare_update()? {
+			return Ok(Submitted::Skipped);
+		}
 
-		unsafe {
-			let mut dsr = bus::DS4SubmitReport::new(self.serial_no, *report);
-			let device = self.client.borrow().device;
-			dsr.ioctl(device, self.event.handle)?;
+		if self.validate_reports {
+			report.check()?;
+		}
+		if self.shadow.needs_probe() {
+			let supported = self.client.borrow().capabilities()?.ds4_extended_report;
+			self.shadow.set_extended_supported(supported);
+		}
+		let submission = self.shadow.merge(report);
+
+		let event = self.event.sync()?;
+		let submit = || unsafe {
+			let client = self.client.borrow();
+			let _pass = client.enter();
+			let device = client.device;
+			let start = if timed { Some(time::Instant::now()) } else { None };
+			submission.ioctl(self.serial_no, device, event.handle)?;
+			Ok(Submitted::Sent(start.map(|start| start.elapsed())))
+		};
+
+		match &self.watch {
+			Some(watch) => watch.update(submit),
+			None => submit(),
+		}
+	}
+
+	/// Updates the virtual controller state with the extended report.
+	///
+	/// Replaces the [shadow](Self::shadow) and submits it in the [report mode](Self::set_report_mode),
+	/// ie. only its basic part unless the mode is [`DS4ReportMode::Extended`].
+	/// Otherwise the same as [`update`](Self::update), the report is validated when [`set_validate_reports`](Self::set_validate_reports) is enabled.
+	///
+	/// Same as [`submit`](Self::submit) for the extended report.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn update_ex(&mut self, report: &DS4ReportEx) -> Result<(), Error> {
+		self.submit(report)
+	}
+
+	/// Sets which input report is submitted, see [`DS4ReportMode`] for how the reports are merged.
+	///
+	/// Takes effect with the next update, nothing is submitted when switching.
+	/// Drivers older than ViGEmBus 1.17 reject extended reports, see [`Capabilities::ds4_extended_report`].
+	/// Defaults to [`DS4ReportMode::Basic`].
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn set_report_mode(&mut self, mode: DS4ReportMode) {
+		self.shadow.set_mode(mode);
+	}
+
+	/// Returns which input report is submitted.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn report_mode(&self) -> DS4ReportMode {
+		self.shadow.mode()
+	}
+
+	/// Returns the extended report the updates were merged into, see [`DS4ReportMode`].
+	///
+	/// Neutral until the first update, updates skipped by [`DetachedPolicy::Ignore`] or rejected by validation are not merged.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn shadow(&self) -> &DS4ReportEx {
+		self.shadow.report()
+	}
+
+	/// Updates the virtual controller state from bytes in the `repr(C)` layout of [`DS4Report`].
+	///
+	/// Intended for producers which write the report into shared memory, see [`DS4Report::from_bytes`] for the layout.
+	/// Same as [`update`](Self::update), the report is validated when [`set_validate_reports`](Self::set_validate_reports) is enabled.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn update_raw(&mut self, bytes: &[u8; mem::size_of::<DS4Report>()]) -> Result<(), Error> {
+		self.update(&DS4Report::from_bytes(bytes))
+	}
+
+	/// Updates the virtual controller state from bytes in the `repr(C)` layout of [`DS4ReportEx`].
+	///
+	/// Intended for producers which write the extended report into shared memory, see [`DS4ReportEx::from_bytes`] for the layout.
+	/// Same as [`update_ex`](Self::update_ex), the report is validated when [`set_validate_reports`](Self::set_validate_reports) is enabled.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn update_ex_raw(&mut self, bytes: &[u8; mem::size_of::<DS4ReportEx>()]) -> Result<(), Error> {
+		self.update_ex(&DS4ReportEx::from_bytes(bytes))
+	}
+
+	/// Updates the virtual controller state and returns how long the driver took.
+	///
+	/// Same as [`update`](Self::update) but measures the round trip of the submit report request,
+	/// from just before it is issued until its completion, with a monotonic clock.
+	///
+	/// On a healthy system the round trip typically takes well under a millisecond.
+	/// Consistently taking multiple milliseconds points at a congested or misbehaving driver.
+	///
+	/// Returns `None` for updates skipped by [`DetachedPolicy::Ignore`].
+	/// The plain [`update`](Self::update) does not read the clock.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline(never)]
+	pub fn update_timed(&mut self, report: &DS4Report) -> Result<Option<time::Duration>, Error> {
+		match self.submit_update(report, true)? {
+			Submitted::Skipped => Ok(None),
+			Submitted::Sent(elapsed) => Ok(elapsed),
+		}
+	}
+
+	/// Updates the virtual controller state and records the latency since the input was captured.
+	///
+	/// Same as [`update`](Self::update), the latency from `captured_at` until the driver accepted the report
+	/// is recorded in the [latency stats](Self::latency_stats), which are created on first use.
+	/// Updates skipped by [`DetachedPolicy::Ignore`] are not recorded.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline(never)]
+	pub fn update_stamped(&mut self, report: &DS4Report, captured_at: time::Instant) -> Result<(), Error> {
+		if let Submitted::Sent(_) = self.submit_update(report, false)? {
+			self.latency.get_or_insert_with(LatencyStats::default).record(captured_at, time::Instant::now());
+		}
+		Ok(())
+	}
+
+	/// Returns the latencies recorded by `update_stamped`.
+	#[inline]
+	pub fn latency_stats(&self) -> Option<&LatencyStats> {
+		self.latency.as_ref()
+	}
+
+	/// Replaces the latency stats, eg. to change their capacity or to take them.
+	#[inline]
+	pub fn set_latency_stats(&mut self, stats: Option<LatencyStats>) -> Option<LatencyStats> {
+		mem::replace(&mut self.latency, stats)
+	}
+
+	/// Sets the stale input timeout.
+	///
+	/// When set, the target is neutralized by submitting [`DS4Report::neutral`] if no update succeeded within the timeout,
+	/// eg. when the thread producing the updates is stuck while a button is held.
+	/// It is neutralized once per stale period, the next successful update clears the condition.
+	///
+	/// All targets share a single background watchdog thread which submits the neutral reports.
+	/// Enabling the timeout duplicates the client handle for use by the watchdog.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline(never)]
+	pub fn set_stale_timeout(&mut self, timeout: Option<time::Duration>) -> Result<(), Error> {
+		self.set_watch(timeout, true)
+	}
+
+	/// Sets the stale input timeout without a background thread.
+	///
+	/// Same as [`set_stale_timeout`](Self::set_stale_timeout) but the target is only neutralized when it is [pumped](Pump::pump).
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline(never)]
+	pub fn set_stale_timeout_manual(&mut self, timeout: Option<time::Duration>) -> Result<(), Error> {
+		self.set_watch(timeout, false)
+	}
+
+	#[cfg(feature = "unstable_ds4")]
+	fn set_watch(&mut self, timeout: Option<time::Duration>, background: bool) -> Result<(), Error> {
+		match (timeout, &self.watch) {
+			(None, _) => self.watch = None,
+			(Some(timeout), Some(watch)) if watch.is_background() == background => watch.set_timeout(timeout),
+			(Some(timeout), _) => {
+				self.watch = Some(Watch::new(self.client.borrow(), Neutral::DualShock4Wired, self.serial_no, timeout, background)?);
+			},
 		}
+		Ok(())
+	}
+
+	#[inline]
+	pub(crate) fn watch(&self) -> Option<&Arc<Watch>> {
+		self.watch.as_ref()
+	}
 
+	/// Returns if the target was neutralized because its input went stale.
+	#[inline]
+	pub fn is_stale(&self) -> bool {
+		self.watch.as_ref().is_some_and(|watch| watch.is_stale())
+	}
+
+	/// Guards the target against holding its last input when the process panics.
+	///
+	/// While enabled and plugged in, a panic which may end the process submits [`DS4Report::neutral`] and unplugs the target
+	/// before the panic continues, see [`install_panic_guard`] for how and when it runs.
+	/// Enabling the guard installs the panic hook if needed and duplicates the client handle for use by the hook.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline(never)]
+	pub fn set_panic_guard(&mut self, enabled: bool) -> Result<(), Error> {
+		self.sync_panic_guard();
+		match (enabled, &self.panic_guard) {
+			(false, _) => self.panic_guard = None,
+			(true, Some(_)) => (),
+			(true, None) => self.panic_guard = Some(PanicGuard::new(self.client.borrow(), Neutral::DualShock4Wired, self.serial_no)?),
+		}
 		Ok(())
 	}
 
-	// #[inline(never)]
-	// pub fn update_ex(&mut self, report: &DS4ReportEx) -> Result<(), Error> {
-	// 	if !self.is_attached() {
-	// 		return Err(Error::NotPluggedIn);
-	// 	}
-	// 	unimplemented!()
-	// }
+	/// Returns if the target is guarded against panics, see [`set_panic_guard`](Self::set_panic_guard).
+	#[inline]
+	pub fn has_panic_guard(&self) -> bool {
+		self.panic_guard.is_some()
+	}
+
+	/// Request output notifications, the rumble motors and lightbar color set by the host.
+	///
+	/// Returns [`Error::NotificationAlreadyActive`] while a previous request notification is alive,
+	/// it is released when the request notification is dropped or the target is unplugged.
+	/// Unplugging the target cancels the pending request, the listener then receives [`Error::OperationAborted`].
+	/// Share the request with a [`RumbleListener`] to let multiple consumers observe the notifications.
+	#[cfg(feature = "ds4-output")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+	#[inline(never)]
+	pub fn request_notification(&mut self) -> Result<DS4RequestNotification, Error> {
+		self.notifications.retain(|link| link.strong_count() > 0);
+		if self.is_attached() && !self.notifications.is_empty() {
+			return Err(Error::NotificationAlreadyActive);
+		}
+		self.force_request_notification()
+	}
+
+	/// Request notification even if a previous request notification is alive.
+	///
+	/// Notifications may get lost or received by one or more of the listeners.
+	/// Failure to create the event object the notification completes through is reported as [`Error::ResourceCreation`].
+	#[cfg(feature = "ds4-output")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+	#[inline(never)]
+	pub fn force_request_notification(&mut self) -> Result<DS4RequestNotification, Error> {
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+
+		let client = self.client.borrow().try_clone()?;
+		let dsrn = bus::RequestNotification::new(bus::DS4RequestNotification::new(self.serial_no)).map_err(Error::ResourceCreation)?;
+		let dsrn = mem::ManuallyDrop::new(Box::new(dsrn));
+
+		let link = Arc::new(bus::CancelLink::default());
+		self.notifications.retain(|link| link.strong_count() > 0);
+		self.notifications.push(Arc::downgrade(&link));
+
+		let generation = self.generation.get();
+		Ok(DS4RequestNotification { client, dsrn, serial_no: self.serial_no, uid: self.uid, generation, current: self.generation.clone(), attached: true, link, tuning: None, _unpin: marker::PhantomPinned })
+	}
 }
 
+#[cfg(windows)]
 impl<CL: Borrow<Client>> fmt::Debug for DualShock4Wired<CL> {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		f.debug_struct("DualShock4Wired")
+			.field("uid", &self.uid)
 			.field("serial_no", &self.serial_no)
 			.field("vendor_id", &self.id.vendor)
 			.field("product_id", &self.id.product)
+			.field("validate_reports", &self.validate_reports)
+			.field("watch", &self.watch.is_some())
+			.field("panic_guard", &self.panic_guard.is_some())
+			.field("progress", &self.progress.is_some())
+			.field("latency", &self.latency.as_ref().map(LatencyStats::len))
 			.finish()
 	}
 }
 
+#[cfg(windows)]
 impl<CL: Borrow<Client>> Drop for DualShock4Wired<CL> {
 	#[inline]
 	fn drop(&mut self) {
-		let _ = self.unplug();
+		let _ = self.unplug_impl(true);
 	}
 }
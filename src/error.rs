@@ -12,14 +12,29 @@ pub enum Error {
 	///
 	/// It can be installed from the [ViGEmBus](https://github.com/ViGEm/ViGEmBus) repository.
 	BusNotFound,
+	/// The ViGEmBus driver is installed, but its device is disabled or not started.
+	///
+	/// Enable the device in the Device Manager or wait for it to start, see [`BusPresence`](crate::BusPresence).
+	BusDisabled,
 	/// ViGEmBus was found, but accessing it returned an error.
 	BusAccessFailed(u32),
 	/// ViGEmBus was found, but it did not accept this client's version.
 	BusVersionMismatch,
 	/// There was no more room to allocate new targets.
+	///
+	/// Compare with [`Client::attached_count`](crate::Client::attached_count),
+	/// a large number points at targets leaked by this process rather than a system out of slots.
 	NoFreeSlot,
-	// InvalidClient,
+	/// The target was created with another client.
+	///
+	/// Requests through a [`IocpDriver`](crate::IocpDriver) must use targets of the client it drives.
+	InvalidClient,
 	// InvalidTarget,
+	/// The target is of a different kind than expected.
+	///
+	/// Eg. updating a [`Fleet`](crate::Fleet) target with the report of another kind,
+	/// or restoring a serial number now owned by another kind of target.
+	KindMismatch,
 	/// The target is already connected.
 	///
 	/// It is an error to try to plugin an already connected target.
@@ -37,6 +52,54 @@ pub enum Error {
 	UserIndexOutOfRange,
 	/// The operation was aborted.
 	OperationAborted,
+	/// A kernel object required by the target could not be created.
+	///
+	/// Contains the windows error code, eg. when the process ran out of handles.
+	ResourceCreation(u32),
+	/// The target already has a live notification request.
+	///
+	/// Drop the previous request notification first or use `force_request_notification`.
+	NotificationAlreadyActive,
+	/// The completion port of the client is already driven by another [`IocpDriver`](crate::IocpDriver).
+	CompletionPortInUse,
+	/// The device of the target did not appear in time.
+	///
+	/// See [`Xbox360Wired::device_path`](crate::Xbox360Wired::device_path).
+	DeviceNotFound,
+	/// More than one device matches the target.
+	///
+	/// See [`Xbox360Wired::device_path`](crate::Xbox360Wired::device_path).
+	AmbiguousDevice,
+	/// The driver rejected the serial number, eg. another target is plugged in under it.
+	///
+	/// See [`Xbox360Wired::plugin_at`](crate::Xbox360Wired::plugin_at).
+	SerialInUse,
+	/// The profile has settings which cannot be applied to its kind of target.
+	///
+	/// See [`Profile::validate`](crate::Profile::validate).
+	InvalidProfile,
+	/// The target template has settings which cannot be applied to a target.
+	///
+	/// See [`XTargetTemplate::validate`](crate::XTargetTemplate::validate).
+	/// Also returned for a setting out of range, eg. the rate of [`Xbox360Wired::drive`](crate::Xbox360Wired::drive).
+	InvalidConfig,
+	/// The driver rejected the interface version of the client and plugging in targets was not allowed.
+	///
+	/// See [`Client::connect_unchecked`](crate::Client::connect_unchecked).
+	UncheckedClient,
+	/// Windows failed to install the device of the plugged in target.
+	///
+	/// Contains the [device manager problem code](https://learn.microsoft.com/en-us/windows-hardware/drivers/install/device-manager-error-messages) of the device,
+	/// eg. 28 when its driver is missing from the driver store. Reinstalling ViGEmBus usually repairs the driver store.
+	///
+	/// See [`Xbox360Wired::wait_ready`](crate::Xbox360Wired::wait_ready).
+	DeviceInstallFailed(u32),
+	/// The report failed validation and was not submitted.
+	///
+	/// See [`DualShock4Wired::set_validate_reports`](crate::DualShock4Wired::set_validate_reports).
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	InvalidReport(crate::DS4ValidationError),
 }
 
 impl From<u32> for Error {
@@ -46,19 +109,43 @@ impl From<u32> for Error {
 	}
 }
 
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl From<crate::DS4ValidationError> for Error {
+	#[inline]
+	fn from(error: crate::DS4ValidationError) -> Error {
+		Error::InvalidReport(error)
+	}
+}
+
 impl fmt::Display for Error {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match *self {
 			Error::WinError(err) => write!(f, "win error: {}", err),
 			Error::BusNotFound => f.write_str("bus not found"),
+			Error::BusDisabled => f.write_str("bus disabled"),
 			Error::BusAccessFailed(err) => write!(f, "bus access failed: {}", err),
 			Error::BusVersionMismatch => f.write_str("bus version mismatch"),
 			Error::NoFreeSlot => f.write_str("no free slot"),
+			Error::InvalidClient => f.write_str("invalid client"),
+			Error::KindMismatch => f.write_str("kind mismatch"),
 			Error::AlreadyConnected => f.write_str("already connected"),
 			Error::NotPluggedIn => f.write_str("not plugged in"),
 			Error::TargetNotReady => f.write_str("target not ready"),
 			Error::UserIndexOutOfRange => f.write_str("user index out of range"),
 			Error::OperationAborted => f.write_str("operation aborted"),
+			Error::ResourceCreation(err) => write!(f, "resource creation failed: {}", err),
+			Error::NotificationAlreadyActive => f.write_str("notification already active"),
+			Error::CompletionPortInUse => f.write_str("completion port in use"),
+			Error::DeviceNotFound => f.write_str("device not found"),
+			Error::AmbiguousDevice => f.write_str("ambiguous device"),
+			Error::SerialInUse => f.write_str("serial number in use"),
+			Error::InvalidProfile => f.write_str("invalid profile"),
+			Error::InvalidConfig => f.write_str("invalid config"),
+			Error::UncheckedClient => f.write_str("unchecked client"),
+			Error::DeviceInstallFailed(code) => write!(f, "device install failed: problem code {}", code),
+			#[cfg(feature = "unstable_ds4")]
+			Error::InvalidReport(err) => write!(f, "invalid report: {}", err),
 		}
 	}
 }
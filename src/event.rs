@@ -1,7 +1,10 @@
 use std::{fmt, ptr};
+use std::cell::RefCell;
 use winapi::um::handleapi::*;
 use winapi::um::synchapi::*;
+use winapi::um::errhandlingapi::GetLastError;
 use winapi::shared::ntdef::HANDLE;
+use crate::Error;
 
 #[repr(transparent)]
 pub struct Event {
@@ -9,11 +12,62 @@ pub struct Event {
 }
 impl Event {
 	#[inline]
-	pub fn new(manual_reset: bool, initial_state: bool) -> Event {
+	pub fn new(manual_reset: bool, initial_state: bool) -> Result<Event, u32> {
 		unsafe {
 			let handle = CreateEventW(ptr::null_mut(), manual_reset as i32, initial_state as i32, ptr::null());
-			debug_assert!(!handle.is_null());
-			Event { handle }
+			if handle.is_null() {
+				return Err(GetLastError());
+			}
+			Ok(Event { handle })
+		}
+	}
+	/// Placeholder for an event created later with `create_if_null`.
+	#[inline]
+	pub const fn null() -> Event {
+		Event { handle: ptr::null_mut() }
+	}
+	#[inline]
+	pub fn create_if_null(&mut self, manual_reset: bool, initial_state: bool) -> Result<(), u32> {
+		self.create_if_null_with(|| Event::new(manual_reset, initial_state))
+	}
+	#[inline]
+	fn create_if_null_with(&mut self, create: impl FnOnce() -> Result<Event, u32>) -> Result<(), u32> {
+		if self.handle.is_null() {
+			*self = create()?;
+		}
+		Ok(())
+	}
+	/// Returns the event to wait on for a synchronous request.
+	///
+	/// Falls back to the calling thread's shared auto-reset event if this event is null.
+	/// Synchronous requests block the thread until they complete, so no two of them wait on the shared event at once.
+	///
+	/// The event is reset before it is handed out. A request completing synchronously still signals its event
+	/// and nothing consumes the signal, the next request to pend would wake up before it completed.
+	/// A dedicated event follows its target across threads, so the stale signal can come from a request made on another thread.
+	#[inline]
+	pub fn sync(&self) -> Result<SyncEvent, Error> {
+		self.sync_with(|| Event::new(false, false))
+	}
+	// Creates missing events with the constructor, the tests inject a failing one
+	#[inline]
+	fn sync_with(&self, create: impl Fn() -> Result<Event, u32>) -> Result<SyncEvent, Error> {
+		if !self.handle.is_null() {
+			return Ok(SyncEvent::reset(self.handle));
+		}
+		let shared = THREAD_EVENT.try_with(|event| {
+			let mut event = event.borrow_mut();
+			event.create_if_null_with(&create)?;
+			Ok(event.handle)
+		});
+		match shared {
+			Ok(Ok(handle)) => Ok(SyncEvent::reset(handle)),
+			Ok(Err(err)) => Err(Error::ResourceCreation(err)),
+			// The thread is exiting, eg. a target dropped from a thread local destructor
+			Err(_) => {
+				let event = create().map_err(Error::ResourceCreation)?;
+				Ok(SyncEvent { handle: event.handle, _owned: Some(event) })
+			},
 		}
 	}
 	#[allow(dead_code)]
@@ -23,6 +77,23 @@ impl Event {
 	}
 }
 
+thread_local! {
+	static THREAD_EVENT: RefCell<Event> = const { RefCell::new(Event::null()) };
+}
+
+/// Event borrowed for the duration of a synchronous request, see [`Event::sync`].
+pub struct SyncEvent {
+	pub(crate) handle: HANDLE,
+	_owned: Option<Event>,
+}
+impl SyncEvent {
+	#[inline]
+	fn reset(handle: HANDLE) -> SyncEvent {
+		unsafe { ResetEvent(handle) };
+		SyncEvent { handle, _owned: None }
+	}
+}
+
 unsafe impl Sync for Event {}
 unsafe impl Send for Event {}
 
@@ -37,6 +108,51 @@ impl fmt::Debug for Event {
 impl Drop for Event {
 	#[inline]
 	fn drop(&mut self) {
-		unsafe { CloseHandle(self.handle) };
+		if !self.handle.is_null() {
+			unsafe { CloseHandle(self.handle) };
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::thread;
+	use winapi::shared::winerror::ERROR_NO_SYSTEM_RESOURCES;
+	use super::*;
+
+	fn exhausted() -> Result<Event, u32> {
+		Err(ERROR_NO_SYSTEM_RESOURCES)
+	}
+
+	#[test]
+	fn sync_creation_failed() {
+		// On a fresh thread, its shared event is created by the first request
+		thread::spawn(|| {
+			assert_eq!(Event::null().sync_with(exhausted).err(), Some(Error::ResourceCreation(ERROR_NO_SYSTEM_RESOURCES)));
+			// The failure is not remembered, the next request creates the shared event
+			assert!(Event::null().sync().is_ok());
+			// Once created the shared event is reused
+			assert!(Event::null().sync_with(exhausted).is_ok());
+		}).join().unwrap();
+	}
+
+	#[test]
+	fn sync_dedicated() {
+		let event = Event::new(false, false).unwrap();
+		let sync = event.sync_with(exhausted).ok().unwrap();
+		assert_eq!(sync.handle, event.handle);
+	}
+
+	#[test]
+	fn create_if_null_failed() {
+		let mut event = Event::null();
+		assert_eq!(event.create_if_null_with(exhausted), Err(ERROR_NO_SYSTEM_RESOURCES));
+		assert!(event.handle.is_null());
+
+		event.create_if_null(false, false).unwrap();
+		let handle = event.handle;
+		assert!(!handle.is_null());
+		assert_eq!(event.create_if_null_with(exhausted), Ok(()));
+		assert_eq!(event.handle, handle);
 	}
 }
@@ -0,0 +1,165 @@
+use std::collections::VecDeque;
+use std::{fmt, time};
+use crate::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+
+/// Hands the values of a single writer to many readers, each reading at its own pace.
+///
+/// The values are kept in a ring of fixed capacity and every reader keeps a [`Cursor`] into the sequence of values written.
+/// Writing never waits for the readers, a reader falling more than the capacity behind skips the overwritten values and counts them as missed.
+///
+/// The writer is shared by a number of handles, it should stop once the last handle is [released](Self::release).
+///
+/// Only built on [`crate::sync`] and model checked with loom, see `tests/loom.rs`.
+pub(crate) struct Fanout<T> {
+	state: Mutex<State<T>>,
+	cond: Condvar,
+}
+
+struct State<T> {
+	ring: VecDeque<T>,
+	capacity: usize,
+	// Sequence number of the oldest value in the ring
+	head: u64,
+	handles: usize,
+	closed: bool,
+}
+
+impl<T> State<T> {
+	fn end(&self) -> u64 {
+		self.head + self.ring.len() as u64
+	}
+}
+
+/// Position of a reader in the sequence of values.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Cursor {
+	next: u64,
+	missed: u64,
+}
+
+impl Cursor {
+	/// Returns the number of values skipped because they were overwritten before they were read.
+	#[inline]
+	pub fn missed(&self) -> u64 {
+		self.missed
+	}
+}
+
+/// Result of reading the next value.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Next<T> {
+	Value(T),
+	/// Nothing was written within the timeout.
+	Empty,
+	/// The writer closed and every value was read.
+	Closed,
+}
+
+impl<T: Copy> Fanout<T> {
+	/// Creates a ring holding up to `capacity` values, shared by a single handle.
+	#[inline]
+	pub fn new(capacity: usize) -> Fanout<T> {
+		let capacity = capacity.max(1);
+		let state = State { ring: VecDeque::with_capacity(capacity), capacity, head: 0, handles: 1, closed: false };
+		Fanout { state: Mutex::new(state), cond: Condvar::new() }
+	}
+
+	fn lock(&self) -> MutexGuard<'_, State<T>> {
+		self.state.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+
+	/// Adds a handle and returns a cursor reading the values written from now on.
+	#[inline]
+	pub fn acquire(&self) -> Cursor {
+		let mut state = self.lock();
+		state.handles += 1;
+		Cursor { next: state.end(), missed: 0 }
+	}
+
+	/// Removes a handle, returns true if it was the last.
+	#[inline]
+	pub fn release(&self) -> bool {
+		let mut state = self.lock();
+		state.handles -= 1;
+		state.handles == 0
+	}
+
+	/// Returns if any handles are left.
+	///
+	/// Handles are only acquired through other handles, once the last is released the writer should stop.
+	#[inline]
+	pub fn is_shared(&self) -> bool {
+		self.lock().handles != 0
+	}
+
+	/// Writes a value, overwriting the oldest value if the ring is full.
+	#[inline]
+	pub fn publish(&self, value: T) {
+		let mut state = self.lock();
+		if state.ring.len() == state.capacity {
+			state.ring.pop_front();
+			state.head += 1;
+		}
+		state.ring.push_back(value);
+		self.cond.notify_all();
+	}
+
+	/// Tells the readers no more values follow, values written before closing remain readable.
+	#[inline]
+	pub fn close(&self) {
+		self.lock().closed = true;
+		self.cond.notify_all();
+	}
+
+	/// Returns if the writer closed.
+	#[inline]
+	pub fn is_closed(&self) -> bool {
+		self.lock().closed
+	}
+
+	/// Reads the next value, waiting up to the timeout for one to be written.
+	///
+	/// If `latest` is true the values in between are skipped and the most recent value is read, these do not count as missed.
+	pub fn next(&self, cursor: &mut Cursor, latest: bool, timeout: time::Duration) -> Next<T> {
+		let deadline = time::Instant::now().checked_add(timeout);
+		let mut state = self.lock();
+		loop {
+			let end = state.end();
+			if cursor.next < end {
+				if latest {
+					cursor.next = end - 1;
+				} else if cursor.next < state.head {
+					cursor.missed += state.head - cursor.next;
+					cursor.next = state.head;
+				}
+				let value = state.ring[(cursor.next - state.head) as usize];
+				cursor.next += 1;
+				return Next::Value(value);
+			}
+			if state.closed {
+				return Next::Closed;
+			}
+			state = match deadline {
+				None => self.cond.wait(state).unwrap_or_else(PoisonError::into_inner),
+				Some(deadline) => {
+					let now = time::Instant::now();
+					if now >= deadline {
+						return Next::Empty;
+					}
+					self.cond.wait_timeout(state, deadline - now).unwrap_or_else(PoisonError::into_inner).0
+				},
+			};
+		}
+	}
+}
+
+impl<T> fmt::Debug for Fanout<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+		f.debug_struct("Fanout")
+			.field("written", &state.end())
+			.field("handles", &state.handles)
+			.field("closed", &state.closed)
+			.finish()
+	}
+}
@@ -0,0 +1,129 @@
+/*!
+Crate features enabled in the build.
+
+Downstream crates can branch on the features enabled in the final dependency graph instead of mirroring the feature flags,
+the constants are known at compile time and the untaken branches are optimized out.
+The feature gated API itself still requires the feature to name it, the constants only tell if it exists.
+
+```
+if vigem_client::features::XUSB_RUMBLE {
+	// Offer rumble support
+}
+println!("{}", vigem_client::features::summary());
+```
+
+The deprecated aliases of `xusb-rumble` enable it and are reported as `xusb-rumble`.
+*/
+
+use std::str;
+
+/// The [`DualShock4Wired`](crate::DualShock4Wired) input report and its methods to update the target.
+pub const UNSTABLE_DS4: bool = cfg!(feature = "unstable_ds4");
+
+/// Rumble and led notifications of [`Xbox360Wired`](crate::Xbox360Wired) targets.
+pub const XUSB_RUMBLE: bool = cfg!(feature = "xusb-rumble");
+
+/// Rumble and lightbar notifications of [`DualShock4Wired`](crate::DualShock4Wired) targets, implies [`UNSTABLE_DS4`].
+pub const DS4_OUTPUT: bool = cfg!(feature = "ds4-output");
+
+/// The `compat` module with free functions named after the C ViGEmClient API.
+pub const C_COMPAT: bool = cfg!(feature = "c-compat");
+
+/// The `testgen` module with deterministic input generators.
+pub const TEST_UTIL: bool = cfg!(feature = "test-util");
+
+/// The `DwmVblank` clock source.
+pub const DWM: bool = cfg!(feature = "dwm");
+
+/// `Serialize` and `Deserialize` for the configuration types.
+pub const SERDE: bool = cfg!(feature = "serde");
+
+/// Tracking of the service handles owned by clients, active in builds with debug assertions.
+pub const DEBUG_HANDLES: bool = cfg!(feature = "debug-handles");
+
+/// The `ffi` module exporting the core operations with a C ABI.
+pub const FFI: bool = cfg!(feature = "ffi");
+
+/// The `GenericGamepadState` conversions from the gamepad states of modern capture APIs.
+pub const INTEROP: bool = cfg!(feature = "interop");
+
+/// All the features by name in the order of the crate documentation.
+pub const ALL: [(&str, bool); 10] = [
+	("unstable_ds4", UNSTABLE_DS4),
+	("xusb-rumble", XUSB_RUMBLE),
+	("ds4-output", DS4_OUTPUT),
+	("c-compat", C_COMPAT),
+	("test-util", TEST_UTIL),
+	("dwm", DWM),
+	("serde", SERDE),
+	("debug-handles", DEBUG_HANDLES),
+	("ffi", FFI),
+	("interop", INTEROP),
+];
+
+/// Returns the crate version and the enabled features for diagnostics output.
+///
+/// Eg. `vigem-client 0.1.4 (unstable_ds4, xusb-rumble)` or `vigem-client 0.1.4 (no features)`.
+#[inline]
+pub fn summary() -> &'static str {
+	// The summary is built at compile time
+	unsafe { str::from_utf8_unchecked(&SUMMARY) }
+}
+
+const PREFIX: &str = concat!("vigem-client ", env!("CARGO_PKG_VERSION"), " (");
+const NONE: &str = "no features";
+const SEPARATOR: &str = ", ";
+
+const SUMMARY: [u8; summary_len()] = summary_bytes();
+
+const fn summary_len() -> usize {
+	let mut len = PREFIX.len() + 1;
+	let mut count = 0;
+	let mut i = 0;
+	while i < ALL.len() {
+		if ALL[i].1 {
+			if count > 0 {
+				len += SEPARATOR.len();
+			}
+			len += ALL[i].0.len();
+			count += 1;
+		}
+		i += 1;
+	}
+	if count == 0 {
+		len += NONE.len();
+	}
+	len
+}
+
+const fn summary_bytes() -> [u8; summary_len()] {
+	let bytes = [0u8; summary_len()];
+	let (mut bytes, mut len) = copy(bytes, 0, PREFIX);
+	let mut count = 0;
+	let mut i = 0;
+	while i < ALL.len() {
+		if ALL[i].1 {
+			if count > 0 {
+				(bytes, len) = copy(bytes, len, SEPARATOR);
+			}
+			(bytes, len) = copy(bytes, len, ALL[i].0);
+			count += 1;
+		}
+		i += 1;
+	}
+	if count == 0 {
+		(bytes, len) = copy(bytes, len, NONE);
+	}
+	copy(bytes, len, ")").0
+}
+
+const fn copy(mut bytes: [u8; summary_len()], mut len: usize, s: &str) -> ([u8; summary_len()], usize) {
+	let s = s.as_bytes();
+	let mut i = 0;
+	while i < s.len() {
+		bytes[len] = s[i];
+		len += 1;
+		i += 1;
+	}
+	(bytes, len)
+}
@@ -0,0 +1,366 @@
+/*!
+C ABI exporting the core operations for other languages.
+
+Build the crate as a dynamic library to call it from C, C# or Python:
+
+```text
+cargo rustc --release --features ffi --crate-type cdylib
+cargo rustc --release --features ffi,unstable_ds4 --crate-type cdylib
+```
+
+The declarations are in `include/vigem_client.h`, regenerate it with `cbindgen --config cbindgen.toml --output include/vigem_client.h`.
+The `vigem_rs_ds4_*` functions are declared under `VIGEM_RS_DS4` and only exported with the `unstable_ds4` feature.
+
+Conventions:
+
+* Every function returns `VIGEM_RS_OK` or a negative error code, [`vigem_rs_last_error_message`] describes the last error of the calling thread.
+* Clients and targets are opaque handles, they are never dereferenced and a handle is never reused.
+  Destroying a handle twice or passing a handle of another kind fails with `VIGEM_RS_INVALID_HANDLE` instead of corrupting memory.
+* Handles can be used from any thread, requests through the same target are serialized.
+* Targets keep their client connected, the client handle can be destroyed before its targets.
+* Panics are caught at the boundary and reported as `VIGEM_RS_PANIC`.
+* The reports are the `repr(C)` layouts of [`XGamepad`] and [`DS4Report`].
+*/
+
+use std::{cell, ffi, panic, ptr};
+use std::collections::HashMap;
+use std::os::raw::c_char;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::*;
+
+/// Success.
+pub const VIGEM_RS_OK: i32 = 0;
+/// The operation failed, see [`vigem_rs_last_error_message`].
+pub const VIGEM_RS_ERROR: i32 = -1;
+/// The handle is not a live handle of the expected kind, eg. it was already destroyed.
+pub const VIGEM_RS_INVALID_HANDLE: i32 = -2;
+/// A required pointer argument is null.
+pub const VIGEM_RS_NULL_POINTER: i32 = -3;
+/// The operation panicked, see [`vigem_rs_last_error_message`].
+pub const VIGEM_RS_PANIC: i32 = -4;
+/// The ViGEmBus driver is not installed, or its device is disabled or not started.
+pub const VIGEM_RS_BUS_NOT_FOUND: i32 = -5;
+/// The target is not plugged in.
+pub const VIGEM_RS_NOT_PLUGGED_IN: i32 = -6;
+
+/// Opaque handle of a client.
+pub enum VigemRsClient {}
+/// Opaque handle of an Xbox360 target.
+pub enum VigemRsX360 {}
+/// Opaque handle of a DualShock4 target.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+pub enum VigemRsDs4 {}
+
+type Shared<T> = Arc<Mutex<T>>;
+
+enum Object {
+	Client(Arc<Client>),
+	Target(TargetKind, Shared<AnyTarget>),
+}
+
+// Live objects by handle, handles count up from one and are never reused
+static OBJECTS: Mutex<Option<HashMap<usize, Object>>> = Mutex::new(None);
+static NEXT_HANDLE: AtomicUsize = AtomicUsize::new(1);
+
+thread_local! {
+	static LAST_ERROR: cell::RefCell<ffi::CString> = cell::RefCell::new(ffi::CString::default());
+}
+
+fn objects() -> MutexGuard<'static, Option<HashMap<usize, Object>>> {
+	OBJECTS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn lock<T>(shared: &Shared<T>) -> MutexGuard<'_, T> {
+	shared.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn insert(object: Object) -> usize {
+	let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+	objects().get_or_insert_with(HashMap::new).insert(handle, object);
+	handle
+}
+
+fn set_last_error(message: &str) {
+	// Interior nul bytes would truncate the message, replace them
+	let message = ffi::CString::new(message.replace('\0', " ")).unwrap_or_default();
+	LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+fn fail(code: i32, message: &str) -> i32 {
+	set_last_error(message);
+	code
+}
+
+fn error(err: Error) -> i32 {
+	let code = match err {
+		Error::BusNotFound | Error::BusDisabled => VIGEM_RS_BUS_NOT_FOUND,
+		Error::NotPluggedIn => VIGEM_RS_NOT_PLUGGED_IN,
+		_ => VIGEM_RS_ERROR,
+	};
+	fail(code, &err.to_string())
+}
+
+fn invalid_handle(kind: &str) -> i32 {
+	fail(VIGEM_RS_INVALID_HANDLE, &format!("invalid {} handle", kind))
+}
+
+// Runs the body of an exported function, nothing may unwind into the caller
+fn boundary(f: impl FnOnce() -> Result<(), i32>) -> i32 {
+	match panic::catch_unwind(panic::AssertUnwindSafe(f)) {
+		Ok(Ok(())) => VIGEM_RS_OK,
+		Ok(Err(code)) => code,
+		Err(payload) => {
+			let message = payload.downcast_ref::<&str>().copied()
+				.or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+				.unwrap_or("unknown panic");
+			fail(VIGEM_RS_PANIC, &format!("panic: {}", message))
+		},
+	}
+}
+
+fn client(handle: *mut VigemRsClient) -> Result<Arc<Client>, i32> {
+	match objects().as_ref().and_then(|objects| objects.get(&(handle as usize))) {
+		Some(Object::Client(client)) => Ok(client.clone()),
+		_ => Err(invalid_handle("client")),
+	}
+}
+
+fn kind_name(kind: TargetKind) -> &'static str {
+	match kind {
+		TargetKind::Xbox360Wired => "x360",
+		#[cfg(feature = "unstable_ds4")]
+		TargetKind::DualShock4Wired => "ds4",
+	}
+}
+
+fn target(handle: usize, kind: TargetKind) -> Result<Shared<AnyTarget>, i32> {
+	match objects().as_ref().and_then(|objects| objects.get(&handle)) {
+		Some(Object::Target(target_kind, target)) if *target_kind == kind => Ok(target.clone()),
+		_ => Err(invalid_handle(kind_name(kind))),
+	}
+}
+
+fn create(client: *mut VigemRsClient, kind: TargetKind) -> Result<usize, i32> {
+	let client = self::client(client)?;
+	let target = match kind {
+		TargetKind::Xbox360Wired => Xbox360Wired::new(client, TargetId::XBOX360_WIRED).into(),
+		#[cfg(feature = "unstable_ds4")]
+		TargetKind::DualShock4Wired => DualShock4Wired::new(client, TargetId::DUALSHOCK4_WIRED).into(),
+	};
+	Ok(insert(Object::Target(kind, Arc::new(Mutex::new(target)))))
+}
+
+fn plugin(handle: usize, kind: TargetKind) -> Result<(), i32> {
+	let target = self::target(handle, kind)?;
+	let mut target = lock(&target);
+	target.plugin().map_err(error)?;
+	target.wait_ready().map_err(error)
+}
+
+fn unplug(handle: usize, kind: TargetKind) -> Result<(), i32> {
+	let target = self::target(handle, kind)?;
+	let result = lock(&target).unplug();
+	result.map_err(error)
+}
+
+fn destroy(handle: usize, kind: TargetKind) -> Result<(), i32> {
+	remove(handle, kind_name(kind), |object| matches!(object, Object::Target(target_kind, _) if *target_kind == kind))
+}
+
+// Removes the object if the predicate accepts it, the object is dropped after releasing the lock
+fn remove(handle: usize, kind: &str, f: impl FnOnce(&Object) -> bool) -> Result<(), i32> {
+	let object = {
+		let mut objects = objects();
+		match objects.as_mut() {
+			Some(objects) if objects.get(&handle).is_some_and(f) => objects.remove(&handle),
+			_ => None,
+		}
+	};
+	match object {
+		Some(object) => {
+			drop(object);
+			Ok(())
+		},
+		None => Err(invalid_handle(kind)),
+	}
+}
+
+/// Returns the message of the last error of the calling thread.
+///
+/// The string is empty if no error occurred yet and is valid until the next failing call on the thread.
+#[no_mangle]
+pub extern "C" fn vigem_rs_last_error_message() -> *const c_char {
+	LAST_ERROR.with(|last| last.borrow().as_ptr())
+}
+
+/// Connects to the ViGEmBus driver.
+///
+/// # Safety
+///
+/// `client` must be valid for writes, it receives the client handle or null on failure.
+#[no_mangle]
+pub unsafe extern "C" fn vigem_rs_connect(client: *mut *mut VigemRsClient) -> i32 {
+	boundary(|| {
+		if client.is_null() {
+			return Err(fail(VIGEM_RS_NULL_POINTER, "client is null"));
+		}
+		*client = ptr::null_mut();
+		let connected = Client::connect().map_err(error)?;
+		*client = insert(Object::Client(Arc::new(connected))) as *mut VigemRsClient;
+		Ok(())
+	})
+}
+
+/// Destroys the client handle.
+///
+/// Targets created with the client keep it connected until they are destroyed.
+///
+/// # Safety
+///
+/// Any value is accepted as handle.
+#[no_mangle]
+pub unsafe extern "C" fn vigem_rs_client_destroy(client: *mut VigemRsClient) -> i32 {
+	boundary(|| remove(client as usize, "client", |object| matches!(object, Object::Client(_))))
+}
+
+/// Creates an Xbox360 target with the client, the target is not plugged in.
+///
+/// # Safety
+///
+/// `target` must be valid for writes, it receives the target handle or null on failure.
+#[no_mangle]
+pub unsafe extern "C" fn vigem_rs_x360_create(client: *mut VigemRsClient, target: *mut *mut VigemRsX360) -> i32 {
+	boundary(|| {
+		if target.is_null() {
+			return Err(fail(VIGEM_RS_NULL_POINTER, "target is null"));
+		}
+		*target = ptr::null_mut();
+		*target = create(client, TargetKind::Xbox360Wired)? as *mut VigemRsX360;
+		Ok(())
+	})
+}
+
+/// Plugs in the Xbox360 target and waits until it is ready.
+///
+/// # Safety
+///
+/// Any value is accepted as handle.
+#[no_mangle]
+pub unsafe extern "C" fn vigem_rs_x360_plugin(target: *mut VigemRsX360) -> i32 {
+	boundary(|| plugin(target as usize, TargetKind::Xbox360Wired))
+}
+
+/// Updates the Xbox360 target.
+///
+/// # Safety
+///
+/// `gamepad` must be valid for reads.
+#[no_mangle]
+pub unsafe extern "C" fn vigem_rs_x360_update(target: *mut VigemRsX360, gamepad: *const XGamepad) -> i32 {
+	boundary(|| {
+		let target = self::target(target as usize, TargetKind::Xbox360Wired)?;
+		if gamepad.is_null() {
+			return Err(fail(VIGEM_RS_NULL_POINTER, "gamepad is null"));
+		}
+		let gamepad = *gamepad;
+		let result = lock(&target).submit_xbox(&gamepad);
+		result.map_err(error)
+	})
+}
+
+/// Unplugs the Xbox360 target, it can be plugged in again.
+///
+/// # Safety
+///
+/// Any value is accepted as handle.
+#[no_mangle]
+pub unsafe extern "C" fn vigem_rs_x360_unplug(target: *mut VigemRsX360) -> i32 {
+	boundary(|| unplug(target as usize, TargetKind::Xbox360Wired))
+}
+
+/// Destroys the Xbox360 target handle, unplugging the target.
+///
+/// # Safety
+///
+/// Any value is accepted as handle.
+#[no_mangle]
+pub unsafe extern "C" fn vigem_rs_x360_destroy(target: *mut VigemRsX360) -> i32 {
+	boundary(|| destroy(target as usize, TargetKind::Xbox360Wired))
+}
+
+/// Creates a DualShock4 target with the client, the target is not plugged in.
+///
+/// # Safety
+///
+/// `target` must be valid for writes, it receives the target handle or null on failure.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[no_mangle]
+pub unsafe extern "C" fn vigem_rs_ds4_create(client: *mut VigemRsClient, target: *mut *mut VigemRsDs4) -> i32 {
+	boundary(|| {
+		if target.is_null() {
+			return Err(fail(VIGEM_RS_NULL_POINTER, "target is null"));
+		}
+		*target = ptr::null_mut();
+		*target = create(client, TargetKind::DualShock4Wired)? as *mut VigemRsDs4;
+		Ok(())
+	})
+}
+
+/// Plugs in the DualShock4 target and waits until it is ready.
+///
+/// # Safety
+///
+/// Any value is accepted as handle.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[no_mangle]
+pub unsafe extern "C" fn vigem_rs_ds4_plugin(target: *mut VigemRsDs4) -> i32 {
+	boundary(|| plugin(target as usize, TargetKind::DualShock4Wired))
+}
+
+/// Updates the DualShock4 target.
+///
+/// # Safety
+///
+/// `report` must be valid for reads.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[no_mangle]
+pub unsafe extern "C" fn vigem_rs_ds4_update(target: *mut VigemRsDs4, report: *const DS4Report) -> i32 {
+	boundary(|| {
+		let target = self::target(target as usize, TargetKind::DualShock4Wired)?;
+		if report.is_null() {
+			return Err(fail(VIGEM_RS_NULL_POINTER, "report is null"));
+		}
+		let report = *report;
+		let result = lock(&target).submit_ds4(&report);
+		result.map_err(error)
+	})
+}
+
+/// Unplugs the DualShock4 target, it can be plugged in again.
+///
+/// # Safety
+///
+/// Any value is accepted as handle.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[no_mangle]
+pub unsafe extern "C" fn vigem_rs_ds4_unplug(target: *mut VigemRsDs4) -> i32 {
+	boundary(|| unplug(target as usize, TargetKind::DualShock4Wired))
+}
+
+/// Destroys the DualShock4 target handle, unplugging the target.
+///
+/// # Safety
+///
+/// Any value is accepted as handle.
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[no_mangle]
+pub unsafe extern "C" fn vigem_rs_ds4_destroy(target: *mut VigemRsDs4) -> i32 {
+	boundary(|| destroy(target as usize, TargetKind::DualShock4Wired))
+}
@@ -0,0 +1,292 @@
+use crate::*;
+
+/// Transforms gamepad states before they are submitted.
+///
+/// The default filter passes gamepad states through unchanged.
+///
+/// ```
+/// let filter = vigem_client::InputFilter::compensate_xinput_deadzone();
+///
+/// let gamepad = vigem_client::XGamepad {
+/// 	left_trigger: 1,
+/// 	..Default::default()
+/// };
+///
+/// // The slightest trigger pull ends up just past the XInput trigger threshold
+/// let filtered = filter.apply(&gamepad);
+/// assert_eq!(filtered.left_trigger, vigem_client::consts::TRIGGER_THRESHOLD + 1);
+/// ```
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputFilter {
+	/// Remaps the axes before any other stage, `None` to disable.
+	pub axis_matrix: Option<AxisMatrix>,
+	/// Radial deadzone of the left thumbstick to compensate for, zero to disable.
+	pub left_thumb_deadzone: u16,
+	/// Radial deadzone of the right thumbstick to compensate for, zero to disable.
+	pub right_thumb_deadzone: u16,
+	/// Trigger threshold to compensate for, zero to disable.
+	pub trigger_threshold: u8,
+	/// Quarter turns to rotate the dpad clockwise, for rotated devices.
+	pub dpad_rotation: u8,
+	/// Swaps the left and right dpad buttons, applied after rotation.
+	pub dpad_mirror_horizontal: bool,
+	/// Swaps the up and down dpad buttons, applied after rotation.
+	pub dpad_mirror_vertical: bool,
+}
+
+impl InputFilter {
+	/// Filter which passes gamepad states through unchanged.
+	#[inline]
+	pub const fn new() -> InputFilter {
+		InputFilter {
+			axis_matrix: None,
+			left_thumb_deadzone: 0,
+			right_thumb_deadzone: 0,
+			trigger_threshold: 0,
+			dpad_rotation: 0,
+			dpad_mirror_horizontal: false,
+			dpad_mirror_vertical: false,
+		}
+	}
+
+	/// Filter compensating for the deadzones recommended by XInput.
+	///
+	/// Games applying the standard XInput deadzones discard small inputs and rescale the rest.
+	/// This filter expands any non-zero input proportionally to just past the deadzone boundary,
+	/// such that after the game applies its deadzone the original response curve is recovered.
+	#[inline]
+	pub const fn compensate_xinput_deadzone() -> InputFilter {
+		InputFilter {
+			left_thumb_deadzone: consts::LEFT_THUMB_DEADZONE,
+			right_thumb_deadzone: consts::RIGHT_THUMB_DEADZONE,
+			trigger_threshold: consts::TRIGGER_THRESHOLD,
+			..InputFilter::new()
+		}
+	}
+
+	/// Applies the filter to a gamepad state.
+	#[inline]
+	pub fn apply(&self, gamepad: &XGamepad) -> XGamepad {
+		let gamepad = &match &self.axis_matrix {
+			Some(matrix) => matrix.apply(gamepad),
+			None => *gamepad,
+		};
+		let mut result = *gamepad;
+		if self.left_thumb_deadzone != 0 {
+			let (x, y) = expand_thumb(gamepad.thumb_lx, gamepad.thumb_ly, self.left_thumb_deadzone);
+			result.thumb_lx = x;
+			result.thumb_ly = y;
+		}
+		if self.right_thumb_deadzone != 0 {
+			let (x, y) = expand_thumb(gamepad.thumb_rx, gamepad.thumb_ry, self.right_thumb_deadzone);
+			result.thumb_rx = x;
+			result.thumb_ry = y;
+		}
+		if self.trigger_threshold != 0 {
+			result.left_trigger = expand_trigger(gamepad.left_trigger, self.trigger_threshold);
+			result.right_trigger = expand_trigger(gamepad.right_trigger, self.trigger_threshold);
+		}
+		// Most filters leave the dpad alone, skipping the stage halves the cost of a passthrough filter (see benches/hotpaths.rs)
+		if self.dpad_rotation & 3 != 0 || self.dpad_mirror_horizontal || self.dpad_mirror_vertical {
+			result.buttons = result.buttons
+				.rotate_dpad_cw(self.dpad_rotation)
+				.mirror_dpad(self.dpad_mirror_horizontal, self.dpad_mirror_vertical);
+		}
+		result
+	}
+}
+
+/// Analog axes of the gamepad, the rows and columns of the [`AxisMatrix`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Axis {
+	LX = 0,
+	LY = 1,
+	RX = 2,
+	RY = 3,
+	LT = 4,
+	RT = 5,
+}
+
+impl Axis {
+	/// All axes in matrix order.
+	pub const ALL: [Axis; 6] = [Axis::LX, Axis::LY, Axis::RX, Axis::RY, Axis::LT, Axis::RT];
+
+	/// Returns if the axis is a trigger.
+	#[inline]
+	pub const fn is_trigger(self) -> bool {
+		matches!(self, Axis::LT | Axis::RT)
+	}
+}
+
+/// Remaps the analog axes of a gamepad state by a matrix, for unusual physical layouts.
+///
+/// The axes `[lx, ly, rx, ry, lt, rt]` are normalized to `-1.0..=1.0` for the thumbsticks and `0.0..=1.0` for the triggers,
+/// every output axis is the dot product of its row in `matrix` with the input axes plus its `bias`.
+/// The outputs are clamped to their range.
+///
+/// ```
+/// use vigem_client::{Axis, AxisMatrix, XGamepad};
+///
+/// let matrix = AxisMatrix::identity().swap_sticks().invert_axis(Axis::LY);
+///
+/// let gamepad = XGamepad { thumb_lx: 1000, thumb_ry: 2000, ..Default::default() };
+/// let remapped = matrix.apply(&gamepad);
+/// assert_eq!((remapped.thumb_rx, remapped.thumb_ly), (1000, -2000));
+/// ```
+///
+/// Matrices compare and hash bitwise.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AxisMatrix {
+	/// Rows of the output axes, columns of the input axes.
+	pub matrix: [[f32; 6]; 6],
+	/// Added to the output axes.
+	pub bias: [f32; 6],
+}
+
+impl Default for AxisMatrix {
+	#[inline]
+	fn default() -> AxisMatrix {
+		AxisMatrix::identity()
+	}
+}
+
+impl AxisMatrix {
+	/// Matrix which passes every axis through unchanged.
+	#[inline]
+	pub const fn identity() -> AxisMatrix {
+		let mut matrix = [[0.0; 6]; 6];
+		let mut i = 0;
+		while i < 6 {
+			matrix[i][i] = 1.0;
+			i += 1;
+		}
+		AxisMatrix { matrix, bias: [0.0; 6] }
+	}
+
+	/// Swaps the outputs of two axes.
+	#[inline]
+	pub fn swap_axes(mut self, a: Axis, b: Axis) -> AxisMatrix {
+		self.matrix.swap(a as usize, b as usize);
+		self.bias.swap(a as usize, b as usize);
+		self
+	}
+
+	/// Swaps the outputs of the left and right thumbsticks.
+	#[inline]
+	pub fn swap_sticks(self) -> AxisMatrix {
+		self.swap_axes(Axis::LX, Axis::RX).swap_axes(Axis::LY, Axis::RY)
+	}
+
+	/// Inverts the output of an axis.
+	///
+	/// Thumbsticks are mirrored around the center, triggers report released when fully pulled and vice versa.
+	#[inline]
+	pub fn invert_axis(mut self, axis: Axis) -> AxisMatrix {
+		let row = axis as usize;
+		for weight in &mut self.matrix[row] {
+			*weight = -*weight;
+		}
+		self.bias[row] = if axis.is_trigger() { 1.0 - self.bias[row] } else { -self.bias[row] };
+		self
+	}
+
+	/// Scales the output of an axis.
+	#[inline]
+	pub fn scale_axis(mut self, axis: Axis, scale: f32) -> AxisMatrix {
+		let row = axis as usize;
+		for weight in &mut self.matrix[row] {
+			*weight *= scale;
+		}
+		self.bias[row] *= scale;
+		self
+	}
+
+	/// Mixes the input axis into the output axis with the given weight.
+	#[inline]
+	pub fn mix(mut self, output: Axis, input: Axis, weight: f32) -> AxisMatrix {
+		self.matrix[output as usize][input as usize] += weight;
+		self
+	}
+
+	/// Applies the matrix to a gamepad state, the buttons are passed through unchanged.
+	pub fn apply(&self, gamepad: &XGamepad) -> XGamepad {
+		let stick = |value: i16| value as f32 / i16::MAX as f32;
+		let trigger = |value: u8| value as f32 / u8::MAX as f32;
+		let input = [
+			stick(gamepad.thumb_lx), stick(gamepad.thumb_ly),
+			stick(gamepad.thumb_rx), stick(gamepad.thumb_ry),
+			trigger(gamepad.left_trigger), trigger(gamepad.right_trigger),
+		];
+		let output = |axis: Axis| {
+			let row = &self.matrix[axis as usize];
+			let value = row.iter().zip(&input).map(|(weight, input)| weight * input).sum::<f32>() + self.bias[axis as usize];
+			if value.is_nan() { 0.0 } else { value }
+		};
+		let stick = |axis: Axis| round_axis(output(axis) * i16::MAX as f32);
+		let trigger = |axis: Axis| (output(axis) * u8::MAX as f32).round().clamp(0.0, 255.0) as u8;
+		XGamepad {
+			buttons: gamepad.buttons,
+			left_trigger: trigger(Axis::LT),
+			right_trigger: trigger(Axis::RT),
+			thumb_lx: stick(Axis::LX),
+			thumb_ly: stick(Axis::LY),
+			thumb_rx: stick(Axis::RX),
+			thumb_ry: stick(Axis::RY),
+		}
+	}
+
+	fn bits(&self) -> [[u32; 6]; 7] {
+		let mut bits = [[0; 6]; 7];
+		for (bits, row) in bits.iter_mut().zip(self.matrix.iter().chain(Some(&self.bias))) {
+			for (bits, value) in bits.iter_mut().zip(row) {
+				*bits = value.to_bits();
+			}
+		}
+		bits
+	}
+}
+
+impl PartialEq for AxisMatrix {
+	#[inline]
+	fn eq(&self, other: &AxisMatrix) -> bool {
+		self.bits() == other.bits()
+	}
+}
+impl Eq for AxisMatrix {}
+
+impl std::hash::Hash for AxisMatrix {
+	#[inline]
+	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+		self.bits().hash(state);
+	}
+}
+
+// Maps the magnitude `(0, 32767]` onto `(deadzone, 32767]` keeping the direction.
+fn expand_thumb(x: i16, y: i16, deadzone: u16) -> (i16, i16) {
+	let (fx, fy) = (x as f32, y as f32);
+	let magnitude = (fx * fx + fy * fy).sqrt();
+	if magnitude == 0.0 {
+		return (0, 0);
+	}
+	let max = i16::MAX as f32;
+	let deadzone = deadzone as f32;
+	let clamped = magnitude.min(max);
+	let scale = (deadzone + clamped * (max - deadzone) / max) / magnitude;
+	(round_axis(fx * scale), round_axis(fy * scale))
+}
+
+// Maps the value `(0, 255]` onto `(threshold, 255]`.
+fn expand_trigger(value: u8, threshold: u8) -> u8 {
+	if value == 0 {
+		return 0;
+	}
+	let range = (255 - threshold as u32) * value as u32;
+	(threshold as u32 + (range + 127) / 255).max(threshold as u32 + 1).min(255) as u8
+}
+
+pub(crate) fn round_axis(value: f32) -> i16 {
+	value.round().max(i16::MIN as f32).min(i16::MAX as f32) as i16
+}
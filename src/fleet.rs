@@ -0,0 +1,611 @@
+use std::{fmt, time};
+use std::borrow::Borrow;
+use std::sync::Arc;
+use winapi::shared::winerror;
+use crate::*;
+
+/// Kind of virtual controller.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TargetKind {
+	/// See [`Xbox360Wired`].
+	Xbox360Wired,
+	/// See [`DualShock4Wired`].
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	DualShock4Wired,
+}
+
+/// Input report of any kind of virtual controller.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TargetReport {
+	/// Report of a [`Xbox360Wired`] target.
+	Xbox360Wired(XGamepad),
+	/// Report of a [`DualShock4Wired`] target.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	DualShock4Wired(DS4Report),
+}
+
+impl TargetReport {
+	/// Returns the kind of target the report is for.
+	#[inline]
+	pub const fn kind(&self) -> TargetKind {
+		match self {
+			TargetReport::Xbox360Wired(_) => TargetKind::Xbox360Wired,
+			#[cfg(feature = "unstable_ds4")]
+			TargetReport::DualShock4Wired(_) => TargetKind::DualShock4Wired,
+		}
+	}
+}
+
+impl From<XGamepad> for TargetReport {
+	#[inline]
+	fn from(gamepad: XGamepad) -> TargetReport {
+		TargetReport::Xbox360Wired(gamepad)
+	}
+}
+#[cfg(feature = "unstable_ds4")]
+impl From<DS4Report> for TargetReport {
+	#[inline]
+	fn from(report: DS4Report) -> TargetReport {
+		TargetReport::DualShock4Wired(report)
+	}
+}
+
+/// Target of any kind managed by a [`Fleet`], see [`AnyTarget`] to keep targets of different kinds together without generics.
+pub enum FleetTarget<CL: Borrow<Client>> {
+	Xbox360Wired(Xbox360Wired<CL>),
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	DualShock4Wired(DualShock4Wired<CL>),
+}
+
+/// Target of any kind sharing its client, for heterogeneous collections without generics.
+///
+/// Forwards the common operations to the target and submits reports of its kind.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use vigem_client::{AnyTarget, Client, TargetId, Xbox360Wired, XGamepad};
+///
+/// let client = Arc::new(Client::connect()?);
+///
+/// let mut targets: Vec<AnyTarget> = vec![Xbox360Wired::new(client.clone(), TargetId::XBOX360_WIRED).into()];
+/// for target in &mut targets {
+/// 	target.plugin()?;
+/// 	target.wait_ready()?;
+/// }
+///
+/// targets[0].submit_xbox(&XGamepad::default())?;
+/// # Ok::<(), vigem_client::Error>(())
+/// ```
+pub type AnyTarget = FleetTarget<Arc<Client>>;
+
+impl<CL: Borrow<Client>> FleetTarget<CL> {
+	/// Returns the kind of target.
+	#[inline]
+	pub fn kind(&self) -> TargetKind {
+		match self {
+			FleetTarget::Xbox360Wired(_) => TargetKind::Xbox360Wired,
+			#[cfg(feature = "unstable_ds4")]
+			FleetTarget::DualShock4Wired(_) => TargetKind::DualShock4Wired,
+		}
+	}
+
+	/// Returns the serial number assigned by the driver, zero if not plugged in.
+	#[inline]
+	pub fn serial_no(&self) -> u32 {
+		match self {
+			FleetTarget::Xbox360Wired(target) => target.serial_no(),
+			#[cfg(feature = "unstable_ds4")]
+			FleetTarget::DualShock4Wired(target) => target.serial_no(),
+		}
+	}
+
+	/// Returns the vendor and product ids.
+	#[inline]
+	pub fn id(&self) -> TargetId {
+		match self {
+			FleetTarget::Xbox360Wired(target) => target.id(),
+			#[cfg(feature = "unstable_ds4")]
+			FleetTarget::DualShock4Wired(target) => target.id(),
+		}
+	}
+
+	/// Returns the process-unique identifier of the target, see [`TargetUid`].
+	#[inline]
+	pub fn uid(&self) -> TargetUid {
+		match self {
+			FleetTarget::Xbox360Wired(target) => target.uid(),
+			#[cfg(feature = "unstable_ds4")]
+			FleetTarget::DualShock4Wired(target) => target.uid(),
+		}
+	}
+
+	/// Returns if the controller is plugged in.
+	#[inline]
+	pub fn is_attached(&self) -> bool {
+		self.serial_no() != 0
+	}
+
+	/// Plugs the controller in, see [`Xbox360Wired::plugin`].
+	#[inline]
+	pub fn plugin(&mut self) -> Result<(), Error> {
+		match self {
+			FleetTarget::Xbox360Wired(target) => target.plugin(),
+			#[cfg(feature = "unstable_ds4")]
+			FleetTarget::DualShock4Wired(target) => target.plugin(),
+		}
+	}
+
+	/// Unplugs the controller, see [`Xbox360Wired::unplug`].
+	#[inline]
+	pub fn unplug(&mut self) -> Result<(), Error> {
+		match self {
+			FleetTarget::Xbox360Wired(target) => target.unplug(),
+			#[cfg(feature = "unstable_ds4")]
+			FleetTarget::DualShock4Wired(target) => target.unplug(),
+		}
+	}
+
+	/// Waits until the virtual controller is ready, see [`Xbox360Wired::wait_ready`].
+	#[inline]
+	pub fn wait_ready(&mut self) -> Result<(), Error> {
+		match self {
+			FleetTarget::Xbox360Wired(target) => target.wait_ready(),
+			#[cfg(feature = "unstable_ds4")]
+			FleetTarget::DualShock4Wired(target) => target.wait_ready(),
+		}
+	}
+
+	/// Updates the target, returns [`Error::KindMismatch`] for a report of another kind.
+	#[inline]
+	pub fn update(&mut self, report: &TargetReport) -> Result<(), Error> {
+		match (self, report) {
+			(FleetTarget::Xbox360Wired(target), TargetReport::Xbox360Wired(gamepad)) => target.update(gamepad),
+			#[cfg(feature = "unstable_ds4")]
+			(FleetTarget::DualShock4Wired(target), TargetReport::DualShock4Wired(report)) => target.update(report),
+			#[cfg(feature = "unstable_ds4")]
+			_ => Err(Error::KindMismatch),
+		}
+	}
+
+	/// Updates an Xbox360 target, returns [`Error::KindMismatch`] for other kinds of targets.
+	#[inline]
+	pub fn submit_xbox(&mut self, gamepad: &XGamepad) -> Result<(), Error> {
+		match self {
+			FleetTarget::Xbox360Wired(target) => target.update(gamepad),
+			#[cfg(feature = "unstable_ds4")]
+			_ => Err(Error::KindMismatch),
+		}
+	}
+
+	/// Updates a DualShock4 target, returns [`Error::KindMismatch`] for other kinds of targets.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn submit_ds4(&mut self, report: &DS4Report) -> Result<(), Error> {
+		match self {
+			FleetTarget::DualShock4Wired(target) => target.update(report),
+			_ => Err(Error::KindMismatch),
+		}
+	}
+
+	fn snapshot(&self, last_report: Option<TargetReport>) -> TargetSnapshot {
+		match self {
+			FleetTarget::Xbox360Wired(target) => TargetSnapshot {
+				kind: TargetKind::Xbox360Wired,
+				id: target.id(),
+				serial_no: target.serial_no(),
+				detached_policy: target.detached_policy(),
+				validate_reports: false,
+				last_report,
+			},
+			#[cfg(feature = "unstable_ds4")]
+			FleetTarget::DualShock4Wired(target) => TargetSnapshot {
+				kind: TargetKind::DualShock4Wired,
+				id: target.id(),
+				serial_no: target.serial_no(),
+				detached_policy: target.detached_policy(),
+				validate_reports: target.validate_reports(),
+				last_report,
+			},
+		}
+	}
+
+	fn adopt(&mut self, serial_no: u32) {
+		match self {
+			FleetTarget::Xbox360Wired(target) => target.adopt(serial_no),
+			#[cfg(feature = "unstable_ds4")]
+			FleetTarget::DualShock4Wired(target) => target.adopt(serial_no),
+		}
+	}
+
+	fn release(&mut self) -> u32 {
+		match self {
+			FleetTarget::Xbox360Wired(target) => target.release(),
+			#[cfg(feature = "unstable_ds4")]
+			FleetTarget::DualShock4Wired(target) => target.release(),
+		}
+	}
+}
+
+impl<CL: Borrow<Client>> From<Xbox360Wired<CL>> for FleetTarget<CL> {
+	#[inline]
+	fn from(target: Xbox360Wired<CL>) -> FleetTarget<CL> {
+		FleetTarget::Xbox360Wired(target)
+	}
+}
+#[cfg(feature = "unstable_ds4")]
+impl<CL: Borrow<Client>> From<DualShock4Wired<CL>> for FleetTarget<CL> {
+	#[inline]
+	fn from(target: DualShock4Wired<CL>) -> FleetTarget<CL> {
+		FleetTarget::DualShock4Wired(target)
+	}
+}
+
+impl<CL: Borrow<Client>> VirtualTarget for FleetTarget<CL> {
+	#[inline]
+	fn id(&self) -> TargetId {
+		self.id()
+	}
+	#[inline]
+	fn is_attached(&self) -> bool {
+		self.is_attached()
+	}
+	#[inline]
+	fn plugin(&mut self) -> Result<(), Error> {
+		self.plugin()
+	}
+	#[inline]
+	fn unplug(&mut self) -> Result<(), Error> {
+		self.unplug()
+	}
+	#[inline]
+	fn wait_ready(&mut self) -> Result<(), Error> {
+		self.wait_ready()
+	}
+}
+
+impl<CL: Borrow<Client>> fmt::Debug for FleetTarget<CL> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			FleetTarget::Xbox360Wired(target) => f.debug_tuple("Xbox360Wired").field(target).finish(),
+			#[cfg(feature = "unstable_ds4")]
+			FleetTarget::DualShock4Wired(target) => f.debug_tuple("DualShock4Wired").field(target).finish(),
+		}
+	}
+}
+
+/// State of a single target, see [`FleetSnapshot`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TargetSnapshot {
+	/// Kind of target.
+	pub kind: TargetKind,
+	/// Vendor and product ids.
+	pub id: TargetId,
+	/// Serial number assigned by the driver, zero if not plugged in.
+	pub serial_no: u32,
+	/// See [`Xbox360Wired::set_detached_policy`].
+	pub detached_policy: DetachedPolicy,
+	/// See [`DualShock4Wired::set_validate_reports`], always `false` for other kinds.
+	pub validate_reports: bool,
+	/// The most recent report submitted through the fleet.
+	pub last_report: Option<TargetReport>,
+}
+
+impl TargetSnapshot {
+	fn instantiate<CL: Borrow<Client>>(&self, client: CL) -> FleetTarget<CL> {
+		match self.kind {
+			TargetKind::Xbox360Wired => {
+				let mut target = Xbox360Wired::new(client, self.id);
+				target.set_detached_policy(self.detached_policy);
+				FleetTarget::Xbox360Wired(target)
+			},
+			#[cfg(feature = "unstable_ds4")]
+			TargetKind::DualShock4Wired => {
+				let mut target = DualShock4Wired::new(client, self.id);
+				target.set_detached_policy(self.detached_policy);
+				target.set_validate_reports(self.validate_reports);
+				FleetTarget::DualShock4Wired(target)
+			},
+		}
+	}
+}
+
+/// State of all the targets of a [`Fleet`], to restore them after a crash.
+///
+/// With the `serde` feature the snapshot can be written to a file in any format supported by serde.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FleetSnapshot {
+	/// The targets in fleet order.
+	pub targets: Vec<TargetSnapshot>,
+}
+
+/// Outcome of restoring a target, see [`Fleet::restore`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RestoreOutcome {
+	/// The target was still plugged in under its serial number and was taken over.
+	Adopted { serial_no: u32 },
+	/// The target was plugged in anew, the serial number may differ from the snapshot.
+	Recreated { serial_no: u32 },
+	/// The target was not plugged in when the snapshot was taken, its serial number is zero, and is left unplugged in the fleet.
+	Unplugged,
+	/// The target could not be restored and is left unplugged in the fleet.
+	///
+	/// A serial number still plugged in as another kind of target fails with [`Error::KindMismatch`],
+	/// the other target is left alone and no new target is plugged in in its place.
+	Failed(Error),
+}
+
+struct Entry<CL: Borrow<Client>> {
+	target: FleetTarget<CL>,
+	last_report: Option<TargetReport>,
+}
+
+struct Autosave {
+	interval: time::Duration,
+	next: Option<time::Instant>,
+	sink: Box<dyn FnMut(&FleetSnapshot) + Send>,
+}
+
+/// Manages targets of any kind and snapshots their state for crash recovery.
+///
+/// ```no_run
+/// let client = vigem_client::Client::connect().unwrap();
+///
+/// let mut fleet = vigem_client::Fleet::new();
+/// fleet.push(vigem_client::Xbox360Wired::new(&client, vigem_client::TargetId::XBOX360_WIRED));
+/// fleet.plugin_all().unwrap();
+/// fleet.update(0, vigem_client::XGamepad::default()).unwrap();
+///
+/// let snapshot = fleet.snapshot();
+/// # let _ = snapshot;
+/// ```
+///
+/// See [`restore`](Self::restore) to bring the targets back from a snapshot.
+pub struct Fleet<CL: Borrow<Client>> {
+	entries: Vec<Entry<CL>>,
+	autosave: Option<Autosave>,
+}
+
+impl<CL: Borrow<Client>> Default for Fleet<CL> {
+	#[inline]
+	fn default() -> Self {
+		Fleet::new()
+	}
+}
+
+impl<CL: Borrow<Client>> Fleet<CL> {
+	/// Creates a new empty fleet.
+	#[inline]
+	pub const fn new() -> Fleet<CL> {
+		Fleet { entries: Vec::new(), autosave: None }
+	}
+
+	/// Adds a target to the end of the fleet, returns its index.
+	#[inline]
+	pub fn push<T: Into<FleetTarget<CL>>>(&mut self, target: T) -> usize {
+		self.entries.push(Entry { target: target.into(), last_report: None });
+		self.entries.len() - 1
+	}
+
+	/// Returns the number of targets.
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Returns if the fleet has no targets.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Returns the target at the index.
+	#[inline]
+	pub fn get(&self, index: usize) -> Option<&FleetTarget<CL>> {
+		self.entries.get(index).map(|entry| &entry.target)
+	}
+
+	/// Returns the target at the index.
+	///
+	/// Reports submitted directly to the target are not remembered by the snapshot.
+	#[inline]
+	pub fn get_mut(&mut self, index: usize) -> Option<&mut FleetTarget<CL>> {
+		self.entries.get_mut(index).map(|entry| &mut entry.target)
+	}
+
+	/// Updates the target at the index and remembers the report for the snapshot.
+	///
+	/// Returns [`Error::KindMismatch`] for a report of another kind.
+	///
+	/// # Panics
+	///
+	/// Panics if the index is out of bounds.
+	#[inline]
+	pub fn update<R: Into<TargetReport>>(&mut self, index: usize, report: R) -> Result<(), Error> {
+		let entry = &mut self.entries[index];
+		let report = report.into();
+		entry.target.update(&report)?;
+		entry.last_report = Some(report);
+		Ok(())
+	}
+
+	/// Plugs in the targets in order and waits until each is ready.
+	///
+	/// Targets which are already plugged in are skipped.
+	/// Stops at the first failure, returning the index of the target and the error.
+	#[inline(never)]
+	pub fn plugin_all(&mut self) -> Result<(), (usize, Error)> {
+		self.plugin_each(|_| ())
+	}
+
+	/// Plugs in the targets in order and waits until each is ready, unplugging them again on failure.
+	///
+	/// Same as [`plugin_all`](Self::plugin_all) but on failure the targets plugged in by this call are unplugged in reverse order
+	/// before returning the index of the failed target and the error. Targets which were already plugged in are left alone.
+	///
+	/// The rollback is best effort: a target which fails to unplug remains plugged in.
+	/// The rollback keeps going and returns the index and error of every such target in [`RollbackError::rollback`].
+	#[inline(never)]
+	pub fn plugin_all_or_rollback(&mut self) -> Result<(), RollbackError> {
+		let mut plugged = Vec::new();
+		let (index, error) = match self.plugin_each(|index| plugged.push(index)) {
+			Ok(()) => return Ok(()),
+			Err(err) => err,
+		};
+		let rollback = plugged.iter().rev()
+			.filter_map(|&index| self.entries[index].target.unplug().err().map(|err| (index, err)))
+			.collect();
+		Err(RollbackError { index, error, rollback })
+	}
+
+	fn plugin_each(&mut self, mut plugged: impl FnMut(usize)) -> Result<(), (usize, Error)> {
+		for (index, entry) in self.entries.iter_mut().enumerate() {
+			if !entry.target.is_attached() {
+				entry.target.plugin().map_err(|err| (index, err))?;
+				// Roll back a target which plugged in but failed to become ready
+				plugged(index);
+				entry.target.wait_ready().map_err(|err| (index, err))?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Captures the state of all the targets.
+	#[inline(never)]
+	pub fn snapshot(&self) -> FleetSnapshot {
+		FleetSnapshot {
+			targets: self.entries.iter().map(|entry| entry.target.snapshot(entry.last_report)).collect(),
+		}
+	}
+
+	/// Captures the state of all the targets and lets go of them without unplugging them.
+	///
+	/// The targets remain plugged in until the client is closed or a fleet is [restored](Self::restore) from the snapshot with the same client.
+	#[inline(never)]
+	pub fn persist(mut self) -> FleetSnapshot {
+		let snapshot = self.snapshot();
+		for entry in &mut self.entries {
+			entry.target.release();
+		}
+		snapshot
+	}
+
+	/// Passes a snapshot to the sink every interval when [pumped](Pump), `None` to stop.
+	///
+	/// The first snapshot is taken on the next pump. The sink typically writes the snapshot to a file.
+	#[inline]
+	pub fn set_autosave(&mut self, interval: Option<time::Duration>, sink: Box<dyn FnMut(&FleetSnapshot) + Send>) {
+		self.autosave = interval.map(|interval| Autosave { interval, next: None, sink });
+	}
+
+	/// Restores the targets of the snapshot, returning the fleet and the outcome of each target in snapshot order.
+	///
+	/// Targets still plugged in under their serial number by the client are adopted,
+	/// which is verified by submitting the last report (or a neutral report) to it.
+	/// Other targets are plugged in anew and updated with their last report.
+	/// Targets which were not plugged in when the snapshot was taken are left unplugged.
+	///
+	/// Failures are reported per target and do not abort the restore,
+	/// failed targets are left unplugged in the fleet so the indices match the snapshot.
+	/// A serial number plugged in as another kind of target fails with [`Error::KindMismatch`].
+	///
+	/// Only restore with a client whose other targets do not use the serial numbers of the snapshot,
+	/// adopting a serial number of a live target would let two targets drive the same virtual controller.
+	#[inline(never)]
+	pub fn restore(client: CL, snapshot: &FleetSnapshot) -> (Fleet<CL>, Vec<RestoreOutcome>) where CL: Clone {
+		let mut fleet = Fleet::new();
+		let mut outcomes = Vec::with_capacity(snapshot.targets.len());
+		for state in &snapshot.targets {
+			let mut target = state.instantiate(client.clone());
+			let outcome = restore_target(client.borrow(), state, &mut target);
+			fleet.entries.push(Entry { target, last_report: state.last_report });
+			outcomes.push(outcome);
+		}
+		(fleet, outcomes)
+	}
+}
+
+fn restore_target<CL: Borrow<Client>>(client: &Client, state: &TargetSnapshot, target: &mut FleetTarget<CL>) -> RestoreOutcome {
+	let report = state.last_report.unwrap_or(neutral(state.kind));
+	if report.kind() != state.kind {
+		return RestoreOutcome::Failed(Error::KindMismatch);
+	}
+
+	// Zero is not a serial number, the driver would report it as free and the target would be plugged in
+	if state.serial_no == 0 {
+		return RestoreOutcome::Unplugged;
+	}
+
+	// Adopt the target if it is still plugged in
+	let alive = match client.probe_serial(state.serial_no) {
+		Ok(alive) => alive == Some(true),
+		Err(err) => return RestoreOutcome::Failed(err),
+	};
+	if alive {
+		target.adopt(state.serial_no);
+		return match target.update(&report) {
+			Ok(()) => RestoreOutcome::Adopted { serial_no: state.serial_no },
+			Err(err) => {
+				target.release();
+				RestoreOutcome::Failed(match err {
+					// The driver rejects reports for another kind of target
+					Error::WinError(winerror::ERROR_INVALID_PARAMETER | winerror::ERROR_INVALID_FUNCTION) => Error::KindMismatch,
+					err => err,
+				})
+			},
+		};
+	}
+
+	let result = target.plugin()
+		.and_then(|_| target.wait_ready())
+		.and_then(|_| target.update(&report));
+	match result {
+		Ok(()) => RestoreOutcome::Recreated { serial_no: target.serial_no() },
+		Err(err) => {
+			let _ = target.unplug();
+			RestoreOutcome::Failed(err)
+		},
+	}
+}
+
+fn neutral(kind: TargetKind) -> TargetReport {
+	match kind {
+		TargetKind::Xbox360Wired => TargetReport::Xbox360Wired(XGamepad::neutral()),
+		#[cfg(feature = "unstable_ds4")]
+		TargetKind::DualShock4Wired => TargetReport::DualShock4Wired(DS4Report::neutral()),
+	}
+}
+
+/// Passes a snapshot to the autosave sink when due, see [`Fleet::set_autosave`].
+impl<CL: Borrow<Client>> Pump for Fleet<CL> {
+	fn pump(&mut self, now: time::Instant) -> Result<(), Error> {
+		let due = match &self.autosave {
+			Some(autosave) => !matches!(autosave.next, Some(next) if now < next),
+			None => false,
+		};
+		if due {
+			let snapshot = self.snapshot();
+			if let Some(autosave) = &mut self.autosave {
+				(autosave.sink)(&snapshot);
+				autosave.next = Some(now + autosave.interval);
+			}
+		}
+		Ok(())
+	}
+}
+
+impl<CL: Borrow<Client>> fmt::Debug for Fleet<CL> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Fleet")
+			.field("targets", &self.entries.iter().map(|entry| &entry.target).collect::<Vec<_>>())
+			.field("autosave", &self.autosave.as_ref().map(|autosave| autosave.interval))
+			.finish()
+	}
+}
@@ -0,0 +1,140 @@
+use std::cell::Cell;
+use std::fmt;
+use crate::sync::{thread_local, AtomicUsize, Condvar, Mutex, MutexGuard, Ordering, PoisonError};
+
+const CLOSED: usize = 1 << (usize::BITS - 1);
+
+/// Reader-writer gate around the driver requests of a client.
+///
+/// Entering is a single atomic increment unless the gate is closed for maintenance.
+/// Closing the gate stops new requests from entering and waits for the requests in flight to leave.
+///
+/// Only built on [`crate::sync`] and model checked with loom, see `tests/loom.rs`.
+pub(crate) struct Gate {
+	// Number of requests in flight plus the `CLOSED` flag
+	state: AtomicUsize,
+	// Serializes maintenance
+	maintenance: Mutex<()>,
+	// Number of requests held off by maintenance
+	waiting: Mutex<usize>,
+	cond: Condvar,
+}
+
+thread_local! {
+	// The gate closed by this thread, it may keep issuing requests during maintenance
+	// Not const initialized, loom's thread_local does not accept it
+	#[allow(clippy::missing_const_for_thread_local)]
+	static OWNER: Cell<usize> = Cell::new(0);
+}
+
+impl Default for Gate {
+	#[inline]
+	fn default() -> Gate {
+		Gate {
+			state: AtomicUsize::new(0),
+			maintenance: Mutex::new(()),
+			waiting: Mutex::new(0),
+			cond: Condvar::new(),
+		}
+	}
+}
+
+impl fmt::Debug for Gate {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let state = self.state.load(Ordering::Relaxed);
+		f.debug_struct("Gate")
+			.field("in_flight", &(state & !CLOSED))
+			.field("closed", &(state & CLOSED != 0))
+			.finish()
+	}
+}
+
+impl Gate {
+	/// Enters the gate for the duration of a driver request.
+	#[inline]
+	pub fn enter(&self) -> Pass<'_> {
+		if self.state.fetch_add(1, Ordering::Acquire) & CLOSED != 0 {
+			self.enter_slow();
+		}
+		Pass { gate: self }
+	}
+
+	#[cold]
+	fn enter_slow(&self) {
+		if matches!(OWNER.try_with(Cell::get), Ok(owner) if owner == self as *const Gate as usize) {
+			return;
+		}
+		self.leave();
+		// The flag only changes while holding the lock, entering under the lock cannot race with closing
+		let mut waiting = self.lock();
+		*waiting += 1;
+		while self.state.load(Ordering::Acquire) & CLOSED != 0 {
+			waiting = self.cond.wait(waiting).unwrap_or_else(PoisonError::into_inner);
+		}
+		self.state.fetch_add(1, Ordering::Acquire);
+		*waiting -= 1;
+		if *waiting == 0 {
+			self.cond.notify_all();
+		}
+	}
+
+	#[inline]
+	fn leave(&self) {
+		let prev = self.state.fetch_sub(1, Ordering::Release);
+		// Wake up the maintenance waiting for the last request in flight
+		if prev == CLOSED + 1 {
+			let _lock = self.lock();
+			self.cond.notify_all();
+		}
+	}
+
+	/// Closes the gate until the returned guard is dropped.
+	pub fn close(&self) -> Closed<'_> {
+		let maintenance = self.maintenance.lock().unwrap_or_else(PoisonError::into_inner);
+		let mut waiting = self.lock();
+		// Let the requests held off by the previous maintenance through first
+		while *waiting != 0 {
+			waiting = self.cond.wait(waiting).unwrap_or_else(PoisonError::into_inner);
+		}
+		self.state.fetch_or(CLOSED, Ordering::Acquire);
+		while self.state.load(Ordering::Acquire) != CLOSED {
+			waiting = self.cond.wait(waiting).unwrap_or_else(PoisonError::into_inner);
+		}
+		drop(waiting);
+		let previous = OWNER.with(|owner| owner.replace(self as *const Gate as usize));
+		Closed { gate: self, previous, _maintenance: maintenance }
+	}
+
+	#[inline]
+	fn lock(&self) -> MutexGuard<'_, usize> {
+		self.waiting.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+
+/// Request in flight, see [`Gate::enter`].
+pub(crate) struct Pass<'a> {
+	gate: &'a Gate,
+}
+
+impl Drop for Pass<'_> {
+	#[inline]
+	fn drop(&mut self) {
+		self.gate.leave();
+	}
+}
+
+/// Gate closed for maintenance, see [`Gate::close`].
+pub(crate) struct Closed<'a> {
+	gate: &'a Gate,
+	previous: usize,
+	_maintenance: MutexGuard<'a, ()>,
+}
+
+impl Drop for Closed<'_> {
+	fn drop(&mut self) {
+		let _ = OWNER.try_with(|owner| owner.set(self.previous));
+		let _waiting = self.gate.lock();
+		self.gate.state.fetch_and(!CLOSED, Ordering::Release);
+		self.gate.cond.notify_all();
+	}
+}
@@ -0,0 +1,100 @@
+// Tracks the service handles owned by clients to catch ownership mistakes of the raw handle conversions.
+// Only compiled with the `debug-handles` feature in builds with debug assertions, otherwise every function is a no-op.
+
+use winapi::shared::ntdef::HANDLE;
+
+#[cfg(all(feature = "debug-handles", debug_assertions))]
+mod imp {
+	use std::collections::HashMap;
+	use std::sync::{Mutex, PoisonError};
+	use winapi::um::handleapi::GetHandleInformation;
+	use super::HANDLE;
+
+	#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+	enum State {
+		Owned,
+		Closed,
+		Released,
+	}
+
+	static HANDLES: Mutex<Option<HashMap<usize, State>>> = Mutex::new(None);
+
+	fn update<R>(device: HANDLE, f: impl FnOnce(Option<State>) -> (Option<State>, R)) -> R {
+		let mut handles = HANDLES.lock().unwrap_or_else(PoisonError::into_inner);
+		let handles = handles.get_or_insert_with(HashMap::new);
+		let (state, result) = f(handles.get(&(device as usize)).copied());
+		match state {
+			Some(state) => handles.insert(device as usize, state),
+			None => handles.remove(&(device as usize)),
+		};
+		result
+	}
+
+	pub fn opened(device: HANDLE) {
+		let owned = update(device, |state| (Some(State::Owned), state == Some(State::Owned)));
+		if owned {
+			panic!("vigem-client: the ViGEmBus service handle {:p} is already owned by another Client, \
+				a handle passed to FromRawHandle must be owned by the caller", device);
+		}
+	}
+
+	pub fn closed(device: HANDLE) {
+		update(device, |state| (state.map(|_| State::Closed), ()));
+	}
+
+	pub fn released(device: HANDLE) {
+		update(device, |state| (state.map(|_| State::Released), ()));
+	}
+
+	pub fn check(device: HANDLE) {
+		match update(device, |state| (state, state)) {
+			Some(State::Closed) => panic!(
+				"vigem-client: the ViGEmBus service handle {:p} was used after it was closed by dropping another Client owning it", device),
+			Some(State::Released) => panic!(
+				"vigem-client: the ViGEmBus service handle {:p} was used after its ownership was given away with into_raw_handle or leak", device),
+			_ => (),
+		}
+		let mut flags = 0;
+		if unsafe { GetHandleInformation(device, &mut flags) } == 0 {
+			panic!("vigem-client: the ViGEmBus service handle {:p} was used after it was closed outside of its Client", device);
+		}
+	}
+}
+
+#[cfg(not(all(feature = "debug-handles", debug_assertions)))]
+mod imp {
+	use super::HANDLE;
+
+	#[inline]
+	pub fn opened(_device: HANDLE) {}
+	#[inline]
+	pub fn closed(_device: HANDLE) {}
+	#[inline]
+	pub fn released(_device: HANDLE) {}
+	#[inline]
+	pub fn check(_device: HANDLE) {}
+}
+
+// A client takes ownership of the handle
+#[inline]
+pub(crate) fn opened(device: HANDLE) {
+	imp::opened(device)
+}
+
+// A client closed its handle
+#[inline]
+pub(crate) fn closed(device: HANDLE) {
+	imp::closed(device)
+}
+
+// A client gave away ownership of its handle without closing it
+#[inline]
+pub(crate) fn released(device: HANDLE) {
+	imp::released(device)
+}
+
+// A client issues a request with its handle
+#[inline]
+pub(crate) fn check(device: HANDLE) {
+	imp::check(device)
+}
@@ -0,0 +1,129 @@
+use std::time;
+use crate::*;
+
+/// Reports which can tell if the user is touching the controller.
+pub trait IdleInput {
+	/// Returns if the report is neutral up to the tolerance, see [`XGamepad::is_neutral`].
+	fn is_neutral(&self, tolerance: u8) -> bool;
+}
+
+impl IdleInput for XGamepad {
+	#[inline]
+	fn is_neutral(&self, tolerance: u8) -> bool {
+		XGamepad::is_neutral(self, tolerance)
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl IdleInput for DS4Report {
+	#[inline]
+	fn is_neutral(&self, tolerance: u8) -> bool {
+		DS4Report::is_neutral(self, tolerance)
+	}
+}
+
+/// Uses [`DS4ReportEx::GYRO_NOISE`] as the gyro threshold, see [`DS4ReportEx::is_user_idle`].
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl IdleInput for DS4ReportEx {
+	#[inline]
+	fn is_neutral(&self, tolerance: u8) -> bool {
+		self.is_user_idle(tolerance, DS4ReportEx::GYRO_NOISE)
+	}
+}
+
+impl<R: IdleInput + ?Sized> IdleInput for &R {
+	#[inline]
+	fn is_neutral(&self, tolerance: u8) -> bool {
+		(**self).is_neutral(tolerance)
+	}
+}
+
+/// Tracks how long the reports have been neutral, eg. to unplug targets after a period of inactivity.
+///
+/// Push every report with its timestamp, the tracker is idle since the first neutral report following the last report with input.
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use vigem_client::{IdleTracker, XButtons, XGamepad};
+///
+/// let start = Instant::now();
+/// let mut idle = IdleTracker::new(1);
+/// idle.push(&XGamepad::pressing(XButtons!(A)), start);
+/// idle.push(&XGamepad::neutral(), start + Duration::from_secs(1));
+/// idle.push(&XGamepad::neutral(), start + Duration::from_secs(5));
+/// assert_eq!(idle.idle_for(), Duration::from_secs(4));
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct IdleTracker {
+	tolerance: u8,
+	idle_since: Option<time::Instant>,
+	latest: Option<time::Instant>,
+}
+
+impl IdleTracker {
+	/// Creates a tracker treating reports neutral up to the tolerance as idle.
+	#[inline]
+	pub const fn new(tolerance: u8) -> IdleTracker {
+		IdleTracker { tolerance, idle_since: None, latest: None }
+	}
+
+	/// Returns the tolerance reports are checked with.
+	#[inline]
+	pub const fn tolerance(&self) -> u8 {
+		self.tolerance
+	}
+
+	/// Consumes a report submitted at the timestamp.
+	///
+	/// Returns if the report is neutral.
+	/// Timestamps are expected in order, an earlier timestamp does not move the latest timestamp back.
+	#[inline]
+	pub fn push<R: IdleInput + ?Sized>(&mut self, report: &R, at: time::Instant) -> bool {
+		self.latest = Some(self.latest.map_or(at, |latest| latest.max(at)));
+		let neutral = report.is_neutral(self.tolerance);
+		if neutral {
+			self.idle_since.get_or_insert(at);
+		} else {
+			self.idle_since = None;
+		}
+		neutral
+	}
+
+	/// Returns if the last report was neutral.
+	#[inline]
+	pub fn is_idle(&self) -> bool {
+		self.idle_since.is_some()
+	}
+
+	/// Returns the timestamp of the first neutral report following the last report with input.
+	#[inline]
+	pub fn idle_since(&self) -> Option<time::Instant> {
+		self.idle_since
+	}
+
+	/// Returns the time between the start of the idle period and the latest report, zero while not idle.
+	#[inline]
+	pub fn idle_for(&self) -> time::Duration {
+		match (self.idle_since, self.latest) {
+			(Some(since), Some(latest)) => latest.saturating_duration_since(since),
+			_ => time::Duration::ZERO,
+		}
+	}
+
+	/// Returns the time between the start of the idle period and now, zero while not idle.
+	///
+	/// Unlike [`idle_for`](Self::idle_for) this keeps growing when no more reports are pushed.
+	#[inline]
+	pub fn idle_until(&self, now: time::Instant) -> time::Duration {
+		self.idle_since.map_or(time::Duration::ZERO, |since| now.saturating_duration_since(since))
+	}
+
+	/// Forgets the reports pushed so far.
+	#[inline]
+	pub fn reset(&mut self) {
+		self.idle_since = None;
+		self.latest = None;
+	}
+}
@@ -0,0 +1,147 @@
+use crate::*;
+
+/// Gamepad state with normalized axes and boolean buttons.
+///
+/// Follows the conventions of modern capture APIs such as Windows.Gaming.Input's `GamepadReading` and GameInput's `GameInputGamepadState`,
+/// fill it in from their readings and convert it to a [`XGamepad`]:
+///
+/// * Thumbstick axes range from `-1.0` to `1.0`, positive x points right and positive y points up.
+/// * Triggers range from `0.0` (released) to `1.0` (fully pressed).
+/// * Out of range values are clamped, `NaN` is treated as `0.0`.
+///
+/// ```
+/// let state = vigem_client::GenericGamepadState {
+/// 	a: true,
+/// 	left_trigger: 1.0,
+/// 	left_thumb_x: -1.0,
+/// 	..Default::default()
+/// };
+///
+/// let gamepad = vigem_client::XGamepad::from(state);
+/// assert_eq!(gamepad.buttons, vigem_client::XButtons!(A));
+/// assert_eq!(gamepad.left_trigger, 255);
+/// assert_eq!(gamepad.thumb_lx, -32768);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "interop")))]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct GenericGamepadState {
+	pub a: bool,
+	pub b: bool,
+	pub x: bool,
+	pub y: bool,
+	pub dpad_up: bool,
+	pub dpad_down: bool,
+	pub dpad_left: bool,
+	pub dpad_right: bool,
+	pub left_shoulder: bool,
+	pub right_shoulder: bool,
+	pub left_thumbstick: bool,
+	pub right_thumbstick: bool,
+	/// The view (back) button.
+	pub view: bool,
+	/// The menu (start) button.
+	pub menu: bool,
+	/// The guide button, not reported by all capture APIs.
+	pub guide: bool,
+	pub left_trigger: f64,
+	pub right_trigger: f64,
+	pub left_thumb_x: f64,
+	pub left_thumb_y: f64,
+	pub right_thumb_x: f64,
+	pub right_thumb_y: f64,
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "interop")))]
+impl From<GenericGamepadState> for XGamepad {
+	#[inline]
+	fn from(state: GenericGamepadState) -> XGamepad {
+		XGamepad::from(&state)
+	}
+}
+#[cfg_attr(docsrs, doc(cfg(feature = "interop")))]
+impl<'a> From<&'a GenericGamepadState> for XGamepad {
+	fn from(state: &'a GenericGamepadState) -> XGamepad {
+		let flag = |pressed: bool, flag: u16| if pressed { flag } else { 0 };
+		let raw =
+			flag(state.a, XButtons::A) |
+			flag(state.b, XButtons::B) |
+			flag(state.x, XButtons::X) |
+			flag(state.y, XButtons::Y) |
+			flag(state.dpad_up, XButtons::UP) |
+			flag(state.dpad_down, XButtons::DOWN) |
+			flag(state.dpad_left, XButtons::LEFT) |
+			flag(state.dpad_right, XButtons::RIGHT) |
+			flag(state.left_shoulder, XButtons::LB) |
+			flag(state.right_shoulder, XButtons::RB) |
+			flag(state.left_thumbstick, XButtons::LTHUMB) |
+			flag(state.right_thumbstick, XButtons::RTHUMB) |
+			flag(state.view, XButtons::BACK) |
+			flag(state.menu, XButtons::START) |
+			flag(state.guide, XButtons::GUIDE);
+		XGamepad {
+			buttons: XButtons { raw },
+			left_trigger: trigger_to_u8(state.left_trigger),
+			right_trigger: trigger_to_u8(state.right_trigger),
+			thumb_lx: axis_to_i16(state.left_thumb_x),
+			thumb_ly: axis_to_i16(state.left_thumb_y),
+			thumb_rx: axis_to_i16(state.right_thumb_x),
+			thumb_ry: axis_to_i16(state.right_thumb_y),
+		}
+	}
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "interop")))]
+impl From<XGamepad> for GenericGamepadState {
+	fn from(gamepad: XGamepad) -> GenericGamepadState {
+		let pressed = |flag: u16| gamepad.buttons.raw & flag != 0;
+		GenericGamepadState {
+			a: pressed(XButtons::A),
+			b: pressed(XButtons::B),
+			x: pressed(XButtons::X),
+			y: pressed(XButtons::Y),
+			dpad_up: pressed(XButtons::UP),
+			dpad_down: pressed(XButtons::DOWN),
+			dpad_left: pressed(XButtons::LEFT),
+			dpad_right: pressed(XButtons::RIGHT),
+			left_shoulder: pressed(XButtons::LB),
+			right_shoulder: pressed(XButtons::RB),
+			left_thumbstick: pressed(XButtons::LTHUMB),
+			right_thumbstick: pressed(XButtons::RTHUMB),
+			view: pressed(XButtons::BACK),
+			menu: pressed(XButtons::START),
+			guide: pressed(XButtons::GUIDE),
+			left_trigger: gamepad.left_trigger as f64 / 255.0,
+			right_trigger: gamepad.right_trigger as f64 / 255.0,
+			left_thumb_x: axis_from_i16(gamepad.thumb_lx),
+			left_thumb_y: axis_from_i16(gamepad.thumb_ly),
+			right_thumb_x: axis_from_i16(gamepad.thumb_rx),
+			right_thumb_y: axis_from_i16(gamepad.thumb_ry),
+		}
+	}
+}
+
+// The negative range is one larger than the positive range, scale each half separately
+// such that -1.0, 0.0 and 1.0 map exactly onto i16::MIN, 0 and i16::MAX.
+// NaN passes through clamp and becomes zero when cast.
+fn axis_to_i16(value: f64) -> i16 {
+	let value = value.clamp(-1.0, 1.0);
+	if value < 0.0 {
+		(value * 32768.0).round() as i16
+	}
+	else {
+		(value * 32767.0).round() as i16
+	}
+}
+
+fn axis_from_i16(value: i16) -> f64 {
+	if value < 0 {
+		value as f64 / 32768.0
+	}
+	else {
+		value as f64 / 32767.0
+	}
+}
+
+fn trigger_to_u8(value: f64) -> u8 {
+	(value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
@@ -0,0 +1,385 @@
+use std::{fmt, mem, ptr, time};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use winapi::um::ioapiset::*;
+use winapi::um::minwinbase::{OVERLAPPED, OVERLAPPED_ENTRY};
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::winbase::INFINITE;
+use winapi::shared::ntdef::HANDLE;
+use winapi::shared::winerror;
+use crate::*;
+
+/// Completion port state of a client.
+#[derive(Debug)]
+pub(crate) struct CompletionPort {
+	pub port: HANDLE,
+	// An IocpDriver polls the port
+	pub driven: bool,
+}
+
+impl Default for CompletionPort {
+	#[inline]
+	fn default() -> CompletionPort {
+		CompletionPort { port: ptr::null_mut(), driven: false }
+	}
+}
+
+/// Identifies a request issued through an [`IocpDriver`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct CompletionKey(u64);
+
+impl CompletionKey {
+	/// Returns the key as a number, unique for the lifetime of its driver.
+	#[inline]
+	pub const fn get(self) -> u64 {
+		self.0
+	}
+}
+
+/// Outcome of a request issued through an [`IocpDriver`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Completion {
+	/// The report was submitted.
+	Update,
+	/// An Xbox360 target received rumble or led changes.
+	#[cfg(feature = "xusb-rumble")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
+	XNotification(XNotification),
+	/// A DualShock4 target received rumble or lightbar changes.
+	#[cfg(feature = "ds4-output")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+	DS4Notification(DS4Notification),
+}
+
+/// Completed request, see [`IocpDriver::poll`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CompletionEvent {
+	/// The key returned when the request was issued.
+	pub key: CompletionKey,
+	/// Serial number of the target the request was issued for.
+	pub serial_no: u32,
+	/// The outcome of the request.
+	///
+	/// Updates of a target which is not ready yet fail with [`Error::TargetNotReady`],
+	/// [cancelled](IocpDriver::cancel) requests fail with [`Error::OperationAborted`].
+	pub result: Result<Completion, Error>,
+}
+
+enum Buffer {
+	X360(bus::XUsbSubmitReport),
+	#[cfg(feature = "unstable_ds4")]
+	DS4(bus::DS4SubmitReport),
+	#[cfg(feature = "xusb-rumble")]
+	XNotification(bus::XUsbRequestNotification),
+	#[cfg(feature = "ds4-output")]
+	DS4Notification(bus::DS4RequestNotification),
+}
+
+// The overlapped structure and buffer must stay at a stable address until the request completes
+#[repr(C)]
+struct Request {
+	overlapped: OVERLAPPED,
+	key: CompletionKey,
+	serial_no: u32,
+	buffer: Buffer,
+}
+
+impl Request {
+	unsafe fn issue(&mut self, device: HANDLE) -> Result<(), u32> {
+		fn io<T>(buffer: &mut T) -> (*mut winapi::ctypes::c_void, u32) {
+			(buffer as *mut T as _, mem::size_of::<T>() as u32)
+		}
+		let (code, (input, size), output) = match &mut self.buffer {
+			Buffer::X360(buffer) => (bus::IOCTL_XUSB_SUBMIT_REPORT, io(buffer), false),
+			#[cfg(feature = "unstable_ds4")]
+			Buffer::DS4(buffer) => (bus::IOCTL_DS4_SUBMIT_REPORT, io(buffer), false),
+			#[cfg(feature = "xusb-rumble")]
+			Buffer::XNotification(buffer) => (bus::IOCTL_XUSB_REQUEST_NOTIFICATION, io(buffer), true),
+			#[cfg(feature = "ds4-output")]
+			Buffer::DS4Notification(buffer) => (bus::IOCTL_DS4_REQUEST_NOTIFICATION, io(buffer), true),
+		};
+		let (out_buffer, out_size) = if output { (input, size) } else { (ptr::null_mut(), 0) };
+		bus::device_io_control_async(device, code, input, size, out_buffer, out_size, &mut self.overlapped)
+	}
+
+	fn result(&self, result: Result<(), u32>) -> Result<Completion, Error> {
+		match &self.buffer {
+			Buffer::X360(_) => update_result(result),
+			#[cfg(feature = "unstable_ds4")]
+			Buffer::DS4(_) => update_result(result),
+			#[cfg(feature = "xusb-rumble")]
+			Buffer::XNotification(buffer) => {
+				notification_result(result)?;
+				Ok(Completion::XNotification(XNotification {
+					large_motor: buffer.LargeMotor,
+					small_motor: buffer.SmallMotor,
+					led_number: buffer.LedNumber,
+				}))
+			},
+			#[cfg(feature = "ds4-output")]
+			Buffer::DS4Notification(buffer) => {
+				notification_result(result)?;
+				Ok(Completion::DS4Notification(DS4Notification {
+					large_motor: buffer.LargeMotor,
+					small_motor: buffer.SmallMotor,
+					lightbar: DS4LightbarColor {
+						red: buffer.LightbarRed,
+						green: buffer.LightbarGreen,
+						blue: buffer.LightbarBlue,
+					},
+				}))
+			},
+		}
+	}
+}
+
+fn update_result(result: Result<(), u32>) -> Result<Completion, Error> {
+	match result {
+		Ok(()) => Ok(Completion::Update),
+		Err(winerror::ERROR_DEV_NOT_EXIST) => Err(Error::TargetNotReady),
+		Err(err) => Err(Error::WinError(err)),
+	}
+}
+
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
+fn notification_result(result: Result<(), u32>) -> Result<(), Error> {
+	match result {
+		Ok(()) => Ok(()),
+		Err(winerror::ERROR_OPERATION_ABORTED) => Err(Error::OperationAborted),
+		Err(err) => Err(Error::WinError(err)),
+	}
+}
+
+/// Issues requests for many targets and collects their completions on a single thread.
+///
+/// Instead of waiting on every request, requests are issued with `begin_*` methods which return immediately
+/// and their completions are collected with [`poll`](Self::poll) through an I/O completion port.
+/// This lets a single thread drive dozens of targets, where the blocking APIs need a thread per target to overlap the driver round trips.
+///
+/// Targets must be created with the client of the driver, or a reference to it, not a [clone](Client::try_clone).
+/// The blocking APIs of the targets keep working alongside the driver.
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// let client = vigem_client::Client::connect().unwrap();
+/// let mut targets: Vec<_> = (0..4).map(|_| vigem_client::Xbox360Wired::new(&client, vigem_client::TargetId::XBOX360_WIRED)).collect();
+/// for target in &mut targets {
+/// 	target.plugin().unwrap();
+/// 	target.wait_ready().unwrap();
+/// }
+///
+/// let mut driver = vigem_client::IocpDriver::new(&client).unwrap();
+/// let gamepad = vigem_client::XGamepad::default();
+/// for target in &targets {
+/// 	driver.begin_update(target, &gamepad).unwrap();
+/// }
+/// while driver.pending() > 0 {
+/// 	for event in driver.poll(Some(Duration::from_millis(100))).unwrap() {
+/// 		println!("{:?}", event);
+/// 	}
+/// }
+/// ```
+pub struct IocpDriver<CL: Borrow<Client>> {
+	client: CL,
+	port: HANDLE,
+	next_key: u64,
+	pending: HashMap<usize, Box<Request>>,
+}
+
+// The pending requests are only touched through `&mut self` and by the driver
+unsafe impl<CL: Borrow<Client> + Send> Send for IocpDriver<CL> {}
+unsafe impl<CL: Borrow<Client> + Sync> Sync for IocpDriver<CL> {}
+
+impl<CL: Borrow<Client>> IocpDriver<CL> {
+	/// Creates the driver of the client's completion port, enabling it if needed.
+	///
+	/// Returns [`Error::CompletionPortInUse`] if the client already has a driver.
+	#[inline(never)]
+	pub fn new(client: CL) -> Result<IocpDriver<CL>, Error> {
+		let port = client.borrow().claim_iocp()?;
+		Ok(IocpDriver { client, port, next_key: 1, pending: HashMap::new() })
+	}
+
+	/// Returns the client.
+	#[inline]
+	pub fn client(&self) -> &CL {
+		&self.client
+	}
+
+	/// Returns the number of requests issued which have not been returned by [`poll`](Self::poll) yet.
+	#[inline]
+	pub fn pending(&self) -> usize {
+		self.pending.len()
+	}
+
+	fn issue(&mut self, serial_no: u32, buffer: Buffer) -> Result<CompletionKey, Error> {
+		let key = CompletionKey(self.next_key);
+		let mut request = Box::new(Request { overlapped: unsafe { mem::zeroed() }, key, serial_no, buffer });
+		let client = self.client.borrow();
+		let result = {
+			let _pass = client.enter();
+			unsafe { request.issue(client.device) }
+		};
+		// Requests failing synchronously are not posted to the completion port
+		if let Err(err) = result {
+			return request.result(Err(err)).map(|_| key);
+		}
+		self.next_key += 1;
+		self.pending.insert(&request.overlapped as *const OVERLAPPED as usize, request);
+		Ok(key)
+	}
+
+	fn check<T: Borrow<Client>>(&self, client: &T, serial_no: u32) -> Result<(), Error> {
+		if client.borrow().device != self.client.borrow().device {
+			return Err(Error::InvalidClient);
+		}
+		if serial_no == 0 {
+			return Err(Error::NotPluggedIn);
+		}
+		Ok(())
+	}
+
+	/// Starts updating the Xbox360 target, its completion is returned by [`poll`](Self::poll).
+	///
+	/// Unlike [`Xbox360Wired::update`] the detached policy does not apply, the target must be plugged in.
+	#[inline(never)]
+	pub fn begin_update<T: Borrow<Client>>(&mut self, target: &Xbox360Wired<T>, gamepad: &XGamepad) -> Result<CompletionKey, Error> {
+		let serial_no = target.serial_no();
+		self.check(target.client(), serial_no)?;
+		let buffer = Buffer::X360(bus::XUsbSubmitReport::new(serial_no, *gamepad));
+		match target.watch() {
+			Some(watch) => watch.update(|| self.issue(serial_no, buffer)),
+			None => self.issue(serial_no, buffer),
+		}
+	}
+
+	/// Starts updating the DualShock4 target, its completion is returned by [`poll`](Self::poll).
+	///
+	/// Unlike [`DualShock4Wired::update`] the detached policy does not apply, the target must be plugged in.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline(never)]
+	pub fn begin_update_ds4<T: Borrow<Client>>(&mut self, target: &DualShock4Wired<T>, report: &DS4Report) -> Result<CompletionKey, Error> {
+		let serial_no = target.serial_no();
+		self.check(target.client(), serial_no)?;
+		if target.validate_reports() {
+			report.validate()?;
+		}
+		let buffer = Buffer::DS4(bus::DS4SubmitReport::new(serial_no, *report));
+		match target.watch() {
+			Some(watch) => watch.update(|| self.issue(serial_no, buffer)),
+			None => self.issue(serial_no, buffer),
+		}
+	}
+
+	/// Requests the next rumble or led notification of the Xbox360 target, its completion is returned by [`poll`](Self::poll).
+	///
+	/// Issue the next request after each completion to keep receiving notifications.
+	/// Pending requests are not aborted when the target is unplugged, [cancel](Self::cancel) them first.
+	#[cfg(feature = "xusb-rumble")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
+	#[inline(never)]
+	pub fn begin_notification<T: Borrow<Client>>(&mut self, target: &Xbox360Wired<T>) -> Result<CompletionKey, Error> {
+		let serial_no = target.serial_no();
+		self.check(target.client(), serial_no)?;
+		self.issue(serial_no, Buffer::XNotification(bus::XUsbRequestNotification::new(serial_no)))
+	}
+
+	/// Requests the next rumble or lightbar notification of the DualShock4 target, its completion is returned by [`poll`](Self::poll).
+	///
+	/// Issue the next request after each completion to keep receiving notifications.
+	/// Pending requests are not aborted when the target is unplugged, [cancel](Self::cancel) them first.
+	#[cfg(feature = "ds4-output")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+	#[inline(never)]
+	pub fn begin_ds4_notification<T: Borrow<Client>>(&mut self, target: &DualShock4Wired<T>) -> Result<CompletionKey, Error> {
+		let serial_no = target.serial_no();
+		self.check(target.client(), serial_no)?;
+		self.issue(serial_no, Buffer::DS4Notification(bus::DS4RequestNotification::new(serial_no)))
+	}
+
+	/// Cancels a pending request, its completion is still returned by [`poll`](Self::poll).
+	///
+	/// Returns `false` if the request is not pending.
+	#[inline]
+	pub fn cancel(&mut self, key: CompletionKey) -> bool {
+		let device = self.client.borrow().device;
+		match self.pending.values_mut().find(|request| request.key == key) {
+			Some(request) => {
+				unsafe { CancelIoEx(device, &mut request.overlapped); }
+				true
+			},
+			None => false,
+		}
+	}
+
+	/// Collects the completed requests, waiting up to the timeout for at least one to complete.
+	///
+	/// Waits indefinitely if the timeout is `None`, returns immediately if no requests are pending.
+	#[inline(never)]
+	pub fn poll(&mut self, timeout: Option<time::Duration>) -> Result<Vec<CompletionEvent>, Error> {
+		let mut events = Vec::new();
+		if self.pending.is_empty() {
+			return Ok(events);
+		}
+
+		let timeout_ms = match timeout {
+			Some(timeout) => timeout.as_millis().min(INFINITE as u128 - 1) as u32,
+			None => INFINITE,
+		};
+		let mut entries: [OVERLAPPED_ENTRY; 64] = unsafe { mem::zeroed() };
+		let mut removed = 0;
+		if unsafe { GetQueuedCompletionStatusEx(self.port, entries.as_mut_ptr(), entries.len() as u32, &mut removed, timeout_ms, 0) } == 0 {
+			let err = unsafe { GetLastError() };
+			if err == winerror::WAIT_TIMEOUT {
+				return Ok(events);
+			}
+			return Err(Error::WinError(err));
+		}
+
+		let device = self.client.borrow().device;
+		for entry in &entries[..removed as usize] {
+			let Some(mut request) = self.pending.remove(&(entry.lpOverlapped as usize)) else { continue };
+			let mut transferred = 0;
+			let result = match unsafe { GetOverlappedResult(device, &mut request.overlapped, &mut transferred, /*bWait: */0) } {
+				0 => Err(unsafe { GetLastError() }),
+				_ => Ok(()),
+			};
+			events.push(CompletionEvent { key: request.key, serial_no: request.serial_no, result: request.result(result) });
+		}
+		Ok(events)
+	}
+}
+
+impl<CL: Borrow<Client>> fmt::Debug for IocpDriver<CL> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("IocpDriver")
+			.field("client", self.client.borrow())
+			.field("pending", &self.pending.len())
+			.finish()
+	}
+}
+
+impl<CL: Borrow<Client>> Drop for IocpDriver<CL> {
+	fn drop(&mut self) {
+		// Cancel the pending requests and collect their completions, the driver may write to them until then
+		let device = self.client.borrow().device;
+		for request in self.pending.values_mut() {
+			unsafe { CancelIoEx(device, &mut request.overlapped); }
+		}
+		let deadline = time::Instant::now() + time::Duration::from_millis(bus::DROP_TIMEOUT_MS as u64);
+		while !self.pending.is_empty() {
+			let remaining = deadline.saturating_duration_since(time::Instant::now());
+			if remaining == time::Duration::ZERO || self.poll(Some(remaining)).is_err() {
+				break;
+			}
+		}
+		// Give up on a wedged driver
+		for (_, request) in self.pending.drain() {
+			mem::forget(request);
+		}
+		self.client.borrow().release_iocp();
+	}
+}
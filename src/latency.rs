@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+use std::time;
+
+/// Capture to submit latencies of the most recent submissions, see [`Xbox360Wired::update_stamped`](crate::Xbox360Wired::update_stamped).
+///
+/// Also measures the phase offsets of a [`SubmitScheduler`](crate::SubmitScheduler).
+///
+/// A ring buffer keeping the last `capacity` samples, older samples are dropped.
+///
+/// ```
+/// use std::time::{Duration, Instant};
+///
+/// let mut stats = vigem_client::LatencyStats::new(100);
+/// let captured_at = Instant::now();
+/// for ms in 1..=10 {
+/// 	stats.record(captured_at, captured_at + Duration::from_millis(ms));
+/// }
+/// assert_eq!(stats.percentile(50.0), Some(Duration::from_millis(5)));
+/// assert_eq!(stats.percentile(90.0), Some(Duration::from_millis(9)));
+/// assert_eq!(stats.max(), Some(Duration::from_millis(10)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct LatencyStats {
+	samples: VecDeque<time::Duration>,
+	capacity: usize,
+	total: u64,
+}
+
+impl Default for LatencyStats {
+	#[inline]
+	fn default() -> Self {
+		LatencyStats::new(LatencyStats::DEFAULT_CAPACITY)
+	}
+}
+
+impl LatencyStats {
+	/// Number of samples kept by default.
+	pub const DEFAULT_CAPACITY: usize = 1024;
+
+	/// Keeps the last `capacity` samples.
+	///
+	/// Panics if the capacity is zero.
+	#[inline]
+	pub fn new(capacity: usize) -> LatencyStats {
+		assert!(capacity > 0, "capacity must be positive");
+		LatencyStats { samples: VecDeque::with_capacity(capacity), capacity, total: 0 }
+	}
+
+	/// Returns the number of samples kept.
+	#[inline]
+	pub fn capacity(&self) -> usize {
+		self.capacity
+	}
+
+	/// Returns the number of samples.
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.samples.len()
+	}
+
+	/// Returns if there are no samples.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.samples.is_empty()
+	}
+
+	/// Returns the number of samples recorded since creation, including those dropped.
+	#[inline]
+	pub fn total(&self) -> u64 {
+		self.total
+	}
+
+	/// Records the latency between capturing the input and submitting it.
+	///
+	/// A submission before the capture counts as zero latency.
+	#[inline]
+	pub fn record(&mut self, captured_at: time::Instant, submitted_at: time::Instant) {
+		self.record_latency(submitted_at.saturating_duration_since(captured_at));
+	}
+
+	/// Records a latency.
+	#[inline]
+	pub fn record_latency(&mut self, latency: time::Duration) {
+		if self.samples.len() == self.capacity {
+			self.samples.pop_front();
+		}
+		self.samples.push_back(latency);
+		self.total += 1;
+	}
+
+	/// Returns the samples, oldest first.
+	#[inline]
+	pub fn samples(&self) -> impl Iterator<Item = time::Duration> + '_ {
+		self.samples.iter().copied()
+	}
+
+	/// Discards the samples.
+	#[inline]
+	pub fn clear(&mut self) {
+		self.samples.clear();
+	}
+
+	/// Returns the most recent latency.
+	#[inline]
+	pub fn last(&self) -> Option<time::Duration> {
+		self.samples.back().copied()
+	}
+
+	/// Returns the smallest latency.
+	#[inline]
+	pub fn min(&self) -> Option<time::Duration> {
+		self.samples().min()
+	}
+
+	/// Returns the largest latency.
+	#[inline]
+	pub fn max(&self) -> Option<time::Duration> {
+		self.samples().max()
+	}
+
+	/// Returns the mean latency.
+	#[inline]
+	pub fn mean(&self) -> Option<time::Duration> {
+		if self.samples.is_empty() {
+			return None;
+		}
+		let sum: u128 = self.samples().map(|sample| sample.as_nanos()).sum();
+		Some(time::Duration::from_nanos((sum / self.samples.len() as u128) as u64))
+	}
+
+	/// Returns the latency at the percentile from 0 to 100, by the nearest rank.
+	///
+	/// The percentile is clamped, 0 returns the smallest and 100 the largest latency.
+	#[inline(never)]
+	pub fn percentile(&self, percentile: f64) -> Option<time::Duration> {
+		if self.samples.is_empty() {
+			return None;
+		}
+		let mut sorted: Vec<_> = self.samples().collect();
+		sorted.sort_unstable();
+		let rank = (percentile.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+		Some(sorted[rank.clamp(1, sorted.len()) - 1])
+	}
+
+	/// Counts the samples in buckets of the given width starting at zero.
+	///
+	/// The last bucket also counts all the samples beyond it.
+	/// Panics if the width is zero or there are no buckets.
+	#[inline(never)]
+	pub fn histogram(&self, width: time::Duration, buckets: usize) -> Vec<u64> {
+		assert!(width > time::Duration::ZERO && buckets > 0, "histogram needs buckets of positive width");
+		let mut counts = vec![0; buckets];
+		for sample in self.samples() {
+			let index = (sample.as_nanos() / width.as_nanos()).min(buckets as u128 - 1) as usize;
+			counts[index] += 1;
+		}
+		counts
+	}
+}
+
+/// What became of an update, see `update_timed`.
+#[cfg(windows)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Submitted {
+	/// Skipped by the detached policy or coalesced by the maximum update rate.
+	Skipped,
+	/// Submitted to the driver, with the round trip of the request if it was timed.
+	Sent(Option<time::Duration>),
+}
@@ -0,0 +1,74 @@
+use std::mem;
+
+/// Version of the byte layouts of the public `repr(C)` report structures.
+///
+/// Bumped whenever the layout of any of them changes, see their `LAYOUT_FINGERPRINT` constants:
+/// [`XGamepad`](crate::XGamepad::LAYOUT_FINGERPRINT), [`XButtons`](crate::XButtons::LAYOUT_FINGERPRINT),
+/// [`TargetId`](crate::TargetId::LAYOUT_FINGERPRINT), `XNotification`, `DS4Report`, `DS4Buttons`, `DS4SpecialButtons`, `DS4TouchReport` and `DS4ReportEx`.
+///
+/// Processes exchanging these structures as bytes, eg. through [`XGamepad::to_bytes`](crate::XGamepad::to_bytes),
+/// should agree on the version and the fingerprints of the structures they exchange before doing so:
+///
+/// ```
+/// use vigem_client::{XGamepad, WIRE_FORMAT_VERSION};
+///
+/// // Sent by both sides of the connection on connect
+/// let handshake = (WIRE_FORMAT_VERSION, XGamepad::LAYOUT_FINGERPRINT);
+///
+/// # let peer = handshake;
+/// // Refuse to exchange reports with a peer built against another layout
+/// if peer != handshake {
+/// 	panic!("peer report layout {:?} does not match {:?}", peer, handshake);
+/// }
+/// ```
+///
+/// The fingerprints catch layout changes the peer's version was not bumped for, the version alone suffices between releases of this crate.
+pub const WIRE_FORMAT_VERSION: u32 = 1;
+
+/// Computes the fingerprint of the layout of a type from the offsets of its fields in declaration order.
+///
+/// Covers the size and alignment of the type and its field offsets, not the names or types of the fields.
+/// Prefer [`layout_fingerprint!`](crate::layout_fingerprint!) which takes the offsets of the named fields.
+pub const fn layout_fingerprint<T>(offsets: &[usize]) -> u64 {
+	// FNV-1a over the little-endian bytes of every value
+	const fn mix(mut hash: u64, value: u64) -> u64 {
+		let bytes = value.to_le_bytes();
+		let mut i = 0;
+		while i < bytes.len() {
+			hash ^= bytes[i] as u64;
+			hash = hash.wrapping_mul(0x100000001b3);
+			i += 1;
+		}
+		hash
+	}
+	let mut hash = 0xcbf29ce484222325;
+	hash = mix(hash, mem::size_of::<T>() as u64);
+	hash = mix(hash, mem::align_of::<T>() as u64);
+	hash = mix(hash, offsets.len() as u64);
+	let mut i = 0;
+	while i < offsets.len() {
+		hash = mix(hash, offsets[i] as u64);
+		i += 1;
+	}
+	hash
+}
+
+/// Computes the fingerprint of the layout of a struct, see [`layout_fingerprint`](fn@crate::layout_fingerprint).
+///
+/// The fields must be listed in declaration order.
+///
+/// ```
+/// #[repr(C)]
+/// struct Mirror { a: u8, b: u16 }
+/// #[repr(C)]
+/// struct Widened { a: u32, b: u16 }
+///
+/// const MIRROR: u64 = vigem_client::layout_fingerprint!(Mirror { a, b });
+/// assert_ne!(MIRROR, vigem_client::layout_fingerprint!(Widened { a, b }));
+/// ```
+#[macro_export]
+macro_rules! layout_fingerprint {
+	($ty:ty { $($field:ident),* $(,)? }) => {
+		$crate::layout_fingerprint::<$ty>(&[$(::core::mem::offset_of!($ty, $field)),*])
+	};
+}
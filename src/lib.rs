@@ -6,17 +6,21 @@ ViGEm client in Rust
 This crate implements a client for the [ViGEmBus Driver](https://github.com/ViGEm/ViGEmBus).
 The driver must be installed for this library to have any use.
 
+ViGEmBus is only available on Windows.
+On other platforms the report types remain available and [`Client::connect`] fails with [`Error::BusNotFound`],
+targets can be constructed but every driver request fails.
+
 The [`Client`] contains the connection to the ViGEmBus driver.
 Start by connecting to the service:
 
-```
+```no_run
 let client = vigem_client::Client::connect().unwrap();
 ```
 
 With a client instance virtual controllers (targets) can be created (eg. [`Xbox360Wired::new`] and [`DualShock4Wired::new`]).
 These targets are constructed from a client and a [`TargetId`].
 
-```
+```no_run
 let client = vigem_client::Client::connect().unwrap();
 
 # let id = vigem_client::TargetId::XBOX360_WIRED;
@@ -27,7 +31,7 @@ let target = vigem_client::Xbox360Wired::new(client, id);
 
 A client can be used by multiple targets by passing a shared borrow of the client:
 
-```
+```no_run
 let client = vigem_client::Client::connect().unwrap();
 
 # let id = vigem_client::TargetId::XBOX360_WIRED;
@@ -37,7 +41,7 @@ let target2 = vigem_client::Xbox360Wired::new(&client, id);
 
 For memory management reasons you can also pass `Rc` or `Arc` clients:
 
-```
+```no_run
 use std::rc::Rc;
 let client = Rc::new(vigem_client::Client::connect().unwrap());
 
@@ -104,32 +108,214 @@ let _ = target.update(&gamepad);
 ```
 
 The DualShock4Wired target is under development.
+
+The [`prelude`] imports the commonly used items in a single line:
+
+```no_run
+use vigem_client::prelude::*;
+
+let mut target = Xbox360Wired::new(Client::connect().unwrap(), TargetId::XBOX360_WIRED);
+```
+
+Threading
+---------
+
+The [`Client`] is `Send` and `Sync`, every request to the driver waits on its own event.
+Targets are `Send` and `Sync` exactly when their client parameter is:
+
+| Client parameter | `Send` | `Sync` |
+|------------------|--------|--------|
+| `Client` | yes | yes |
+| `&Client` | yes | yes |
+| `Arc<Client>` | yes | yes |
+| `Rc<Client>` | no | no |
+
+Share an `Arc<Client>` to move targets to other threads, updating a target needs `&mut` access.
+Targets have no thread affinity, calls may come from any thread as long as they do not overlap.
+Notification requests (eg. [`XRequestNotification`]) are `Send` and `Sync` and keep their own client,
+requests and polls take `Pin<&mut Self>` so only a single thread waits on the driver at a time.
+The [`IocpDriver`] follows its client parameter like targets do, it drives many targets from a single thread.
+The [`ManualDriver`] is neither, it is meant to be pumped from the host's own thread.
+
+Features
+--------
+
+Parts of the API are only available with the corresponding crate feature enabled:
+
+| Feature | Description |
+|---------|-------------|
+| `unstable_ds4` | The [`DualShock4Wired`] input reports and its methods to update the target. |
+| `xusb-rumble` | Rumble and led notifications of [`Xbox360Wired`] targets, see `Xbox360Wired::request_notification`. |
+| `ds4-output` | Rumble and lightbar notifications of [`DualShock4Wired`] targets, see `DualShock4Wired::request_notification`. Enables `unstable_ds4`. |
+| `c-compat` | The [`compat`](crate::compat) module with free functions named after the C ViGEmClient API. |
+| `test-util` | The [`testgen`](crate::testgen) module with deterministic input generators for examples and tests, and the [`replay`](crate::replay) module verifying submitted reports against golden logs. |
+| `dwm` | The [`DwmVblank`] clock source submitting on the vertical blank of the desktop compositor. |
+| `serde` | Implements `Serialize` and `Deserialize` for the configuration types. |
+| `debug-handles` | Tracks the service handles owned by clients in debug builds, panics on a request through a closed handle or a handle owned twice. |
+| `ffi` | The [`ffi`](crate::ffi) module exporting the core operations with a C ABI, build as `cdylib` to call them from other languages. |
+| `interop` | The [`GenericGamepadState`] conversions from the gamepad states of modern capture APIs such as Windows.Gaming.Input and GameInput. |
+| `xusb-rumble-unstable` | Deprecated alias of `xusb-rumble`. |
+| `unstable_xtarget_notification` | Deprecated alias of `xusb-rumble`. |
+
+The [`features`](crate::features) module tells which features are enabled, eg. for downstream crates building on the optional API.
 */
 
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+#[cfg(windows)]
 mod bus;
+#[cfg(windows)]
 mod event;
+mod sync;
+#[cfg(windows)]
+mod gate;
 mod error;
+#[cfg(windows)]
 mod client;
+mod attached;
 mod x360;
 mod ds4;
+mod filter;
+mod bindings;
+mod target;
+mod set;
+#[cfg(feature = "interop")]
+mod interop;
+mod rumble;
+#[cfg(feature = "unstable_ds4")]
+mod reportmode;
+#[cfg(feature = "unstable_ds4")]
+mod touchpad;
+#[cfg(feature = "ds4-output")]
+mod lightbar;
+mod pump;
+#[cfg(windows)]
+mod watchdog;
+#[cfg(windows)]
+mod iocp;
+mod schedule;
+#[cfg(windows)]
+mod fleet;
+#[cfg(windows)]
+mod raw;
+mod shaper;
+mod trigger;
+mod ratelimit;
+mod drive;
+#[cfg(windows)]
+mod devpath;
+#[cfg(windows)]
+mod panicguard;
+mod statecell;
+#[cfg(all(windows, any(feature = "xusb-rumble", feature = "ds4-output")))]
+mod fanout;
+mod sanitize;
+mod latency;
+#[cfg(windows)]
+mod handles;
+#[cfg(windows)]
+mod profile;
+mod smooth;
+#[cfg(windows)]
+mod tuning;
+mod idle;
+mod layout;
+#[cfg(feature = "unstable_ds4")]
+mod crossmap;
+#[cfg(not(windows))]
+mod stub;
+
+pub mod consts;
+pub mod features;
+pub mod prelude;
+
+#[cfg(all(windows, feature = "c-compat"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "c-compat")))]
+pub mod compat;
+
+#[cfg(feature = "test-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+pub mod testgen;
 
+#[cfg(feature = "test-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+pub mod replay;
+
+#[cfg(all(windows, feature = "ffi"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "ffi")))]
+pub mod ffi;
+
+#[cfg(windows)]
 use self::event::*;
+#[cfg(windows)]
+use self::gate::*;
+#[cfg(windows)]
+use self::attached::*;
+#[cfg(windows)]
+use self::watchdog::*;
 pub use self::error::Error;
+#[cfg(windows)]
 pub use self::client::*;
 pub use self::x360::*;
 pub use self::ds4::*;
+pub use self::filter::*;
+pub use self::bindings::*;
+pub use self::target::*;
+pub use self::set::*;
+#[cfg(feature = "interop")]
+pub use self::interop::*;
+pub use self::rumble::*;
+#[cfg(feature = "unstable_ds4")]
+pub use self::reportmode::*;
+#[cfg(feature = "unstable_ds4")]
+pub use self::touchpad::*;
+#[cfg(feature = "ds4-output")]
+pub use self::lightbar::*;
+pub use self::pump::*;
+#[cfg(windows)]
+pub use self::iocp::*;
+pub use self::schedule::*;
+#[cfg(windows)]
+pub use self::fleet::*;
+#[cfg(windows)]
+pub use self::raw::*;
+pub use self::shaper::*;
+pub use self::trigger::*;
+pub use self::ratelimit::*;
+pub use self::drive::*;
+#[cfg(windows)]
+pub use self::panicguard::*;
+pub use self::statecell::*;
+pub use self::sanitize::*;
+pub use self::latency::*;
+#[cfg(windows)]
+pub use self::profile::*;
+pub use self::smooth::*;
+#[cfg(windows)]
+pub use self::tuning::*;
+pub use self::idle::*;
+pub use self::layout::*;
+#[cfg(feature = "unstable_ds4")]
+pub use self::crossmap::*;
+#[cfg(not(windows))]
+pub use self::stub::*;
 
 /// Vendor and product ids.
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct TargetId {
 	pub vendor: u16,
 	pub product: u16,
 }
 impl TargetId {
+	/// Fingerprint of the byte layout, see [`WIRE_FORMAT_VERSION`](crate::WIRE_FORMAT_VERSION).
+	pub const LAYOUT_FINGERPRINT: u64 = crate::layout_fingerprint!(TargetId { vendor, product });
+
 	/// Default vender and product ids for a wired Xbox360 target.
 	pub const XBOX360_WIRED: TargetId = TargetId { vendor: 0x045E, product: 0x028E };
 	/// Default vender and product ids for a wired DualShock4 target.
 	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
 	pub const DUALSHOCK4_WIRED: TargetId = TargetId { vendor: 0x054C, product: 0x05C4 };
 }
@@ -0,0 +1,258 @@
+use std::{error, time};
+#[cfg(windows)]
+use std::thread;
+use crate::*;
+
+/// Destination of the lightbar color of a DualShock4 target, eg. a physical controller or RGB software.
+#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+pub trait LightbarSink {
+	/// Shows the color.
+	fn set_color(&mut self, color: DS4LightbarColor) -> Result<(), Box<dyn error::Error + Send + Sync>>;
+}
+
+impl<S: LightbarSink + ?Sized> LightbarSink for &mut S {
+	#[inline]
+	fn set_color(&mut self, color: DS4LightbarColor) -> Result<(), Box<dyn error::Error + Send + Sync>> {
+		(**self).set_color(color)
+	}
+}
+impl<S: LightbarSink + ?Sized> LightbarSink for Box<S> {
+	#[inline]
+	fn set_color(&mut self, color: DS4LightbarColor) -> Result<(), Box<dyn error::Error + Send + Sync>> {
+		(**self).set_color(color)
+	}
+}
+
+/// Prints the lightbar color to stdout.
+#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PrintSink;
+
+impl LightbarSink for PrintSink {
+	#[inline]
+	fn set_color(&mut self, color: DS4LightbarColor) -> Result<(), Box<dyn error::Error + Send + Sync>> {
+		println!("lightbar: #{:02X}{:02X}{:02X}", color.red, color.green, color.blue);
+		Ok(())
+	}
+}
+
+/// How the lightbar color is delivered to a [`LightbarSink`].
+///
+/// Colors arriving faster than the sink is written are coalesced, only the most recent color is delivered.
+#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LightbarPolicy {
+	/// Maximum rate of writes to the sink in Hz, zero for unlimited.
+	pub max_hz: f32,
+	/// Skips writing a color identical to the previously written color.
+	pub skip_identical: bool,
+	/// Fades from the current color to a new color over this duration, zero to switch immediately.
+	pub smoothing: time::Duration,
+}
+
+impl Default for LightbarPolicy {
+	#[inline]
+	fn default() -> LightbarPolicy {
+		LightbarPolicy::new()
+	}
+}
+
+impl LightbarPolicy {
+	/// Delivers every color immediately.
+	#[inline]
+	pub const fn new() -> LightbarPolicy {
+		LightbarPolicy { max_hz: 0.0, skip_identical: false, smoothing: time::Duration::ZERO }
+	}
+
+	// Minimum time between writes
+	fn interval(&self) -> time::Duration {
+		if self.max_hz > 0.0 && self.max_hz.is_finite() {
+			time::Duration::from_secs_f64(1.0 / self.max_hz as f64)
+		}
+		else {
+			time::Duration::ZERO
+		}
+	}
+}
+
+/// Delivers lightbar colors to a sink according to a [`LightbarPolicy`].
+///
+/// Driven by the caller's clock, see [`forward_lightbar`] to forward the notifications of a target on a thread.
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use vigem_client::{DS4LightbarColor, LightbarForwarder, LightbarPolicy, PrintSink};
+///
+/// let policy = LightbarPolicy { max_hz: 30.0, skip_identical: true, ..LightbarPolicy::new() };
+/// let mut forwarder = LightbarForwarder::new(PrintSink, policy);
+///
+/// let now = Instant::now();
+/// forwarder.push(DS4LightbarColor { red: 255, green: 0, blue: 0 }, now);
+/// assert_eq!(forwarder.tick(now).unwrap(), None);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+#[derive(Debug)]
+pub struct LightbarForwarder<S> {
+	sink: S,
+	policy: LightbarPolicy,
+	target: Option<DS4LightbarColor>,
+	fade_from: [f32; 3],
+	fade_start: time::Instant,
+	written: Option<DS4LightbarColor>,
+	last_write: Option<time::Instant>,
+	dirty: bool,
+}
+
+impl<S: LightbarSink> LightbarForwarder<S> {
+	/// Creates a forwarder, nothing is written until the first color is pushed.
+	#[inline]
+	pub fn new(sink: S, policy: LightbarPolicy) -> LightbarForwarder<S> {
+		LightbarForwarder {
+			sink, policy,
+			target: None,
+			fade_from: [0.0; 3],
+			fade_start: time::Instant::now(),
+			written: None,
+			last_write: None,
+			dirty: false,
+		}
+	}
+
+	/// Returns the sink.
+	#[inline]
+	pub fn sink(&self) -> &S {
+		&self.sink
+	}
+
+	/// Returns the sink.
+	#[inline]
+	pub fn sink_mut(&mut self) -> &mut S {
+		&mut self.sink
+	}
+
+	/// Returns the policy.
+	#[inline]
+	pub fn policy(&self) -> &LightbarPolicy {
+		&self.policy
+	}
+
+	/// Pushes a new color, replacing any color not yet delivered.
+	pub fn push(&mut self, color: DS4LightbarColor, now: time::Instant) {
+		// Fade from whatever is showing right now, the first color is shown immediately
+		self.fade_from = match self.target {
+			Some(_) => self.current(now),
+			None => to_f32(color),
+		};
+		self.fade_start = now;
+		self.target = Some(color);
+		self.dirty = true;
+	}
+
+	/// Writes to the sink if due.
+	///
+	/// Returns when the forwarder wants to be ticked again, `None` when idle until the next color is pushed.
+	pub fn tick(&mut self, now: time::Instant) -> Result<Option<time::Instant>, Box<dyn error::Error + Send + Sync>> {
+		if !self.dirty {
+			return Ok(None);
+		}
+
+		if let Some(last_write) = self.last_write {
+			let due = last_write + self.policy.interval();
+			if now < due {
+				return Ok(Some(due));
+			}
+		}
+
+		let fading = now < self.fade_start + self.policy.smoothing;
+		let color = from_f32(self.current(now));
+		if !(self.policy.skip_identical && self.written == Some(color)) {
+			self.sink.set_color(color)?;
+			self.written = Some(color);
+			self.last_write = Some(now);
+		}
+
+		if fading {
+			// Keep fading at the rate limit or roughly every frame when unlimited
+			let step = self.policy.interval().max(FADE_STEP);
+			Ok(Some(now + step))
+		}
+		else {
+			self.dirty = false;
+			Ok(None)
+		}
+	}
+
+	// The color shown at the given moment while fading
+	fn current(&self, now: time::Instant) -> [f32; 3] {
+		let Some(target) = self.target else { return self.fade_from };
+		let target = to_f32(target);
+		let smoothing = self.policy.smoothing.as_secs_f32();
+		let t = if smoothing > 0.0 { (now.saturating_duration_since(self.fade_start).as_secs_f32() / smoothing).min(1.0) } else { 1.0 };
+		let mut color = [0.0; 3];
+		for i in 0..3 {
+			color[i] = self.fade_from[i] + (target[i] - self.fade_from[i]) * t;
+		}
+		color
+	}
+}
+
+/// Spawns a thread forwarding the lightbar color of a DualShock4 target to the sink.
+///
+/// The thread exits with `Ok(())` once the target is unplugged or dropped, or with the error of the sink.
+///
+/// ```no_run
+/// let client = vigem_client::Client::connect().unwrap();
+/// let mut target = vigem_client::DualShock4Wired::new(client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+/// target.plugin().unwrap();
+///
+/// let policy = vigem_client::LightbarPolicy {
+/// 	max_hz: 30.0,
+/// 	skip_identical: true,
+/// 	smoothing: std::time::Duration::from_millis(200),
+/// };
+/// let notification = target.request_notification().unwrap();
+/// let thread = vigem_client::forward_lightbar(notification, vigem_client::PrintSink, policy);
+/// # drop(target); thread.join().unwrap().unwrap();
+/// ```
+#[cfg(windows)]
+#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+pub fn forward_lightbar<S: LightbarSink + Send + 'static>(
+	notification: DS4RequestNotification,
+	sink: S,
+	policy: LightbarPolicy,
+) -> thread::JoinHandle<Result<(), Box<dyn error::Error + Send + Sync>>> {
+	thread::spawn(move || {
+		let _tuning = notification.apply_tuning();
+		let mut forwarder = LightbarForwarder::new(sink, policy);
+		let mut reqn = Box::pin(notification);
+		reqn.as_mut().request();
+		loop {
+			let next = forwarder.tick(time::Instant::now())?;
+			// Wait for the next notification, or poll it while the forwarder has work to do
+			let wait = next.is_none();
+			match reqn.as_mut().poll(wait) {
+				Ok(Some(data)) => {
+					forwarder.push(data.lightbar, time::Instant::now());
+					reqn.as_mut().request();
+				},
+				Ok(None) => {
+					if let Some(next) = next {
+						thread::sleep(next.saturating_duration_since(time::Instant::now()).min(FADE_STEP));
+					}
+				},
+				Err(Error::OperationAborted) => return Ok(()),
+				Err(err) => return Err(err.into()),
+			}
+		}
+	})
+}
+
+const FADE_STEP: time::Duration = time::Duration::from_millis(16);
+
+fn to_f32(color: DS4LightbarColor) -> [f32; 3] {
+	[color.red as f32, color.green as f32, color.blue as f32]
+}
+fn from_f32(color: [f32; 3]) -> DS4LightbarColor {
+	let channel = |value: f32| value.round().clamp(0.0, 255.0) as u8;
+	DS4LightbarColor { red: channel(color[0]), green: channel(color[1]), blue: channel(color[2]) }
+}
@@ -0,0 +1,151 @@
+use std::sync::{Arc, Mutex, MutexGuard, Once, PoisonError, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{panic, process, thread};
+use crate::*;
+
+/// Time waited for the driver to unplug each guarded target.
+const UNPLUG_TIMEOUT_MS: u32 = 500;
+
+/// Guards a target against being left behind holding input when the process panics.
+///
+/// Holds a duplicated client handle to remain usable while the target is borrowed by the panicking thread.
+/// Once it unplugged the target, the target forgets its serial number the next time it is used, see [`take_neutralized`](Self::take_neutralized).
+#[derive(Debug)]
+pub(crate) struct PanicGuard {
+	client: Client,
+	neutral: Neutral,
+	serial_no: Mutex<u32>,
+	neutralized: AtomicBool,
+}
+
+static REGISTRY: Mutex<Vec<Weak<PanicGuard>>> = Mutex::new(Vec::new());
+static INSTALL: Once = Once::new();
+
+impl PanicGuard {
+	pub fn new(client: &Client, neutral: Neutral, serial_no: u32) -> Result<Arc<PanicGuard>, Error> {
+		install_panic_guard();
+		let client = client.try_clone()?;
+		let guard = Arc::new(PanicGuard { client, neutral, serial_no: Mutex::new(serial_no), neutralized: AtomicBool::new(false) });
+		let mut registry = lock(&REGISTRY);
+		registry.retain(|weak| weak.strong_count() > 0);
+		registry.push(Arc::downgrade(&guard));
+		Ok(guard)
+	}
+
+	/// Sets the serial number of the guarded target, zero when it is unplugged.
+	///
+	/// Once this returns the panic guard no longer touches the previous serial number.
+	pub fn set_serial_no(&self, serial_no: u32) {
+		*lock(&self.serial_no) = serial_no;
+		self.neutralized.store(false, Ordering::Release);
+	}
+
+	/// Returns if the guard unplugged the target and the target has not caught up yet.
+	#[inline]
+	pub fn is_neutralized(&self) -> bool {
+		self.neutralized.load(Ordering::Acquire)
+	}
+
+	/// Returns if the guard unplugged the target, the target then treats itself as unplugged.
+	#[inline]
+	pub fn take_neutralized(&self) -> bool {
+		self.neutralized.swap(false, Ordering::AcqRel)
+	}
+
+	// Submits the neutral report and unplugs the target
+	fn neutralize(&self) {
+		let mut serial_no = lock(&self.serial_no);
+		if *serial_no == 0 {
+			return;
+		}
+		let _pass = self.client.enter();
+		let device = self.client.device;
+		unsafe {
+			if let Ok(event) = Event::null().sync() {
+				let _ = match self.neutral {
+					Neutral::Xbox360Wired => bus::XUsbSubmitReport::new(*serial_no, XGamepad::neutral()).ioctl(device, event.handle),
+					#[cfg(feature = "unstable_ds4")]
+					Neutral::DualShock4Wired => bus::DS4SubmitReport::new(*serial_no, DS4Report::neutral()).ioctl(device, event.handle),
+				};
+			}
+			let _ = bus::UnplugTarget::new(*serial_no).ioctl_bounded(device, UNPLUG_TIMEOUT_MS);
+		}
+		*serial_no = 0;
+		self.neutralized.store(true, Ordering::Release);
+	}
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+	mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// With `panic = "abort"` every panic ends the process, otherwise only a panic unwinding out of the main thread does
+fn ends_process(thread_name: Option<&str>) -> bool {
+	cfg!(panic = "abort") || thread_name == Some("main")
+}
+
+// Neutralizes and unplugs all the guarded targets
+fn neutralize_all() {
+	let guards: Vec<_> = lock(&REGISTRY).iter().filter_map(Weak::upgrade).collect();
+	for guard in guards {
+		guard.neutralize();
+	}
+}
+
+/// Installs the panic hook neutralizing and unplugging the guarded targets.
+///
+/// Targets are guarded with `set_panic_guard`, eg. [`Xbox360Wired::set_panic_guard`], which installs the hook if needed.
+/// Install it explicitly to control its order with other panic hooks: the hook calls the hook it replaces after cleaning up.
+/// Installing it again does nothing.
+///
+/// When the process panics the guarded targets are updated with the neutral report and unplugged before the panic continues,
+/// instead of holding their last input until the unwind gets around to dropping them.
+/// The hook also runs with `panic = "abort"`, when the targets are never dropped at all.
+///
+/// Only panics which may end the process are acted upon: every panic with `panic = "abort"`, otherwise panics of the main thread.
+/// Panics of other threads only end that thread, or are caught, and leave the targets alone.
+/// The hook cannot tell whether a panic of the main thread is caught, eg. with [`std::panic::catch_unwind`].
+/// Targets unplugged by a panic which is caught are treated as unplugged from then on:
+/// they forget their serial number, updates follow their [detached policy](DetachedPolicy) and they can be plugged in again.
+///
+/// Nothing runs when the process is terminated without panicking, eg. by `std::process::abort` or a stack overflow.
+/// The driver unplugs the targets of a process once its handles are closed by the system.
+/// `std::process::exit` does not run destructors either, see [`exit_guarded`].
+pub fn install_panic_guard() {
+	INSTALL.call_once(|| {
+		let previous = panic::take_hook();
+		panic::set_hook(Box::new(move |info| {
+			if ends_process(thread::current().name()) {
+				neutralize_all();
+			}
+			previous(info);
+		}));
+	});
+}
+
+/// Neutralizes and unplugs the guarded targets, then exits the process with the exit code.
+///
+/// Best effort replacement of [`std::process::exit`], which does not run the destructors unplugging the targets.
+/// Only targets guarded with `set_panic_guard` are cleaned up, see [`install_panic_guard`].
+pub fn exit_guarded(code: i32) -> ! {
+	neutralize_all();
+	process::exit(code)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn main_thread_ends_process() {
+		assert!(ends_process(Some("main")));
+	}
+
+	#[test]
+	fn other_threads() {
+		// Worker threads, unnamed threads and the test threads only end themselves, unless panics abort
+		for name in [None, Some("worker"), Some("other_threads"), Some("Main")] {
+			assert_eq!(ends_process(name), cfg!(panic = "abort"), "{:?}", name);
+		}
+	}
+}
@@ -0,0 +1,43 @@
+/*!
+Commonly used types, traits and macros in a single import.
+
+```no_run
+use vigem_client::prelude::*;
+
+let client = Client::connect().unwrap();
+let mut target = Xbox360Wired::new(client, TargetId::XBOX360_WIRED);
+target.plugin().unwrap();
+target.wait_ready().unwrap();
+
+let gamepad = XGamepad::pressing(XButtons!(A | X)).left_trigger_t(Trigger::digital());
+target.update(&gamepad).unwrap();
+```
+
+Items behind crate features, eg. [`DS4Report`](crate::DS4Report) with `unstable_ds4`, are only included with their feature enabled.
+
+Stability
+---------
+
+Items may be added in minor releases, they are removed or renamed only in major releases.
+Only items with names specific to this crate are added, so glob importing the prelude next to the standard prelude
+and the preludes of other crates does not become ambiguous in an update.
+Niche items such as the bus internals, drivers and testing utilities are not part of the prelude, import them by path.
+*/
+
+pub use crate::{Client, Error, TargetId, VirtualTarget};
+pub use crate::{Xbox360Wired, XGamepad, XButtons};
+pub use crate::DualShock4Wired;
+pub use crate::Trigger;
+
+#[cfg(feature = "unstable_ds4")]
+pub use crate::{DS4Report, DS4Buttons, DpadDirection};
+
+#[cfg(feature = "xusb-rumble")]
+pub use crate::XNotification;
+#[cfg(all(windows, feature = "xusb-rumble"))]
+pub use crate::XRequestNotification;
+
+#[cfg(feature = "ds4-output")]
+pub use crate::{DS4Notification, DS4LightbarColor};
+#[cfg(all(windows, feature = "ds4-output"))]
+pub use crate::DS4RequestNotification;
@@ -0,0 +1,156 @@
+use std::time;
+use std::borrow::Borrow;
+use crate::*;
+
+/// Settings of a virtual controller, to create identically configured targets across tools.
+///
+/// With the `serde` feature profiles can be stored in any format supported by serde, eg. JSON for sharing between remappers and test tools.
+///
+/// ```no_run
+/// let client = vigem_client::Client::connect()?;
+///
+/// let profile = vigem_client::Profile {
+/// 	filter: vigem_client::InputFilter::compensate_xinput_deadzone(),
+/// 	detached_policy: vigem_client::DetachedPolicy::AutoPlugin,
+/// 	..vigem_client::Profile::xbox360()
+/// };
+///
+/// // The initial report is submitted once the target is ready
+/// let mut target = profile.create_target(&client)?;
+/// target.plugin()?;
+/// target.wait_ready()?;
+///
+/// let gamepad = vigem_client::XGamepad { left_trigger: 1, ..Default::default() };
+/// target.update(&profile.filter.apply(&gamepad).into())?;
+/// # Ok::<(), vigem_client::Error>(())
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Profile {
+	/// Kind of controller.
+	pub kind: TargetKind,
+	/// Vendor and product ids.
+	pub id: TargetId,
+	/// Report submitted once the target is ready, `None` for the neutral report of the kind.
+	pub report: Option<TargetReport>,
+	/// Filter for the gamepad states of Xbox360 controllers, applied by the application before updating the target.
+	pub filter: InputFilter,
+	/// Conversion of Xbox360 gamepad states for DualShock4 controllers, applied by the application before updating the target.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	pub cross_map: Option<CrossMap>,
+	/// See [`Xbox360Wired::set_detached_policy`].
+	pub detached_policy: DetachedPolicy,
+	/// See [`DualShock4Wired::set_validate_reports`], ignored for other kinds.
+	pub validate_reports: bool,
+	/// See [`Xbox360Wired::set_max_update_rate`], only Xbox360 controllers support a maximum update rate.
+	pub max_update_rate: Option<f32>,
+	/// See [`Xbox360Wired::set_stale_timeout`].
+	pub stale_timeout: Option<time::Duration>,
+	/// See [`Xbox360Wired::set_panic_guard`].
+	pub panic_guard: bool,
+}
+
+impl Default for Profile {
+	#[inline]
+	fn default() -> Profile {
+		Profile::xbox360()
+	}
+}
+
+impl Profile {
+	/// Profile of a wired Xbox360 controller with the default settings.
+	#[inline]
+	pub const fn xbox360() -> Profile {
+		Profile::new(TargetKind::Xbox360Wired, TargetId::XBOX360_WIRED)
+	}
+
+	/// Profile of a wired DualShock4 controller with the default settings.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub const fn dualshock4() -> Profile {
+		Profile::new(TargetKind::DualShock4Wired, TargetId::DUALSHOCK4_WIRED)
+	}
+
+	/// Profile with the default settings.
+	#[inline]
+	pub const fn new(kind: TargetKind, id: TargetId) -> Profile {
+		Profile {
+			kind,
+			id,
+			report: None,
+			filter: InputFilter::new(),
+			#[cfg(feature = "unstable_ds4")]
+			cross_map: None,
+			detached_policy: DetachedPolicy::Error,
+			validate_reports: false,
+			max_update_rate: None,
+			stale_timeout: None,
+			panic_guard: false,
+		}
+	}
+
+	/// Returns the report submitted once the target is ready.
+	#[inline]
+	pub fn initial_report(&self) -> TargetReport {
+		match (self.report, self.kind) {
+			(Some(report), _) => report,
+			(None, TargetKind::Xbox360Wired) => TargetReport::Xbox360Wired(XGamepad::neutral()),
+			#[cfg(feature = "unstable_ds4")]
+			(None, TargetKind::DualShock4Wired) => TargetReport::DualShock4Wired(DS4Report::neutral()),
+		}
+	}
+
+	/// Checks the settings can be applied to a target.
+	///
+	/// Fails with [`Error::KindMismatch`] if the report is of another kind than the profile
+	/// and with [`Error::InvalidProfile`] for a maximum update rate which is not positive or not supported by the kind.
+	#[inline]
+	pub fn validate(&self) -> Result<(), Error> {
+		if self.initial_report().kind() != self.kind {
+			return Err(Error::KindMismatch);
+		}
+		if let Some(max_hz) = self.max_update_rate {
+			if self.kind != TargetKind::Xbox360Wired || max_hz.is_nan() || max_hz <= 0.0 {
+				return Err(Error::InvalidProfile);
+			}
+		}
+		Ok(())
+	}
+
+	/// Creates a new target configured by this profile.
+	///
+	/// The target is not plugged in, it submits the [initial report](Self::initial_report) once it is ready.
+	/// Fails if the profile does not [validate](Self::validate).
+	#[cfg(windows)]
+	#[inline(never)]
+	pub fn create_target<CL: Borrow<Client>>(&self, client: CL) -> Result<FleetTarget<CL>, Error> {
+		self.validate()?;
+		match self.kind {
+			TargetKind::Xbox360Wired => {
+				let mut target = Xbox360Wired::new(client, self.id);
+				target.set_detached_policy(self.detached_policy);
+				target.set_max_update_rate(self.max_update_rate)?;
+				target.set_stale_timeout(self.stale_timeout)?;
+				target.set_panic_guard(self.panic_guard)?;
+				if let Some(TargetReport::Xbox360Wired(gamepad)) = self.report {
+					target.set_initial_report(Some(gamepad));
+				}
+				Ok(FleetTarget::Xbox360Wired(target))
+			},
+			#[cfg(feature = "unstable_ds4")]
+			TargetKind::DualShock4Wired => {
+				let mut target = DualShock4Wired::new(client, self.id);
+				target.set_detached_policy(self.detached_policy);
+				target.set_validate_reports(self.validate_reports);
+				target.set_stale_timeout(self.stale_timeout)?;
+				target.set_panic_guard(self.panic_guard)?;
+				if let Some(TargetReport::DualShock4Wired(report)) = self.report {
+					target.set_initial_report(Some(report));
+				}
+				Ok(FleetTarget::DualShock4Wired(target))
+			},
+		}
+	}
+}
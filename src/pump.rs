@@ -0,0 +1,139 @@
+use std::{cell, rc, time};
+#[cfg(windows)]
+use std::borrow::Borrow;
+use crate::*;
+
+/// Components which make progress when pumped from the host's loop, without any threads spawned by this crate.
+///
+/// Pumping never waits on the driver for longer than a synchronous request takes.
+/// Operations which still block inherently are [`wait_ready`](Xbox360Wired::wait_ready) until the target is ready,
+/// and the synchronous requests of `plugin`, `unplug` and `update` until the driver completes them.
+pub trait Pump {
+	/// Makes progress without waiting, `now` is the current time of the host's clock.
+	fn pump(&mut self, now: time::Instant) -> Result<(), Error>;
+}
+
+impl<P: Pump + ?Sized> Pump for &mut P {
+	#[inline]
+	fn pump(&mut self, now: time::Instant) -> Result<(), Error> {
+		(**self).pump(now)
+	}
+}
+impl<P: Pump + ?Sized> Pump for Box<P> {
+	#[inline]
+	fn pump(&mut self, now: time::Instant) -> Result<(), Error> {
+		(**self).pump(now)
+	}
+}
+impl<P: Pump + ?Sized> Pump for rc::Rc<cell::RefCell<P>> {
+	#[inline]
+	fn pump(&mut self, now: time::Instant) -> Result<(), Error> {
+		self.borrow_mut().pump(now)
+	}
+}
+
+/// Submits the update coalesced by the maximum update rate once due, see `set_max_update_rate`.
+/// Neutralizes the target if its input went stale, see `set_stale_timeout_manual`.
+#[cfg(windows)]
+impl<CL: Borrow<Client>> Pump for Xbox360Wired<CL> {
+	#[inline]
+	fn pump(&mut self, now: time::Instant) -> Result<(), Error> {
+		let result = self.pump_rate_limit(now);
+		if let Some(watch) = self.watch() {
+			watch.check(now);
+		}
+		result
+	}
+}
+
+/// Neutralizes the target if its input went stale, see `set_stale_timeout_manual`.
+#[cfg(windows)]
+impl<CL: Borrow<Client>> Pump for DualShock4Wired<CL> {
+	#[inline]
+	fn pump(&mut self, now: time::Instant) -> Result<(), Error> {
+		if let Some(watch) = self.watch() {
+			watch.check(now);
+		}
+		Ok(())
+	}
+}
+
+/// Pumps registered components in one call from a single threaded host.
+///
+/// Shared components can be registered as `Rc<RefCell<_>>` to keep access to them between pumps.
+///
+/// ```no_run
+/// use std::{cell::RefCell, rc::Rc, time::{Duration, Instant}};
+///
+/// # #[cfg(windows)] {
+/// let client = vigem_client::Client::connect().unwrap();
+/// let target = Rc::new(RefCell::new(vigem_client::Xbox360Wired::new(client, vigem_client::TargetId::XBOX360_WIRED)));
+/// target.borrow_mut().set_stale_timeout_manual(Some(Duration::from_millis(500))).unwrap();
+///
+/// let mut driver = vigem_client::ManualDriver::new();
+/// driver.register(target.clone());
+///
+/// // From the host's per-frame callback
+/// driver.pump(Instant::now()).unwrap();
+/// # }
+/// ```
+#[derive(Default)]
+pub struct ManualDriver<'a> {
+	components: Vec<Box<dyn Pump + 'a>>,
+}
+
+impl<'a> ManualDriver<'a> {
+	/// Creates a driver without components.
+	#[inline]
+	pub fn new() -> ManualDriver<'a> {
+		ManualDriver { components: Vec::new() }
+	}
+
+	/// Registers a component, components are pumped in registration order.
+	#[inline]
+	pub fn register<P: Pump + 'a>(&mut self, component: P) {
+		self.components.push(Box::new(component));
+	}
+
+	/// Returns the number of registered components.
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.components.len()
+	}
+
+	/// Returns if no components are registered.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.components.is_empty()
+	}
+
+	/// Pumps every component.
+	///
+	/// All components are pumped even if some fail, returns the first error.
+	pub fn pump(&mut self, now: time::Instant) -> Result<(), Error> {
+		let mut result = Ok(());
+		for component in &mut self.components {
+			if let Err(err) = component.pump(now) {
+				if result.is_ok() {
+					result = Err(err);
+				}
+			}
+		}
+		result
+	}
+}
+
+impl Pump for ManualDriver<'_> {
+	#[inline]
+	fn pump(&mut self, now: time::Instant) -> Result<(), Error> {
+		ManualDriver::pump(self, now)
+	}
+}
+
+impl std::fmt::Debug for ManualDriver<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		f.debug_struct("ManualDriver")
+			.field("components", &self.components.len())
+			.finish()
+	}
+}
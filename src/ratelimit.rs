@@ -0,0 +1,115 @@
+use std::time;
+
+/// Coalesces reports submitted faster than a maximum rate, see [`Xbox360Wired::set_max_update_rate`](crate::Xbox360Wired::set_max_update_rate).
+///
+/// Latest wins: a report offered before the minimum interval since the last submission elapsed replaces the pending report.
+/// The pending report is due once the interval elapsed, it is submitted by the next offer or poll.
+///
+/// ```
+/// use std::time::{Duration, Instant};
+///
+/// let mut limit = vigem_client::RateLimit::new(100.0);
+/// let start = Instant::now();
+/// assert_eq!(limit.offer(1, start), Some(1));
+/// assert_eq!(limit.offer(2, start + Duration::from_millis(4)), None);
+/// assert_eq!(limit.offer(3, start + Duration::from_millis(8)), None);
+/// assert_eq!(limit.poll(start + Duration::from_millis(10)), Some(3));
+/// ```
+#[derive(Clone, Debug)]
+pub struct RateLimit<R> {
+	interval: time::Duration,
+	last: Option<time::Instant>,
+	pending: Option<R>,
+}
+
+impl<R> RateLimit<R> {
+	/// Limits the submissions to the rate in Hz.
+	///
+	/// Panics if the rate is not positive.
+	#[inline]
+	pub fn new(max_hz: f32) -> RateLimit<R> {
+		RateLimit { interval: interval(max_hz), last: None, pending: None }
+	}
+
+	// Limits the submissions to the minimum interval, eg. as captured by a target template
+	#[cfg(windows)]
+	#[inline]
+	pub(crate) fn with_interval(interval: time::Duration) -> RateLimit<R> {
+		RateLimit { interval, last: None, pending: None }
+	}
+
+	/// Returns the minimum interval between submissions.
+	#[inline]
+	pub fn interval(&self) -> time::Duration {
+		self.interval
+	}
+
+	/// Changes the rate in Hz, keeping the pending report.
+	///
+	/// Panics if the rate is not positive.
+	#[inline]
+	pub fn set_max_rate(&mut self, max_hz: f32) {
+		self.interval = interval(max_hz);
+	}
+
+	/// Returns the pending report.
+	#[inline]
+	pub fn pending(&self) -> Option<&R> {
+		self.pending.as_ref()
+	}
+
+	/// Returns when the pending report is due.
+	#[inline]
+	pub fn next_due(&self) -> Option<time::Instant> {
+		self.pending.as_ref()?;
+		Some(self.last? + self.interval)
+	}
+
+	/// Offers a report at the given time.
+	///
+	/// Returns the report to submit now, otherwise the report is kept pending in place of the previously pending report.
+	#[inline]
+	pub fn offer(&mut self, report: R, now: time::Instant) -> Option<R> {
+		self.pending = Some(report);
+		self.poll(now)
+	}
+
+	/// Returns the pending report if it is due.
+	#[inline]
+	pub fn poll(&mut self, now: time::Instant) -> Option<R> {
+		if self.pending.is_none() || matches!(self.last, Some(last) if now < last + self.interval) {
+			return None;
+		}
+		self.last = Some(now);
+		self.pending.take()
+	}
+
+	/// Returns the pending report regardless of the rate, counting it as submitted at the given time.
+	#[inline]
+	pub fn flush(&mut self, now: time::Instant) -> Option<R> {
+		let report = self.pending.take()?;
+		self.last = Some(now);
+		Some(report)
+	}
+
+	/// Puts a report back as pending after submitting it failed, unless a newer report was offered since.
+	///
+	/// It is due again once the interval since the failed submission elapsed, or when flushed.
+	#[inline]
+	pub fn restore(&mut self, report: R) {
+		if self.pending.is_none() {
+			self.pending = Some(report);
+		}
+	}
+
+	/// Discards the pending report.
+	#[inline]
+	pub fn clear(&mut self) {
+		self.pending = None;
+	}
+}
+
+fn interval(max_hz: f32) -> time::Duration {
+	assert!(max_hz > 0.0, "rate must be positive");
+	time::Duration::from_secs_f64(1.0 / max_hz as f64)
+}
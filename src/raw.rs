@@ -0,0 +1,250 @@
+use std::{fmt, mem};
+use std::borrow::Borrow;
+use winapi::shared::winerror;
+use crate::*;
+
+/// A virtual controller of any target type the driver may support.
+///
+/// **Unsupported territory**: an escape hatch to experiment with target types this crate does not know about,
+/// eg. those of driver forks or future driver versions.
+/// Nothing about the target type, its reports or how the driver reacts to them is checked by this crate.
+/// Prefer the typed targets, eg. [`Xbox360Wired`] and [`DualShock4Wired`], whenever possible.
+///
+/// ```no_run
+/// use vigem_client::{RawTarget, TargetId, XGamepad};
+///
+/// let client = vigem_client::Client::connect().unwrap();
+/// let mut target = RawTarget::new(&client, RawTarget::TYPE_XBOX360_WIRED, TargetId::XBOX360_WIRED);
+/// target.plugin().unwrap();
+/// target.wait_ready().unwrap();
+///
+/// // Submits the report in the layout of the Xbox360 target
+/// target.submit_raw(RawTarget::IOCTL_XUSB_SUBMIT_REPORT, &XGamepad::default().to_bytes()).unwrap();
+/// ```
+pub struct RawTarget<CL: Borrow<Client>> {
+	client: CL,
+	event: Event,
+	serial_no: u32,
+	target_type: i32,
+	id: TargetId,
+	uid: TargetUid,
+}
+
+impl RawTarget<Client> {
+	/// Target type of the Xbox360 wired controller.
+	pub const TYPE_XBOX360_WIRED: i32 = bus::TARGET_TYPE_XBOX360_WIRED;
+	/// Target type of the Xbox One wired controller, historically defined by the driver but never implemented.
+	pub const TYPE_XBOXONE_WIRED: i32 = 1;
+	/// Target type of the DualShock4 wired controller.
+	pub const TYPE_DUALSHOCK4_WIRED: i32 = bus::TARGET_TYPE_DUALSHOCK4_WIRED;
+
+	/// Control code submitting the report of an Xbox360 target.
+	pub const IOCTL_XUSB_SUBMIT_REPORT: u32 = bus::IOCTL_XUSB_SUBMIT_REPORT;
+	/// Control code submitting the report of a DualShock4 target.
+	pub const IOCTL_DS4_SUBMIT_REPORT: u32 = bus::IOCTL_DS4_SUBMIT_REPORT;
+}
+
+impl<CL: Borrow<Client>> RawTarget<CL> {
+	/// Creates a new instance, the target type is passed to the driver as is.
+	#[inline]
+	pub fn new(client: CL, target_type: i32, id: TargetId) -> RawTarget<CL> {
+		RawTarget { client, event: Event::null(), serial_no: 0, target_type, id, uid: TargetUid::next() }
+	}
+
+	/// Returns if the controller is plugged in.
+	#[inline]
+	pub fn is_attached(&self) -> bool {
+		self.serial_no != 0
+	}
+
+	/// Returns the target type.
+	#[inline]
+	pub fn target_type(&self) -> i32 {
+		self.target_type
+	}
+
+	/// Returns the vendor and product ids.
+	#[inline]
+	pub fn id(&self) -> TargetId {
+		self.id
+	}
+
+	/// Returns the process-unique identifier of the target, see [`TargetUid`].
+	#[inline]
+	pub fn uid(&self) -> TargetUid {
+		self.uid
+	}
+
+	/// Returns the serial number assigned by the driver, zero if not plugged in.
+	#[inline]
+	pub fn serial_no(&self) -> u32 {
+		self.serial_no
+	}
+
+	/// Returns the client.
+	#[inline]
+	pub fn client(&self) -> &CL {
+		&self.client
+	}
+
+	/// Plugs the controller in.
+	///
+	/// Unlike the typed targets only serial numbers in use are skipped, any other failure is returned immediately.
+	/// A target type the driver does not support fails with the [`Error::WinError`] the driver returns.
+	#[inline(never)]
+	pub fn plugin(&mut self) -> Result<(), Error> {
+		if self.is_attached() {
+			return Err(Error::AlreadyConnected);
+		}
+		self.client.borrow().check_plugin()?;
+
+		let event = self.event.sync()?;
+		let mut plugin = bus::PluginTarget::new(1, self.target_type, self.id.vendor, self.id.product);
+		let client = self.client.borrow();
+		let device = client.device;
+
+		loop {
+			match unsafe { let _pass = client.enter(); plugin.ioctl(device, event.handle) } {
+				Ok(()) => break,
+				// The serial number is in use
+				Err(winerror::ERROR_INVALID_PARAMETER | winerror::ERROR_ALREADY_EXISTS | winerror::ERROR_FILE_EXISTS) => (),
+				Err(err) => return Err(Error::WinError(err)),
+			}
+			plugin.SerialNo += 1;
+			if plugin.SerialNo >= u16::MAX as u32 {
+				return Err(Error::NoFreeSlot);
+			}
+		}
+
+		self.serial_no = plugin.SerialNo;
+		client.track_plugin(self.serial_no);
+		Ok(())
+	}
+
+	/// Unplugs the controller.
+	#[inline(never)]
+	pub fn unplug(&mut self) -> Result<(), Error> {
+		self.unplug_impl(false)
+	}
+
+	// Unplugs the controller, bounded gives up waiting for the driver after `bus::DROP_TIMEOUT_MS`
+	fn unplug_impl(&mut self, bounded: bool) -> Result<(), Error> {
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+		let event = if bounded { None } else { Some(self.event.sync()?) };
+
+		let result = unsafe {
+			let mut unplug = bus::UnplugTarget::new(self.serial_no);
+			let client = self.client.borrow();
+			let _pass = client.enter();
+			let device = client.device;
+			match &event {
+				Some(event) => unplug.ioctl(device, event.handle),
+				None => unplug.ioctl_bounded(device, bus::DROP_TIMEOUT_MS),
+			}
+		};
+		self.client.borrow().track_unplug(self.serial_no, result);
+		result?;
+
+		self.serial_no = 0;
+		Ok(())
+	}
+
+	/// Waits until the virtual controller is ready.
+	///
+	/// Fails with [`Error::DeviceInstallFailed`] if Windows failed to install the device, see [`Xbox360Wired::wait_ready`].
+	#[inline(never)]
+	pub fn wait_ready(&mut self) -> Result<(), Error> {
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+
+		let event = self.event.sync()?;
+		unsafe {
+			let client = self.client.borrow();
+			let _pass = client.enter();
+			let device = client.device;
+			devpath::InstallWatch::wait_ready(device, event.handle, self.id, self.serial_no)?;
+		}
+		Ok(())
+	}
+
+	/// Submits a report with the control code.
+	///
+	/// The report is prefixed with the size and serial number header shared by the driver's submit report requests,
+	/// include any trailing padding the driver expects in the report.
+	#[inline(never)]
+	pub fn submit_raw(&mut self, code: u32, report: &[u8]) -> Result<(), Error> {
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+
+		// Reports of the known targets fit on the stack, only allocate for unusually large reports
+		let header = 2 * mem::size_of::<u32>();
+		let size = header + report.len();
+		let mut stack = [0u8; 128];
+		let mut heap;
+		let buffer = if size <= stack.len() {
+			&mut stack[..size]
+		}
+		else {
+			heap = vec![0u8; size];
+			&mut heap[..]
+		};
+		buffer[0..4].copy_from_slice(&(size as u32).to_le_bytes());
+		buffer[4..8].copy_from_slice(&self.serial_no.to_le_bytes());
+		buffer[header..].copy_from_slice(report);
+
+		let event = self.event.sync()?;
+		let client = self.client.borrow();
+		let _pass = client.enter();
+		match unsafe { bus::ioctl_raw(client.device, code, buffer, event.handle) } {
+			Ok(()) => Ok(()),
+			Err(winerror::ERROR_DEV_NOT_EXIST) => Err(Error::TargetNotReady),
+			Err(err) => Err(Error::WinError(err)),
+		}
+	}
+}
+
+impl<CL: Borrow<Client>> VirtualTarget for RawTarget<CL> {
+	#[inline]
+	fn id(&self) -> TargetId {
+		self.id()
+	}
+	#[inline]
+	fn is_attached(&self) -> bool {
+		self.is_attached()
+	}
+	#[inline]
+	fn plugin(&mut self) -> Result<(), Error> {
+		self.plugin()
+	}
+	#[inline]
+	fn unplug(&mut self) -> Result<(), Error> {
+		self.unplug()
+	}
+	#[inline]
+	fn wait_ready(&mut self) -> Result<(), Error> {
+		self.wait_ready()
+	}
+}
+
+impl<CL: Borrow<Client>> fmt::Debug for RawTarget<CL> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("RawTarget")
+			.field("uid", &self.uid)
+			.field("serial_no", &self.serial_no)
+			.field("target_type", &self.target_type)
+			.field("vendor_id", &self.id.vendor)
+			.field("product_id", &self.id.product)
+			.finish()
+	}
+}
+
+impl<CL: Borrow<Client>> Drop for RawTarget<CL> {
+	#[inline]
+	fn drop(&mut self) {
+		let _ = self.unplug_impl(true);
+	}
+}
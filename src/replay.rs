@@ -0,0 +1,295 @@
+/*!
+Replay verification against golden logs of submitted reports.
+
+A [`ReplayVerifier`] stands in for the target, it records every report submitted to it
+and compares the run against a golden log afterwards:
+
+```
+use std::time::Duration;
+use vigem_client::{XButtons, XGamepad};
+use vigem_client::replay::{ReplayVerifier, TimedReport};
+
+let golden = [
+	TimedReport::new(Duration::ZERO, XGamepad::neutral()),
+	TimedReport::new(Duration::from_millis(10), XGamepad::pressing(XButtons!(A))),
+];
+
+let mut verifier = ReplayVerifier::verify_against(&golden, Duration::from_millis(2));
+verifier.submit_at(Duration::ZERO, &XGamepad::neutral());
+verifier.submit_at(Duration::from_millis(11), &XGamepad::pressing(XButtons!(B)));
+
+let diff = verifier.diff();
+assert_eq!(diff.first_divergence, Some(1));
+assert_eq!(diff.fields[0].field, "buttons");
+assert!(diff.timing.is_empty());
+```
+
+Golden logs are stored with [`save`] and read back with [`load`].
+*/
+
+use std::convert::TryInto;
+use std::io::{self, BufRead, Write};
+use std::time::{Duration, Instant};
+use std::fmt;
+use crate::*;
+
+/// A report submitted at an offset from the start of the run.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TimedReport<R> {
+	/// Offset from the start of the run.
+	pub at: Duration,
+	pub report: R,
+}
+
+impl<R> TimedReport<R> {
+	#[inline]
+	pub const fn new(at: Duration, report: R) -> TimedReport<R> {
+		TimedReport { at, report }
+	}
+}
+
+/// Reports which can be replayed and compared field by field.
+pub trait ReplayReport: Copy + PartialEq {
+	/// Encodes the report in its byte layout.
+	fn encode(&self) -> Vec<u8>;
+	/// Decodes the report from its byte layout, `None` if the length does not match.
+	fn decode(bytes: &[u8]) -> Option<Self>;
+	/// Returns the named fields of the report.
+	fn fields(&self) -> Vec<(&'static str, i32)>;
+}
+
+impl ReplayReport for XGamepad {
+	#[inline]
+	fn encode(&self) -> Vec<u8> {
+		self.to_bytes().to_vec()
+	}
+	#[inline]
+	fn decode(bytes: &[u8]) -> Option<XGamepad> {
+		Some(XGamepad::from_bytes(bytes.try_into().ok()?))
+	}
+	fn fields(&self) -> Vec<(&'static str, i32)> {
+		vec![
+			("buttons", self.buttons.raw as i32),
+			("left_trigger", self.left_trigger as i32),
+			("right_trigger", self.right_trigger as i32),
+			("thumb_lx", self.thumb_lx as i32),
+			("thumb_ly", self.thumb_ly as i32),
+			("thumb_rx", self.thumb_rx as i32),
+			("thumb_ry", self.thumb_ry as i32),
+		]
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl ReplayReport for DS4Report {
+	#[inline]
+	fn encode(&self) -> Vec<u8> {
+		self.to_bytes().to_vec()
+	}
+	#[inline]
+	fn decode(bytes: &[u8]) -> Option<DS4Report> {
+		Some(DS4Report::from_bytes(bytes.try_into().ok()?))
+	}
+	fn fields(&self) -> Vec<(&'static str, i32)> {
+		vec![
+			("thumb_lx", self.thumb_lx as i32),
+			("thumb_ly", self.thumb_ly as i32),
+			("thumb_rx", self.thumb_rx as i32),
+			("thumb_ry", self.thumb_ry as i32),
+			("buttons", self.buttons as i32),
+			("special", self.special as i32),
+			("trigger_l", self.trigger_l as i32),
+			("trigger_r", self.trigger_r as i32),
+		]
+	}
+}
+
+/// A field of a report which differs from the golden log.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct FieldDiff {
+	/// Index of the report in the run.
+	pub index: usize,
+	pub field: &'static str,
+	pub expected: i32,
+	pub actual: i32,
+}
+
+/// A report submitted further from its time in the golden log than the tolerance.
+///
+/// Times are relative to the first report of the run and of the golden log respectively.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TimingDeviation {
+	/// Index of the report in the run.
+	pub index: usize,
+	pub expected: Duration,
+	pub actual: Duration,
+}
+
+/// Differences between a run and its golden log, see [`ReplayVerifier::diff`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReplayDiff {
+	/// Index of the first report differing from the golden log, or the length of the shorter if one ends early.
+	pub first_divergence: Option<usize>,
+	/// Number of reports in the golden log.
+	pub expected_len: usize,
+	/// Number of reports submitted.
+	pub actual_len: usize,
+	/// Differing fields of all reports, in order.
+	pub fields: Vec<FieldDiff>,
+	/// Reports submitted outside the tolerance, in order.
+	pub timing: Vec<TimingDeviation>,
+}
+
+impl ReplayDiff {
+	/// Returns if the run matches the golden log, both payload and timing.
+	#[inline]
+	pub fn is_match(&self) -> bool {
+		self.first_divergence.is_none() && self.timing.is_empty()
+	}
+}
+
+impl fmt::Display for ReplayDiff {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.is_match() {
+			return write!(f, "{} reports match", self.actual_len);
+		}
+		if let Some(index) = self.first_divergence {
+			write!(f, "diverges at report {} ({} expected, {} submitted)", index, self.expected_len, self.actual_len)?;
+		} else {
+			write!(f, "payload matches")?;
+		}
+		for diff in &self.fields {
+			write!(f, "\n  [{}] {}: expected {}, actual {}", diff.index, diff.field, diff.expected, diff.actual)?;
+		}
+		for dev in &self.timing {
+			write!(f, "\n  [{}] at {:?}, expected {:?}", dev.index, dev.actual, dev.expected)?;
+		}
+		Ok(())
+	}
+}
+
+/// Records submitted reports and compares them against a golden log.
+///
+/// Implements [`Submit`] to stand in for a target, reports submitted through it are timed by the wall clock since the first submission.
+/// Use [`submit_at`](Self::submit_at) to time them explicitly for deterministic runs.
+#[derive(Clone, Debug)]
+pub struct ReplayVerifier<R> {
+	golden: Vec<TimedReport<R>>,
+	tolerance: Duration,
+	recorded: Vec<TimedReport<R>>,
+	start: Option<Instant>,
+}
+
+impl<R: ReplayReport> ReplayVerifier<R> {
+	/// Verifies the run against the golden log, allowing submissions to deviate by the tolerance in time.
+	#[inline]
+	pub fn verify_against(golden: &[TimedReport<R>], tolerance: Duration) -> ReplayVerifier<R> {
+		ReplayVerifier { golden: golden.to_vec(), tolerance, recorded: Vec::new(), start: None }
+	}
+
+	/// Records the report at the given offset from the start of the run.
+	#[inline]
+	pub fn submit_at(&mut self, at: Duration, report: &R) {
+		self.recorded.push(TimedReport::new(at, *report));
+	}
+
+	/// Returns the reports submitted so far.
+	#[inline]
+	pub fn recorded(&self) -> &[TimedReport<R>] {
+		&self.recorded
+	}
+
+	/// Returns the golden log.
+	#[inline]
+	pub fn golden(&self) -> &[TimedReport<R>] {
+		&self.golden
+	}
+
+	/// Returns the allowed timing deviation.
+	#[inline]
+	pub fn tolerance(&self) -> Duration {
+		self.tolerance
+	}
+
+	/// Compares the reports submitted so far against the golden log.
+	pub fn diff(&self) -> ReplayDiff {
+		let mut diff = ReplayDiff {
+			expected_len: self.golden.len(),
+			actual_len: self.recorded.len(),
+			..ReplayDiff::default()
+		};
+		let golden_start = self.golden.first().map_or(Duration::ZERO, |first| first.at);
+		let recorded_start = self.recorded.first().map_or(Duration::ZERO, |first| first.at);
+		for (index, (expected, actual)) in self.golden.iter().zip(&self.recorded).enumerate() {
+			if expected.report != actual.report {
+				diff.first_divergence.get_or_insert(index);
+				for ((field, expected), (_, actual)) in expected.report.fields().into_iter().zip(actual.report.fields()) {
+					if expected != actual {
+						diff.fields.push(FieldDiff { index, field, expected, actual });
+					}
+				}
+			}
+			let expected = expected.at.saturating_sub(golden_start);
+			let actual = actual.at.saturating_sub(recorded_start);
+			if actual.abs_diff(expected) > self.tolerance {
+				diff.timing.push(TimingDeviation { index, expected, actual });
+			}
+		}
+		if diff.first_divergence.is_none() && self.golden.len() != self.recorded.len() {
+			diff.first_divergence = Some(self.golden.len().min(self.recorded.len()));
+		}
+		diff
+	}
+}
+
+impl<R: ReplayReport> Submit<R> for ReplayVerifier<R> {
+	#[inline]
+	fn submit_report(&mut self, report: &R) -> Result<(), Error> {
+		let now = Instant::now();
+		let start = *self.start.get_or_insert(now);
+		self.submit_at(now - start, report);
+		Ok(())
+	}
+}
+
+/// Writes the reports as a golden log.
+///
+/// One report per line, the offset in microseconds followed by the bytes of the report in hex.
+pub fn save<R: ReplayReport, W: Write>(reports: &[TimedReport<R>], mut writer: W) -> io::Result<()> {
+	for timed in reports {
+		write!(writer, "{}", timed.at.as_micros())?;
+		for (i, byte) in timed.report.encode().into_iter().enumerate() {
+			write!(writer, "{}{:02x}", if i == 0 { " " } else { "" }, byte)?;
+		}
+		writeln!(writer)?;
+	}
+	writer.flush()
+}
+
+/// Reads a golden log written by [`save`].
+///
+/// Empty lines and lines starting with `#` are skipped.
+pub fn load<R: ReplayReport, B: BufRead>(reader: B) -> io::Result<Vec<TimedReport<R>>> {
+	let mut reports = Vec::new();
+	for (number, line) in reader.lines().enumerate() {
+		let line = line?;
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("invalid golden log entry on line {}", number + 1));
+		let (at, hex) = line.split_once(' ').ok_or_else(invalid)?;
+		let at: u64 = at.parse().map_err(|_| invalid())?;
+		if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+			return Err(invalid());
+		}
+		let bytes = (0..hex.len()).step_by(2)
+			.map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+			.collect::<Result<Vec<u8>, _>>()
+			.map_err(|_| invalid())?;
+		let report = R::decode(&bytes).ok_or_else(invalid)?;
+		reports.push(TimedReport::new(Duration::from_micros(at), report));
+	}
+	Ok(reports)
+}
@@ -0,0 +1,271 @@
+// Only the DualShock4 target keeps a shadow, which is not built on other platforms
+#![cfg_attr(not(windows), allow(dead_code))]
+
+use crate::*;
+
+/// Which input report a [`DualShock4Wired`] target submits, see `DualShock4Wired::set_report_mode`.
+///
+/// The target keeps the extended report it was last updated with, its shadow, and submits from it in the selected mode:
+///
+/// * A basic update replaces the thumbsticks, buttons, special byte and triggers of the shadow.
+///   The timestamp, battery status, motion sensors and touch packets keep their last values.
+/// * An extended update replaces the whole shadow.
+/// * [`Basic`](Self::Basic) submits the basic part of the shadow, [`Extended`](Self::Extended) submits the whole shadow.
+/// * Switching modes submits nothing, the next update submits the shadow in the new mode.
+/// * When [down-converting](DualShock4Wired::set_downgrade_reports) for a driver without support for the extended report,
+///   [`Extended`](Self::Extended) submits the basic part of the shadow like [`Basic`](Self::Basic).
+///
+/// Falling back to basic reports and upgrading again therefore does not reset the fields only the extended report carries.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DS4ReportMode {
+	/// Submits the basic [`DS4Report`], accepted by every ViGEmBus version.
+	#[default]
+	Basic,
+	/// Submits the [`DS4ReportEx`], accepted by ViGEmBus 1.17 or newer, see [`Capabilities::ds4_extended_report`](crate::Capabilities::ds4_extended_report).
+	Extended,
+}
+
+/// The report submitted to the driver for an update.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Submission {
+	Basic(DS4Report),
+	Extended(DS4ReportEx),
+}
+
+#[cfg(windows)]
+impl Submission {
+	#[inline]
+	pub unsafe fn ioctl(&self, serial_no: u32, device: winapi::shared::ntdef::HANDLE, event: winapi::shared::ntdef::HANDLE) -> Result<(), u32> {
+		match *self {
+			Submission::Basic(report) => bus::DS4SubmitReport::new(serial_no, report).ioctl(device, event),
+			Submission::Extended(report) => bus::DS4SubmitReportEx::new(serial_no, report).ioctl(device, event),
+		}
+	}
+}
+
+/// The extended report a target was last updated with and the mode it is submitted in, see [`DS4ReportMode`].
+///
+/// With down-conversion enabled the extended mode falls back to the basic report once the driver is known not to support it.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Shadow {
+	mode: DS4ReportMode,
+	report: DS4ReportEx,
+	downgrade: bool,
+	extended_supported: Option<bool>,
+}
+
+impl Shadow {
+	#[inline]
+	pub fn mode(&self) -> DS4ReportMode {
+		self.mode
+	}
+
+	#[inline]
+	pub fn set_mode(&mut self, mode: DS4ReportMode) {
+		self.mode = mode;
+	}
+
+	#[inline]
+	pub fn report(&self) -> &DS4ReportEx {
+		&self.report
+	}
+
+	#[inline]
+	pub fn downgrade(&self) -> bool {
+		self.downgrade
+	}
+
+	#[inline]
+	pub fn set_downgrade(&mut self, downgrade: bool) {
+		self.downgrade = downgrade;
+	}
+
+	/// Whether the driver must be asked if it supports the extended report before the next submission.
+	#[inline]
+	pub fn needs_probe(&self) -> bool {
+		self.mode == DS4ReportMode::Extended && self.downgrade && self.extended_supported.is_none()
+	}
+
+	#[inline]
+	pub fn set_extended_supported(&mut self, supported: bool) {
+		self.extended_supported = Some(supported);
+	}
+
+	/// Merges the report and returns what to submit.
+	#[inline]
+	pub fn merge<R: Ds4Submittable>(&mut self, report: &R) -> Submission {
+		report.merge(&mut self.report);
+		self.submission()
+	}
+
+	#[inline]
+	fn submission(&self) -> Submission {
+		match self.mode {
+			DS4ReportMode::Basic => Submission::Basic(self.report.report()),
+			DS4ReportMode::Extended if self.downgrade && self.extended_supported == Some(false) => Submission::Basic(self.report.report()),
+			DS4ReportMode::Extended => Submission::Extended(self.report),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn shadow(mode: DS4ReportMode) -> Shadow {
+		Shadow { mode, ..Shadow::default() }
+	}
+
+	// An extended report with every field beyond the basic report set
+	fn motion() -> DS4ReportEx {
+		let touch = DS4TouchReport::new(7, Some(DS4TouchPoint { id: 3, x: 100, y: 200 }), None);
+		DS4ReportEx {
+			timestamp: 0x1234,
+			battery_lvl: 0x20,
+			gyro_x: -5, gyro_y: 6, gyro_z: -7,
+			accel_x: 100, accel_y: -200, accel_z: 8000,
+			battery_lvl_special: BatteryStatus::new(6, true).raw,
+			touch_packets_n: 1,
+			current_touch: touch,
+			..DS4ReportEx::neutral()
+		}.with_report(&DS4Report::PRESS_CROSS)
+	}
+
+	#[test]
+	fn basic_mode() {
+		let mut shadow = Shadow::default();
+		assert_eq!(shadow.mode(), DS4ReportMode::Basic);
+		assert_eq!(shadow.merge(&DS4Report::PRESS_CIRCLE), Submission::Basic(DS4Report::PRESS_CIRCLE));
+		// The basic part of an extended update is submitted
+		assert_eq!(shadow.merge(&motion()), Submission::Basic(DS4Report::PRESS_CROSS));
+		assert_eq!(*shadow.report(), motion());
+	}
+
+	#[test]
+	fn extended_mode() {
+		let mut shadow = shadow(DS4ReportMode::Extended);
+		assert_eq!(shadow.merge(&motion()), Submission::Extended(motion()));
+		// A basic update keeps the timestamp, status, sensors and touch packets
+		let expected = motion().with_report(&DS4Report::DPAD_UP);
+		assert_eq!(shadow.merge(&DS4Report::DPAD_UP), Submission::Extended(expected));
+		assert_eq!(*shadow.report(), expected);
+	}
+
+	#[test]
+	fn basic_merge_fields() {
+		let mut shadow = shadow(DS4ReportMode::Extended);
+		shadow.merge(&motion());
+		let basic = DS4Report {
+			thumb_lx: 1, thumb_ly: 2, thumb_rx: 3, thumb_ry: 4,
+			buttons: 0x1234, special: 0xFD, trigger_l: 5, trigger_r: 6,
+		};
+		let Submission::Extended(report) = shadow.merge(&basic) else { panic!("not extended") };
+		assert_eq!(report.report(), basic);
+		assert_eq!({ report.timestamp }, 0x1234);
+		assert_eq!(report.battery_lvl, 0x20);
+		assert_eq!(report.gyro(), [-5, 6, -7]);
+		assert_eq!(report.accel(), [100, -200, 8000]);
+		assert_eq!(report.battery(), BatteryStatus::new(6, true));
+		assert_eq!(report.touch_packets_n, 1);
+		assert_eq!(report.touch_frame(), motion().touch_frame());
+	}
+
+	#[test]
+	fn neutral_shadow() {
+		// A basic update before any extended update submits the neutral extended fields
+		let mut shadow = shadow(DS4ReportMode::Extended);
+		let expected = DS4ReportEx::from(DS4Report::PRESS_L1);
+		assert_eq!(shadow.merge(&DS4Report::PRESS_L1), Submission::Extended(expected));
+		assert_eq!(expected.battery(), BatteryStatus::WIRED_FULL);
+		assert_eq!(expected.touch_packets_n, 0);
+	}
+
+	#[test]
+	fn switch_to_basic_and_back() {
+		let mut shadow = shadow(DS4ReportMode::Extended);
+		shadow.merge(&motion());
+
+		// Falling back to basic submits the basic reports only
+		shadow.set_mode(DS4ReportMode::Basic);
+		assert_eq!(shadow.merge(&DS4Report::PRESS_R1), Submission::Basic(DS4Report::PRESS_R1));
+		assert_eq!(shadow.merge(&DS4Report::neutral()), Submission::Basic(DS4Report::neutral()));
+
+		// Upgrading again has kept the fields the basic reports do not carry
+		shadow.set_mode(DS4ReportMode::Extended);
+		let expected = motion().with_report(&DS4Report::PRESS_SQUARE);
+		assert_eq!(shadow.merge(&DS4Report::PRESS_SQUARE), Submission::Extended(expected));
+	}
+
+	#[test]
+	fn switch_submits_nothing() {
+		let mut shadow = shadow(DS4ReportMode::Basic);
+		shadow.merge(&DS4Report::PRESS_CROSS);
+		let before = *shadow.report();
+		shadow.set_mode(DS4ReportMode::Extended);
+		shadow.set_mode(DS4ReportMode::Basic);
+		assert_eq!(*shadow.report(), before);
+	}
+
+	// Submits both kinds of report to an extended mode shadow on a driver with or without support
+	fn matrix(downgrade: bool, supported: Option<bool>) -> [Submission; 2] {
+		let mut shadow = Shadow { downgrade, ..shadow(DS4ReportMode::Extended) };
+		assert_eq!(shadow.needs_probe(), downgrade);
+		if let Some(supported) = supported {
+			shadow.set_extended_supported(supported);
+		}
+		assert_eq!(shadow.needs_probe(), downgrade && supported.is_none());
+		[shadow.merge(&motion()), shadow.merge(&DS4Report::PRESS_L1)]
+	}
+
+	#[test]
+	fn report_kind_by_capability() {
+		let extended = [Submission::Extended(motion()), Submission::Extended(motion().with_report(&DS4Report::PRESS_L1))];
+		let basic = [Submission::Basic(DS4Report::PRESS_CROSS), Submission::Basic(DS4Report::PRESS_L1)];
+
+		// Without down-conversion both kinds of report are submitted extended, an old driver rejects them
+		assert_eq!(matrix(false, Some(true)), extended);
+		assert_eq!(matrix(false, Some(false)), extended);
+		assert_eq!(matrix(false, None), extended);
+
+		// With down-conversion an old driver gets the basic part instead
+		assert_eq!(matrix(true, Some(true)), extended);
+		assert_eq!(matrix(true, Some(false)), basic);
+	}
+
+	#[test]
+	fn downgrade_keeps_shadow() {
+		let mut shadow = Shadow { downgrade: true, ..shadow(DS4ReportMode::Extended) };
+		shadow.set_extended_supported(false);
+		assert_eq!(shadow.merge(&motion()), Submission::Basic(DS4Report::PRESS_CROSS));
+		// The extended fields are kept for a driver which does support them
+		assert_eq!(*shadow.report(), motion());
+		shadow.set_downgrade(false);
+		assert_eq!(shadow.merge(&DS4Report::PRESS_CROSS), Submission::Extended(motion()));
+	}
+
+	#[test]
+	fn downgrade_probe() {
+		// Only the extended mode with down-conversion needs to know the capability
+		let mut shadow = shadow(DS4ReportMode::Basic);
+		shadow.set_downgrade(true);
+		assert!(!shadow.needs_probe());
+		shadow.set_mode(DS4ReportMode::Extended);
+		assert!(shadow.needs_probe());
+		shadow.set_extended_supported(true);
+		assert!(!shadow.needs_probe());
+		shadow.set_mode(DS4ReportMode::Basic);
+		assert_eq!(shadow.merge(&motion()), Submission::Basic(DS4Report::PRESS_CROSS));
+	}
+
+	#[test]
+	fn extended_replaces_everything() {
+		let mut shadow = shadow(DS4ReportMode::Extended);
+		shadow.merge(&motion());
+		// The extended update does not merge, fields it leaves neutral are neutral
+		let report = DS4ReportEx::from(DS4Report::PRESS_TRIANGLE);
+		assert_eq!(shadow.merge(&report), Submission::Extended(report));
+		assert_eq!(shadow.report().touch_packets_n, 0);
+		assert_eq!(shadow.report().gyro(), [0; 3]);
+	}
+}
@@ -0,0 +1,689 @@
+use std::time;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+#[cfg(all(windows, any(feature = "xusb-rumble", feature = "ds4-output")))]
+use std::{fmt, pin::Pin, thread};
+#[cfg(all(windows, any(feature = "xusb-rumble", feature = "ds4-output")))]
+use crate::fanout::{Cursor, Fanout, Next};
+
+/// Rumble intensities of a controller with impulse triggers, such as the Xbox One controller.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct FourMotors {
+	/// Low frequency motor in the left grip.
+	pub large_motor: u8,
+	/// High frequency motor in the right grip.
+	pub small_motor: u8,
+	/// Impulse trigger motor behind the left trigger.
+	pub left_trigger: u8,
+	/// Impulse trigger motor behind the right trigger.
+	pub right_trigger: u8,
+}
+
+/// Maps rumble requests onto the two motors of a virtual Xbox360 controller.
+///
+/// The Xbox360 controller has a low frequency large motor and a high frequency small motor, it has no impulse triggers.
+/// Four motor requests are folded by adding the weighted left trigger to the large motor and the weighted right trigger to the small motor,
+/// saturating at full intensity.
+/// The left trigger is folded onto the low frequency band as both sit on the left side of the controller, likewise for the right.
+///
+/// ```
+/// use vigem_client::{FourMotors, MotorMap};
+///
+/// let motors = FourMotors { large_motor: 100, small_motor: 0, left_trigger: 0, right_trigger: 200 };
+///
+/// // Without folding the impulse triggers are dropped
+/// assert_eq!(MotorMap::two_motor().apply(&motors), (100, 0));
+///
+/// // Folding at half weight
+/// assert_eq!(MotorMap::fold_triggers(0.5).apply(&motors), (100, 100));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MotorMap {
+	trigger_weight: f32,
+}
+
+impl Default for MotorMap {
+	#[inline]
+	fn default() -> MotorMap {
+		MotorMap::two_motor()
+	}
+}
+
+impl MotorMap {
+	/// Passes the large and small motors through unchanged, dropping the impulse triggers.
+	#[inline]
+	pub const fn two_motor() -> MotorMap {
+		MotorMap { trigger_weight: 0.0 }
+	}
+
+	/// Folds the impulse triggers into the large and small motors with the given weight.
+	///
+	/// The weight is clamped to `0.0..=1.0`, NaN is treated as zero.
+	#[inline]
+	pub fn fold_triggers(weight: f32) -> MotorMap {
+		let trigger_weight = if weight.is_nan() { 0.0 } else { weight.clamp(0.0, 1.0) };
+		MotorMap { trigger_weight }
+	}
+
+	/// Returns the weight of the impulse triggers.
+	#[inline]
+	pub fn trigger_weight(&self) -> f32 {
+		self.trigger_weight
+	}
+
+	/// Returns the `(large, small)` motor intensities.
+	#[inline]
+	pub fn apply(&self, motors: &FourMotors) -> (u8, u8) {
+		let fold = |motor: u8, trigger: u8| {
+			let value = motor as f32 + trigger as f32 * self.trigger_weight;
+			value.round().min(255.0) as u8
+		};
+		(fold(motors.large_motor, motors.left_trigger), fold(motors.small_motor, motors.right_trigger))
+	}
+}
+
+/// Rumble requested by the host through either kind of target.
+///
+/// Motor intensities are normalized to `0.0..=1.0`.
+/// Both controllers have the low frequency large motor on the left and the high frequency small motor on the right,
+/// so the large and small motors map onto each other across controllers.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum RumbleEvent {
+	/// Rumble of an [`Xbox360Wired`](crate::Xbox360Wired) target.
+	Xbox360 {
+		large_motor: f32,
+		small_motor: f32,
+		led_number: u8,
+	},
+	/// Rumble and lightbar of a [`DualShock4Wired`](crate::DualShock4Wired) target.
+	#[cfg(feature = "ds4-output")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+	DualShock4 {
+		large_motor: f32,
+		small_motor: f32,
+		lightbar: crate::DS4LightbarColor,
+	},
+}
+
+impl RumbleEvent {
+	/// Returns the intensity of the large motor.
+	#[inline]
+	pub fn large_motor(&self) -> f32 {
+		match *self {
+			RumbleEvent::Xbox360 { large_motor, .. } => large_motor,
+			#[cfg(feature = "ds4-output")]
+			RumbleEvent::DualShock4 { large_motor, .. } => large_motor,
+		}
+	}
+
+	/// Returns the intensity of the small motor.
+	#[inline]
+	pub fn small_motor(&self) -> f32 {
+		match *self {
+			RumbleEvent::Xbox360 { small_motor, .. } => small_motor,
+			#[cfg(feature = "ds4-output")]
+			RumbleEvent::DualShock4 { small_motor, .. } => small_motor,
+		}
+	}
+
+	/// Returns the `(large, small)` motor intensities as sent to a target.
+	#[inline]
+	pub fn motors(&self) -> (u8, u8) {
+		(denormalize(self.large_motor()), denormalize(self.small_motor()))
+	}
+
+	/// Returns the lightbar color if the host set one.
+	#[cfg(feature = "ds4-output")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+	#[inline]
+	pub fn lightbar(&self) -> Option<crate::DS4LightbarColor> {
+		match *self {
+			RumbleEvent::Xbox360 { .. } => None,
+			RumbleEvent::DualShock4 { lightbar, .. } => Some(lightbar),
+		}
+	}
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
+impl From<crate::XNotification> for RumbleEvent {
+	#[inline]
+	fn from(notification: crate::XNotification) -> RumbleEvent {
+		RumbleEvent::Xbox360 {
+			large_motor: normalize(notification.large_motor),
+			small_motor: normalize(notification.small_motor),
+			led_number: notification.led_number,
+		}
+	}
+}
+
+#[cfg(feature = "ds4-output")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+impl From<crate::DS4Notification> for RumbleEvent {
+	#[inline]
+	fn from(notification: crate::DS4Notification) -> RumbleEvent {
+		RumbleEvent::DualShock4 {
+			large_motor: normalize(notification.large_motor),
+			small_motor: normalize(notification.small_motor),
+			lightbar: notification.lightbar,
+		}
+	}
+}
+
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
+#[inline]
+fn normalize(motor: u8) -> f32 {
+	motor as f32 / 255.0
+}
+#[inline]
+fn denormalize(motor: f32) -> u8 {
+	if motor.is_nan() { 0 } else { (motor.clamp(0.0, 1.0) * 255.0).round() as u8 }
+}
+
+/// Merges the rumble of multiple targets into one stream.
+///
+/// Every listener feeds the hub through a [`RumbleSink`] tagged with its source,
+/// the last event of every source is kept for hosts which only care about the current state.
+/// Sources are identified by the serial number of their target.
+///
+/// ```no_run
+/// # #[cfg(all(windows, feature = "xusb-rumble"))] {
+/// let client = vigem_client::Client::connect().unwrap();
+/// let mut target = vigem_client::Xbox360Wired::new(&client, vigem_client::TargetId::XBOX360_WIRED);
+/// target.plugin().unwrap();
+///
+/// let hub = vigem_client::RumbleHub::new();
+/// let thread = hub.listen_x360(target.request_notification().unwrap());
+///
+/// while let Some((source, event)) = hub.recv() {
+/// 	println!("{}: {:?}", source, event.motors());
+/// }
+/// # thread.join().unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RumbleHub {
+	shared: Arc<HubShared>,
+}
+
+/// Feeds rumble events of one source into a [`RumbleHub`].
+#[derive(Debug)]
+pub struct RumbleSink {
+	source: u32,
+	shared: Arc<HubShared>,
+}
+
+#[derive(Debug, Default)]
+struct HubShared {
+	state: Mutex<HubState>,
+	// Signalled for every event and once the last sink is dropped
+	cond: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct HubState {
+	events: VecDeque<(u32, RumbleEvent)>,
+	last: BTreeMap<u32, RumbleEvent>,
+	sinks: usize,
+	closed: bool,
+}
+
+impl Default for RumbleHub {
+	#[inline]
+	fn default() -> RumbleHub {
+		RumbleHub::new()
+	}
+}
+
+impl RumbleHub {
+	/// Creates a hub without sources.
+	pub fn new() -> RumbleHub {
+		RumbleHub { shared: Arc::default() }
+	}
+
+	/// Returns a sink feeding the hub with events tagged with `source`.
+	#[inline]
+	pub fn sink(&self, source: u32) -> RumbleSink {
+		lock(&self.shared.state).sinks += 1;
+		RumbleSink { source, shared: self.shared.clone() }
+	}
+
+	/// Spawns a thread feeding the hub with the notifications of an Xbox360 target.
+	///
+	/// The source is the serial number of the target, the thread exits when the target is unplugged or dropped.
+	#[cfg(all(windows, feature = "xusb-rumble"))]
+	#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
+	pub fn listen_x360(&self, notification: crate::XRequestNotification) -> std::thread::JoinHandle<()> {
+		let sink = self.sink(notification.serial_no());
+		notification.spawn_thread(move |_, data| sink.send(data))
+	}
+
+	/// Spawns a thread feeding the hub with the notifications of a DualShock4 target.
+	///
+	/// The source is the serial number of the target, the thread exits when the target is unplugged or dropped.
+	#[cfg(all(windows, feature = "ds4-output"))]
+	#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+	pub fn listen_ds4(&self, notification: crate::DS4RequestNotification) -> std::thread::JoinHandle<()> {
+		let sink = self.sink(notification.serial_no());
+		notification.spawn_thread(move |_, data| sink.send(data))
+	}
+
+	/// Returns the next event without waiting.
+	#[inline]
+	pub fn try_recv(&self) -> Option<(u32, RumbleEvent)> {
+		lock(&self.shared.state).events.pop_front()
+	}
+
+	/// Waits for the next event.
+	///
+	/// Returns `None` once all sinks are dropped and no events are left.
+	#[inline]
+	pub fn recv(&self) -> Option<(u32, RumbleEvent)> {
+		self.recv_timeout(time::Duration::MAX)
+	}
+
+	/// Waits for the next event up to the timeout.
+	///
+	/// Returns `None` on timeout or once all sinks are dropped and no events are left.
+	pub fn recv_timeout(&self, timeout: time::Duration) -> Option<(u32, RumbleEvent)> {
+		let deadline = time::Instant::now().checked_add(timeout);
+		let mut state = lock(&self.shared.state);
+		loop {
+			if let Some(event) = state.events.pop_front() {
+				return Some(event);
+			}
+			if state.sinks == 0 {
+				return None;
+			}
+			state = match deadline {
+				Some(deadline) => {
+					let timeout = deadline.saturating_duration_since(time::Instant::now());
+					if timeout.is_zero() {
+						return None;
+					}
+					self.shared.cond.wait_timeout(state, timeout).unwrap_or_else(PoisonError::into_inner).0
+				},
+				None => self.shared.cond.wait(state).unwrap_or_else(PoisonError::into_inner),
+			};
+		}
+	}
+
+	/// Returns the last event of the source.
+	#[inline]
+	pub fn last(&self, source: u32) -> Option<RumbleEvent> {
+		lock(&self.shared.state).last.get(&source).copied()
+	}
+
+	/// Returns the sources which sent events with their last event, ordered by source.
+	#[inline]
+	pub fn sources(&self) -> Vec<(u32, RumbleEvent)> {
+		lock(&self.shared.state).last.iter().map(|(&source, &event)| (source, event)).collect()
+	}
+}
+
+impl Drop for RumbleHub {
+	#[inline]
+	fn drop(&mut self) {
+		let mut state = lock(&self.shared.state);
+		state.closed = true;
+		state.events.clear();
+	}
+}
+
+impl RumbleSink {
+	/// Returns the source the events are tagged with.
+	#[inline]
+	pub fn source(&self) -> u32 {
+		self.source
+	}
+
+	/// Sends an event to the hub, events are dropped if the hub is gone.
+	#[inline]
+	pub fn send<E: Into<RumbleEvent>>(&self, event: E) {
+		let event = event.into();
+		let mut state = lock(&self.shared.state);
+		state.last.insert(self.source, event);
+		if !state.closed {
+			state.events.push_back((self.source, event));
+			self.shared.cond.notify_one();
+		}
+	}
+}
+
+impl Clone for RumbleSink {
+	#[inline]
+	fn clone(&self) -> RumbleSink {
+		lock(&self.shared.state).sinks += 1;
+		RumbleSink { source: self.source, shared: self.shared.clone() }
+	}
+}
+
+impl Drop for RumbleSink {
+	#[inline]
+	fn drop(&mut self) {
+		let mut state = lock(&self.shared.state);
+		state.sinks -= 1;
+		if state.sinks == 0 {
+			self.shared.cond.notify_all();
+		}
+	}
+}
+
+/// How a [`RumbleSubscription`] catches up with the events it has not received yet.
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "xusb-rumble", feature = "ds4-output"))))]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum RumbleCoalesce {
+	/// Receives every event in order.
+	///
+	/// A subscription falling more than [`RUMBLE_BACKLOG`] events behind skips the oldest, see [`RumbleSubscription::missed`].
+	#[default]
+	Every,
+	/// Receives only the most recent event, skipping the older events not received yet.
+	///
+	/// For consumers which only care about the current motor state, eg. an on-screen indicator.
+	Latest,
+}
+
+/// Number of events a [`RumbleListener`] keeps for subscriptions which fall behind.
+#[cfg(any(feature = "xusb-rumble", feature = "ds4-output"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "xusb-rumble", feature = "ds4-output"))))]
+pub const RUMBLE_BACKLOG: usize = 64;
+
+/// Shares the notifications of one target with any number of consumers.
+///
+/// A target supports a single notification request, a second request steals notifications from the first.
+/// The listener owns the one request and spawns the thread receiving its notifications,
+/// every [subscription](Self::subscribe) receives all events from the moment it subscribed at its own pace.
+///
+/// The listener and its subscriptions share the request, it is cancelled once all of them are dropped.
+/// The listener itself can be dropped once the subscriptions are handed out.
+/// Once the target is unplugged or dropped the subscriptions receive the remaining events and end.
+///
+/// ```no_run
+/// let client = vigem_client::Client::connect().unwrap();
+/// let mut target = vigem_client::Xbox360Wired::new(&client, vigem_client::TargetId::XBOX360_WIRED);
+/// target.plugin().unwrap();
+///
+/// // One driver request, many readers
+/// let listener = vigem_client::RumbleListener::x360(target.request_notification().unwrap());
+/// let mut forwarder = listener.subscribe();
+/// let mut indicator = listener.subscribe();
+/// indicator.set_coalesce(vigem_client::RumbleCoalesce::Latest);
+/// drop(listener);
+///
+/// std::thread::spawn(move || {
+/// 	while let Some(event) = indicator.recv() {
+/// 		println!("indicator: {:?}", event.motors());
+/// 	}
+/// });
+/// while let Some(event) = forwarder.recv() {
+/// 	println!("forward: {:?}", event.motors());
+/// }
+/// ```
+#[cfg(all(windows, any(feature = "xusb-rumble", feature = "ds4-output")))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "xusb-rumble", feature = "ds4-output"))))]
+#[derive(Debug)]
+pub struct RumbleListener {
+	shared: Arc<Shared>,
+}
+
+/// Receives the events of a [`RumbleListener`].
+#[cfg(all(windows, any(feature = "xusb-rumble", feature = "ds4-output")))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "xusb-rumble", feature = "ds4-output"))))]
+#[derive(Debug)]
+pub struct RumbleSubscription {
+	shared: Arc<Shared>,
+	cursor: Cursor,
+	coalesce: RumbleCoalesce,
+}
+
+#[cfg(all(windows, any(feature = "xusb-rumble", feature = "ds4-output")))]
+struct Shared {
+	fanout: Fanout<RumbleEvent>,
+	link: Arc<crate::bus::CancelLink>,
+	serial_no: u32,
+	uid: crate::TargetUid,
+}
+
+#[cfg(all(windows, any(feature = "xusb-rumble", feature = "ds4-output")))]
+impl fmt::Debug for Shared {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Shared")
+			.field("fanout", &self.fanout)
+			.field("serial_no", &self.serial_no)
+			.field("uid", &self.uid)
+			.finish()
+	}
+}
+
+#[cfg(all(windows, any(feature = "xusb-rumble", feature = "ds4-output")))]
+impl Shared {
+	fn release(&self) {
+		if self.fanout.release() {
+			self.link.cancel();
+		}
+	}
+}
+
+// Notification requests the listener can receive on its thread
+#[cfg(all(windows, any(feature = "xusb-rumble", feature = "ds4-output")))]
+trait Notifications: Send + 'static {
+	fn cancel_link(&self) -> Arc<crate::bus::CancelLink>;
+	fn serial_no(&self) -> u32;
+	fn uid(&self) -> crate::TargetUid;
+	fn apply_tuning(&self) -> Option<crate::TuningGuard>;
+	fn request(self: Pin<&mut Self>);
+	fn poll(self: Pin<&mut Self>) -> Result<Option<RumbleEvent>, crate::Error>;
+}
+
+#[cfg(all(windows, feature = "xusb-rumble"))]
+impl Notifications for crate::XRequestNotification {
+	fn cancel_link(&self) -> Arc<crate::bus::CancelLink> {
+		self.cancel_link()
+	}
+	fn serial_no(&self) -> u32 {
+		self.serial_no()
+	}
+	fn uid(&self) -> crate::TargetUid {
+		self.uid()
+	}
+	fn apply_tuning(&self) -> Option<crate::TuningGuard> {
+		self.apply_tuning()
+	}
+	fn request(self: Pin<&mut Self>) {
+		self.request()
+	}
+	fn poll(self: Pin<&mut Self>) -> Result<Option<RumbleEvent>, crate::Error> {
+		Ok(self.poll(true)?.map(RumbleEvent::from))
+	}
+}
+
+#[cfg(all(windows, feature = "ds4-output"))]
+impl Notifications for crate::DS4RequestNotification {
+	fn cancel_link(&self) -> Arc<crate::bus::CancelLink> {
+		self.cancel_link()
+	}
+	fn serial_no(&self) -> u32 {
+		self.serial_no()
+	}
+	fn uid(&self) -> crate::TargetUid {
+		self.uid()
+	}
+	fn apply_tuning(&self) -> Option<crate::TuningGuard> {
+		self.apply_tuning()
+	}
+	fn request(self: Pin<&mut Self>) {
+		self.request()
+	}
+	fn poll(self: Pin<&mut Self>) -> Result<Option<RumbleEvent>, crate::Error> {
+		Ok(self.poll(true)?.map(RumbleEvent::from))
+	}
+}
+
+#[cfg(all(windows, any(feature = "xusb-rumble", feature = "ds4-output")))]
+impl RumbleListener {
+	/// Spawns the thread receiving the notifications of an Xbox360 target.
+	#[cfg(feature = "xusb-rumble")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
+	#[inline]
+	pub fn x360(notification: crate::XRequestNotification) -> RumbleListener {
+		RumbleListener::spawn(notification)
+	}
+
+	/// Spawns the thread receiving the notifications of a DualShock4 target.
+	#[cfg(feature = "ds4-output")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "ds4-output")))]
+	#[inline]
+	pub fn ds4(notification: crate::DS4RequestNotification) -> RumbleListener {
+		RumbleListener::spawn(notification)
+	}
+
+	fn spawn<N: Notifications>(notification: N) -> RumbleListener {
+		let shared = Arc::new(Shared {
+			fanout: Fanout::new(RUMBLE_BACKLOG),
+			link: notification.cancel_link(),
+			serial_no: notification.serial_no(),
+			uid: notification.uid(),
+		});
+		let thread_shared = shared.clone();
+		thread::spawn(move || {
+			let shared = thread_shared;
+			let _tuning = notification.apply_tuning();
+			// Safety: the request notification object is not accessible after it is pinned
+			let mut reqn = notification;
+			let mut reqn = unsafe { Pin::new_unchecked(&mut reqn) };
+			loop {
+				reqn.as_mut().request();
+				// Checked after requesting, cancelling the request when the last handle is released cannot miss it
+				if !shared.fanout.is_shared() {
+					break;
+				}
+				match reqn.as_mut().poll() {
+					Ok(None) => {},
+					Ok(Some(event)) => shared.fanout.publish(event),
+					// When the target is unplugged or dropped, or the last handle released, the notification request is aborted
+					Err(_) => break,
+				}
+			}
+			shared.fanout.close();
+		});
+		RumbleListener { shared }
+	}
+
+	/// Returns the serial number of the target the notifications are received for.
+	#[inline]
+	pub fn serial_no(&self) -> u32 {
+		self.shared.serial_no
+	}
+
+	/// Returns the uid of the target the notifications are received for, see [`TargetUid`](crate::TargetUid).
+	#[inline]
+	pub fn uid(&self) -> crate::TargetUid {
+		self.shared.uid
+	}
+
+	/// Returns if notifications are still received, false once the target is unplugged or dropped.
+	#[inline]
+	pub fn is_active(&self) -> bool {
+		!self.shared.fanout.is_closed()
+	}
+
+	/// Returns a new subscription receiving the events from now on.
+	///
+	/// Subscriptions receive every event by default, see [`RumbleSubscription::set_coalesce`].
+	#[inline]
+	pub fn subscribe(&self) -> RumbleSubscription {
+		let cursor = self.shared.fanout.acquire();
+		RumbleSubscription { shared: self.shared.clone(), cursor, coalesce: RumbleCoalesce::Every }
+	}
+}
+
+#[cfg(all(windows, any(feature = "xusb-rumble", feature = "ds4-output")))]
+impl Drop for RumbleListener {
+	#[inline]
+	fn drop(&mut self) {
+		self.shared.release();
+	}
+}
+
+#[cfg(all(windows, any(feature = "xusb-rumble", feature = "ds4-output")))]
+impl RumbleSubscription {
+	/// Returns the serial number of the target the notifications are received for.
+	#[inline]
+	pub fn serial_no(&self) -> u32 {
+		self.shared.serial_no
+	}
+
+	/// Returns the uid of the target the notifications are received for, see [`TargetUid`](crate::TargetUid).
+	#[inline]
+	pub fn uid(&self) -> crate::TargetUid {
+		self.shared.uid
+	}
+
+	/// Sets how the subscription catches up with the events it has not received yet.
+	///
+	/// Defaults to [`RumbleCoalesce::Every`].
+	#[inline]
+	pub fn set_coalesce(&mut self, coalesce: RumbleCoalesce) {
+		self.coalesce = coalesce;
+	}
+
+	/// Returns how the subscription catches up with the events it has not received yet.
+	#[inline]
+	pub fn coalesce(&self) -> RumbleCoalesce {
+		self.coalesce
+	}
+
+	/// Returns another subscription of the same listener receiving the events from now on.
+	#[inline]
+	pub fn subscribe(&self) -> RumbleSubscription {
+		let cursor = self.shared.fanout.acquire();
+		RumbleSubscription { shared: self.shared.clone(), cursor, coalesce: RumbleCoalesce::Every }
+	}
+
+	/// Returns the number of events skipped because the subscription fell more than [`RUMBLE_BACKLOG`] events behind.
+	///
+	/// Events skipped by [`RumbleCoalesce::Latest`] are not counted.
+	#[inline]
+	pub fn missed(&self) -> u64 {
+		self.cursor.missed()
+	}
+
+	/// Returns the next event without waiting.
+	#[inline]
+	pub fn try_recv(&mut self) -> Option<RumbleEvent> {
+		self.recv_timeout(time::Duration::ZERO)
+	}
+
+	/// Waits for the next event.
+	///
+	/// Returns `None` once the target is unplugged or dropped and no events are left.
+	#[inline]
+	pub fn recv(&mut self) -> Option<RumbleEvent> {
+		self.recv_timeout(time::Duration::MAX)
+	}
+
+	/// Waits for the next event up to the timeout.
+	#[inline]
+	pub fn recv_timeout(&mut self, timeout: time::Duration) -> Option<RumbleEvent> {
+		let latest = self.coalesce == RumbleCoalesce::Latest;
+		match self.shared.fanout.next(&mut self.cursor, latest, timeout) {
+			Next::Value(event) => Some(event),
+			Next::Empty | Next::Closed => None,
+		}
+	}
+}
+
+#[cfg(all(windows, any(feature = "xusb-rumble", feature = "ds4-output")))]
+impl Drop for RumbleSubscription {
+	#[inline]
+	fn drop(&mut self) {
+		self.shared.release();
+	}
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+	mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
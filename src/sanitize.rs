@@ -0,0 +1,68 @@
+use crate::*;
+
+/// How much of a report survives redaction, for logs which leave the machine.
+///
+/// Reports can reveal what the user typed, eg. on an on-screen keyboard navigated with the dpad.
+/// Defaults to [`PresenceOnly`](Self::PresenceOnly), widen the policy explicitly to log more.
+///
+/// ```
+/// use vigem_client::{RedactPolicy, XButtons, XGamepad};
+///
+/// let gamepad = XGamepad { buttons: XButtons!(UP|A), thumb_lx: 1200, ..Default::default() };
+/// assert_eq!(gamepad.redacted(RedactPolicy::default()).compact().to_string(), "? | LT:0 RT:0 | L(0,0) R(0,0)");
+/// assert_eq!(gamepad.redacted(RedactPolicy::ButtonsOnly).compact().to_string(), "UP+A | LT:0 RT:0 | L(0,0) R(0,0)");
+/// assert_eq!(gamepad.redacted(RedactPolicy::Full), gamepad);
+/// ```
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RedactPolicy {
+	/// Only whether there is any input at all.
+	///
+	/// Idle reports become the neutral report, other reports the neutral report with a marker which is not a real input:
+	/// the unassigned button bit `0x0800` of an [`XGamepad`] and the invalid dpad `0xF` of a `DS4Report`.
+	/// Both show as `?` in the compact formats.
+	#[default]
+	PresenceOnly,
+	/// The buttons, including the dpad, with the thumbsticks centered and the triggers released.
+	ButtonsOnly,
+	/// The report unchanged.
+	Full,
+}
+
+impl XGamepad {
+	/// Returns the gamepad redacted by the policy, see [`RedactPolicy`].
+	#[inline]
+	pub fn redacted(&self, policy: RedactPolicy) -> XGamepad {
+		match policy {
+			RedactPolicy::PresenceOnly if *self == XGamepad::neutral() => XGamepad::neutral(),
+			RedactPolicy::PresenceOnly => XGamepad::pressing(XButtons { raw: 0x0800 }),
+			RedactPolicy::ButtonsOnly => XGamepad::pressing(self.buttons),
+			RedactPolicy::Full => *self,
+		}
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+impl DS4Report {
+	/// Returns the report redacted by the policy, see [`RedactPolicy`].
+	///
+	/// The report counter in the upper bits of the special byte is dropped unless the policy is [`Full`](RedactPolicy::Full).
+	#[inline]
+	pub fn redacted(&self, policy: RedactPolicy) -> DS4Report {
+		let buttons_only = DS4Report { buttons: self.buttons, special: self.special & 0x3, ..DS4Report::neutral() };
+		match policy {
+			RedactPolicy::PresenceOnly if buttons_only == DS4Report::neutral() && self.has_neutral_axes() => DS4Report::neutral(),
+			RedactPolicy::PresenceOnly => DS4Report { buttons: 0xF, ..DS4Report::neutral() },
+			RedactPolicy::ButtonsOnly => buttons_only,
+			RedactPolicy::Full => *self,
+		}
+	}
+
+	#[inline]
+	fn has_neutral_axes(&self) -> bool {
+		let neutral = DS4Report::neutral();
+		(self.thumb_lx, self.thumb_ly, self.thumb_rx, self.thumb_ry, self.trigger_l, self.trigger_r) ==
+			(neutral.thumb_lx, neutral.thumb_ly, neutral.thumb_rx, neutral.thumb_ry, neutral.trigger_l, neutral.trigger_r)
+	}
+}
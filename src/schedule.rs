@@ -0,0 +1,274 @@
+use std::{thread, time};
+use std::borrow::Borrow;
+use crate::*;
+
+/// Source of the ticks a [`SubmitScheduler`] submits on, eg. a fixed rate timer or the display's vertical blank.
+pub trait ClockSource {
+	/// Blocks until the next tick.
+	fn wait_next_tick(&mut self);
+
+	/// Returns the current time, used to measure the phase offset.
+	///
+	/// Override to drive the scheduler from a simulated clock.
+	#[inline]
+	fn now(&self) -> time::Instant {
+		time::Instant::now()
+	}
+}
+
+impl<C: ClockSource + ?Sized> ClockSource for &mut C {
+	#[inline]
+	fn wait_next_tick(&mut self) {
+		(**self).wait_next_tick()
+	}
+	#[inline]
+	fn now(&self) -> time::Instant {
+		(**self).now()
+	}
+}
+impl<C: ClockSource + ?Sized> ClockSource for Box<C> {
+	#[inline]
+	fn wait_next_tick(&mut self) {
+		(**self).wait_next_tick()
+	}
+	#[inline]
+	fn now(&self) -> time::Instant {
+		(**self).now()
+	}
+}
+
+/// Ticks at a fixed rate.
+///
+/// Ticks are scheduled relative to the first tick so the rate does not drift, missed ticks are skipped.
+/// The precision is limited by the resolution of the system timer the thread sleeps on.
+#[derive(Clone, Debug)]
+pub struct FixedRate {
+	interval: time::Duration,
+	next: Option<time::Instant>,
+}
+
+impl FixedRate {
+	/// Ticks every interval, the first tick is immediate.
+	#[inline]
+	pub const fn new(interval: time::Duration) -> FixedRate {
+		FixedRate { interval, next: None }
+	}
+
+	/// Ticks at the rate in Hz.
+	///
+	/// Panics if the rate is not positive and finite.
+	#[inline]
+	pub fn from_hz(hz: f64) -> FixedRate {
+		FixedRate::try_from_hz(hz).expect("rate must be positive and finite")
+	}
+
+	/// Ticks at the rate in Hz, `None` if the rate is not positive and finite or too low for its interval to be represented.
+	#[inline]
+	pub fn try_from_hz(hz: f64) -> Option<FixedRate> {
+		if !(hz > 0.0 && hz.is_finite()) {
+			return None;
+		}
+		let interval = time::Duration::try_from_secs_f64(1.0 / hz).ok()?;
+		Some(FixedRate::new(interval))
+	}
+
+	/// Returns the interval between ticks.
+	#[inline]
+	pub const fn interval(&self) -> time::Duration {
+		self.interval
+	}
+
+	/// Returns when the next tick is due, `None` before the first tick.
+	#[inline]
+	pub(crate) fn next_tick(&self) -> Option<time::Instant> {
+		self.next.map(|last| last + self.interval)
+	}
+
+	/// Ticks without blocking if a tick is due at the time, returns if it ticked.
+	pub(crate) fn poll(&mut self, now: time::Instant) -> bool {
+		let due = self.next_tick().unwrap_or(now);
+		if now < due {
+			return false;
+		}
+		// Keeps the phase unless ticks were missed
+		self.next = Some(if due + self.interval > now { due } else { now });
+		true
+	}
+}
+
+impl ClockSource for FixedRate {
+	fn wait_next_tick(&mut self) {
+		let now = time::Instant::now();
+		let next = match self.next {
+			Some(next) if next + self.interval > now => next + self.interval,
+			// First tick or ticks were missed
+			_ => now,
+		};
+		self.next = Some(next);
+		if next > now {
+			thread::sleep(next - now);
+		}
+	}
+}
+
+/// Ticks on the vertical blank of the desktop compositor.
+///
+/// Waits with `DwmFlush`, without desktop composition it falls back to a fixed rate of 60Hz.
+#[cfg(all(windows, feature = "dwm"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "dwm")))]
+#[derive(Clone, Debug)]
+pub struct DwmVblank {
+	fallback: FixedRate,
+}
+
+#[cfg(all(windows, feature = "dwm"))]
+impl Default for DwmVblank {
+	#[inline]
+	fn default() -> DwmVblank {
+		DwmVblank::new()
+	}
+}
+
+#[cfg(all(windows, feature = "dwm"))]
+impl DwmVblank {
+	/// Creates the clock source.
+	#[inline]
+	pub fn new() -> DwmVblank {
+		DwmVblank { fallback: FixedRate::from_hz(60.0) }
+	}
+}
+
+#[cfg(all(windows, feature = "dwm"))]
+impl ClockSource for DwmVblank {
+	#[inline]
+	fn wait_next_tick(&mut self) {
+		if unsafe { winapi::um::dwmapi::DwmFlush() } < 0 {
+			self.fallback.wait_next_tick();
+		}
+	}
+}
+
+/// Targets accepting reports from a [`SubmitScheduler`].
+pub trait Submit<R> {
+	/// Submits the report, see eg. [`Xbox360Wired::update`].
+	fn submit_report(&mut self, report: &R) -> Result<(), Error>;
+}
+
+impl<CL: Borrow<Client>> Submit<XGamepad> for Xbox360Wired<CL> {
+	#[inline]
+	fn submit_report(&mut self, report: &XGamepad) -> Result<(), Error> {
+		self.update(report)
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+impl<CL: Borrow<Client>> Submit<DS4Report> for DualShock4Wired<CL> {
+	#[inline]
+	fn submit_report(&mut self, report: &DS4Report) -> Result<(), Error> {
+		self.update(report)
+	}
+}
+
+impl<R, T: Submit<R> + ?Sized> Submit<R> for &mut T {
+	#[inline]
+	fn submit_report(&mut self, report: &R) -> Result<(), Error> {
+		(**self).submit_report(report)
+	}
+}
+
+/// Submits the freshest input right before it is sampled, eg. on the vertical blank.
+///
+/// Every tick of the clock source the callback produces the report to submit.
+/// Report when input arrives with [`input_arrived`](Self::input_arrived) to measure how long it waited for its submission.
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// let client = vigem_client::Client::connect().unwrap();
+/// let mut target = vigem_client::Xbox360Wired::new(client, vigem_client::TargetId::XBOX360_WIRED);
+/// target.plugin().unwrap();
+/// target.wait_ready().unwrap();
+///
+/// let mut scheduler = vigem_client::SubmitScheduler::new(vigem_client::FixedRate::from_hz(125.0));
+/// for _ in 0..125 {
+/// 	scheduler.tick(&mut target, || {
+/// 		// Sample the physical input here
+/// 		Some(vigem_client::XGamepad::default())
+/// 	}).unwrap();
+/// }
+/// println!("mean phase offset: {:?}", scheduler.stats().mean());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SubmitScheduler<C: ClockSource> {
+	clock: C,
+	arrival: Option<time::Instant>,
+	stats: LatencyStats,
+}
+
+impl<C: ClockSource> SubmitScheduler<C> {
+	/// Creates a scheduler ticking on the clock source.
+	#[inline]
+	pub fn new(clock: C) -> SubmitScheduler<C> {
+		SubmitScheduler { clock, arrival: None, stats: LatencyStats::default() }
+	}
+
+	/// Returns the clock source.
+	#[inline]
+	pub fn clock(&self) -> &C {
+		&self.clock
+	}
+
+	/// Returns the clock source.
+	#[inline]
+	pub fn clock_mut(&mut self) -> &mut C {
+		&mut self.clock
+	}
+
+	/// Records that fresh input arrived now.
+	#[inline]
+	pub fn input_arrived(&mut self) {
+		let now = self.clock.now();
+		self.input_arrived_at(now);
+	}
+
+	/// Records that fresh input arrived at the given time.
+	///
+	/// Only the most recent arrival before a submission is measured.
+	#[inline]
+	pub fn input_arrived_at(&mut self, at: time::Instant) {
+		self.arrival = Some(at);
+	}
+
+	/// Waits for the next tick and submits the report produced by the callback.
+	///
+	/// Nothing is submitted if the callback returns `None`.
+	/// Returns if a report was submitted.
+	pub fn tick<R, T: Submit<R> + ?Sized, F: FnOnce() -> Option<R>>(&mut self, target: &mut T, produce: F) -> Result<bool, Error> {
+		self.clock.wait_next_tick();
+		let Some(report) = produce() else { return Ok(false) };
+		let now = self.clock.now();
+		target.submit_report(&report)?;
+		if let Some(arrival) = self.arrival.take() {
+			self.stats.record(arrival, now);
+		}
+		Ok(true)
+	}
+
+	/// Returns the phase offset between the most recent input arrival and its submission.
+	#[inline]
+	pub fn phase_offset(&self) -> Option<time::Duration> {
+		self.stats.last()
+	}
+
+	/// Returns the statistics of the phase offsets of the most recent submissions.
+	#[inline]
+	pub fn stats(&self) -> &LatencyStats {
+		&self.stats
+	}
+
+	/// Clears the statistics of the phase offset.
+	#[inline]
+	pub fn reset_stats(&mut self) {
+		self.stats.clear();
+	}
+}
@@ -0,0 +1,183 @@
+use std::{fmt, thread, time};
+use crate::*;
+
+/// Error of plugging in a collection of targets with rollback, see [`TargetSet::plugin_all_or_rollback`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RollbackError {
+	/// Index of the target which failed to plug in.
+	pub index: usize,
+	/// Why the target failed to plug in.
+	pub error: Error,
+	/// Targets which failed to unplug during the rollback, in the order they were unplugged.
+	///
+	/// These targets remain plugged in.
+	pub rollback: Vec<(usize, Error)>,
+}
+
+impl fmt::Display for RollbackError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "target {} failed to plug in: {}", self.index, self.error)?;
+		if !self.rollback.is_empty() {
+			write!(f, ", {} failed to unplug", self.rollback.len())?;
+		}
+		Ok(())
+	}
+}
+
+impl std::error::Error for RollbackError {}
+
+/// A collection of targets plugged in and unplugged together.
+///
+/// Windows plays the 'Device Connect' sound for every target plugged in.
+/// There is no way for a client to suppress these sounds per device:
+/// the virtual controllers are installed by Plug and Play as children of the bus,
+/// outside of any device installation the client could mark as quiet.
+/// Staggering the plugins with [`set_stagger`](Self::set_stagger) spreads the sounds out instead of playing them back to back.
+///
+/// ```no_run
+/// let client = vigem_client::Client::connect().unwrap();
+/// let id = vigem_client::TargetId::XBOX360_WIRED;
+///
+/// let mut set = vigem_client::TargetSet::new();
+/// set.push(vigem_client::Xbox360Wired::new(&client, id));
+/// set.push(vigem_client::Xbox360Wired::new(&client, id));
+/// set.set_stagger(std::time::Duration::from_millis(500));
+///
+/// set.plugin_all().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct TargetSet<T> {
+	targets: Vec<T>,
+	stagger: time::Duration,
+	sleep: fn(time::Duration),
+}
+
+impl<T> Default for TargetSet<T> {
+	#[inline]
+	fn default() -> Self {
+		TargetSet::new()
+	}
+}
+
+impl<T> TargetSet<T> {
+	/// Creates a new empty set.
+	#[inline]
+	pub const fn new() -> TargetSet<T> {
+		TargetSet { targets: Vec::new(), stagger: time::Duration::from_secs(0), sleep: thread::sleep }
+	}
+
+	/// Returns the delay between plugging in consecutive targets.
+	#[inline]
+	pub fn stagger(&self) -> time::Duration {
+		self.stagger
+	}
+
+	/// Sets the delay between plugging in consecutive targets.
+	///
+	/// Defaults to zero.
+	#[inline]
+	pub fn set_stagger(&mut self, stagger: time::Duration) {
+		self.stagger = stagger;
+	}
+
+	/// Sets the function waiting for the stagger delay.
+	///
+	/// Defaults to [`thread::sleep`], override to drive the set from a simulated clock.
+	#[inline]
+	pub fn set_sleep(&mut self, sleep: fn(time::Duration)) {
+		self.sleep = sleep;
+	}
+
+	/// Adds a target to the end of the set.
+	#[inline]
+	pub fn push(&mut self, target: T) {
+		self.targets.push(target);
+	}
+
+	/// Returns the targets in order.
+	#[inline]
+	pub fn targets(&self) -> &[T] {
+		&self.targets
+	}
+
+	/// Returns the targets in order.
+	#[inline]
+	pub fn targets_mut(&mut self) -> &mut [T] {
+		&mut self.targets
+	}
+
+	/// Returns the targets, consuming the set.
+	#[inline]
+	pub fn into_targets(self) -> Vec<T> {
+		self.targets
+	}
+}
+
+impl<T: VirtualTarget> TargetSet<T> {
+	/// Plugs in the targets in order.
+	///
+	/// Targets which are already plugged in are skipped.
+	/// Waits for the stagger delay between plugging in consecutive targets.
+	///
+	/// Stops at the first failure, returning the index of the target and the error.
+	/// Targets plugged in before the failure remain plugged in.
+	#[inline(never)]
+	pub fn plugin_all(&mut self) -> Result<(), (usize, Error)> {
+		self.plugin_each(|_| ())
+	}
+
+	/// Plugs in the targets in order, unplugging them again on failure.
+	///
+	/// Same as [`plugin_all`](Self::plugin_all) but on failure the targets plugged in by this call are unplugged in reverse order
+	/// before returning the index of the failed target and the error. Targets which were already plugged in are left alone.
+	///
+	/// The rollback is best effort: a target which fails to unplug remains plugged in, see [`VirtualTarget::is_attached`].
+	/// The rollback keeps going and returns the index and error of every such target in [`RollbackError::rollback`].
+	#[inline(never)]
+	pub fn plugin_all_or_rollback(&mut self) -> Result<(), RollbackError> {
+		let mut plugged = Vec::new();
+		let (index, error) = match self.plugin_each(|index| plugged.push(index)) {
+			Ok(()) => return Ok(()),
+			Err(err) => err,
+		};
+		let rollback = plugged.iter().rev()
+			.filter_map(|&index| self.targets[index].unplug().err().map(|err| (index, err)))
+			.collect();
+		Err(RollbackError { index, error, rollback })
+	}
+
+	fn plugin_each(&mut self, mut plugged: impl FnMut(usize)) -> Result<(), (usize, Error)> {
+		let mut first = true;
+		for (index, target) in self.targets.iter_mut().enumerate() {
+			if target.is_attached() {
+				continue;
+			}
+			if !first && self.stagger > time::Duration::from_secs(0) {
+				(self.sleep)(self.stagger);
+			}
+			first = false;
+			target.plugin().map_err(|err| (index, err))?;
+			plugged(index);
+		}
+		Ok(())
+	}
+
+	/// Unplugs all the targets in reverse order.
+	///
+	/// Targets which are not plugged in are skipped.
+	/// Keeps going after a failure and returns the first failure encountered.
+	#[inline(never)]
+	pub fn unplug_all(&mut self) -> Result<(), (usize, Error)> {
+		let mut result = Ok(());
+		for (index, target) in self.targets.iter_mut().enumerate().rev() {
+			if target.is_attached() {
+				if let Err(err) = target.unplug() {
+					if result.is_ok() {
+						result = Err((index, err));
+					}
+				}
+			}
+		}
+		result
+	}
+}
@@ -0,0 +1,225 @@
+use std::{collections::VecDeque, fmt, time};
+use crate::*;
+
+/// Distribution of the random latency added on top of the fixed delay, see [`ShaperConfig`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Jitter {
+	/// No jitter, every report is delayed by exactly the fixed delay.
+	None,
+	/// Uniformly distributed between zero and the maximum.
+	Uniform {
+		/// The largest latency added.
+		max: time::Duration,
+	},
+	/// Normally distributed around zero, the total delay is clamped to be non-negative.
+	Normal {
+		/// The standard deviation.
+		std_dev: time::Duration,
+	},
+}
+
+/// Configuration of a [`LatencyShaper`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ShaperConfig {
+	/// Fixed delay of every report.
+	pub delay: time::Duration,
+	/// Random latency added to the fixed delay.
+	pub jitter: Jitter,
+	/// Probability in `0.0..=1.0` a report is dropped instead of submitted.
+	pub drop_probability: f64,
+	/// Seed of the random number generator, the same seed reproduces the same jitter and drops.
+	pub seed: u64,
+}
+
+impl Default for ShaperConfig {
+	#[inline]
+	fn default() -> ShaperConfig {
+		ShaperConfig::new()
+	}
+}
+
+impl ShaperConfig {
+	/// Configuration without delay, jitter or drops.
+	#[inline]
+	pub const fn new() -> ShaperConfig {
+		ShaperConfig {
+			delay: time::Duration::ZERO,
+			jitter: Jitter::None,
+			drop_probability: 0.0,
+			seed: 0,
+		}
+	}
+}
+
+/// Delays, jitters and drops reports before submitting them to the target.
+///
+/// **Testing only**: simulates a laggy input path to test how the software consuming the virtual controller copes with it.
+///
+/// Pushed reports are buffered with their release time and submitted once it arrives when pumped, see [`Pump`].
+/// Reports are never reordered, a report is not released before the report pushed before it.
+/// Reports still buffered when the shaper is dropped are submitted immediately.
+///
+/// ```no_run
+/// use std::time::{Duration, Instant};
+///
+/// let client = vigem_client::Client::connect().unwrap();
+/// let mut target = vigem_client::Xbox360Wired::new(client, vigem_client::TargetId::XBOX360_WIRED);
+/// target.plugin().unwrap();
+/// target.wait_ready().unwrap();
+///
+/// let config = vigem_client::ShaperConfig {
+/// 	delay: Duration::from_millis(50),
+/// 	jitter: vigem_client::Jitter::Uniform { max: Duration::from_millis(20) },
+/// 	drop_probability: 0.01,
+/// 	seed: 42,
+/// };
+/// let mut shaper = vigem_client::LatencyShaper::new(target, config);
+/// shaper.push(vigem_client::XGamepad::default());
+///
+/// // From the host's loop
+/// use vigem_client::Pump;
+/// shaper.pump(Instant::now()).unwrap();
+/// ```
+pub struct LatencyShaper<T: Submit<R>, R> {
+	target: T,
+	config: ShaperConfig,
+	rng: u64,
+	queue: VecDeque<(time::Instant, R)>,
+	last_release: Option<time::Instant>,
+}
+
+impl<T: Submit<R>, R> LatencyShaper<T, R> {
+	/// Wraps the target.
+	#[inline]
+	pub fn new(target: T, config: ShaperConfig) -> LatencyShaper<T, R> {
+		LatencyShaper { target, config, rng: config.seed, queue: VecDeque::new(), last_release: None }
+	}
+
+	/// Returns the configuration.
+	#[inline]
+	pub fn config(&self) -> &ShaperConfig {
+		&self.config
+	}
+
+	/// Returns the target.
+	#[inline]
+	pub fn target(&self) -> &T {
+		&self.target
+	}
+
+	/// Returns the target.
+	#[inline]
+	pub fn target_mut(&mut self) -> &mut T {
+		&mut self.target
+	}
+
+	/// Buffers the report arriving now.
+	///
+	/// Returns `false` if the report was dropped.
+	#[inline]
+	pub fn push(&mut self, report: R) -> bool {
+		self.push_at(report, time::Instant::now())
+	}
+
+	/// Buffers the report arriving at the given time.
+	///
+	/// Returns `false` if the report was dropped.
+	pub fn push_at(&mut self, report: R, now: time::Instant) -> bool {
+		if self.config.drop_probability > 0.0 && self.next_f64() < self.config.drop_probability {
+			return false;
+		}
+		let delay = self.config.delay.as_secs_f64() + match self.config.jitter {
+			Jitter::None => 0.0,
+			Jitter::Uniform { max } => self.next_f64() * max.as_secs_f64(),
+			Jitter::Normal { std_dev } => self.next_normal() * std_dev.as_secs_f64(),
+		};
+		let mut release = now + time::Duration::from_secs_f64(delay.max(0.0));
+		// Preserve the order of the reports
+		if let Some(last) = self.last_release {
+			release = release.max(last);
+		}
+		self.last_release = Some(release);
+		self.queue.push_back((release, report));
+		true
+	}
+
+	/// Returns the number of buffered reports.
+	#[inline]
+	pub fn pending(&self) -> usize {
+		self.queue.len()
+	}
+
+	/// Returns the release time of the next buffered report.
+	#[inline]
+	pub fn next_release(&self) -> Option<time::Instant> {
+		self.queue.front().map(|&(release, _)| release)
+	}
+
+	/// Returns the release times of the buffered reports in order.
+	#[inline]
+	pub fn releases(&self) -> impl Iterator<Item = time::Instant> + '_ {
+		self.queue.iter().map(|&(release, _)| release)
+	}
+
+	/// Submits all buffered reports immediately.
+	///
+	/// On error the failed report is discarded and the remaining reports stay buffered.
+	pub fn flush(&mut self) -> Result<(), Error> {
+		while let Some((_, report)) = self.queue.pop_front() {
+			self.target.submit_report(&report)?;
+		}
+		Ok(())
+	}
+
+	// splitmix64
+	fn next_u64(&mut self) -> u64 {
+		self.rng = self.rng.wrapping_add(0x9E3779B97F4A7C15);
+		let mut z = self.rng;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+		z ^ (z >> 31)
+	}
+	// Uniform in `0.0..1.0`
+	fn next_f64(&mut self) -> f64 {
+		(self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+	}
+	// Standard normal with the Box-Muller transform
+	fn next_normal(&mut self) -> f64 {
+		let u1 = 1.0 - self.next_f64();
+		let u2 = self.next_f64();
+		(-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+	}
+}
+
+/// Submits the buffered reports whose release time has arrived.
+///
+/// On error the failed report is discarded and the remaining reports stay buffered.
+impl<T: Submit<R>, R> Pump for LatencyShaper<T, R> {
+	fn pump(&mut self, now: time::Instant) -> Result<(), Error> {
+		while matches!(self.queue.front(), Some(&(release, _)) if release <= now) {
+			if let Some((_, report)) = self.queue.pop_front() {
+				self.target.submit_report(&report)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl<T: Submit<R> + fmt::Debug, R> fmt::Debug for LatencyShaper<T, R> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("LatencyShaper")
+			.field("target", &self.target)
+			.field("config", &self.config)
+			.field("pending", &self.queue.len())
+			.finish()
+	}
+}
+
+impl<T: Submit<R>, R> Drop for LatencyShaper<T, R> {
+	fn drop(&mut self) {
+		// Keep submitting past errors, nothing can be reported from here
+		while let Some((_, report)) = self.queue.pop_front() {
+			let _ = self.target.submit_report(&report);
+		}
+	}
+}
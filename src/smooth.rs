@@ -0,0 +1,223 @@
+use std::time;
+use crate::*;
+use crate::filter::round_axis;
+
+/// Smoothing curve of an [`AxisSmoother`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum SmoothingMode {
+	/// Moves a fixed fraction of the remaining distance per time constant, reaching 63% of a step after one time constant.
+	///
+	/// Responds immediately but the velocity jumps when the input steps.
+	#[default]
+	Exponential,
+	/// Critically damped spring, reaching 26% of a step after one time constant and 59% after two.
+	///
+	/// The velocity changes smoothly and the axis never overshoots, at the cost of lagging more than the exponential curve.
+	Spring,
+}
+
+/// Smooths the analog axes of gamepad states submitted at a higher rate than their source updates.
+///
+/// A source updating at 60 Hz submitted at 500 Hz holds every state for several reports and then jumps,
+/// games deriving velocity from the deltas of the thumbsticks see these staircases.
+/// The smoother glides towards the latest state instead, taking the time since the previous state into account
+/// such that the result does not depend on the submit rate.
+///
+/// The axes are smoothed in normalized space, `-1.0..=1.0` for the thumbsticks and `0.0..=1.0` for the triggers.
+/// The buttons are passed through unchanged.
+///
+/// Smoothing trades latency for smoothness: the time constant delays every input.
+/// Choose it in the order of the update interval of the source, eg. 16ms for a 60 Hz source,
+/// and use [`bypass_below`](Self::bypass_below) to let small corrections through undelayed.
+///
+/// ```
+/// use std::time::Duration;
+/// use vigem_client::{AxisSmoother, XGamepad};
+///
+/// let mut smoother = AxisSmoother::exponential(Duration::from_millis(16));
+/// smoother.apply(&XGamepad::default(), Duration::ZERO);
+///
+/// // The thumbstick glides towards the new state
+/// let gamepad = XGamepad { thumb_lx: 32767, ..Default::default() };
+/// let smoothed = smoother.apply(&gamepad, Duration::from_millis(2));
+/// assert!(smoothed.thumb_lx > 0 && smoothed.thumb_lx < 32767);
+/// ```
+///
+/// Pass the smoother the time between states, or let [`smooth`](Self::smooth) supply it to a [`PacedDriver`] callback.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AxisSmoother {
+	mode: SmoothingMode,
+	time_constants: [f32; 6],
+	bypass_below: f32,
+	position: [f32; 6],
+	velocity: [f32; 6],
+	primed: bool,
+}
+
+impl Default for AxisSmoother {
+	#[inline]
+	fn default() -> AxisSmoother {
+		AxisSmoother::new()
+	}
+}
+
+impl AxisSmoother {
+	/// Smoother which passes gamepad states through unchanged.
+	#[inline]
+	pub const fn new() -> AxisSmoother {
+		AxisSmoother {
+			mode: SmoothingMode::Exponential,
+			time_constants: [0.0; 6],
+			bypass_below: 0.0,
+			position: [0.0; 6],
+			velocity: [0.0; 6],
+			primed: false,
+		}
+	}
+
+	/// Smoother with exponential smoothing of every axis.
+	#[inline]
+	pub fn exponential(time_constant: time::Duration) -> AxisSmoother {
+		AxisSmoother::new().all_axes(time_constant)
+	}
+
+	/// Smoother with critically damped spring smoothing of every axis.
+	#[inline]
+	pub fn spring(time_constant: time::Duration) -> AxisSmoother {
+		AxisSmoother { mode: SmoothingMode::Spring, ..AxisSmoother::new() }.all_axes(time_constant)
+	}
+
+	/// Sets the time constant of every axis.
+	#[inline]
+	pub fn all_axes(mut self, time_constant: time::Duration) -> AxisSmoother {
+		self.time_constants = [time_constant.as_secs_f32(); 6];
+		self
+	}
+
+	/// Sets the time constant of the axis, zero passes the axis through unchanged.
+	#[inline]
+	pub fn axis(mut self, axis: Axis, time_constant: time::Duration) -> AxisSmoother {
+		self.time_constants[axis as usize] = time_constant.as_secs_f32();
+		self
+	}
+
+	/// Passes changes through without smoothing while the axis is closer than the threshold to its input.
+	///
+	/// The threshold is in normalized space, eg. `0.02` for 2% of the range of the axis.
+	/// Small corrections arrive undelayed, large movements are smoothed until they come within the threshold.
+	#[inline]
+	pub fn bypass_below(mut self, threshold: f32) -> AxisSmoother {
+		self.bypass_below = threshold;
+		self
+	}
+
+	/// Returns the smoothing curve.
+	#[inline]
+	pub fn mode(&self) -> SmoothingMode {
+		self.mode
+	}
+
+	/// Returns the time constant of the axis.
+	#[inline]
+	pub fn time_constant(&self, axis: Axis) -> time::Duration {
+		time::Duration::from_secs_f32(self.time_constants[axis as usize])
+	}
+
+	/// Forgets the smoothed state, the next state passes through unchanged.
+	#[inline]
+	pub fn reset(&mut self) {
+		self.primed = false;
+	}
+
+	/// Smooths the gamepad state given the time since the previous state.
+	///
+	/// The first state after creating or [resetting](Self::reset) the smoother passes through unchanged.
+	pub fn apply(&mut self, gamepad: &XGamepad, dt: time::Duration) -> XGamepad {
+		let stick = |value: i16| value as f32 / i16::MAX as f32;
+		let trigger = |value: u8| value as f32 / u8::MAX as f32;
+		let input = [
+			stick(gamepad.thumb_lx), stick(gamepad.thumb_ly),
+			stick(gamepad.thumb_rx), stick(gamepad.thumb_ry),
+			trigger(gamepad.left_trigger), trigger(gamepad.right_trigger),
+		];
+		if !self.primed {
+			self.position = input;
+			self.velocity = [0.0; 6];
+			self.primed = true;
+			return *gamepad;
+		}
+
+		let dt = dt.as_secs_f32();
+		for (i, &input) in input.iter().enumerate() {
+			let (position, velocity) = step(self.mode, self.position[i], self.velocity[i], input, self.time_constants[i], dt);
+			let (position, velocity) = if (input - position).abs() < self.bypass_below { (input, 0.0) } else { (position, velocity) };
+			self.position[i] = position;
+			self.velocity[i] = velocity;
+		}
+
+		let stick = |axis: Axis| round_axis(self.position[axis as usize] * i16::MAX as f32);
+		let trigger = |axis: Axis| (self.position[axis as usize] * u8::MAX as f32).round().clamp(0.0, 255.0) as u8;
+		XGamepad {
+			buttons: gamepad.buttons,
+			left_trigger: trigger(Axis::LT),
+			right_trigger: trigger(Axis::RT),
+			thumb_lx: stick(Axis::LX),
+			thumb_ly: stick(Axis::LY),
+			thumb_rx: stick(Axis::RX),
+			thumb_ry: stick(Axis::RY),
+		}
+	}
+
+	/// Wraps a [`PacedDriver`] callback to smooth the gamepad states it produces.
+	///
+	/// The time between states is derived from the elapsed time passed to the callback.
+	///
+	/// ```no_run
+	/// use std::time::Duration;
+	/// use vigem_client::{AxisSmoother, PacedDriver, XGamepad};
+	///
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::Xbox360Wired::new(client, vigem_client::TargetId::XBOX360_WIRED);
+	/// target.plugin().unwrap();
+	/// target.wait_ready().unwrap();
+	///
+	/// // Submit a 60 Hz source at 500 Hz
+	/// let smoother = AxisSmoother::exponential(Duration::from_millis(16));
+	/// let mut driver = PacedDriver::new(&mut target, 500.0, smoother.smooth(|elapsed: Duration| {
+	/// 	let frame = (elapsed.as_secs_f32() * 60.0) as i16;
+	/// 	Some(XGamepad { thumb_lx: frame.wrapping_mul(100), ..Default::default() })
+	/// }));
+	/// driver.run(&mut vigem_client::FixedRate::from_hz(500.0));
+	/// ```
+	#[inline]
+	pub fn smooth<F: FnMut(time::Duration) -> Option<XGamepad>>(mut self, mut produce: F) -> impl FnMut(time::Duration) -> Option<XGamepad> {
+		let mut last = time::Duration::ZERO;
+		move |elapsed| {
+			let gamepad = produce(elapsed)?;
+			let dt = elapsed.saturating_sub(last);
+			last = elapsed;
+			Some(self.apply(&gamepad, dt))
+		}
+	}
+}
+
+// Advances the axis towards the input, exact for an input held constant over the step.
+fn step(mode: SmoothingMode, position: f32, velocity: f32, input: f32, time_constant: f32, dt: f32) -> (f32, f32) {
+	if time_constant <= 0.0 {
+		return (input, 0.0);
+	}
+	if dt <= 0.0 {
+		return (position, velocity);
+	}
+	let omega = 1.0 / time_constant;
+	let decay = (-omega * dt).exp();
+	let error = position - input;
+	match mode {
+		SmoothingMode::Exponential => (input + error * decay, 0.0),
+		SmoothingMode::Spring => {
+			// Solution of x'' = -2ωx' - ω²x for the error x
+			let b = velocity + omega * error;
+			(input + (error + b * dt) * decay, (velocity - omega * b * dt) * decay)
+		},
+	}
+}
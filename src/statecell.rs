@@ -0,0 +1,186 @@
+use std::fmt;
+use crate::sync::{spin_loop, AtomicBool, AtomicUsize, Ordering, UnsafeCell};
+
+// Index of the buffer in between the sides
+const INDEX: usize = 3;
+// Set while the buffer in between holds a value the consumer has not taken yet
+const FRESH: usize = 4;
+
+/// Hands the latest value over from a producer thread to a consumer thread without locking.
+///
+/// A triple buffer: the producer and the consumer each own a buffer and swap it with the buffer in between.
+/// Publishing never waits for the consumer and reading never waits for the producer,
+/// values published in between reads are skipped.
+///
+/// Meant for a single producer and a single consumer, eg. a thread receiving states from the network
+/// and a thread submitting them to the target at a fixed rate, see [`Xbox360Wired::drive_from_cell`](crate::Xbox360Wired::drive_from_cell).
+/// Concurrent publishes, or concurrent reads, are serialized by spinning.
+///
+/// ```
+/// use std::{sync::Arc, thread};
+/// use vigem_client::{StateCell, XGamepad};
+///
+/// let cell = Arc::new(StateCell::new(XGamepad::default()));
+/// let producer = {
+/// 	let cell = cell.clone();
+/// 	thread::spawn(move || {
+/// 		for thumb_lx in 0..1000 {
+/// 			cell.publish(XGamepad { thumb_lx, ..Default::default() });
+/// 		}
+/// 		cell.close();
+/// 	})
+/// };
+/// while !cell.is_closed() {
+/// 	let _gamepad = cell.latest();
+/// }
+/// producer.join().unwrap();
+/// assert_eq!(cell.latest().thumb_lx, 999);
+/// ```
+pub struct StateCell<T> {
+	buffers: [UnsafeCell<T>; 3],
+	shared: AtomicUsize,
+	producer: Side,
+	consumer: Side,
+	closed: AtomicBool,
+}
+
+// The buffer owned by either side, locked against concurrent use of the same side
+struct Side {
+	busy: AtomicBool,
+	index: UnsafeCell<usize>,
+}
+
+impl Side {
+	fn new(index: usize) -> Side {
+		Side { busy: AtomicBool::new(false), index: UnsafeCell::new(index) }
+	}
+	fn lock(&self) -> SideGuard<'_> {
+		while self.busy.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+			spin_loop();
+		}
+		SideGuard(self)
+	}
+}
+
+struct SideGuard<'a>(&'a Side);
+
+impl SideGuard<'_> {
+	fn index(&self) -> usize {
+		self.0.index.with(|index| unsafe { *index })
+	}
+	fn set_index(&self, index: usize) {
+		self.0.index.with_mut(|ptr| unsafe { *ptr = index });
+	}
+}
+
+impl Drop for SideGuard<'_> {
+	fn drop(&mut self) {
+		self.0.busy.store(false, Ordering::Release);
+	}
+}
+
+impl<T: Copy> StateCell<T> {
+	/// Creates a cell holding the initial value, which is not fresh.
+	#[inline]
+	pub fn new(value: T) -> StateCell<T> {
+		StateCell {
+			buffers: [UnsafeCell::new(value), UnsafeCell::new(value), UnsafeCell::new(value)],
+			shared: AtomicUsize::new(1),
+			producer: Side::new(0),
+			consumer: Side::new(2),
+			closed: AtomicBool::new(false),
+		}
+	}
+
+	/// Publishes a value, replacing the value published before if it was not read yet.
+	#[inline]
+	pub fn publish(&self, value: T) {
+		let side = self.producer.lock();
+		let index = side.index();
+		self.buffers[index].with_mut(|ptr| unsafe { *ptr = value });
+		side.set_index(self.shared.swap(index | FRESH, Ordering::AcqRel) & INDEX);
+	}
+
+	/// Returns the latest value published, or the last value read if nothing was published since.
+	#[inline]
+	pub fn latest(&self) -> T {
+		self.read().0
+	}
+
+	/// Returns the latest value if it was published since the last read.
+	#[inline]
+	pub fn take(&self) -> Option<T> {
+		match self.read() {
+			(value, true) => Some(value),
+			(_, false) => None,
+		}
+	}
+
+	/// Returns the latest value, `None` once the cell is closed and every value published before closing was read.
+	///
+	/// The value published right before closing is returned before `None`.
+	///
+	/// ```
+	/// let cell = vigem_client::StateCell::new(0);
+	/// assert_eq!(cell.poll(), Some(0));
+	/// cell.publish(1);
+	/// cell.close();
+	/// assert_eq!(cell.poll(), Some(1));
+	/// assert_eq!(cell.poll(), None);
+	/// ```
+	#[inline]
+	pub fn poll(&self) -> Option<T> {
+		// Closing happens after the last publish, checking it first cannot miss that value
+		let closed = self.is_closed();
+		match self.read() {
+			(value, true) => Some(value),
+			(value, false) if !closed => Some(value),
+			(_, false) => None,
+		}
+	}
+
+	fn read(&self) -> (T, bool) {
+		let side = self.consumer.lock();
+		let mut index = side.index();
+		let fresh = self.shared.load(Ordering::Relaxed) & FRESH != 0;
+		if fresh {
+			index = self.shared.swap(index, Ordering::AcqRel) & INDEX;
+			side.set_index(index);
+		}
+		(self.buffers[index].with(|ptr| unsafe { *ptr }), fresh)
+	}
+
+	/// Tells the consumer no more values follow.
+	///
+	/// Values published before closing remain readable.
+	#[inline]
+	pub fn close(&self) {
+		self.closed.store(true, Ordering::Release);
+	}
+
+	/// Returns if the cell was closed.
+	#[inline]
+	pub fn is_closed(&self) -> bool {
+		self.closed.load(Ordering::Acquire)
+	}
+}
+
+// The buffers are only accessed by the side owning them, values are copied out
+unsafe impl<T: Send> Send for StateCell<T> {}
+unsafe impl<T: Send> Sync for StateCell<T> {}
+
+impl<T: Copy + Default> Default for StateCell<T> {
+	#[inline]
+	fn default() -> Self {
+		StateCell::new(T::default())
+	}
+}
+
+impl<T> fmt::Debug for StateCell<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("StateCell")
+			.field("fresh", &(self.shared.load(Ordering::Relaxed) & FRESH != 0))
+			.field("closed", &self.closed.load(Ordering::Relaxed))
+			.finish()
+	}
+}
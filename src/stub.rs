@@ -0,0 +1,239 @@
+/*!
+Stand-ins for the client and targets on platforms without ViGEmBus.
+
+Keeps code sharing the report types across platforms compiling, only the driver requests are Windows only.
+Connecting always fails with [`Error::BusNotFound`], without a client no target can be plugged in.
+*/
+
+use std::borrow::Borrow;
+use std::time;
+use crate::*;
+
+/// Connection to the ViGEmBus service, never established on this platform.
+///
+/// ```
+/// assert_eq!(vigem_client::Client::connect().err(), Some(vigem_client::Error::BusNotFound));
+/// ```
+#[derive(Debug)]
+pub struct Client {
+	_private: (),
+}
+
+impl Client {
+	/// Fails with [`Error::BusNotFound`], ViGEmBus is only available on Windows.
+	#[inline]
+	pub fn connect() -> Result<Client, Error> {
+		Err(Error::BusNotFound)
+	}
+
+	/// Fails with [`Error::BusNotFound`], ViGEmBus is only available on Windows.
+	#[inline]
+	pub fn connect_unchecked() -> Result<Client, Error> {
+		Err(Error::BusNotFound)
+	}
+
+	/// Fails with [`Error::BusNotFound`] without waiting, the bus never shows up on this platform.
+	#[inline]
+	pub fn connect_with_retry(_timeout: time::Duration, _interval: time::Duration) -> Result<Client, Error> {
+		Err(Error::BusNotFound)
+	}
+}
+
+/// Virtual Microsoft Xbox 360 Controller (wired).
+pub type XTarget = Xbox360Wired<Client>;
+
+/// A virtual Microsoft Xbox 360 Controller (wired), never plugged in on this platform.
+#[derive(Debug)]
+pub struct Xbox360Wired<CL: Borrow<Client>> {
+	client: CL,
+	id: TargetId,
+	uid: TargetUid,
+}
+
+impl<CL: Borrow<Client>> Xbox360Wired<CL> {
+	/// Creates a new instance.
+	#[inline]
+	pub fn new(client: CL, id: TargetId) -> Xbox360Wired<CL> {
+		Xbox360Wired { client, id, uid: TargetUid::next() }
+	}
+
+	/// Returns if the controller is plugged in, never.
+	#[inline]
+	pub fn is_attached(&self) -> bool {
+		false
+	}
+
+	/// Returns the vendor and product ids.
+	#[inline]
+	pub fn id(&self) -> TargetId {
+		self.id
+	}
+
+	/// Returns the uid of this instance.
+	#[inline]
+	pub fn uid(&self) -> TargetUid {
+		self.uid
+	}
+
+	/// Returns the serial number, zero while not plugged in.
+	#[inline]
+	pub fn serial_no(&self) -> u32 {
+		0
+	}
+
+	/// Returns the client.
+	#[inline]
+	pub fn client(&self) -> &CL {
+		&self.client
+	}
+
+	/// Fails with [`Error::BusNotFound`].
+	#[inline]
+	pub fn plugin(&mut self) -> Result<(), Error> {
+		Err(Error::BusNotFound)
+	}
+
+	/// Fails with [`Error::NotPluggedIn`].
+	#[inline]
+	pub fn unplug(&mut self) -> Result<(), Error> {
+		Err(Error::NotPluggedIn)
+	}
+
+	/// Fails with [`Error::NotPluggedIn`].
+	#[inline]
+	pub fn wait_ready(&mut self) -> Result<(), Error> {
+		Err(Error::NotPluggedIn)
+	}
+
+	/// Fails with [`Error::NotPluggedIn`].
+	#[inline]
+	pub fn update(&mut self, _gamepad: &XGamepad) -> Result<(), Error> {
+		Err(Error::NotPluggedIn)
+	}
+}
+
+/// A virtual Sony DualShock 4 (wired), never plugged in on this platform.
+#[derive(Debug)]
+pub struct DualShock4Wired<CL: Borrow<Client>> {
+	client: CL,
+	id: TargetId,
+	uid: TargetUid,
+}
+
+impl<CL: Borrow<Client>> DualShock4Wired<CL> {
+	/// Creates a new instance.
+	#[inline]
+	pub fn new(client: CL, id: TargetId) -> DualShock4Wired<CL> {
+		DualShock4Wired { client, id, uid: TargetUid::next() }
+	}
+
+	/// Returns if the controller is plugged in, never.
+	#[inline]
+	pub fn is_attached(&self) -> bool {
+		false
+	}
+
+	/// Returns the vendor and product ids.
+	#[inline]
+	pub fn id(&self) -> TargetId {
+		self.id
+	}
+
+	/// Returns the uid of this instance.
+	#[inline]
+	pub fn uid(&self) -> TargetUid {
+		self.uid
+	}
+
+	/// Returns the serial number, zero while not plugged in.
+	#[inline]
+	pub fn serial_no(&self) -> u32 {
+		0
+	}
+
+	/// Returns the client.
+	#[inline]
+	pub fn client(&self) -> &CL {
+		&self.client
+	}
+
+	/// Fails with [`Error::BusNotFound`].
+	#[inline]
+	pub fn plugin(&mut self) -> Result<(), Error> {
+		Err(Error::BusNotFound)
+	}
+
+	/// Fails with [`Error::NotPluggedIn`].
+	#[inline]
+	pub fn unplug(&mut self) -> Result<(), Error> {
+		Err(Error::NotPluggedIn)
+	}
+
+	/// Fails with [`Error::NotPluggedIn`].
+	#[inline]
+	pub fn wait_ready(&mut self) -> Result<(), Error> {
+		Err(Error::NotPluggedIn)
+	}
+
+	/// Has no effect, no report is ever submitted.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn set_validate_reports(&mut self, _validate: bool) {}
+
+	/// Fails with [`Error::NotPluggedIn`].
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn update(&mut self, _report: &DS4Report) -> Result<(), Error> {
+		Err(Error::NotPluggedIn)
+	}
+
+	/// Fails with [`Error::NotPluggedIn`].
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn update_ex(&mut self, _report: &DS4ReportEx) -> Result<(), Error> {
+		Err(Error::NotPluggedIn)
+	}
+
+	/// Fails with [`Error::NotPluggedIn`].
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn submit<R: Ds4Submittable>(&mut self, _report: &R) -> Result<(), Error> {
+		Err(Error::NotPluggedIn)
+	}
+
+	/// Fails with [`Error::NotPluggedIn`].
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn update_ex_raw(&mut self, _bytes: &[u8; std::mem::size_of::<DS4ReportEx>()]) -> Result<(), Error> {
+		Err(Error::NotPluggedIn)
+	}
+}
+
+impl XTargetTemplate {
+	/// Creates a new target from this template.
+	///
+	/// The target is not plugged in, only the ids are applied.
+	/// Fails if the template does not [validate](Self::validate).
+	#[inline]
+	pub fn instantiate<CL: Borrow<Client>>(&self, client: CL) -> Result<Xbox360Wired<CL>, Error> {
+		self.validate()?;
+		Ok(Xbox360Wired::new(client, self.id))
+	}
+}
+
+impl DS4TargetTemplate {
+	/// Creates a new target from this template.
+	///
+	/// The target is not plugged in, only the ids are applied.
+	/// Fails if the template does not [validate](Self::validate).
+	#[inline]
+	pub fn instantiate<CL: Borrow<Client>>(&self, client: CL) -> Result<DualShock4Wired<CL>, Error> {
+		self.validate()?;
+		Ok(DualShock4Wired::new(client, self.id))
+	}
+}
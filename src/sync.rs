@@ -0,0 +1,52 @@
+/*!
+Synchronization primitives of the internals which never call into the driver.
+
+Modules built only on these primitives are model checked with [loom](https://docs.rs/loom) under `cfg(loom)`,
+the primitives are swapped for loom's and the tests include the module sources directly, see `tests/loom.rs`.
+Keep driver requests and other FFI out of those modules.
+*/
+
+// Only the pure data modules are built on other platforms, most primitives go unused there
+#![cfg_attr(not(windows), allow(unused_imports))]
+
+#[cfg(not(loom))]
+pub(crate) use std::sync::{Condvar, Mutex, MutexGuard};
+#[cfg(not(loom))]
+pub(crate) use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(not(loom))]
+pub(crate) use std::thread_local;
+#[cfg(not(loom))]
+pub(crate) use std::hint::spin_loop;
+
+#[cfg(loom)]
+pub(crate) use loom::sync::{Condvar, Mutex, MutexGuard};
+#[cfg(loom)]
+pub(crate) use loom::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(loom)]
+pub(crate) use loom::cell::UnsafeCell;
+#[cfg(loom)]
+pub(crate) use loom::thread_local;
+#[cfg(loom)]
+pub(crate) use loom::hint::spin_loop;
+
+pub(crate) use std::sync::PoisonError;
+
+/// `UnsafeCell` with the access API of loom's, which tracks the accesses to detect data races.
+#[cfg(not(loom))]
+#[derive(Debug)]
+pub(crate) struct UnsafeCell<T>(std::cell::UnsafeCell<T>);
+#[cfg(not(loom))]
+impl<T> UnsafeCell<T> {
+	#[inline]
+	pub(crate) const fn new(data: T) -> UnsafeCell<T> {
+		UnsafeCell(std::cell::UnsafeCell::new(data))
+	}
+	#[inline]
+	pub(crate) fn with<R>(&self, f: impl FnOnce(*const T) -> R) -> R {
+		f(self.0.get())
+	}
+	#[inline]
+	pub(crate) fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+		f(self.0.get())
+	}
+}
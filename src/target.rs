@@ -0,0 +1,164 @@
+use std::fmt;
+use std::borrow::Borrow;
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::*;
+
+/// Process-unique identifier of a target, to correlate the logs of subsystems.
+///
+/// Assigned when the target is created and kept for its lifetime, across plugging in, unplugging and reannouncing.
+/// Unlike serial numbers, which the driver hands out again once a target is unplugged, uids are never reused within the process.
+///
+/// ```no_run
+/// let client = vigem_client::Client::connect().unwrap();
+/// let first = vigem_client::Xbox360Wired::new(&client, vigem_client::TargetId::XBOX360_WIRED);
+/// let second = vigem_client::Xbox360Wired::new(&client, vigem_client::TargetId::XBOX360_WIRED);
+/// assert!(first.uid() < second.uid());
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct TargetUid(u64);
+
+impl TargetUid {
+	// Monotonic, starting at one
+	#[inline]
+	pub(crate) fn next() -> TargetUid {
+		static NEXT: AtomicU64 = AtomicU64::new(1);
+		TargetUid(NEXT.fetch_add(1, Ordering::Relaxed))
+	}
+
+	/// Returns the uid as a number.
+	#[inline]
+	pub const fn get(self) -> u64 {
+		self.0
+	}
+}
+
+/// Formats the uid as `#` followed by the number, eg. `#3`.
+impl fmt::Display for TargetUid {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "#{}", self.0)
+	}
+}
+
+/// What updating a target which is not plugged in does.
+///
+/// See [`Xbox360Wired::set_detached_policy`] and [`DualShock4Wired::set_detached_policy`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DetachedPolicy {
+	/// Returns [`Error::NotPluggedIn`].
+	#[default]
+	Error,
+	/// Returns success without submitting the update, eg. when the target is intentionally disabled.
+	Ignore,
+	/// Plugs the target in and waits until it is ready before submitting the update.
+	///
+	/// If plugging in fails its error is returned and the update is not submitted, the next update tries again.
+	AutoPlugin,
+}
+
+/// Progress of plugging in a target, see [`Xbox360Wired::set_progress_observer`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum PlugStage {
+	/// Offering a serial number to the driver, the driver rejects serial numbers in use.
+	///
+	/// The attempt is the serial number offered, it starts at one and increments with every serial number tried.
+	/// [`Xbox360Wired::plugin_at`] offers only the requested serial number.
+	SerialScan { attempt: u32 },
+	/// The driver accepted the serial number, the target is plugged in.
+	DriverAccepted { serial: u32 },
+	/// Waiting for the target to be ready to accept updates.
+	WaitingReady,
+	/// The target is ready to accept updates.
+	Ready,
+	/// The target was unplugged and its device removed, see [`Xbox360Wired::reannounce`].
+	Removed { serial: u32 },
+}
+
+/// Observes the progress of plugging in a target.
+pub type ProgressObserver = Box<dyn Fn(PlugStage) + Send + Sync>;
+
+/// Common interface of the virtual controller targets.
+///
+/// Allows managing targets of different kinds, see [`TargetSet`].
+pub trait VirtualTarget {
+	/// Returns the vendor and product ids.
+	fn id(&self) -> TargetId;
+	/// Returns if the controller is plugged in.
+	fn is_attached(&self) -> bool;
+	/// Plugs the controller in.
+	fn plugin(&mut self) -> Result<(), Error>;
+	/// Unplugs the controller.
+	fn unplug(&mut self) -> Result<(), Error>;
+	/// Waits until the virtual controller is ready.
+	fn wait_ready(&mut self) -> Result<(), Error>;
+}
+
+impl<CL: Borrow<Client>> VirtualTarget for Xbox360Wired<CL> {
+	#[inline]
+	fn id(&self) -> TargetId {
+		self.id()
+	}
+	#[inline]
+	fn is_attached(&self) -> bool {
+		self.is_attached()
+	}
+	#[inline]
+	fn plugin(&mut self) -> Result<(), Error> {
+		self.plugin()
+	}
+	#[inline]
+	fn unplug(&mut self) -> Result<(), Error> {
+		self.unplug()
+	}
+	#[inline]
+	fn wait_ready(&mut self) -> Result<(), Error> {
+		self.wait_ready()
+	}
+}
+
+impl<CL: Borrow<Client>> VirtualTarget for DualShock4Wired<CL> {
+	#[inline]
+	fn id(&self) -> TargetId {
+		self.id()
+	}
+	#[inline]
+	fn is_attached(&self) -> bool {
+		self.is_attached()
+	}
+	#[inline]
+	fn plugin(&mut self) -> Result<(), Error> {
+		self.plugin()
+	}
+	#[inline]
+	fn unplug(&mut self) -> Result<(), Error> {
+		self.unplug()
+	}
+	#[inline]
+	fn wait_ready(&mut self) -> Result<(), Error> {
+		self.wait_ready()
+	}
+}
+
+impl<T: VirtualTarget + ?Sized> VirtualTarget for Box<T> {
+	#[inline]
+	fn id(&self) -> TargetId {
+		(**self).id()
+	}
+	#[inline]
+	fn is_attached(&self) -> bool {
+		(**self).is_attached()
+	}
+	#[inline]
+	fn plugin(&mut self) -> Result<(), Error> {
+		(**self).plugin()
+	}
+	#[inline]
+	fn unplug(&mut self) -> Result<(), Error> {
+		(**self).unplug()
+	}
+	#[inline]
+	fn wait_ready(&mut self) -> Result<(), Error> {
+		(**self).wait_ready()
+	}
+}
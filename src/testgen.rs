@@ -0,0 +1,167 @@
+/*!
+Deterministic input generators for examples and tests.
+
+The generators are pure functions of the time since the start of the waveform,
+every frame can be reproduced exactly from its timestamp:
+
+```
+use std::time::Duration;
+use vigem_client::testgen::Waveform;
+
+let circle = Waveform::circle(1.0, Duration::from_secs(4));
+let ramp = Waveform::ramp_trigger(Duration::from_secs(2));
+
+// A quarter turn counter-clockwise points the left stick straight up
+let mut gamepad = circle.gamepad(Duration::from_secs(1));
+assert_eq!((gamepad.thumb_lx, gamepad.thumb_ly), (0, 32767));
+
+// Waveforms combine by applying them to the same frame
+ramp.apply(Duration::from_secs(1), &mut gamepad);
+assert_eq!(gamepad.left_trigger, 128);
+```
+*/
+
+use std::f64::consts::PI;
+use std::time::Duration;
+use crate::*;
+
+/// Periodic input waveform.
+///
+/// Each waveform drives only its own inputs, see [`apply`](Self::apply).
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Waveform {
+	/// Spins the left thumb stick counter-clockwise starting from the right.
+	Circle { radius: f32, period: Duration },
+	/// Holds the buttons during the first half of every period.
+	SquareButtons { mask: u16, period: Duration },
+	/// Ramps both triggers from released to fully pressed every period.
+	RampTrigger { period: Duration },
+}
+
+impl Waveform {
+	/// Spins the left thumb stick in a circle, the radius ranges from `0.0` to `1.0`.
+	#[inline]
+	pub const fn circle(radius: f32, period: Duration) -> Waveform {
+		Waveform::Circle { radius, period }
+	}
+
+	/// Holds the buttons during the first half of every period.
+	///
+	/// The mask holds [`XButtons`] flags or, applied to a `DS4Report`, its button flags above the dpad nibble.
+	#[inline]
+	pub const fn square_buttons(mask: u16, period: Duration) -> Waveform {
+		Waveform::SquareButtons { mask, period }
+	}
+
+	/// Ramps both triggers from `0` to `255` every period.
+	#[inline]
+	pub const fn ramp_trigger(period: Duration) -> Waveform {
+		Waveform::RampTrigger { period }
+	}
+
+	/// Returns the period of the waveform.
+	#[inline]
+	pub const fn period(&self) -> Duration {
+		match *self {
+			Waveform::Circle { period, .. } => period,
+			Waveform::SquareButtons { period, .. } => period,
+			Waveform::RampTrigger { period } => period,
+		}
+	}
+
+	/// Returns the phase within the current period, from `0.0` up to but excluding `1.0`.
+	///
+	/// A zero period is always at phase `0.0`.
+	#[inline]
+	pub fn phase(&self, t: Duration) -> f64 {
+		let period = self.period().as_nanos();
+		if period == 0 {
+			return 0.0;
+		}
+		(t.as_nanos() % period) as f64 / period as f64
+	}
+
+	/// Applies the waveform at time `t` to the gamepad, leaving the other inputs untouched.
+	pub fn apply(&self, t: Duration, gamepad: &mut XGamepad) {
+		let phase = self.phase(t);
+		match *self {
+			Waveform::Circle { radius, .. } => {
+				let (x, y) = circle(radius, phase);
+				gamepad.thumb_lx = (x * 32767.0).round() as i16;
+				gamepad.thumb_ly = (y * 32767.0).round() as i16;
+			},
+			Waveform::SquareButtons { mask, .. } => {
+				if phase < 0.5 {
+					gamepad.buttons.raw |= mask;
+				}
+				else {
+					gamepad.buttons.raw &= !mask;
+				}
+			},
+			Waveform::RampTrigger { .. } => {
+				let value = ramp(phase);
+				gamepad.left_trigger = value;
+				gamepad.right_trigger = value;
+			},
+		}
+	}
+
+	/// Returns the neutral gamepad with the waveform at time `t` applied.
+	#[inline]
+	pub fn gamepad(&self, t: Duration) -> XGamepad {
+		let mut gamepad = XGamepad::default();
+		self.apply(t, &mut gamepad);
+		gamepad
+	}
+
+	/// Applies the waveform at time `t` to the report, leaving the other inputs untouched.
+	///
+	/// The thumb sticks are centered at `0x80` with the y axis pointing down.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	pub fn apply_ds4(&self, t: Duration, report: &mut DS4Report) {
+		let phase = self.phase(t);
+		match *self {
+			Waveform::Circle { radius, .. } => {
+				let (x, y) = circle(radius, phase);
+				report.thumb_lx = (128.0 + (x * 127.0).round()) as u8;
+				report.thumb_ly = (128.0 - (y * 127.0).round()) as u8;
+			},
+			Waveform::SquareButtons { mask, .. } => {
+				let mask = mask & !0xF;
+				if phase < 0.5 {
+					report.buttons |= mask;
+				}
+				else {
+					report.buttons &= !mask;
+				}
+			},
+			Waveform::RampTrigger { .. } => {
+				let value = ramp(phase);
+				report.trigger_l = value;
+				report.trigger_r = value;
+			},
+		}
+	}
+
+	/// Returns the neutral report with the waveform at time `t` applied.
+	#[cfg(feature = "unstable_ds4")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+	#[inline]
+	pub fn ds4_report(&self, t: Duration) -> DS4Report {
+		let mut report = DS4Report::neutral();
+		self.apply_ds4(t, &mut report);
+		report
+	}
+}
+
+fn circle(radius: f32, phase: f64) -> (f64, f64) {
+	let radius = (radius as f64).clamp(0.0, 1.0);
+	let angle = phase * 2.0 * PI;
+	(angle.cos() * radius, angle.sin() * radius)
+}
+
+fn ramp(phase: f64) -> u8 {
+	(phase * 256.0).min(255.0) as u8
+}
@@ -0,0 +1,127 @@
+use crate::*;
+
+/// How thumbstick input moves the touch contact.
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TouchpadMode {
+	/// The stick position maps onto the touchpad around its center.
+	///
+	/// With a scale of `1.0` full deflection reaches the edges of the touchpad.
+	Absolute { scale: f32 },
+	/// The stick moves the contact like a mouse, in touchpad units per frame at full deflection.
+	///
+	/// The position is kept between presses.
+	Relative { sensitivity: f32 },
+}
+
+/// Maps thumbstick input onto DualShock4 touchpad contacts.
+///
+/// Feed the stick input as `-1.0..=1.0` with positive y pointing down, then take one touch packet per frame.
+/// Every press gets a new contact id and all packets are stamped with a [`DS4TouchTimestamper`].
+///
+/// ```
+/// use vigem_client::{TouchpadMapper, TouchpadMode};
+///
+/// let mut mapper = TouchpadMapper::new(TouchpadMode::Absolute { scale: 1.0 });
+/// assert_eq!(mapper.frame(), None);
+///
+/// mapper.feed(-1.0, -1.0, true);
+/// let report = mapper.frame().unwrap();
+/// let point = report.point1().unwrap();
+/// assert_eq!((point.x, point.y), (0, 0));
+///
+/// // Releasing sends a single packet lifting the contact
+/// mapper.feed(0.0, 0.0, false);
+/// assert_eq!(mapper.frame().unwrap().point1(), None);
+/// assert_eq!(mapper.frame(), None);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable_ds4")))]
+#[derive(Clone, Debug)]
+pub struct TouchpadMapper {
+	mode: TouchpadMode,
+	timestamper: DS4TouchTimestamper,
+	x: f32,
+	y: f32,
+	id: u8,
+	pressed: bool,
+	lifted: bool,
+}
+
+impl TouchpadMapper {
+	/// Creates a mapper, the contact starts in the center of the touchpad.
+	#[inline]
+	pub fn new(mode: TouchpadMode) -> TouchpadMapper {
+		TouchpadMapper {
+			mode,
+			timestamper: DS4TouchTimestamper::new(0),
+			x: CENTER_X,
+			y: CENTER_Y,
+			id: 0x7f,
+			pressed: false,
+			lifted: false,
+		}
+	}
+
+	/// Returns the mapping mode.
+	#[inline]
+	pub fn mode(&self) -> TouchpadMode {
+		self.mode
+	}
+
+	/// Feeds the stick input for the next frame.
+	///
+	/// In absolute mode `dx_or_x` and `dy_or_y` are the stick position, in relative mode they are its velocity.
+	/// NaN input is treated as zero.
+	pub fn feed(&mut self, dx_or_x: f32, dy_or_y: f32, pressed: bool) {
+		let dx_or_x = if dx_or_x.is_nan() { 0.0 } else { dx_or_x.clamp(-1.0, 1.0) };
+		let dy_or_y = if dy_or_y.is_nan() { 0.0 } else { dy_or_y.clamp(-1.0, 1.0) };
+		match self.mode {
+			TouchpadMode::Absolute { scale } => {
+				self.x = CENTER_X * (1.0 + dx_or_x * scale);
+				self.y = CENTER_Y * (1.0 + dy_or_y * scale);
+			},
+			TouchpadMode::Relative { sensitivity } => {
+				self.x += dx_or_x * sensitivity;
+				self.y += dy_or_y * sensitivity;
+			},
+		}
+		self.x = if self.x.is_nan() { CENTER_X } else { self.x.clamp(0.0, MAX_X) };
+		self.y = if self.y.is_nan() { CENTER_Y } else { self.y.clamp(0.0, MAX_Y) };
+
+		if pressed && !self.pressed {
+			self.id = (self.id + 1) & 0x7f;
+		}
+		self.lifted = self.pressed && !pressed;
+		self.pressed = pressed;
+	}
+
+	/// Returns the touch packet for this frame.
+	///
+	/// Returns `None` when there is nothing to report, ie. the touchpad was not touched and no contact was just lifted.
+	pub fn frame(&mut self) -> Option<DS4TouchReport> {
+		let point = DS4TouchPoint { id: self.id, x: self.x.round() as u16, y: self.y.round() as u16 };
+		if self.pressed {
+			Some(DS4TouchReport::new(self.timestamper.next(), Some(point), None))
+		}
+		else if self.lifted {
+			self.lifted = false;
+			let mut report = DS4TouchReport::new(self.timestamper.next(), Some(point), None);
+			report.is_up_tracking_num1 |= 0x80;
+			Some(report)
+		}
+		else {
+			None
+		}
+	}
+
+	/// Returns the timestamper of the touch packets.
+	#[inline]
+	pub fn timestamper(&self) -> &DS4TouchTimestamper {
+		&self.timestamper
+	}
+}
+
+const CENTER_X: f32 = (DS4TouchReport::WIDTH / 2) as f32;
+const CENTER_Y: f32 = (DS4TouchReport::HEIGHT / 2) as f32;
+const MAX_X: f32 = (DS4TouchReport::WIDTH - 1) as f32;
+const MAX_Y: f32 = (DS4TouchReport::HEIGHT - 1) as f32;
@@ -0,0 +1,83 @@
+use crate::*;
+
+/// An analog trigger paired with its digital state.
+///
+/// Xbox games consider a trigger pressed past [`consts::TRIGGER_THRESHOLD`],
+/// the DualShock4 reports a separate L2/R2 button next to the analog value.
+/// Converting from this type keeps the two in sync: the digital state is pressed exactly when the analog value is past the threshold,
+/// a digital override raises the analog value just past the threshold if needed.
+///
+/// ```
+/// use vigem_client::Trigger;
+///
+/// assert_eq!(Trigger::analog(0.5).to_xbox(), 128);
+/// assert_eq!(Trigger::analog(0.1).to_ds4(), (26, false));
+/// assert_eq!(Trigger::analog(0.1).with_digital(true).to_ds4(), (31, true));
+/// assert_eq!(Trigger::digital().to_ds4(), (255, true));
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Trigger {
+	value: f32,
+	digital: bool,
+}
+
+impl Trigger {
+	/// The released trigger.
+	#[inline]
+	pub const fn off() -> Trigger {
+		Trigger { value: 0.0, digital: false }
+	}
+
+	/// The fully pulled trigger, for triggers which are buttons at the source.
+	#[inline]
+	pub const fn digital() -> Trigger {
+		Trigger { value: 1.0, digital: true }
+	}
+
+	/// The trigger pulled by the normalized value, clamped to `0.0..=1.0`.
+	///
+	/// NaN is treated as released.
+	#[inline]
+	pub fn analog(value: f32) -> Trigger {
+		let value = if value.is_nan() { 0.0 } else { value.clamp(0.0, 1.0) };
+		Trigger { value, digital: false }
+	}
+
+	/// Returns the trigger with the digital override, pressed regardless of the analog value.
+	#[inline]
+	pub const fn with_digital(self, digital: bool) -> Trigger {
+		Trigger { digital, ..self }
+	}
+
+	/// Returns the normalized analog value.
+	#[inline]
+	pub const fn value(&self) -> f32 {
+		self.value
+	}
+
+	/// Returns if the digital override is set.
+	#[inline]
+	pub const fn is_digital(&self) -> bool {
+		self.digital
+	}
+
+	/// Returns if the trigger is pressed, either past the threshold or by the digital override.
+	#[inline]
+	pub fn is_pressed(&self) -> bool {
+		self.to_xbox() > consts::TRIGGER_THRESHOLD
+	}
+
+	/// Returns the value of an Xbox trigger, see [`XGamepad::left_trigger`].
+	#[inline]
+	pub fn to_xbox(&self) -> u8 {
+		let value = (self.value * u8::MAX as f32).round() as u8;
+		if self.digital { value.max(consts::TRIGGER_THRESHOLD + 1) } else { value }
+	}
+
+	/// Returns the value of a DualShock4 trigger and the state of its L2/R2 button.
+	#[inline]
+	pub fn to_ds4(&self) -> (u8, bool) {
+		let value = self.to_xbox();
+		(value, value > consts::TRIGGER_THRESHOLD)
+	}
+}
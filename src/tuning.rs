@@ -0,0 +1,123 @@
+use std::{fmt, iter, marker, ptr};
+use winapi::shared::minwindef::DWORD;
+use winapi::um::{avrt, errhandlingapi, processthreadsapi, winbase};
+use winapi::um::winnt::HANDLE;
+use crate::Error;
+
+/// Scheduling hints for threads submitting reports under latency constraints.
+///
+/// Registers the thread with the Multimedia Class Scheduler Service (MMCSS) and falls back to raising the thread priority when it is unavailable.
+/// Applied to the threads spawned by the crate through [`XRequestNotification::with_tuning`](crate::XRequestNotification::with_tuning) and
+/// [`DS4RequestNotification::with_tuning`](crate::DS4RequestNotification::with_tuning), and to your own threads through [`apply_to_current_thread`](Self::apply_to_current_thread).
+///
+/// ```no_run
+/// let _tuning = vigem_client::ThreadTuning::games().apply_to_current_thread().unwrap();
+/// // Submit reports from this thread...
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ThreadTuning {
+	task: Option<&'static str>,
+	priority: i32,
+}
+
+impl ThreadTuning {
+	/// Registers with the MMCSS `Games` task, falling back to the highest normal thread priority.
+	#[inline]
+	pub const fn games() -> ThreadTuning {
+		ThreadTuning { task: Some("Games"), priority: winbase::THREAD_PRIORITY_HIGHEST as i32 }
+	}
+
+	/// Registers with the MMCSS `Pro Audio` task, falling back to the time critical thread priority.
+	#[inline]
+	pub const fn pro_audio() -> ThreadTuning {
+		ThreadTuning { task: Some("Pro Audio"), priority: winbase::THREAD_PRIORITY_TIME_CRITICAL as i32 }
+	}
+
+	/// Only sets the thread priority, eg. `THREAD_PRIORITY_ABOVE_NORMAL`.
+	#[inline]
+	pub const fn priority(priority: i32) -> ThreadTuning {
+		ThreadTuning { task: None, priority }
+	}
+
+	/// Returns the MMCSS task registered with.
+	#[inline]
+	pub const fn task(&self) -> Option<&'static str> {
+		self.task
+	}
+
+	/// Returns the thread priority set when not registered with MMCSS.
+	#[inline]
+	pub const fn fallback_priority(&self) -> i32 {
+		self.priority
+	}
+
+	/// Applies the hints to the calling thread.
+	///
+	/// The hints are reverted when the returned guard is dropped, which must happen on the same thread.
+	#[inline(never)]
+	pub fn apply_to_current_thread(&self) -> Result<TuningGuard, Error> {
+		unsafe {
+			let thread = processthreadsapi::GetCurrentThread();
+			let original = processthreadsapi::GetThreadPriority(thread);
+			if original as DWORD == winbase::THREAD_PRIORITY_ERROR_RETURN {
+				return Err(Error::WinError(errhandlingapi::GetLastError()));
+			}
+			if let Some(task) = self.task {
+				let task: Vec<u16> = task.encode_utf16().chain(iter::once(0)).collect();
+				let mut index = 0;
+				let mmcss = avrt::AvSetMmThreadCharacteristicsW(task.as_ptr(), &mut index);
+				if !mmcss.is_null() {
+					return Ok(TuningGuard { mmcss, original, _thread: marker::PhantomData });
+				}
+			}
+			if processthreadsapi::SetThreadPriority(thread, self.priority) == 0 {
+				return Err(Error::WinError(errhandlingapi::GetLastError()));
+			}
+			Ok(TuningGuard { mmcss: ptr::null_mut(), original, _thread: marker::PhantomData })
+		}
+	}
+}
+
+/// Reverts the [`ThreadTuning`] of the thread when dropped.
+///
+/// Bound to the thread it was applied to and therefore not `Send`.
+pub struct TuningGuard {
+	mmcss: HANDLE,
+	original: i32,
+	_thread: marker::PhantomData<*const ()>,
+}
+
+impl TuningGuard {
+	/// Returns if the thread was registered with MMCSS rather than only raised in priority.
+	#[inline]
+	pub fn is_mmcss(&self) -> bool {
+		!self.mmcss.is_null()
+	}
+
+	/// Returns the priority of the thread before the hints were applied, restored when dropped.
+	#[inline]
+	pub fn original_priority(&self) -> i32 {
+		self.original
+	}
+}
+
+impl Drop for TuningGuard {
+	#[inline(never)]
+	fn drop(&mut self) {
+		unsafe {
+			if !self.mmcss.is_null() {
+				avrt::AvRevertMmThreadCharacteristics(self.mmcss);
+			}
+			processthreadsapi::SetThreadPriority(processthreadsapi::GetCurrentThread(), self.original);
+		}
+	}
+}
+
+impl fmt::Debug for TuningGuard {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("TuningGuard")
+			.field("mmcss", &self.is_mmcss())
+			.field("original_priority", &self.original)
+			.finish()
+	}
+}
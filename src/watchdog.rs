@@ -0,0 +1,308 @@
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, Once, PoisonError, Weak};
+use std::{thread, time};
+use crate::*;
+
+/// The neutral report the watchdog submits.
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum Neutral {
+	Xbox360Wired,
+	#[cfg(feature = "unstable_ds4")]
+	DualShock4Wired,
+}
+
+#[derive(Debug)]
+struct State {
+	serial_no: u32,
+	timeout: time::Duration,
+	last_update: time::Instant,
+	neutralized: bool,
+	// A neutral report is being submitted without holding the lock
+	submitting: bool,
+}
+
+/// What the watchdog does about a watch.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Check {
+	/// Unplugged or already neutralized, nothing to wait for until it is updated.
+	Idle,
+	/// Checks again at the deadline.
+	Wait(time::Instant),
+	/// Went stale, submits the neutral report to the serial number.
+	Neutralize(u32),
+}
+
+impl State {
+	fn new(serial_no: u32, timeout: time::Duration, now: time::Instant) -> State {
+		State { serial_no, timeout, last_update: now, neutralized: false, submitting: false }
+	}
+
+	fn set_serial_no(&mut self, serial_no: u32, now: time::Instant) {
+		self.serial_no = serial_no;
+		self.last_update = now;
+		self.neutralized = false;
+	}
+
+	fn updated(&mut self, now: time::Instant) {
+		self.last_update = now;
+		self.neutralized = false;
+	}
+
+	// Marks the watch neutralized when it went stale, the neutral report is submitted once per stale period
+	fn check(&mut self, now: time::Instant) -> Check {
+		if self.serial_no == 0 || self.neutralized {
+			return Check::Idle;
+		}
+		let deadline = self.last_update + self.timeout;
+		if now < deadline {
+			return Check::Wait(deadline);
+		}
+		self.neutralized = true;
+		Check::Neutralize(self.serial_no)
+	}
+}
+
+/// Watches a target for stale input.
+///
+/// All background watches are serviced by a single background thread which submits the neutral report
+/// to targets which haven't been updated within their timeout.
+/// Manual watches are only serviced when the target is pumped.
+///
+/// The neutral report is submitted without holding any locks, a slow driver does not hold up other targets.
+/// Changing the serial number waits for a neutral report in flight, an update racing it may be overwritten until the next update.
+#[derive(Debug)]
+pub(crate) struct Watch {
+	client: Client,
+	neutral: Neutral,
+	background: bool,
+	state: Mutex<State>,
+	submitted: Condvar,
+}
+
+struct Registry {
+	watches: Vec<Weak<Watch>>,
+	// Woken while the watches were being checked
+	woken: bool,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry { watches: Vec::new(), woken: false });
+static WAKEUP: Condvar = Condvar::new();
+static SPAWN: Once = Once::new();
+
+fn wake() {
+	lock(&REGISTRY).woken = true;
+	WAKEUP.notify_one();
+}
+
+impl Watch {
+	pub fn new(client: &Client, neutral: Neutral, serial_no: u32, timeout: time::Duration, background: bool) -> Result<Arc<Watch>, Error> {
+		let client = client.try_clone()?;
+		let state = Mutex::new(State::new(serial_no, timeout, time::Instant::now()));
+		let watch = Arc::new(Watch { client, neutral, background, state, submitted: Condvar::new() });
+
+		if background {
+			SPAWN.call_once(|| { thread::spawn(run); });
+			lock(&REGISTRY).watches.push(Arc::downgrade(&watch));
+			wake();
+		}
+
+		Ok(watch)
+	}
+
+	fn lock(&self) -> MutexGuard<'_, State> {
+		lock(&self.state)
+	}
+
+	pub fn is_background(&self) -> bool {
+		self.background
+	}
+
+	pub fn timeout(&self) -> time::Duration {
+		self.lock().timeout
+	}
+
+	pub fn set_timeout(&self, timeout: time::Duration) {
+		self.lock().timeout = timeout;
+		wake();
+	}
+
+	/// Sets the serial number of the watched target, zero when it is unplugged.
+	///
+	/// Once this returns the watchdog no longer submits reports for the previous serial number.
+	pub fn set_serial_no(&self, serial_no: u32) {
+		let mut state = self.lock();
+		while state.submitting {
+			state = self.submitted.wait(state).unwrap_or_else(PoisonError::into_inner);
+		}
+		state.set_serial_no(serial_no, time::Instant::now());
+		drop(state);
+		wake();
+	}
+
+	/// Submits a report while holding off the watchdog, the update counts if it succeeds.
+	pub fn update<T, F: FnOnce() -> Result<T, Error>>(&self, submit: F) -> Result<T, Error> {
+		let mut state = self.lock();
+		let result = submit();
+		if result.is_ok() {
+			state.updated(time::Instant::now());
+		}
+		result
+	}
+
+	pub fn is_stale(&self) -> bool {
+		self.lock().neutralized
+	}
+
+	/// Neutralizes the target if it went stale, else returns when it will go stale.
+	pub fn check(&self, now: time::Instant) -> Option<time::Instant> {
+		let mut state = self.lock();
+		let serial_no = match state.check(now) {
+			Check::Idle => return None,
+			Check::Wait(deadline) => return Some(deadline),
+			Check::Neutralize(serial_no) => serial_no,
+		};
+		state.submitting = true;
+		drop(state);
+
+		{
+			let _pass = self.client.enter();
+			let device = self.client.device;
+			// Waits on the shared event of the thread servicing the watch
+			if let Ok(event) = Event::null().sync() {
+				let _ = unsafe {
+					match self.neutral {
+						Neutral::Xbox360Wired => bus::XUsbSubmitReport::new(serial_no, XGamepad::neutral()).ioctl(device, event.handle),
+						#[cfg(feature = "unstable_ds4")]
+						Neutral::DualShock4Wired => bus::DS4SubmitReport::new(serial_no, DS4Report::neutral()).ioctl(device, event.handle),
+					}
+				};
+			}
+		}
+
+		self.lock().submitting = false;
+		self.submitted.notify_all();
+		None
+	}
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+	mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn run() {
+	let mut registry = lock(&REGISTRY);
+	loop {
+		registry.woken = false;
+		let mut watches = Vec::with_capacity(registry.watches.len());
+		registry.watches.retain(|weak| match weak.upgrade() {
+			Some(watch) => {
+				watches.push(watch);
+				true
+			},
+			None => false,
+		});
+		drop(registry);
+
+		// Checks the watches without holding the registry, creating watches is not held up by the driver
+		let now = time::Instant::now();
+		let next = watches.iter().filter_map(|watch| watch.check(now)).min();
+		drop(watches);
+
+		registry = lock(&REGISTRY);
+		if registry.woken {
+			continue;
+		}
+		registry = match next {
+			Some(deadline) => WAKEUP.wait_timeout(registry, deadline.saturating_duration_since(time::Instant::now())).unwrap_or_else(PoisonError::into_inner).0,
+			None => WAKEUP.wait(registry).unwrap_or_else(PoisonError::into_inner),
+		};
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TIMEOUT: time::Duration = time::Duration::from_millis(100);
+
+	fn ms(ms: u64) -> time::Duration {
+		time::Duration::from_millis(ms)
+	}
+
+	#[test]
+	fn fresh() {
+		let t0 = time::Instant::now();
+		let mut state = State::new(1, TIMEOUT, t0);
+		assert_eq!(state.check(t0), Check::Wait(t0 + TIMEOUT));
+		assert_eq!(state.check(t0 + ms(99)), Check::Wait(t0 + TIMEOUT));
+		assert!(!state.neutralized);
+	}
+
+	#[test]
+	fn once_per_stale_period() {
+		let t0 = time::Instant::now();
+		let mut state = State::new(1, TIMEOUT, t0);
+		assert_eq!(state.check(t0 + TIMEOUT), Check::Neutralize(1));
+		assert!(state.neutralized);
+		// Stays neutralized without submitting again, however long it stays stale
+		for i in 1..10 {
+			assert_eq!(state.check(t0 + TIMEOUT + ms(i * 100)), Check::Idle);
+		}
+		assert!(state.neutralized);
+	}
+
+	#[test]
+	fn update_clears() {
+		let t0 = time::Instant::now();
+		let mut state = State::new(1, TIMEOUT, t0);
+		assert_eq!(state.check(t0 + ms(150)), Check::Neutralize(1));
+
+		// The next update clears the condition and starts a new stale period
+		let t1 = t0 + ms(200);
+		state.updated(t1);
+		assert!(!state.neutralized);
+		assert_eq!(state.check(t1 + ms(50)), Check::Wait(t1 + TIMEOUT));
+		assert_eq!(state.check(t1 + TIMEOUT), Check::Neutralize(1));
+		assert_eq!(state.check(t1 + ms(500)), Check::Idle);
+	}
+
+	#[test]
+	fn updates_keep_fresh() {
+		let t0 = time::Instant::now();
+		let mut state = State::new(1, TIMEOUT, t0);
+		for i in 1..=20 {
+			let now = t0 + ms(i * 60);
+			assert_eq!(state.check(now), Check::Wait(now - ms(60) + TIMEOUT));
+			state.updated(now);
+		}
+		assert!(!state.neutralized);
+	}
+
+	#[test]
+	fn unplugged() {
+		let t0 = time::Instant::now();
+		let mut state = State::new(0, TIMEOUT, t0);
+		assert_eq!(state.check(t0 + ms(1000)), Check::Idle);
+
+		let t1 = t0 + ms(2000);
+		state.set_serial_no(3, t1);
+		assert_eq!(state.check(t1), Check::Wait(t1 + TIMEOUT));
+		assert_eq!(state.check(t1 + TIMEOUT), Check::Neutralize(3));
+
+		// Unplugging clears the condition, plugging in again starts fresh
+		state.set_serial_no(0, t1 + ms(150));
+		assert!(!state.neutralized);
+		assert_eq!(state.check(t1 + ms(1000)), Check::Idle);
+		let t2 = t1 + ms(1100);
+		state.set_serial_no(4, t2);
+		assert_eq!(state.check(t2 + TIMEOUT), Check::Neutralize(4));
+	}
+
+	#[test]
+	fn timeout_changed() {
+		let t0 = time::Instant::now();
+		let mut state = State::new(1, TIMEOUT, t0);
+		state.timeout = ms(20);
+		assert_eq!(state.check(t0 + ms(10)), Check::Wait(t0 + ms(20)));
+		assert_eq!(state.check(t0 + ms(20)), Check::Neutralize(1));
+	}
+}
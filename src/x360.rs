@@ -1,13 +1,26 @@
-use std::{fmt, mem, ptr};
-#[cfg(feature = "unstable_xtarget_notification")]
-use std::{marker, pin, thread};
+use std::{fmt, mem, time};
+#[cfg(windows)]
+use std::{ffi, ptr, thread};
+#[cfg(windows)]
+use std::sync::Arc;
+#[cfg(all(windows, feature = "xusb-rumble"))]
+use std::{marker, pin};
+#[cfg(all(windows, feature = "xusb-rumble"))]
+use std::sync::Weak;
+#[cfg(all(windows, feature = "xusb-rumble"))]
+use std::collections::VecDeque;
+#[cfg(windows)]
 use std::borrow::Borrow;
-use winapi::um::xinput::XINPUT_GAMEPAD;
+use std::convert::TryFrom;
+#[cfg(windows)]
+use winapi::um::xinput::{self, XINPUT_GAMEPAD};
+#[cfg(windows)]
 use winapi::shared::winerror;
 use crate::*;
 
 /// XInput compatible button flags.
 #[derive(Copy, Clone, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(transparent)]
 pub struct XButtons {
 	pub raw: u16,
@@ -26,8 +39,24 @@ pub const fn XButtons(raw: u16) -> XButtons {
 /// let buttons = vigem_client::XButtons!(UP|RIGHT|LB|A|X);
 /// assert_eq!(buttons, vigem_client::XButtons(0x5109));
 /// ```
+///
+/// An empty invocation produces no buttons pressed.
+/// A runtime `u16` expression can be ORed into the named flags after a `;`.
+/// The expression is evaluated as a whole before it is combined, so `XButtons!(A; x & y)` is `A | (x & y)`.
+/// Without the runtime part the result remains usable in const contexts.
+///
+/// ```
+/// let extra = vigem_client::XButtons::B;
+/// let buttons = vigem_client::XButtons!(A|X; extra);
+/// assert_eq!(buttons, vigem_client::XButtons(0x5000 | 0x2000));
+///
+/// const NONE: vigem_client::XButtons = vigem_client::XButtons!();
+/// assert_eq!(NONE.raw, 0);
+/// ```
 #[macro_export]
 macro_rules! XButtons {
+	() => { $crate::XButtons { raw: 0 } };
+
 	(UP) => { $crate::XButtons { raw: $crate::XButtons::UP } };
 	(DOWN) => { $crate::XButtons { raw: $crate::XButtons::DOWN } };
 	(LEFT) => { $crate::XButtons { raw: $crate::XButtons::LEFT } };
@@ -44,12 +73,18 @@ macro_rules! XButtons {
 	(X) => { $crate::XButtons { raw: $crate::XButtons::X } };
 	(Y) => { $crate::XButtons { raw: $crate::XButtons::Y } };
 
+	($($face:ident)|* ; $raw:expr) => {
+		$crate::XButtons { raw: $crate::XButtons!($($face)|*).raw | ($raw) }
+	};
 	($($face:ident)|*) => {
 		$crate::XButtons { raw: 0 $(| $crate::XButtons!($face).raw)* }
 	};
 }
 
 impl XButtons {
+	/// Fingerprint of the byte layout, see [`WIRE_FORMAT_VERSION`](crate::WIRE_FORMAT_VERSION).
+	pub const LAYOUT_FINGERPRINT: u64 = crate::layout_fingerprint!(XButtons { raw });
+
 	/// Dpad up button.
 	pub const UP: u16     = 0x0001;
 	/// Dpad down button.
@@ -80,6 +115,54 @@ impl XButtons {
 	pub const X: u16      = 0x4000;
 	/// Y button.
 	pub const Y: u16      = 0x8000;
+
+	/// Rotates the dpad buttons clockwise by the given number of quarter turns.
+	///
+	/// Other buttons are not affected.
+	///
+	/// ```
+	/// use vigem_client::XButtons;
+	/// assert_eq!(XButtons!(UP | RIGHT | A).rotate_dpad_cw(1), XButtons!(RIGHT | DOWN | A));
+	/// ```
+	#[inline]
+	pub const fn rotate_dpad_cw(self, quarter_turns: u8) -> XButtons {
+		// Dpad buttons in clockwise order
+		const CW: [u16; 4] = [XButtons::UP, XButtons::RIGHT, XButtons::DOWN, XButtons::LEFT];
+		let mut raw = self.raw & !(XButtons::UP | XButtons::RIGHT | XButtons::DOWN | XButtons::LEFT);
+		let mut i = 0;
+		while i < 4 {
+			if self.raw & CW[i] != 0 {
+				raw |= CW[(i + quarter_turns as usize % 4) % 4];
+			}
+			i += 1;
+		}
+		XButtons { raw }
+	}
+
+	/// Mirrors the dpad buttons.
+	///
+	/// Mirroring horizontally swaps left and right, mirroring vertically swaps up and down.
+	/// Other buttons are not affected.
+	///
+	/// ```
+	/// use vigem_client::XButtons;
+	/// assert_eq!(XButtons!(UP | RIGHT | A).mirror_dpad(true, false), XButtons!(UP | LEFT | A));
+	/// ```
+	#[inline]
+	pub const fn mirror_dpad(self, horizontal: bool, vertical: bool) -> XButtons {
+		let mut raw = self.raw;
+		if horizontal {
+			raw = raw & !(XButtons::LEFT | XButtons::RIGHT)
+				| if raw & XButtons::LEFT != 0 { XButtons::RIGHT } else { 0 }
+				| if raw & XButtons::RIGHT != 0 { XButtons::LEFT } else { 0 };
+		}
+		if vertical {
+			raw = raw & !(XButtons::UP | XButtons::DOWN)
+				| if raw & XButtons::UP != 0 { XButtons::DOWN } else { 0 }
+				| if raw & XButtons::DOWN != 0 { XButtons::UP } else { 0 };
+		}
+		XButtons { raw }
+	}
 }
 
 impl From<u16> for XButtons {
@@ -107,23 +190,24 @@ impl AsMut<u16> for XButtons {
 	}
 }
 
+const XBUTTON_NAMES: [&str; 16] = [
+	"UP", "DOWN", "LEFT", "RIGHT",
+	"START", "BACK", "LTHUMB", "RTHUMB",
+	"LB", "RB", "GUIDE", "?",
+	"A", "B", "X", "Y",
+];
+
 impl fmt::Debug for XButtons {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		if f.alternate() {
-			const NAMES: [&'static str; 16] = [
-				"UP", "DOWN", "LEFT", "RIGHT",
-				"START", "BACK", "LTHUMB", "RTHUMB",
-				"LB", "RB", "GUIDE", "?",
-				"A", "B", "X", "Y",
-			];
 			let mut comma = false;
-			for index in 0..16 {
+			for (index, name) in XBUTTON_NAMES.iter().enumerate() {
 				if self.raw & (1 << index) != 0 {
 					if comma {
 						f.write_str("|")?;
-						comma = true;
 					}
-					f.write_str(NAMES[index])?;
+					comma = true;
+					f.write_str(name)?;
 				}
 			}
 			Ok(())
@@ -140,6 +224,7 @@ impl fmt::Debug for XButtons {
 ///
 /// ![image](https://user-images.githubusercontent.com/2324759/124391245-f889b180-dcef-11eb-927c-4b76d2ca332d.png)
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct XGamepad {
 	pub buttons: XButtons,
@@ -151,24 +236,205 @@ pub struct XGamepad {
 	pub thumb_ry: i16,
 }
 
+impl XGamepad {
+	/// Fingerprint of the byte layout, see [`WIRE_FORMAT_VERSION`](crate::WIRE_FORMAT_VERSION).
+	pub const LAYOUT_FINGERPRINT: u64 = crate::layout_fingerprint!(XGamepad { buttons, left_trigger, right_trigger, thumb_lx, thumb_ly, thumb_rx, thumb_ry });
+
+	/// The neutral gamepad state: nothing pressed and the thumbsticks centered.
+	#[inline]
+	pub const fn neutral() -> XGamepad {
+		XGamepad {
+			buttons: XButtons { raw: 0 },
+			left_trigger: 0,
+			right_trigger: 0,
+			thumb_lx: 0,
+			thumb_ly: 0,
+			thumb_rx: 0,
+			thumb_ry: 0,
+		}
+	}
+
+	/// The neutral gamepad state with the buttons pressed.
+	///
+	/// ```
+	/// let gamepad = vigem_client::XGamepad::pressing(vigem_client::XButtons!(DOWN));
+	/// assert_eq!(gamepad.compact().to_string(), "DOWN | LT:0 RT:0 | L(0,0) R(0,0)");
+	/// ```
+	#[inline]
+	pub const fn pressing(buttons: XButtons) -> XGamepad {
+		XGamepad { buttons, ..XGamepad::neutral() }
+	}
+
+	/// Returns if the gamepad is neutral up to the tolerance, ie. the user is not touching it.
+	///
+	/// No buttons may be pressed, the triggers may be up to `tolerance` and the thumbsticks up to `tolerance * 256` away from center.
+	/// The thumbstick tolerance matches the resolution of a DualShock4 axis, see [`DS4Report::is_neutral`].
+	///
+	/// ```
+	/// let gamepad = vigem_client::XGamepad { left_trigger: 2, thumb_lx: -512, ..Default::default() };
+	/// assert!(gamepad.is_neutral(2));
+	/// assert!(!gamepad.is_neutral(1));
+	/// ```
+	#[inline]
+	pub const fn is_neutral(&self, tolerance: u8) -> bool {
+		let axis = tolerance as u16 * 256;
+		self.buttons.raw == 0
+			&& self.left_trigger <= tolerance
+			&& self.right_trigger <= tolerance
+			&& self.thumb_lx.unsigned_abs() <= axis
+			&& self.thumb_ly.unsigned_abs() <= axis
+			&& self.thumb_rx.unsigned_abs() <= axis
+			&& self.thumb_ry.unsigned_abs() <= axis
+	}
+
+	/// Returns the gamepad with the left trigger, see [`Trigger::to_xbox`].
+	#[inline]
+	pub fn left_trigger_t(self, trigger: Trigger) -> XGamepad {
+		XGamepad { left_trigger: trigger.to_xbox(), ..self }
+	}
+
+	/// Returns the gamepad with the right trigger, see [`Trigger::to_xbox`].
+	#[inline]
+	pub fn right_trigger_t(self, trigger: Trigger) -> XGamepad {
+		XGamepad { right_trigger: trigger.to_xbox(), ..self }
+	}
+
+	/// Returns a compact single line formatter for overlays and logs.
+	///
+	/// The format is stable: the pressed buttons joined by `+` (or `-` if none) in the order of their bits,
+	/// followed by the triggers and the thumbsticks as `x,y` pairs, separated by ` | `.
+	///
+	/// ```
+	/// let gamepad = vigem_client::XGamepad {
+	/// 	buttons: vigem_client::XButtons!(A|X),
+	/// 	left_trigger: 255,
+	/// 	thumb_lx: -1200,
+	/// 	thumb_ly: 30000,
+	/// 	..Default::default()
+	/// };
+	/// assert_eq!(gamepad.compact().to_string(), "A+X | LT:255 RT:0 | L(-1200,30000) R(0,0)");
+	/// ```
+	#[inline]
+	pub fn compact(&self) -> CompactXGamepad<'_> {
+		CompactXGamepad(self)
+	}
+
+	/// Decodes the gamepad from its `repr(C)` byte layout.
+	///
+	/// The layout is the field order of this struct without padding, multi-byte fields are little-endian.
+	///
+	/// ```
+	/// let bytes = [0x01, 0x10, 0xff, 0x00, 0x00, 0x80, 0xff, 0x7f, 0, 0, 0, 0];
+	/// let gamepad = vigem_client::XGamepad::from_bytes(&bytes);
+	/// assert_eq!(gamepad.buttons.raw, 0x1001);
+	/// assert_eq!(gamepad.left_trigger, 255);
+	/// assert_eq!(gamepad.thumb_lx, i16::MIN);
+	/// assert_eq!(gamepad.thumb_ly, i16::MAX);
+	/// assert_eq!(gamepad.to_bytes(), bytes);
+	/// ```
+	#[inline]
+	pub const fn from_bytes(bytes: &[u8; mem::size_of::<XGamepad>()]) -> XGamepad {
+		XGamepad {
+			buttons: XButtons { raw: u16::from_le_bytes([bytes[0], bytes[1]]) },
+			left_trigger: bytes[2],
+			right_trigger: bytes[3],
+			thumb_lx: i16::from_le_bytes([bytes[4], bytes[5]]),
+			thumb_ly: i16::from_le_bytes([bytes[6], bytes[7]]),
+			thumb_rx: i16::from_le_bytes([bytes[8], bytes[9]]),
+			thumb_ry: i16::from_le_bytes([bytes[10], bytes[11]]),
+		}
+	}
+
+	/// Encodes the gamepad in its `repr(C)` byte layout.
+	///
+	/// See [`from_bytes`](Self::from_bytes) for the layout.
+	#[inline]
+	pub const fn to_bytes(&self) -> [u8; mem::size_of::<XGamepad>()] {
+		let buttons = self.buttons.raw.to_le_bytes();
+		let thumb_lx = self.thumb_lx.to_le_bytes();
+		let thumb_ly = self.thumb_ly.to_le_bytes();
+		let thumb_rx = self.thumb_rx.to_le_bytes();
+		let thumb_ry = self.thumb_ry.to_le_bytes();
+		[
+			buttons[0], buttons[1], self.left_trigger, self.right_trigger,
+			thumb_lx[0], thumb_lx[1], thumb_ly[0], thumb_ly[1],
+			thumb_rx[0], thumb_rx[1], thumb_ry[0], thumb_ry[1],
+		]
+	}
+
+	/// Returns the gamepad as `XInputGetState` reports it back after submitting it to the driver.
+	///
+	/// The driver forwards the report to the XUSB stack unchanged, triggers and thumbsticks keep their full resolution.
+	/// `XInputGetState` then drops the buttons outside the documented set: the [`GUIDE`](XButtons::GUIDE) button
+	/// and the unassigned bit `0x0800`. The guide button is only reported by the undocumented `XInputGetStateEx`.
+	///
+	/// Small trigger and thumbstick values are not lost along the way, games ignore them by applying the deadzones
+	/// recommended by XInput, see [`consts`] and [`InputFilter::compensate_xinput_deadzone`] to counter it.
+	///
+	/// ```
+	/// use vigem_client::{XButtons, XGamepad};
+	///
+	/// let gamepad = XGamepad { buttons: XButtons!(GUIDE|A), left_trigger: 5, ..Default::default() };
+	/// let seen = gamepad.quantize_like_driver();
+	/// assert_eq!(seen.buttons, XButtons!(A));
+	/// assert_eq!(seen.left_trigger, 5);
+	/// ```
+	#[inline]
+	pub const fn quantize_like_driver(&self) -> XGamepad {
+		// Buttons reported by XInputGetState
+		const XINPUT_BUTTONS: u16 = !(XButtons::GUIDE | 0x0800);
+		XGamepad { buttons: XButtons { raw: self.buttons.raw & XINPUT_BUTTONS }, ..*self }
+	}
+}
+
+/// Compact formatter of an [`XGamepad`], see [`XGamepad::compact`].
+#[derive(Copy, Clone, Debug)]
+pub struct CompactXGamepad<'a>(&'a XGamepad);
+
+impl fmt::Display for CompactXGamepad<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let gamepad = self.0;
+		let mut plus = false;
+		for (index, name) in XBUTTON_NAMES.iter().enumerate() {
+			if gamepad.buttons.raw & (1 << index) != 0 {
+				if plus {
+					f.write_str("+")?;
+				}
+				plus = true;
+				f.write_str(name)?;
+			}
+		}
+		if !plus {
+			f.write_str("-")?;
+		}
+		write!(f, " | LT:{} RT:{} | L({},{}) R({},{})",
+			gamepad.left_trigger, gamepad.right_trigger,
+			gamepad.thumb_lx, gamepad.thumb_ly, gamepad.thumb_rx, gamepad.thumb_ry)
+	}
+}
+
+#[cfg(windows)]
 impl From<XINPUT_GAMEPAD> for XGamepad {
 	#[inline]
 	fn from(gamepad: XINPUT_GAMEPAD) -> Self {
 		unsafe { mem::transmute(gamepad) }
 	}
 }
+#[cfg(windows)]
 impl From<XGamepad> for XINPUT_GAMEPAD {
 	#[inline]
 	fn from(report: XGamepad) -> XINPUT_GAMEPAD {
 		unsafe { mem::transmute(report) }
 	}
 }
+#[cfg(windows)]
 impl AsRef<XINPUT_GAMEPAD> for XGamepad {
 	#[inline]
 	fn as_ref(&self) -> &XINPUT_GAMEPAD {
 		unsafe { mem::transmute(self) }
 	}
 }
+#[cfg(windows)]
 impl AsMut<XINPUT_GAMEPAD> for XGamepad {
 	#[inline]
 	fn as_mut(&mut self) -> &mut XINPUT_GAMEPAD {
@@ -176,8 +442,82 @@ impl AsMut<XINPUT_GAMEPAD> for XGamepad {
 	}
 }
 
+/// XInput user index.
+///
+/// XInput only accepts user indices from 0 to 3.
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use vigem_client::UserIndex;
+///
+/// assert_eq!(UserIndex::try_from(3u32).unwrap().as_u32(), 3);
+/// assert!(UserIndex::try_from(4u32).is_err());
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct UserIndex(u8);
+
+impl UserIndex {
+	/// Number of user indices supported by XInput.
+	pub const COUNT: u32 = 4;
+
+	/// Creates a user index, returns `None` if out of range.
+	#[inline]
+	pub const fn new(index: u8) -> Option<UserIndex> {
+		if (index as u32) < UserIndex::COUNT { Some(UserIndex(index)) } else { None }
+	}
+
+	/// Returns the user index as accepted by the XInput functions.
+	#[inline]
+	pub const fn as_u32(self) -> u32 {
+		self.0 as u32
+	}
+
+	/// Returns the user index.
+	#[inline]
+	pub const fn as_u8(self) -> u8 {
+		self.0
+	}
+}
+
+impl TryFrom<u32> for UserIndex {
+	type Error = Error;
+	#[inline]
+	fn try_from(index: u32) -> Result<UserIndex, Error> {
+		if index < UserIndex::COUNT { Ok(UserIndex(index as u8)) } else { Err(Error::UserIndexOutOfRange) }
+	}
+}
+
+impl TryFrom<u8> for UserIndex {
+	type Error = Error;
+	#[inline]
+	fn try_from(index: u8) -> Result<UserIndex, Error> {
+		UserIndex::try_from(index as u32)
+	}
+}
+
+impl From<UserIndex> for u32 {
+	#[inline]
+	fn from(index: UserIndex) -> u32 {
+		index.as_u32()
+	}
+}
+
+impl From<UserIndex> for u8 {
+	#[inline]
+	fn from(index: UserIndex) -> u8 {
+		index.as_u8()
+	}
+}
+
+impl fmt::Display for UserIndex {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.0.fmt(f)
+	}
+}
+
 /// XInput notification structure.
-#[cfg(feature = "unstable_xtarget_notification")]
+#[cfg(feature = "xusb-rumble")]
+#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
 #[repr(C)]
 pub struct XNotification {
@@ -186,20 +526,90 @@ pub struct XNotification {
 	pub led_number: u8,
 }
 
+#[cfg(feature = "xusb-rumble")]
+#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
+impl XNotification {
+	/// Fingerprint of the byte layout, see [`WIRE_FORMAT_VERSION`](crate::WIRE_FORMAT_VERSION).
+	pub const LAYOUT_FINGERPRINT: u64 = crate::layout_fingerprint!(XNotification { large_motor, small_motor, led_number });
+
+	/// Returns the XInput user index derived from the led number assigned by the host.
+	///
+	/// More reliable than [`Xbox360Wired::get_user_index`].
+	#[inline]
+	pub fn user_index(&self) -> Option<UserIndex> {
+		UserIndex::new(self.led_number)
+	}
+}
+
 /// XInput notification request.
-#[cfg(feature = "unstable_xtarget_notification")]
+#[cfg(all(windows, feature = "xusb-rumble"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
 pub struct XRequestNotification {
 	client: Client,
-	xurn: bus::RequestNotification<bus::XUsbRequestNotification>,
+	// Boxed to be leaked if the driver does not complete the cancellation when dropped
+	xurn: mem::ManuallyDrop<Box<bus::RequestNotification<bus::XUsbRequestNotification>>>,
+	serial_no: u32,
+	uid: TargetUid,
+	// Generation of the target the notifications were requested for
+	generation: u32,
+	current: Arc<bus::Generation>,
+	// Kept outside the buffer the driver writes into while a request is pending
+	attached: bool,
+	link: Arc<bus::CancelLink>,
+	tuning: Option<ThreadTuning>,
 	_unpin: marker::PhantomPinned,
 }
 
-#[cfg(feature = "unstable_xtarget_notification")]
+#[cfg(all(windows, feature = "xusb-rumble"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
 impl XRequestNotification {
 	/// Returns if the underlying target is still attached.
 	#[inline]
 	pub fn is_attached(&self) -> bool {
-		self.xurn.buffer.SerialNo != 0
+		self.attached
+	}
+
+	/// Returns the serial number of the target the notifications were requested for.
+	///
+	/// Identifies the source of the notifications when a single thread handles the notifications of multiple targets.
+	/// Remains available after the underlying target was unplugged.
+	#[inline]
+	pub fn serial_no(&self) -> u32 {
+		self.serial_no
+	}
+
+	/// Returns the uid of the target the notifications were requested for, see [`TargetUid`].
+	#[inline]
+	pub fn uid(&self) -> TargetUid {
+		self.uid
+	}
+
+	/// Sets the scheduling hints applied to the thread handling the notifications.
+	///
+	/// Applies to [`spawn_thread`](Self::spawn_thread) and the other consumers spawning a thread, eg. [`RumbleListener`].
+	/// The hints are best effort, the thread runs untuned if they cannot be applied.
+	#[inline]
+	pub fn with_tuning(mut self, tuning: ThreadTuning) -> Self {
+		self.tuning = Some(tuning);
+		self
+	}
+
+	/// Returns the scheduling hints applied to the thread handling the notifications.
+	#[inline]
+	pub fn tuning(&self) -> Option<ThreadTuning> {
+		self.tuning
+	}
+
+	// Applies the scheduling hints to the thread handling the notifications
+	#[inline]
+	pub(crate) fn apply_tuning(&self) -> Option<TuningGuard> {
+		self.tuning.and_then(|tuning| tuning.apply_to_current_thread().ok())
+	}
+
+	// Lets a listener cancel the pending request from another thread
+	#[inline]
+	pub(crate) fn cancel_link(&self) -> Arc<bus::CancelLink> {
+		self.link.clone()
 	}
 
 	/// Spawns a thread to handle the notifications.
@@ -207,10 +617,12 @@ impl XRequestNotification {
 	/// The callback `f` is invoked for every notification.
 	///
 	/// Returns a [`JoinHandle`](thread::JoinHandle) for the created thread.
-	/// It is recommended to join the thread after the target from which the notifications are requested is dropped.
+	/// The thread exits once the target from which the notifications are requested is unplugged or dropped.
+	/// It is recommended to join the thread after that point.
 	#[inline]
 	pub fn spawn_thread<F: FnMut(&XRequestNotification, XNotification) + Send + 'static>(self, mut f: F) -> thread::JoinHandle<()> {
 		thread::spawn(move || {
+			let _tuning = self.apply_tuning();
 			// Safety: the request notification object is not accessible after it is pinned
 			let mut reqn = self;
 			let mut reqn = unsafe { pin::Pin::new_unchecked(&mut reqn) };
@@ -220,7 +632,7 @@ impl XRequestNotification {
 				match result {
 					Ok(None) => {},
 					Ok(Some(data)) => f(&reqn, data),
-					// When the target is dropped the notification request is aborted
+					// When the target is unplugged or dropped the notification request is aborted
 					Err(_) => break,
 				}
 			}
@@ -232,8 +644,18 @@ impl XRequestNotification {
 	pub fn request(self: pin::Pin<&mut Self>) {
 		unsafe {
 			let device = self.client.device;
-			let xurn = &mut self.get_unchecked_mut().xurn;
-			if xurn.buffer.SerialNo != 0 {
+			let this = self.get_unchecked_mut();
+			let xurn = &mut this.xurn;
+			match this.current.follow(this.generation, true) {
+				bus::Follow::Current => (),
+				// The target was reannounced since, request against its new generation
+				bus::Follow::Continued(generation) => this.generation = generation,
+				// The target was unplugged since, its serial number may already be reused
+				bus::Follow::Swapping | bus::Follow::Gone => this.attached = false,
+			}
+			if this.attached {
+				// Pinned, so the target can cancel the request through this address until dropped
+				this.link.set(device, &mut xurn.overlapped);
 				xurn.ioctl(device);
 			}
 		}
@@ -250,85 +672,317 @@ impl XRequestNotification {
 	/// * `Ok(Some(_))`: The notification was successfully received.  
 	///   Another request should be made or any other calls to `poll` return the same result.
 	/// * `Err(OperationAborted)`: The underlying target was unplugged causing any pending notification requests to abort.
+	///   Notifications completing after the target was unplugged are discarded the same way, they may belong to a target reusing its serial number.
 	/// * `Err(_)`: An unexpected error occurred.
+	///
+	/// The request survives the target being [reannounced](Xbox360Wired::reannounce), it is requested again once the target is back.
+	/// Until then polling without `wait` returns `Ok(None)`.
 	#[inline(never)]
 	pub fn poll(self: pin::Pin<&mut Self>, wait: bool) -> Result<Option<XNotification>, Error> {
 		unsafe {
 			let device = self.client.device;
-			let xurn = &mut self.get_unchecked_mut().xurn;
-			match xurn.poll(device, wait) {
-				Ok(()) => Ok(Some(XNotification {
-					large_motor: xurn.buffer.LargeMotor,
-					small_motor: xurn.buffer.SmallMotor,
-					led_number: xurn.buffer.LedNumber,
-				})),
-				Err(winerror::ERROR_IO_INCOMPLETE) => Ok(None),
-				Err(winerror::ERROR_OPERATION_ABORTED) => {
-					// Operation was aborted, fail all future calls
-					// The is aborted when the underlying target is unplugged
-					// This has the potential for a race condition:
-					//  What happens if a new target is plugged inbetween calls to poll and request...
-					xurn.buffer.SerialNo = 0;
-					Err(Error::OperationAborted)
-				},
-				Err(err) => Err(Error::WinError(err)),
+			let this = self.get_unchecked_mut();
+			let xurn = &mut this.xurn;
+			loop {
+				return match xurn.poll(device, wait) {
+					// Completed for a target reusing the serial number after the target was unplugged, discard it
+					Ok(()) if this.current.follow(this.generation, false) == bus::Follow::Gone => {
+						this.attached = false;
+						Err(Error::OperationAborted)
+					},
+					Ok(()) => Ok(Some(XNotification {
+						large_motor: xurn.buffer.LargeMotor,
+						small_motor: xurn.buffer.SmallMotor,
+						led_number: xurn.buffer.LedNumber,
+					})),
+					Err(winerror::ERROR_IO_INCOMPLETE) => Ok(None),
+					Err(winerror::ERROR_OPERATION_ABORTED) => match this.current.follow(this.generation, wait) {
+						// Aborted by reannouncing the target, request again against its new generation
+						bus::Follow::Continued(generation) => {
+							this.generation = generation;
+							this.link.set(device, &mut xurn.overlapped);
+							xurn.ioctl(device);
+							if wait {
+								continue;
+							}
+							Ok(None)
+						},
+						bus::Follow::Swapping => Ok(None),
+						// Operation was aborted, fail all future calls
+						// The is aborted when the underlying target is unplugged
+						bus::Follow::Current | bus::Follow::Gone => {
+							this.attached = false;
+							Err(Error::OperationAborted)
+						},
+					},
+					Err(err) => Err(Error::WinError(err)),
+				};
 			}
 		}
 	}
 }
 
-#[cfg(feature = "unstable_xtarget_notification")]
+// Requests and polls take `Pin<&mut Self>`, shared access never reads the buffer the driver writes into
+#[cfg(all(windows, feature = "xusb-rumble"))]
 unsafe impl Sync for XRequestNotification {}
-#[cfg(feature = "unstable_xtarget_notification")]
+#[cfg(all(windows, feature = "xusb-rumble"))]
 unsafe impl Send for XRequestNotification {}
 
-#[cfg(feature = "unstable_xtarget_notification")]
+#[cfg(all(windows, feature = "xusb-rumble"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
 impl fmt::Debug for XRequestNotification {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		f.debug_struct("XRequestNotification")
 			.field("client", &format_args!("{:?}", self.client))
-			.field("serial_no", &self.xurn.buffer.SerialNo)
+			.field("serial_no", &self.serial_no)
+			.field("uid", &self.uid)
+			.field("tuning", &self.tuning)
+			.field("attached", &self.is_attached())
 			.finish()
 	}
 }
 
-#[cfg(feature = "unstable_xtarget_notification")]
+#[cfg(all(windows, feature = "xusb-rumble"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
 impl Drop for XRequestNotification {
 	fn drop(&mut self) {
+		// Wait for the target to finish cancelling if it is doing so
+		self.link.clear();
 		unsafe {
-			let this = pin::Pin::new_unchecked(self);
-			if this.xurn.buffer.SerialNo != 0 {
+			let this = pin::Pin::new_unchecked(self).get_unchecked_mut();
+			if this.attached {
+				// Give up on a wedged driver, the pending request keeps the buffer alive
 				let device = this.client.device;
-				let xurn = &mut this.get_unchecked_mut().xurn;
-				let _ = xurn.cancel(device);
+				if let Err(winerror::ERROR_TIMEOUT) = this.xurn.cancel_bounded(device, bus::DROP_TIMEOUT_MS) {
+					return;
+				}
 			}
+			mem::ManuallyDrop::drop(&mut this.xurn);
 		}
 	}
 }
 
+/// Receives notifications by [pumping](Pump::pump) instead of a dedicated thread.
+///
+/// Each pump issues a notification request if none is pending and collects the completed ones without waiting.
+#[cfg(all(windows, feature = "xusb-rumble"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
+pub struct XNotificationPoller {
+	reqn: pin::Pin<Box<XRequestNotification>>,
+	pending: bool,
+	queue: VecDeque<XNotification>,
+}
+
+#[cfg(all(windows, feature = "xusb-rumble"))]
+impl XNotificationPoller {
+	/// Creates a poller for the notification request.
+	#[inline]
+	pub fn new(reqn: XRequestNotification) -> XNotificationPoller {
+		XNotificationPoller { reqn: Box::pin(reqn), pending: false, queue: VecDeque::new() }
+	}
+
+	/// Returns if the underlying target is still attached.
+	///
+	/// Once the target is unplugged no more notifications are received.
+	#[inline]
+	pub fn is_attached(&self) -> bool {
+		self.reqn.is_attached()
+	}
+
+	/// Returns the oldest received notification.
+	#[inline]
+	pub fn try_recv(&mut self) -> Option<XNotification> {
+		self.queue.pop_front()
+	}
+}
+
+#[cfg(all(windows, feature = "xusb-rumble"))]
+impl Pump for XNotificationPoller {
+	fn pump(&mut self, _now: time::Instant) -> Result<(), Error> {
+		while self.reqn.is_attached() {
+			if !self.pending {
+				self.reqn.as_mut().request();
+				self.pending = true;
+			}
+			match self.reqn.as_mut().poll(false) {
+				Ok(None) => break,
+				Ok(Some(data)) => {
+					self.pending = false;
+					self.queue.push_back(data);
+				},
+				Err(Error::OperationAborted) => break,
+				Err(err) => {
+					self.pending = false;
+					return Err(err);
+				},
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(all(windows, feature = "xusb-rumble"))]
+impl fmt::Debug for XNotificationPoller {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("XNotificationPoller")
+			.field("reqn", &self.reqn)
+			.field("pending", &self.pending)
+			.field("queued", &self.queue.len())
+			.finish()
+	}
+}
+
+/// Configuration of a [`Xbox360Wired`] target.
+///
+/// Captures everything needed to create identical targets, but not whether a target is plugged in.
+/// Callbacks such as the progress observer and per-thread resources such as the dedicated event are not captured.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct XTargetTemplate {
+	/// Vendor and product ids.
+	pub id: TargetId,
+	/// What updating the target does while it is not plugged in, see [`Xbox360Wired::set_detached_policy`].
+	pub detached_policy: DetachedPolicy,
+	/// The report submitted once the target is ready, see [`Xbox360Wired::set_initial_report`].
+	pub initial_report: Option<XGamepad>,
+	/// The minimum interval between submissions, see [`Xbox360Wired::set_max_update_rate`].
+	pub min_update_interval: Option<time::Duration>,
+	/// The stale input timeout, see [`Xbox360Wired::set_stale_timeout`].
+	pub stale_timeout: Option<time::Duration>,
+	/// Whether the stale input timeout is only serviced when pumped, see [`Xbox360Wired::set_stale_timeout_manual`].
+	pub stale_timeout_manual: bool,
+	/// Whether the target is guarded against panics, see [`Xbox360Wired::set_panic_guard`].
+	pub panic_guard: bool,
+	/// The capacity of the latency stats, `None` if none are kept, see [`Xbox360Wired::set_latency_stats`].
+	pub latency_capacity: Option<usize>,
+}
+
+impl XTargetTemplate {
+	/// Creates a new template with the default configuration.
+	#[inline]
+	pub const fn new(id: TargetId) -> XTargetTemplate {
+		XTargetTemplate {
+			id,
+			detached_policy: DetachedPolicy::Error,
+			initial_report: None,
+			min_update_interval: None,
+			stale_timeout: None,
+			stale_timeout_manual: false,
+			panic_guard: false,
+			latency_capacity: None,
+		}
+	}
+
+	/// Checks the configuration can be applied to a target.
+	///
+	/// Fails with [`Error::InvalidConfig`] for a latency capacity of zero.
+	#[inline]
+	pub fn validate(&self) -> Result<(), Error> {
+		if self.latency_capacity == Some(0) {
+			return Err(Error::InvalidConfig);
+		}
+		Ok(())
+	}
+
+	/// Creates a new target from this template.
+	///
+	/// The target is not plugged in.
+	/// Fails if the template does not [validate](Self::validate)
+	/// or the client handle cannot be duplicated for the stale input timeout or the panic guard.
+	#[cfg(windows)]
+	#[inline(never)]
+	pub fn instantiate<CL: Borrow<Client>>(&self, client: CL) -> Result<Xbox360Wired<CL>, Error> {
+		self.validate()?;
+		let mut target = Xbox360Wired::new(client, self.id);
+		target.detached = self.detached_policy;
+		target.initial_report = self.initial_report;
+		target.rate_limit = self.min_update_interval.map(RateLimit::with_interval);
+		target.set_watch(self.stale_timeout, !self.stale_timeout_manual)?;
+		target.set_panic_guard(self.panic_guard)?;
+		target.latency = self.latency_capacity.map(LatencyStats::new);
+		Ok(target)
+	}
+}
+
 /// Virtual Microsoft Xbox 360 Controller (wired).
+#[cfg(windows)]
 pub type XTarget = Xbox360Wired<Client>;
 
 /// A virtual Microsoft Xbox 360 Controller (wired).
+///
+/// A target may be used from any thread as long as calls do not overlap, eg. behind a `Mutex` or moved between threads.
+/// Every blocking call waits on an event of the calling thread, or on the [dedicated event](Self::set_dedicated_event) which is reset before each request.
+#[cfg(windows)]
 pub struct Xbox360Wired<CL: Borrow<Client>> {
 	client: CL,
 	event: Event,
 	serial_no: u32,
 	id: TargetId,
+	uid: TargetUid,
+	watch: Option<Arc<Watch>>,
+	panic_guard: Option<Arc<PanicGuard>>,
+	detached: DetachedPolicy,
+	progress: Option<ProgressObserver>,
+	rate_limit: Option<RateLimit<XGamepad>>,
+	latency: Option<LatencyStats>,
+	last_report: Option<XGamepad>,
+	initial_report: Option<XGamepad>,
+	#[cfg(feature = "xusb-rumble")]
+	notifications: Vec<Weak<bus::CancelLink>>,
+	#[cfg(feature = "xusb-rumble")]
+	generation: Arc<bus::Generation>,
+	#[cfg(feature = "xusb-rumble")]
+	rumble: Option<XNotificationPoller>,
 }
 
+#[cfg(windows)]
 impl<CL: Borrow<Client>> Xbox360Wired<CL> {
 	/// Creates a new instance.
 	#[inline]
 	pub fn new(client: CL, id: TargetId) -> Xbox360Wired<CL> {
-		let event = Event::new(false, false);
-		Xbox360Wired { client, event, serial_no: 0, id }
+		let event = Event::null();
+		Xbox360Wired {
+			client, event, serial_no: 0, id,
+			uid: TargetUid::next(),
+			watch: None,
+			panic_guard: None,
+			detached: DetachedPolicy::Error,
+			progress: None,
+			rate_limit: None,
+			latency: None,
+			last_report: None,
+			initial_report: None,
+			#[cfg(feature = "xusb-rumble")]
+			notifications: Vec::new(),
+			#[cfg(feature = "xusb-rumble")]
+			generation: Arc::default(),
+			#[cfg(feature = "xusb-rumble")]
+			rumble: None,
+		}
 	}
 
 	/// Returns if the controller is plugged in.
+	///
+	/// A target unplugged by its [panic guard](Self::set_panic_guard) is not plugged in.
 	#[inline]
 	pub fn is_attached(&self) -> bool {
-		self.serial_no != 0
+		self.serial_no != 0 && !self.panic_guard.as_ref().is_some_and(|guard| guard.is_neutralized())
+	}
+
+	// Forgets the serial number if the panic guard unplugged the target, eg. for a caught panic on the main thread
+	fn sync_panic_guard(&mut self) {
+		if self.panic_guard.as_ref().is_some_and(|guard| guard.take_neutralized()) {
+			let serial_no = self.release();
+			self.client.borrow().track_unplug(serial_no, Ok(()));
+			#[cfg(feature = "xusb-rumble")]
+			{
+				self.notifications.clear();
+				self.rumble = None;
+			}
+			if let Some(limit) = &mut self.rate_limit {
+				limit.clear();
+			}
+		}
 	}
 
 	/// Returns the vendor and product ids.
@@ -337,93 +991,422 @@ impl<CL: Borrow<Client>> Xbox360Wired<CL> {
 		self.id
 	}
 
+	/// Returns the process-unique identifier of the target, see [`TargetUid`].
+	#[inline]
+	pub fn uid(&self) -> TargetUid {
+		self.uid
+	}
+
+	/// Returns the serial number assigned by the driver, zero if not plugged in.
+	#[inline]
+	pub fn serial_no(&self) -> u32 {
+		self.serial_no
+	}
+
+	/// Sets what updating the target does while it is not plugged in.
+	///
+	/// Defaults to [`DetachedPolicy::Error`].
+	#[inline]
+	pub fn set_detached_policy(&mut self, policy: DetachedPolicy) {
+		self.detached = policy;
+	}
+
+	/// Returns what updating the target does while it is not plugged in.
+	#[inline]
+	pub fn detached_policy(&self) -> DetachedPolicy {
+		self.detached
+	}
+
+	// Applies the detached policy before an update, returns false if the update should be skipped.
+	#[inline]
+	fn prepare_update(&mut self) -> Result<bool, Error> {
+		self.sync_panic_guard();
+		if self.is_attached() {
+			return Ok(true);
+		}
+		match self.detached {
+			DetachedPolicy::Error => Err(Error::NotPluggedIn),
+			DetachedPolicy::Ignore => Ok(false),
+			DetachedPolicy::AutoPlugin => {
+				self.plugin()?;
+				self.wait_ready()?;
+				Ok(true)
+			},
+		}
+	}
+
 	/// Returns the client.
 	#[inline]
 	pub fn client(&self) -> &CL {
 		&self.client
 	}
 
+	/// Returns the configuration of this target.
+	#[inline]
+	pub fn template(&self) -> XTargetTemplate {
+		XTargetTemplate {
+			id: self.id,
+			detached_policy: self.detached,
+			initial_report: self.initial_report,
+			min_update_interval: self.rate_limit.as_ref().map(RateLimit::interval),
+			stale_timeout: self.watch.as_ref().map(|watch| watch.timeout()),
+			stale_timeout_manual: self.watch.as_ref().is_some_and(|watch| !watch.is_background()),
+			panic_guard: self.panic_guard.is_some(),
+			latency_capacity: self.latency.as_ref().map(LatencyStats::capacity),
+		}
+	}
+
 	/// Unplugs and destroys the controller, returning the client.
 	#[inline]
 	pub fn drop(mut self) -> CL {
-		let _ = self.unplug();
+		let _ = self.unplug_impl(true);
 
 		unsafe {
 			let client = (&self.client as *const CL).read();
 			ptr::drop_in_place(&mut self.event);
+			ptr::drop_in_place(&mut self.watch);
+			ptr::drop_in_place(&mut self.panic_guard);
+			ptr::drop_in_place(&mut self.progress);
+			ptr::drop_in_place(&mut self.rate_limit);
+			ptr::drop_in_place(&mut self.latency);
+			#[cfg(feature = "xusb-rumble")]
+			ptr::drop_in_place(&mut self.notifications);
+			#[cfg(feature = "xusb-rumble")]
+			ptr::drop_in_place(&mut self.generation);
+			#[cfg(feature = "xusb-rumble")]
+			ptr::drop_in_place(&mut self.rumble);
 			mem::forget(self);
 			client
 		}
 	}
 
+	/// Sets the observer notified of the progress of [`plugin`](Self::plugin) and [`wait_ready`](Self::wait_ready), `None` to remove it.
+	///
+	/// The observer is invoked on the calling thread without holding any locks.
+	/// It cannot call back into the target, which is mutably borrowed while plugging in.
+	#[inline]
+	pub fn set_progress_observer(&mut self, observer: Option<ProgressObserver>) {
+		self.progress = observer;
+	}
+
+	/// Sets an observer notified of the progress together with the [uid](Self::uid) of the target.
+	///
+	/// Same as [`set_progress_observer`](Self::set_progress_observer), for observers shared by multiple targets which log which target progressed.
+	#[inline]
+	pub fn observe_progress<F: Fn(TargetUid, PlugStage) + Send + Sync + 'static>(&mut self, observer: F) {
+		let uid = self.uid;
+		self.progress = Some(Box::new(move |stage| observer(uid, stage)));
+	}
+
+	#[inline]
+	fn progress(&self, stage: PlugStage) {
+		if let Some(observer) = &self.progress {
+			observer(stage);
+		}
+	}
+
+	/// Uses an event object of its own to wait on the driver.
+	///
+	/// By default targets wait on an event object shared by all targets on the calling thread,
+	/// which keeps the number of kernel objects down when driving many targets.
+	/// Failure to create the event object is reported as [`Error::ResourceCreation`].
+	#[inline]
+	pub fn set_dedicated_event(&mut self, dedicated: bool) -> Result<(), Error> {
+		if dedicated {
+			self.event.create_if_null(false, false).map_err(Error::ResourceCreation)
+		}
+		else {
+			self.event = Event::null();
+			Ok(())
+		}
+	}
+
 	/// Plugs the controller in.
+	///
+	/// Failure to create the event object of the calling thread used to wait on the driver is reported as [`Error::ResourceCreation`].
 	#[inline(never)]
 	pub fn plugin(&mut self) -> Result<(), Error> {
+		self.plugin_impl(None)
+	}
+
+	/// Plugs the controller in under the serial number.
+	///
+	/// Fails with [`Error::SerialInUse`] if the driver rejects the serial number, eg. when another target is plugged in under it.
+	#[inline(never)]
+	pub fn plugin_at(&mut self, serial_no: u32) -> Result<(), Error> {
+		self.plugin_impl(Some(serial_no))
+	}
+
+	fn plugin_impl(&mut self, serial_no: Option<u32>) -> Result<(), Error> {
+		self.sync_panic_guard();
 		if self.is_attached() {
 			return Err(Error::AlreadyConnected);
 		}
+		self.client.borrow().check_plugin()?;
+
+		let event = self.event.sync()?;
 
-		let mut plugin = bus::PluginTarget::x360_wired(1, self.id.vendor, self.id.product);
-		let device = self.client.borrow().device;
+		let mut plugin = bus::PluginTarget::x360_wired(serial_no.unwrap_or(1), self.id.vendor, self.id.product);
+		let client = self.client.borrow();
+		let device = client.device;
 
 		// Yes this is how the driver is implemented
-		while unsafe { plugin.ioctl(device, self.event.handle) }.is_err() {
+		self.progress(PlugStage::SerialScan { attempt: plugin.SerialNo });
+		while unsafe { let _pass = client.enter(); plugin.ioctl(device, event.handle) }.is_err() {
+			if serial_no.is_some() {
+				return Err(Error::SerialInUse);
+			}
 			plugin.SerialNo += 1;
+			self.progress(PlugStage::SerialScan { attempt: plugin.SerialNo });
 			if plugin.SerialNo >= u16::MAX as u32 {
 				return Err(Error::NoFreeSlot);
 			}
 		}
 
 		self.serial_no = plugin.SerialNo;
+		self.client.borrow().track_plugin(self.serial_no);
+		if let Some(watch) = &self.watch {
+			watch.set_serial_no(self.serial_no);
+		}
+		if let Some(guard) = &self.panic_guard {
+			guard.set_serial_no(self.serial_no);
+		}
+		#[cfg(feature = "xusb-rumble")]
+		self.generation.bump();
+		self.progress(PlugStage::DriverAccepted { serial: self.serial_no });
 		Ok(())
 	}
 
+	// Takes over a target plugged in under the serial number without plugging it in, see `Fleet::restore`
+	pub(crate) fn adopt(&mut self, serial_no: u32) {
+		self.serial_no = serial_no;
+		#[cfg(feature = "xusb-rumble")]
+		self.generation.bump();
+		if let Some(watch) = &self.watch {
+			watch.set_serial_no(serial_no);
+		}
+		if let Some(guard) = &self.panic_guard {
+			guard.set_serial_no(serial_no);
+		}
+		self.client.borrow().track_adopt(serial_no);
+	}
+
+	// Lets go of the target without unplugging it, it remains plugged in until adopted or the client is closed
+	pub(crate) fn release(&mut self) -> u32 {
+		if let Some(watch) = &self.watch {
+			watch.set_serial_no(0);
+		}
+		if let Some(guard) = &self.panic_guard {
+			guard.set_serial_no(0);
+		}
+		#[cfg(feature = "xusb-rumble")]
+		self.generation.bump();
+		self.last_report = None;
+		mem::replace(&mut self.serial_no, 0)
+	}
+
 	/// Unplugs the controller.
+	///
+	/// Waits for the driver to complete the request, however long it takes.
+	/// Dropping the target instead gives up waiting after a few seconds.
 	#[inline(never)]
 	pub fn unplug(&mut self) -> Result<(), Error> {
+		self.unplug_impl(false)
+	}
+
+	// Unplugs the controller, bounded gives up waiting for the driver after `bus::DROP_TIMEOUT_MS`
+	fn unplug_impl(&mut self, bounded: bool) -> Result<(), Error> {
+		self.unplug_inner(bounded, false)
+	}
+
+	// Unplugs the controller, reannouncing keeps the notification links, the pending update and the last report
+	fn unplug_inner(&mut self, bounded: bool, reannounce: bool) -> Result<(), Error> {
+		self.sync_panic_guard();
 		if !self.is_attached() {
 			return Err(Error::NotPluggedIn);
 		}
+		let event = if bounded { None } else { Some(self.event.sync()?) };
 
-		unsafe {
+		// Stop the watchdog from submitting reports to a serial number which may be reused
+		if let Some(watch) = &self.watch {
+			watch.set_serial_no(0);
+		}
+		if let Some(guard) = &self.panic_guard {
+			guard.set_serial_no(0);
+		}
+
+		// Abort the pending notification requests so their listeners observe OperationAborted promptly
+		// When reannouncing the listeners request again once the target is back
+		#[cfg(feature = "xusb-rumble")]
+		for link in &self.notifications {
+			if let Some(link) = link.upgrade() {
+				link.cancel();
+			}
+		}
+		#[cfg(feature = "xusb-rumble")]
+		if !reannounce {
+			self.notifications.clear();
+			self.rumble = None;
+		}
+
+		let result = unsafe {
 			let mut unplug = bus::UnplugTarget::new(self.serial_no);
-			let device = self.client.borrow().device;
-			unplug.ioctl(device, self.event.handle)?;
+			let client = self.client.borrow();
+			let _pass = client.enter();
+			let device = client.device;
+			match &event {
+				Some(event) => unplug.ioctl(device, event.handle),
+				None => unplug.ioctl_bounded(device, bus::DROP_TIMEOUT_MS),
+			}
+		};
+		self.client.borrow().track_unplug(self.serial_no, result);
+
+		if let Err(err) = result {
+			if let Some(watch) = &self.watch {
+				watch.set_serial_no(self.serial_no);
+			}
+			if let Some(guard) = &self.panic_guard {
+				guard.set_serial_no(self.serial_no);
+			}
+			return Err(Error::WinError(err));
 		}
 
 		self.serial_no = 0;
+		if !reannounce {
+			#[cfg(feature = "xusb-rumble")]
+			self.generation.bump();
+			if let Some(limit) = &mut self.rate_limit {
+				limit.clear();
+			}
+			self.last_report = None;
+		}
 		Ok(())
 	}
 
-	/// Waits until the virtual controller is ready.
+	/// Unplugs the controller and plugs it in again under the same serial number, making it announce itself anew.
+	///
+	/// Nudges games which only scan for controllers at launch or on device change notifications without recreating the target.
+	/// The target keeps its settings, eg. the stale timeout, the maximum update rate and the latency stats,
+	/// and its live notification requests continue receiving notifications once it is back.
+	///
+	/// Unplugs the controller, waits for its device to be removed, plugs it in under the same serial number,
+	/// waits until it is ready and submits the last report again, or the [initial report](Self::set_initial_report) if nothing was submitted yet.
+	/// The progress observer is notified of every step, with [`PlugStage::Removed`] once the device is gone.
+	///
+	/// If it cannot be plugged in again, eg. when another target took the serial number meanwhile ([`Error::SerialInUse`]),
+	/// the target is left unplugged and its notification requests end. Plug it in again with [`plugin`](Self::plugin).
+	#[inline(never)]
+	pub fn reannounce(&mut self) -> Result<(), Error> {
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+		#[cfg(feature = "xusb-rumble")]
+		let swap = self.generation.begin_swap();
+		let result = self.reannounce_steps();
+		#[cfg(feature = "xusb-rumble")]
+		swap.end(self.is_attached());
+		if !self.is_attached() {
+			#[cfg(feature = "xusb-rumble")]
+			self.notifications.clear();
+			if let Some(limit) = &mut self.rate_limit {
+				limit.clear();
+			}
+			self.last_report = None;
+		}
+		result
+	}
+
+	fn reannounce_steps(&mut self) -> Result<(), Error> {
+		let serial_no = self.serial_no;
+		let last_report = self.last_report;
+		self.unplug_inner(false, true)?;
+		devpath::wait_removed(&bus::GUID_DEVINTERFACE_XUSB, false, self.id, serial_no, devpath::DEVICE_PATH_TIMEOUT)?;
+		self.progress(PlugStage::Removed { serial: serial_no });
+		self.plugin_at(serial_no)?;
+		self.wait_driver()?;
+		let gamepad = last_report.or(self.initial_report).unwrap_or(XGamepad::neutral());
+		self.submit(&gamepad, false)?;
+		Ok(())
+	}
+
+	/// Waits until the virtual controller is ready and submits the [initial report](Self::set_initial_report).
 	///
 	/// Any updates submitted before the virtual controller is ready may return an error.
+	///
+	/// If the virtual controller is not ready after a grace period, the installation state of its device is consulted.
+	/// Fails with [`Error::DeviceInstallFailed`] if Windows failed to install the device, eg. due to a broken driver store.
 	#[inline(never)]
 	pub fn wait_ready(&mut self) -> Result<(), Error> {
+		self.wait_ready_timed().map(|_| ())
+	}
+
+	/// Waits until the virtual controller is ready and returns how long the driver took.
+	///
+	/// Same as [`wait_ready`](Self::wait_ready) but measures the driver round trip with a monotonic clock,
+	/// the submission of the initial report is not included.
+	#[inline(never)]
+	pub fn wait_ready_timed(&mut self) -> Result<time::Duration, Error> {
+		let elapsed = self.wait_driver()?;
+		let initial = self.initial_report.unwrap_or(XGamepad::neutral());
+		self.submit(&initial, false)?;
+		Ok(elapsed)
+	}
+
+	// Waits until the driver reports the virtual controller ready
+	fn wait_driver(&mut self) -> Result<time::Duration, Error> {
 		if !self.is_attached() {
 			return Err(Error::NotPluggedIn);
 		}
 
-		unsafe {
-			let mut wait = bus::WaitDeviceReady::new(self.serial_no);
-			let device = self.client.borrow().device;
-			wait.ioctl(device, self.event.handle)?;
-		}
+		let event = self.event.sync()?;
+		self.progress(PlugStage::WaitingReady);
+		let elapsed = unsafe {
+			let client = self.client.borrow();
+			let _pass = client.enter();
+			let device = client.device;
+			let start = time::Instant::now();
+			devpath::InstallWatch::wait_ready(device, event.handle, self.id, self.serial_no)?;
+			start.elapsed()
+		};
+		self.progress(PlugStage::Ready);
+		Ok(elapsed)
+	}
 
-		Ok(())
+	/// Sets the report submitted once the virtual controller is ready, `None` for the neutral report.
+	///
+	/// Submitted by [`wait_ready`](Self::wait_ready), including when plugging in automatically with [`DetachedPolicy::AutoPlugin`],
+	/// such that games see a known state from the moment the controller appears instead of whatever the driver defaults to.
+	/// It is an ordinary submission: it counts as the last report and refreshes the stale timeout.
+	#[inline]
+	pub fn set_initial_report(&mut self, gamepad: Option<XGamepad>) {
+		self.initial_report = gamepad;
+	}
+
+	/// Returns the report submitted once the virtual controller is ready, `None` for the neutral report.
+	#[inline]
+	pub fn initial_report(&self) -> Option<&XGamepad> {
+		self.initial_report.as_ref()
 	}
 
 	/// Gets the user index of the device in XInput.
+	///
+	/// Returns [`Error::UserIndexOutOfRange`] if the driver has not assigned a valid user index.
+	///
+	/// The driver is known to report a user index which does not match the one XInput uses,
+	/// when possible prefer the LED derived `XNotification::user_index` (requires the `xusb-rumble` feature).
 	#[inline(never)]
-	pub fn get_user_index(&mut self) -> Result<u32, Error> {
+	pub fn get_user_index(&mut self) -> Result<UserIndex, Error> {
 		if !self.is_attached() {
 			return Err(Error::NotPluggedIn);
 		}
 
+		let event = self.event.sync()?;
 		let user_index = unsafe {
 			let mut gui = bus::XUsbGetUserIndex::new(self.serial_no);
-			let device = self.client.borrow().device;
-			match gui.ioctl(device, self.event.handle) {
+			let client = self.client.borrow();
+			let _pass = client.enter();
+			let device = client.device;
+			match gui.ioctl(device, event.handle) {
 				Ok(()) => (),
 				// Err(winerror::ERROR_ACCESS_DENIED) => return Err(Error::InvalidTarget),
 				Err(winerror::ERROR_INVALID_DEVICE_OBJECT_PARAMETER) => return Err(Error::UserIndexOutOfRange),
@@ -433,63 +1416,458 @@ impl<CL: Borrow<Client>> Xbox360Wired<CL> {
 			gui.UserIndex
 		};
 
-		Ok(user_index)
+		UserIndex::try_from(user_index)
 	}
 
-	/// Updates the virtual controller state.
+	/// Waits for XInput to assign the target a user index.
+	///
+	/// The driver only knows the user index once XInput picked up the controller, which may take a moment after it is ready.
+	/// Polls [`get_user_index`](Self::get_user_index) until it succeeds or the timeout elapses, then returns its last error.
 	#[inline(never)]
-	pub fn update(&mut self, gamepad: &XGamepad) -> Result<(), Error> {
+	pub fn wait_for_user_index(&mut self, timeout: time::Duration) -> Result<UserIndex, Error> {
+		let start = time::Instant::now();
+		loop {
+			match self.get_user_index() {
+				Err(Error::UserIndexOutOfRange) if start.elapsed() < timeout => thread::sleep(time::Duration::from_millis(10)),
+				result => return result,
+			}
+		}
+	}
+
+	/// Submits the gamepad and waits for `XInputGetState` to report it back.
+	///
+	/// Every XInput slot is checked, the user index reported by the driver is not always reliable.
+	/// The gamepad is compared as [quantized like the driver](XGamepad::quantize_like_driver), pick a state no other controller is in.
+	///
+	/// Returns the time until XInput reported the gamepad, or `None` if it did not within the timeout.
+	#[inline(never)]
+	pub fn verify_xinput(&mut self, gamepad: &XGamepad, timeout: time::Duration) -> Result<Option<time::Duration>, Error> {
+		self.update(gamepad)?;
+		let start = time::Instant::now();
+		let expected = gamepad.quantize_like_driver();
+		loop {
+			let seen = (0..UserIndex::COUNT).any(|user_index| unsafe {
+				let mut state: xinput::XINPUT_STATE = mem::zeroed();
+				xinput::XInputGetState(user_index, &mut state) == 0 && XGamepad::from(state.Gamepad) == expected
+			});
+			let elapsed = start.elapsed();
+			if seen {
+				return Ok(Some(elapsed));
+			}
+			if elapsed >= timeout {
+				return Ok(None);
+			}
+			thread::sleep(time::Duration::from_millis(1));
+		}
+	}
+
+	/// Returns the device path of the virtual controller's XUSB interface, eg. to open it with `CreateFile`.
+	///
+	/// The device is matched by the vendor and product ids and the serial number the bus assigned to the target.
+	/// Waits a few seconds for the device to appear after plugging in, see [`device_path_timeout`](Self::device_path_timeout).
+	#[inline]
+	pub fn device_path(&mut self) -> Result<ffi::OsString, Error> {
+		self.device_path_timeout(devpath::DEVICE_PATH_TIMEOUT)
+	}
+
+	/// Returns the device path of the virtual controller's XUSB interface, waiting up to the timeout for it to appear.
+	///
+	/// Fails with [`Error::DeviceNotFound`] on timeout and [`Error::AmbiguousDevice`] if more than one device matches the target.
+	#[inline(never)]
+	pub fn device_path_timeout(&mut self, timeout: time::Duration) -> Result<ffi::OsString, Error> {
 		if !self.is_attached() {
 			return Err(Error::NotPluggedIn);
 		}
+		devpath::device_path(&bus::GUID_DEVINTERFACE_XUSB, false, self.id, self.serial_no, timeout)
+	}
 
-		unsafe {
+	/// Updates the virtual controller state.
+	///
+	/// If the target is not plugged in the [detached policy](Self::set_detached_policy) applies.
+	/// With a [maximum update rate](Self::set_max_update_rate) updates within the minimum interval are coalesced.
+	#[inline(never)]
+	pub fn update(&mut self, gamepad: &XGamepad) -> Result<(), Error> {
+		self.submit_update(gamepad, false)?;
+		Ok(())
+	}
+
+	// Applies the detached policy and the rate limit, the round trip is only measured when timed
+	fn submit_update(&mut self, gamepad: &XGamepad, timed: bool) -> Result<Submitted, Error> {
+		if !self.prepare_update()? {
+			return Ok(Submitted::Skipped);
+		}
+		let gamepad = match &mut self.rate_limit {
+			Some(limit) => match limit.offer(*gamepad, time::Instant::now()) {
+				Some(gamepad) => gamepad,
+				None => return Ok(Submitted::Skipped),
+			},
+			None => *gamepad,
+		};
+		self.submit(&gamepad, timed).map(Submitted::Sent)
+	}
+
+	// Submits the report to the driver, bypassing the rate limit, returns the round trip of the request when timed
+	fn submit(&mut self, gamepad: &XGamepad, timed: bool) -> Result<Option<time::Duration>, Error> {
+		let event = self.event.sync()?;
+		let submit = || unsafe {
 			let mut xsr = bus::XUsbSubmitReport::new(self.serial_no, *gamepad);
-			let device = self.client.borrow().device;
-			match xsr.ioctl(device, self.event.handle) {
-				Ok(()) => Ok(()),
+			let client = self.client.borrow();
+			let _pass = client.enter();
+			let device = client.device;
+			let start = if timed { Some(time::Instant::now()) } else { None };
+			match xsr.ioctl(device, event.handle) {
+				Ok(()) => Ok(start.map(|start| start.elapsed())),
 				Err(winerror::ERROR_DEV_NOT_EXIST) => Err(Error::TargetNotReady),
 				Err(err) => Err(Error::WinError(err)),
 			}
+		};
+
+		let result = match &self.watch {
+			Some(watch) => watch.update(submit),
+			None => submit(),
+		};
+		if result.is_ok() {
+			self.last_report = Some(*gamepad);
+		}
+		result
+	}
+
+	/// Updates the virtual controller state from bytes in the `repr(C)` layout of [`XGamepad`].
+	///
+	/// Intended for producers which write the report into shared memory, see [`XGamepad::from_bytes`] for the layout.
+	#[inline]
+	pub fn update_raw(&mut self, bytes: &[u8; mem::size_of::<XGamepad>()]) -> Result<(), Error> {
+		self.update(&XGamepad::from_bytes(bytes))
+	}
+
+	/// Updates the virtual controller state and returns how long the driver took.
+	///
+	/// Same as [`update`](Self::update) but measures the round trip of the submit report request,
+	/// from just before it is issued until its completion, with a monotonic clock.
+	///
+	/// On a healthy system the round trip typically takes well under a millisecond.
+	/// Consistently taking multiple milliseconds points at a congested or misbehaving driver.
+	///
+	/// Returns `None` for updates skipped by [`DetachedPolicy::Ignore`] or coalesced by the maximum update rate.
+	/// The plain [`update`](Self::update) does not read the clock.
+	#[inline(never)]
+	pub fn update_timed(&mut self, gamepad: &XGamepad) -> Result<Option<time::Duration>, Error> {
+		match self.submit_update(gamepad, true)? {
+			Submitted::Skipped => Ok(None),
+			Submitted::Sent(elapsed) => Ok(elapsed),
+		}
+	}
+
+	/// Updates the virtual controller state and records the latency since the input was captured.
+	///
+	/// Same as [`update`](Self::update), the latency from `captured_at` until the driver accepted the report
+	/// is recorded in the [latency stats](Self::latency_stats), which are created on first use.
+	/// Updates skipped by [`DetachedPolicy::Ignore`] or coalesced by the maximum update rate are not recorded.
+	#[inline(never)]
+	pub fn update_stamped(&mut self, gamepad: &XGamepad, captured_at: time::Instant) -> Result<(), Error> {
+		if let Submitted::Sent(_) = self.submit_update(gamepad, false)? {
+			self.latency.get_or_insert_with(LatencyStats::default).record(captured_at, time::Instant::now());
+		}
+		Ok(())
+	}
+
+	/// Returns the latencies recorded by [`update_stamped`](Self::update_stamped).
+	#[inline]
+	pub fn latency_stats(&self) -> Option<&LatencyStats> {
+		self.latency.as_ref()
+	}
+
+	/// Replaces the latency stats, eg. to change their capacity or to take them.
+	#[inline]
+	pub fn set_latency_stats(&mut self, stats: Option<LatencyStats>) -> Option<LatencyStats> {
+		mem::replace(&mut self.latency, stats)
+	}
+
+	/// Updates the virtual controller state with the reports of the callback at the rate in Hz until it returns `None`.
+	///
+	/// The callback is called with the time elapsed since the first tick, see [`PacedDriver`] for the error policy.
+	/// Blocks the calling thread, returns the counts and why driving stopped.
+	/// Fails immediately if the target cannot be updated, see [`set_detached_policy`](Self::set_detached_policy),
+	/// or with [`Error::InvalidConfig`] if the rate is not positive and finite, see [`FixedRate::try_from_hz`].
+	///
+	/// ```no_run
+	/// use std::time::Duration;
+	///
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::Xbox360Wired::new(client, vigem_client::TargetId::XBOX360_WIRED);
+	/// target.plugin().unwrap();
+	/// target.wait_ready().unwrap();
+	///
+	/// // Spin the left thumbstick for 10 seconds
+	/// let summary = target.drive(100.0, |elapsed| {
+	/// 	let t = elapsed.as_secs_f64();
+	/// 	(t < 10.0).then(|| vigem_client::XGamepad {
+	/// 		thumb_lx: (t.cos() * 30000.0) as i16,
+	/// 		thumb_ly: (t.sin() * 30000.0) as i16,
+	/// 		..Default::default()
+	/// 	})
+	/// }).unwrap();
+	/// assert_eq!(summary.stop, Some(vigem_client::DriveStop::Finished));
+	/// ```
+	pub fn drive<F: FnMut(time::Duration) -> Option<XGamepad>>(&mut self, rate_hz: f32, f: F) -> Result<DriveSummary, Error> {
+		let mut clock = FixedRate::try_from_hz(rate_hz as f64).ok_or(Error::InvalidConfig)?;
+		self.prepare_update()?;
+		Ok(PacedDriver::new(self, rate_hz, f).run(&mut clock))
+	}
+
+	/// Updates the virtual controller state with the latest gamepad published to the cell at the rate in Hz until the cell is closed.
+	///
+	/// Same as [`drive`](Self::drive) with a producer thread publishing the gamepad states, see [`StateCell`].
+	/// The latest gamepad is submitted every tick whether or not a new one was published since.
+	/// The gamepad published right before closing the cell is submitted before driving stops, see [`StateCell::poll`].
+	#[inline]
+	pub fn drive_from_cell(&mut self, cell: &StateCell<XGamepad>, rate_hz: f32) -> Result<DriveSummary, Error> {
+		self.drive(rate_hz, |_| cell.poll())
+	}
+
+	/// Sets the maximum rate in Hz updates are submitted to the driver, `None` to submit every update.
+	///
+	/// Protects the driver and the other targets from a producer updating in a tight loop.
+	/// Updates within the minimum interval since the last submission are coalesced, latest wins:
+	/// the update is kept pending in place of the previously pending update and `Ok(())` is returned without submitting it.
+	/// The pending update is submitted by the first update after the interval, which supersedes it, by [`flush`](Self::flush)
+	/// or when the target is pumped once the interval elapsed, see [`Pump`].
+	/// Pump the target or flush it after the last update to have the final state reach the driver.
+	///
+	/// Coalesced updates do not count towards the [stale input timeout](Self::set_stale_timeout).
+	/// Removing the limit submits the pending update, unplugging the target discards it.
+	///
+	/// Panics if the rate is not positive.
+	#[inline]
+	pub fn set_max_update_rate(&mut self, max_hz: Option<f32>) -> Result<(), Error> {
+		match (max_hz, &mut self.rate_limit) {
+			(None, _) => {
+				self.flush()?;
+				self.rate_limit = None;
+			},
+			(Some(max_hz), Some(limit)) => limit.set_max_rate(max_hz),
+			(Some(max_hz), None) => self.rate_limit = Some(RateLimit::new(max_hz)),
 		}
+		Ok(())
+	}
+
+	/// Returns the update coalesced by the maximum update rate which is yet to be submitted.
+	#[inline]
+	pub fn pending_update(&self) -> Option<&XGamepad> {
+		self.rate_limit.as_ref()?.pending()
+	}
+
+	/// Submits the update coalesced by the maximum update rate now.
+	///
+	/// The update stays pending if submitting it fails.
+	#[inline]
+	pub fn flush(&mut self) -> Result<(), Error> {
+		let pending = self.rate_limit.as_mut().and_then(|limit| limit.flush(time::Instant::now()));
+		match pending {
+			Some(gamepad) => self.submit_pending(gamepad),
+			None => Ok(()),
+		}
+	}
+
+	// Submits the coalesced update once its interval elapsed
+	pub(crate) fn pump_rate_limit(&mut self, now: time::Instant) -> Result<(), Error> {
+		let pending = self.rate_limit.as_mut().and_then(|limit| limit.poll(now));
+		match pending {
+			Some(gamepad) if self.is_attached() => self.submit_pending(gamepad),
+			_ => Ok(()),
+		}
+	}
+
+	// Submits the coalesced update, it stays pending if the submission fails
+	fn submit_pending(&mut self, gamepad: XGamepad) -> Result<(), Error> {
+		let result = self.submit(&gamepad, false);
+		if result.is_err() {
+			if let Some(limit) = &mut self.rate_limit {
+				limit.restore(gamepad);
+			}
+		}
+		result.map(|_| ())
+	}
+
+	/// Sets the stale input timeout.
+	///
+	/// When set, the target is neutralized by submitting [`XGamepad::neutral`] if no update succeeded within the timeout,
+	/// eg. when the thread producing the updates is stuck while a button is held.
+	/// It is neutralized once per stale period, the next successful update clears the condition.
+	///
+	/// All targets share a single background watchdog thread which submits the neutral reports.
+	/// Enabling the timeout duplicates the client handle for use by the watchdog.
+	#[inline(never)]
+	pub fn set_stale_timeout(&mut self, timeout: Option<time::Duration>) -> Result<(), Error> {
+		self.set_watch(timeout, true)
+	}
+
+	/// Sets the stale input timeout without a background thread.
+	///
+	/// Same as [`set_stale_timeout`](Self::set_stale_timeout) but the target is only neutralized when it is [pumped](Pump::pump).
+	#[inline(never)]
+	pub fn set_stale_timeout_manual(&mut self, timeout: Option<time::Duration>) -> Result<(), Error> {
+		self.set_watch(timeout, false)
+	}
+
+	fn set_watch(&mut self, timeout: Option<time::Duration>, background: bool) -> Result<(), Error> {
+		match (timeout, &self.watch) {
+			(None, _) => self.watch = None,
+			(Some(timeout), Some(watch)) if watch.is_background() == background => watch.set_timeout(timeout),
+			(Some(timeout), _) => {
+				self.watch = Some(Watch::new(self.client.borrow(), Neutral::Xbox360Wired, self.serial_no, timeout, background)?);
+			},
+		}
+		Ok(())
+	}
+
+	#[inline]
+	pub(crate) fn watch(&self) -> Option<&Arc<Watch>> {
+		self.watch.as_ref()
+	}
+
+	/// Returns if the target was neutralized because its input went stale.
+	///
+	/// See [`set_stale_timeout`](Self::set_stale_timeout).
+	#[inline]
+	pub fn is_stale(&self) -> bool {
+		self.watch.as_ref().is_some_and(|watch| watch.is_stale())
+	}
+
+	/// Guards the target against holding its last input when the process panics.
+	///
+	/// While enabled and plugged in, a panic which may end the process submits [`XGamepad::neutral`] and unplugs the target
+	/// before the panic continues, see [`install_panic_guard`] for how and when it runs.
+	/// Enabling the guard installs the panic hook if needed and duplicates the client handle for use by the hook.
+	#[inline(never)]
+	pub fn set_panic_guard(&mut self, enabled: bool) -> Result<(), Error> {
+		self.sync_panic_guard();
+		match (enabled, &self.panic_guard) {
+			(false, _) => self.panic_guard = None,
+			(true, Some(_)) => (),
+			(true, None) => self.panic_guard = Some(PanicGuard::new(self.client.borrow(), Neutral::Xbox360Wired, self.serial_no)?),
+		}
+		Ok(())
+	}
+
+	/// Returns if the target is guarded against panics, see [`set_panic_guard`](Self::set_panic_guard).
+	#[inline]
+	pub fn has_panic_guard(&self) -> bool {
+		self.panic_guard.is_some()
 	}
 
 	/// Request notification.
 	///
 	/// See examples/notification.rs for a complete example how to use this interface.
 	///
-	/// Do not create more than one request notification per target.
-	/// Notifications may get lost or received by one or more listeners.
-	#[cfg(feature = "unstable_xtarget_notification")]
+	/// Only one request notification per target can be live, else notifications get lost or received by one or more listeners.
+	/// Share the request with a [`RumbleListener`] to let multiple consumers observe the notifications.
+	/// Returns [`Error::NotificationAlreadyActive`] while a previous request notification is alive,
+	/// it is released when the request notification is dropped or the target is unplugged.
+	///
+	/// Unplugging the target cancels the pending request, the listener then receives [`Error::OperationAborted`].
+	/// The request notification may be dropped before or after the target.
+	#[cfg(feature = "xusb-rumble")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
 	#[inline(never)]
 	pub fn request_notification(&mut self) -> Result<XRequestNotification, Error> {
+		self.notifications.retain(|link| link.strong_count() > 0);
+		if self.is_attached() && !self.notifications.is_empty() {
+			return Err(Error::NotificationAlreadyActive);
+		}
+		self.force_request_notification()
+	}
+
+	/// Polls for rumble and led notifications from the host's own poll loop, without a thread or blocking.
+	///
+	/// The first call requests notifications, every call checks the pending request without waiting and requests again once it completed.
+	/// In the common case that is a single zero-wait check of the overlapped request, cheap enough to call every millisecond.
+	///
+	/// Returns the latest notification received since the previous call, it supersedes those received before it, or `None` if nothing new arrived.
+	/// Fails with [`Error::NotPluggedIn`] once the target is unplugged and [`Error::NotificationAlreadyActive`] while a [request](Self::request_notification) of its own is alive.
+	/// The request survives the target being [reannounced](Self::reannounce) and is dropped when it is unplugged.
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::Xbox360Wired::new(client, vigem_client::TargetId::XBOX360_WIRED);
+	/// target.plugin().unwrap();
+	/// target.wait_ready().unwrap();
+	///
+	/// // From the host's 1 kHz loop
+	/// if let Some(rumble) = target.poll_rumble().unwrap() {
+	/// 	println!("motors: {} {}", rumble.large_motor, rumble.small_motor);
+	/// }
+	/// ```
+	#[cfg(feature = "xusb-rumble")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
+	#[inline(never)]
+	pub fn poll_rumble(&mut self) -> Result<Option<XNotification>, Error> {
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+		let mut poller = match self.rumble.take() {
+			Some(poller) => poller,
+			None => XNotificationPoller::new(self.request_notification()?),
+		};
+		poller.pump(time::Instant::now())?;
+		let latest = poller.queue.pop_back();
+		poller.queue.clear();
+		// Aborted without the target being unplugged through it, request again on the next call
+		if latest.is_none() && !poller.is_attached() {
+			return Err(Error::OperationAborted);
+		}
+		self.rumble = Some(poller);
+		Ok(latest)
+	}
+
+	/// Request notification even if a previous request notification is alive.
+	///
+	/// Notifications may get lost or received by one or more of the listeners.
+	/// Failure to create the event object the notification completes through is reported as [`Error::ResourceCreation`].
+	#[cfg(feature = "xusb-rumble")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "xusb-rumble")))]
+	#[inline(never)]
+	pub fn force_request_notification(&mut self) -> Result<XRequestNotification, Error> {
 		if !self.is_attached() {
 			return Err(Error::NotPluggedIn);
 		}
 
 		let client = self.client.borrow().try_clone()?;
-		let xurn = bus::RequestNotification::new(
-			bus::XUsbRequestNotification::new(self.serial_no));
+		let xurn = bus::RequestNotification::new(bus::XUsbRequestNotification::new(self.serial_no)).map_err(Error::ResourceCreation)?;
+		let xurn = mem::ManuallyDrop::new(Box::new(xurn));
+
+		let link = Arc::new(bus::CancelLink::default());
+		self.notifications.retain(|link| link.strong_count() > 0);
+		self.notifications.push(Arc::downgrade(&link));
 
-		Ok(XRequestNotification { client, xurn, _unpin: marker::PhantomPinned })
+		let generation = self.generation.get();
+		Ok(XRequestNotification { client, xurn, serial_no: self.serial_no, uid: self.uid, generation, current: self.generation.clone(), attached: true, link, tuning: None, _unpin: marker::PhantomPinned })
 	}
 }
 
+#[cfg(windows)]
 impl<CL: Borrow<Client>> fmt::Debug for Xbox360Wired<CL> {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		f.debug_struct("Xbox360Wired")
 			.field("client", &format_args!("{:?}", self.client.borrow()))
 			.field("event", &format_args!("{:?}", self.event))
+			.field("uid", &self.uid)
 			.field("serial_no", &self.serial_no)
 			.field("vendor_id", &self.id.vendor)
 			.field("product_id", &self.id.product)
+			.field("watch", &self.watch.is_some())
+			.field("panic_guard", &self.panic_guard.is_some())
+			.field("progress", &self.progress.is_some())
+			.field("rate_limit", &self.rate_limit.as_ref().map(RateLimit::interval))
+			.field("latency", &self.latency.as_ref().map(LatencyStats::len))
 			.finish()
 	}
 }
 
+#[cfg(windows)]
 impl<CL: Borrow<Client>> Drop for Xbox360Wired<CL> {
 	#[inline]
 	fn drop(&mut self) {
-		let _ = self.unplug();
+		let _ = self.unplug_impl(true);
 	}
 }
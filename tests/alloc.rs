@@ -0,0 +1,133 @@
+/*!
+Checks the report types stay allocation free for real-time threads.
+
+The global allocator counts the allocations of the thread inside `no_alloc`, other test threads are unaffected.
+*/
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt::{self, Write};
+use std::time::{Duration, Instant};
+use vigem_client as vigem;
+
+struct CountingAlloc;
+
+thread_local! {
+	static COUNTING: Cell<bool> = const { Cell::new(false) };
+	static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		if COUNTING.with(Cell::get) {
+			ALLOCATIONS.with(|count| count.set(count.get() + 1));
+		}
+		System.alloc(layout)
+	}
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		System.dealloc(ptr, layout)
+	}
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+// Panics if the closure allocates
+fn no_alloc<T>(f: impl FnOnce() -> T) -> T {
+	ALLOCATIONS.with(|count| count.set(0));
+	COUNTING.with(|counting| counting.set(true));
+	let result = f();
+	COUNTING.with(|counting| counting.set(false));
+	let allocations = ALLOCATIONS.with(Cell::get);
+	assert_eq!(allocations, 0, "allocated {} times", allocations);
+	result
+}
+
+// Formats into a fixed size buffer on the stack
+struct StackBuf {
+	buf: [u8; 256],
+	len: usize,
+}
+impl StackBuf {
+	fn new() -> StackBuf {
+		StackBuf { buf: [0; 256], len: 0 }
+	}
+	fn as_str(&self) -> &str {
+		std::str::from_utf8(&self.buf[..self.len]).unwrap()
+	}
+}
+impl Write for StackBuf {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		let end = self.len + s.len();
+		self.buf.get_mut(self.len..end).ok_or(fmt::Error)?.copy_from_slice(s.as_bytes());
+		self.len = end;
+		Ok(())
+	}
+}
+
+#[test]
+fn build_xgamepad() {
+	let gamepad = no_alloc(|| {
+		vigem::XGamepad::pressing(vigem::XButtons!(A|UP))
+			.left_trigger_t(vigem::Trigger::analog(0.5))
+			.right_trigger_t(vigem::Trigger::digital())
+	});
+	let bytes = no_alloc(|| gamepad.to_bytes());
+	assert_eq!(no_alloc(|| vigem::XGamepad::from_bytes(&bytes)), gamepad);
+}
+
+#[test]
+fn apply_filter() {
+	let mut filter = vigem::InputFilter::compensate_xinput_deadzone();
+	filter.axis_matrix = Some(vigem::AxisMatrix::identity().swap_sticks());
+	filter.dpad_rotation = 1;
+	let gamepad = vigem::XGamepad { buttons: vigem::XButtons!(UP), thumb_lx: 100, left_trigger: 1, ..Default::default() };
+
+	let filtered = no_alloc(|| filter.apply(&gamepad));
+	assert_eq!(filtered.buttons, vigem::XButtons!(RIGHT));
+}
+
+#[test]
+fn format_xgamepad() {
+	let gamepad = vigem::XGamepad { buttons: vigem::XButtons!(A|X), left_trigger: 255, ..Default::default() };
+
+	let mut buf = StackBuf::new();
+	no_alloc(|| write!(buf, "{}", gamepad.compact())).unwrap();
+	assert_eq!(buf.as_str(), "A+X | LT:255 RT:0 | L(0,0) R(0,0)");
+
+	let mut buf = StackBuf::new();
+	no_alloc(|| write!(buf, "{:#?}", gamepad.buttons)).unwrap();
+	assert_eq!(buf.as_str(), "A|X");
+
+	let mut buf = StackBuf::new();
+	no_alloc(|| write!(buf, "{:?}", gamepad)).unwrap();
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn build_and_format_ds4() {
+	let report = no_alloc(|| {
+		vigem::DS4Report::pressing(vigem::DS4Buttons { raw: vigem::DS4Buttons::CROSS })
+			.with_dpad(vigem::DpadDirection::NorthEast)
+			.right_trigger_t(vigem::Trigger::analog(1.0))
+	});
+	assert_eq!(no_alloc(|| report.validate()), Ok(()));
+
+	let mut buf = StackBuf::new();
+	no_alloc(|| write!(buf, "{}", report.compact())).unwrap();
+	assert_eq!(buf.as_str(), "↗+Cross+R2 | LT:0 RT:255 | L(128,128) R(128,128)");
+
+	let mut buf = StackBuf::new();
+	no_alloc(|| write!(buf, "{:?}", report)).unwrap();
+}
+
+#[test]
+fn rate_limit() {
+	let mut limit = vigem::RateLimit::new(100.0);
+	let start = Instant::now();
+	no_alloc(|| {
+		assert_eq!(limit.offer(vigem::XGamepad::default(), start), Some(vigem::XGamepad::default()));
+		assert_eq!(limit.offer(vigem::XGamepad::default(), start + Duration::from_millis(1)), None);
+		assert_eq!(limit.poll(start + Duration::from_millis(10)), Some(vigem::XGamepad::default()));
+	});
+}
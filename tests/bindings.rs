@@ -0,0 +1,98 @@
+use std::collections::HashSet;
+use vigem_client as vigem;
+use vigem::{Axis, Bindings};
+
+fn active(actions: &[&str]) -> HashSet<String> {
+	actions.iter().map(|action| action.to_string()).collect()
+}
+
+#[test]
+fn buttons_or_together() {
+	let mut bindings = Bindings::new();
+	bindings.bind_button("Jump", vigem::XButtons!(A));
+	bindings.bind_button("Jump", vigem::XButtons!(LB));
+	bindings.bind_button("Menu", vigem::XButtons!(START));
+	bindings.bind_button("Map", vigem::XButtons!(BACK));
+
+	let mut gamepad = vigem::XGamepad { buttons: vigem::XButtons!(Y), ..Default::default() };
+	bindings.apply(&active(&["Jump", "Menu", "Unbound"]), &mut gamepad);
+	assert_eq!(gamepad.buttons, vigem::XButtons!(A | LB | START | Y));
+
+	let mut gamepad = vigem::XGamepad::default();
+	bindings.apply(&active(&[]), &mut gamepad);
+	assert_eq!(gamepad, vigem::XGamepad::default());
+}
+
+#[test]
+fn axes_set_deflection() {
+	let mut bindings = Bindings::new();
+	bindings.bind_axis("Left", Axis::LX, -1.0);
+	bindings.bind_axis("Right", Axis::LX, 1.0);
+	bindings.bind_axis("Walk", Axis::LY, 0.5);
+	bindings.bind_axis("Fire", Axis::RT, 2.0);
+	bindings.bind_axis("Brake", Axis::LT, -1.0);
+
+	let mut gamepad = vigem::XGamepad { left_trigger: 100, ..Default::default() };
+	bindings.apply(&active(&["Left", "Walk", "Fire", "Brake"]), &mut gamepad);
+	assert_eq!((gamepad.thumb_lx, gamepad.thumb_ly), (-i16::MAX, 16384));
+	assert_eq!((gamepad.left_trigger, gamepad.right_trigger), (0, 255));
+
+	// Later bindings override earlier ones on the same axis
+	let mut gamepad = vigem::XGamepad::default();
+	bindings.apply(&active(&["Left", "Right"]), &mut gamepad);
+	assert_eq!(gamepad.thumb_lx, i16::MAX);
+}
+
+#[test]
+fn unbind() {
+	let mut bindings = Bindings::new();
+	bindings.bind_button("Jump", vigem::XButtons!(A));
+	bindings.bind_axis("Jump", Axis::LY, 1.0);
+	bindings.bind_button("Menu", vigem::XButtons!(START));
+	assert_eq!(bindings.get("Jump").count(), 2);
+	bindings.unbind("Jump");
+	assert_eq!(bindings.get("Jump").count(), 0);
+	assert_eq!(bindings.len(), 1);
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4_apply() {
+	use vigem::{DS4Button, DpadDirection};
+	let mut bindings = Bindings::new();
+	bindings.bind_ds4("Jump", DS4Button::Buttons(0x0020));
+	bindings.bind_ds4("Jump", DS4Button::Buttons(0x0100 | 0x0005));
+	bindings.bind_ds4("Home", DS4Button::Special(1));
+	bindings.bind_ds4("Up", DS4Button::Dpad(DpadDirection::North));
+	bindings.bind_ds4("Right", DS4Button::Dpad(DpadDirection::East));
+	bindings.bind_ds4("Down", DS4Button::Dpad(DpadDirection::South));
+	bindings.bind_button("Xbox", vigem::XButtons!(A));
+	bindings.bind_axis("Forward", Axis::LY, 1.0);
+
+	let mut report = vigem::DS4Report::neutral();
+	bindings.apply_ds4(&active(&["Jump", "Home", "Up", "Right", "Xbox", "Forward"]), &mut report);
+	// The dpad nibble of button bindings is ignored, the dpad directions combine
+	assert_eq!(report.buttons, 0x0120 | DpadDirection::NorthEast as u16);
+	assert_eq!(report.special, 1);
+	assert_eq!(report.thumb_ly, 1);
+
+	// Opposite directions cancel out
+	let mut report = vigem::DS4Report::neutral();
+	bindings.apply_ds4(&active(&["Up", "Down"]), &mut report);
+	assert_eq!(report.buttons, DpadDirection::None as u16);
+
+	// Dpad directions pressed in the report combine with the bindings
+	let mut report = vigem::DS4Report { buttons: DpadDirection::West as u16, ..vigem::DS4Report::neutral() };
+	bindings.apply_ds4(&active(&["Down"]), &mut report);
+	assert_eq!(report.buttons, DpadDirection::SouthWest as u16);
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4_ignored_by_xbox() {
+	let mut bindings = Bindings::new();
+	bindings.bind_ds4("Jump", vigem::DS4Button::Buttons(0x0020));
+	let mut gamepad = vigem::XGamepad::default();
+	bindings.apply(&active(&["Jump"]), &mut gamepad);
+	assert_eq!(gamepad, vigem::XGamepad::default());
+}
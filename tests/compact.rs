@@ -0,0 +1,35 @@
+use vigem_client as vigem;
+
+#[test]
+fn neutral() {
+	assert_eq!(vigem::XGamepad::neutral().compact().to_string(), "- | LT:0 RT:0 | L(0,0) R(0,0)");
+}
+
+#[test]
+fn everything_pressed() {
+	let gamepad = vigem::XGamepad {
+		buttons: vigem::XButtons(0xffff),
+		left_trigger: 255,
+		right_trigger: 255,
+		thumb_lx: 1,
+		thumb_ly: 2,
+		thumb_rx: 3,
+		thumb_ry: 4,
+	};
+	assert_eq!(gamepad.compact().to_string(),
+		"UP+DOWN+LEFT+RIGHT+START+BACK+LTHUMB+RTHUMB+LB+RB+GUIDE+?+A+B+X+Y | LT:255 RT:255 | L(1,2) R(3,4)");
+}
+
+#[test]
+fn extreme_axes() {
+	let gamepad = vigem::XGamepad {
+		buttons: vigem::XButtons!(A|X),
+		left_trigger: 255,
+		right_trigger: 0,
+		thumb_lx: i16::MIN,
+		thumb_ly: i16::MAX,
+		thumb_rx: i16::MAX,
+		thumb_ry: i16::MIN,
+	};
+	assert_eq!(gamepad.compact().to_string(), "A+X | LT:255 RT:0 | L(-32768,32767) R(32767,-32768)");
+}
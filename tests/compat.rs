@@ -0,0 +1,69 @@
+#![cfg(windows)]
+
+use vigem_client as vigem;
+use vigem::compat::*;
+
+// Connects to the driver, returns `None` if it is not installed
+fn connect() -> Option<vigem::Client> {
+	match vigem::Client::connect() {
+		Ok(client) => Some(client),
+		Err(vigem::Error::BusNotFound | vigem::Error::BusDisabled) => {
+			eprintln!("skipped: ViGEmBus is not installed");
+			None
+		},
+		Err(err) => panic!("failed to connect to ViGEmBus: {}", err),
+	}
+}
+
+#[test]
+fn simple_success() {
+	let Some(client) = connect() else { return };
+	let mut target = target_x360_alloc(&client);
+
+	target_add(&client, &mut target).unwrap();
+	let result = target_x360_update(&client, &mut target, XUSB_REPORT::default());
+	assert_eq!(result, Ok(()));
+
+	target_remove(&client, &mut target).unwrap();
+}
+
+#[test]
+fn not_plugged_in() {
+	let Some(client) = connect() else { return };
+	let mut target = target_x360_alloc(&client);
+
+	assert_eq!(target_x360_update(&client, &mut target, XUSB_REPORT::default()), Err(vigem::Error::NotPluggedIn));
+	assert_eq!(target_remove(&client, &mut target), Err(vigem::Error::NotPluggedIn));
+}
+
+#[test]
+fn already_connected() {
+	let Some(client) = connect() else { return };
+	let mut target = target_x360_alloc(&client);
+
+	target_add(&client, &mut target).unwrap();
+	assert_eq!(target_add(&client, &mut target), Err(vigem::Error::AlreadyConnected));
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4_success() {
+	let Some(client) = connect() else { return };
+	let mut target = target_ds4_alloc(&client);
+
+	target_add(&client, &mut target).unwrap();
+	assert_eq!(target_ds4_update(&client, &mut target, DS4_REPORT::default()), Ok(()));
+	target_remove(&client, &mut target).unwrap();
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn register_notification() {
+	let Some(client) = connect() else { return };
+	let mut target = target_x360_alloc(&client);
+	target_add(&client, &mut target).unwrap();
+
+	let thread = target_x360_register_notification(&client, &mut target, |_large, _small, _led| {}).unwrap();
+	target_remove(&client, &mut target).unwrap();
+	thread.join().unwrap();
+}
@@ -0,0 +1,144 @@
+use vigem_client as vigem;
+use vigem::{CrossMap, DS4Button, DS4Buttons, DS4Report, DS4SpecialButtons, DpadDirection, TriggerPolicy, XButtons, XGamepad};
+
+fn gamepads() -> Vec<XGamepad> {
+	let mut gamepads = Vec::new();
+	for bit in 0..16 {
+		gamepads.push(XGamepad { buttons: XButtons { raw: 1 << bit }, ..Default::default() });
+	}
+	for &value in &[i16::MIN, -32767, -12345, -1, 0, 1, 777, 32766, i16::MAX] {
+		gamepads.push(XGamepad {
+			buttons: vigem::XButtons!(A | UP | RIGHT),
+			left_trigger: value as u8,
+			right_trigger: !value as u8,
+			thumb_lx: value,
+			thumb_ly: !value,
+			thumb_rx: value.wrapping_neg(),
+			thumb_ry: value / 3,
+		});
+	}
+	gamepads
+}
+
+#[test]
+fn default_is_plain_conversion() {
+	let map = CrossMap::default();
+	assert_eq!(map, CrossMap::new());
+	for gamepad in gamepads() {
+		let report = map.convert_x_to_ds4(&gamepad);
+		assert_eq!(report, DS4Report::from(gamepad));
+		assert_eq!(report, DS4Report::from(&gamepad));
+		assert_eq!(map.convert_ds4_to_x(&report), XGamepad::from(report));
+		assert_eq!(map.convert_ds4_to_x(&report), XGamepad::from(&report));
+	}
+}
+
+#[test]
+fn plain_buttons() {
+	let gamepad = XGamepad::pressing(vigem::XButtons!(A | B | X | Y | LB | RB | BACK | START | LTHUMB | RTHUMB | GUIDE));
+	let report = DS4Report::from(gamepad);
+	let expected = DS4Buttons::CROSS | DS4Buttons::CIRCLE | DS4Buttons::SQUARE | DS4Buttons::TRIANGLE
+		| DS4Buttons::L1 | DS4Buttons::R1 | DS4Buttons::SHARE | DS4Buttons::OPTIONS | DS4Buttons::L3 | DS4Buttons::R3;
+	assert_eq!(report.buttons, expected | DpadDirection::None as u16);
+	assert_eq!(report.special, DS4SpecialButtons::PS);
+	assert_eq!(XGamepad::from(report), gamepad);
+}
+
+#[test]
+fn plain_dpad() {
+	let cases = [
+		(vigem::XButtons!(UP), DpadDirection::North),
+		(vigem::XButtons!(UP | RIGHT), DpadDirection::NorthEast),
+		(vigem::XButtons!(DOWN | LEFT), DpadDirection::SouthWest),
+		(vigem::XButtons!(UP | DOWN), DpadDirection::None),
+		(vigem::XButtons!(), DpadDirection::None),
+	];
+	for &(buttons, direction) in &cases {
+		let report = DS4Report::from(XGamepad::pressing(buttons));
+		assert_eq!(report.dpad(), Ok(direction));
+	}
+	// Opposite directions cancel out and do not come back
+	assert_eq!(XGamepad::from(DS4Report::from(XGamepad::pressing(vigem::XButtons!(UP | DOWN | LEFT)))).buttons, vigem::XButtons!(LEFT));
+}
+
+#[test]
+fn plain_sticks() {
+	let gamepad = XGamepad { thumb_lx: i16::MAX, thumb_ly: i16::MAX, thumb_rx: -i16::MAX, thumb_ry: 0, ..Default::default() };
+	let report = DS4Report::from(gamepad);
+	// The vertical axes point down on the DualShock4
+	assert_eq!((report.thumb_lx, report.thumb_ly, report.thumb_rx, report.thumb_ry), (255, 1, 1, 128));
+	assert_eq!(XGamepad::from(report), gamepad);
+	assert_eq!(XGamepad::from(DS4Report { thumb_lx: 0, ..DS4Report::neutral() }).thumb_lx, -i16::MAX);
+}
+
+#[test]
+fn plain_triggers() {
+	let threshold = vigem::consts::TRIGGER_THRESHOLD;
+	let report = DS4Report::from(XGamepad { left_trigger: threshold, right_trigger: threshold + 1, ..Default::default() });
+	assert_eq!((report.trigger_l, report.trigger_r), (threshold, threshold + 1));
+	assert_eq!(report.buttons & (DS4Buttons::L2 | DS4Buttons::R2), DS4Buttons::R2);
+
+	// A digital press raises the Xbox trigger past the threshold
+	let report = DS4Report { buttons: DS4Buttons::L2 | DpadDirection::None as u16, ..DS4Report::neutral() };
+	assert_eq!(XGamepad::from(report).left_trigger, threshold + 1);
+}
+
+#[test]
+fn trigger_policies() {
+	let gamepad = XGamepad { left_trigger: 1, ..Default::default() };
+	let pressed = |triggers| {
+		let map = CrossMap { triggers, ..CrossMap::new() };
+		map.convert_x_to_ds4(&gamepad).buttons & DS4Buttons::L2 != 0
+	};
+	assert!(!pressed(TriggerPolicy::Threshold));
+	assert!(pressed(TriggerPolicy::AnyPull));
+	assert!(!pressed(TriggerPolicy::AnalogOnly));
+
+	let report = DS4Report { buttons: DS4Buttons::L2 | DpadDirection::None as u16, ..DS4Report::neutral() };
+	let map = CrossMap { triggers: TriggerPolicy::AnyPull, ..CrossMap::new() };
+	assert_eq!(map.convert_ds4_to_x(&report).left_trigger, 1);
+	let map = CrossMap { triggers: TriggerPolicy::AnalogOnly, ..CrossMap::new() };
+	assert_eq!(map.convert_ds4_to_x(&report).left_trigger, 0);
+}
+
+#[test]
+fn swapped_buttons() {
+	let map = CrossMap::new().swap(DS4Button::Buttons(DS4Buttons::CROSS), DS4Button::Buttons(DS4Buttons::CIRCLE));
+	let report = map.convert_x_to_ds4(&XGamepad::pressing(vigem::XButtons!(A)));
+	assert_eq!(report.buttons & !0xF, DS4Buttons::CIRCLE);
+	let report = map.convert_x_to_ds4(&XGamepad::pressing(vigem::XButtons!(B)));
+	assert_eq!(report.buttons & !0xF, DS4Buttons::CROSS);
+
+	let report = DS4Report::pressing(DS4Buttons::new(DS4Buttons::CROSS));
+	assert_eq!(map.convert_ds4_to_x(&report).buttons, vigem::XButtons!(B));
+	assert_eq!(XGamepad::from(report).buttons, vigem::XButtons!(A));
+}
+
+#[test]
+fn mapped_buttons() {
+	let map = CrossMap::new()
+		.map(vigem::XButtons!(BACK), Some(DS4Button::Special(DS4SpecialButtons::TOUCHPAD)))
+		.map(vigem::XButtons!(GUIDE), None);
+	let report = map.convert_x_to_ds4(&XGamepad::pressing(vigem::XButtons!(BACK | GUIDE)));
+	assert_eq!(report.special, DS4SpecialButtons::TOUCHPAD);
+	assert_eq!(report.buttons & !0xF, 0);
+	assert_eq!(map.convert_ds4_to_x(&report).buttons, vigem::XButtons!(BACK));
+}
+
+#[test]
+fn swapped_sticks() {
+	let gamepad = XGamepad { thumb_lx: i16::MAX, thumb_ry: i16::MAX, ..Default::default() };
+	let map = CrossMap::new().swap_sticks();
+	let report = map.convert_x_to_ds4(&gamepad);
+	assert_eq!((report.thumb_lx, report.thumb_ly, report.thumb_rx, report.thumb_ry), (128, 1, 255, 128));
+	assert_eq!(map.convert_ds4_to_x(&report), gamepad);
+}
+
+#[test]
+fn inverted_axes() {
+	let gamepad = XGamepad { thumb_lx: i16::MAX, thumb_ly: i16::MAX, ..Default::default() };
+	let map = CrossMap { invert: [true, true, false, false], ..CrossMap::new() };
+	let report = map.convert_x_to_ds4(&gamepad);
+	assert_eq!((report.thumb_lx, report.thumb_ly), (1, 255));
+	assert_eq!(map.convert_ds4_to_x(&report), gamepad);
+}
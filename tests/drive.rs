@@ -0,0 +1,123 @@
+use std::time::{Duration, Instant};
+use vigem_client as vigem;
+use vigem::{ClockSource, DriveStop, PacedDriver, Pump};
+
+// Advances a simulated clock by a fixed step every tick
+struct FakeClock {
+	now: Instant,
+	step: Duration,
+}
+
+impl ClockSource for FakeClock {
+	fn wait_next_tick(&mut self) {
+		self.now += self.step;
+	}
+	fn now(&self) -> Instant {
+		self.now
+	}
+}
+
+#[derive(Default)]
+struct Recorder {
+	reports: Vec<u32>,
+	fail: Option<vigem::Error>,
+}
+
+impl vigem::Submit<u32> for Recorder {
+	fn submit_report(&mut self, report: &u32) -> Result<(), vigem::Error> {
+		if let Some(err) = self.fail {
+			return Err(err);
+		}
+		self.reports.push(*report);
+		Ok(())
+	}
+}
+
+#[test]
+fn runs_until_none() {
+	let mut clock = FakeClock { now: Instant::now(), step: Duration::from_millis(10) };
+	let mut elapsed = Vec::new();
+	let mut driver = PacedDriver::new(Recorder::default(), 100.0, |t: Duration| {
+		elapsed.push(t);
+		(t < Duration::from_millis(50)).then_some(t.as_millis() as u32)
+	});
+	let summary = driver.run(&mut clock);
+
+	assert_eq!(summary.ticks, 6);
+	assert_eq!(summary.submitted, 5);
+	assert_eq!(summary.elapsed, Duration::from_millis(50));
+	assert_eq!(summary.stop, Some(DriveStop::Finished));
+	assert_eq!(driver.target().reports, [0, 10, 20, 30, 40]);
+	drop(driver);
+	assert_eq!(elapsed, (0..6).map(|i| Duration::from_millis(10 * i)).collect::<Vec<_>>());
+}
+
+#[test]
+fn stops_on_hard_error() {
+	let mut clock = FakeClock { now: Instant::now(), step: Duration::from_millis(10) };
+	let target = Recorder { fail: Some(vigem::Error::NotPluggedIn), ..Default::default() };
+	let mut driver = PacedDriver::new(target, 100.0, |_| Some(1));
+	let summary = driver.run(&mut clock);
+
+	assert_eq!(summary.ticks, 1);
+	assert_eq!(summary.submitted, 0);
+	assert_eq!(summary.stop, Some(DriveStop::Failed(vigem::Error::NotPluggedIn)));
+	assert!(!driver.tick(clock.now));
+	assert_eq!(driver.summary().ticks, 1);
+}
+
+#[test]
+fn continues_when_not_ready() {
+	let mut clock = FakeClock { now: Instant::now(), step: Duration::from_millis(10) };
+	let mut count = 0;
+	let mut driver = PacedDriver::new(Recorder::default(), 100.0, |_| {
+		count += 1;
+		(count <= 4).then_some(count)
+	});
+	driver.target_mut().fail = Some(vigem::Error::TargetNotReady);
+	clock.wait_next_tick();
+	assert!(driver.tick(clock.now));
+	assert!(driver.tick(clock.now));
+	driver.target_mut().fail = None;
+	let summary = driver.run(&mut clock);
+
+	assert_eq!(summary.ticks, 5);
+	assert_eq!(summary.not_ready, 2);
+	assert_eq!(summary.submitted, 2);
+	assert_eq!(driver.into_target().reports, [3, 4]);
+}
+
+#[test]
+fn pumps_at_the_rate() {
+	let start = Instant::now();
+	let mut driver = PacedDriver::new(Recorder::default(), 100.0, |t: Duration| {
+		(t < Duration::from_millis(100)).then_some(t.as_millis() as u32)
+	});
+	assert_eq!(driver.interval(), Duration::from_millis(10));
+
+	// Pumped every millisecond, ticks every 10ms
+	for ms in 0..60 {
+		driver.pump(start + Duration::from_millis(ms)).unwrap();
+	}
+	assert_eq!(driver.summary().ticks, 6);
+	assert_eq!(driver.target().reports, [0, 10, 20, 30, 40, 50]);
+	assert_eq!(driver.next_tick(), Some(start + Duration::from_millis(60)));
+
+	// Missed ticks are skipped
+	driver.pump(start + Duration::from_millis(95)).unwrap();
+	assert_eq!(driver.next_tick(), Some(start + Duration::from_millis(105)));
+	driver.pump(start + Duration::from_millis(105)).unwrap();
+	assert!(driver.is_finished());
+	assert_eq!(driver.next_tick(), None);
+	assert_eq!(driver.target().reports, [0, 10, 20, 30, 40, 50, 95]);
+}
+
+#[test]
+fn pump_returns_hard_error_once() {
+	let start = Instant::now();
+	let target = Recorder { fail: Some(vigem::Error::OperationAborted), ..Default::default() };
+	let mut driver = PacedDriver::new(target, 100.0, |_| Some(0));
+	assert_eq!(driver.pump(start), Err(vigem::Error::OperationAborted));
+	assert_eq!(driver.pump(start + Duration::from_secs(1)), Ok(()));
+	assert_eq!(driver.summary().ticks, 1);
+}
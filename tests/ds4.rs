@@ -0,0 +1,994 @@
+use vigem_client as vigem;
+
+#[test]
+fn validate_dpad() {
+	for dpad in 0..=8 {
+		let report = vigem::DS4Report { buttons: 0xFFF0 | dpad, ..Default::default() };
+		assert_eq!(report.validate(), Ok(()), "dpad {}", dpad);
+	}
+	for dpad in 9..=0xF {
+		let report = vigem::DS4Report { buttons: 0xFFF0 | dpad, ..Default::default() };
+		assert_eq!(report.validate(), Err(vigem::DS4ValidationError::Dpad(dpad as u8)));
+	}
+}
+
+#[test]
+fn validate_free_fields() {
+	// Sticks, triggers and the special byte accept every value
+	for value in 0..=255 {
+		let report = vigem::DS4Report {
+			thumb_lx: value,
+			thumb_ly: value,
+			thumb_rx: value,
+			thumb_ry: value,
+			special: value,
+			trigger_l: value,
+			trigger_r: value,
+			..Default::default()
+		};
+		assert_eq!(report.validate(), Ok(()));
+	}
+}
+
+#[test]
+fn validate_error() {
+	let error = vigem::Error::from(vigem::DS4ValidationError::Dpad(0xA));
+	assert_eq!(error, vigem::Error::InvalidReport(vigem::DS4ValidationError::Dpad(0xA)));
+	assert_eq!(error.to_string(), "invalid report: dpad out of range: 0xa");
+}
+
+#[test]
+fn dpad_rotate() {
+	use vigem::DpadDirection::*;
+	const CW: [vigem::DpadDirection; 8] = [North, NorthEast, East, SouthEast, South, SouthWest, West, NorthWest];
+	for (index, &dir) in CW.iter().enumerate() {
+		for turns in 0..4u8 {
+			assert_eq!(dir.rotated_cw(turns), CW[(index + turns as usize * 2) % 8]);
+			// Full turns wrap around
+			assert_eq!(dir.rotated_cw(turns + 4), dir.rotated_cw(turns));
+		}
+	}
+	for turns in 0..4 {
+		assert_eq!(None.rotated_cw(turns), None);
+	}
+}
+
+#[test]
+fn dpad_mirror() {
+	use vigem::DpadDirection::*;
+	let table = [
+		// direction, horizontal, vertical, both
+		(North, North, South, South),
+		(NorthEast, NorthWest, SouthEast, SouthWest),
+		(East, West, East, West),
+		(SouthEast, SouthWest, NorthEast, NorthWest),
+		(South, South, North, North),
+		(SouthWest, SouthEast, NorthWest, NorthEast),
+		(West, East, West, East),
+		(NorthWest, NorthEast, SouthWest, SouthEast),
+		(None, None, None, None),
+	];
+	for &(dir, horizontal, vertical, both) in &table {
+		assert_eq!(dir.mirrored(false, false), dir);
+		assert_eq!(dir.mirrored(true, false), horizontal);
+		assert_eq!(dir.mirrored(false, true), vertical);
+		assert_eq!(dir.mirrored(true, true), both);
+		// Both mirrors equal a half turn
+		assert_eq!(dir.mirrored(true, true), dir.rotated_cw(2));
+	}
+}
+
+#[test]
+fn dpad_raw() {
+	use std::convert::TryFrom;
+	for &dir in &vigem::DpadDirection::ALL {
+		assert_eq!(vigem::DpadDirection::try_from(u16::from(dir)), Ok(dir));
+	}
+	assert_eq!(vigem::DpadDirection::try_from(9), Err(vigem::DS4ValidationError::Dpad(9)));
+}
+
+#[test]
+fn dpad_report() {
+	for &dir in &vigem::DpadDirection::ALL {
+		let mut report = vigem::DS4Report { buttons: 0xFFF0 | 0xF, ..Default::default() };
+		report.set_dpad(dir);
+		assert_eq!(report.buttons, 0xFFF0 | dir as u16);
+		assert_eq!(report.dpad_raw(), dir as u16);
+		assert_eq!(report.dpad(), Ok(dir));
+		assert!(format!("{:?}", report).contains(&format!("dpad: {:?}", dir)));
+	}
+	for raw in [0x9, 0xB, 0xF] {
+		let report = vigem::DS4Report { buttons: 0x0020 | raw, ..Default::default() };
+		assert_eq!(report.dpad_raw(), raw);
+		assert_eq!(report.dpad(), Err(vigem::DS4ValidationError::Dpad(raw as u8)));
+		assert!(format!("{:?}", report).contains(&format!("dpad: Invalid({:#X})", raw)));
+	}
+}
+
+#[test]
+fn report_bytes() {
+	let bytes = [0x00, 0xff, 0x80, 0x7f, 0x28, 0x01, 0x03, 0x40, 0xc0, 0xaa];
+	let report = vigem::DS4Report::from_bytes(&bytes);
+	assert_eq!(report.thumb_lx, 0x00);
+	assert_eq!(report.thumb_ly, 0xff);
+	assert_eq!(report.thumb_rx, 0x80);
+	assert_eq!(report.thumb_ry, 0x7f);
+	assert_eq!(report.buttons, 0x0128);
+	assert_eq!(report.special, 0x03);
+	assert_eq!(report.trigger_l, 0x40);
+	assert_eq!(report.trigger_r, 0xc0);
+	assert_eq!(report.validate(), Ok(()));
+
+	// The padding byte is ignored and encoded as zero
+	let mut expected = bytes;
+	expected[9] = 0;
+	assert_eq!(report.to_bytes(), expected);
+}
+
+#[test]
+fn touch_timestamp_wraps() {
+	let mut timestamper = vigem::DS4TouchTimestamper::new(255);
+	assert_eq!(timestamper.next(), 255);
+	assert_eq!(timestamper.next(), 0);
+	assert_eq!(timestamper.next(), 1);
+
+	let mut timestamper = vigem::DS4TouchTimestamper::default();
+	for i in 0..1000u32 {
+		assert_eq!(timestamper.next(), i as u8);
+	}
+}
+
+#[test]
+fn touch_timestamp_frame() {
+	let mut timestamper = vigem::DS4TouchTimestamper::new(10);
+	timestamper.next();
+	timestamper.next();
+	timestamper.next();
+
+	// Three distinct timestamps, most recent first
+	let frame = timestamper.frame();
+	assert_eq!(frame, [12, 11, 10]);
+	assert!(frame[0] != frame[1] && frame[1] != frame[2] && frame[0] != frame[2]);
+
+	// Ages increase along the frame, also across the wraparound
+	let mut timestamper = vigem::DS4TouchTimestamper::new(255);
+	timestamper.next();
+	timestamper.next();
+	timestamper.next();
+	let frame = timestamper.frame();
+	assert_eq!(frame, [1, 0, 255]);
+	assert_eq!(frame[0].wrapping_sub(frame[1]), 1);
+	assert_eq!(frame[1].wrapping_sub(frame[2]), 1);
+}
+
+#[test]
+fn compact_neutral() {
+	assert_eq!(vigem::DS4Report::neutral().compact().to_string(), "- | LT:0 RT:0 | L(128,128) R(128,128)");
+}
+
+#[test]
+fn compact_everything_pressed() {
+	let report = vigem::DS4Report {
+		thumb_lx: 0,
+		thumb_ly: 255,
+		thumb_rx: 255,
+		thumb_ry: 0,
+		buttons: 0xfff7,
+		special: 0x3,
+		trigger_l: 255,
+		trigger_r: 255,
+	};
+	assert_eq!(report.compact().to_string(),
+		"↖+Square+Cross+Circle+Triangle+L1+R1+L2+R2+Share+Options+L3+R3+PS+Touchpad | LT:255 RT:255 | L(0,255) R(255,0)");
+}
+
+#[test]
+fn compact_dpad() {
+	let glyphs = ["↑", "↗", "→", "↘", "↓", "↙", "←", "↖"];
+	for (dpad, glyph) in glyphs.iter().enumerate() {
+		let report = vigem::DS4Report { buttons: dpad as u16, ..Default::default() };
+		assert_eq!(report.compact().to_string(), format!("{} | LT:0 RT:0 | L(128,128) R(128,128)", glyph));
+	}
+	let report = vigem::DS4Report { buttons: 0x20 | 0xB, ..Default::default() };
+	assert_eq!(report.compact().to_string(), "?+Cross | LT:0 RT:0 | L(128,128) R(128,128)");
+}
+
+// USB input report 0x01 at rest: sticks centered, dpad released, report counter 0x2c
+const USB_NEUTRAL: [u8; 64] = [
+	0x01, 0x80, 0x7f, 0x81, 0x80, 0x08, 0x00, 0xb0, 0x00, 0x00, 0x3e, 0xa1, 0x10, 0x07, 0x00, 0xf9,
+	0xff, 0x03, 0x00, 0x8c, 0xff, 0xe8, 0x1f, 0x6a, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1b, 0x00,
+	0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00,
+	0x80, 0x00, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x80, 0x00,
+];
+
+#[test]
+fn parse_usb_neutral() {
+	let report = vigem::DS4Report::from_usb_input_report(&USB_NEUTRAL).unwrap();
+	assert_eq!(report, vigem::DS4Report { thumb_ly: 0x7f, thumb_rx: 0x81, ..vigem::DS4Report::neutral() });
+}
+
+#[test]
+fn parse_usb_pressed() {
+	// Cross and R1 held with the dpad east, PS pressed, left stick pushed up and L2 fully pulled
+	let mut data = USB_NEUTRAL;
+	data[2] = 0x00;
+	data[5] = 0x22;
+	data[6] = 0x02;
+	data[7] = 0x01 | 0xb0;
+	data[8] = 0xff;
+	let report = vigem::DS4Report::from_usb_input_report(&data).unwrap();
+	assert_eq!(report.thumb_ly, 0x00);
+	assert_eq!(report.buttons, 0x0222);
+	assert_eq!(report.special, 0x01);
+	assert_eq!(report.trigger_l, 0xff);
+	assert_eq!(report.trigger_r, 0x00);
+	assert_eq!(report.validate(), Ok(()));
+}
+
+#[test]
+fn parse_bluetooth() {
+	// Full Bluetooth report 0x11: two header bytes precede the USB layout, followed by a CRC tail
+	let mut data = [0u8; 78];
+	data[0] = 0x11;
+	data[1] = 0xc0;
+	data[3..3 + 9].copy_from_slice(&[0x10, 0x20, 0x30, 0x40, 0x15, 0x30, 0x06, 0x40, 0x50]);
+	let report = vigem::DS4Report::from_usb_input_report(&data).unwrap();
+	assert_eq!(report, vigem::DS4Report {
+		thumb_lx: 0x10, thumb_ly: 0x20, thumb_rx: 0x30, thumb_ry: 0x40,
+		buttons: 0x3015, special: 0x02, trigger_l: 0x40, trigger_r: 0x50,
+	});
+
+	// Short Bluetooth report 0x01 before the full mode is enabled
+	let short = [0x01, 0x10, 0x20, 0x30, 0x40, 0x15, 0x30, 0x06, 0x40, 0x50];
+	assert_eq!(vigem::DS4Report::from_usb_input_report(&short), Ok(report));
+}
+
+#[test]
+fn parse_errors() {
+	use vigem::DS4ParseError;
+	assert_eq!(vigem::DS4Report::from_usb_input_report(&[]), Err(DS4ParseError::Length(0)));
+	assert_eq!(vigem::DS4Report::from_usb_input_report(&USB_NEUTRAL[..9]), Err(DS4ParseError::Length(9)));
+	assert_eq!(vigem::DS4Report::from_usb_input_report(&[0x11; 64]), Err(DS4ParseError::Length(64)));
+	assert_eq!(vigem::DS4Report::from_usb_input_report(&[0x05; 64]), Err(DS4ParseError::ReportId(0x05)));
+}
+
+#[test]
+fn touch_report_roundtrip() {
+	let point = vigem::DS4TouchPoint { id: 0x45, x: 1919, y: 941 };
+	let report = vigem::DS4TouchReport::new(3, None, Some(point));
+	assert_eq!(report.is_up_tracking_num1, 0x80);
+	assert_eq!(report.is_up_tracking_num2, 0x45);
+	assert_eq!(report.touch_data2, [0x7f, 0xd7, 0x3a]);
+	assert_eq!(report.point1(), None);
+	assert_eq!(report.point2(), Some(point));
+}
+
+#[test]
+fn touchpad_clamps_edges() {
+	use vigem::{TouchpadMapper, TouchpadMode};
+	let mut mapper = TouchpadMapper::new(TouchpadMode::Absolute { scale: 2.0 });
+	mapper.feed(0.75, -0.75, true);
+	let point = mapper.frame().unwrap().point1().unwrap();
+	assert_eq!((point.x, point.y), (vigem::DS4TouchReport::WIDTH - 1, 0));
+
+	let mut mapper = TouchpadMapper::new(TouchpadMode::Relative { sensitivity: 500.0 });
+	for _ in 0..10 {
+		mapper.feed(-1.0, 1.0, true);
+	}
+	let point = mapper.frame().unwrap().point1().unwrap();
+	assert_eq!((point.x, point.y), (0, vigem::DS4TouchReport::HEIGHT - 1));
+}
+
+#[test]
+fn touchpad_contact_ids() {
+	use vigem::{TouchpadMapper, TouchpadMode};
+	let mut mapper = TouchpadMapper::new(TouchpadMode::Absolute { scale: 1.0 });
+	mapper.feed(0.0, 0.0, true);
+	let first = mapper.frame().unwrap();
+	mapper.feed(0.5, 0.0, true);
+	let held = mapper.frame().unwrap();
+	assert_eq!(first.point1().unwrap().id, held.point1().unwrap().id);
+	assert_eq!(held.packet_counter, first.packet_counter.wrapping_add(1));
+
+	mapper.feed(0.0, 0.0, false);
+	let lifted = mapper.frame().unwrap();
+	assert_eq!(lifted.is_up_tracking_num1, 0x80 | first.is_up_tracking_num1);
+	assert_eq!(mapper.frame(), None);
+
+	mapper.feed(0.0, 0.0, true);
+	let second = mapper.frame().unwrap();
+	assert_eq!(second.point1().unwrap().id, first.point1().unwrap().id + 1);
+}
+
+#[test]
+fn touchpad_relative_accumulates() {
+	use vigem::{TouchpadMapper, TouchpadMode};
+	let mut mapper = TouchpadMapper::new(TouchpadMode::Relative { sensitivity: 10.0 });
+	mapper.feed(1.0, 0.5, true);
+	mapper.feed(1.0, 0.5, true);
+	let point = mapper.frame().unwrap().point1().unwrap();
+	assert_eq!((point.x, point.y), (960 + 20, 471 + 10));
+
+	// Position is kept between presses
+	mapper.feed(0.0, 0.0, false);
+	mapper.frame();
+	mapper.feed(-1.0, 0.0, true);
+	let point = mapper.frame().unwrap().point1().unwrap();
+	assert_eq!((point.x, point.y), (960 + 10, 471 + 10));
+}
+
+#[test]
+fn presets() {
+	use vigem::{DpadDirection, DS4Buttons, DS4Report};
+
+	let pressing = |raw| DS4Report { buttons: 0x8 | raw, ..DS4Report::neutral() };
+	assert_eq!(DS4Report::PRESS_CROSS, pressing(DS4Buttons::CROSS));
+	assert_eq!(DS4Report::PRESS_CIRCLE, pressing(DS4Buttons::CIRCLE));
+	assert_eq!(DS4Report::PRESS_SQUARE, pressing(DS4Buttons::SQUARE));
+	assert_eq!(DS4Report::PRESS_TRIANGLE, pressing(DS4Buttons::TRIANGLE));
+	assert_eq!(DS4Report::PRESS_OPTIONS, pressing(DS4Buttons::OPTIONS));
+	assert_eq!(DS4Report::PRESS_SHARE, pressing(DS4Buttons::SHARE));
+	assert_eq!(DS4Report::PRESS_L1, pressing(DS4Buttons::L1));
+	assert_eq!(DS4Report::PRESS_R1, pressing(DS4Buttons::R1));
+
+	let dpad = |direction| {
+		let mut report = DS4Report::neutral();
+		report.set_dpad(direction);
+		report
+	};
+	assert_eq!(DS4Report::DPAD_UP, dpad(DpadDirection::North));
+	assert_eq!(DS4Report::DPAD_DOWN, dpad(DpadDirection::South));
+	assert_eq!(DS4Report::DPAD_LEFT, dpad(DpadDirection::West));
+	assert_eq!(DS4Report::DPAD_RIGHT, dpad(DpadDirection::East));
+}
+
+#[test]
+fn pressing() {
+	use vigem::{DpadDirection, DS4Buttons, DS4Report};
+
+	let buttons = DS4Buttons { raw: DS4Buttons::CROSS | DS4Buttons::OPTIONS };
+	let report = DS4Report::pressing(buttons);
+	assert_eq!(report.buttons, 0x8 | 0x2020);
+	assert_eq!(report.dpad(), Ok(DpadDirection::None));
+	assert_eq!((report.thumb_lx, report.thumb_ly, report.trigger_l), (0x80, 0x80, 0));
+
+	// The dpad nibble of the buttons is ignored
+	assert_eq!(DS4Buttons::new(0x2025).raw, 0x2020);
+	assert_eq!(DS4Report::pressing(DS4Buttons { raw: 0x0027 }), DS4Report::PRESS_CROSS);
+
+	// Builders combine
+	let report = DS4Report::DPAD_DOWN.with_buttons(buttons);
+	assert_eq!(report.buttons, 0x2020 | DpadDirection::South as u16);
+}
+
+#[test]
+fn report_debug() {
+	let report = vigem::DS4Report {
+		thumb_lx: 0,
+		thumb_ry: 255,
+		buttons: 0x0021,
+		special: 0x15,
+		trigger_r: 200,
+		..Default::default()
+	};
+	assert_eq!(format!("{:?}", report),
+		"DS4Report { thumb_lx: 0, thumb_ly: 128, thumb_rx: 128, thumb_ry: 255, buttons: 0x0021, dpad: NorthEast, \
+		special: 0x01, counter: 5, trigger_l: 0, trigger_r: 200 }");
+	assert_eq!(format!("{:#?}", report), "\
+DS4Report {
+    thumb_lx: 0,
+    thumb_ly: 128,
+    thumb_rx: 128,
+    thumb_ry: 255,
+    buttons: 0x0021,
+    dpad: NorthEast,
+    special: 0x01,
+    counter: 5,
+    trigger_l: 0,
+    trigger_r: 200,
+}");
+}
+
+#[test]
+fn trigger_builder() {
+	use vigem::{DS4Buttons, DS4Report, Trigger};
+
+	let report = DS4Report::neutral()
+		.left_trigger_t(Trigger::digital())
+		.right_trigger_t(Trigger::analog(0.1));
+	assert_eq!((report.trigger_l, report.buttons & DS4Buttons::L2 != 0), (255, true));
+	assert_eq!((report.trigger_r, report.buttons & DS4Buttons::R2 != 0), (26, false));
+
+	// Releasing the trigger clears the button, other buttons and the dpad are kept
+	let report = DS4Report::PRESS_CROSS.with_buttons(DS4Buttons { raw: DS4Buttons::L2 }).left_trigger_t(Trigger::off());
+	assert_eq!(report.trigger_l, 0);
+	assert_eq!(report.buttons, DS4Buttons::CROSS | 0x8);
+}
+
+#[test]
+fn buttons_display() {
+	use vigem::{DpadDirection, DS4Buttons};
+
+	assert_eq!(DS4Buttons::default().to_string(), "");
+	assert_eq!(DS4Buttons { raw: DS4Buttons::CROSS | DS4Buttons::R1 }.to_string(), "CROSS|R1");
+	assert_eq!(DS4Buttons { raw: DS4Buttons::R3 | DS4Buttons::SQUARE }.to_string(), "SQUARE|R3");
+	assert_eq!(DS4Buttons { raw: DS4Buttons::CROSS }.with_dpad(DpadDirection::South).to_string(), "CROSS|DPAD_SOUTH");
+	assert_eq!(DS4Buttons::default().with_dpad(DpadDirection::NorthWest).to_string(), "DPAD_NORTHWEST");
+
+	// The released dpad is implied
+	assert_eq!(DS4Buttons { raw: DS4Buttons::L1 }.with_dpad(DpadDirection::None).to_string(), "L1");
+	assert_eq!(DS4Buttons::new(0x0107).dpad(), Some(DpadDirection::None));
+
+	// Nibbles not holding a direction print raw
+	assert_eq!(DS4Buttons { raw: DS4Buttons::L1 | 0xB }.to_string(), "L1|0xB");
+	assert_eq!(DS4Buttons { raw: 0xB }.dpad(), None);
+}
+
+#[test]
+fn buttons_parse() {
+	use vigem::{DpadDirection, DS4Buttons, DS4ButtonsParseError};
+
+	let names = [
+		("SQUARE", DS4Buttons::SQUARE), ("CROSS", DS4Buttons::CROSS), ("CIRCLE", DS4Buttons::CIRCLE), ("TRIANGLE", DS4Buttons::TRIANGLE),
+		("L1", DS4Buttons::L1), ("R1", DS4Buttons::R1), ("L2", DS4Buttons::L2), ("R2", DS4Buttons::R2),
+		("SHARE", DS4Buttons::SHARE), ("OPTIONS", DS4Buttons::OPTIONS), ("L3", DS4Buttons::L3), ("R3", DS4Buttons::R3),
+	];
+	for (name, raw) in names {
+		assert_eq!(name.parse::<DS4Buttons>(), Ok(DS4Buttons { raw }));
+		assert_eq!(name.to_lowercase().parse::<DS4Buttons>(), Ok(DS4Buttons { raw }));
+	}
+	let directions = [
+		("DPAD_NORTH", DpadDirection::North), ("DPAD_NORTHEAST", DpadDirection::NorthEast),
+		("DPAD_EAST", DpadDirection::East), ("DPAD_SOUTHEAST", DpadDirection::SouthEast),
+		("DPAD_SOUTH", DpadDirection::South), ("DPAD_SOUTHWEST", DpadDirection::SouthWest),
+		("DPAD_WEST", DpadDirection::West), ("DPAD_NORTHWEST", DpadDirection::NorthWest),
+		("DPAD_NONE", DpadDirection::None),
+	];
+	for (name, direction) in directions {
+		let buttons: DS4Buttons = name.parse().unwrap();
+		assert_eq!(buttons.dpad(), Some(direction));
+		assert_eq!(buttons.raw & !0xF, 0);
+	}
+
+	let buttons: DS4Buttons = " cross | R1|dpad_south ".parse().unwrap();
+	assert_eq!(buttons, DS4Buttons { raw: DS4Buttons::CROSS | DS4Buttons::R1 }.with_dpad(DpadDirection::South));
+	assert_eq!("".parse(), Ok(DS4Buttons::default()));
+	assert_eq!("CROSS|CROSS".parse(), Ok(DS4Buttons { raw: DS4Buttons::CROSS }));
+	assert_eq!("L1|0xB".parse(), Ok(DS4Buttons { raw: DS4Buttons::L1 | 0xB }));
+
+	// Malformed input names the offending token
+	let err = "CROSS|X".parse::<DS4Buttons>().unwrap_err();
+	assert!(matches!(&err, DS4ButtonsParseError::UnknownToken { token, .. } if token == "X"));
+	assert!(err.to_string().starts_with("unknown button \"X\", expected one of: SQUARE, CROSS,"));
+	assert!(err.to_string().ends_with("DPAD_NORTHWEST, DPAD_NONE"));
+	assert!(matches!("CROSS||R1".parse::<DS4Buttons>(), Err(DS4ButtonsParseError::UnknownToken { token, .. }) if token.is_empty()));
+	assert!(matches!("0x10".parse::<DS4Buttons>(), Err(DS4ButtonsParseError::UnknownToken { .. })));
+	assert_eq!("DPAD_NORTH|CROSS|DPAD_NONE".parse::<DS4Buttons>(), Err(DS4ButtonsParseError::MultipleDpad { token: "DPAD_NONE".into() }));
+}
+
+#[test]
+fn buttons_round_trip() {
+	use vigem::{DpadDirection, DS4Buttons};
+
+	for flags in 0..0x1000u16 {
+		for direction in DpadDirection::ALL {
+			let buttons = DS4Buttons::new(flags << 4).with_dpad(direction);
+			assert_eq!(buttons.to_string().parse(), Ok(buttons));
+		}
+	}
+	for raw in 0..=0xFFFFu16 {
+		let buttons = DS4Buttons { raw };
+		assert_eq!(buttons.to_string().parse(), Ok(buttons));
+	}
+}
+
+#[test]
+fn special_buttons() {
+	use vigem::{DS4Report, DS4SpecialButtons};
+
+	assert_eq!(DS4SpecialButtons::default().to_string(), "");
+	assert_eq!(DS4SpecialButtons { raw: DS4SpecialButtons::PS }.to_string(), "PS_HOME");
+	assert_eq!(DS4SpecialButtons { raw: DS4SpecialButtons::TOUCHPAD }.to_string(), "TOUCHPAD");
+	assert_eq!(DS4SpecialButtons { raw: 0x3 }.to_string(), "PS_HOME|TOUCHPAD");
+	assert_eq!(DS4SpecialButtons { raw: 0x15 }.to_string(), "PS_HOME|0x14");
+	assert_eq!(DS4SpecialButtons::new(0x15).raw, 0x1);
+
+	assert_eq!("ps_home | Touchpad".parse(), Ok(DS4SpecialButtons { raw: 0x3 }));
+	assert_eq!("".parse(), Ok(DS4SpecialButtons::default()));
+	let err = "PS".parse::<DS4SpecialButtons>().unwrap_err();
+	assert_eq!(err.to_string(), "unknown button \"PS\", expected one of: PS_HOME, TOUCHPAD");
+
+	for raw in 0..=0xFFu8 {
+		let buttons = DS4SpecialButtons { raw };
+		assert_eq!(buttons.to_string().parse(), Ok(buttons));
+	}
+
+	// The report counter is kept
+	let report = DS4Report { special: 0x14, ..DS4Report::neutral() };
+	assert_eq!(report.with_special(DS4SpecialButtons { raw: 0xFF }).special, 0x17);
+}
+
+#[test]
+fn raw_passthrough() {
+	use vigem::{DS4Buttons, DS4Report, DS4SpecialButtons};
+
+	// Every bit survives the builder and the report untouched
+	for raw in [0x0000, 0x000B, 0x0128, 0xFFFF] {
+		let buttons = DS4Buttons::from_raw(raw);
+		assert_eq!(buttons.raw(), raw);
+		let report = DS4Report::neutral().buttons_raw(buttons.raw());
+		assert_eq!(report.buttons, raw);
+		assert_eq!(DS4Buttons::from_raw(report.buttons), buttons);
+		assert_eq!(DS4Report::from_bytes(&report.to_bytes()), report);
+	}
+	for raw in [0x00, 0x04, 0x83, 0xFF] {
+		let special = DS4SpecialButtons::from_raw(raw);
+		assert_eq!(special.raw(), raw);
+		let report = DS4Report::neutral().special_raw(special.raw());
+		assert_eq!(report.special, raw);
+		assert_eq!(DS4SpecialButtons::from_raw(report.special), special);
+	}
+
+	// The builders do not validate, the report does
+	assert_eq!(DS4Report::neutral().buttons_raw(0x000B).validate(), Err(vigem::DS4ValidationError::Dpad(0xB)));
+	assert_eq!(DS4Buttons::new(0xFFFF).raw(), 0xFFF0);
+	assert_eq!(DS4SpecialButtons::new(0xFF).raw(), 0x3);
+}
+
+#[test]
+fn raw_debug() {
+	use vigem::{DS4Buttons, DS4SpecialButtons};
+
+	assert_eq!(format!("{:?}", DS4Buttons::from_raw(0x0128)), "DS4Buttons { raw: 0x0128 }");
+	assert_eq!(format!("{:?}", DS4Buttons::from_raw(0x012B)), "DS4Buttons { raw: 0x012B, unknown: 0xB }");
+	assert_eq!(format!("{:?}", DS4SpecialButtons::from_raw(0x03)), "DS4SpecialButtons { raw: 0x03 }");
+	assert_eq!(format!("{:?}", DS4SpecialButtons::from_raw(0x85)), "DS4SpecialButtons { raw: 0x85, unknown: 0x84 }");
+}
+
+#[test]
+fn report_ex_neutral() {
+	let report = vigem::DS4ReportEx::default();
+	assert_eq!(report, vigem::DS4ReportEx::neutral());
+	assert_eq!(report.report(), vigem::DS4Report::neutral());
+	assert_eq!(report.battery(), vigem::BatteryStatus::WIRED_FULL);
+	assert_eq!(report.gyro(), [0; 3]);
+	assert_eq!(report.accel(), [0; 3]);
+	assert_eq!(report.touch_packets_n, 0);
+	for touch in report.touch_frame() {
+		assert_eq!((touch.point1(), touch.point2()), (None, None));
+	}
+}
+
+#[test]
+fn report_ex_basic_part() {
+	let basic = vigem::DS4Report::PRESS_CROSS.with_dpad(vigem::DpadDirection::West);
+	let report = vigem::DS4ReportEx { timestamp: 1000, gyro_y: -300, touch_packets_n: 1, ..Default::default() };
+
+	// Replacing the basic part keeps the other fields
+	let merged = report.with_report(&basic);
+	assert_eq!(merged.report(), basic);
+	assert_eq!({ merged.timestamp }, 1000);
+	assert_eq!({ merged.gyro_y }, -300);
+	assert_eq!(merged.touch_packets_n, 1);
+
+	let mut set = report;
+	set.set_report(&basic);
+	assert_eq!(set, merged);
+
+	// Converting from the basic report fills in the neutral extended fields
+	assert_eq!(vigem::DS4ReportEx::from(basic), vigem::DS4ReportEx::neutral().with_report(&basic));
+	assert_eq!(vigem::DS4Report::from(merged), basic);
+}
+
+#[test]
+fn battery_status() {
+	use vigem::BatteryStatus;
+	for level in 0..=10 {
+		let status = BatteryStatus::new(level, false);
+		assert_eq!((status.level(), status.percent()), (level, level * 10));
+		assert!(!status.is_cable() && !status.is_charging());
+		assert!(BatteryStatus::new(level, true).is_charging());
+	}
+	let full = BatteryStatus::new(BatteryStatus::LEVEL_FULL, true);
+	assert_eq!(full, BatteryStatus::WIRED_FULL);
+	assert_eq!((full.raw, full.percent()), (0x1B, 100));
+	assert!(full.is_cable() && !full.is_charging());
+
+	// The high nibble holds more than the cable bit
+	let raw = BatteryStatus::from_raw(0x68);
+	assert_eq!((raw.level(), raw.is_cable()), (8, false));
+	assert_eq!(BatteryStatus::new(0x3F, false).raw, 0x0F);
+}
+
+#[test]
+fn validate_ex_basic_part() {
+	let report = vigem::DS4ReportEx::from(vigem::DS4Report { buttons: 0xFFF9, ..Default::default() });
+	assert_eq!(report.validate(), Err(vigem::DS4ValidationError::Dpad(9)));
+	let report = vigem::DS4ReportEx::from(vigem::DS4Report { buttons: 0xFFF8, ..Default::default() });
+	assert_eq!(report.validate(), Ok(()));
+}
+
+#[test]
+fn validate_ex_battery() {
+	for raw in 0..=255u8 {
+		let report = vigem::DS4ReportEx { battery_lvl_special: raw, ..Default::default() };
+		let expected = if raw & 0xF <= 11 { Ok(()) } else { Err(vigem::DS4ValidationError::Battery(raw & 0xF)) };
+		assert_eq!(report.validate(), expected, "status {:#x}", raw);
+	}
+}
+
+#[test]
+fn validate_ex_touch_packets() {
+	for count in 0..=3 {
+		let report = vigem::DS4ReportEx { touch_packets_n: count, ..Default::default() };
+		assert_eq!(report.validate(), Ok(()));
+	}
+	for count in [4, 5, 0x80, 0xFF] {
+		let report = vigem::DS4ReportEx { touch_packets_n: count, ..Default::default() };
+		assert_eq!(report.validate(), Err(vigem::DS4ValidationError::TouchPackets(count)));
+	}
+}
+
+#[test]
+fn validate_ex_touch_points() {
+	use vigem::{DS4ReportEx, DS4TouchPoint, DS4TouchReport, DS4ValidationError};
+	let touch = |x, y| DS4TouchReport::new(0, None, Some(DS4TouchPoint { id: 1, x, y }));
+	let last = (DS4TouchReport::WIDTH - 1, DS4TouchReport::HEIGHT - 1);
+
+	// The corners of the touchpad are valid in every valid packet
+	for index in 0..3 {
+		for (x, y) in [(0, 0), (last.0, 0), (0, last.1), last] {
+			let mut report = DS4ReportEx { touch_packets_n: 3, ..Default::default() };
+			match index {
+				0 => report.current_touch = touch(x, y),
+				_ => report.previous_touch = { let mut previous = report.previous_touch; previous[index - 1] = touch(x, y); previous },
+			}
+			assert_eq!(report.validate(), Ok(()), "packet {} at {},{}", index, x, y);
+		}
+	}
+
+	let report = DS4ReportEx { touch_packets_n: 1, current_touch: touch(DS4TouchReport::WIDTH, 0), ..Default::default() };
+	assert_eq!(report.validate(), Err(DS4ValidationError::TouchX(1920)));
+	let report = DS4ReportEx { touch_packets_n: 1, current_touch: touch(0, DS4TouchReport::HEIGHT), ..Default::default() };
+	assert_eq!(report.validate(), Err(DS4ValidationError::TouchY(942)));
+	let report = DS4ReportEx { touch_packets_n: 3, previous_touch: [touch(0, 0), touch(4095, 0)], ..Default::default() };
+	assert_eq!(report.validate(), Err(DS4ValidationError::TouchX(4095)));
+
+	// Packets beyond the count and contacts not touching are not checked
+	let report = DS4ReportEx { touch_packets_n: 1, previous_touch: [touch(4095, 4095); 2], ..Default::default() };
+	assert_eq!(report.validate(), Ok(()));
+	let mut up = touch(4095, 4095);
+	up.is_up_tracking_num2 |= 0x80;
+	let report = DS4ReportEx { touch_packets_n: 1, current_touch: up, ..Default::default() };
+	assert_eq!(report.validate(), Ok(()));
+}
+
+#[test]
+fn validate_ex_free_fields() {
+	// The timestamp and the motion sensors accept every value
+	for value in [i16::MIN, -1, 0, 1, i16::MAX] {
+		let report = vigem::DS4ReportEx {
+			timestamp: value as u16,
+			battery_lvl: value as u8,
+			gyro_x: value, gyro_y: value, gyro_z: value,
+			accel_x: value, accel_y: value, accel_z: value,
+			..Default::default()
+		};
+		assert_eq!(report.validate(), Ok(()));
+	}
+}
+
+#[test]
+fn validate_ex_error_display() {
+	use vigem::DS4ValidationError;
+	assert_eq!(DS4ValidationError::Battery(12).to_string(), "battery level out of range: 12");
+	assert_eq!(DS4ValidationError::TouchPackets(4).to_string(), "touch packet count out of range: 4");
+	assert_eq!(DS4ValidationError::TouchX(1920).to_string(), "touch x out of range: 1920");
+	assert_eq!(DS4ValidationError::TouchY(942).to_string(), "touch y out of range: 942");
+}
+
+#[test]
+fn report_ex_bytes() {
+	let mut bytes = [0u8; 63];
+	for (i, byte) in bytes.iter_mut().enumerate() {
+		*byte = i as u8;
+	}
+	let report = vigem::DS4ReportEx::from_bytes(&bytes);
+	assert_eq!(report.report(), vigem::DS4Report::from_bytes(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 0]));
+	assert_eq!({ report.timestamp }, 0x0a09);
+	assert_eq!(report.battery_lvl, 11);
+	assert_eq!(report.gyro(), [0x0d0c, 0x0f0e, 0x1110]);
+	assert_eq!(report.accel(), [0x1312, 0x1514, 0x1716]);
+	assert_eq!(report._unknown1, [24, 25, 26, 27, 28]);
+	assert_eq!(report.battery_lvl_special, 29);
+	assert_eq!(report._unknown2, [30, 31]);
+	assert_eq!(report.touch_packets_n, 32);
+	let [current, previous1, previous2] = report.touch_frame();
+	assert_eq!(current.to_bytes(), [33, 34, 35, 36, 37, 38, 39, 40, 41]);
+	assert_eq!(previous1.to_bytes(), [42, 43, 44, 45, 46, 47, 48, 49, 50]);
+	assert_eq!(previous2.to_bytes(), [51, 52, 53, 54, 55, 56, 57, 58, 59]);
+	assert_eq!(report._padding, [60, 61, 62]);
+
+	// Every byte round trips, including the reserved and padding bytes
+	assert_eq!(report.to_bytes(), bytes);
+}
+
+#[test]
+fn report_ex_bytes_layout() {
+	// The bytes are the in-memory layout of the packed struct on little-endian targets
+	let point = vigem::DS4TouchPoint { id: 5, x: 1919, y: 941 };
+	let report = vigem::DS4ReportEx {
+		timestamp: 0xBEEF,
+		gyro_x: -2, accel_z: 8192,
+		battery_lvl_special: vigem::BatteryStatus::new(7, true).raw,
+		touch_packets_n: 1,
+		current_touch: vigem::DS4TouchReport::new(0x42, Some(point), None),
+		..vigem::DS4Report::PRESS_CIRCLE.into()
+	};
+	let bytes = report.to_bytes();
+	if cfg!(target_endian = "little") {
+		assert_eq!(bytes, unsafe { std::mem::transmute::<vigem::DS4ReportEx, [u8; 63]>(report) });
+	}
+	assert_eq!(&bytes[9..11], [0xEF, 0xBE]);
+	assert_eq!(&bytes[12..14], [0xFE, 0xFF]);
+	assert_eq!(&bytes[22..24], [0x00, 0x20]);
+	assert_eq!(bytes[29], 0x17);
+	assert_eq!(bytes[32], 1);
+	assert_eq!(&bytes[33..42], [0x42, 0x05, 0x7F, 0xD7, 0x3A, 0x80, 0, 0, 0]);
+
+	// Decoding reads the same values back through the accessors
+	let decoded = vigem::DS4ReportEx::from_bytes(&bytes);
+	assert_eq!(decoded, report);
+	assert_eq!(decoded.report(), vigem::DS4Report::PRESS_CIRCLE);
+	assert_eq!(decoded.battery(), vigem::BatteryStatus::new(7, true));
+	assert_eq!(decoded.touch_frame()[0].point1(), Some(point));
+	assert_eq!(decoded.validate(), Ok(()));
+}
+
+#[test]
+fn touch_report_bytes() {
+	let bytes = [0x10, 0x01, 0x00, 0x10, 0x00, 0x82, 0xFF, 0xFF, 0xFF];
+	let touch = vigem::DS4TouchReport::from_bytes(&bytes);
+	assert_eq!(touch.packet_counter, 0x10);
+	assert_eq!(touch.point1(), Some(vigem::DS4TouchPoint { id: 1, x: 0, y: 1 }));
+	assert_eq!(touch.point2(), None);
+	assert_eq!(touch.to_bytes(), bytes);
+}
+
+#[test]
+fn report_ex_builder() {
+	let report = vigem::DS4ReportEx::builder()
+		.report(&vigem::DS4Report::PRESS_TRIANGLE)
+		.timestamp(0x1234)
+		.battery(vigem::BatteryStatus::new(4, false))
+		.gyro([1, -2, 3])
+		.accel([-4, 5, -6])
+		.build();
+	assert_eq!(report.report(), vigem::DS4Report::PRESS_TRIANGLE);
+	assert_eq!({ report.timestamp }, 0x1234);
+	assert_eq!(report.battery(), vigem::BatteryStatus::new(4, false));
+	assert_eq!(report.gyro(), [1, -2, 3]);
+	assert_eq!(report.accel(), [-4, 5, -6]);
+	assert_eq!(report.touch_packets_n, 0);
+	assert_eq!(vigem::DS4ReportExBuilder::new().build(), vigem::DS4ReportEx::neutral());
+}
+
+#[test]
+fn report_ex_builder_touch_frame() {
+	let points = [
+		[Some(vigem::DS4TouchPoint { id: 1, x: 10, y: 20 }), None],
+		[Some(vigem::DS4TouchPoint { id: 1, x: 11, y: 21 }), None],
+		[Some(vigem::DS4TouchPoint { id: 1, x: 12, y: 22 }), Some(vigem::DS4TouchPoint { id: 2, x: 500, y: 600 })],
+	];
+	let mut timestamper = vigem::DS4TouchTimestamper::new(40);
+	let report = vigem::DS4ReportEx::builder().touch_reports_with(&mut timestamper, &points).build();
+	assert_eq!(report.touch_packets_n, 3);
+
+	// Three distinct timestamps, most recent first, with the most recent contacts
+	let frame = report.touch_frame();
+	assert_eq!(frame.map(|touch| touch.packet_counter), [42, 41, 40]);
+	assert_eq!(frame.map(|touch| touch.packet_counter), timestamper.frame());
+	assert_eq!(frame.map(|touch| [touch.point1(), touch.point2()]), [points[2], points[1], points[0]]);
+	assert_eq!(report.validate(), Ok(()));
+
+	// The next report continues the counters
+	let report = vigem::DS4ReportEx::builder().touch_reports_with(&mut timestamper, &points[..1]).build();
+	assert_eq!(report.touch_packets_n, 1);
+	assert_eq!(report.current_touch.packet_counter, 43);
+}
+
+#[test]
+fn report_ex_builder_touch_wraps() {
+	let point = [Some(vigem::DS4TouchPoint { id: 9, x: 100, y: 100 }), None];
+	let mut timestamper = vigem::DS4TouchTimestamper::new(254);
+	let report = vigem::DS4ReportEx::builder().touch_reports_with(&mut timestamper, &[point; 3]).build();
+	assert_eq!(report.touch_frame().map(|touch| touch.packet_counter), [0, 255, 254]);
+
+	let report = vigem::DS4ReportEx::builder().touch_reports_with(&mut timestamper, &[point; 2]).build();
+	assert_eq!(report.touch_packets_n, 2);
+	assert_eq!(report.touch_frame()[..2].iter().map(|touch| touch.packet_counter).collect::<Vec<_>>(), [2, 1]);
+
+	// The report timestamp is left alone
+	assert_eq!({ report.timestamp }, 0);
+}
+
+#[test]
+fn report_ex_builder_touch_reports() {
+	let touch = vigem::DS4TouchReport::new(7, Some(vigem::DS4TouchPoint { id: 3, x: 1, y: 2 }), None);
+	let report = vigem::DS4ReportEx::builder().touch_reports(&[touch]).build();
+	assert_eq!(report.touch_packets_n, 1);
+	assert_eq!(report.current_touch, touch);
+
+	// An empty frame clears the touch packets
+	let report = vigem::DS4ReportEx::builder().touch_reports(&[touch, touch]).touch_reports(&[]).build();
+	assert_eq!(report, vigem::DS4ReportEx::neutral());
+}
+
+#[test]
+#[should_panic]
+fn report_ex_builder_touch_overflow() {
+	let mut timestamper = vigem::DS4TouchTimestamper::default();
+	let _ = vigem::DS4ReportEx::builder().touch_reports_with(&mut timestamper, &[[None, None]; 4]);
+}
+
+#[test]
+fn parse_ex_usb_neutral() {
+	let report = vigem::DS4ReportEx::from_usb_input_report(&USB_NEUTRAL).unwrap();
+	assert_eq!(report.report(), vigem::DS4Report { thumb_ly: 0x7f, thumb_rx: 0x81, special: 0xb0, ..vigem::DS4Report::neutral() });
+	assert_eq!({ report.timestamp }, 0xa13e);
+	assert_eq!(report.battery_lvl, 0x10);
+	// Lying flat on the desk, gravity along y
+	assert_eq!(report.gyro(), [7, -7, 3]);
+	assert_eq!(report.accel(), [-116, 8168, 1130]);
+	assert_eq!(report.battery(), vigem::BatteryStatus::WIRED_FULL);
+	assert_eq!(report.touch_packets_n, 0);
+	for touch in report.touch_frame() {
+		assert_eq!((touch.point1(), touch.point2()), (None, None));
+	}
+	assert_eq!(report.validate(), Ok(()));
+	assert_eq!(report.to_bytes()[..], USB_NEUTRAL[1..]);
+}
+
+#[test]
+fn parse_ex_usb_touching() {
+	// One finger resting at 1000,500 on a charging controller at 60%
+	let mut data = USB_NEUTRAL;
+	data[30] = 0x16;
+	data[33] = 0x01;
+	data[34..43].copy_from_slice(&[0x2a, 0x05, 0xe8, 0x43, 0x1f, 0x80, 0x00, 0x00, 0x00]);
+	let report = vigem::DS4ReportEx::from_usb_input_report(&data).unwrap();
+	assert_eq!(report.battery(), vigem::BatteryStatus::new(6, true));
+	assert!(report.battery().is_charging());
+	assert_eq!(report.touch_packets_n, 1);
+	assert_eq!(report.current_touch.packet_counter, 0x2a);
+	assert_eq!(report.current_touch.point1(), Some(vigem::DS4TouchPoint { id: 5, x: 1000, y: 500 }));
+	assert_eq!(report.current_touch.point2(), None);
+	assert_eq!(report.validate(), Ok(()));
+}
+
+#[test]
+fn parse_ex_bluetooth() {
+	// Full Bluetooth report 0x11: two header bytes precede the USB layout, followed by a CRC tail
+	let mut data = [0u8; 78];
+	data[0] = 0x11;
+	data[1] = 0xc0;
+	data[3..66].copy_from_slice(&USB_NEUTRAL[1..]);
+	data[74..78].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+	let report = vigem::DS4ReportEx::from_usb_input_report(&data).unwrap();
+	assert_eq!(report, vigem::DS4ReportEx::from_usb_input_report(&USB_NEUTRAL).unwrap());
+
+	// A fourth touch packet does not fit the report
+	data[35] = 4;
+	let report = vigem::DS4ReportEx::from_usb_input_report(&data).unwrap();
+	assert_eq!(report.touch_packets_n, 3);
+
+	// The short report only carries the basic report, the counter is kept
+	let short = [0x01, 0x10, 0x20, 0x30, 0x40, 0x15, 0x30, 0x06 | 0xfc, 0x40, 0x50];
+	let report = vigem::DS4ReportEx::from_usb_input_report(&short).unwrap();
+	assert_eq!(report, vigem::DS4ReportEx::from(vigem::DS4Report {
+		thumb_lx: 0x10, thumb_ly: 0x20, thumb_rx: 0x30, thumb_ry: 0x40,
+		buttons: 0x3015, special: 0xfe, trigger_l: 0x40, trigger_r: 0x50,
+	}));
+}
+
+#[test]
+fn parse_ex_errors() {
+	use vigem::{DS4ParseError, DS4ReportEx};
+	assert_eq!(DS4ReportEx::from_usb_input_report(&[]), Err(DS4ParseError::Length(0)));
+	assert_eq!(DS4ReportEx::from_usb_input_report(&USB_NEUTRAL[..9]), Err(DS4ParseError::Length(9)));
+	assert_eq!(DS4ReportEx::from_usb_input_report(&[0x11; 64]), Err(DS4ParseError::Length(64)));
+	assert_eq!(DS4ReportEx::from_usb_input_report(&[0x05; 64]), Err(DS4ParseError::ReportId(0x05)));
+}
+
+fn debug_report() -> vigem::DS4ReportEx {
+	let point = |id, x, y| Some(vigem::DS4TouchPoint { id, x, y });
+	vigem::DS4ReportEx {
+		timestamp: 1000,
+		gyro_x: 1, gyro_y: -2, gyro_z: 3,
+		accel_y: 8192,
+		battery_lvl_special: vigem::BatteryStatus::new(6, true).raw,
+		touch_packets_n: 2,
+		current_touch: vigem::DS4TouchReport::new(42, point(5, 1000, 500), point(6, 10, 20)),
+		previous_touch: [vigem::DS4TouchReport::new(41, None, None), vigem::DS4TouchReport::new(40, point(4, 1, 2), None)],
+		..vigem::DS4Report { thumb_lx: 0, thumb_ry: 255, buttons: 0x0021, special: 0x15, trigger_r: 200, ..Default::default() }.into()
+	}
+}
+
+#[test]
+fn report_ex_debug() {
+	assert_eq!(format!("{:?}", debug_report()),
+		"DS4ReportEx { ↗+Cross+PS | LT:0 RT:200 | L(0,128) R(128,255), counter: 5, timestamp: 1000, battery: 60% charging, \
+		gyro: [1, -2, 3], accel: [0, 8192, 0], touch: [#42 5@(1000,500) 6@(10,20), #41 -] }");
+
+	// Only the valid touch packets are listed
+	let neutral = vigem::DS4ReportEx { current_touch: debug_report().current_touch, ..Default::default() };
+	assert_eq!(format!("{:?}", neutral),
+		"DS4ReportEx { - | LT:0 RT:0 | L(128,128) R(128,128), counter: 0, timestamp: 0, battery: 100% cable, \
+		gyro: [0, 0, 0], accel: [0, 0, 0], touch: [] }");
+	assert!(!format!("{:?}", neutral).contains('\n'));
+}
+
+#[test]
+fn report_ex_debug_alternate() {
+	assert_eq!(format!("{:#?}", debug_report()), "\
+DS4ReportEx {
+    report: DS4Report {
+        thumb_lx: 0,
+        thumb_ly: 128,
+        thumb_rx: 128,
+        thumb_ry: 255,
+        buttons: 0x0021,
+        dpad: NorthEast,
+        special: 0x01,
+        counter: 5,
+        trigger_l: 0,
+        trigger_r: 200,
+    },
+    timestamp: 1000,
+    battery: 60% charging,
+    gyro: [
+        1,
+        -2,
+        3,
+    ],
+    accel: [
+        0,
+        8192,
+        0,
+    ],
+    touch: [
+        DS4TouchReport {
+            packet_counter: 42,
+            point1: Some(
+                DS4TouchPoint {
+                    id: 5,
+                    x: 1000,
+                    y: 500,
+                },
+            ),
+            point2: Some(
+                DS4TouchPoint {
+                    id: 6,
+                    x: 10,
+                    y: 20,
+                },
+            ),
+        },
+        DS4TouchReport {
+            packet_counter: 41,
+            point1: None,
+            point2: None,
+        },
+    ],
+}");
+}
+
+#[test]
+fn battery_status_display() {
+	use vigem::BatteryStatus;
+	assert_eq!(BatteryStatus::new(4, false).to_string(), "40%");
+	assert_eq!(BatteryStatus::new(6, true).to_string(), "60% charging");
+	assert_eq!(BatteryStatus::new(10, true).to_string(), "100% charging");
+	assert_eq!(BatteryStatus::WIRED_FULL.to_string(), "100% cable");
+	assert_eq!(BatteryStatus::new(0, false).to_string(), "0%");
+}
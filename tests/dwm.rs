@@ -0,0 +1,19 @@
+#![cfg(windows)]
+
+use std::time::{Duration, Instant};
+use vigem_client as vigem;
+use vigem::ClockSource;
+
+#[test]
+#[ignore = "needs an interactive desktop"]
+fn vblank() {
+	let mut scheduler = vigem::SubmitScheduler::new(vigem::DwmVblank::new());
+	let start = Instant::now();
+	for _ in 0..10 {
+		scheduler.clock_mut().wait_next_tick();
+	}
+	// Between 10 ticks at 240Hz and 10 ticks at 30Hz
+	let elapsed = start.elapsed();
+	assert!(elapsed >= Duration::from_millis(30), "{:?}", elapsed);
+	assert!(elapsed <= Duration::from_millis(500), "{:?}", elapsed);
+}
@@ -0,0 +1,58 @@
+//! Compile tests, the feature gated API must be reachable under its feature name.
+
+#![allow(unused_imports)]
+use vigem_client as vigem;
+
+#[cfg(all(windows, feature = "xusb-rumble"))]
+#[test]
+fn xusb_rumble() {
+	let _: fn(&mut vigem::Xbox360Wired<vigem::Client>) -> Result<vigem::XRequestNotification, vigem::Error> = vigem::Xbox360Wired::request_notification;
+	let _ = vigem::XNotification::default();
+}
+
+#[cfg(all(windows, feature = "ds4-output"))]
+#[test]
+fn ds4_output() {
+	let _: fn(&mut vigem::DualShock4Wired<vigem::Client>) -> Result<vigem::DS4RequestNotification, vigem::Error> = vigem::DualShock4Wired::request_notification;
+	let _ = vigem::DS4Notification::default();
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn unstable_ds4() {
+	let _: fn(&mut vigem::DualShock4Wired<vigem::Client>, &vigem::DS4Report) -> Result<(), vigem::Error> = vigem::DualShock4Wired::update;
+	let _ = vigem::TargetId::DUALSHOCK4_WIRED;
+}
+
+#[cfg(feature = "interop")]
+#[test]
+fn interop() {
+	let _ = vigem::XGamepad::from(vigem::GenericGamepadState::default());
+}
+
+#[test]
+fn constants() {
+	assert_eq!(vigem::features::UNSTABLE_DS4, cfg!(feature = "unstable_ds4"));
+	assert_eq!(vigem::features::XUSB_RUMBLE, cfg!(feature = "xusb-rumble"));
+	assert_eq!(vigem::features::DS4_OUTPUT, cfg!(feature = "ds4-output"));
+	assert_eq!(vigem::features::C_COMPAT, cfg!(feature = "c-compat"));
+	assert_eq!(vigem::features::TEST_UTIL, cfg!(feature = "test-util"));
+	assert_eq!(vigem::features::DWM, cfg!(feature = "dwm"));
+	assert_eq!(vigem::features::SERDE, cfg!(feature = "serde"));
+	assert_eq!(vigem::features::DEBUG_HANDLES, cfg!(feature = "debug-handles"));
+	assert_eq!(vigem::features::FFI, cfg!(feature = "ffi"));
+	assert_eq!(vigem::features::INTEROP, cfg!(feature = "interop"));
+}
+
+#[test]
+fn summary() {
+	let summary = vigem::features::summary();
+	assert!(summary.starts_with(concat!("vigem-client ", env!("CARGO_PKG_VERSION"), " (")));
+	assert!(summary.ends_with(')'));
+	for &(name, enabled) in &vigem::features::ALL {
+		assert_eq!(summary.contains(name), enabled, "{}: {}", name, summary);
+	}
+	if vigem::features::ALL.iter().all(|&(_, enabled)| !enabled) {
+		assert!(summary.ends_with("(no features)"), "{}", summary);
+	}
+}
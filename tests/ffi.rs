@@ -0,0 +1,130 @@
+#![cfg(windows)]
+
+use std::{ffi, ptr, thread};
+use vigem_client as vigem;
+use vigem::ffi::*;
+
+fn last_error() -> String {
+	unsafe { ffi::CStr::from_ptr(vigem_rs_last_error_message()) }.to_string_lossy().into_owned()
+}
+
+// Connects to the driver, returns `None` if it is not installed
+fn connect() -> Option<*mut VigemRsClient> {
+	let mut client = ptr::null_mut();
+	match unsafe { vigem_rs_connect(&mut client) } {
+		VIGEM_RS_OK => Some(client),
+		VIGEM_RS_BUS_NOT_FOUND => {
+			assert!(client.is_null());
+			eprintln!("skipped: ViGEmBus is not installed");
+			None
+		},
+		code => panic!("failed to connect to ViGEmBus: {} {}", code, last_error()),
+	}
+}
+
+#[test]
+fn no_error() {
+	// Every thread starts without an error
+	thread::spawn(|| assert_eq!(last_error(), "")).join().unwrap();
+}
+
+#[test]
+fn null_pointers() {
+	unsafe {
+		assert_eq!(vigem_rs_connect(ptr::null_mut()), VIGEM_RS_NULL_POINTER);
+		assert_eq!(last_error(), "client is null");
+		assert_eq!(vigem_rs_x360_create(ptr::null_mut(), ptr::null_mut()), VIGEM_RS_NULL_POINTER);
+		assert_eq!(last_error(), "target is null");
+	}
+}
+
+#[test]
+fn invalid_handles() {
+	unsafe {
+		let bogus = 0x1234usize;
+		let mut target = ptr::dangling_mut::<VigemRsX360>();
+		assert_eq!(vigem_rs_x360_create(bogus as *mut _, &mut target), VIGEM_RS_INVALID_HANDLE);
+		assert!(target.is_null());
+		assert_eq!(last_error(), "invalid client handle");
+
+		assert_eq!(vigem_rs_x360_plugin(ptr::null_mut()), VIGEM_RS_INVALID_HANDLE);
+		assert_eq!(vigem_rs_x360_update(bogus as *mut _, &vigem::XGamepad::default()), VIGEM_RS_INVALID_HANDLE);
+		assert_eq!(vigem_rs_x360_unplug(bogus as *mut _), VIGEM_RS_INVALID_HANDLE);
+		assert_eq!(vigem_rs_x360_destroy(bogus as *mut _), VIGEM_RS_INVALID_HANDLE);
+		assert_eq!(last_error(), "invalid x360 handle");
+		assert_eq!(vigem_rs_client_destroy(bogus as *mut _), VIGEM_RS_INVALID_HANDLE);
+	}
+}
+
+#[test]
+fn x360_lifecycle() {
+	let Some(client) = connect() else { return };
+	unsafe {
+		let mut target = ptr::null_mut();
+		assert_eq!(vigem_rs_x360_create(client, &mut target), VIGEM_RS_OK);
+		assert!(!target.is_null());
+
+		assert_eq!(vigem_rs_x360_update(target, &vigem::XGamepad::default()), VIGEM_RS_NOT_PLUGGED_IN);
+		assert_eq!(last_error(), "not plugged in");
+
+		assert_eq!(vigem_rs_x360_plugin(target), VIGEM_RS_OK);
+		assert_eq!(vigem_rs_x360_update(target, ptr::null()), VIGEM_RS_NULL_POINTER);
+		let gamepad = vigem::XGamepad { left_trigger: 255, ..Default::default() };
+		assert_eq!(vigem_rs_x360_update(target, &gamepad), VIGEM_RS_OK);
+		assert_eq!(vigem_rs_x360_unplug(target), VIGEM_RS_OK);
+
+		assert_eq!(vigem_rs_x360_destroy(target), VIGEM_RS_OK);
+		assert_eq!(vigem_rs_client_destroy(client), VIGEM_RS_OK);
+	}
+}
+
+#[test]
+fn double_destroy() {
+	let Some(client) = connect() else { return };
+	unsafe {
+		let mut target = ptr::null_mut();
+		assert_eq!(vigem_rs_x360_create(client, &mut target), VIGEM_RS_OK);
+
+		assert_eq!(vigem_rs_x360_destroy(target), VIGEM_RS_OK);
+		assert_eq!(vigem_rs_x360_destroy(target), VIGEM_RS_INVALID_HANDLE);
+		assert_eq!(vigem_rs_x360_plugin(target), VIGEM_RS_INVALID_HANDLE);
+
+		assert_eq!(vigem_rs_client_destroy(client), VIGEM_RS_OK);
+		assert_eq!(vigem_rs_client_destroy(client), VIGEM_RS_INVALID_HANDLE);
+	}
+}
+
+#[test]
+fn wrong_kind() {
+	let Some(client) = connect() else { return };
+	unsafe {
+		// A client handle is not a target handle and vice versa
+		assert_eq!(vigem_rs_x360_destroy(client as *mut VigemRsX360), VIGEM_RS_INVALID_HANDLE);
+		let mut target = ptr::null_mut();
+		assert_eq!(vigem_rs_x360_create(client, &mut target), VIGEM_RS_OK);
+		assert_eq!(vigem_rs_client_destroy(target as *mut VigemRsClient), VIGEM_RS_INVALID_HANDLE);
+		#[cfg(feature = "unstable_ds4")]
+		assert_eq!(vigem_rs_ds4_plugin(target as *mut VigemRsDs4), VIGEM_RS_INVALID_HANDLE);
+
+		// The target keeps the client connected
+		assert_eq!(vigem_rs_client_destroy(client), VIGEM_RS_OK);
+		assert_eq!(vigem_rs_x360_plugin(target), VIGEM_RS_OK);
+		assert_eq!(vigem_rs_x360_destroy(target), VIGEM_RS_OK);
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4_lifecycle() {
+	let Some(client) = connect() else { return };
+	unsafe {
+		let mut target = ptr::null_mut();
+		assert_eq!(vigem_rs_ds4_create(client, &mut target), VIGEM_RS_OK);
+		assert_eq!(vigem_rs_ds4_plugin(target), VIGEM_RS_OK);
+		assert_eq!(vigem_rs_ds4_update(target, &vigem::DS4Report::default()), VIGEM_RS_OK);
+		assert_eq!(vigem_rs_ds4_unplug(target), VIGEM_RS_OK);
+		assert_eq!(vigem_rs_ds4_destroy(target), VIGEM_RS_OK);
+		assert_eq!(vigem_rs_ds4_destroy(target), VIGEM_RS_INVALID_HANDLE);
+		assert_eq!(vigem_rs_client_destroy(client), VIGEM_RS_OK);
+	}
+}
@@ -0,0 +1,218 @@
+use vigem_client as vigem;
+
+// Radial deadzone as recommended by the XInput documentation, returns the normalized magnitude.
+fn game_thumb(x: i16, y: i16, deadzone: u16) -> f32 {
+	let magnitude = ((x as f32).powi(2) + (y as f32).powi(2)).sqrt().min(32767.0);
+	if magnitude <= deadzone as f32 {
+		return 0.0;
+	}
+	(magnitude - deadzone as f32) / (32767.0 - deadzone as f32)
+}
+
+fn game_trigger(value: u8, threshold: u8) -> f32 {
+	if value <= threshold {
+		return 0.0;
+	}
+	(value - threshold) as f32 / (255 - threshold) as f32
+}
+
+#[test]
+fn identity() {
+	let gamepad = vigem::XGamepad {
+		buttons: vigem::XButtons!(A | B),
+		left_trigger: 10,
+		right_trigger: 200,
+		thumb_lx: -1200,
+		thumb_ly: 30000,
+		thumb_rx: 5,
+		thumb_ry: i16::MIN,
+	};
+	assert_eq!(vigem::InputFilter::new().apply(&gamepad), gamepad);
+	assert_eq!(vigem::InputFilter::default(), vigem::InputFilter::new());
+}
+
+#[test]
+fn compensate_thumb_recovers_curve() {
+	let filter = vigem::InputFilter::compensate_xinput_deadzone();
+	for &(x, y) in &[(1, 0), (0, -1), (100, 100), (-5000, 2000), (16000, -16000), (32767, 0), (0, i16::MIN), (-23170, -23170)] {
+		let gamepad = vigem::XGamepad { thumb_lx: x, thumb_ly: y, thumb_rx: y, thumb_ry: x, ..Default::default() };
+		let filtered = filter.apply(&gamepad);
+
+		let expected = game_thumb(x, y, 0);
+		let left = game_thumb(filtered.thumb_lx, filtered.thumb_ly, vigem::consts::LEFT_THUMB_DEADZONE);
+		let right = game_thumb(filtered.thumb_rx, filtered.thumb_ry, vigem::consts::RIGHT_THUMB_DEADZONE);
+		assert!((left - expected).abs() < 0.001, "left ({}, {}): {} != {}", x, y, left, expected);
+		assert!((right - expected).abs() < 0.001, "right ({}, {}): {} != {}", x, y, right, expected);
+
+		// Direction is preserved
+		assert_eq!(filtered.thumb_lx.signum(), x.signum());
+		assert_eq!(filtered.thumb_ly.signum(), y.signum());
+	}
+}
+
+#[test]
+fn compensate_thumb_boundary() {
+	let filter = vigem::InputFilter::compensate_xinput_deadzone();
+
+	// Neutral stays neutral
+	let filtered = filter.apply(&vigem::XGamepad::default());
+	assert_eq!(filtered, vigem::XGamepad::default());
+
+	// The smallest deflection lands just past the deadzone
+	let filtered = filter.apply(&vigem::XGamepad { thumb_lx: 1, ..Default::default() });
+	assert_eq!(filtered.thumb_lx, vigem::consts::LEFT_THUMB_DEADZONE as i16 + 1);
+	assert_eq!(filtered.thumb_ly, 0);
+}
+
+#[test]
+fn compensate_trigger_recovers_curve() {
+	let filter = vigem::InputFilter::compensate_xinput_deadzone();
+	let threshold = vigem::consts::TRIGGER_THRESHOLD;
+	for value in 0..=255u8 {
+		let gamepad = vigem::XGamepad { left_trigger: value, right_trigger: value, ..Default::default() };
+		let filtered = filter.apply(&gamepad);
+		assert_eq!(filtered.left_trigger, filtered.right_trigger);
+
+		let expected = value as f32 / 255.0;
+		let actual = game_trigger(filtered.left_trigger, threshold);
+		assert!((actual - expected).abs() <= 0.5 / (255 - threshold) as f32 + 0.001, "{}: {} != {}", value, actual, expected);
+	}
+	assert_eq!(filter.apply(&vigem::XGamepad { left_trigger: 1, ..Default::default() }).left_trigger, threshold + 1);
+	assert_eq!(filter.apply(&vigem::XGamepad { left_trigger: 255, ..Default::default() }).left_trigger, 255);
+}
+
+#[test]
+fn rotate_dpad() {
+	use vigem::XButtons;
+	let cw = [XButtons!(UP), XButtons!(RIGHT), XButtons!(DOWN), XButtons!(LEFT)];
+	for index in 0..4 {
+		for turns in 0..8u8 {
+			let expected = cw[(index + turns as usize) % 4];
+			assert_eq!(cw[index].rotate_dpad_cw(turns), expected);
+			// Other buttons are left alone
+			let others = XButtons!(A | START | GUIDE);
+			assert_eq!(XButtons(cw[index].raw | others.raw).rotate_dpad_cw(turns), XButtons(expected.raw | others.raw));
+		}
+	}
+	// Diagonals rotate as a whole
+	assert_eq!(XButtons!(UP | RIGHT).rotate_dpad_cw(1), XButtons!(RIGHT | DOWN));
+	assert_eq!(XButtons!(DOWN | LEFT).rotate_dpad_cw(3), XButtons!(DOWN | RIGHT));
+}
+
+#[test]
+fn mirror_dpad() {
+	use vigem::XButtons;
+	let all = XButtons!(UP | DOWN | LEFT | RIGHT | B);
+	for &(horizontal, vertical) in &[(false, false), (true, false), (false, true), (true, true)] {
+		assert_eq!(all.mirror_dpad(horizontal, vertical), all);
+		assert_eq!(XButtons!().mirror_dpad(horizontal, vertical), XButtons!());
+	}
+	assert_eq!(XButtons!(UP | LEFT).mirror_dpad(true, false), XButtons!(UP | RIGHT));
+	assert_eq!(XButtons!(UP | LEFT).mirror_dpad(false, true), XButtons!(DOWN | LEFT));
+	assert_eq!(XButtons!(UP | LEFT).mirror_dpad(true, true), XButtons!(DOWN | RIGHT));
+}
+
+#[test]
+fn filter_dpad() {
+	let filter = vigem::InputFilter {
+		dpad_rotation: 1,
+		dpad_mirror_horizontal: true,
+		..vigem::InputFilter::new()
+	};
+	let gamepad = vigem::XGamepad { buttons: vigem::XButtons!(UP | X), ..Default::default() };
+	// Up rotates to right, then mirrors to left
+	assert_eq!(filter.apply(&gamepad).buttons, vigem::XButtons!(LEFT | X));
+}
+
+#[test]
+fn filter_dpad_full_turns() {
+	let gamepad = vigem::XGamepad { buttons: vigem::XButtons!(UP | RIGHT | A), ..Default::default() };
+	for dpad_rotation in [0, 4, 8, 252] {
+		let filter = vigem::InputFilter { dpad_rotation, ..vigem::InputFilter::new() };
+		assert_eq!(filter.apply(&gamepad), gamepad);
+	}
+	// Mirroring alone still applies
+	let filter = vigem::InputFilter { dpad_rotation: 4, dpad_mirror_vertical: true, ..vigem::InputFilter::new() };
+	assert_eq!(filter.apply(&gamepad).buttons, vigem::XButtons!(DOWN | RIGHT | A));
+}
+
+#[test]
+fn axis_matrix_identity() {
+	let matrix = vigem::AxisMatrix::identity();
+	assert_eq!(matrix, vigem::AxisMatrix::default());
+	for value in [i16::MIN, -32767, -12345, -1, 0, 1, 777, 32766, i16::MAX] {
+		let gamepad = vigem::XGamepad {
+			buttons: vigem::XButtons!(A | UP),
+			left_trigger: value as u8,
+			right_trigger: !value as u8,
+			thumb_lx: value,
+			thumb_ly: !value,
+			thumb_rx: value.wrapping_neg(),
+			thumb_ry: value / 3,
+		};
+		assert_eq!(matrix.apply(&gamepad), gamepad);
+	}
+	for value in 0..=255 {
+		let gamepad = vigem::XGamepad { left_trigger: value, right_trigger: 255 - value, ..Default::default() };
+		assert_eq!(matrix.apply(&gamepad), gamepad);
+	}
+}
+
+#[test]
+fn axis_matrix_swap() {
+	let gamepad = vigem::XGamepad {
+		left_trigger: 10, right_trigger: 20,
+		thumb_lx: 100, thumb_ly: 200, thumb_rx: 300, thumb_ry: 400,
+		..Default::default()
+	};
+	let swapped = vigem::AxisMatrix::identity().swap_sticks().apply(&gamepad);
+	assert_eq!(swapped, vigem::XGamepad {
+		left_trigger: 10, right_trigger: 20,
+		thumb_lx: 300, thumb_ly: 400, thumb_rx: 100, thumb_ry: 200,
+		..Default::default()
+	});
+
+	let swapped = vigem::AxisMatrix::identity().swap_axes(vigem::Axis::LX, vigem::Axis::RY).apply(&gamepad);
+	assert_eq!((swapped.thumb_lx, swapped.thumb_ry), (400, 100));
+}
+
+#[test]
+fn axis_matrix_invert() {
+	use vigem::Axis;
+	let matrix = vigem::AxisMatrix::identity().invert_axis(Axis::LY).invert_axis(Axis::RT);
+	let gamepad = vigem::XGamepad { thumb_ly: 1234, right_trigger: 55, ..Default::default() };
+	let inverted = matrix.apply(&gamepad);
+	assert_eq!((inverted.thumb_ly, inverted.right_trigger), (-1234, 200));
+	// Inverting twice is the identity
+	assert_eq!(matrix.invert_axis(Axis::LY).invert_axis(Axis::RT).apply(&gamepad), gamepad);
+}
+
+#[test]
+fn axis_matrix_clamps() {
+	use vigem::Axis;
+	let matrix = vigem::AxisMatrix::identity()
+		.scale_axis(Axis::LX, 4.0)
+		.scale_axis(Axis::LT, 4.0)
+		.mix(Axis::RY, Axis::RX, 1.0);
+	let gamepad = vigem::XGamepad {
+		left_trigger: 100,
+		thumb_lx: -20000,
+		thumb_rx: 30000, thumb_ry: 30000,
+		..Default::default()
+	};
+	let amplified = matrix.apply(&gamepad);
+	assert_eq!(amplified.thumb_lx, i16::MIN);
+	assert_eq!(amplified.left_trigger, 255);
+	assert_eq!(amplified.thumb_rx, 30000);
+	assert_eq!(amplified.thumb_ry, i16::MAX);
+}
+
+#[test]
+fn filter_axis_matrix_stage() {
+	let filter = vigem::InputFilter {
+		axis_matrix: Some(vigem::AxisMatrix::identity().swap_sticks()),
+		..vigem::InputFilter::new()
+	};
+	let gamepad = vigem::XGamepad { thumb_lx: 5, thumb_ry: -7, ..Default::default() };
+	assert_eq!(filter.apply(&gamepad), vigem::XGamepad { thumb_rx: 5, thumb_ly: -7, ..Default::default() });
+}
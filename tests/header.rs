@@ -0,0 +1,179 @@
+/*!
+Checks `include/vigem_client.h` against the Rust declarations it is generated from.
+
+Renders the declarations the way cbindgen does with `cbindgen.toml` and expects to find them verbatim in the header,
+such that editing `src/ffi.rs` without regenerating the header fails here.
+The header is diffed against the actual cbindgen output in CI, see `.github/workflows/header.yml`.
+*/
+
+const HEADER: &str = include_str!("../include/vigem_client.h");
+const FFI: &str = include_str!("../src/ffi.rs");
+const X360: &str = include_str!("../src/x360.rs");
+const DS4: &str = include_str!("../src/ds4.rs");
+
+const DS4_GUARD: &str = "#[cfg(feature = \"unstable_ds4\")]";
+
+fn c_type(ty: &str) -> String {
+	let ty = ty.trim();
+	if let Some(pointee) = ty.strip_prefix("*mut ") {
+		let pointee = c_type(pointee);
+		return if pointee.ends_with('*') { format!("{}*", pointee) } else { format!("{} *", pointee) };
+	}
+	if let Some(pointee) = ty.strip_prefix("*const ") {
+		return format!("const {} *", c_type(pointee));
+	}
+	match ty {
+		"i32" => "int32_t",
+		"u8" => "uint8_t",
+		"u16" => "uint16_t",
+		"i16" => "int16_t",
+		"c_char" => "char",
+		"XButtons" => "VigemRsXButtons",
+		"XGamepad" => "VigemRsXGamepad",
+		"DS4Report" => "VigemRsDS4Report",
+		ty => ty,
+	}.to_string()
+}
+
+// Declares a name of the C type, without a space after a pointer
+fn c_decl(ty: &str, name: &str) -> String {
+	let ty = c_type(ty);
+	if ty.ends_with('*') { format!("{}{}", ty, name) } else { format!("{} {}", ty, name) }
+}
+
+fn doxy(docs: &[&str]) -> String {
+	let mut comment = String::from("/**\n");
+	for line in docs {
+		if line.is_empty() {
+			comment.push_str(" *\n");
+		}
+		else {
+			comment.push_str(" * ");
+			comment.push_str(line);
+			comment.push('\n');
+		}
+	}
+	comment.push_str(" */\n");
+	comment
+}
+
+fn guard(gated: bool, item: String) -> String {
+	if gated { format!("#if defined(VIGEM_RS_DS4)\n{}\n#endif", item) } else { item }
+}
+
+// Renders the constants, opaque handles and functions of the ffi module
+fn ffi_items() -> Vec<String> {
+	let mut items = Vec::new();
+	let mut docs = Vec::new();
+	let mut gated = false;
+	for line in FFI.lines() {
+		if let Some(doc) = line.strip_prefix("///") {
+			docs.push(doc.strip_prefix(' ').unwrap_or(doc));
+			continue;
+		}
+		if line == DS4_GUARD {
+			gated = true;
+			continue;
+		}
+		if line.starts_with("#[") {
+			continue;
+		}
+		let decl = if let Some(rest) = line.strip_prefix("pub const ") {
+			let (name, value) = rest.split_once(": i32 = ").unwrap();
+			Some(format!("#define {} {}", name, value.trim_end_matches(';')))
+		}
+		else if let Some(name) = line.strip_prefix("pub enum ").and_then(|rest| rest.strip_suffix(" {}")) {
+			Some(format!("typedef struct {0} {0};", name))
+		}
+		else if let Some(rest) = line.strip_prefix("pub extern \"C\" fn ").or_else(|| line.strip_prefix("pub unsafe extern \"C\" fn ")) {
+			let (name, rest) = rest.split_once('(').unwrap();
+			let (params, ret) = rest.split_once(") -> ").unwrap();
+			let params = params.split(", ").filter(|param| !param.is_empty()).map(|param| {
+				let (name, ty) = param.split_once(": ").unwrap();
+				c_decl(ty, name)
+			}).collect::<Vec<_>>();
+			let params = if params.is_empty() { "void".to_string() } else { params.join(", ") };
+			Some(format!("{};", c_decl(ret.trim_end_matches(" {"), &format!("{}({})", name, params))))
+		}
+		else {
+			None
+		};
+		if let Some(decl) = decl {
+			items.push(guard(gated, format!("{}{}", doxy(&docs), decl)));
+		}
+		docs.clear();
+		gated = false;
+	}
+	items
+}
+
+// Renders a `repr(C)` struct with its fields, or a `repr(transparent)` struct as a typedef
+fn struct_item(src: &str, name: &str) -> String {
+	let lines: Vec<&str> = src.lines().collect();
+	let start = lines.iter().position(|line| *line == format!("pub struct {} {{", name)).unwrap();
+
+	let mut docs = Vec::new();
+	let mut gated = false;
+	for line in lines[..start].iter().rev() {
+		if let Some(doc) = line.strip_prefix("///") {
+			docs.push(doc.strip_prefix(' ').unwrap_or(doc));
+		}
+		else if *line == DS4_GUARD {
+			gated = true;
+		}
+		else if !line.starts_with("#[") {
+			break;
+		}
+	}
+	docs.reverse();
+
+	let fields: Vec<(&str, &str)> = lines[start + 1..].iter()
+		.take_while(|line| **line != "}")
+		.map(|line| line.trim().strip_prefix("pub ").unwrap().trim_end_matches(',').split_once(": ").unwrap())
+		.collect();
+
+	let c_name = c_type(name);
+	let decl = if lines[start - 1] == "#[repr(transparent)]" {
+		format!("typedef {} {};", c_type(fields[0].1), c_name)
+	}
+	else {
+		let fields: String = fields.iter().map(|&(name, ty)| format!("  {};\n", c_decl(ty, name))).collect();
+		format!("typedef struct {0} {{\n{1}}} {0};", c_name, fields)
+	};
+	guard(gated, format!("{}{}", doxy(&docs), decl))
+}
+
+// Checkouts with autocrlf have CRLF line endings
+fn header() -> String {
+	HEADER.replace("\r\n", "\n")
+}
+
+fn assert_declared(item: &str) {
+	assert!(header().contains(item), "include/vigem_client.h is out of date, regenerate it with cbindgen, missing:\n{}", item);
+}
+
+#[test]
+fn functions_and_constants() {
+	let items = ffi_items();
+	for item in &items {
+		assert_declared(item);
+	}
+
+	// Nothing was removed from the Rust side but kept in the header
+	let declared = header().lines().filter(|line| line.starts_with("#define VIGEM_RS_") || line.starts_with("typedef struct VigemRs") && line.ends_with(';') || line.contains(" vigem_rs_") || line.contains("*vigem_rs_")).count();
+	assert_eq!(declared, items.len());
+}
+
+#[test]
+fn reports() {
+	assert_declared(&struct_item(X360, "XButtons"));
+	assert_declared(&struct_item(X360, "XGamepad"));
+	assert_declared(&struct_item(DS4, "DS4Report"));
+}
+
+#[test]
+fn layout() {
+	let header = header();
+	assert!(header.starts_with("/* Generated by cbindgen from src/ffi.rs, do not edit. */\n\n#ifndef VIGEM_CLIENT_H\n#define VIGEM_CLIENT_H\n\n#include <stdint.h>\n"));
+	assert!(header.ends_with("#endif /* VIGEM_CLIENT_H */\n"));
+}
@@ -0,0 +1,182 @@
+use std::time::{Duration, Instant};
+use vigem_client::{IdleTracker, XButtons, XGamepad};
+
+fn ms(ms: u64) -> Duration {
+	Duration::from_millis(ms)
+}
+
+#[test]
+fn xgamepad_neutral() {
+	assert!(XGamepad::neutral().is_neutral(0));
+	assert!(!XGamepad::pressing(XButtons!(A)).is_neutral(255));
+	assert!(!XGamepad::pressing(XButtons!(GUIDE)).is_neutral(255));
+
+	// Triggers are compared directly
+	let gamepad = XGamepad { left_trigger: 3, ..XGamepad::neutral() };
+	assert!(gamepad.is_neutral(3));
+	assert!(!gamepad.is_neutral(2));
+	let gamepad = XGamepad { right_trigger: 255, ..XGamepad::neutral() };
+	assert!(gamepad.is_neutral(255));
+	assert!(!gamepad.is_neutral(254));
+
+	// Thumbsticks in steps of 256 in either direction
+	for &(lx, ly, rx, ry) in &[(256, 0, 0, 0), (0, -256, 0, 0), (0, 0, 256, 0), (0, 0, 0, -256)] {
+		let gamepad = XGamepad { thumb_lx: lx, thumb_ly: ly, thumb_rx: rx, thumb_ry: ry, ..XGamepad::neutral() };
+		assert!(gamepad.is_neutral(1));
+		assert!(!gamepad.is_neutral(0));
+	}
+	let gamepad = XGamepad { thumb_lx: 257, ..XGamepad::neutral() };
+	assert!(!gamepad.is_neutral(1));
+
+	// Full deflection including i16::MIN
+	let gamepad = XGamepad { thumb_lx: i16::MIN, thumb_ry: i16::MAX, ..XGamepad::neutral() };
+	assert!(!gamepad.is_neutral(127));
+	assert!(gamepad.is_neutral(128));
+	assert!(gamepad.is_neutral(255));
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4_neutral() {
+	use vigem_client::{DS4Buttons, DS4Report, DS4SpecialButtons};
+
+	assert!(DS4Report::neutral().is_neutral(0));
+	assert!(!DS4Report::PRESS_CROSS.is_neutral(255));
+	assert!(!DS4Report::neutral().with_special(DS4SpecialButtons::new(DS4SpecialButtons::TOUCHPAD)).is_neutral(255));
+	assert!(!DS4Report::neutral().buttons_raw(0x0).is_neutral(255), "dpad up is input");
+	assert!(!DS4Report::neutral().buttons_raw(0x8 | DS4Buttons::R3).is_neutral(255));
+
+	// The report counter is not input
+	assert!(DS4Report::neutral().special_raw(0xfc).is_neutral(0));
+	assert!(!DS4Report::neutral().special_raw(0xfd).is_neutral(0));
+
+	// Jitter around center in both directions
+	for &(lx, ly, rx, ry) in &[(0x7f, 0x80, 0x80, 0x80), (0x80, 0x81, 0x80, 0x80), (0x80, 0x80, 0x7e, 0x80), (0x80, 0x80, 0x80, 0x82)] {
+		let report = DS4Report { thumb_lx: lx, thumb_ly: ly, thumb_rx: rx, thumb_ry: ry, ..DS4Report::neutral() };
+		assert!(report.is_neutral(2));
+		assert!(!report.is_neutral(0));
+	}
+	let report = DS4Report { thumb_lx: 0x7e, ..DS4Report::neutral() };
+	assert!(report.is_neutral(2));
+	assert!(!report.is_neutral(1));
+
+	// Extremes
+	let report = DS4Report { thumb_lx: 0x00, thumb_ly: 0xff, ..DS4Report::neutral() };
+	assert!(!report.is_neutral(0x7f));
+	assert!(report.is_neutral(0x80));
+	let report = DS4Report { trigger_l: 1, ..DS4Report::neutral() };
+	assert!(report.is_neutral(1));
+	assert!(!report.is_neutral(0));
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4_ex_user_idle() {
+	use vigem_client::{BatteryStatus, DS4Report, DS4ReportEx, DS4TouchPoint, DS4TouchReport};
+
+	assert!(DS4ReportEx::neutral().is_user_idle(0, 0));
+	assert!(!DS4ReportEx::from(DS4Report::PRESS_CROSS).is_user_idle(255, u16::MAX));
+
+	// The tolerance applies to the basic report
+	let report = DS4ReportEx::from(DS4Report { thumb_rx: 0x82, trigger_r: 2, ..DS4Report::neutral() });
+	assert!(report.is_user_idle(2, 0));
+	assert!(!report.is_user_idle(1, 0));
+
+	// The gyro threshold applies to every axis in both directions
+	for gyro in [[20, 0, 0], [0, -20, 0], [0, 0, 20]] {
+		let report = DS4ReportEx::builder().gyro(gyro).build();
+		assert!(report.is_user_idle(0, 20));
+		assert!(!report.is_user_idle(0, 19));
+	}
+	let report = DS4ReportEx::builder().gyro([i16::MIN, 0, 0]).build();
+	assert!(report.is_user_idle(0, 32768));
+	assert!(!report.is_user_idle(0, 32767));
+
+	// Gravity, the timestamp, the report counter and the battery status are not input
+	let report = DS4ReportEx::builder()
+		.accel([-116, 8168, 1130])
+		.timestamp(0xa13e)
+		.battery(BatteryStatus::new(3, false))
+		.report(&DS4Report::neutral().special_raw(0xfc))
+		.build();
+	assert!(report.is_user_idle(0, 0));
+
+	// Touching contacts of the valid touch packets are input, lifted contacts and stale packets are not
+	let touching = DS4TouchReport::new(1, None, Some(DS4TouchPoint { id: 3, x: 10, y: 10 }));
+	let lifted = DS4TouchReport::new(2, None, None);
+	assert!(!DS4ReportEx::builder().touch_reports(&[touching]).build().is_user_idle(255, u16::MAX));
+	assert!(!DS4ReportEx::builder().touch_reports(&[lifted, lifted, touching]).build().is_user_idle(255, u16::MAX));
+	assert!(DS4ReportEx::builder().touch_reports(&[lifted]).build().is_user_idle(0, 0));
+	let stale = DS4ReportEx { touch_packets_n: 1, previous_touch: [touching; 2], ..DS4ReportEx::neutral() };
+	assert!(stale.is_user_idle(0, 0));
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4_ex_tracker() {
+	use vigem_client::DS4ReportEx;
+
+	// The tracker uses the default gyro threshold
+	let start = Instant::now();
+	let mut idle = IdleTracker::new(1);
+	let noise = DS4ReportEx::GYRO_NOISE as i16;
+	assert!(idle.push(&DS4ReportEx::builder().gyro([noise, -noise, 0]).build(), start));
+	assert!(idle.push(&DS4ReportEx { thumb_lx: 0x7f, ..DS4ReportEx::neutral() }, start + ms(10)));
+	assert_eq!(idle.idle_for(), ms(10));
+	assert!(!idle.push(&DS4ReportEx::builder().gyro([0, 0, noise + 1]).build(), start + ms(20)));
+	assert_eq!(idle.idle_for(), Duration::ZERO);
+}
+
+#[test]
+fn tracker_idle_for() {
+	let start = Instant::now();
+	let mut idle = IdleTracker::new(0);
+	assert!(!idle.is_idle());
+	assert_eq!(idle.idle_for(), Duration::ZERO);
+
+	assert!(idle.push(&XGamepad::neutral(), start));
+	assert!(idle.is_idle());
+	assert_eq!(idle.idle_since(), Some(start));
+	assert_eq!(idle.idle_for(), Duration::ZERO);
+
+	idle.push(&XGamepad::neutral(), start + ms(100));
+	assert_eq!(idle.idle_for(), ms(100));
+	assert_eq!(idle.idle_until(start + ms(250)), ms(250));
+
+	// Input restarts the idle period at the next neutral report
+	assert!(!idle.push(&XGamepad::pressing(XButtons!(B)), start + ms(200)));
+	assert!(!idle.is_idle());
+	assert_eq!(idle.idle_for(), Duration::ZERO);
+	assert_eq!(idle.idle_until(start + ms(1000)), Duration::ZERO);
+
+	idle.push(&XGamepad::neutral(), start + ms(300));
+	idle.push(&XGamepad::neutral(), start + ms(700));
+	assert_eq!(idle.idle_since(), Some(start + ms(300)));
+	assert_eq!(idle.idle_for(), ms(400));
+}
+
+#[test]
+fn tracker_tolerance() {
+	let start = Instant::now();
+	let mut idle = IdleTracker::new(2);
+	assert_eq!(idle.tolerance(), 2);
+	let drift = XGamepad { thumb_lx: 400, left_trigger: 2, ..XGamepad::neutral() };
+	assert!(idle.push(&drift, start));
+	assert!(idle.push(&XGamepad::neutral(), start + ms(50)));
+	assert_eq!(idle.idle_for(), ms(50));
+	assert!(!idle.push(&XGamepad { left_trigger: 3, ..XGamepad::neutral() }, start + ms(60)));
+}
+
+#[test]
+fn tracker_out_of_order() {
+	let start = Instant::now();
+	let mut idle = IdleTracker::new(0);
+	idle.push(&XGamepad::neutral(), start + ms(100));
+	idle.push(&XGamepad::neutral(), start + ms(50));
+	assert_eq!(idle.idle_since(), Some(start + ms(100)));
+	assert_eq!(idle.idle_for(), Duration::ZERO);
+
+	idle.reset();
+	assert!(!idle.is_idle());
+	assert_eq!(idle.idle_for(), Duration::ZERO);
+}
@@ -1,16 +1,380 @@
+/*!
+Tests against the ViGEmBus driver.
+
+Without the driver installed these tests are skipped with a message on stderr.
+Tests plugging in many controllers or running for a while are ignored by default, run them with:
+
+```text
+cargo test --all-features --test integration -- --include-ignored
+```
+*/
+
+#![cfg(windows)]
+
 use vigem_client as vigem;
 
+// Connects to the driver, returns `None` if it is not installed
+fn connect() -> Option<vigem::Client> {
+	match vigem::Client::connect() {
+		Ok(client) => Some(client),
+		Err(vigem::Error::BusNotFound | vigem::Error::BusDisabled) => {
+			eprintln!("skipped: ViGEmBus is not installed");
+			None
+		},
+		Err(err) => panic!("failed to connect to ViGEmBus: {}", err),
+	}
+}
+
 #[test]
 fn connection() {
-	let _client1 = vigem::Client::connect().unwrap();
+	let Some(_client1) = connect() else { return };
 	let _client2 = vigem::Client::connect().unwrap();
 }
 
+#[test]
+fn connection_flags() {
+	let Some(_client) = connect() else { return };
+	for no_buffering in [false, true] {
+		for write_through in [false, true] {
+			let client = vigem::Client::builder().no_buffering(no_buffering).write_through(write_through).connect().unwrap();
+			let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+			target.plugin().unwrap();
+			target.wait_ready().unwrap();
+			target.update(&vigem::XGamepad::default()).unwrap();
+			target.unplug().unwrap();
+		}
+	}
+}
+
+#[test]
+fn connect_unchecked() {
+	let Some(_client) = connect() else { return };
+	// The installed driver accepts the version, the unchecked client is an ordinary client
+	let client = vigem::Client::connect_unchecked().unwrap();
+	assert_eq!(client.bus_version(), vigem::BusVersion::accepted());
+	assert_eq!(client.try_clone().unwrap().bus_version(), vigem::BusVersion::accepted());
+	let mut target = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+}
+
+#[test]
+fn connect_diagnostic() {
+	let (result, instances) = vigem::Client::connect_diagnostic();
+	match result {
+		// Connecting stops at the first instance accepting the version
+		Ok(client) => {
+			assert_eq!(instances.last(), Some(&vigem::BusInstance::Accepted));
+			let capabilities = client.capabilities().unwrap();
+			assert_eq!(capabilities.bus_version, vigem::BusVersion::accepted());
+			// The extended report is probed on drivers answering the serial number probe, both arrived with ViGEmBus 1.17
+			assert!(!capabilities.ds4_extended_report || capabilities.probe_serial);
+			#[cfg(not(feature = "unstable_ds4"))]
+			assert!(!capabilities.ds4_extended_report);
+		},
+		Err(vigem::Error::BusNotFound) => assert!(instances.is_empty()),
+		Err(_) => assert!(!instances.contains(&vigem::BusInstance::Accepted)),
+	}
+}
+
+#[test]
+fn connect_to_path() {
+	let path = std::ffi::OsStr::new(r"\\?\ROOT#VIGEM-CLIENT-NONEXISTENT#0000#{96e42b22-f5e9-42f8-b043-ed0f932f014f}");
+	match vigem::Client::connect_to_path(path) {
+		Err(vigem::Error::BusAccessFailed(err)) => assert_ne!(err, 0),
+		result => panic!("unexpected {:?}", result.map(|_| ())),
+	}
+}
+
+#[test]
+fn enumerate_buses() {
+	let buses = vigem::Client::enumerate_buses().unwrap();
+	let (result, instances) = vigem::Client::connect_diagnostic();
+	// Connecting walks the same instances up to the first accepting the version
+	let walked: Vec<_> = buses.iter().map(|bus| bus.instance).take(instances.len()).collect();
+	assert_eq!(walked, instances);
+	for bus in &buses {
+		assert_eq!(bus.path.is_none(), matches!(bus.instance, vigem::BusInstance::DetailFailed(_)));
+	}
+	match buses.iter().find(|bus| bus.is_usable()) {
+		Some(bus) => {
+			drop(result.unwrap());
+			let client = vigem::Client::connect_to_path(bus.path.as_ref().unwrap()).unwrap();
+			assert_eq!(client.bus_version(), vigem::BusVersion::accepted());
+		},
+		None => assert!(result.is_err()),
+	}
+}
+
+#[test]
+fn interface_guid() {
+	use winapi::shared::guiddef::GUID;
+
+	// {96E42B22-F5E9-42F8-B043-ED0F932F014F}
+	let vigem = GUID { Data1: 0x96E42B22, Data2: 0xF5E9, Data3: 0x42F8, Data4: [0xB0, 0x43, 0xED, 0x0F, 0x93, 0x2F, 0x01, 0x4F] };
+	assert_eq!(vigem::ClientBuilder::new().interface_guid(&vigem), vigem::ClientBuilder::new());
+
+	let unknown = GUID { Data1: 0x6E0B8F1D, Data2: 0x3C4A, Data3: 0x4B8E, Data4: [0x9F, 0x21, 0x5D, 0x7A, 0x00, 0xC3, 0x11, 0x42] };
+	let builder = vigem::ClientBuilder::new().interface_guid(&unknown);
+	assert_ne!(builder, vigem::ClientBuilder::new());
+	assert_eq!(builder.connect().err(), Some(vigem::Error::BusNotFound));
+	assert_eq!(builder.connect_unchecked().err(), Some(vigem::Error::BusNotFound));
+	assert_eq!(builder.enumerate_buses().unwrap(), []);
+}
+
+#[test]
+fn connect_with_retry() {
+	use std::time::{Duration, Instant};
+
+	let unknown = winapi::shared::guiddef::GUID { Data1: 0x6E0B8F1D, Data2: 0x3C4A, Data3: 0x4B8E, Data4: [0x9F, 0x21, 0x5D, 0x7A, 0x00, 0xC3, 0x11, 0x42] };
+	let builder = vigem::ClientBuilder::new().interface_guid(&unknown);
+	let start = Instant::now();
+	assert_eq!(builder.connect_with_retry(Duration::from_millis(100), Duration::from_millis(20)).err(), Some(vigem::Error::BusNotFound));
+	assert!(start.elapsed() >= Duration::from_millis(100));
+
+	// Connects on the first attempt when the driver is ready
+	if connect().is_some() {
+		let start = Instant::now();
+		vigem::Client::connect_with_retry(Duration::from_secs(10), Duration::from_secs(1)).unwrap();
+		assert!(start.elapsed() < Duration::from_secs(1));
+
+		// Timeouts past the end of time do not overflow
+		vigem::Client::connect_with_retry(Duration::MAX, Duration::from_secs(1)).unwrap();
+	}
+}
+
+#[test]
+fn bus_presence() {
+	let presence = vigem::Client::bus_presence().unwrap();
+	match vigem::Client::connect() {
+		Ok(_) => assert_eq!(presence, vigem::BusPresence::Active),
+		Err(err @ (vigem::Error::BusNotFound | vigem::Error::BusDisabled)) => assert_eq!(presence.error(), Some(err)),
+		Err(_) => (),
+	}
+}
+
+#[test]
+fn ping() {
+	let Some(client) = connect() else { return };
+	client.ping().unwrap();
+	let mut target = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	client.ping().unwrap();
+	target.wait_ready().unwrap();
+	// Concurrently with updates of the target through the same client
+	std::thread::scope(|scope| {
+		scope.spawn(|| {
+			for _ in 0..100 {
+				client.ping().unwrap();
+			}
+		});
+		for _ in 0..100 {
+			target.update(&vigem::XGamepad::default()).unwrap();
+		}
+	});
+	target.unplug().unwrap();
+	client.ping().unwrap();
+}
+
+#[test]
+fn owned_handle() {
+	use std::os::windows::io::{AsHandle, AsRawHandle, OwnedHandle};
+
+	let Some(client) = connect() else { return };
+	let raw = client.as_raw_handle();
+	assert_eq!(client.as_handle().as_raw_handle(), raw);
+
+	// Round trips without closing the handle
+	let handle = OwnedHandle::from(client);
+	assert_eq!(handle.as_raw_handle(), raw);
+	let client = vigem::Client::from(handle);
+	client.ping().unwrap();
+	let mut target = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+}
+
+#[test]
+fn duplicate_to() {
+	use std::os::windows::io::{AsHandle, FromRawHandle, OwnedHandle};
+	use winapi::um::{processthreadsapi, winnt};
+
+	let Some(client) = connect() else { return };
+
+	// Into this very process, through a real process handle rather than the pseudo handle
+	let process = unsafe {
+		let process = processthreadsapi::OpenProcess(winnt::PROCESS_DUP_HANDLE, 0, processthreadsapi::GetCurrentProcessId());
+		assert!(!process.is_null());
+		OwnedHandle::from_raw_handle(process)
+	};
+	let duplicate = client.duplicate_to(process.as_handle()).unwrap();
+	let duplicate = unsafe { vigem::Client::from_raw_handle(duplicate) };
+	duplicate.ping().unwrap();
+	let mut target = vigem::Xbox360Wired::new(&duplicate, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	drop(target);
+	drop(duplicate);
+	client.ping().unwrap();
+
+	// Without the access right to duplicate handles into the process
+	let limited = unsafe {
+		let process = processthreadsapi::OpenProcess(winnt::PROCESS_QUERY_LIMITED_INFORMATION, 0, processthreadsapi::GetCurrentProcessId());
+		assert!(!process.is_null());
+		OwnedHandle::from_raw_handle(process)
+	};
+	assert_eq!(client.duplicate_to(limited.as_handle()), Err(vigem::Error::WinError(winapi::shared::winerror::ERROR_ACCESS_DENIED)));
+}
+
+#[test]
+fn driver_version() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	// Querying the version does not disturb plugged in targets
+	let version = client.driver_version().unwrap();
+	assert!(version.bus.accepted);
+	assert_ne!(version.bus.required, 0);
+	if let Some(file) = version.file {
+		assert_ne!(file, [0; 4]);
+	}
+	target.update(&vigem::XGamepad::default()).unwrap();
+}
+
+#[test]
+#[ignore = "needs XInput to pick up the controller"]
+fn xinput_verify() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	assert_eq!(target.wait_for_user_index(std::time::Duration::from_millis(10)), Err(vigem::Error::NotPluggedIn));
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	target.wait_for_user_index(std::time::Duration::from_secs(2)).unwrap();
+	let gamepad = vigem::XGamepad { buttons: vigem::XButtons!(X|Y), right_trigger: 99, ..Default::default() };
+	assert!(target.verify_xinput(&gamepad, std::time::Duration::from_secs(1)).unwrap().is_some());
+}
+
+#[test]
+fn update_bounced_between_threads() {
+	use std::sync::{Arc, Barrier, Mutex};
+
+	let Some(client) = connect() else { return };
+	let client = Arc::new(client);
+	for dedicated in [false, true] {
+		let mut target = vigem::Xbox360Wired::new(client.clone(), vigem::TargetId::XBOX360_WIRED);
+		target.set_dedicated_event(dedicated).unwrap();
+		target.plugin().unwrap();
+		target.wait_ready().unwrap();
+
+		// Both threads take turns, the target is never used concurrently but every update follows one from the other thread
+		const ROUNDS: u32 = 20000;
+		let target = Arc::new(Mutex::new(target));
+		let barrier = Arc::new(Barrier::new(2));
+		let threads: Vec<_> = (0..2).map(|parity| {
+			let target = target.clone();
+			let barrier = barrier.clone();
+			std::thread::spawn(move || {
+				for round in 0..ROUNDS {
+					if round % 2 == parity {
+						let gamepad = vigem::XGamepad { left_trigger: round as u8, thumb_lx: round as i16, ..Default::default() };
+						target.lock().unwrap().update(&gamepad).unwrap();
+					}
+					barrier.wait();
+				}
+			})
+		}).collect();
+		for thread in threads {
+			thread.join().unwrap();
+		}
+		target.lock().unwrap().unplug().unwrap();
+	}
+}
+
+#[test]
+fn into_raw_handle() {
+	use std::os::windows::io::{FromRawHandle, IntoRawHandle};
+	let Some(client) = connect() else { return };
+	let device = client.into_raw_handle();
+	let client = unsafe { vigem::Client::from_raw_handle(device) };
+	let mut target = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	target.update(&vigem::XGamepad::default()).unwrap();
+}
+
+#[test]
+fn leak() {
+	use std::os::windows::io::FromRawHandle;
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	let serial_no = target.serial_no();
+	std::mem::forget(target);
+	let device = client.leak();
+	let client = unsafe { vigem::Client::from_raw_handle(device) };
+	assert_eq!(client.probe_serial(serial_no), Ok(Some(true)));
+	drop(client);
+	let client = vigem::Client::connect().unwrap();
+	assert_ne!(client.probe_serial(serial_no), Ok(Some(true)));
+}
+
+// Returns the panic message of the closure
+#[cfg(all(feature = "debug-handles", debug_assertions))]
+fn panic_message(f: impl FnOnce()) -> String {
+	let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_err();
+	payload.downcast_ref::<String>().cloned().unwrap_or_default()
+}
+
+#[cfg(all(feature = "debug-handles", debug_assertions))]
+#[test]
+fn debug_handles_owned_twice() {
+	use std::os::windows::io::{AsRawHandle, FromRawHandle};
+	let Some(client) = connect() else { return };
+	let message = panic_message(|| {
+		let twice = unsafe { vigem::Client::from_raw_handle(client.as_raw_handle()) };
+		std::mem::forget(twice);
+	});
+	assert!(message.contains("already owned by another Client"), "{}", message);
+}
+
+#[cfg(all(feature = "debug-handles", debug_assertions))]
+#[test]
+fn debug_handles_closed() {
+	use std::os::windows::io::{FromRawHandle, IntoRawHandle};
+	let Some(client) = connect() else { return };
+	let device = client.into_raw_handle();
+	let client = unsafe { vigem::Client::from_raw_handle(device) };
+	unsafe { winapi::um::handleapi::CloseHandle(device) };
+	let message = panic_message(|| {
+		let _ = client.probe_serial(1);
+	});
+	assert!(message.contains("was used after it was closed"), "{}", message);
+	// Already closed
+	client.leak();
+}
+
+#[test]
+fn profile_create_target() {
+	let Some(client) = connect() else { return };
+	let profile = vigem::Profile {
+		detached_policy: vigem::DetachedPolicy::AutoPlugin,
+		stale_timeout: Some(std::time::Duration::from_secs(1)),
+		..vigem::Profile::xbox360()
+	};
+	let mut target = profile.create_target(&client).unwrap();
+	assert_eq!(target.kind(), vigem::TargetKind::Xbox360Wired);
+	// Auto plugin submits the initial report once the target is ready
+	let gamepad = vigem::XGamepad { left_trigger: 1, ..Default::default() };
+	target.update(&gamepad.into()).unwrap();
+	assert!(target.serial_no() != 0);
+}
+
 #[test]
 fn simple_success() {
-	let mut target = vigem::Xbox360Wired::new(
-		vigem::Client::connect().unwrap(),
-		vigem::TargetId::XBOX360_WIRED);
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
 
 	target.plugin().unwrap();
 	target.wait_ready().unwrap();
@@ -21,12 +385,1377 @@ fn simple_success() {
 
 #[test]
 fn target_not_ready() {
-	let mut target = vigem::Xbox360Wired::new(
-		vigem::Client::connect().unwrap(),
-		vigem::TargetId::XBOX360_WIRED);
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
 
 	target.plugin().unwrap();
 	let _result = target.update(&vigem::XGamepad::default());
 
 	// assert_eq!(result, Err(vigem::Error::TargetNotReady));
 }
+
+#[test]
+fn update_timed() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+
+	target.plugin().unwrap();
+	target.wait_ready_timed().unwrap();
+	let elapsed = target.update_timed(&vigem::XGamepad::default()).unwrap().expect("submitted");
+
+	assert!(elapsed < std::time::Duration::from_secs(1));
+}
+
+#[test]
+#[ignore = "needs XInput to pick up the controller"]
+fn xinput_state() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let gamepad = vigem::XGamepad {
+		buttons: vigem::XButtons!(A | Y | GUIDE),
+		left_trigger: 5,
+		right_trigger: 255,
+		thumb_lx: 1234,
+		thumb_ry: -1,
+		..Default::default()
+	};
+	target.update(&gamepad).unwrap();
+	std::thread::sleep(std::time::Duration::from_millis(100));
+	assert!(xinput_has(&gamepad));
+}
+
+#[test]
+#[ignore = "needs XInput to pick up the controller"]
+fn xinput_initial_report() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	let gamepad = vigem::XGamepad { buttons: vigem::XButtons!(START), left_trigger: 77, ..Default::default() };
+	target.set_initial_report(Some(gamepad));
+	assert_eq!(target.initial_report(), Some(&gamepad));
+
+	// Submitted by wait_ready without any update
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	std::thread::sleep(std::time::Duration::from_millis(100));
+	assert!(xinput_has(&gamepad));
+
+	// Reannouncing submits it again as nothing else was submitted
+	target.reannounce().unwrap();
+	std::thread::sleep(std::time::Duration::from_millis(100));
+	assert!(xinput_has(&gamepad));
+}
+
+// Looks for the gamepad state in every XInput slot, the user index reported by the driver is unreliable
+fn xinput_has(gamepad: &vigem::XGamepad) -> bool {
+	use winapi::um::xinput;
+
+	let expected = gamepad.quantize_like_driver();
+	(0..4).any(|user_index| {
+		let mut state: xinput::XINPUT_STATE = unsafe { std::mem::zeroed() };
+		let success = unsafe { xinput::XInputGetState(user_index, &mut state) } == 0;
+		success && vigem::XGamepad::from(state.Gamepad) == expected
+	})
+}
+
+#[test]
+fn initial_report() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	assert_eq!(target.initial_report(), None);
+
+	// Nothing is submitted while unplugged
+	let gamepad = vigem::XGamepad { thumb_lx: 1000, ..Default::default() };
+	target.set_initial_report(Some(gamepad));
+	assert_eq!(target.wait_ready(), Err(vigem::Error::NotPluggedIn));
+
+	// The initial report is watched by the stale timeout like any other submission
+	target.set_stale_timeout(Some(std::time::Duration::from_millis(50))).unwrap();
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	assert!(!target.is_stale());
+	std::thread::sleep(std::time::Duration::from_millis(200));
+	assert!(target.is_stale());
+
+	// Reannouncing submits the initial report again and the target is live again
+	target.reannounce().unwrap();
+	assert!(!target.is_stale());
+
+	target.set_initial_report(None);
+	assert_eq!(target.initial_report(), None);
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4_update_validation() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::DualShock4Wired::new(client, vigem::TargetId::DUALSHOCK4_WIRED);
+
+	let report = vigem::DS4Report::default();
+	assert_eq!(target.update(&report), Err(vigem::Error::NotPluggedIn));
+
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	assert_eq!(target.update(&report), Ok(()));
+
+	target.set_validate_reports(true);
+	let invalid = vigem::DS4Report { buttons: 0xF, ..report };
+	assert_eq!(target.update(&invalid), Err(vigem::Error::InvalidReport(vigem::DS4ValidationError::Dpad(0xF))));
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4_submit() {
+	let Some(client) = connect() else { return };
+	let extended = client.capabilities().unwrap().ds4_extended_report;
+	let mut target = vigem::DualShock4Wired::new(client, vigem::TargetId::DUALSHOCK4_WIRED);
+	assert_eq!(target.submit(&vigem::DS4Report::neutral()), Err(vigem::Error::NotPluggedIn));
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	fn both<R: vigem::Ds4Submittable, CL: std::borrow::Borrow<vigem::Client>>(target: &mut vigem::DualShock4Wired<CL>, report: &R) -> Result<(), vigem::Error> {
+		target.submit(report)
+	}
+	let report = vigem::DS4ReportEx { gyro_z: 50, ..vigem::DS4Report::PRESS_SQUARE.into() };
+	assert_eq!(both(&mut target, &vigem::DS4Report::PRESS_CROSS), Ok(()));
+	assert_eq!(both(&mut target, &report), Ok(()));
+	assert_eq!(*target.shadow(), report);
+
+	// Down-converting lets the extended mode work on every driver
+	target.set_report_mode(vigem::DS4ReportMode::Extended);
+	target.set_downgrade_reports(true);
+	assert!(target.downgrade_reports());
+	assert_eq!(target.submit(&report), Ok(()));
+	assert_eq!(target.submit(&vigem::DS4Report::neutral()), Ok(()));
+
+	// Without it an old driver rejects the extended report
+	target.set_downgrade_reports(false);
+	let result = target.submit(&report);
+	if extended {
+		assert_eq!(result, Ok(()));
+	}
+	else {
+		assert!(matches!(result, Err(vigem::Error::WinError(_))), "{:?}", result);
+	}
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4_update_ex() {
+	let Some(client) = connect() else { return };
+	let extended = client.capabilities().unwrap().ds4_extended_report;
+	let mut target = vigem::DualShock4Wired::new(client, vigem::TargetId::DUALSHOCK4_WIRED);
+	assert_eq!(target.update_ex(&vigem::DS4ReportEx::neutral()), Err(vigem::Error::NotPluggedIn));
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let report = vigem::DS4ReportEx { gyro_x: 100, touch_packets_n: 1, ..vigem::DS4Report::PRESS_CROSS.into() };
+
+	// The basic mode submits the basic part on every driver
+	assert_eq!(target.report_mode(), vigem::DS4ReportMode::Basic);
+	assert_eq!(target.update_ex(&report), Ok(()));
+	assert_eq!(*target.shadow(), report);
+
+	// The extended mode keeps the sensors and touch packets across basic updates
+	target.set_report_mode(vigem::DS4ReportMode::Extended);
+	let result = target.update(&vigem::DS4Report::PRESS_CIRCLE);
+	assert_eq!(*target.shadow(), report.with_report(&vigem::DS4Report::PRESS_CIRCLE));
+	if extended {
+		assert_eq!(result, Ok(()));
+		assert_eq!(target.update_ex(&report), Ok(()));
+	}
+	else {
+		assert!(matches!(result, Err(vigem::Error::WinError(_))), "{:?}", result);
+	}
+	target.set_report_mode(vigem::DS4ReportMode::Basic);
+	assert_eq!(target.update(&vigem::DS4Report::neutral()), Ok(()));
+
+	// Rejected extended reports are neither submitted nor merged
+	target.set_validate_reports(true);
+	let invalid = vigem::DS4ReportEx { touch_packets_n: 4, ..report };
+	assert_eq!(target.update_ex(&invalid), Err(vigem::Error::InvalidReport(vigem::DS4ValidationError::TouchPackets(4))));
+	assert_eq!(target.update_ex_raw(&invalid.to_bytes()), Err(vigem::Error::InvalidReport(vigem::DS4ValidationError::TouchPackets(4))));
+	assert_eq!(target.shadow().report(), vigem::DS4Report::neutral());
+
+	// The raw bytes are submitted like the decoded report
+	assert_eq!(target.update_ex_raw(&report.to_bytes()), Ok(()));
+	assert_eq!(*target.shadow(), report);
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4_update_raw_validation() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::DualShock4Wired::new(client, vigem::TargetId::DUALSHOCK4_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	// Same as update in every build, the bytes are only validated when asked to
+	let invalid = vigem::DS4Report { buttons: 0xF, ..Default::default() }.to_bytes();
+	assert_eq!(target.update_raw(&invalid), Ok(()));
+	target.set_validate_reports(true);
+	assert_eq!(target.update_raw(&invalid), Err(vigem::Error::InvalidReport(vigem::DS4ValidationError::Dpad(0xF))));
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn notification_serial_no() {
+	let Some(client) = connect() else { return };
+	let mut target1 = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	let mut target2 = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	target1.plugin().unwrap();
+	target2.plugin().unwrap();
+
+	let notification1 = target1.request_notification().unwrap();
+	let notification2 = target2.request_notification().unwrap();
+	assert_ne!(notification1.serial_no(), 0);
+	assert_ne!(notification2.serial_no(), 0);
+	assert_ne!(notification1.serial_no(), notification2.serial_no());
+
+	// The serial number outlives the target
+	let serial_no = notification1.serial_no();
+	drop(target1);
+	assert_eq!(notification1.serial_no(), serial_no);
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn notification_unplug_cancels() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let (tx, rx) = std::sync::mpsc::channel();
+	let thread = target.request_notification().unwrap().spawn_thread(|_, _| {});
+	std::thread::spawn(move || {
+		thread.join().unwrap();
+		tx.send(()).unwrap();
+	});
+
+	// The listener exits while the target is still alive
+	target.unplug().unwrap();
+	rx.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn poll_rumble_sequencing() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	assert_eq!(target.poll_rumble(), Err(vigem::Error::NotPluggedIn));
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	// Arming takes the one request of the target, polling again re-arms without requesting anew
+	for _ in 0..1000 {
+		assert_eq!(target.poll_rumble(), Ok(None));
+	}
+	assert_eq!(target.request_notification().err(), Some(vigem::Error::NotificationAlreadyActive));
+
+	// Reannouncing keeps the request
+	target.reannounce().unwrap();
+	target.wait_ready().unwrap();
+	assert_eq!(target.poll_rumble(), Ok(None));
+
+	// Unplugging drops the request, plugging in again arms a new one
+	target.unplug().unwrap();
+	assert_eq!(target.poll_rumble(), Err(vigem::Error::NotPluggedIn));
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	assert_eq!(target.poll_rumble(), Ok(None));
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+#[ignore = "needs XInput to pick up the controller"]
+fn poll_rumble_delivery() {
+	use winapi::um::xinput;
+
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	let user_index = target.wait_for_user_index(std::time::Duration::from_secs(2)).unwrap().as_u32();
+	assert_eq!(target.poll_rumble(), Ok(None));
+
+	for speed in 1..=100u16 {
+		// The driver only forwards the high byte of the motor speeds
+		let mut vibration = xinput::XINPUT_VIBRATION { wLeftMotorSpeed: speed << 8, wRightMotorSpeed: (100 - speed) << 8 };
+		assert_eq!(unsafe { xinput::XInputSetState(user_index, &mut vibration) }, 0);
+		let start = std::time::Instant::now();
+		let received = loop {
+			if let Some(notification) = target.poll_rumble().unwrap() {
+				break notification;
+			}
+			assert!(start.elapsed() < std::time::Duration::from_secs(1), "notification {} lost", speed);
+			std::thread::sleep(std::time::Duration::from_millis(1));
+		};
+		assert_eq!((received.large_motor, received.small_motor), (speed as u8, 100 - speed as u8));
+		// Not delivered twice
+		for _ in 0..50 {
+			assert_eq!(target.poll_rumble(), Ok(None));
+		}
+	}
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn rumble_listener_subscriptions() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let listener = vigem::RumbleListener::x360(target.request_notification().unwrap());
+	assert_eq!(listener.serial_no(), target.serial_no());
+	let mut every = listener.subscribe();
+	let mut latest = every.subscribe();
+	latest.set_coalesce(vigem::RumbleCoalesce::Latest);
+	assert_eq!(every.coalesce(), vigem::RumbleCoalesce::Every);
+
+	// The listener holds the one request of the target
+	assert_eq!(target.request_notification().err(), Some(vigem::Error::NotificationAlreadyActive));
+	drop(listener);
+	assert_eq!(every.try_recv(), None);
+
+	// Unplugging ends every subscription
+	target.unplug().unwrap();
+	assert_eq!(every.recv_timeout(std::time::Duration::from_secs(5)), None);
+	assert_eq!(latest.recv_timeout(std::time::Duration::from_secs(5)), None);
+	assert_eq!(every.missed(), 0);
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn rumble_listener_last_drop_cancels() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let listener = vigem::RumbleListener::x360(target.request_notification().unwrap());
+	let subscription = listener.subscribe();
+	drop(listener);
+	assert!(target.request_notification().is_err());
+
+	// Dropping the last subscription cancels the request and frees it for a new one
+	drop(subscription);
+	let start = std::time::Instant::now();
+	while target.request_notification().is_err() {
+		assert!(start.elapsed() < std::time::Duration::from_secs(5));
+		std::thread::sleep(std::time::Duration::from_millis(10));
+	}
+	assert!(target.is_attached());
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn notification_replug_discards_stale() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	let serial_no = target.serial_no();
+
+	let mut stale = Box::pin(target.request_notification().unwrap());
+
+	// Replugging likely reuses the serial number, the stale listener must not pick up the new target's notifications
+	target.unplug().unwrap();
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	assert_eq!(stale.serial_no(), serial_no);
+	stale.as_mut().request();
+	assert_eq!(stale.as_mut().poll(false), Err(vigem::Error::OperationAborted));
+	assert!(!stale.is_attached());
+
+	// The replugged target hands out a fresh listener
+	let mut fresh = Box::pin(target.request_notification().unwrap());
+	fresh.as_mut().request();
+	assert!(fresh.is_attached());
+	assert!(fresh.as_mut().poll(false).is_ok());
+}
+
+#[cfg(feature = "ds4-output")]
+#[test]
+fn ds4_notification_replug_discards_stale() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::DualShock4Wired::new(client, vigem::TargetId::DUALSHOCK4_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let mut stale = Box::pin(target.request_notification().unwrap());
+	target.unplug().unwrap();
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	stale.as_mut().request();
+	assert_eq!(stale.as_mut().poll(false), Err(vigem::Error::OperationAborted));
+	assert!(!stale.is_attached());
+}
+
+#[cfg(feature = "ds4-output")]
+#[test]
+fn ds4_notification_unplug_cancels() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::DualShock4Wired::new(client, vigem::TargetId::DUALSHOCK4_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let (tx, rx) = std::sync::mpsc::channel();
+	let thread = target.request_notification().unwrap().spawn_thread(|_, _| {});
+	std::thread::spawn(move || {
+		thread.join().unwrap();
+		tx.send(()).unwrap();
+	});
+
+	target.unplug().unwrap();
+	rx.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+}
+
+#[test]
+fn stale_timeout() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+
+	target.set_stale_timeout(Some(std::time::Duration::from_millis(50))).unwrap();
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let gamepad = vigem::XGamepad { buttons: vigem::XButtons!(A), ..Default::default() };
+	target.update(&gamepad).unwrap();
+	assert!(!target.is_stale());
+
+	std::thread::sleep(std::time::Duration::from_millis(200));
+	assert!(target.is_stale());
+
+	target.update(&gamepad).unwrap();
+	assert!(!target.is_stale());
+
+	target.set_stale_timeout(None).unwrap();
+	std::thread::sleep(std::time::Duration::from_millis(200));
+	assert!(!target.is_stale());
+}
+
+#[test]
+fn attached_count() {
+	let Some(client) = connect() else { return };
+	assert_eq!(client.attached_count(), 0);
+
+	let mut target1 = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	let mut target2 = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	let mut target3 = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	target1.plugin().unwrap();
+	target2.plugin().unwrap();
+	target3.plugin().unwrap();
+	assert_eq!(client.attached_count(), 3);
+
+	let mut serials = client.attached_serials();
+	serials.sort_unstable();
+	let mut expected = vec![target1.serial_no(), target2.serial_no(), target3.serial_no()];
+	expected.sort_unstable();
+	assert_eq!(serials, expected);
+
+	// Unplug
+	target1.unplug().unwrap();
+	assert_eq!(client.attached_count(), 2);
+
+	// Drop
+	drop(target2);
+	assert_eq!(client.attached_count(), 1);
+
+	// Manual drop returning the client
+	let _client = target3.drop();
+	assert_eq!(client.attached_count(), 0);
+
+	// Failed unplug does not change the count
+	assert_eq!(target1.unplug(), Err(vigem::Error::NotPluggedIn));
+	assert_eq!(client.attached_count(), 0);
+}
+
+#[test]
+fn detached_policy() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	let gamepad = vigem::XGamepad::default();
+
+	assert_eq!(target.detached_policy(), vigem::DetachedPolicy::Error);
+	assert_eq!(target.update(&gamepad), Err(vigem::Error::NotPluggedIn));
+
+	target.set_detached_policy(vigem::DetachedPolicy::Ignore);
+	assert_eq!(target.update(&gamepad), Ok(()));
+	assert_eq!(target.update_timed(&gamepad), Ok(None));
+	assert!(!target.is_attached());
+
+	target.set_detached_policy(vigem::DetachedPolicy::AutoPlugin);
+	assert_eq!(target.update(&gamepad), Ok(()));
+	assert!(target.is_attached());
+
+	target.unplug().unwrap();
+	assert_eq!(target.update(&gamepad), Ok(()));
+	assert!(target.is_attached());
+}
+
+#[test]
+fn stale_timeout_manual() {
+	use vigem::Pump;
+
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+
+	target.set_stale_timeout_manual(Some(std::time::Duration::from_millis(50))).unwrap();
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let gamepad = vigem::XGamepad { buttons: vigem::XButtons!(A), ..Default::default() };
+	target.update(&gamepad).unwrap();
+
+	// Without a background thread nothing happens until the target is pumped
+	std::thread::sleep(std::time::Duration::from_millis(200));
+	assert!(!target.is_stale());
+
+	let now = std::time::Instant::now();
+	target.pump(now).unwrap();
+	assert!(target.is_stale());
+}
+
+#[test]
+fn drive() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	assert_eq!(target.drive(100.0, |_| Some(vigem::XGamepad::default())), Err(vigem::Error::NotPluggedIn));
+
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	// Invalid rates fail before driving instead of panicking
+	for rate in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+		assert_eq!(target.drive(rate, |_| Some(vigem::XGamepad::default())), Err(vigem::Error::InvalidConfig));
+	}
+	let summary = target.drive(100.0, |elapsed| {
+		(elapsed < std::time::Duration::from_millis(100)).then(vigem::XGamepad::default)
+	}).unwrap();
+	assert_eq!(summary.stop, Some(vigem::DriveStop::Finished));
+	assert_eq!(summary.submitted + summary.not_ready + 1, summary.ticks);
+}
+
+#[test]
+fn drive_from_cell_close() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	// The gamepad published right before closing is submitted before driving stops
+	let gamepad = vigem::XGamepad::pressing(vigem::XButtons!(A));
+	let cell = vigem::StateCell::new(vigem::XGamepad::default());
+	cell.publish(gamepad);
+	cell.close();
+	let summary = target.drive_from_cell(&cell, 100.0).unwrap();
+	assert_eq!(summary.stop, Some(vigem::DriveStop::Finished));
+	assert_eq!(summary.ticks, 2);
+	assert_eq!(summary.submitted + summary.not_ready, 1);
+}
+
+#[test]
+fn max_update_rate() {
+	use vigem::Pump;
+
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	target.set_max_update_rate(Some(10.0)).unwrap();
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	// A runaway producer, only the first update is submitted and the latest is kept pending
+	for i in 0..1000 {
+		target.update(&vigem::XGamepad { left_trigger: i as u8, ..Default::default() }).unwrap();
+	}
+	assert_eq!(target.pending_update().map(|gamepad| gamepad.left_trigger), Some((999 % 256) as u8));
+
+	// Pumping before the interval elapsed keeps it pending
+	target.pump(std::time::Instant::now()).unwrap();
+	assert!(target.pending_update().is_some());
+	target.flush().unwrap();
+	assert!(target.pending_update().is_none());
+
+	target.update(&vigem::XGamepad::default()).unwrap();
+	target.unplug().unwrap();
+	assert!(target.pending_update().is_none());
+	target.set_max_update_rate(None).unwrap();
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn notification_poller() {
+	use vigem::Pump;
+
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let mut poller = vigem::XNotificationPoller::new(target.request_notification().unwrap());
+	poller.pump(std::time::Instant::now()).unwrap();
+	assert!(poller.is_attached());
+
+	// Unplugging aborts the pending request, observed on the next pump
+	target.unplug().unwrap();
+	poller.pump(std::time::Instant::now()).unwrap();
+	assert!(!poller.is_attached());
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn notification_already_active() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let notification = target.request_notification().unwrap();
+	assert_eq!(target.request_notification().err(), Some(vigem::Error::NotificationAlreadyActive));
+
+	// Forcing ignores the live request
+	let forced = target.force_request_notification().unwrap();
+	drop(forced);
+	assert_eq!(target.request_notification().err(), Some(vigem::Error::NotificationAlreadyActive));
+
+	// Dropping the request releases the target
+	drop(notification);
+	let notification = target.request_notification().unwrap();
+
+	// As does unplugging the target
+	target.unplug().unwrap();
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	target.request_notification().unwrap();
+	drop(notification);
+}
+
+#[cfg(feature = "ds4-output")]
+#[test]
+fn ds4_notification_already_active() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::DualShock4Wired::new(client, vigem::TargetId::DUALSHOCK4_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let notification = target.request_notification().unwrap();
+	assert_eq!(target.request_notification().err(), Some(vigem::Error::NotificationAlreadyActive));
+	let _forced = target.force_request_notification().unwrap();
+	drop(notification);
+	assert_eq!(target.request_notification().err(), Some(vigem::Error::NotificationAlreadyActive));
+}
+
+#[test]
+fn progress_observer() {
+	use std::sync::{Arc, Mutex};
+	use vigem::PlugStage;
+
+	let Some(client) = connect() else { return };
+	let stages = Arc::new(Mutex::new(Vec::new()));
+	let observer = |stages: &Arc<Mutex<Vec<PlugStage>>>| -> vigem::ProgressObserver {
+		let stages = stages.clone();
+		Box::new(move |stage| stages.lock().unwrap().push(stage))
+	};
+
+	let mut first = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	first.set_progress_observer(Some(observer(&stages)));
+	first.plugin().unwrap();
+	first.wait_ready().unwrap();
+
+	// Serial numbers in use by other targets are scanned past
+	let serial = first.serial_no();
+	let mut expected: Vec<_> = (1..=serial).map(|attempt| PlugStage::SerialScan { attempt }).collect();
+	expected.extend([PlugStage::DriverAccepted { serial }, PlugStage::WaitingReady, PlugStage::Ready]);
+	assert_eq!(*stages.lock().unwrap(), expected);
+
+	// The second target has to retry at least past the serial number of the first
+	let stages = Arc::new(Mutex::new(Vec::new()));
+	let mut second = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	second.set_progress_observer(Some(observer(&stages)));
+	second.plugin().unwrap();
+	let serial = second.serial_no();
+	let mut expected: Vec<_> = (1..=serial).map(|attempt| PlugStage::SerialScan { attempt }).collect();
+	expected.push(PlugStage::DriverAccepted { serial });
+	assert_eq!(*stages.lock().unwrap(), expected);
+	assert!(expected.len() > 2);
+}
+
+#[test]
+fn reannounce() {
+	use std::sync::{Arc, Mutex};
+	use vigem::PlugStage;
+
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	target.update(&vigem::XGamepad::pressing(vigem::XButtons!(A))).unwrap();
+	let serial = target.serial_no();
+
+	let stages = Arc::new(Mutex::new(Vec::new()));
+	let observer = stages.clone();
+	target.set_progress_observer(Some(Box::new(move |stage| observer.lock().unwrap().push(stage))));
+	target.reannounce().unwrap();
+
+	assert!(target.is_attached());
+	assert_eq!(target.serial_no(), serial);
+	assert_eq!(*stages.lock().unwrap(), [
+		PlugStage::Removed { serial },
+		PlugStage::SerialScan { attempt: serial },
+		PlugStage::DriverAccepted { serial },
+		PlugStage::WaitingReady,
+		PlugStage::Ready,
+	]);
+	assert_eq!(client.attached_serials(), [serial]);
+	target.update(&vigem::XGamepad::default()).unwrap();
+}
+
+#[test]
+fn target_uid() {
+	use std::sync::{Arc, Mutex};
+
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	let raw = vigem::RawTarget::new(&client, vigem::RawTarget::TYPE_XBOX360_WIRED, vigem::TargetId::XBOX360_WIRED);
+	let uid = target.uid();
+	assert!(raw.uid() > uid);
+	assert_eq!(uid.to_string(), format!("#{}", uid.get()));
+	assert!(format!("{:?}", target).contains(&format!("uid: {:?}", uid)));
+	assert!(format!("{:?}", raw).contains(&format!("uid: {:?}", raw.uid())));
+
+	// The progress observer is told which target progressed
+	let seen = Arc::new(Mutex::new(Vec::new()));
+	let observer = seen.clone();
+	target.observe_progress(move |uid, stage| observer.lock().unwrap().push((uid, stage)));
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	assert!(!seen.lock().unwrap().is_empty());
+	assert!(seen.lock().unwrap().iter().all(|&(seen, _)| seen == uid));
+
+	// Kept across reannouncing and replugging
+	target.reannounce().unwrap();
+	target.unplug().unwrap();
+	target.plugin().unwrap();
+	assert_eq!(target.uid(), uid);
+	let fleet_target = vigem::Profile::xbox360().create_target(&client).unwrap();
+	assert!(fleet_target.uid() > raw.uid());
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn notification_uid() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let notification = target.request_notification().unwrap();
+	assert_eq!(notification.uid(), target.uid());
+	assert!(format!("{:?}", notification).contains(&format!("uid: {:?}", target.uid())));
+	let listener = vigem::RumbleListener::x360(notification);
+	assert_eq!(listener.uid(), target.uid());
+	assert_eq!(listener.subscribe().uid(), target.uid());
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn reannounce_keeps_listener() {
+	use std::{sync::mpsc, time::Duration};
+	use winapi::um::xinput;
+
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let (tx, rx) = mpsc::channel();
+	let thread = target.request_notification().unwrap().spawn_thread(move |_, data| {
+		let _ = tx.send(data);
+	});
+
+	target.reannounce().unwrap();
+	while rx.try_recv().is_ok() {}
+	assert!(!thread.is_finished());
+
+	// The listener receives the notifications of the reannounced target
+	let user_index = target.get_user_index().unwrap();
+	let mut vibration = xinput::XINPUT_VIBRATION { wLeftMotorSpeed: 0x8000, wRightMotorSpeed: 0 };
+	assert_eq!(unsafe { xinput::XInputSetState(user_index.into(), &mut vibration) }, 0);
+	let deadline = std::time::Instant::now() + Duration::from_secs(5);
+	loop {
+		let data = rx.recv_timeout(deadline.saturating_duration_since(std::time::Instant::now())).unwrap();
+		if data.large_motor != 0 {
+			break;
+		}
+	}
+
+	// And still ends once the target is unplugged
+	target.unplug().unwrap();
+	thread.join().unwrap();
+}
+
+#[test]
+fn reannounce_serial_taken() {
+	use std::sync::{Arc, Mutex};
+
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	let serial = target.serial_no();
+
+	// Another client takes the serial number while the target is unplugged
+	let thief = Arc::new(Mutex::new(vigem::Xbox360Wired::new(vigem::Client::connect().unwrap(), vigem::TargetId::XBOX360_WIRED)));
+	let observer = thief.clone();
+	target.set_progress_observer(Some(Box::new(move |stage| {
+		if let vigem::PlugStage::Removed { serial } = stage {
+			observer.lock().unwrap().plugin_at(serial).unwrap();
+		}
+	})));
+
+	#[cfg(feature = "xusb-rumble")]
+	let mut listener = Box::pin(target.request_notification().unwrap());
+	#[cfg(feature = "xusb-rumble")]
+	listener.as_mut().request();
+
+	assert_eq!(target.reannounce(), Err(vigem::Error::SerialInUse));
+	assert!(!target.is_attached());
+	assert_eq!(thief.lock().unwrap().serial_no(), serial);
+	assert!(client.attached_serials().is_empty());
+
+	// The listener does not follow the target to the other client's controller
+	#[cfg(feature = "xusb-rumble")]
+	{
+		assert_eq!(listener.as_mut().poll(true), Err(vigem::Error::OperationAborted));
+		assert!(!listener.is_attached());
+	}
+
+	// The target can be plugged in anew
+	target.set_progress_observer(None);
+	target.plugin().unwrap();
+	assert_ne!(target.serial_no(), serial);
+}
+
+#[test]
+#[ignore = "plugs in 64 controllers"]
+fn shared_thread_event() {
+	fn handle_count() -> u32 {
+		let mut count = 0;
+		unsafe {
+			winapi::um::processthreadsapi::GetProcessHandleCount(winapi::um::processthreadsapi::GetCurrentProcess(), &mut count);
+		}
+		count
+	}
+
+	let Some(client) = connect() else { return };
+	let mut targets: Vec<_> = (0..64).map(|_| vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED)).collect();
+
+	// Warm up the event shared by the targets on this thread
+	targets[0].plugin().unwrap();
+	targets[0].unplug().unwrap();
+
+	let before = handle_count();
+	for target in &mut targets {
+		target.plugin().unwrap();
+	}
+	let after = handle_count();
+	// Every target waiting on an event of its own would add 64 handles
+	assert!(after < before + 16, "handle count grew from {} to {}", before, after);
+
+	for target in &mut targets {
+		target.wait_ready().unwrap();
+		target.update(&vigem::XGamepad::default()).unwrap();
+	}
+}
+
+#[test]
+fn probe_serial() {
+	let Some(client) = connect() else { return };
+	assert_eq!(client.probe_serial(0), Ok(Some(false)));
+
+	let mut target = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	let serial_no = target.serial_no();
+	// Probing right after plugging in may find the target still busy getting ready
+	if client.probe_serial(serial_no).unwrap().is_none() {
+		// Driver too old to tell
+		return;
+	}
+	assert_eq!(client.probe_serial(serial_no), Ok(Some(true)));
+	target.wait_ready().unwrap();
+	assert_eq!(client.probe_serial(serial_no), Ok(Some(true)));
+
+	target.unplug().unwrap();
+	assert_eq!(client.probe_serial(serial_no), Ok(Some(false)));
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn drop_with_pending_notification() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	let serial_no = target.serial_no();
+
+	// Keep a notification request pending while the target is dropped
+	let mut reqn = Box::pin(target.request_notification().unwrap());
+	reqn.as_mut().request();
+
+	// The bound on a wedged driver is unit tested in the bus module
+	drop(target);
+	drop(reqn);
+	assert_ne!(client.probe_serial(serial_no), Ok(Some(true)));
+}
+
+#[test]
+fn maintenance_blocks_updates() {
+	use std::sync::{mpsc, Arc};
+	use std::time::Duration;
+
+	let Some(client) = connect() else { return };
+	let client = Arc::new(client);
+	let mut target = vigem::Xbox360Wired::new(client.clone(), vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let guard = client.maintenance();
+	// The maintenance thread keeps access to the driver
+	assert_eq!(client.probe_serial(target.serial_no()), Ok(Some(true)));
+
+	let (tx, rx) = mpsc::channel();
+	let thread = std::thread::spawn(move || {
+		tx.send(target.update(&vigem::XGamepad::default())).unwrap();
+		target
+	});
+
+	// Updates from other threads resume once the guard is dropped
+	assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+	drop(guard);
+	assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(Ok(())));
+	thread.join().unwrap();
+}
+
+#[test]
+#[ignore = "hammers the driver with updates"]
+fn maintenance_interleaves_updates() {
+	use std::sync::atomic::{AtomicBool, Ordering};
+	use std::sync::Arc;
+
+	let Some(client) = connect() else { return };
+	let client = Arc::new(client);
+	let mut target = vigem::Xbox360Wired::new(client.clone(), vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	// Hammer updates while maintenance repeatedly waits for the ones in flight
+	let done = Arc::new(AtomicBool::new(false));
+	let thread = std::thread::spawn({
+		let done = done.clone();
+		move || {
+			let mut count = 0;
+			while !done.load(Ordering::Relaxed) {
+				target.update(&vigem::XGamepad::default()).unwrap();
+				count += 1;
+			}
+			count
+		}
+	});
+
+	for _ in 0..100 {
+		let _guard = client.maintenance();
+		std::thread::sleep(std::time::Duration::from_millis(1));
+	}
+	done.store(true, Ordering::Relaxed);
+	assert!(thread.join().unwrap() > 0);
+}
+
+#[test]
+fn iocp_completions() {
+	use std::collections::HashMap;
+	use std::time::Duration;
+
+	let Some(client) = connect() else { return };
+	let mut targets: Vec<_> = (0..4).map(|_| vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED)).collect();
+	for target in &mut targets {
+		target.plugin().unwrap();
+		target.wait_ready().unwrap();
+	}
+
+	let mut driver = vigem::IocpDriver::new(&client).unwrap();
+	assert!(client.is_iocp_enabled());
+	assert_eq!(vigem::IocpDriver::new(&client).err(), Some(vigem::Error::CompletionPortInUse));
+
+	// Every request completes exactly once and is attributed to its target
+	let mut expected = HashMap::new();
+	for round in 0..100 {
+		let gamepad = vigem::XGamepad { thumb_lx: round, ..Default::default() };
+		for target in &targets {
+			let key = driver.begin_update(target, &gamepad).unwrap();
+			assert_eq!(expected.insert(key, target.serial_no()), None);
+		}
+	}
+	while driver.pending() > 0 {
+		for event in driver.poll(Some(Duration::from_secs(5))).unwrap() {
+			assert_eq!(expected.remove(&event.key), Some(event.serial_no));
+			assert_eq!(event.result, Ok(vigem::Completion::Update));
+		}
+	}
+	assert!(expected.is_empty());
+
+	// The blocking API keeps working alongside the driver
+	for target in &mut targets {
+		target.update(&vigem::XGamepad::default()).unwrap();
+	}
+	assert_eq!(driver.poll(Some(Duration::ZERO)), Ok(Vec::new()));
+}
+
+#[test]
+fn iocp_invalid_client() {
+	let Some(client) = connect() else { return };
+	let Some(other) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(&other, vigem::TargetId::XBOX360_WIRED);
+	let mut driver = vigem::IocpDriver::new(&client).unwrap();
+
+	assert_eq!(driver.begin_update(&target, &vigem::XGamepad::default()), Err(vigem::Error::InvalidClient));
+	target.plugin().unwrap();
+	assert_eq!(driver.begin_update(&target, &vigem::XGamepad::default()), Err(vigem::Error::InvalidClient));
+
+	let unplugged = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	assert_eq!(driver.begin_update(&unplugged, &vigem::XGamepad::default()), Err(vigem::Error::NotPluggedIn));
+
+	// The port is released with the driver
+	drop(driver);
+	drop(vigem::IocpDriver::new(&client).unwrap());
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn iocp_cancel_notification() {
+	use std::time::Duration;
+
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let mut driver = vigem::IocpDriver::new(&client).unwrap();
+	let key = driver.begin_notification(&target).unwrap();
+	assert!(driver.cancel(key));
+
+	let events = driver.poll(Some(Duration::from_secs(5))).unwrap();
+	assert_eq!(events.len(), 1);
+	assert_eq!(events[0].key, key);
+	assert_eq!(events[0].result, Err(vigem::Error::OperationAborted));
+	assert!(!driver.cancel(key));
+}
+
+#[test]
+fn fleet_restore_after_clean_shutdown() {
+	let Some(client) = connect() else { return };
+	let mut fleet = vigem::Fleet::new();
+	fleet.push(vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED));
+	fleet.push(vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED));
+	fleet.plugin_all().unwrap();
+	let gamepad = vigem::XGamepad { thumb_lx: 1234, ..Default::default() };
+	fleet.update(1, gamepad).unwrap();
+	let snapshot = fleet.snapshot();
+	assert_eq!(snapshot.targets[1].last_report, Some(vigem::TargetReport::Xbox360Wired(gamepad)));
+
+	// Dropping the fleet unplugs the targets, they are plugged in anew
+	drop(fleet);
+	let (fleet, outcomes) = vigem::Fleet::restore(&client, &snapshot);
+	assert_eq!(fleet.len(), 2);
+	for (outcome, target) in outcomes.iter().zip(fleet.snapshot().targets) {
+		assert_eq!(*outcome, vigem::RestoreOutcome::Recreated { serial_no: target.serial_no });
+	}
+	assert_eq!(fleet.snapshot().targets[1].last_report, Some(vigem::TargetReport::Xbox360Wired(gamepad)));
+}
+
+#[test]
+fn fleet_restore_adopts_persisted() {
+	let Some(client) = connect() else { return };
+	let mut fleet = vigem::Fleet::new();
+	fleet.push(vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED));
+	fleet.plugin_all().unwrap();
+	let serial_no = fleet.get(0).unwrap().serial_no();
+
+	// Persisted targets stay plugged in and are taken over
+	let snapshot = fleet.persist();
+	assert_eq!(client.probe_serial(serial_no), Ok(Some(true)));
+	let (fleet, outcomes) = vigem::Fleet::restore(&client, &snapshot);
+	assert_eq!(outcomes, [vigem::RestoreOutcome::Adopted { serial_no }]);
+	assert_eq!(fleet.get(0).unwrap().serial_no(), serial_no);
+	assert_eq!(client.attached_serials(), [serial_no]);
+
+	drop(fleet);
+	assert_eq!(client.probe_serial(serial_no), Ok(Some(false)));
+}
+
+#[test]
+fn fleet_restore_stale_serials() {
+	let Some(client) = connect() else { return };
+	// A serial number from a previous session which is no longer plugged in
+	let snapshot = vigem::FleetSnapshot {
+		targets: vec![vigem::TargetSnapshot {
+			kind: vigem::TargetKind::Xbox360Wired,
+			id: vigem::TargetId::XBOX360_WIRED,
+			serial_no: 60000,
+			detached_policy: vigem::DetachedPolicy::Ignore,
+			validate_reports: false,
+			last_report: None,
+		}],
+	};
+	let (fleet, outcomes) = vigem::Fleet::restore(&client, &snapshot);
+	let serial_no = fleet.get(0).unwrap().serial_no();
+	assert_ne!(serial_no, 0);
+	assert_eq!(outcomes, [vigem::RestoreOutcome::Recreated { serial_no }]);
+	assert_eq!(fleet.snapshot().targets[0].detached_policy, vigem::DetachedPolicy::Ignore);
+}
+
+#[test]
+fn fleet_restore_unplugged() {
+	let Some(client) = connect() else { return };
+	let mut fleet = vigem::Fleet::new();
+	fleet.push(vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED));
+	fleet.push(vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED));
+	fleet.get_mut(1).unwrap().plugin().unwrap();
+	fleet.get_mut(1).unwrap().wait_ready().unwrap();
+	let snapshot = fleet.persist();
+	assert_eq!(snapshot.targets[0].serial_no, 0);
+
+	// The target which was never plugged in stays unplugged
+	let (fleet, outcomes) = vigem::Fleet::restore(&client, &snapshot);
+	let serial_no = snapshot.targets[1].serial_no;
+	assert_eq!(outcomes, [vigem::RestoreOutcome::Unplugged, vigem::RestoreOutcome::Adopted { serial_no }]);
+	assert!(!fleet.get(0).unwrap().is_attached());
+	assert_eq!(client.attached_serials(), [serial_no]);
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn fleet_restore_partial_failure() {
+	let Some(client) = connect() else { return };
+	let mut ds4 = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+	ds4.plugin().unwrap();
+	ds4.wait_ready().unwrap();
+
+	// The first serial number is now owned by a DualShock4 target
+	let x360 = |serial_no| vigem::TargetSnapshot {
+		kind: vigem::TargetKind::Xbox360Wired,
+		id: vigem::TargetId::XBOX360_WIRED,
+		serial_no,
+		detached_policy: vigem::DetachedPolicy::Error,
+		validate_reports: false,
+		last_report: None,
+	};
+	let snapshot = vigem::FleetSnapshot { targets: vec![x360(ds4.serial_no()), x360(60000)] };
+	let (fleet, outcomes) = vigem::Fleet::restore(&client, &snapshot);
+
+	// The mismatch fails its entry and leaves the other target alone, the rest is restored
+	assert_eq!(outcomes[0], vigem::RestoreOutcome::Failed(vigem::Error::KindMismatch));
+	assert_eq!(fleet.get(0).unwrap().serial_no(), 0);
+	assert!(matches!(outcomes[1], vigem::RestoreOutcome::Recreated { .. }));
+	assert_eq!(client.probe_serial(ds4.serial_no()), Ok(Some(true)));
+	assert!(client.attached_serials().contains(&ds4.serial_no()));
+}
+
+#[test]
+fn any_target_forwarding() {
+	let Some(client) = connect() else { return };
+	let client = std::sync::Arc::new(client);
+	let id = vigem::TargetId { vendor: 0x045E, product: 0x028F };
+	let mut target: vigem::AnyTarget = vigem::Xbox360Wired::new(client.clone(), id).into();
+	assert_eq!(target.kind(), vigem::TargetKind::Xbox360Wired);
+	assert_eq!(target.id(), id);
+	assert!(!target.is_attached());
+	assert_eq!(target.submit_xbox(&vigem::XGamepad::default()), Err(vigem::Error::NotPluggedIn));
+	assert_eq!(target.wait_ready(), Err(vigem::Error::NotPluggedIn));
+	assert_eq!(target.unplug(), Err(vigem::Error::NotPluggedIn));
+
+	target.plugin().unwrap();
+	assert!(target.is_attached());
+	assert_ne!(target.serial_no(), 0);
+	assert_eq!(target.plugin(), Err(vigem::Error::AlreadyConnected));
+	target.wait_ready().unwrap();
+	assert_eq!(client.attached_serials(), [target.serial_no()]);
+	target.submit_xbox(&vigem::XGamepad::pressing(vigem::XButtons!(A))).unwrap();
+
+	target.unplug().unwrap();
+	assert!(!target.is_attached());
+	assert_eq!(client.attached_count(), 0);
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn any_target_mismatch() {
+	let Some(client) = connect() else { return };
+	let client = std::sync::Arc::new(client);
+	let mut targets: Vec<vigem::AnyTarget> = vec![
+		vigem::Xbox360Wired::new(client.clone(), vigem::TargetId::XBOX360_WIRED).into(),
+		vigem::DualShock4Wired::new(client.clone(), vigem::TargetId::DUALSHOCK4_WIRED).into(),
+	];
+	for target in &mut targets {
+		target.plugin().unwrap();
+		target.wait_ready().unwrap();
+	}
+	assert_eq!(targets[1].kind(), vigem::TargetKind::DualShock4Wired);
+	assert_eq!(targets[1].id(), vigem::TargetId::DUALSHOCK4_WIRED);
+
+	// Reports of another kind are rejected before reaching the driver
+	assert_eq!(targets[0].submit_ds4(&vigem::DS4Report::default()), Err(vigem::Error::KindMismatch));
+	assert_eq!(targets[1].submit_xbox(&vigem::XGamepad::default()), Err(vigem::Error::KindMismatch));
+	assert_eq!(targets[1].update(&vigem::XGamepad::default().into()), Err(vigem::Error::KindMismatch));
+
+	assert_eq!(targets[0].submit_xbox(&vigem::XGamepad::default()), Ok(()));
+	assert_eq!(targets[1].submit_ds4(&vigem::DS4Report::default()), Ok(()));
+	assert_eq!(targets[1].update(&vigem::DS4Report::default().into()), Ok(()));
+
+	drop(targets);
+	assert_eq!(client.attached_count(), 0);
+}
+
+#[test]
+fn raw_target_lifecycle() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::RawTarget::new(&client, vigem::RawTarget::TYPE_XBOX360_WIRED, vigem::TargetId::XBOX360_WIRED);
+	let report = vigem::XGamepad::pressing(vigem::XButtons!(A)).to_bytes();
+	assert_eq!(target.submit_raw(vigem::RawTarget::IOCTL_XUSB_SUBMIT_REPORT, &report), Err(vigem::Error::NotPluggedIn));
+
+	target.plugin().unwrap();
+	assert_eq!(target.plugin(), Err(vigem::Error::AlreadyConnected));
+	target.wait_ready().unwrap();
+	let serial_no = target.serial_no();
+	assert_eq!(client.attached_serials(), [serial_no]);
+	target.submit_raw(vigem::RawTarget::IOCTL_XUSB_SUBMIT_REPORT, &report).unwrap();
+
+	target.unplug().unwrap();
+	assert_eq!(client.attached_count(), 0);
+	assert_eq!(client.probe_serial(serial_no), Ok(Some(false)));
+}
+
+#[test]
+fn raw_target_bogus_type() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::RawTarget::new(&client, 0x7F, vigem::TargetId::XBOX360_WIRED);
+
+	// Fails without scanning every serial number
+	let start = std::time::Instant::now();
+	assert!(target.plugin().is_err());
+	assert!(start.elapsed() < std::time::Duration::from_secs(5));
+	assert!(!target.is_attached());
+	assert_eq!(client.attached_count(), 0);
+}
+
+// Opens the device path for querying only, as done by HID enumeration
+fn open_device(path: &std::ffi::OsStr) -> bool {
+	use std::os::windows::ffi::OsStrExt;
+	use winapi::um::{fileapi, handleapi, winnt};
+	let path: Vec<u16> = path.encode_wide().chain(Some(0)).collect();
+	unsafe {
+		let handle = fileapi::CreateFileW(
+			path.as_ptr(),
+			0,
+			winnt::FILE_SHARE_READ | winnt::FILE_SHARE_WRITE,
+			std::ptr::null_mut(),
+			fileapi::OPEN_EXISTING,
+			0,
+			std::ptr::null_mut());
+		if handle == handleapi::INVALID_HANDLE_VALUE {
+			return false;
+		}
+		handleapi::CloseHandle(handle);
+		true
+	}
+}
+
+#[test]
+fn device_path() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::Xbox360Wired::new(client, vigem::TargetId::XBOX360_WIRED);
+	assert_eq!(target.device_path(), Err(vigem::Error::NotPluggedIn));
+
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	let path = target.device_path().unwrap();
+	assert!(open_device(&path), "failed to open {:?}", path);
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4_device_path() {
+	let Some(client) = connect() else { return };
+	let mut target = vigem::DualShock4Wired::new(client, vigem::TargetId::DUALSHOCK4_WIRED);
+	assert_eq!(target.device_path(), Err(vigem::Error::NotPluggedIn));
+
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	let path = target.device_path().unwrap();
+	assert!(open_device(&path), "failed to open {:?}", path);
+
+	// Each target resolves to its own device
+	let mut other = vigem::DualShock4Wired::new(vigem::Client::connect().unwrap(), vigem::TargetId::DUALSHOCK4_WIRED);
+	other.plugin().unwrap();
+	other.wait_ready().unwrap();
+	assert_ne!(other.device_path().unwrap(), path);
+}
+
+// The panic hook is process wide, the panicking part runs in a child process to keep it away from the other tests
+#[test]
+fn panic_guard() {
+	if std::env::var_os("VIGEM_PANIC_GUARD_CHILD").is_some() {
+		return panic_guard_child();
+	}
+	let Some(_client) = connect() else { return };
+	let output = std::process::Command::new(std::env::current_exe().unwrap())
+		.args(["panic_guard", "--exact", "--nocapture", "--test-threads=1"])
+		.env("VIGEM_PANIC_GUARD_CHILD", "1")
+		.output()
+		.unwrap();
+	let stdout = String::from_utf8_lossy(&output.stdout);
+	assert!(output.status.success(), "{}{}", stdout, String::from_utf8_lossy(&output.stderr));
+	assert!(stdout.contains("panic guard: unplugged") || stdout.contains("panic guard: skipped"), "{}", stdout);
+}
+
+fn panic_guard_child() {
+	let client = vigem::Client::connect().unwrap();
+	let mut target = vigem::Xbox360Wired::new(client.try_clone().unwrap(), vigem::TargetId::XBOX360_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	target.update(&vigem::XGamepad::pressing(vigem::XButtons!(A))).unwrap();
+	target.set_panic_guard(true).unwrap();
+	let serial_no = target.serial_no();
+
+	// A panic of a worker thread does not end the process, the target is left alone
+	let result = std::thread::spawn(|| panic!("testing the panic guard on a worker")).join();
+	assert!(result.is_err());
+	assert!(target.is_attached());
+	assert_eq!(target.update(&vigem::XGamepad::neutral()), Ok(()));
+
+	// A caught panic of the main thread unplugs the target
+	let main = std::thread::Builder::new().name("main".into());
+	let result = main.spawn(|| panic!("testing the panic guard")).unwrap().join();
+	assert!(result.is_err());
+	assert!(!target.is_attached());
+	let unplugged = client.probe_serial(serial_no).unwrap();
+
+	// The target knows it was unplugged, updates follow the detached policy instead of going to the stale serial number
+	assert_eq!(target.update(&vigem::XGamepad::neutral()), Err(vigem::Error::NotPluggedIn));
+	assert_eq!(target.serial_no(), 0);
+	assert!(!target.client().attached_serials().contains(&serial_no));
+	target.set_detached_policy(vigem::DetachedPolicy::AutoPlugin);
+	assert_eq!(target.update(&vigem::XGamepad::neutral()), Ok(()));
+	assert!(target.is_attached());
+	assert_eq!(target.unplug(), Ok(()));
+
+	match unplugged {
+		Some(plugged_in) => {
+			assert!(!plugged_in, "target still plugged in after the panic");
+			println!("panic guard: unplugged");
+		},
+		None => println!("panic guard: skipped, the driver is too old to probe serial numbers"),
+	}
+}
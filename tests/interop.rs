@@ -0,0 +1,104 @@
+use vigem_client as vigem;
+
+#[test]
+fn axis_range() {
+	let table = [
+		(-1.0, i16::MIN),
+		(-0.5, -16384),
+		(0.0, 0),
+		(0.5, 16384),
+		(1.0, i16::MAX),
+		// Out of range values are clamped
+		(-2.0, i16::MIN),
+		(7.5, i16::MAX),
+		(f64::NEG_INFINITY, i16::MIN),
+		(f64::INFINITY, i16::MAX),
+		(f64::NAN, 0),
+		// Rounding near the extremes
+		(1.0 - 0.4 / 32767.0, i16::MAX),
+		(-1.0 + 0.4 / 32768.0, i16::MIN),
+		(0.4 / 32767.0, 0),
+	];
+	for &(value, expected) in &table {
+		let state = vigem::GenericGamepadState {
+			left_thumb_x: value,
+			left_thumb_y: value,
+			right_thumb_x: value,
+			right_thumb_y: value,
+			..Default::default()
+		};
+		let gamepad = vigem::XGamepad::from(state);
+		assert_eq!(gamepad.thumb_lx, expected, "{}", value);
+		assert_eq!(gamepad.thumb_ly, expected, "{}", value);
+		assert_eq!(gamepad.thumb_rx, expected, "{}", value);
+		assert_eq!(gamepad.thumb_ry, expected, "{}", value);
+	}
+}
+
+#[test]
+fn trigger_range() {
+	let table = [
+		(0.0, 0),
+		(0.5, 128),
+		(1.0, 255),
+		(-1.0, 0),
+		(2.0, 255),
+		(f64::NAN, 0),
+		(0.5 / 255.0 - 0.0001, 0),
+		(1.0 - 0.4 / 255.0, 255),
+	];
+	for &(value, expected) in &table {
+		let state = vigem::GenericGamepadState { left_trigger: value, right_trigger: value, ..Default::default() };
+		let gamepad = vigem::XGamepad::from(state);
+		assert_eq!(gamepad.left_trigger, expected, "{}", value);
+		assert_eq!(gamepad.right_trigger, expected, "{}", value);
+	}
+}
+
+type Press = fn(&mut vigem::GenericGamepadState);
+
+#[test]
+fn button_table() {
+	let table: [(Press, vigem::XButtons); 15] = [
+		(|s| s.a = true, vigem::XButtons!(A)),
+		(|s| s.b = true, vigem::XButtons!(B)),
+		(|s| s.x = true, vigem::XButtons!(X)),
+		(|s| s.y = true, vigem::XButtons!(Y)),
+		(|s| s.dpad_up = true, vigem::XButtons!(UP)),
+		(|s| s.dpad_down = true, vigem::XButtons!(DOWN)),
+		(|s| s.dpad_left = true, vigem::XButtons!(LEFT)),
+		(|s| s.dpad_right = true, vigem::XButtons!(RIGHT)),
+		(|s| s.left_shoulder = true, vigem::XButtons!(LB)),
+		(|s| s.right_shoulder = true, vigem::XButtons!(RB)),
+		(|s| s.left_thumbstick = true, vigem::XButtons!(LTHUMB)),
+		(|s| s.right_thumbstick = true, vigem::XButtons!(RTHUMB)),
+		(|s| s.view = true, vigem::XButtons!(BACK)),
+		(|s| s.menu = true, vigem::XButtons!(START)),
+		(|s| s.guide = true, vigem::XButtons!(GUIDE)),
+	];
+	let mut all = vigem::GenericGamepadState::default();
+	for &(press, buttons) in &table {
+		let mut state = vigem::GenericGamepadState::default();
+		press(&mut state);
+		assert_eq!(vigem::XGamepad::from(state).buttons, buttons);
+		press(&mut all);
+	}
+	assert_eq!(vigem::XGamepad::from(all).buttons, vigem::XButtons(0xF7FF));
+}
+
+#[test]
+fn round_trip() {
+	let gamepad = vigem::XGamepad {
+		buttons: vigem::XButtons!(UP | RIGHT | LB | A | X | GUIDE),
+		left_trigger: 7,
+		right_trigger: 255,
+		thumb_lx: i16::MIN,
+		thumb_ly: i16::MAX,
+		thumb_rx: -1200,
+		thumb_ry: 30000,
+	};
+	let state = vigem::GenericGamepadState::from(gamepad);
+	assert_eq!(state.left_thumb_x, -1.0);
+	assert_eq!(state.left_thumb_y, 1.0);
+	assert_eq!(vigem::XGamepad::from(state), gamepad);
+}
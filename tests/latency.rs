@@ -0,0 +1,85 @@
+use std::time::{Duration, Instant};
+use vigem_client::LatencyStats;
+
+fn ms(ms: u64) -> Duration {
+	Duration::from_millis(ms)
+}
+
+#[test]
+fn empty() {
+	let stats = LatencyStats::default();
+	assert_eq!(stats.capacity(), LatencyStats::DEFAULT_CAPACITY);
+	assert!(stats.is_empty());
+	assert_eq!(stats.percentile(50.0), None);
+	assert_eq!(stats.min(), None);
+	assert_eq!(stats.max(), None);
+	assert_eq!(stats.mean(), None);
+	assert_eq!(stats.histogram(ms(1), 3), [0, 0, 0]);
+}
+
+#[test]
+fn record_synthetic() {
+	let start = Instant::now();
+	let mut stats = LatencyStats::new(16);
+	stats.record(start, start + ms(3));
+	stats.record(start + ms(10), start + ms(11));
+	stats.record(start + ms(20), start + ms(22));
+	// Submitted before captured counts as zero
+	stats.record(start + ms(5), start);
+
+	assert_eq!(stats.samples().collect::<Vec<_>>(), [ms(3), ms(1), ms(2), ms(0)]);
+	assert_eq!(stats.min(), Some(ms(0)));
+	assert_eq!(stats.max(), Some(ms(3)));
+	assert_eq!(stats.mean(), Some(Duration::from_micros(1500)));
+}
+
+#[test]
+fn ring_buffer() {
+	let mut stats = LatencyStats::new(3);
+	for i in 1..=5 {
+		stats.record_latency(ms(i));
+	}
+	assert_eq!(stats.len(), 3);
+	assert_eq!(stats.total(), 5);
+	assert_eq!(stats.samples().collect::<Vec<_>>(), [ms(3), ms(4), ms(5)]);
+
+	stats.clear();
+	assert!(stats.is_empty());
+	assert_eq!(stats.total(), 5);
+}
+
+#[test]
+fn percentiles() {
+	let mut stats = LatencyStats::new(100);
+	// Recorded out of order
+	for i in (1..=100).rev() {
+		stats.record_latency(ms(i));
+	}
+	assert_eq!(stats.percentile(0.0), Some(ms(1)));
+	assert_eq!(stats.percentile(1.0), Some(ms(1)));
+	assert_eq!(stats.percentile(50.0), Some(ms(50)));
+	assert_eq!(stats.percentile(90.0), Some(ms(90)));
+	assert_eq!(stats.percentile(99.0), Some(ms(99)));
+	assert_eq!(stats.percentile(99.5), Some(ms(100)));
+	assert_eq!(stats.percentile(100.0), Some(ms(100)));
+	// Clamped
+	assert_eq!(stats.percentile(-1.0), Some(ms(1)));
+	assert_eq!(stats.percentile(250.0), Some(ms(100)));
+}
+
+#[test]
+fn histogram() {
+	let mut stats = LatencyStats::new(16);
+	for latency in [0, 1, 999, 1000, 1500, 2000, 2999, 50000] {
+		stats.record_latency(Duration::from_micros(latency));
+	}
+	// The last bucket counts the samples beyond it
+	assert_eq!(stats.histogram(ms(1), 3), [3, 2, 3]);
+	assert_eq!(stats.histogram(ms(100), 2), [8, 0]);
+}
+
+#[test]
+#[should_panic]
+fn zero_capacity() {
+	let _ = LatencyStats::new(0);
+}
@@ -0,0 +1,92 @@
+//! Pins the byte layouts of the public report structures.
+//!
+//! A failure here means a layout changed: bump `WIRE_FORMAT_VERSION` and update the pinned values together.
+
+use std::mem;
+use vigem_client as vigem;
+
+#[test]
+fn wire_format_version() {
+	assert_eq!(vigem::WIRE_FORMAT_VERSION, 1);
+}
+
+#[test]
+fn x360_layouts() {
+	assert_eq!((mem::size_of::<vigem::XButtons>(), vigem::XButtons::LAYOUT_FINGERPRINT), (2, 0x7ae60e06058e89a4));
+	assert_eq!((mem::size_of::<vigem::XGamepad>(), vigem::XGamepad::LAYOUT_FINGERPRINT), (12, 0xd4e63a9cf56ed38d));
+	assert_eq!((mem::size_of::<vigem::TargetId>(), vigem::TargetId::LAYOUT_FINGERPRINT), (4, 0xc1be1c4b06a33243));
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn x360_notification_layout() {
+	assert_eq!((mem::size_of::<vigem::XNotification>(), vigem::XNotification::LAYOUT_FINGERPRINT), (3, 0x051cd2e623e3d547));
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4_layouts() {
+	assert_eq!((mem::size_of::<vigem::DS4Buttons>(), vigem::DS4Buttons::LAYOUT_FINGERPRINT), (2, 0x7ae60e06058e89a4));
+	assert_eq!((mem::size_of::<vigem::DS4SpecialButtons>(), vigem::DS4SpecialButtons::LAYOUT_FINGERPRINT), (1, 0xded2f10554e98744));
+	assert_eq!((mem::size_of::<vigem::DS4Report>(), vigem::DS4Report::LAYOUT_FINGERPRINT), (10, 0xc11f0743322bedc8));
+	assert_eq!((mem::size_of::<vigem::DS4TouchReport>(), vigem::DS4TouchReport::LAYOUT_FINGERPRINT), (9, 0x535efc90550bef88));
+	assert_eq!((mem::size_of::<vigem::DS4ReportEx>(), vigem::DS4ReportEx::LAYOUT_FINGERPRINT), (63, 0x0c3e953dd8ec480d));
+}
+
+// DS4_REPORT_EX of ViGEmBusShared.h, the USB input report without its report id
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4_report_ex_offsets() {
+	use vigem::DS4ReportEx;
+	assert_eq!(mem::align_of::<DS4ReportEx>(), 1);
+	let offsets = [
+		mem::offset_of!(DS4ReportEx, thumb_lx),
+		mem::offset_of!(DS4ReportEx, buttons),
+		mem::offset_of!(DS4ReportEx, special),
+		mem::offset_of!(DS4ReportEx, trigger_r),
+		mem::offset_of!(DS4ReportEx, timestamp),
+		mem::offset_of!(DS4ReportEx, battery_lvl),
+		mem::offset_of!(DS4ReportEx, gyro_x),
+		mem::offset_of!(DS4ReportEx, accel_x),
+		mem::offset_of!(DS4ReportEx, accel_z),
+		mem::offset_of!(DS4ReportEx, _unknown1),
+		mem::offset_of!(DS4ReportEx, battery_lvl_special),
+		mem::offset_of!(DS4ReportEx, _unknown2),
+		mem::offset_of!(DS4ReportEx, touch_packets_n),
+		mem::offset_of!(DS4ReportEx, current_touch),
+		mem::offset_of!(DS4ReportEx, previous_touch),
+		mem::offset_of!(DS4ReportEx, _padding),
+	];
+	assert_eq!(offsets, [0, 4, 6, 8, 9, 11, 12, 18, 22, 24, 29, 30, 32, 33, 42, 60]);
+}
+
+// Mirrors of XGamepad with a single change each
+#[repr(C)]
+struct Mirror { buttons: u16, left_trigger: u8, right_trigger: u8, thumb_lx: i16, thumb_ly: i16, thumb_rx: i16, thumb_ry: i16 }
+#[repr(C)]
+struct Reordered { left_trigger: u8, right_trigger: u8, buttons: u16, thumb_lx: i16, thumb_ly: i16, thumb_rx: i16, thumb_ry: i16 }
+#[repr(C)]
+struct Widened { buttons: u16, left_trigger: u8, right_trigger: u8, thumb_lx: i32, thumb_ly: i16, thumb_rx: i16, thumb_ry: i16 }
+#[repr(C)]
+struct Extended { buttons: u16, left_trigger: u8, right_trigger: u8, thumb_lx: i16, thumb_ly: i16, thumb_rx: i16, thumb_ry: i16, extra: u16 }
+#[repr(C, packed)]
+struct Packed { buttons: u16, left_trigger: u8, right_trigger: u8, thumb_lx: i16, thumb_ly: i16, thumb_rx: i16, thumb_ry: i16 }
+
+#[test]
+fn fingerprint_mirrors() {
+	let mirror = vigem::layout_fingerprint!(Mirror { buttons, left_trigger, right_trigger, thumb_lx, thumb_ly, thumb_rx, thumb_ry });
+	assert_eq!(mirror, vigem::XGamepad::LAYOUT_FINGERPRINT);
+
+	// Declaration order is what counts, reordering the fields changes the fingerprint
+	assert_ne!(mirror, vigem::layout_fingerprint!(Reordered { left_trigger, right_trigger, buttons, thumb_lx, thumb_ly, thumb_rx, thumb_ry }));
+	assert_ne!(mirror, vigem::layout_fingerprint!(Widened { buttons, left_trigger, right_trigger, thumb_lx, thumb_ly, thumb_rx, thumb_ry }));
+	assert_ne!(mirror, vigem::layout_fingerprint!(Extended { buttons, left_trigger, right_trigger, thumb_lx, thumb_ly, thumb_rx, thumb_ry, extra }));
+	assert_ne!(mirror, vigem::layout_fingerprint!(Packed { buttons, left_trigger, right_trigger, thumb_lx, thumb_ly, thumb_rx, thumb_ry }));
+}
+
+#[test]
+fn fingerprint_const() {
+	const FINGERPRINT: u64 = vigem::layout_fingerprint!(Mirror { buttons, left_trigger, right_trigger, thumb_lx, thumb_ly, thumb_rx, thumb_ry });
+	assert_eq!(FINGERPRINT, vigem::layout_fingerprint::<Mirror>(&[0, 2, 3, 4, 6, 8, 10]));
+	assert_ne!(FINGERPRINT, vigem::layout_fingerprint::<Mirror>(&[]));
+}
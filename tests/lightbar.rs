@@ -0,0 +1,101 @@
+use std::time::{Duration, Instant};
+use vigem_client::{DS4LightbarColor, LightbarForwarder, LightbarPolicy, LightbarSink};
+
+#[derive(Default)]
+struct Recorder(Vec<DS4LightbarColor>);
+
+impl LightbarSink for Recorder {
+	fn set_color(&mut self, color: DS4LightbarColor) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+		self.0.push(color);
+		Ok(())
+	}
+}
+
+const RED: DS4LightbarColor = DS4LightbarColor { red: 255, green: 0, blue: 0 };
+const GREEN: DS4LightbarColor = DS4LightbarColor { red: 0, green: 255, blue: 0 };
+const BLUE: DS4LightbarColor = DS4LightbarColor { red: 0, green: 0, blue: 255 };
+
+fn ms(ms: u64) -> Duration {
+	Duration::from_millis(ms)
+}
+
+#[test]
+fn immediate() {
+	let start = Instant::now();
+	let mut forwarder = LightbarForwarder::new(Recorder::default(), LightbarPolicy::new());
+	assert_eq!(forwarder.tick(start).unwrap(), None);
+
+	for (i, &color) in [RED, RED, GREEN].iter().enumerate() {
+		let now = start + ms(i as u64);
+		forwarder.push(color, now);
+		assert_eq!(forwarder.tick(now).unwrap(), None);
+	}
+	assert_eq!(forwarder.sink().0, [RED, RED, GREEN]);
+}
+
+#[test]
+fn skip_identical() {
+	let start = Instant::now();
+	let policy = LightbarPolicy { skip_identical: true, ..LightbarPolicy::new() };
+	let mut forwarder = LightbarForwarder::new(Recorder::default(), policy);
+	for (i, &color) in [RED, RED, GREEN, GREEN, RED].iter().enumerate() {
+		let now = start + ms(i as u64);
+		forwarder.push(color, now);
+		forwarder.tick(now).unwrap();
+	}
+	assert_eq!(forwarder.sink().0, [RED, GREEN, RED]);
+}
+
+#[test]
+fn rate_limit_coalesces() {
+	let start = Instant::now();
+	let policy = LightbarPolicy { max_hz: 10.0, ..LightbarPolicy::new() };
+	let mut forwarder = LightbarForwarder::new(Recorder::default(), policy);
+
+	forwarder.push(RED, start);
+	assert_eq!(forwarder.tick(start).unwrap(), None);
+
+	// Colors within the interval are coalesced into the latest
+	forwarder.push(GREEN, start + ms(10));
+	let due = forwarder.tick(start + ms(10)).unwrap().unwrap();
+	assert_eq!(due, start + ms(100));
+	forwarder.push(BLUE, start + ms(20));
+	assert_eq!(forwarder.tick(start + ms(50)).unwrap(), Some(due));
+	assert_eq!(forwarder.tick(due).unwrap(), None);
+
+	assert_eq!(forwarder.sink().0, [RED, BLUE]);
+}
+
+#[test]
+fn smoothing() {
+	let start = Instant::now();
+	let policy = LightbarPolicy { smoothing: ms(100), ..LightbarPolicy::new() };
+	let mut forwarder = LightbarForwarder::new(Recorder::default(), policy);
+
+	// The first color is shown immediately
+	forwarder.push(RED, start);
+	assert_eq!(forwarder.tick(start).unwrap(), Some(start + ms(16)));
+	assert_eq!(forwarder.tick(start + ms(100)).unwrap(), None);
+
+	// Then fades to the next color
+	forwarder.push(BLUE, start + ms(100));
+	assert!(forwarder.tick(start + ms(150)).unwrap().is_some());
+	assert_eq!(forwarder.tick(start + ms(200)).unwrap(), None);
+
+	let halfway = DS4LightbarColor { red: 128, green: 0, blue: 128 };
+	assert_eq!(forwarder.sink().0, [RED, RED, halfway, BLUE]);
+}
+
+#[test]
+fn sink_error() {
+	struct Failing;
+	impl LightbarSink for Failing {
+		fn set_color(&mut self, _: DS4LightbarColor) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+			Err("unplugged".into())
+		}
+	}
+	let now = Instant::now();
+	let mut forwarder = LightbarForwarder::new(Failing, LightbarPolicy::new());
+	forwarder.push(RED, now);
+	assert_eq!(forwarder.tick(now).unwrap_err().to_string(), "unplugged");
+}
@@ -0,0 +1,272 @@
+/*!
+Model checks the synchronization internals with loom.
+
+```text
+RUSTFLAGS="--cfg loom" cargo test --release --test loom
+```
+
+The modules are included from source, they only depend on the primitives of `sync` which are loom's under `cfg(loom)`.
+Loom also checks the accesses to the buffers of the `StateCell` for data races.
+*/
+
+#![cfg(loom)]
+
+#[allow(dead_code)]
+#[path = "../src/sync.rs"]
+mod sync;
+#[allow(dead_code)]
+#[path = "../src/gate.rs"]
+mod gate;
+#[allow(dead_code)]
+#[path = "../src/statecell.rs"]
+mod statecell;
+#[allow(dead_code)]
+#[path = "../src/fanout.rs"]
+mod fanout;
+
+use loom::sync::Arc;
+use loom::sync::atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst};
+use loom::thread;
+use std::time::Duration;
+use gate::Gate;
+use statecell::StateCell;
+use fanout::{Fanout, Next};
+
+#[test]
+fn close_waits_for_requests_in_flight() {
+	loom::model(|| {
+		let gate = Arc::new(Gate::default());
+		let in_flight = Arc::new(AtomicUsize::new(0));
+
+		let request = {
+			let (gate, in_flight) = (gate.clone(), in_flight.clone());
+			thread::spawn(move || {
+				let _pass = gate.enter();
+				in_flight.fetch_add(1, SeqCst);
+				in_flight.fetch_sub(1, SeqCst);
+			})
+		};
+
+		let closed = gate.close();
+		assert_eq!(in_flight.load(SeqCst), 0);
+		drop(closed);
+		request.join().unwrap();
+	});
+}
+
+#[test]
+fn enter_waits_while_closed() {
+	loom::model(|| {
+		let gate = Arc::new(Gate::default());
+		let maintenance = Arc::new(AtomicBool::new(false));
+
+		let request = {
+			let (gate, maintenance) = (gate.clone(), maintenance.clone());
+			thread::spawn(move || {
+				let _pass = gate.enter();
+				assert!(!maintenance.load(SeqCst));
+			})
+		};
+
+		let closed = gate.close();
+		maintenance.store(true, SeqCst);
+		maintenance.store(false, SeqCst);
+		drop(closed);
+		request.join().unwrap();
+	});
+}
+
+#[test]
+fn owner_enters_while_closed() {
+	loom::model(|| {
+		let gate = Arc::new(Gate::default());
+
+		let request = {
+			let gate = gate.clone();
+			thread::spawn(move || drop(gate.enter()))
+		};
+
+		// The thread doing maintenance keeps issuing requests without deadlocking on itself
+		let closed = gate.close();
+		drop(gate.enter());
+		drop(closed);
+		request.join().unwrap();
+	});
+}
+
+#[test]
+fn maintenance_is_serialized() {
+	loom::model(|| {
+		let gate = Arc::new(Gate::default());
+		let maintaining = Arc::new(AtomicUsize::new(0));
+
+		let threads: Vec<_> = (0..2).map(|_| {
+			let (gate, maintaining) = (gate.clone(), maintaining.clone());
+			thread::spawn(move || {
+				let _closed = gate.close();
+				assert_eq!(maintaining.fetch_add(1, SeqCst), 0);
+				maintaining.fetch_sub(1, SeqCst);
+			})
+		}).collect();
+
+		for thread in threads {
+			thread.join().unwrap();
+		}
+	});
+}
+
+#[test]
+fn state_cell_hands_over_whole_values() {
+	loom::model(|| {
+		let cell = Arc::new(StateCell::new((0u32, 0u32)));
+
+		let producer = {
+			let cell = cell.clone();
+			thread::spawn(move || {
+				for i in 1..=2 {
+					cell.publish((i, i));
+				}
+				cell.close();
+			})
+		};
+
+		// Values are never torn and never go back in time
+		let mut last = 0;
+		for _ in 0..2 {
+			let (a, b) = cell.latest();
+			assert_eq!(a, b);
+			assert!(a >= last);
+			last = a;
+		}
+
+		producer.join().unwrap();
+		assert!(cell.is_closed());
+		assert_eq!(cell.latest(), (2, 2));
+	});
+}
+
+#[test]
+fn state_cell_take_once() {
+	loom::model(|| {
+		let cell = Arc::new(StateCell::new(0u32));
+
+		let producer = {
+			let cell = cell.clone();
+			thread::spawn(move || cell.publish(1))
+		};
+
+		let taken = cell.take();
+		producer.join().unwrap();
+		match taken {
+			// Taken before the publish, it is taken now
+			None => assert_eq!(cell.take(), Some(1)),
+			Some(value) => {
+				assert_eq!(value, 1);
+				assert_eq!(cell.take(), None);
+			},
+		}
+		assert_eq!(cell.latest(), 1);
+	});
+}
+
+#[test]
+fn state_cell_poll_after_close() {
+	loom::model(|| {
+		let cell = Arc::new(StateCell::new(0u32));
+
+		let producer = {
+			let cell = cell.clone();
+			thread::spawn(move || {
+				cell.publish(1);
+				cell.close();
+			})
+		};
+
+		// The value published before closing is always read before polling stops
+		let mut last = cell.poll();
+		producer.join().unwrap();
+		while let Some(value) = cell.poll() {
+			last = Some(value);
+		}
+		assert_eq!(last, Some(1));
+	});
+}
+
+// Reads until the writer closes, returns the values read
+fn read_all(fanout: &Fanout<u32>, cursor: &mut fanout::Cursor, latest: bool) -> Vec<u32> {
+	let mut values = Vec::new();
+	while let Next::Value(value) = fanout.next(cursor, latest, Duration::MAX) {
+		values.push(value);
+	}
+	values
+}
+
+#[test]
+fn fanout_readers_keep_up_or_count_missed() {
+	loom::model(|| {
+		let fanout = Arc::new(Fanout::new(2));
+		let mut cursor = fanout.acquire();
+
+		let writer = {
+			let fanout = fanout.clone();
+			thread::spawn(move || {
+				for i in 1..=3 {
+					fanout.publish(i);
+				}
+				fanout.close();
+			})
+		};
+
+		// Values arrive in order, overwritten values are counted instead
+		let values = read_all(&fanout, &mut cursor, false);
+		assert!(values.windows(2).all(|pair| pair[0] + 1 == pair[1]), "{:?}", values);
+		assert_eq!(values.last(), Some(&3));
+		assert_eq!(values.len() as u64 + cursor.missed(), 3);
+		writer.join().unwrap();
+	});
+}
+
+#[test]
+fn fanout_latest_skips_ahead() {
+	loom::model(|| {
+		let fanout = Arc::new(Fanout::new(2));
+		let mut every = fanout.acquire();
+		let mut latest = fanout.acquire();
+
+		let writer = {
+			let fanout = fanout.clone();
+			thread::spawn(move || {
+				for i in 1..=3 {
+					fanout.publish(i);
+				}
+				fanout.close();
+			})
+		};
+
+		// The readers do not disturb each other, coalescing does not count as missed
+		let values = read_all(&fanout, &mut latest, true);
+		assert!(values.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", values);
+		assert_eq!(values.last(), Some(&3));
+		assert_eq!(latest.missed(), 0);
+		writer.join().unwrap();
+		assert_eq!(read_all(&fanout, &mut every, false), [2, 3]);
+		assert_eq!(every.missed(), 1);
+	});
+}
+
+#[test]
+fn fanout_release_last_once() {
+	loom::model(|| {
+		let fanout = Arc::new(Fanout::<u32>::new(1));
+		fanout.acquire();
+
+		let threads: Vec<_> = (0..2).map(|_| {
+			let fanout = fanout.clone();
+			thread::spawn(move || fanout.release())
+		}).collect();
+
+		let last = threads.into_iter().map(|thread| thread.join().unwrap()).filter(|&last| last).count();
+		assert_eq!(last, 1);
+		assert!(!fanout.is_shared());
+	});
+}
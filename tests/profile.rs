@@ -0,0 +1,105 @@
+#![cfg(windows)]
+
+use vigem_client as vigem;
+
+#[test]
+fn defaults() {
+	let profile = vigem::Profile::default();
+	assert_eq!(profile, vigem::Profile::xbox360());
+	assert_eq!(profile.kind, vigem::TargetKind::Xbox360Wired);
+	assert_eq!(profile.id, vigem::TargetId::XBOX360_WIRED);
+	assert_eq!(profile.initial_report(), vigem::TargetReport::Xbox360Wired(vigem::XGamepad::neutral()));
+	assert_eq!(profile.validate(), Ok(()));
+}
+
+#[test]
+fn validate_rate() {
+	let profile = |max_hz| vigem::Profile { max_update_rate: Some(max_hz), ..vigem::Profile::xbox360() };
+	assert_eq!(profile(250.0).validate(), Ok(()));
+	assert_eq!(profile(0.0).validate(), Err(vigem::Error::InvalidProfile));
+	assert_eq!(profile(-1.0).validate(), Err(vigem::Error::InvalidProfile));
+	assert_eq!(profile(f32::NAN).validate(), Err(vigem::Error::InvalidProfile));
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn validate_ds4() {
+	let profile = vigem::Profile::dualshock4();
+	assert_eq!(profile.initial_report(), vigem::TargetReport::DualShock4Wired(vigem::DS4Report::neutral()));
+	assert_eq!(profile.validate(), Ok(()));
+
+	// DualShock4 targets have no rate limit
+	let rate = vigem::Profile { max_update_rate: Some(250.0), ..profile };
+	assert_eq!(rate.validate(), Err(vigem::Error::InvalidProfile));
+
+	let report = vigem::Profile { report: Some(vigem::XGamepad::default().into()), ..profile };
+	assert_eq!(report.validate(), Err(vigem::Error::KindMismatch));
+}
+
+#[cfg(feature = "serde")]
+fn round_trip(profile: &vigem::Profile) {
+	let json = serde_json::to_string_pretty(profile).unwrap();
+	assert_eq!(&serde_json::from_str::<vigem::Profile>(&json).unwrap(), profile);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn example_xbox360() {
+	let profile: vigem::Profile = serde_json::from_str(include_str!("../examples/profiles/xbox360.json")).unwrap();
+	let expected = vigem::Profile {
+		filter: vigem::InputFilter::compensate_xinput_deadzone(),
+		detached_policy: vigem::DetachedPolicy::AutoPlugin,
+		max_update_rate: Some(250.0),
+		stale_timeout: Some(std::time::Duration::from_secs(2)),
+		panic_guard: true,
+		..vigem::Profile::xbox360()
+	};
+	assert_eq!(profile, expected);
+	assert_eq!(profile.validate(), Ok(()));
+	round_trip(&profile);
+}
+
+#[cfg(all(feature = "serde", feature = "unstable_ds4"))]
+#[test]
+fn example_dualshock4() {
+	let profile: vigem::Profile = serde_json::from_str(include_str!("../examples/profiles/dualshock4.json")).unwrap();
+	let expected = vigem::Profile {
+		report: Some(vigem::DS4Report::neutral().into()),
+		validate_reports: true,
+		stale_timeout: Some(std::time::Duration::from_millis(500)),
+		panic_guard: true,
+		..vigem::Profile::dualshock4()
+	};
+	assert_eq!(profile, expected);
+	assert_eq!(profile.validate(), Ok(()));
+	round_trip(&profile);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn round_trip_axis_matrix() {
+	let profile = vigem::Profile {
+		filter: vigem::InputFilter {
+			axis_matrix: Some(vigem::AxisMatrix::identity().swap_sticks().scale_axis(vigem::Axis::LT, 0.5)),
+			dpad_rotation: 2,
+			..vigem::InputFilter::new()
+		},
+		report: Some(vigem::XGamepad { buttons: vigem::XButtons!(A | UP), thumb_lx: -1234, ..Default::default() }.into()),
+		..vigem::Profile::xbox360()
+	};
+	round_trip(&profile);
+}
+
+#[cfg(all(feature = "serde", feature = "unstable_ds4"))]
+#[test]
+fn round_trip_cross_map() {
+	let map = vigem::CrossMap::new()
+		.swap(vigem::DS4Button::Buttons(vigem::DS4Buttons::CROSS), vigem::DS4Button::Buttons(vigem::DS4Buttons::CIRCLE))
+		.map(vigem::XButtons!(BACK), Some(vigem::DS4Button::Special(vigem::DS4SpecialButtons::TOUCHPAD)))
+		.swap_sticks();
+	let profile = vigem::Profile {
+		cross_map: Some(vigem::CrossMap { triggers: vigem::TriggerPolicy::AnyPull, ..map }),
+		..vigem::Profile::dualshock4()
+	};
+	round_trip(&profile);
+}
@@ -0,0 +1,97 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use vigem_client as vigem;
+use vigem::Pump;
+
+#[derive(Default)]
+struct FakeComponent {
+	pumped: Vec<Instant>,
+	fail: Option<vigem::Error>,
+}
+
+impl Pump for FakeComponent {
+	fn pump(&mut self, now: Instant) -> Result<(), vigem::Error> {
+		self.pumped.push(now);
+		match self.fail {
+			Some(err) => Err(err),
+			None => Ok(()),
+		}
+	}
+}
+
+#[test]
+fn pumps_all_components() {
+	let first = Rc::new(RefCell::new(FakeComponent::default()));
+	let second = Rc::new(RefCell::new(FakeComponent::default()));
+
+	let mut driver = vigem::ManualDriver::new();
+	assert!(driver.is_empty());
+	driver.register(first.clone());
+	driver.register(second.clone());
+	assert_eq!(driver.len(), 2);
+
+	// A fake clock, the driver passes the host's time through unchanged
+	let start = Instant::now();
+	for frame in 0..3 {
+		driver.pump(start + Duration::from_millis(16 * frame)).unwrap();
+	}
+
+	let expected: Vec<_> = (0..3).map(|frame| start + Duration::from_millis(16 * frame)).collect();
+	assert_eq!(first.borrow().pumped, expected);
+	assert_eq!(second.borrow().pumped, expected);
+}
+
+#[test]
+fn pumps_past_failures() {
+	let failing = Rc::new(RefCell::new(FakeComponent { fail: Some(vigem::Error::OperationAborted), ..Default::default() }));
+	let also_failing = Rc::new(RefCell::new(FakeComponent { fail: Some(vigem::Error::NotPluggedIn), ..Default::default() }));
+	let healthy = Rc::new(RefCell::new(FakeComponent::default()));
+
+	let mut driver = vigem::ManualDriver::new();
+	driver.register(failing.clone());
+	driver.register(also_failing.clone());
+	driver.register(healthy.clone());
+
+	// The first error is returned after every component was pumped
+	assert_eq!(driver.pump(Instant::now()), Err(vigem::Error::OperationAborted));
+	assert_eq!(also_failing.borrow().pumped.len(), 1);
+	assert_eq!(healthy.borrow().pumped.len(), 1);
+}
+
+#[test]
+fn borrowed_components() {
+	let mut component = FakeComponent::default();
+	{
+		let mut driver = vigem::ManualDriver::new();
+		driver.register(&mut component);
+		driver.pump(Instant::now()).unwrap();
+	}
+	assert_eq!(component.pumped.len(), 1);
+}
+
+#[cfg(windows)]
+#[test]
+fn fleet_autosave() {
+	use std::sync::{Arc, Mutex};
+
+	let saved = Arc::new(Mutex::new(0));
+	let mut fleet = vigem::Fleet::<vigem::Client>::new();
+	fleet.set_autosave(Some(Duration::from_secs(1)), Box::new({
+		let saved = saved.clone();
+		move |snapshot: &vigem::FleetSnapshot| {
+			assert!(snapshot.targets.is_empty());
+			*saved.lock().unwrap() += 1;
+		}
+	}));
+
+	// Saves on the first pump and then every interval
+	let start = Instant::now();
+	for ms in [0, 500, 999, 1000, 1500, 2100] {
+		fleet.pump(start + Duration::from_millis(ms)).unwrap();
+	}
+	assert_eq!(*saved.lock().unwrap(), 3);
+
+	fleet.set_autosave(None, Box::new(|_: &vigem::FleetSnapshot| panic!("autosave disabled")));
+	fleet.pump(start + Duration::from_secs(10)).unwrap();
+}
@@ -0,0 +1,114 @@
+use std::time::{Duration, Instant};
+use vigem_client as vigem;
+
+// Producer updating every 5µs, 200 kHz, against a fake clock
+fn run(max_hz: f32, updates: u32) -> (Vec<(Instant, u32)>, vigem::RateLimit<u32>, Instant) {
+	let mut limit = vigem::RateLimit::new(max_hz);
+	let start = Instant::now();
+	let mut submitted = Vec::new();
+	let mut now = start;
+	for i in 0..updates {
+		now = start + Duration::from_micros(5 * i as u64);
+		if let Some(report) = limit.offer(i, now) {
+			submitted.push((now, report));
+		}
+	}
+	(submitted, limit, now)
+}
+
+#[test]
+fn caps_submission_rate() {
+	let (submitted, limit, _) = run(1000.0, 100_000);
+	assert_eq!(limit.interval(), Duration::from_millis(1));
+
+	// 0.5s of updates at 1 kHz
+	assert_eq!(submitted.len(), 500);
+	assert!(submitted.windows(2).all(|pair| pair[1].0 - pair[0].0 >= limit.interval()));
+	// Reports are never reordered
+	assert!(submitted.windows(2).all(|pair| pair[0].1 < pair[1].1));
+}
+
+#[test]
+fn latest_wins() {
+	let mut limit = vigem::RateLimit::new(100.0);
+	let start = Instant::now();
+	assert_eq!(limit.offer(1, start), Some(1));
+	assert_eq!(limit.next_due(), None);
+	assert_eq!(limit.offer(2, start + Duration::from_millis(1)), None);
+	assert_eq!(limit.offer(3, start + Duration::from_millis(2)), None);
+	assert_eq!(limit.pending(), Some(&3));
+	assert_eq!(limit.next_due(), Some(start + Duration::from_millis(10)));
+
+	assert_eq!(limit.poll(start + Duration::from_millis(9)), None);
+	assert_eq!(limit.poll(start + Duration::from_millis(10)), Some(3));
+	assert_eq!(limit.pending(), None);
+	assert_eq!(limit.poll(start + Duration::from_millis(30)), None);
+
+	// The first update after the interval is submitted immediately
+	assert_eq!(limit.offer(4, start + Duration::from_millis(30)), Some(4));
+}
+
+#[test]
+fn final_state_reaches_driver() {
+	// The last update ends up pending, pumping submits it once due
+	let (submitted, mut limit, end) = run(60.0, 10_001);
+	assert!(submitted.last().map(|&(_, report)| report) != Some(10_000));
+	let due = limit.next_due().unwrap();
+	assert!(due > end);
+	assert_eq!(limit.poll(end), None);
+	assert_eq!(limit.poll(due), Some(10_000));
+
+	// Or flush submits it regardless of the rate
+	let (_, mut limit, end) = run(60.0, 10_001);
+	assert_eq!(limit.flush(end), Some(10_000));
+	assert_eq!(limit.flush(end), None);
+}
+
+#[test]
+fn failed_submission_stays_pending() {
+	let mut limit = vigem::RateLimit::new(100.0);
+	let start = Instant::now();
+	limit.offer(1, start);
+	limit.offer(2, start + Duration::from_millis(1));
+
+	// Submitting the flushed report failed, it is flushed again
+	let report = limit.flush(start + Duration::from_millis(2)).unwrap();
+	limit.restore(report);
+	assert_eq!(limit.pending(), Some(&2));
+	assert_eq!(limit.flush(start + Duration::from_millis(3)), Some(2));
+
+	// Submitting the polled report failed, it is due again after the interval
+	limit.offer(3, start + Duration::from_millis(4));
+	let report = limit.poll(start + Duration::from_millis(13)).unwrap();
+	limit.restore(report);
+	assert_eq!(limit.next_due(), Some(start + Duration::from_millis(23)));
+	assert_eq!(limit.poll(start + Duration::from_millis(23)), Some(3));
+
+	// A report offered since wins over the failed one
+	limit.offer(4, start + Duration::from_millis(24));
+	let report = limit.flush(start + Duration::from_millis(25)).unwrap();
+	limit.offer(5, start + Duration::from_millis(26));
+	limit.restore(report);
+	assert_eq!(limit.pending(), Some(&5));
+}
+
+#[test]
+fn clear_and_change_rate() {
+	let mut limit = vigem::RateLimit::new(10.0);
+	let start = Instant::now();
+	limit.offer(1, start);
+	limit.offer(2, start);
+	limit.set_max_rate(1000.0);
+	assert_eq!(limit.pending(), Some(&2));
+	assert_eq!(limit.poll(start + Duration::from_millis(1)), Some(2));
+
+	limit.offer(3, start + Duration::from_millis(1));
+	limit.clear();
+	assert_eq!(limit.flush(start + Duration::from_secs(1)), None);
+}
+
+#[test]
+#[should_panic]
+fn zero_rate() {
+	vigem::RateLimit::<u32>::new(0.0);
+}
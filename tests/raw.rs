@@ -0,0 +1,37 @@
+use vigem_client as vigem;
+
+#[test]
+fn gamepad_layout() {
+	assert_eq!(std::mem::size_of::<vigem::XGamepad>(), 12);
+}
+
+#[test]
+fn gamepad_from_bytes() {
+	let bytes = [0x09, 0x51, 0x20, 0xfe, 0x34, 0x12, 0xcc, 0xed, 0x00, 0x80, 0xff, 0x7f];
+	let gamepad = vigem::XGamepad::from_bytes(&bytes);
+	assert_eq!(gamepad.buttons, vigem::XButtons!(UP|RIGHT|LB|A|X));
+	assert_eq!(gamepad.left_trigger, 0x20);
+	assert_eq!(gamepad.right_trigger, 0xfe);
+	assert_eq!(gamepad.thumb_lx, 0x1234);
+	assert_eq!(gamepad.thumb_ly, -0x1234);
+	assert_eq!(gamepad.thumb_rx, i16::MIN);
+	assert_eq!(gamepad.thumb_ry, i16::MAX);
+	assert_eq!(gamepad.to_bytes(), bytes);
+}
+
+#[test]
+fn gamepad_matches_memory_layout() {
+	// The byte layout is the in-memory layout of the repr(C) struct on the little-endian targets this crate supports
+	let gamepad = vigem::XGamepad {
+		buttons: vigem::XButtons(0xf00f),
+		left_trigger: 1,
+		right_trigger: 2,
+		thumb_lx: -3,
+		thumb_ly: 4,
+		thumb_rx: -5,
+		thumb_ry: 6,
+	};
+	let memory: [u8; 12] = unsafe { std::mem::transmute(gamepad) };
+	assert_eq!(gamepad.to_bytes(), memory);
+	assert_eq!(vigem::XGamepad::from_bytes(&memory), gamepad);
+}
@@ -0,0 +1,103 @@
+use std::time::Duration;
+use vigem_client::replay::{self, FieldDiff, ReplayVerifier, TimedReport, TimingDeviation};
+use vigem_client::{Submit, XButtons, XGamepad};
+
+fn ms(ms: u64) -> Duration {
+	Duration::from_millis(ms)
+}
+
+fn golden() -> Vec<TimedReport<XGamepad>> {
+	vec![
+		TimedReport::new(ms(100), XGamepad::neutral()),
+		TimedReport::new(ms(110), XGamepad::pressing(XButtons!(A))),
+		TimedReport::new(ms(120), XGamepad { left_trigger: 255, ..XGamepad::neutral() }),
+	]
+}
+
+#[test]
+fn matching_run() {
+	let golden = golden();
+	let mut verifier = ReplayVerifier::verify_against(&golden, ms(2));
+	// Only relative timing matters, the run may start at another offset
+	for (i, timed) in golden.iter().enumerate() {
+		verifier.submit_at(ms(i as u64 * 10 + 1) + ms(i as u64 % 2), &timed.report);
+	}
+	let diff = verifier.diff();
+	assert!(diff.is_match(), "{}", diff);
+	assert_eq!(diff.first_divergence, None);
+	assert_eq!((diff.expected_len, diff.actual_len), (3, 3));
+	assert_eq!(diff.to_string(), "3 reports match");
+}
+
+#[test]
+fn payload_divergence() {
+	let golden = golden();
+	let mut verifier = ReplayVerifier::verify_against(&golden, ms(2));
+	verifier.submit_at(ms(0), &XGamepad::neutral());
+	verifier.submit_at(ms(10), &XGamepad::pressing(XButtons!(B)));
+	verifier.submit_at(ms(20), &XGamepad { left_trigger: 128, thumb_lx: -1, ..XGamepad::neutral() });
+	let diff = verifier.diff();
+	assert!(!diff.is_match());
+	assert_eq!(diff.first_divergence, Some(1));
+	assert_eq!(diff.fields, [
+		FieldDiff { index: 1, field: "buttons", expected: 0x1000, actual: 0x2000 },
+		FieldDiff { index: 2, field: "left_trigger", expected: 255, actual: 128 },
+		FieldDiff { index: 2, field: "thumb_lx", expected: 0, actual: -1 },
+	]);
+	assert!(diff.timing.is_empty());
+}
+
+#[test]
+fn length_divergence() {
+	let golden = golden();
+	let mut verifier = ReplayVerifier::verify_against(&golden, ms(2));
+	verifier.submit_at(ms(0), &golden[0].report);
+	verifier.submit_at(ms(10), &golden[1].report);
+	let diff = verifier.diff();
+	assert_eq!(diff.first_divergence, Some(2));
+	assert!(diff.fields.is_empty());
+	assert_eq!((diff.expected_len, diff.actual_len), (3, 2));
+}
+
+#[test]
+fn timing_divergence() {
+	let golden = golden();
+	let mut verifier = ReplayVerifier::verify_against(&golden, ms(2));
+	verifier.submit_at(ms(0), &golden[0].report);
+	verifier.submit_at(ms(12), &golden[1].report);
+	verifier.submit_at(ms(25), &golden[2].report);
+	let diff = verifier.diff();
+	assert_eq!(diff.first_divergence, None);
+	assert_eq!(diff.timing, [TimingDeviation { index: 2, expected: ms(20), actual: ms(25) }]);
+	assert!(!diff.is_match());
+}
+
+#[test]
+fn submit_records() {
+	let golden = golden();
+	let mut verifier = ReplayVerifier::verify_against(&golden, Duration::from_secs(60));
+	for timed in &golden {
+		verifier.submit_report(&timed.report).unwrap();
+	}
+	assert_eq!(verifier.recorded().len(), 3);
+	assert_eq!(verifier.recorded()[0].at, Duration::ZERO);
+	assert!(verifier.diff().is_match());
+}
+
+#[test]
+fn save_load() {
+	let golden = golden();
+	let mut log = Vec::new();
+	replay::save(&golden, &mut log).unwrap();
+	let text = String::from_utf8(log.clone()).unwrap();
+	assert_eq!(text.lines().nth(1), Some("110000 001000000000000000000000"));
+	assert_eq!(replay::load::<XGamepad, _>(&log[..]).unwrap(), golden);
+
+	// Comments and empty lines are skipped
+	let commented = format!("# recorded from a physical pad\n\n{}", text);
+	assert_eq!(replay::load::<XGamepad, _>(commented.as_bytes()).unwrap(), golden);
+
+	// Reports of the wrong length are rejected
+	assert!(replay::load::<XGamepad, _>(&b"0 0000"[..]).is_err());
+	assert!(replay::load::<XGamepad, _>(&b"x 000000000000000000000000"[..]).is_err());
+}
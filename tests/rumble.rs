@@ -0,0 +1,150 @@
+use vigem_client::{FourMotors, MotorMap};
+
+const FULL: FourMotors = FourMotors { large_motor: 255, small_motor: 255, left_trigger: 255, right_trigger: 255 };
+
+#[test]
+fn two_motor() {
+	let map = MotorMap::two_motor();
+	assert_eq!(map, MotorMap::default());
+	for value in 0..=255 {
+		let motors = FourMotors { large_motor: value, small_motor: 255 - value, left_trigger: 255, right_trigger: value };
+		assert_eq!(map.apply(&motors), (value, 255 - value));
+	}
+}
+
+#[test]
+fn weight_extremes() {
+	let motors = FourMotors { large_motor: 10, small_motor: 20, left_trigger: 30, right_trigger: 40 };
+	assert_eq!(MotorMap::fold_triggers(0.0).apply(&motors), (10, 20));
+	assert_eq!(MotorMap::fold_triggers(1.0).apply(&motors), (40, 60));
+}
+
+#[test]
+fn weight_clamped() {
+	assert_eq!(MotorMap::fold_triggers(-1.0).trigger_weight(), 0.0);
+	assert_eq!(MotorMap::fold_triggers(2.0).trigger_weight(), 1.0);
+	assert_eq!(MotorMap::fold_triggers(f32::NAN).trigger_weight(), 0.0);
+	assert_eq!(MotorMap::fold_triggers(f32::INFINITY).trigger_weight(), 1.0);
+}
+
+#[test]
+fn saturates() {
+	assert_eq!(MotorMap::fold_triggers(1.0).apply(&FULL), (255, 255));
+	let motors = FourMotors { large_motor: 200, small_motor: 0, left_trigger: 200, right_trigger: 0 };
+	assert_eq!(MotorMap::fold_triggers(0.5).apply(&motors), (255, 0));
+}
+
+#[test]
+fn bands() {
+	// The left trigger folds into the large motor, the right trigger into the small motor
+	let map = MotorMap::fold_triggers(1.0);
+	let left = FourMotors { left_trigger: 100, ..Default::default() };
+	let right = FourMotors { right_trigger: 100, ..Default::default() };
+	assert_eq!(map.apply(&left), (100, 0));
+	assert_eq!(map.apply(&right), (0, 100));
+}
+
+#[test]
+fn rounding() {
+	let motors = FourMotors { large_motor: 0, small_motor: 0, left_trigger: 3, right_trigger: 1 };
+	assert_eq!(MotorMap::fold_triggers(0.5).apply(&motors), (2, 1));
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn event_from_xnotification() {
+	use vigem_client::{RumbleEvent, XNotification};
+	for value in 0..=255 {
+		let event = RumbleEvent::from(XNotification { large_motor: value, small_motor: 255 - value, led_number: 2 });
+		assert_eq!(event.large_motor(), value as f32 / 255.0);
+		assert_eq!(event.small_motor(), (255 - value) as f32 / 255.0);
+		assert_eq!(event.motors(), (value, 255 - value));
+	}
+	let event = RumbleEvent::from(XNotification { large_motor: 0, small_motor: 255, led_number: 2 });
+	assert_eq!(event, RumbleEvent::Xbox360 { large_motor: 0.0, small_motor: 1.0, led_number: 2 });
+}
+
+#[cfg(feature = "ds4-output")]
+#[test]
+fn event_from_ds4_notification() {
+	use vigem_client::{DS4LightbarColor, DS4Notification, RumbleEvent};
+	let lightbar = DS4LightbarColor { red: 1, green: 2, blue: 3 };
+	for value in 0..=255 {
+		let event = RumbleEvent::from(DS4Notification { large_motor: value, small_motor: 255 - value, lightbar });
+		assert_eq!(event.motors(), (value, 255 - value));
+		assert_eq!(event.lightbar(), Some(lightbar));
+	}
+}
+
+#[test]
+fn event_motors_saturate() {
+	use vigem_client::RumbleEvent;
+	let event = RumbleEvent::Xbox360 { large_motor: 2.0, small_motor: f32::NAN, led_number: 0 };
+	assert_eq!(event.motors(), (255, 0));
+	let event = RumbleEvent::Xbox360 { large_motor: -1.0, small_motor: 0.5, led_number: 0 };
+	assert_eq!(event.motors(), (0, 128));
+}
+
+#[test]
+fn hub_tags_and_caches() {
+	use vigem_client::{RumbleEvent, RumbleHub};
+	let event = |large_motor| RumbleEvent::Xbox360 { large_motor, small_motor: 0.0, led_number: 0 };
+
+	let hub = RumbleHub::new();
+	let first = hub.sink(1);
+	let second = hub.sink(2);
+	assert_eq!(hub.try_recv(), None);
+	assert_eq!(hub.last(1), None);
+
+	first.send(event(0.25));
+	second.send(event(0.5));
+	first.send(event(1.0));
+	assert_eq!(hub.try_recv(), Some((1, event(0.25))));
+	assert_eq!(hub.try_recv(), Some((2, event(0.5))));
+	assert_eq!(hub.try_recv(), Some((1, event(1.0))));
+	assert_eq!(hub.try_recv(), None);
+
+	assert_eq!(hub.last(1), Some(event(1.0)));
+	assert_eq!(hub.last(2), Some(event(0.5)));
+	assert_eq!(hub.sources(), [(1, event(1.0)), (2, event(0.5))]);
+}
+
+#[test]
+fn hub_recv_ends_with_sinks() {
+	use vigem_client::{RumbleEvent, RumbleHub};
+	let hub = RumbleHub::new();
+	let sink = hub.sink(7);
+	let thread = std::thread::spawn(move || sink.send(RumbleEvent::Xbox360 { large_motor: 1.0, small_motor: 1.0, led_number: 0 }));
+	assert_eq!(hub.recv().map(|(source, event)| (source, event.motors())), Some((7, (255, 255))));
+	thread.join().unwrap();
+	assert_eq!(hub.recv(), None);
+}
+
+#[test]
+fn hub_recv_timeout() {
+	use std::time::Duration;
+	use vigem_client::{RumbleEvent, RumbleHub};
+	let event = RumbleEvent::Xbox360 { large_motor: 0.5, small_motor: 0.0, led_number: 0 };
+
+	// Without sinks there is nothing to wait for
+	let hub = RumbleHub::new();
+	assert_eq!(hub.recv_timeout(Duration::from_secs(3600)), None);
+
+	// A live sink keeps the hub waiting until the timeout
+	let sink = hub.sink(3);
+	assert_eq!(hub.recv_timeout(Duration::from_millis(1)), None);
+	sink.send(event);
+	assert_eq!(hub.recv_timeout(Duration::ZERO), Some((3, event)));
+
+	// Clones of a sink keep the hub waiting as well
+	let clone = sink.clone();
+	drop(sink);
+	assert_eq!(hub.recv_timeout(Duration::from_millis(1)), None);
+	drop(clone);
+	assert_eq!(hub.recv(), None);
+
+	// Sinks outliving the hub drop their events
+	let sink = hub.sink(4);
+	drop(hub);
+	sink.send(event);
+}
@@ -0,0 +1,94 @@
+use vigem_client as vigem;
+use vigem::RedactPolicy;
+
+fn gamepad() -> vigem::XGamepad {
+	vigem::XGamepad {
+		buttons: vigem::XButtons!(DOWN|START|A),
+		left_trigger: 10,
+		right_trigger: 200,
+		thumb_lx: -1,
+		thumb_ly: 2,
+		thumb_rx: -3,
+		thumb_ry: 4,
+	}
+}
+
+#[test]
+fn default_policy() {
+	assert_eq!(RedactPolicy::default(), RedactPolicy::PresenceOnly);
+}
+
+#[test]
+fn presence_only() {
+	let neutral = vigem::XGamepad::neutral();
+	assert_eq!(neutral.redacted(RedactPolicy::PresenceOnly), neutral);
+
+	let redacted = gamepad().redacted(RedactPolicy::PresenceOnly);
+	assert_eq!(redacted.buttons.raw, 0x0800);
+	assert_eq!(redacted.left_trigger, 0);
+	assert_eq!(redacted.right_trigger, 0);
+	assert_eq!((redacted.thumb_lx, redacted.thumb_ly, redacted.thumb_rx, redacted.thumb_ry), (0, 0, 0, 0));
+
+	// Any single field counts as input
+	let inputs = [
+		vigem::XGamepad { buttons: vigem::XButtons!(UP), ..neutral },
+		vigem::XGamepad { left_trigger: 1, ..neutral },
+		vigem::XGamepad { right_trigger: 1, ..neutral },
+		vigem::XGamepad { thumb_lx: 1, ..neutral },
+		vigem::XGamepad { thumb_ly: -1, ..neutral },
+		vigem::XGamepad { thumb_rx: 1, ..neutral },
+		vigem::XGamepad { thumb_ry: -1, ..neutral },
+	];
+	for input in &inputs {
+		assert_eq!(input.redacted(RedactPolicy::PresenceOnly), redacted, "{:?}", input);
+	}
+}
+
+#[test]
+fn buttons_only() {
+	let redacted = gamepad().redacted(RedactPolicy::ButtonsOnly);
+	assert_eq!(redacted.buttons, vigem::XButtons!(DOWN|START|A));
+	assert_eq!(redacted.left_trigger, 0);
+	assert_eq!(redacted.right_trigger, 0);
+	assert_eq!(redacted.thumb_lx, 0);
+	assert_eq!(redacted.thumb_ly, 0);
+	assert_eq!(redacted.thumb_rx, 0);
+	assert_eq!(redacted.thumb_ry, 0);
+}
+
+#[test]
+fn full() {
+	assert_eq!(gamepad().redacted(RedactPolicy::Full), gamepad());
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4() {
+	let neutral = vigem::DS4Report::neutral();
+	let report = vigem::DS4Report {
+		thumb_lx: 1,
+		thumb_ly: 2,
+		thumb_rx: 3,
+		thumb_ry: 4,
+		buttons: vigem::DS4Buttons::CROSS | vigem::DpadDirection::West as u16,
+		special: 0x1 | 0x14,
+		trigger_l: 5,
+		trigger_r: 6,
+	};
+
+	assert_eq!(neutral.redacted(RedactPolicy::PresenceOnly), neutral);
+	// The report counter alone is not input
+	assert_eq!(vigem::DS4Report { special: 0xFC, ..neutral }.redacted(RedactPolicy::PresenceOnly), neutral);
+	let presence = report.redacted(RedactPolicy::PresenceOnly);
+	assert_eq!(presence, vigem::DS4Report { buttons: 0xF, ..neutral });
+	assert_eq!(vigem::DS4Report { trigger_r: 1, ..neutral }.redacted(RedactPolicy::PresenceOnly), presence);
+	assert_eq!(vigem::DS4Report { thumb_ry: 0, ..neutral }.redacted(RedactPolicy::PresenceOnly), presence);
+
+	let buttons = report.redacted(RedactPolicy::ButtonsOnly);
+	assert_eq!(buttons.buttons, report.buttons);
+	assert_eq!(buttons.special, 0x1);
+	assert_eq!((buttons.thumb_lx, buttons.thumb_ly, buttons.thumb_rx, buttons.thumb_ry), (0x80, 0x80, 0x80, 0x80));
+	assert_eq!((buttons.trigger_l, buttons.trigger_r), (0, 0));
+
+	assert_eq!(report.redacted(RedactPolicy::Full), report);
+}
@@ -0,0 +1,133 @@
+use std::time::{Duration, Instant};
+use vigem_client as vigem;
+use vigem::ClockSource;
+
+// Advances a simulated clock by a fixed step every tick
+struct FakeClock {
+	now: Instant,
+	step: Duration,
+	ticks: u32,
+}
+
+impl FakeClock {
+	fn new(step: Duration) -> FakeClock {
+		FakeClock { now: Instant::now(), step, ticks: 0 }
+	}
+}
+
+impl ClockSource for FakeClock {
+	fn wait_next_tick(&mut self) {
+		self.now += self.step;
+		self.ticks += 1;
+	}
+	fn now(&self) -> Instant {
+		self.now
+	}
+}
+
+#[derive(Default)]
+struct Recorder {
+	reports: Vec<u32>,
+	fail: bool,
+}
+
+impl vigem::Submit<u32> for Recorder {
+	fn submit_report(&mut self, report: &u32) -> Result<(), vigem::Error> {
+		if self.fail {
+			return Err(vigem::Error::NotPluggedIn);
+		}
+		self.reports.push(*report);
+		Ok(())
+	}
+}
+
+#[test]
+fn submits_every_tick() {
+	let mut scheduler = vigem::SubmitScheduler::new(FakeClock::new(Duration::from_millis(16)));
+	let mut target = Recorder::default();
+	for i in 0..5 {
+		assert_eq!(scheduler.tick(&mut target, || Some(i)), Ok(true));
+	}
+	assert_eq!(target.reports, [0, 1, 2, 3, 4]);
+	assert_eq!(scheduler.clock().ticks, 5);
+}
+
+#[test]
+fn skips_without_report() {
+	let mut scheduler = vigem::SubmitScheduler::new(FakeClock::new(Duration::from_millis(16)));
+	let mut target = Recorder::default();
+	assert_eq!(scheduler.tick(&mut target, || None), Ok(false));
+	assert!(target.reports.is_empty());
+	// The clock still ticked
+	assert_eq!(scheduler.clock().ticks, 1);
+}
+
+#[test]
+fn phase_offset() {
+	let mut scheduler = vigem::SubmitScheduler::new(FakeClock::new(Duration::from_millis(10)));
+	let mut target = Recorder::default();
+	assert_eq!(scheduler.phase_offset(), None);
+
+	// Input arriving 3ms after the start waits 7ms for the tick
+	let start = scheduler.clock().now();
+	scheduler.input_arrived_at(start + Duration::from_millis(3));
+	scheduler.tick(&mut target, || Some(0)).unwrap();
+	assert_eq!(scheduler.phase_offset(), Some(Duration::from_millis(7)));
+
+	// Only the freshest arrival is measured
+	scheduler.input_arrived_at(start + Duration::from_millis(11));
+	scheduler.input_arrived_at(start + Duration::from_millis(19));
+	scheduler.tick(&mut target, || Some(1)).unwrap();
+	assert_eq!(scheduler.phase_offset(), Some(Duration::from_millis(1)));
+
+	// Submissions without fresh input are not measured
+	scheduler.tick(&mut target, || Some(2)).unwrap();
+
+	let stats = scheduler.stats();
+	assert_eq!(stats.len(), 2);
+	assert_eq!(stats.min(), Some(Duration::from_millis(1)));
+	assert_eq!(stats.max(), Some(Duration::from_millis(7)));
+	assert_eq!(stats.mean(), Some(Duration::from_millis(4)));
+
+	scheduler.reset_stats();
+	assert!(scheduler.stats().is_empty());
+	assert_eq!(scheduler.phase_offset(), None);
+}
+
+#[test]
+fn input_arrived_uses_clock() {
+	let mut scheduler = vigem::SubmitScheduler::new(FakeClock::new(Duration::from_millis(8)));
+	let mut target = Recorder::default();
+	scheduler.input_arrived();
+	scheduler.tick(&mut target, || Some(0)).unwrap();
+	assert_eq!(scheduler.phase_offset(), Some(Duration::from_millis(8)));
+}
+
+#[test]
+fn submit_error() {
+	let mut scheduler = vigem::SubmitScheduler::new(FakeClock::new(Duration::from_millis(8)));
+	let mut target = Recorder { fail: true, ..Default::default() };
+	scheduler.input_arrived();
+	assert_eq!(scheduler.tick(&mut target, || Some(0)), Err(vigem::Error::NotPluggedIn));
+	// Failed submissions are not measured
+	assert_eq!(scheduler.phase_offset(), None);
+}
+
+#[test]
+fn fixed_rate() {
+	let mut clock = vigem::FixedRate::new(Duration::from_millis(20));
+	let start = Instant::now();
+	for _ in 0..4 {
+		clock.wait_next_tick();
+	}
+	// The first tick is immediate
+	assert!(start.elapsed() >= Duration::from_millis(60));
+}
+
+#[test]
+fn fixed_rate_from_hz() {
+	assert_eq!(vigem::FixedRate::from_hz(125.0).interval(), Duration::from_millis(8));
+	for hz in [0.0, -1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e-300] {
+		assert!(vigem::FixedRate::try_from_hz(hz).is_none(), "{}", hz);
+	}
+}
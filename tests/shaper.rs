@@ -0,0 +1,119 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use vigem_client as vigem;
+use vigem::Pump;
+
+// Records the submitted reports, shared to inspect them after the shaper is dropped
+#[derive(Clone, Default)]
+struct Recorder {
+	reports: Rc<RefCell<Vec<u32>>>,
+}
+
+impl vigem::Submit<u32> for Recorder {
+	fn submit_report(&mut self, report: &u32) -> Result<(), vigem::Error> {
+		self.reports.borrow_mut().push(*report);
+		Ok(())
+	}
+}
+
+fn config(jitter: vigem::Jitter, seed: u64) -> vigem::ShaperConfig {
+	vigem::ShaperConfig {
+		delay: Duration::from_millis(50),
+		jitter,
+		drop_probability: 0.0,
+		seed,
+	}
+}
+
+#[test]
+fn fixed_delay_schedule() {
+	let recorder = Recorder::default();
+	let mut shaper = vigem::LatencyShaper::new(recorder.clone(), config(vigem::Jitter::None, 0));
+
+	// A fake clock, reports arrive every 10ms
+	let start = Instant::now();
+	for i in 0..4 {
+		assert!(shaper.push_at(i, start + Duration::from_millis(10 * i as u64)));
+	}
+	let expected: Vec<_> = (0..4).map(|i| start + Duration::from_millis(50 + 10 * i)).collect();
+	assert_eq!(shaper.releases().collect::<Vec<_>>(), expected);
+
+	shaper.pump(start + Duration::from_millis(49)).unwrap();
+	assert!(recorder.reports.borrow().is_empty());
+	shaper.pump(start + Duration::from_millis(50)).unwrap();
+	assert_eq!(*recorder.reports.borrow(), [0]);
+	shaper.pump(start + Duration::from_millis(75)).unwrap();
+	assert_eq!(*recorder.reports.borrow(), [0, 1, 2]);
+	assert_eq!(shaper.next_release(), Some(start + Duration::from_millis(80)));
+	assert_eq!(shaper.pending(), 1);
+}
+
+#[test]
+fn seeded_jitter_is_reproducible() {
+	let start = Instant::now();
+	let schedule = |jitter, seed| {
+		let mut shaper = vigem::LatencyShaper::new(Recorder::default(), config(jitter, seed));
+		for i in 0..32 {
+			shaper.push_at(i, start + Duration::from_millis(i as u64));
+		}
+		shaper.releases().collect::<Vec<_>>()
+	};
+
+	let uniform = vigem::Jitter::Uniform { max: Duration::from_millis(20) };
+	let normal = vigem::Jitter::Normal { std_dev: Duration::from_millis(20) };
+	assert_eq!(schedule(uniform, 7), schedule(uniform, 7));
+	assert_eq!(schedule(normal, 7), schedule(normal, 7));
+	assert_ne!(schedule(uniform, 7), schedule(uniform, 8));
+}
+
+#[test]
+fn jitter_preserves_order() {
+	let start = Instant::now();
+	for jitter in [vigem::Jitter::Uniform { max: Duration::from_millis(20) }, vigem::Jitter::Normal { std_dev: Duration::from_millis(40) }] {
+		let recorder = Recorder::default();
+		let mut shaper = vigem::LatencyShaper::new(recorder.clone(), config(jitter, 1));
+		for i in 0..64 {
+			shaper.push_at(i, start + Duration::from_millis(i as u64));
+		}
+		let releases: Vec<_> = shaper.releases().collect();
+		assert!(releases.windows(2).all(|pair| pair[0] <= pair[1]));
+		if let vigem::Jitter::Uniform { max } = jitter {
+			for (i, &release) in releases.iter().enumerate() {
+				let arrival = start + Duration::from_millis(i as u64);
+				assert!(release >= arrival + Duration::from_millis(50));
+			}
+			assert!(releases[0] <= start + Duration::from_millis(50) + max);
+		}
+
+		shaper.pump(start + Duration::from_secs(1)).unwrap();
+		assert_eq!(*recorder.reports.borrow(), (0..64).collect::<Vec<_>>());
+	}
+}
+
+#[test]
+fn drop_probability() {
+	let start = Instant::now();
+	let mut never = vigem::LatencyShaper::new(Recorder::default(), vigem::ShaperConfig { drop_probability: 0.0, ..vigem::ShaperConfig::new() });
+	let mut always = vigem::LatencyShaper::new(Recorder::default(), vigem::ShaperConfig { drop_probability: 1.0, ..vigem::ShaperConfig::new() });
+	for i in 0..16 {
+		assert!(never.push_at(i, start));
+		assert!(!always.push_at(i, start));
+	}
+	assert_eq!(never.pending(), 16);
+	assert_eq!(always.pending(), 0);
+}
+
+#[test]
+fn flushes_on_drop() {
+	let recorder = Recorder::default();
+	let start = Instant::now();
+	let mut shaper = vigem::LatencyShaper::new(recorder.clone(), config(vigem::Jitter::None, 0));
+	for i in 0..3 {
+		shaper.push_at(i, start);
+	}
+	shaper.pump(start).unwrap();
+	assert!(recorder.reports.borrow().is_empty());
+	drop(shaper);
+	assert_eq!(*recorder.reports.borrow(), [0, 1, 2]);
+}
@@ -0,0 +1,130 @@
+use std::time::Duration;
+use vigem_client as vigem;
+
+const TAU: Duration = Duration::from_millis(10);
+
+fn full_left() -> vigem::XGamepad {
+	vigem::XGamepad { thumb_lx: i16::MAX, ..Default::default() }
+}
+
+// Steps the smoother from neutral to the gamepad, returns the normalized left thumbstick after every frame
+fn step_response(mut smoother: vigem::AxisSmoother, frames: usize, dt: Duration) -> Vec<f32> {
+	smoother.apply(&vigem::XGamepad::default(), Duration::ZERO);
+	(0..frames).map(|_| smoother.apply(&full_left(), dt).thumb_lx as f32 / i16::MAX as f32).collect()
+}
+
+fn assert_close(value: f32, expected: f32) {
+	assert!((value - expected).abs() < 0.002, "{} != {}", value, expected);
+}
+
+#[test]
+fn passthrough() {
+	let gamepad = vigem::XGamepad {
+		buttons: vigem::XButtons!(A | B),
+		left_trigger: 10,
+		right_trigger: 200,
+		thumb_lx: -1200,
+		thumb_ly: 30000,
+		thumb_rx: 5,
+		thumb_ry: i16::MIN + 1,
+	};
+	let mut smoother = vigem::AxisSmoother::new();
+	smoother.apply(&vigem::XGamepad::default(), Duration::ZERO);
+	assert_eq!(smoother.apply(&gamepad, Duration::from_millis(1)), gamepad);
+}
+
+#[test]
+fn first_state_passes_through() {
+	let mut smoother = vigem::AxisSmoother::exponential(TAU);
+	assert_eq!(smoother.apply(&full_left(), Duration::from_millis(1)), full_left());
+
+	smoother.reset();
+	assert_eq!(smoother.apply(&vigem::XGamepad::default(), Duration::from_millis(1)), vigem::XGamepad::default());
+}
+
+#[test]
+fn exponential_step() {
+	let response = step_response(vigem::AxisSmoother::exponential(TAU), 1, TAU);
+	assert_close(response[0], 1.0 - (-1.0f32).exp());
+}
+
+#[test]
+fn spring_step() {
+	let response = step_response(vigem::AxisSmoother::spring(TAU), 2, TAU);
+	assert_close(response[0], 1.0 - 2.0 * (-1.0f32).exp());
+	assert_close(response[1], 1.0 - 3.0 * (-2.0f32).exp());
+}
+
+#[test]
+fn rate_independent() {
+	for smoother in [vigem::AxisSmoother::exponential(TAU), vigem::AxisSmoother::spring(TAU)] {
+		let coarse = step_response(smoother, 2, Duration::from_millis(10));
+		let fine = step_response(smoother, 20, Duration::from_millis(1));
+		assert_close(coarse[0], fine[9]);
+		assert_close(coarse[1], fine[19]);
+	}
+}
+
+#[test]
+fn settles() {
+	// Within 1% after 5 time constants, the spring lags and takes 7
+	let response = step_response(vigem::AxisSmoother::exponential(TAU), 50, Duration::from_millis(1));
+	assert!(response[49] > 0.99, "{}", response[49]);
+	let response = step_response(vigem::AxisSmoother::spring(TAU), 70, Duration::from_millis(1));
+	assert!(response[69] > 0.99, "{}", response[69]);
+	assert!(response[39] < 0.99, "{}", response[39]);
+}
+
+#[test]
+fn no_overshoot() {
+	for smoother in [vigem::AxisSmoother::exponential(TAU), vigem::AxisSmoother::spring(TAU)] {
+		let response = step_response(smoother, 200, Duration::from_millis(1));
+		assert!(response.windows(2).all(|pair| pair[0] <= pair[1]), "{:?}", smoother.mode());
+		assert_eq!(response[199], 1.0);
+	}
+}
+
+#[test]
+fn bypass_below() {
+	let mut smoother = vigem::AxisSmoother::exponential(TAU).bypass_below(0.05);
+	smoother.apply(&vigem::XGamepad::default(), Duration::ZERO);
+
+	// Small corrections pass through
+	let small = vigem::XGamepad { thumb_lx: 1000, left_trigger: 10, ..Default::default() };
+	assert_eq!(smoother.apply(&small, Duration::from_millis(1)), small);
+
+	// Large movements are smoothed until they come within the threshold
+	let smoothed = smoother.apply(&full_left(), Duration::from_millis(1));
+	assert!(smoothed.thumb_lx < i16::MAX / 2, "{}", smoothed.thumb_lx);
+	let response = step_response(vigem::AxisSmoother::exponential(TAU).bypass_below(0.05), 40, Duration::from_millis(1));
+	assert_eq!(response[39], 1.0);
+}
+
+#[test]
+fn per_axis() {
+	let mut smoother = vigem::AxisSmoother::exponential(TAU).axis(vigem::Axis::LT, Duration::ZERO);
+	assert_eq!(smoother.time_constant(vigem::Axis::LX), TAU);
+	assert_eq!(smoother.time_constant(vigem::Axis::LT), Duration::ZERO);
+	smoother.apply(&vigem::XGamepad::default(), Duration::ZERO);
+
+	let gamepad = vigem::XGamepad { buttons: vigem::XButtons!(X), left_trigger: 255, right_trigger: 255, ..Default::default() };
+	let smoothed = smoother.apply(&gamepad, TAU);
+	assert_eq!(smoothed.buttons, gamepad.buttons);
+	assert_eq!(smoothed.left_trigger, 255);
+	assert_eq!(smoothed.right_trigger, 161);
+}
+
+#[test]
+fn smooth_callback() {
+	// The callback supplies the time between states from the elapsed time
+	let mut produce = vigem::AxisSmoother::exponential(TAU).smooth(|elapsed: Duration| {
+		if elapsed > Duration::from_millis(100) {
+			return None;
+		}
+		Some(if elapsed.is_zero() { vigem::XGamepad::default() } else { full_left() })
+	});
+	assert_eq!(produce(Duration::ZERO), Some(vigem::XGamepad::default()));
+	let smoothed = produce(TAU).unwrap();
+	assert_close(smoothed.thumb_lx as f32 / i16::MAX as f32, 1.0 - (-1.0f32).exp());
+	assert_eq!(produce(Duration::from_millis(200)), None);
+}
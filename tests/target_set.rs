@@ -0,0 +1,225 @@
+use std::{cell::Cell, cell::RefCell, rc::Rc, time};
+use vigem_client as vigem;
+
+// Records the order and simulated time of the calls made by the target set
+type Log = Rc<RefCell<Vec<(usize, &'static str, time::Duration)>>>;
+
+thread_local! {
+	// Simulated time advanced by the stagger delays
+	static NOW: Cell<time::Duration> = const { Cell::new(time::Duration::ZERO) };
+}
+
+fn fake_sleep(duration: time::Duration) {
+	NOW.with(|now| now.set(now.get() + duration));
+}
+
+struct FakeTarget {
+	index: usize,
+	attached: bool,
+	fail_plugin: Option<vigem::Error>,
+	fail_unplug: Option<vigem::Error>,
+	log: Log,
+}
+impl FakeTarget {
+	fn new(index: usize, log: &Log) -> FakeTarget {
+		FakeTarget { index, attached: false, fail_plugin: None, fail_unplug: None, log: log.clone() }
+	}
+}
+impl vigem::VirtualTarget for FakeTarget {
+	fn id(&self) -> vigem::TargetId {
+		vigem::TargetId::XBOX360_WIRED
+	}
+	fn is_attached(&self) -> bool {
+		self.attached
+	}
+	fn plugin(&mut self) -> Result<(), vigem::Error> {
+		self.log.borrow_mut().push((self.index, "plugin", NOW.with(Cell::get)));
+		if let Some(err) = self.fail_plugin {
+			return Err(err);
+		}
+		self.attached = true;
+		Ok(())
+	}
+	fn unplug(&mut self) -> Result<(), vigem::Error> {
+		self.log.borrow_mut().push((self.index, "unplug", NOW.with(Cell::get)));
+		if let Some(err) = self.fail_unplug {
+			return Err(err);
+		}
+		self.attached = false;
+		Ok(())
+	}
+	fn wait_ready(&mut self) -> Result<(), vigem::Error> {
+		Ok(())
+	}
+}
+
+fn calls(log: &Log) -> Vec<(usize, &'static str)> {
+	log.borrow().iter().map(|&(index, call, _)| (index, call)).collect()
+}
+
+#[test]
+fn plugin_order() {
+	let log = Log::default();
+	let mut set = vigem::TargetSet::new();
+	for index in 0..4 {
+		set.push(FakeTarget::new(index, &log));
+	}
+
+	assert_eq!(set.plugin_all(), Ok(()));
+	assert!(set.targets().iter().all(|target| target.attached));
+	assert_eq!(calls(&log), [(0, "plugin"), (1, "plugin"), (2, "plugin"), (3, "plugin")]);
+
+	log.borrow_mut().clear();
+	assert_eq!(set.unplug_all(), Ok(()));
+	assert!(set.targets().iter().all(|target| !target.attached));
+	assert_eq!(calls(&log), [(3, "unplug"), (2, "unplug"), (1, "unplug"), (0, "unplug")]);
+}
+
+#[test]
+fn plugin_skips_attached() {
+	let log = Log::default();
+	let mut set = vigem::TargetSet::new();
+	for index in 0..3 {
+		set.push(FakeTarget::new(index, &log));
+	}
+	set.targets_mut()[1].attached = true;
+
+	assert_eq!(set.plugin_all(), Ok(()));
+	assert_eq!(calls(&log), [(0, "plugin"), (2, "plugin")]);
+}
+
+#[test]
+fn plugin_stops_at_failure() {
+	let log = Log::default();
+	let mut set = vigem::TargetSet::new();
+	for index in 0..4 {
+		set.push(FakeTarget::new(index, &log));
+	}
+	set.targets_mut()[2].fail_plugin = Some(vigem::Error::NoFreeSlot);
+
+	assert_eq!(set.plugin_all(), Err((2, vigem::Error::NoFreeSlot)));
+	assert_eq!(calls(&log), [(0, "plugin"), (1, "plugin"), (2, "plugin")]);
+	let attached: Vec<bool> = set.targets().iter().map(|target| target.attached).collect();
+	assert_eq!(attached, [true, true, false, false]);
+}
+
+#[test]
+fn unplug_keeps_going() {
+	let log = Log::default();
+	let mut set = vigem::TargetSet::new();
+	for index in 0..3 {
+		set.push(FakeTarget::new(index, &log));
+	}
+	set.plugin_all().unwrap();
+	set.targets_mut()[1].fail_unplug = Some(vigem::Error::WinError(5));
+
+	log.borrow_mut().clear();
+	assert_eq!(set.unplug_all(), Err((1, vigem::Error::WinError(5))));
+	assert_eq!(calls(&log), [(2, "unplug"), (1, "unplug"), (0, "unplug")]);
+}
+
+#[test]
+fn plugin_stagger() {
+	let stagger = time::Duration::from_millis(30);
+	let log = Log::default();
+	let mut set = vigem::TargetSet::new();
+	assert_eq!(set.stagger(), time::Duration::from_secs(0));
+	set.set_stagger(stagger);
+	for index in 0..3 {
+		set.push(FakeTarget::new(index, &log));
+	}
+
+	set.set_sleep(fake_sleep);
+	NOW.with(|now| now.set(time::Duration::ZERO));
+	set.targets_mut()[1].attached = true;
+
+	// No delay before the first target, skipped targets are not waited for
+	set.plugin_all().unwrap();
+	let times: Vec<_> = log.borrow().iter().map(|&(index, _, time)| (index, time)).collect();
+	assert_eq!(times, [(0, time::Duration::ZERO), (2, stagger)]);
+
+	// Unplugging does not wait
+	log.borrow_mut().clear();
+	set.unplug_all().unwrap();
+	assert!(log.borrow().iter().all(|&(_, _, time)| time == stagger));
+
+	// Every target after the first waits once
+	set.push(FakeTarget::new(3, &log));
+	log.borrow_mut().clear();
+	set.plugin_all().unwrap();
+	let times: Vec<_> = log.borrow().iter().map(|&(index, _, time)| (index, time)).collect();
+	assert_eq!(times, [(0, stagger), (1, stagger * 2), (2, stagger * 3), (3, stagger * 4)]);
+}
+
+fn attached(set: &vigem::TargetSet<FakeTarget>) -> Vec<bool> {
+	set.targets().iter().map(|target| target.attached).collect()
+}
+
+#[test]
+fn rollback_at_each_position() {
+	for failing in 0..4 {
+		let log = Log::default();
+		let mut set = vigem::TargetSet::new();
+		for index in 0..4 {
+			set.push(FakeTarget::new(index, &log));
+		}
+		set.targets_mut()[failing].fail_plugin = Some(vigem::Error::NoFreeSlot);
+
+		assert_eq!(set.plugin_all_or_rollback(), Err(vigem::RollbackError { index: failing, error: vigem::Error::NoFreeSlot, rollback: Vec::new() }));
+		assert_eq!(attached(&set), [false; 4]);
+		// The targets plugged in are unplugged in reverse order
+		let expected: Vec<_> = (0..failing).map(|index| (index, "plugin"))
+			.chain(Some((failing, "plugin")))
+			.chain((0..failing).rev().map(|index| (index, "unplug")))
+			.collect();
+		assert_eq!(calls(&log), expected);
+	}
+}
+
+#[test]
+fn rollback_keeps_going() {
+	let log = Log::default();
+	let mut set = vigem::TargetSet::new();
+	for index in 0..4 {
+		set.push(FakeTarget::new(index, &log));
+	}
+	set.targets_mut()[0].fail_unplug = Some(vigem::Error::OperationAborted);
+	set.targets_mut()[1].fail_unplug = Some(vigem::Error::WinError(5));
+	set.targets_mut()[3].fail_plugin = Some(vigem::Error::NoFreeSlot);
+
+	// The error of the failed plugin is returned along with the errors of the rollback
+	let err = set.plugin_all_or_rollback().unwrap_err();
+	assert_eq!(err.index, 3);
+	assert_eq!(err.error, vigem::Error::NoFreeSlot);
+	assert_eq!(err.rollback, [(1, vigem::Error::WinError(5)), (0, vigem::Error::OperationAborted)]);
+	assert_eq!(err.to_string(), "target 3 failed to plug in: no free slot, 2 failed to unplug");
+	assert_eq!(calls(&log), [(0, "plugin"), (1, "plugin"), (2, "plugin"), (3, "plugin"), (2, "unplug"), (1, "unplug"), (0, "unplug")]);
+	assert_eq!(attached(&set), [true, true, false, false]);
+}
+
+#[test]
+fn rollback_leaves_attached_alone() {
+	let log = Log::default();
+	let mut set = vigem::TargetSet::new();
+	for index in 0..3 {
+		set.push(FakeTarget::new(index, &log));
+	}
+	set.targets_mut()[0].attached = true;
+	set.targets_mut()[2].fail_plugin = Some(vigem::Error::NoFreeSlot);
+
+	assert_eq!(set.plugin_all_or_rollback(), Err(vigem::RollbackError { index: 2, error: vigem::Error::NoFreeSlot, rollback: Vec::new() }));
+	assert_eq!(calls(&log), [(1, "plugin"), (2, "plugin"), (1, "unplug")]);
+	assert_eq!(attached(&set), [true, false, false]);
+}
+
+#[test]
+fn rollback_success() {
+	let log = Log::default();
+	let mut set = vigem::TargetSet::new();
+	for index in 0..3 {
+		set.push(FakeTarget::new(index, &log));
+	}
+
+	assert_eq!(set.plugin_all_or_rollback(), Ok(()));
+	assert_eq!(attached(&set), [true; 3]);
+}
@@ -0,0 +1,131 @@
+//! Templates round trip the configuration of targets, without the driver.
+
+#![cfg(windows)]
+
+use std::fs;
+use std::os::windows::io::OwnedHandle;
+use std::time::Duration;
+use vigem_client as vigem;
+
+// Any handle stands in for the bus, instantiating a template only duplicates it
+fn client() -> vigem::Client {
+	vigem::Client::from(OwnedHandle::from(fs::File::open("NUL").unwrap()))
+}
+
+#[test]
+fn x360_defaults() {
+	let client = client();
+	let id = vigem::TargetId { vendor: 0x1234, product: 0x5678 };
+
+	let target = vigem::Xbox360Wired::new(&client, id);
+	assert_eq!(target.template(), vigem::XTargetTemplate::new(id));
+
+	let copy = vigem::XTargetTemplate::new(id).instantiate(&client).unwrap();
+	assert_eq!(copy.id(), id);
+	assert!(!copy.is_attached());
+	assert_eq!(copy.template(), vigem::XTargetTemplate::new(id));
+}
+
+#[test]
+fn x360_round_trip() {
+	let client = client();
+	let template = vigem::XTargetTemplate {
+		detached_policy: vigem::DetachedPolicy::Ignore,
+		initial_report: Some(vigem::XGamepad { left_trigger: 255, ..Default::default() }),
+		min_update_interval: Some(Duration::from_millis(4)),
+		stale_timeout: Some(Duration::from_millis(500)),
+		stale_timeout_manual: true,
+		panic_guard: true,
+		latency_capacity: Some(64),
+		..vigem::XTargetTemplate::new(vigem::TargetId::XBOX360_WIRED)
+	};
+
+	let target = template.instantiate(&client).unwrap();
+	assert!(!target.is_attached());
+	assert_eq!(target.detached_policy(), vigem::DetachedPolicy::Ignore);
+	assert_eq!(target.initial_report(), template.initial_report.as_ref());
+	assert!(target.has_panic_guard());
+	assert_eq!(target.latency_stats().map(vigem::LatencyStats::capacity), Some(64));
+	assert_eq!(target.template(), template);
+
+	// The background watchdog is another configuration
+	let template = vigem::XTargetTemplate { stale_timeout_manual: false, ..template };
+	assert_eq!(template.instantiate(&client).unwrap().template(), template);
+}
+
+#[test]
+fn x360_configured_target() {
+	let client = client();
+	let mut target = vigem::Xbox360Wired::new(&client, vigem::TargetId::XBOX360_WIRED);
+	target.set_detached_policy(vigem::DetachedPolicy::AutoPlugin);
+	target.set_max_update_rate(Some(250.0)).unwrap();
+	target.set_stale_timeout(Some(Duration::from_secs(1))).unwrap();
+	target.set_latency_stats(Some(vigem::LatencyStats::new(16)));
+
+	let template = target.template();
+	assert_eq!(template.detached_policy, vigem::DetachedPolicy::AutoPlugin);
+	assert_eq!(template.min_update_interval, Some(Duration::from_millis(4)));
+	assert_eq!(template.stale_timeout, Some(Duration::from_secs(1)));
+	assert!(!template.stale_timeout_manual);
+	assert!(!template.panic_guard);
+	assert_eq!(template.latency_capacity, Some(16));
+	assert_eq!(template.instantiate(&client).unwrap().template(), template);
+}
+
+#[test]
+fn x360_zero_latency_capacity() {
+	let client = client();
+	let template = vigem::XTargetTemplate {
+		latency_capacity: Some(0),
+		..vigem::XTargetTemplate::new(vigem::TargetId::XBOX360_WIRED)
+	};
+	assert_eq!(template.validate(), Err(vigem::Error::InvalidConfig));
+	assert_eq!(template.instantiate(&client).err(), Some(vigem::Error::InvalidConfig));
+}
+
+#[test]
+fn ds4_round_trip() {
+	let client = client();
+	let id = vigem::TargetId { vendor: 0x054C, product: 0x05C4 };
+	assert_eq!(vigem::DualShock4Wired::new(&client, id).template(), vigem::DS4TargetTemplate::new(id));
+
+	let template = vigem::DS4TargetTemplate {
+		validate_reports: true,
+		detached_policy: vigem::DetachedPolicy::AutoPlugin,
+		latency_capacity: Some(8),
+		..vigem::DS4TargetTemplate::new(id)
+	};
+	let target = template.instantiate(&client).unwrap();
+	assert_eq!(target.id(), id);
+	assert!(!target.is_attached());
+	assert_eq!(target.detached_policy(), vigem::DetachedPolicy::AutoPlugin);
+	assert_eq!(target.template(), template);
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4_round_trip_unstable() {
+	let client = client();
+	let template = vigem::DS4TargetTemplate {
+		initial_report: Some(vigem::DS4Report::neutral()),
+		stale_timeout: Some(Duration::from_millis(250)),
+		panic_guard: true,
+		report_mode: vigem::DS4ReportMode::Extended,
+		..vigem::DS4TargetTemplate::new(vigem::TargetId::DUALSHOCK4_WIRED)
+	};
+	let target = template.instantiate(&client).unwrap();
+	assert!(target.has_panic_guard());
+	assert_eq!(target.report_mode(), vigem::DS4ReportMode::Extended);
+	assert_eq!(target.template(), template);
+}
+
+#[test]
+fn ds4_zero_latency_capacity() {
+	let client = client();
+	let template = vigem::DS4TargetTemplate {
+		latency_capacity: Some(0),
+		..vigem::DS4TargetTemplate::new(vigem::TargetId { vendor: 0x054C, product: 0x05C4 })
+	};
+	assert_eq!(template.validate(), Err(vigem::Error::InvalidConfig));
+	assert_eq!(template.instantiate(&client).err(), Some(vigem::Error::InvalidConfig));
+}
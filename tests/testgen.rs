@@ -0,0 +1,109 @@
+use std::time::Duration;
+use vigem_client::testgen::Waveform;
+use vigem_client::{XButtons, XGamepad};
+
+fn ms(ms: u64) -> Duration {
+	Duration::from_millis(ms)
+}
+
+#[test]
+fn phase() {
+	let wave = Waveform::ramp_trigger(ms(1000));
+	assert_eq!(wave.phase(ms(0)), 0.0);
+	assert_eq!(wave.phase(ms(250)), 0.25);
+	assert_eq!(wave.phase(ms(1000)), 0.0);
+	assert_eq!(wave.phase(ms(2500)), 0.5);
+
+	// Zero period waveforms are frozen at phase zero
+	assert_eq!(Waveform::ramp_trigger(Duration::ZERO).phase(ms(123)), 0.0);
+}
+
+#[test]
+fn circle() {
+	let wave = Waveform::circle(1.0, ms(4000));
+	let stick = |t| {
+		let gamepad = wave.gamepad(ms(t));
+		(gamepad.thumb_lx, gamepad.thumb_ly)
+	};
+	assert_eq!(stick(0), (32767, 0));
+	assert_eq!(stick(1000), (0, 32767));
+	assert_eq!(stick(2000), (-32767, 0));
+	assert_eq!(stick(3000), (0, -32767));
+	assert_eq!(stick(4000), (32767, 0));
+	assert_eq!(stick(500), (23170, 23170));
+
+	let half = Waveform::circle(0.5, ms(4000)).gamepad(ms(2000));
+	assert_eq!((half.thumb_lx, half.thumb_ly), (-16384, 0));
+
+	// Only the left stick is driven
+	let gamepad = wave.gamepad(ms(500));
+	assert_eq!(XGamepad { thumb_lx: 0, thumb_ly: 0, ..gamepad }, XGamepad::default());
+}
+
+#[test]
+fn square_buttons() {
+	let wave = Waveform::square_buttons(XButtons::A | XButtons::B, ms(100));
+	assert_eq!(wave.gamepad(ms(0)).buttons, XButtons!(A | B));
+	assert_eq!(wave.gamepad(ms(49)).buttons, XButtons!(A | B));
+	assert_eq!(wave.gamepad(ms(50)).buttons, XButtons!());
+	assert_eq!(wave.gamepad(ms(99)).buttons, XButtons!());
+	assert_eq!(wave.gamepad(ms(100)).buttons, XButtons!(A | B));
+
+	// Other buttons are left untouched
+	let mut gamepad = XGamepad { buttons: XButtons!(A | X), ..XGamepad::default() };
+	wave.apply(ms(50), &mut gamepad);
+	assert_eq!(gamepad.buttons, XButtons!(X));
+}
+
+#[test]
+fn ramp_trigger() {
+	let wave = Waveform::ramp_trigger(ms(1000));
+	let trigger = |t| {
+		let gamepad = wave.gamepad(ms(t));
+		assert_eq!(gamepad.left_trigger, gamepad.right_trigger);
+		gamepad.left_trigger
+	};
+	assert_eq!(trigger(0), 0);
+	assert_eq!(trigger(250), 64);
+	assert_eq!(trigger(500), 128);
+	assert_eq!(trigger(999), 255);
+	assert_eq!(trigger(1000), 0);
+}
+
+#[test]
+fn combine() {
+	let t = ms(1000);
+	let mut gamepad = Waveform::circle(1.0, ms(4000)).gamepad(t);
+	Waveform::ramp_trigger(ms(2000)).apply(t, &mut gamepad);
+	Waveform::square_buttons(XButtons::Y, ms(4000)).apply(t, &mut gamepad);
+	assert_eq!(gamepad, XGamepad {
+		buttons: XButtons!(Y),
+		left_trigger: 128,
+		right_trigger: 128,
+		thumb_lx: 0,
+		thumb_ly: 32767,
+		..XGamepad::default()
+	});
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn ds4() {
+	let circle = Waveform::circle(1.0, ms(4000));
+	let stick = |t| {
+		let report = circle.ds4_report(ms(t));
+		(report.thumb_lx, report.thumb_ly)
+	};
+	assert_eq!(stick(0), (255, 128));
+	assert_eq!(stick(1000), (128, 1));
+	assert_eq!(stick(2000), (1, 128));
+	assert_eq!(stick(3000), (128, 255));
+
+	let ramp = Waveform::ramp_trigger(ms(1000)).ds4_report(ms(500));
+	assert_eq!((ramp.trigger_l, ramp.trigger_r), (128, 128));
+
+	// The dpad nibble is masked out
+	let buttons = Waveform::square_buttons(0x0021, ms(100)).ds4_report(ms(0));
+	assert_eq!(buttons.buttons, vigem_client::DS4Report::neutral().buttons | 0x0020);
+	assert_eq!(buttons.dpad(), Ok(vigem_client::DpadDirection::None));
+}
@@ -0,0 +1,90 @@
+//! Compile tests, documents which handle-bearing types are `Send` and `Sync`.
+
+#![cfg(windows)]
+#![allow(unused_imports)]
+use std::rc::Rc;
+use std::sync::Arc;
+use vigem_client as vigem;
+
+fn send_sync<T: Send + Sync>() {}
+
+// Resolves only if the type does not implement the trait, else the `_` is ambiguous
+macro_rules! assert_not_impl {
+	($ty:ty: $trait:path) => {{
+		trait AmbiguousIfImpl<A> {
+			fn some_item() {}
+		}
+		impl<T: ?Sized> AmbiguousIfImpl<()> for T {}
+		impl<T: ?Sized + $trait> AmbiguousIfImpl<u8> for T {}
+		<$ty as AmbiguousIfImpl<_>>::some_item();
+	}};
+}
+
+#[test]
+fn client() {
+	send_sync::<vigem::Client>();
+	send_sync::<vigem::Error>();
+}
+
+#[test]
+fn xbox360_wired() {
+	send_sync::<vigem::Xbox360Wired<vigem::Client>>();
+	send_sync::<vigem::Xbox360Wired<&'static vigem::Client>>();
+	send_sync::<vigem::Xbox360Wired<Arc<vigem::Client>>>();
+	send_sync::<vigem::TargetSet<vigem::Xbox360Wired<Arc<vigem::Client>>>>();
+	assert_not_impl!(vigem::Xbox360Wired<Rc<vigem::Client>>: Send);
+	assert_not_impl!(vigem::Xbox360Wired<Rc<vigem::Client>>: Sync);
+}
+
+#[test]
+fn raw_target() {
+	send_sync::<vigem::RawTarget<vigem::Client>>();
+	send_sync::<vigem::RawTarget<Arc<vigem::Client>>>();
+	assert_not_impl!(vigem::RawTarget<Rc<vigem::Client>>: Send);
+}
+
+#[cfg(feature = "unstable_ds4")]
+#[test]
+fn dualshock4_wired() {
+	send_sync::<vigem::DualShock4Wired<vigem::Client>>();
+	send_sync::<vigem::DualShock4Wired<&'static vigem::Client>>();
+	send_sync::<vigem::DualShock4Wired<Arc<vigem::Client>>>();
+	send_sync::<vigem::TouchpadMapper>();
+	assert_not_impl!(vigem::DualShock4Wired<Rc<vigem::Client>>: Send);
+	assert_not_impl!(vigem::DualShock4Wired<Rc<vigem::Client>>: Sync);
+}
+
+#[cfg(feature = "xusb-rumble")]
+#[test]
+fn xusb_rumble() {
+	send_sync::<vigem::XRequestNotification>();
+	send_sync::<vigem::XNotificationPoller>();
+}
+
+#[cfg(feature = "ds4-output")]
+#[test]
+fn ds4_output() {
+	send_sync::<vigem::DS4RequestNotification>();
+	send_sync::<vigem::DS4NotificationPoller>();
+}
+
+#[test]
+fn iocp_driver() {
+	send_sync::<vigem::IocpDriver<vigem::Client>>();
+	send_sync::<vigem::IocpDriver<&'static vigem::Client>>();
+	send_sync::<vigem::IocpDriver<Arc<vigem::Client>>>();
+	assert_not_impl!(vigem::IocpDriver<Rc<vigem::Client>>: Send);
+	assert_not_impl!(vigem::IocpDriver<Rc<vigem::Client>>: Sync);
+}
+
+#[test]
+fn manual_driver() {
+	assert_not_impl!(vigem::ManualDriver<'static>: Send);
+	assert_not_impl!(vigem::ManualDriver<'static>: Sync);
+}
+
+#[test]
+fn tuning_guard() {
+	send_sync::<vigem::ThreadTuning>();
+	assert_not_impl!(vigem::TuningGuard: Send);
+}
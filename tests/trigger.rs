@@ -0,0 +1,60 @@
+use vigem_client as vigem;
+use vigem::{consts::TRIGGER_THRESHOLD, Trigger};
+
+#[test]
+fn constructors() {
+	assert_eq!(Trigger::off().to_xbox(), 0);
+	assert_eq!(Trigger::off().to_ds4(), (0, false));
+	assert_eq!(Trigger::digital().to_xbox(), 255);
+	assert_eq!(Trigger::digital().to_ds4(), (255, true));
+	assert_eq!(Trigger::default(), Trigger::off());
+}
+
+#[test]
+fn analog_boundaries() {
+	assert_eq!(Trigger::analog(0.0).to_ds4(), (0, false));
+	assert_eq!(Trigger::analog(1.0).to_ds4(), (255, true));
+	assert_eq!(Trigger::analog(-1.0).to_xbox(), 0);
+	assert_eq!(Trigger::analog(2.0).to_xbox(), 255);
+	assert_eq!(Trigger::analog(f32::NAN).to_xbox(), 0);
+	assert_eq!(Trigger::analog(f32::INFINITY).to_xbox(), 255);
+}
+
+#[test]
+fn threshold() {
+	// Exactly at the threshold is released, one past it is pressed
+	let at = Trigger::analog(TRIGGER_THRESHOLD as f32 / 255.0);
+	let past = Trigger::analog((TRIGGER_THRESHOLD + 1) as f32 / 255.0);
+	assert_eq!(at.to_ds4(), (TRIGGER_THRESHOLD, false));
+	assert_eq!(past.to_ds4(), (TRIGGER_THRESHOLD + 1, true));
+	assert!(!at.is_pressed());
+	assert!(past.is_pressed());
+
+	// The analog and digital states agree for every value
+	for value in 0..=255u8 {
+		let trigger = Trigger::analog(value as f32 / 255.0);
+		assert_eq!(trigger.to_xbox(), value);
+		assert_eq!(trigger.to_ds4(), (value, value > TRIGGER_THRESHOLD));
+	}
+}
+
+#[test]
+fn digital_override() {
+	// Raises the analog value just past the threshold
+	assert_eq!(Trigger::off().with_digital(true).to_ds4(), (TRIGGER_THRESHOLD + 1, true));
+	assert_eq!(Trigger::analog(0.5).with_digital(true).to_ds4(), (128, true));
+	assert!(Trigger::off().with_digital(true).is_pressed());
+	assert!(Trigger::digital().is_digital());
+	assert!(!Trigger::digital().with_digital(false).is_digital());
+	assert_eq!(Trigger::digital().with_digital(false).to_ds4(), (255, true));
+}
+
+#[test]
+fn xgamepad_builder() {
+	let gamepad = vigem::XGamepad::default()
+		.left_trigger_t(Trigger::digital())
+		.right_trigger_t(Trigger::analog(0.5));
+	assert_eq!(gamepad.left_trigger, 255);
+	assert_eq!(gamepad.right_trigger, 128);
+	assert_eq!(gamepad.left_trigger_t(Trigger::off()).left_trigger, 0);
+}
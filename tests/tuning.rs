@@ -0,0 +1,50 @@
+#![cfg(windows)]
+
+use winapi::um::processthreadsapi::{GetCurrentThread, GetThreadPriority};
+use winapi::um::winbase::THREAD_PRIORITY_HIGHEST;
+use vigem_client::{ThreadTuning, TuningGuard};
+
+fn priority() -> i32 {
+	unsafe { GetThreadPriority(GetCurrentThread()) }
+}
+
+#[test]
+fn games_reverts() {
+	let original = priority();
+	let guard = ThreadTuning::games().apply_to_current_thread().unwrap();
+	assert_eq!(guard.original_priority(), original);
+	drop(guard);
+	assert_eq!(priority(), original);
+}
+
+#[test]
+fn priority_reverts() {
+	let original = priority();
+	let guard = ThreadTuning::priority(THREAD_PRIORITY_HIGHEST as i32).apply_to_current_thread().unwrap();
+	assert!(!guard.is_mmcss());
+	assert_eq!(priority(), THREAD_PRIORITY_HIGHEST as i32);
+	drop(guard);
+	assert_eq!(priority(), original);
+}
+
+#[test]
+fn nested() {
+	let original = priority();
+	let outer = ThreadTuning::priority(THREAD_PRIORITY_HIGHEST as i32).apply_to_current_thread().unwrap();
+	let inner: TuningGuard = ThreadTuning::games().apply_to_current_thread().unwrap();
+	assert_eq!(inner.original_priority(), THREAD_PRIORITY_HIGHEST as i32);
+	drop(inner);
+	assert_eq!(priority(), THREAD_PRIORITY_HIGHEST as i32);
+	drop(outer);
+	assert_eq!(priority(), original);
+}
+
+#[test]
+fn on_spawned_thread() {
+	std::thread::spawn(|| {
+		let original = priority();
+		let guard = ThreadTuning::pro_audio().apply_to_current_thread().unwrap();
+		drop(guard);
+		assert_eq!(priority(), original);
+	}).join().unwrap();
+}
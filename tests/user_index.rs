@@ -0,0 +1,24 @@
+use std::convert::TryFrom;
+use vigem_client::{Error, UserIndex};
+
+#[test]
+fn range() {
+	for index in 0..4u32 {
+		assert_eq!(UserIndex::try_from(index).map(UserIndex::as_u32), Ok(index));
+		assert_eq!(UserIndex::new(index as u8).map(UserIndex::as_u32), Some(index));
+	}
+	for &index in &[4u32, 5, 255, 256, u32::MAX] {
+		assert_eq!(UserIndex::try_from(index), Err(Error::UserIndexOutOfRange));
+	}
+	assert_eq!(UserIndex::try_from(4u8), Err(Error::UserIndexOutOfRange));
+	assert_eq!(UserIndex::new(4), None);
+}
+
+#[test]
+fn conversions() {
+	let index = UserIndex::try_from(2u8).unwrap();
+	assert_eq!(u32::from(index), 2);
+	assert_eq!(u8::from(index), 2);
+	assert_eq!(index.as_u8(), 2);
+	assert_eq!(index.to_string(), "2");
+}
@@ -0,0 +1,52 @@
+use vigem_client as vigem;
+
+#[cfg(windows)]
+#[test]
+fn accepted() {
+	let version = vigem::BusVersion::accepted();
+	assert!(version.accepted);
+	assert_eq!(version.required, vigem::BusVersion::REQUIRED);
+	assert_eq!(version.driver, None);
+	assert_eq!(version.to_string(), "driver speaks version 1");
+}
+
+#[cfg(windows)]
+#[test]
+fn rejected() {
+	let version = vigem::BusVersion { required: 1, accepted: false, driver: Some(3) };
+	assert_eq!(version.to_string(), "driver speaks version 3, this crate needs 1");
+
+	let version = vigem::BusVersion { required: 1, accepted: false, driver: None };
+	assert_eq!(version.to_string(), "driver speaks an unknown version above 16, this crate needs 1");
+}
+
+#[test]
+fn unchecked_error() {
+	assert_eq!(vigem::Error::UncheckedClient.to_string(), "unchecked client");
+}
+
+#[test]
+fn install_failed_error() {
+	let error = vigem::Error::DeviceInstallFailed(28);
+	assert_ne!(error, vigem::Error::TargetNotReady);
+	assert_eq!(error.to_string(), "device install failed: problem code 28");
+}
+
+#[cfg(windows)]
+#[test]
+fn bus_presence() {
+	use winapi::um::setupapi::{SPINT_ACTIVE, SPINT_DEFAULT, SPINT_REMOVED};
+	use vigem::BusPresence;
+
+	assert_eq!(BusPresence::classify([]), BusPresence::NotInstalled);
+	assert_eq!(BusPresence::classify([SPINT_REMOVED, SPINT_REMOVED | SPINT_ACTIVE]), BusPresence::NotInstalled);
+	assert_eq!(BusPresence::classify([0]), BusPresence::Disabled);
+	assert_eq!(BusPresence::classify([SPINT_DEFAULT, SPINT_REMOVED]), BusPresence::Disabled);
+	assert_eq!(BusPresence::classify([0, SPINT_ACTIVE]), BusPresence::Active);
+	assert_eq!(BusPresence::classify([SPINT_ACTIVE | SPINT_DEFAULT]), BusPresence::Active);
+
+	assert_eq!(BusPresence::Active.error(), None);
+	assert_eq!(BusPresence::Disabled.error(), Some(vigem::Error::BusDisabled));
+	assert_eq!(BusPresence::NotInstalled.error(), Some(vigem::Error::BusNotFound));
+	assert_eq!(vigem::Error::BusDisabled.to_string(), "bus disabled");
+}
@@ -0,0 +1,58 @@
+use vigem_client as vigem;
+
+#[test]
+fn empty() {
+	const NONE: vigem::XButtons = vigem::XButtons!();
+	assert_eq!(NONE.raw, 0);
+	assert_eq!(vigem::XButtons!(), vigem::XButtons::default());
+}
+
+#[test]
+fn single() {
+	assert_eq!(vigem::XButtons!(A).raw, vigem::XButtons::A);
+	assert_eq!(vigem::XButtons!(GUIDE).raw, vigem::XButtons::GUIDE);
+}
+
+#[test]
+fn combined() {
+	const BUTTONS: vigem::XButtons = vigem::XButtons!(UP|RIGHT|LB|A|X);
+	assert_eq!(BUTTONS.raw, 0x5109);
+}
+
+#[test]
+fn mixed() {
+	let extra = vigem::XButtons::B | vigem::XButtons::Y;
+	assert_eq!(vigem::XButtons!(A|X; extra).raw, 0xf000);
+	assert_eq!(vigem::XButtons!(; extra).raw, 0xa000);
+	assert_eq!(vigem::XButtons!(A; 0).raw, vigem::XButtons::A);
+
+	// The runtime expression is evaluated before it is combined
+	let mask = 0x00ff;
+	assert_eq!(vigem::XButtons!(A; 0x0f0f & mask).raw, 0x100f);
+}
+
+#[test]
+fn pressing() {
+	const DOWN: vigem::XGamepad = vigem::XGamepad::pressing(vigem::XButtons!(DOWN));
+	assert_eq!(DOWN, vigem::XGamepad { buttons: vigem::XButtons!(DOWN), ..vigem::XGamepad::neutral() });
+}
+
+#[test]
+fn quantize_like_driver() {
+	let gamepad = vigem::XGamepad {
+		buttons: vigem::XButtons(0xffff),
+		left_trigger: 1,
+		right_trigger: 29,
+		thumb_lx: -1,
+		thumb_ly: 1,
+		thumb_rx: i16::MIN,
+		thumb_ry: i16::MAX,
+	};
+	let seen = gamepad.quantize_like_driver();
+	assert_eq!(seen.buttons, vigem::XButtons(0xf3ff));
+	// Analog values pass through at full resolution
+	assert_eq!(seen, vigem::XGamepad { buttons: seen.buttons, ..gamepad });
+	// Idempotent
+	assert_eq!(seen.quantize_like_driver(), seen);
+	assert_eq!(vigem::XGamepad::neutral().quantize_like_driver(), vigem::XGamepad::neutral());
+}